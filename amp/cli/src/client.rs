@@ -2,7 +2,9 @@ use reqwest::Client;
 use serde_json::Value;
 use anyhow::Result;
 use uuid::Uuid;
+use serde::Serialize;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 static CLIENT_QUIET: AtomicBool = AtomicBool::new(false);
 
@@ -12,10 +14,46 @@ fn client_log(message: &str) {
     }
 }
 
+/// Relationships a client has created, by type. Failed requests are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RelationshipCounts {
+    pub defined_in: usize,
+    pub depends_on: usize,
+    pub calls: usize,
+    pub other: usize,
+}
+
+impl RelationshipCounts {
+    pub fn total(&self) -> usize {
+        self.defined_in + self.depends_on + self.calls + self.other
+    }
+
+    /// Counts added since an earlier snapshot of the same client.
+    pub fn since(&self, earlier: &RelationshipCounts) -> RelationshipCounts {
+        RelationshipCounts {
+            defined_in: self.defined_in - earlier.defined_in,
+            depends_on: self.depends_on - earlier.depends_on,
+            calls: self.calls - earlier.calls,
+            other: self.other - earlier.other,
+        }
+    }
+
+    fn record(&mut self, relation_type: &str) {
+        match relation_type {
+            "defined_in" => self.defined_in += 1,
+            "depends_on" => self.depends_on += 1,
+            "calls" => self.calls += 1,
+            _ => self.other += 1,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct AmpClient {
     client: Client,
     base_url: String,
+    // Shared by clones so per-file workers add to the same tally
+    relationships: Arc<Mutex<RelationshipCounts>>,
 }
 
 impl AmpClient {
@@ -23,13 +61,28 @@ impl AmpClient {
         Self {
             client: Client::new(),
             base_url: base_url.to_string(),
+            relationships: Arc::default(),
         }
     }
 
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     pub fn set_quiet(&self, quiet: bool) {
         CLIENT_QUIET.store(quiet, Ordering::Relaxed);
     }
 
+    pub fn relationships_created(&self) -> RelationshipCounts {
+        self.relationships.lock().map(|counts| *counts).unwrap_or_default()
+    }
+
+    fn record_relationship(&self, relation_type: &str) {
+        if let Ok(mut counts) = self.relationships.lock() {
+            counts.record(relation_type);
+        }
+    }
+
     pub async fn create_object(&self, object: Value) -> Result<Value> {
         let response = self.client
             .post(&format!("{}/v1/objects", self.base_url))
@@ -194,7 +247,9 @@ impl AmpClient {
             .await?;
         
         if response.status().is_success() {
-            Ok(response.json().await?)
+            let result = response.json().await?;
+            self.record_relationship(relation_enum);
+            Ok(result)
         } else {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
@@ -211,6 +266,7 @@ impl AmpClient {
             "modifies" => "modifies",
             "implements" => "implements",
             "produced" => "produced",
+            "superseded_by" => "superseded_by",
             _ => "defined_in", // default fallback
         };
         
@@ -233,6 +289,7 @@ impl AmpClient {
         
         if response.status().is_success() {
             let result = response.json().await?;
+            self.record_relationship(relation_enum);
             client_log("Relationship created successfully");
             Ok(result)
         } else {
@@ -311,6 +368,160 @@ impl AmpClient {
         }
     }
 
+    /// Download a project's gzip'd NDJSON archive.
+    pub async fn export_project(&self, project_id: &str) -> Result<Vec<u8>> {
+        let response = self.client
+            .get(format!("{}/v1/projects/{}/export", self.base_url, project_id))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.bytes().await?.to_vec())
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to export project {}: {} {}", project_id, status, body)
+        }
+    }
+
+    /// Upload an archive from `export_project`; returns the server's import counts.
+    pub async fn import_project(&self, archive: Vec<u8>) -> Result<Value> {
+        let response = self.client
+            .post(format!("{}/v1/projects/import", self.base_url))
+            .header("Content-Type", "application/gzip")
+            .body(archive)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to import archive: {} {}", status, body)
+        }
+    }
+
+    /// Re-sync one file across the memory layers after it changed on disk.
+    pub async fn sync_file(&self, path: &str, action: &str) -> Result<Value> {
+        let response = self.client
+            .post(format!("{}/v1/codebase/sync", self.base_url))
+            .json(&serde_json::json!({
+                "path": path,
+                "action": action,
+                "summary": format!("File {} detected by amp index --watch", action),
+                "agent_id": "amp-cli",
+            }))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to sync {}: {} {}", path, status, body)
+        }
+    }
+
+    /// Remove the FileChunk and FileLog objects stored for a deleted file.
+    pub async fn delete_file_objects(&self, path: &str) -> Result<Value> {
+        let response = self.client
+            .post(format!("{}/v1/codebase/delete-file", self.base_url))
+            .json(&serde_json::json!({ "path": path }))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "Failed to delete file objects for {}: {} {}",
+                path,
+                status,
+                body
+            )
+        }
+    }
+
+    /// Path and `content_hash` of every stored FileLog.
+    pub async fn get_file_hashes(&self) -> Result<Value> {
+        let response = self.client
+            .get(format!("{}/v1/codebase/file-hashes", self.base_url))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to get file hashes: {} {}", status, body)
+        }
+    }
+
+    /// SurrealDB round-trip time and indexed project count.
+    pub async fn db_health(&self) -> Result<Value> {
+        let response = self.client
+            .get(format!("{}/v1/debug/db-health", self.base_url))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Database health check failed: {} {}", status, body)
+        }
+    }
+
+    /// Result of a test embedding with the configured provider.
+    pub async fn embedding_health(&self) -> Result<Value> {
+        let response = self.client
+            .get(format!("{}/v1/debug/embedding-health", self.base_url))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            anyhow::bail!("Embedding health check failed: {}", response.status())
+        }
+    }
+
+    /// System analytics with detailed stats, optionally for one project.
+    pub async fn get_analytics(&self, project_id: Option<&str>) -> Result<Value> {
+        let mut request = self.client.get(format!("{}/v1/analytics", self.base_url));
+        if let Some(project_id) = project_id {
+            request = request.query(&[("project_id", project_id)]);
+        }
+        let response = request.send().await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            anyhow::bail!("Failed to load analytics: {}", response.status())
+        }
+    }
+
+    /// Live agent connections, optionally for one project.
+    pub async fn list_connections(&self, project_id: Option<&str>) -> Result<Value> {
+        let mut request = self.client.get(format!("{}/v1/connections", self.base_url));
+        if let Some(project_id) = project_id {
+            request = request.query(&[("project_id", project_id)]);
+        }
+        let response = request.send().await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            anyhow::bail!("Failed to list connections: {}", response.status())
+        }
+    }
+
     pub async fn health_check(&self) -> Result<bool> {
         let response = self.client
             .get(&format!("{}/health", self.base_url))
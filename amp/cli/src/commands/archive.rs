@@ -0,0 +1,191 @@
+use crate::client::AmpClient;
+use crate::commands::output::{self, OutputOptions};
+use anyhow::{Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde_json::Value;
+use std::io::{Read, Write};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Archive path used when `amp export` is run without `-o`.
+pub fn default_archive_path(project_id: &str) -> String {
+    format!("{}.amp.gz", project_id)
+}
+
+/// Exports to these paths are written as plain newline-delimited JSON.
+fn is_ndjson_path(path: &str) -> bool {
+    path.ends_with(".jsonl") || path.ends_with(".ndjson")
+}
+
+fn gunzip(archive: &[u8]) -> Result<Vec<u8>> {
+    let mut ndjson = Vec::new();
+    GzDecoder::new(archive)
+        .read_to_end(&mut ndjson)
+        .context("Server returned an archive that is not valid gzip")?;
+    Ok(ndjson)
+}
+
+fn gzip(ndjson: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(ndjson)?;
+    Ok(encoder.finish()?)
+}
+
+pub async fn run_export(
+    project_id: &str,
+    output_path: Option<&str>,
+    client: &AmpClient,
+) -> Result<()> {
+    let path = output_path
+        .map(str::to_string)
+        .unwrap_or_else(|| default_archive_path(project_id));
+    let mut archive = client.export_project(project_id).await?;
+    if is_ndjson_path(&path) {
+        archive = gunzip(&archive)?;
+    }
+    std::fs::write(&path, &archive).with_context(|| format!("Failed to write {}", path))?;
+    println!(
+        "Exported project {} to {} ({} bytes)",
+        project_id,
+        path,
+        archive.len()
+    );
+    Ok(())
+}
+
+pub async fn run_import(path: &str, client: &AmpClient, options: OutputOptions) -> Result<()> {
+    let mut archive = std::fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    // The server only accepts gzip; plain .jsonl dumps are compressed on the way up
+    if !archive.starts_with(&GZIP_MAGIC) {
+        archive = gzip(&archive)?;
+    }
+    let report = client.import_project(archive).await?;
+
+    if options.show_summary() {
+        println!(
+            "Imported {} into project {}",
+            path,
+            report["project_id"].as_str().unwrap_or("?")
+        );
+    }
+    println!(
+        "{}",
+        output::render(
+            options.format,
+            &["Kind", "Created", "Skipped", "Remapped"],
+            &import_rows(&report),
+            &report
+        )?
+    );
+    Ok(())
+}
+
+fn import_rows(report: &Value) -> Vec<Vec<String>> {
+    [
+        ("objects", "Objects"),
+        ("relationships", "Relationships"),
+        ("cache_blocks", "Cache blocks"),
+    ]
+    .iter()
+    .map(|(key, label)| {
+        let count = |field: &str| report[*key][field].as_u64().unwrap_or(0).to_string();
+        vec![
+            label.to_string(),
+            count("created"),
+            count("skipped"),
+            count("remapped"),
+        ]
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::output::OutputFormat;
+    use axum::{
+        body::Bytes,
+        extract::State,
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    const DUMP: &str = concat!(
+        r#"{"kind":"header","project_id":"amp"}"#,
+        "\n",
+        r#"{"kind":"object","data":{"id":"a","type":"note"}}"#,
+        "\n",
+        r#"{"kind":"relationship","data":{"in":"a","out":"a","type":"depends_on"}}"#,
+        "\n",
+    );
+
+    type Uploads = Arc<Mutex<Vec<String>>>;
+
+    async fn import(State(uploads): State<Uploads>, body: Bytes) -> Json<Value> {
+        let ndjson = String::from_utf8(gunzip(&body).unwrap()).unwrap();
+        let count = |kind: &str| ndjson.matches(&format!(r#""kind":"{}""#, kind)).count();
+        let report = json!({
+            "project_id": "amp",
+            "objects": { "created": count("object") },
+            "relationships": { "created": count("relationship") },
+        });
+        uploads.lock().unwrap().push(ndjson);
+        Json(report)
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_dump_round_trips_through_import() {
+        let uploads: Uploads = Arc::default();
+        let app = Router::new()
+            .route(
+                "/v1/projects/amp/export",
+                get(|| async { gzip(DUMP.as_bytes()).unwrap() }),
+            )
+            .route("/v1/projects/import", post(import))
+            .with_state(uploads.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let dir = tempfile::tempdir().unwrap();
+        let dump = dir.path().join("dump.jsonl");
+        let dump = dump.to_str().unwrap();
+        run_export("amp", Some(dump), &client).await.unwrap();
+        assert_eq!(std::fs::read_to_string(dump).unwrap(), DUMP);
+
+        let options = OutputOptions {
+            format: OutputFormat::Json,
+            quiet: true,
+        };
+        run_import(dump, &client, options).await.unwrap();
+        assert_eq!(*uploads.lock().unwrap(), vec![DUMP.to_string()]);
+    }
+
+    #[test]
+    fn test_default_archive_path_uses_project_id() {
+        assert_eq!(default_archive_path("amp"), "amp.amp.gz");
+    }
+
+    #[test]
+    fn test_import_rows_list_counts_per_kind() {
+        let report = json!({
+            "project_id": "amp",
+            "objects": { "created": 3, "skipped": 1, "remapped": 2 },
+            "relationships": { "created": 4, "skipped": 0, "remapped": 0 },
+            "cache_blocks": { "created": 1, "skipped": 0, "remapped": 0 },
+        });
+        assert_eq!(
+            import_rows(&report),
+            vec![
+                vec!["Objects", "3", "1", "2"],
+                vec!["Relationships", "4", "0", "0"],
+                vec!["Cache blocks", "1", "0", "0"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use crate::client::AmpClient;
+use crate::commands::index::{file_content_hash, get_index_settings, is_text_file, ExcludeRules};
+use crate::commands::watch::SyncAction;
+use anyhow::Result;
+use ignore::WalkBuilder;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How the working tree differs from the FileLogs stored for it.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DiffReport {
+    /// On disk but never indexed
+    pub added: Vec<PathBuf>,
+    /// Content hash differs from the stored one, or none was stored
+    pub modified: Vec<PathBuf>,
+    /// Indexed but no longer on disk
+    pub deleted: Vec<PathBuf>,
+    pub unchanged: Vec<PathBuf>,
+}
+
+impl DiffReport {
+    /// Files whose index is stale, with the sync that brings it up to date.
+    pub fn drifted(&self) -> Vec<(&Path, SyncAction)> {
+        let mut drifted: Vec<(&Path, SyncAction)> = Vec::new();
+        drifted.extend(self.added.iter().map(|p| (p.as_path(), SyncAction::Create)));
+        drifted.extend(
+            self.modified
+                .iter()
+                .map(|p| (p.as_path(), SyncAction::Edit)),
+        );
+        drifted.extend(
+            self.deleted
+                .iter()
+                .map(|p| (p.as_path(), SyncAction::Delete)),
+        );
+        drifted
+    }
+}
+
+/// Compare the index with the working tree under `path`; with `fix`, sync
+/// each drifted file. Without it nothing is written.
+pub async fn run_diff(path: &str, exclude: &[String], fix: bool, client: &AmpClient) -> Result<()> {
+    let root = Path::new(path).canonicalize()?;
+    let respect_gitignore = match get_index_settings(client).await {
+        Ok(settings) => settings.respect_gitignore,
+        Err(_) => true,
+    };
+    let rules = Arc::new(ExcludeRules::new(&root, exclude, respect_gitignore));
+
+    let local = hash_tree(&root, &rules);
+    let stored = client.get_file_hashes().await?;
+    let stored = stored.as_array().cloned().unwrap_or_default();
+    let report = compare(&root, &local, &stored, &rules);
+
+    print_report(&root, &report);
+    if fix {
+        let synced = sync_drifted(&root, &report, client).await;
+        println!(
+            "\nSynced {} of {} drifted files",
+            synced,
+            report.drifted().len()
+        );
+    }
+    Ok(())
+}
+
+/// SHA256 of every text file the indexer would pick up.
+fn hash_tree(root: &Path, rules: &Arc<ExcludeRules>) -> Vec<(PathBuf, String)> {
+    let mut walker = WalkBuilder::new(root);
+    walker
+        .follow_links(false)
+        .hidden(false)
+        .git_ignore(false)
+        .git_exclude(false)
+        .git_global(false);
+    let rules = Arc::clone(rules);
+    walker.filter_entry(move |entry| {
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        entry.depth() == 0 || !rules.is_excluded(entry.path(), is_dir)
+    });
+    let mut files = Vec::new();
+    for entry in walker.build().flatten() {
+        let path = entry.path();
+        if !path.is_file() || !is_text_file(path) {
+            continue;
+        }
+        if let Some(hash) = file_content_hash(path) {
+            files.push((path.to_path_buf(), hash));
+        }
+    }
+    files
+}
+
+/// Bucket files by comparing local hashes with the stored FileLogs under `root`.
+pub(crate) fn compare(
+    root: &Path,
+    local: &[(PathBuf, String)],
+    stored: &[Value],
+    rules: &ExcludeRules,
+) -> DiffReport {
+    let root_key = format!("{}\\", path_key(&root.to_string_lossy()));
+    let mut stored_by_key: HashMap<String, (PathBuf, Option<&str>)> = HashMap::new();
+    for log in stored {
+        let Some(file_path) = log.get("file_path").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let key = path_key(file_path);
+        if key.starts_with(&root_key) {
+            let hash = log.get("content_hash").and_then(|v| v.as_str());
+            stored_by_key.insert(key, (PathBuf::from(file_path), hash));
+        }
+    }
+
+    let mut report = DiffReport::default();
+    for (path, hash) in local {
+        match stored_by_key.remove(&path_key(&path.to_string_lossy())) {
+            None => report.added.push(path.clone()),
+            Some((_, Some(stored))) if stored == hash => report.unchanged.push(path.clone()),
+            Some(_) => report.modified.push(path.clone()),
+        }
+    }
+    for (_, (path, _)) in stored_by_key {
+        // Still on disk means a directory log or a file the walk skipped
+        if path.exists() || rules.is_excluded(&path, false) {
+            continue;
+        }
+        report.deleted.push(path);
+    }
+    for bucket in [
+        &mut report.added,
+        &mut report.modified,
+        &mut report.deleted,
+        &mut report.unchanged,
+    ] {
+        bucket.sort();
+    }
+    report
+}
+
+/// Same normalization the server uses to match FileLog paths.
+fn path_key(path: &str) -> String {
+    let mut normalized = path.replace('/', "\\");
+    if let Some(stripped) = normalized.strip_prefix(r"\\?\") {
+        normalized = stripped.to_string();
+    }
+    normalized.trim_end_matches('\\').to_lowercase()
+}
+
+fn print_report(root: &Path, report: &DiffReport) {
+    println!("Index vs working tree for {}", root.display());
+    for (label, bucket) in [
+        ("added", &report.added),
+        ("modified", &report.modified),
+        ("deleted", &report.deleted),
+    ] {
+        println!("\n{} ({}):", label, bucket.len());
+        for path in bucket {
+            println!("  {}", path.strip_prefix(root).unwrap_or(path).display());
+        }
+    }
+    println!("\nunchanged: {}", report.unchanged.len());
+}
+
+/// Sync each drifted file the way `amp index --watch` would. Returns how many succeeded.
+async fn sync_drifted(root: &Path, report: &DiffReport, client: &AmpClient) -> usize {
+    let mut synced = 0;
+    for (path, action) in report.drifted() {
+        let display = path.strip_prefix(root).unwrap_or(path).display();
+        let path = path.to_string_lossy();
+        if let Err(e) = client.sync_file(&path, action.as_str()).await {
+            println!("  {:<6} {}  failed: {}", action.as_str(), display, e);
+            continue;
+        }
+        if action == SyncAction::Delete {
+            if let Err(e) = client.delete_file_objects(&path).await {
+                println!(
+                    "  {:<6} {}  cleanup failed: {}",
+                    action.as_str(),
+                    display,
+                    e
+                );
+                continue;
+            }
+        }
+        println!("  {:<6} {}", action.as_str(), display);
+        synced += 1;
+    }
+    synced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::State,
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Clone)]
+    struct Mock {
+        stored: Arc<Vec<Value>>,
+        calls: Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    async fn file_hashes(State(mock): State<Mock>) -> Json<Value> {
+        Json(Value::Array(mock.stored.to_vec()))
+    }
+
+    async fn sync(State(mock): State<Mock>, Json(body): Json<Value>) -> Json<Value> {
+        let path = body["path"].as_str().unwrap().to_string();
+        let action = body["action"].as_str().unwrap().to_string();
+        mock.calls.lock().unwrap().push((path, action));
+        Json(json!({ "symbols_added": 0, "symbols_removed": 0, "symbols_changed": 0 }))
+    }
+
+    async fn delete_file(State(mock): State<Mock>, Json(body): Json<Value>) -> Json<Value> {
+        let path = body["path"].as_str().unwrap().to_string();
+        mock.calls
+            .lock()
+            .unwrap()
+            .push((path, "delete-file".to_string()));
+        Json(json!({ "objects_deleted": 1, "relationships_deleted": 0 }))
+    }
+
+    fn hash(content: &str) -> String {
+        use sha2::{Digest, Sha256};
+        format!("{:x}", Sha256::digest(content.as_bytes()))
+    }
+
+    #[tokio::test]
+    async fn test_diff_buckets_and_fix_syncs_drifted_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/same.rs"), "fn same() {}\n").unwrap();
+        std::fs::write(root.join("src/changed.rs"), "fn changed() { 2 }\n").unwrap();
+        std::fs::write(root.join("src/new.rs"), "fn new() {}\n").unwrap();
+        let stored_path = |name: &str| root.join(name).to_string_lossy().to_string();
+        let stored = vec![
+            json!({ "file_path": stored_path("src"), "content_hash": null }),
+            json!({ "file_path": stored_path("src/same.rs"), "content_hash": hash("fn same() {}\n") }),
+            json!({ "file_path": stored_path("src/changed.rs"), "content_hash": hash("fn changed() { 1 }\n") }),
+            json!({ "file_path": stored_path("src/gone.rs"), "content_hash": hash("fn gone() {}\n") }),
+            // Another project's file is not part of this tree
+            json!({ "file_path": "/elsewhere/lib.rs", "content_hash": "abc" }),
+        ];
+
+        let rules = Arc::new(ExcludeRules::new(&root, &[], true));
+        let report = compare(&root, &hash_tree(&root, &rules), &stored, &rules);
+        assert_eq!(
+            report,
+            DiffReport {
+                added: vec![root.join("src/new.rs")],
+                modified: vec![root.join("src/changed.rs")],
+                deleted: vec![root.join("src/gone.rs")],
+                unchanged: vec![root.join("src/same.rs")],
+            }
+        );
+
+        let mock = Mock {
+            stored: Arc::new(stored),
+            calls: Arc::default(),
+        };
+        let app = Router::new()
+            .route("/v1/codebase/file-hashes", get(file_hashes))
+            .route("/v1/codebase/sync", post(sync))
+            .route("/v1/codebase/delete-file", post(delete_file))
+            .with_state(mock.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        run_diff(&root.to_string_lossy(), &[], false, &client)
+            .await
+            .unwrap();
+        assert!(mock.calls.lock().unwrap().is_empty());
+
+        run_diff(&root.to_string_lossy(), &[], true, &client)
+            .await
+            .unwrap();
+        let calls = mock.calls.lock().unwrap();
+        let calls: Vec<(String, &str)> = calls
+            .iter()
+            .map(|(path, action)| (path.clone(), action.as_str()))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                (stored_path("src/new.rs"), "create"),
+                (stored_path("src/changed.rs"), "edit"),
+                (stored_path("src/gone.rs"), "delete"),
+                (stored_path("src/gone.rs"), "delete-file"),
+            ]
+        );
+    }
+}
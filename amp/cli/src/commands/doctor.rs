@@ -0,0 +1,366 @@
+use crate::client::AmpClient;
+use crate::commands::output::{self, OutputFormat, OutputOptions};
+use anyhow::Result;
+use comfy_table::{presets::UTF8_FULL_CONDENSED, Cell, Color, Table};
+use serde::Serialize;
+use serde_json::Value;
+use std::env;
+use std::future::Future;
+use tokio::time::{timeout, Duration};
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Environment the checks read, captured once so tests can supply their own.
+#[derive(Debug, Clone)]
+pub struct DoctorEnv {
+    pub server_url: Option<String>,
+    pub mcp_transport: Option<String>,
+    pub mcp_port: u16,
+}
+
+impl DoctorEnv {
+    pub fn from_env() -> Self {
+        Self {
+            server_url: env::var("AMP_SERVER_URL").ok(),
+            mcp_transport: env::var("MCP_TRANSPORT").ok(),
+            mcp_port: env::var("MCP_PORT")
+                .ok()
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(8106),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not applicable to this setup; does not fail the run
+    Skip,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            suggestion: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            suggestion: Some(suggestion.into()),
+        }
+    }
+
+    fn skip(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Skip,
+            detail: detail.into(),
+            suggestion: None,
+        }
+    }
+}
+
+/// Run every check and print the results. Returns whether all of them passed.
+pub async fn run_doctor(client: &AmpClient, options: OutputOptions) -> Result<bool> {
+    let checks = run_checks(client, &DoctorEnv::from_env()).await;
+    let healthy = checks.iter().all(|c| c.status != CheckStatus::Fail);
+
+    if options.format == OutputFormat::Table {
+        println!("{}", colored_table(&checks));
+    } else {
+        let rows: Vec<Vec<String>> = checks
+            .iter()
+            .map(|c| {
+                vec![
+                    c.name.to_string(),
+                    status_label(c.status).to_string(),
+                    c.detail.clone(),
+                ]
+            })
+            .collect();
+        println!(
+            "{}",
+            output::render(
+                options.format,
+                &["Check", "Status", "Detail"],
+                &rows,
+                &checks
+            )?
+        );
+    }
+
+    if options.show_summary() {
+        let failing: Vec<&Check> = checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .collect();
+        if failing.is_empty() {
+            println!("\nAll checks passed.");
+        } else {
+            println!("\nSuggestions:");
+            for check in failing {
+                if let Some(suggestion) = &check.suggestion {
+                    println!("  - {}: {}", check.name, suggestion);
+                }
+            }
+        }
+    }
+
+    Ok(healthy)
+}
+
+pub async fn run_checks(client: &AmpClient, doctor_env: &DoctorEnv) -> Vec<Check> {
+    let (server, db, embedding, mcp) = tokio::join!(
+        with_timeout(client.health_check()),
+        with_timeout(client.db_health()),
+        with_timeout(client.embedding_health()),
+        mcp_port_check(doctor_env),
+    );
+
+    let server = match server {
+        Ok(true) => Check::pass(
+            "AMP server",
+            format!("{}/health responded", client.base_url()),
+        ),
+        Ok(false) => Check::fail(
+            "AMP server",
+            format!("{}/health returned an error", client.base_url()),
+            "Check the amp-server logs (`docker compose logs amp-server`)",
+        ),
+        Err(e) => Check::fail(
+            "AMP server",
+            format!("{} unreachable: {}", client.base_url(), e),
+            "Run `docker compose up amp-server` to start the server",
+        ),
+    };
+
+    let (database, projects) = match db {
+        Ok(health) => {
+            let latency = health["latency_ms"].as_u64().unwrap_or(0);
+            let count = health["indexed_projects"].as_u64().unwrap_or(0);
+            let projects = if count > 0 {
+                Check::pass("Indexed projects", format!("{} indexed", count))
+            } else {
+                Check::fail(
+                    "Indexed projects",
+                    "No projects indexed yet",
+                    "Run `amp index` from your project root",
+                )
+            };
+            (
+                Check::pass("SurrealDB", format!("Responded in {}ms", latency)),
+                projects,
+            )
+        }
+        Err(e) => (
+            Check::fail(
+                "SurrealDB",
+                e.to_string(),
+                "Check DATABASE_URL and the database errors in the amp-server logs",
+            ),
+            Check::fail(
+                "Indexed projects",
+                "Could not count projects without the database",
+                "Fix the SurrealDB check first",
+            ),
+        ),
+    };
+
+    let embedding = match embedding {
+        Ok(health) => embedding_check(&health),
+        Err(e) => Check::fail(
+            "Embeddings",
+            e.to_string(),
+            "Start the server, then rerun `amp doctor`",
+        ),
+    };
+
+    let server_url = match &doctor_env.server_url {
+        Some(url) => Check::pass("AMP_SERVER_URL", url.clone()),
+        None => Check::fail(
+            "AMP_SERVER_URL",
+            format!("Not set; using {}", client.base_url()),
+            "Set AMP_SERVER_URL to your server's address, e.g. http://localhost:8105",
+        ),
+    };
+
+    vec![server, database, embedding, mcp, server_url, projects]
+}
+
+fn embedding_check(health: &Value) -> Check {
+    let provider = health["provider"].as_str().unwrap_or("unknown");
+    let model = health["model"].as_str().unwrap_or("unknown");
+    if health["ok"].as_bool().unwrap_or(false) {
+        return Check::pass(
+            "Embeddings",
+            format!(
+                "{} / {} ({} dims, {}ms)",
+                provider,
+                model,
+                health["dimension"].as_u64().unwrap_or(0),
+                health["latency_ms"].as_u64().unwrap_or(0)
+            ),
+        );
+    }
+
+    let error = health["error"].as_str().unwrap_or("Test embedding failed");
+    let suggestion = if health["enabled"].as_bool().unwrap_or(false) {
+        format!(
+            "Check the {} API key or that the provider is reachable from the server",
+            provider
+        )
+    } else {
+        "Set EMBEDDING_PROVIDER (openai, openrouter, cohere or ollama) or pick one in the UI settings"
+            .to_string()
+    };
+    Check::fail("Embeddings", error, suggestion)
+}
+
+/// With the HTTP transport the MCP server must be able to bind `MCP_PORT`.
+async fn mcp_port_check(doctor_env: &DoctorEnv) -> Check {
+    let transport = doctor_env.mcp_transport.as_deref().unwrap_or("stdio");
+    if !matches!(transport, "http" | "sse") {
+        return Check::skip("MCP port", format!("MCP_TRANSPORT={}", transport));
+    }
+
+    let port = doctor_env.mcp_port;
+    match tokio::net::TcpListener::bind(("0.0.0.0", port)).await {
+        Ok(_) => Check::pass("MCP port", format!("{} is free", port)),
+        Err(e) => Check::fail(
+            "MCP port",
+            format!("{} is in use: {}", port, e),
+            "Stop whatever holds the port or set MCP_PORT to a free one",
+        ),
+    }
+}
+
+async fn with_timeout<T>(check: impl Future<Output = Result<T>>) -> Result<T> {
+    match timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => anyhow::bail!("timed out after {}s", CHECK_TIMEOUT.as_secs()),
+    }
+}
+
+fn status_label(status: CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Pass => "PASS",
+        CheckStatus::Fail => "FAIL",
+        CheckStatus::Skip => "SKIP",
+    }
+}
+
+fn colored_table(checks: &[Check]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_header(vec!["Check", "Status", "Detail"]);
+    for check in checks {
+        let color = match check.status {
+            CheckStatus::Pass => Color::Green,
+            CheckStatus::Fail => Color::Red,
+            CheckStatus::Skip => Color::DarkGrey,
+        };
+        table.add_row(vec![
+            Cell::new(check.name),
+            Cell::new(status_label(check.status)).fg(color),
+            Cell::new(&check.detail),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Json, Router};
+    use serde_json::json;
+
+    fn doctor_env(mcp_transport: Option<&str>, mcp_port: u16) -> DoctorEnv {
+        DoctorEnv {
+            server_url: Some("http://localhost:8105".to_string()),
+            mcp_transport: mcp_transport.map(str::to_string),
+            mcp_port,
+        }
+    }
+
+    fn status_of(checks: &[Check], name: &str) -> CheckStatus {
+        checks.iter().find(|c| c.name == name).unwrap().status
+    }
+
+    #[tokio::test]
+    async fn test_checks_pass_against_healthy_server() {
+        let app = Router::new()
+            .route("/health", get(|| async { Json(json!({ "status": "healthy" })) }))
+            .route(
+                "/v1/debug/db-health",
+                get(|| async { Json(json!({ "status": "ok", "latency_ms": 2, "indexed_projects": 1 })) }),
+            )
+            .route(
+                "/v1/debug/embedding-health",
+                get(|| async {
+                    Json(json!({ "enabled": true, "provider": "ollama", "model": "nomic", "dimension": 768, "ok": true, "latency_ms": 40 }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let checks = run_checks(&client, &doctor_env(None, 8106)).await;
+        assert!(
+            checks.iter().all(|c| c.status != CheckStatus::Fail),
+            "{:?}",
+            checks
+        );
+        assert_eq!(status_of(&checks, "MCP port"), CheckStatus::Skip);
+    }
+
+    #[tokio::test]
+    async fn test_checks_fail_with_suggestions_when_server_is_down() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let client = AmpClient::new(&format!("http://127.0.0.1:{}", port));
+        let env = DoctorEnv {
+            server_url: None,
+            ..doctor_env(Some("http"), port)
+        };
+
+        let checks = run_checks(&client, &env).await;
+        assert_eq!(status_of(&checks, "AMP server"), CheckStatus::Fail);
+        assert_eq!(status_of(&checks, "AMP_SERVER_URL"), CheckStatus::Fail);
+        assert_eq!(status_of(&checks, "MCP port"), CheckStatus::Pass);
+        assert!(checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .all(|c| c.suggestion.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_mcp_port_in_use_fails() {
+        let held = tokio::net::TcpListener::bind("0.0.0.0:0").await.unwrap();
+        let port = held.local_addr().unwrap().port();
+        let check = mcp_port_check(&doctor_env(Some("http"), port)).await;
+        assert_eq!(check.status, CheckStatus::Fail);
+    }
+}
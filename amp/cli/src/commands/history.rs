@@ -1,39 +1,92 @@
+use crate::commands::output::{self, OutputFormat, OutputOptions};
 use crate::{client::AmpClient, config::Config, session::Session};
 use anyhow::Result;
 
-pub async fn show_history(_client: &AmpClient) -> Result<()> {
-    println!("AMP Session History");
-    println!("==================");
-    
+pub async fn show_history(_client: &AmpClient, options: OutputOptions) -> Result<()> {
+    if options.show_summary() {
+        println!("AMP Session History");
+        println!("==================");
+    }
+
     let config = Config::from_env()?;
     let sessions = Session::list_sessions(&config.session_dir).await?;
-    
-    if sessions.is_empty() {
-        println!("No sessions found");
+
+    if sessions.is_empty() && options.format != OutputFormat::Json {
+        if options.show_summary() {
+            println!("No sessions found");
+        }
         return Ok(());
     }
-    
-    for session in sessions.iter().take(10) {
-        let duration = if let Some(ended_at) = session.ended_at {
-            let duration = ended_at - session.started_at;
-            format!("{}s", duration.num_seconds())
-        } else {
-            "ongoing".to_string()
-        };
-        
-        println!(
-            "{} | {:?} | {} | {} | {}",
-            session.started_at.format("%Y-%m-%d %H:%M:%S"),
-            session.status,
-            duration,
-            session.agent_command,
-            session.id
-        );
-    }
-    
-    if sessions.len() > 10 {
+
+    let shown = &sessions[..sessions.len().min(10)];
+    println!("{}", render_history(shown, options)?);
+
+    if sessions.len() > 10 && options.show_summary() {
         println!("... and {} more sessions", sessions.len() - 10);
     }
-    
+
     Ok(())
 }
+
+fn render_history(sessions: &[Session], options: OutputOptions) -> Result<String> {
+    let rows: Vec<Vec<String>> = sessions
+        .iter()
+        .map(|session| {
+            let duration = if let Some(ended_at) = session.ended_at {
+                let duration = ended_at - session.started_at;
+                format!("{}s", duration.num_seconds())
+            } else {
+                "ongoing".to_string()
+            };
+            vec![
+                session.started_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                format!("{:?}", session.status),
+                duration,
+                session.agent_command.clone(),
+                session.id.to_string(),
+            ]
+        })
+        .collect();
+
+    output::render(
+        options.format,
+        &["Started", "Status", "Duration", "Agent", "Session"],
+        &rows,
+        sessions,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_history_formats() {
+        let mut session = Session::new("claude".to_string(), "amp".to_string());
+        session.complete();
+        let sessions = vec![session.clone()];
+
+        let markdown = render_history(
+            &sessions,
+            OutputOptions {
+                format: OutputFormat::Markdown,
+                quiet: false,
+            },
+        )
+        .unwrap();
+        let row = markdown.lines().nth(2).unwrap();
+        assert!(row.contains("| Completed | 0s | claude |"));
+        assert!(row.contains(&session.id.to_string()));
+
+        let json = render_history(
+            &sessions,
+            OutputOptions {
+                format: OutputFormat::Json,
+                quiet: true,
+            },
+        )
+        .unwrap();
+        let parsed: Vec<Session> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0].id, session.id);
+    }
+}
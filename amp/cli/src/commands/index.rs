@@ -1,10 +1,14 @@
-use crate::client::AmpClient;
+use crate::client::{AmpClient, RelationshipCounts};
+use crate::commands::output::{self, OutputFormat, OutputOptions};
 use anyhow::Result;
+use serde::Serialize;
 use serde_json::{json, Value};
 use std::path::{Path, PathBuf};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::WalkBuilder;
 use uuid::Uuid;
 use chrono::Utc;
@@ -17,6 +21,10 @@ use std::io::IsTerminal;
 
 use crate::commands::index_ui::{start_index_ui, IndexUiHandle, IndexUiState};
 
+// The server's word-window chunker, so fallback chunks match the ones it makes
+#[path = "../../../server/src/services/word_windows.rs"]
+mod word_windows;
+
 static INDEX_QUIET: AtomicBool = AtomicBool::new(false);
 const MAX_AI_LOG_CONTENT_CHARS: usize = 20000;
 const AI_LOG_CONTENT_HEAD_CHARS: usize = 12000;
@@ -58,13 +66,44 @@ impl Drop for UiGuard {
     }
 }
 
-pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client: &AmpClient) -> Result<()> {
-    let use_tui = std::io::stdout().is_terminal();
+/// Totals reported when `amp index` finishes.
+#[derive(Debug, Default, Serialize)]
+pub struct IndexSummary {
+    pub project_id: String,
+    pub directories: usize,
+    pub files_processed: usize,
+    pub symbols: usize,
+    pub relationships: RelationshipCounts,
+    pub deleted_during_indexing: usize,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+pub async fn run_index(
+    path: &str,
+    exclude: &[String],
+    init_root: bool,
+    output_dir: Option<&str>,
+    files_from: Option<&str>,
+    options: OutputOptions,
+    client: &AmpClient,
+) -> Result<IndexSummary> {
+    // Read the list before the UI takes over the terminal
+    let listed_files = match files_from {
+        Some(source) => Some(read_file_list(source, path)?),
+        None => None,
+    };
+    // JSON output replaces both the progress log and the TUI
+    let json_output = options.format == OutputFormat::Json;
+    let use_tui = !json_output && std::io::stdout().is_terminal();
     let cancel_flag = Arc::new(AtomicBool::new(false));
-    if use_tui {
+    if use_tui || json_output {
         INDEX_QUIET.store(true, Ordering::Relaxed);
         client.set_quiet(true);
     }
+    // The client may have linked objects before (e.g. `--watch` reuses it)
+    let relationships_before = client.relationships_created();
     let ui_state = Arc::new(Mutex::new(IndexUiState::default()));
     let mut ui_guard = UiGuard { handle: None };
 
@@ -99,6 +138,15 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
     if init_root {
         maybe_init_amp_root(&root_path)?;
     }
+
+    // FILE_LOG markdown mirror target; created up front so a bad path fails fast
+    let output_dir = match output_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            Some(Path::new(dir).canonicalize()?)
+        }
+        None => None,
+    };
     
     // Create project root node first
     let (project_object_id, project_id) = create_project_node(&root_path, client).await?;
@@ -141,42 +189,14 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
     let mut created_symbols = 0;
     let mut created_directories = 0;
     let mut errors = Vec::new();
+    let mut vanished_files = 0;
     
-    // Default exclude patterns
-    let mut exclude_patterns = vec![
-        ".git".to_string(),
-        ".venv".to_string(),
-        "venv".to_string(),
-        "env".to_string(),
-        ".env".to_string(),
-        "node_modules".to_string(),
-        "lib".to_string(),
-        "Lib".to_string(),
-        "libs".to_string(),
-        "target".to_string(),
-        "dist".to_string(),
-        "build".to_string(),
-        "__pycache__".to_string(),
-        ".pytest_cache".to_string(),
-        ".mypy_cache".to_string(),
-        ".tox".to_string(),
-        "*.pyc".to_string(),
-        "*.pyo".to_string(),
-        "*.log".to_string(),
-        "*.tmp".to_string(),
-        ".DS_Store".to_string(),
-        "Thumbs.db".to_string(),
-        ".idea".to_string(),
-        ".vscode".to_string(),
-        "amp-core".to_string(),
-        "*.egg-info".to_string(),
-        ".coverage".to_string(),
-        "htmlcov".to_string(),
-    ];
-    exclude_patterns.extend_from_slice(exclude);
-    
+    let exclude_rules = Arc::new(ExcludeRules::new(&root_path, exclude, index_respect_gitignore));
     if !use_tui {
-        index_log!("Exclude patterns: {:?}", exclude_patterns);
+        index_log!("Exclude patterns: {:?}", exclude_rules.patterns());
+        if exclude_rules.has_amp_ignore() {
+            index_log!("Using {} (aggressive defaults disabled)", AMP_IGNORE_FILE);
+        }
     }
     
     // Track created directories to avoid duplicates
@@ -188,25 +208,55 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
     let mut files_to_process = Vec::new();
     let mut skipped_files = Vec::new();
     
+    // Exclusions are evaluated here rather than by the walker so each skipped
+    // path can be attributed to the rule that excluded it.
+    let excluded_paths: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
     let mut walker = WalkBuilder::new(&root_path);
-    walker.follow_links(false).hidden(false);
-    if index_respect_gitignore {
-        walker.git_ignore(true).git_exclude(false).git_global(false);
-    } else {
-        walker.git_ignore(false).git_exclude(false).git_global(false);
+    walker
+        .follow_links(false)
+        .hidden(false)
+        .git_ignore(false)
+        .git_exclude(false)
+        .git_global(false);
+    {
+        let exclude_rules = Arc::clone(&exclude_rules);
+        let excluded_paths = Arc::clone(&excluded_paths);
+        walker.filter_entry(move |entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            match exclude_rules.classify(entry.path(), is_dir) {
+                Some(rule) => {
+                    excluded_paths
+                        .lock()
+                        .unwrap()
+                        .push(format!("Excluded by {}: {}", rule.label(), entry.path().display()));
+                    false
+                }
+                None => true,
+            }
+        });
     }
 
-    for entry in walker.build() {
+    // A --files-from list replaces the walk; only the listed files are indexed
+    let entries: Box<dyn Iterator<Item = Result<PathBuf, ignore::Error>>> = match listed_files {
+        Some(listed) => {
+            let (present, missing): (Vec<PathBuf>, Vec<PathBuf>) =
+                listed.into_iter().partition(|path| path.is_file());
+            for path in missing {
+                skipped_files.push(format!("Listed file not found: {}", path.display()));
+            }
+            Box::new(present.into_iter().map(Ok))
+        }
+        None => Box::new(walker.build().map(|entry| entry.map(|entry| entry.into_path()))),
+    };
+
+    for entry in entries {
         check_cancel(&cancel_flag)?;
         match entry {
             Ok(entry) => {
-                let path = entry.path();
-                
-                // Skip if matches exclude patterns
-                if should_exclude(path, &exclude_patterns) {
-                    skipped_files.push(format!("Excluded: {}", path.display()));
-                    continue;
-                }
+                let path = entry.as_path();
                 
                 // Ensure directory chain exists for this entry
                 if let Some(dir_path) = if path.is_dir() { Some(path) } else { path.parent() } {
@@ -255,6 +305,8 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
         }
     }
     
+    skipped_files.splice(0..0, excluded_paths.lock().unwrap().drain(..));
+
     with_ui_state(&ui_state, use_tui, |state| {
         state.phase = "Indexing".to_string();
         state.supported_files = files_to_process.len();
@@ -274,9 +326,6 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
         for skip in skipped_files.iter().take(10) {
             index_log!("   {}", skip);
         }
-        if index_respect_gitignore {
-            index_log!("Note: .gitignore entries are filtered before walking and are not counted above.");
-        }
     }
 
     if index_ai_enabled && !created_dir_nodes.is_empty() {
@@ -352,6 +401,10 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
                     file_index.insert(key, file_id);
                 }
             }
+            Ok(Err(e)) if e.is::<FileVanished>() => {
+                skipped_files.push(e.to_string());
+                vanished_files += 1;
+            }
             Ok(Err(e)) => {
                 errors.push(format!("Failed to create file node: {}", e));
                 with_ui_state(&ui_state, use_tui, |state| state.errors += 1);
@@ -381,6 +434,8 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
         };
         let file_id = match file_index.get(&key) {
             Some(id) => id.clone(),
+            // Already reported as deleted while its node was being created
+            None if !file_path.exists() => continue,
             None => {
                 errors.push(format!("Missing file node for {}", file_path.display()));
                 with_ui_state(&ui_state, use_tui, |state| state.errors += 1);
@@ -393,29 +448,39 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
         let project_id = project_id.clone();
         let root_path = root_path.to_path_buf();
         let file_index = Arc::clone(&file_index);
+        let output_dir = output_dir.clone();
         join_set.spawn(async move {
             let _permit = permit;
-            let symbols_count = process_file_hierarchical_with_id(
+            let (symbols_count, file_log_job, file_calls) = process_file_hierarchical_with_id(
                 &file_path,
                 &file_id,
                 &project_id,
                 &root_path,
                 file_index.as_ref(),
                 index_ai_enabled,
+                output_dir.as_deref(),
                 &client,
             )
             .await?;
-            Ok::<(PathBuf, usize), anyhow::Error>((file_path, symbols_count))
+            Ok::<(PathBuf, usize, Option<FileLogJob>, FileCalls), anyhow::Error>((file_path, symbols_count, file_log_job, file_calls))
         });
     }
 
+    let mut file_log_jobs: Vec<FileLogJob> = Vec::new();
+    let mut project_symbols: HashMap<String, Vec<String>> = HashMap::new();
+    let mut pending_calls: Vec<(String, String)> = Vec::new();
     while let Some(result) = join_set.join_next().await {
         if cancel_flag.load(Ordering::Relaxed) {
             join_set.abort_all();
             anyhow::bail!("Indexing cancelled by user.");
         }
         match result {
-            Ok(Ok((file_path, symbols_count))) => {
+            Ok(Ok((file_path, symbols_count, file_log_job, file_calls))) => {
+                file_log_jobs.extend(file_log_job);
+                for (name, symbol_id) in file_calls.symbols {
+                    project_symbols.entry(name).or_default().push(symbol_id);
+                }
+                pending_calls.extend(file_calls.unresolved);
                 processed_files += 1;
                 created_symbols += symbols_count;
                 if !use_tui {
@@ -424,10 +489,16 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
                 with_ui_state(&ui_state, use_tui, |state| {
                     state.processed_files = processed_files;
                     state.created_symbols = created_symbols;
+                    state.created_relationships =
+                        client.relationships_created().since(&relationships_before).total();
                     state.current_path = file_path.display().to_string();
                     state.status_message = "Processing files".to_string();
                 });
             }
+            Ok(Err(e)) if e.is::<FileVanished>() => {
+                skipped_files.push(e.to_string());
+                vanished_files += 1;
+            }
             Ok(Err(e)) => {
                 errors.push(format!("Error processing file: {}", e));
                 with_ui_state(&ui_state, use_tui, |state| state.errors += 1);
@@ -438,13 +509,38 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
             }
         }
     }
+
+    // Calls into other files can only be linked once every file's symbols exist
+    for (caller_id, callee_id) in resolve_project_calls(&pending_calls, &project_symbols) {
+        if let Err(e) = client.create_relationship_direct(&caller_id, &callee_id, "calls").await {
+            index_log!("Failed to create call relationship: {}", e);
+        }
+    }
+
+    if !file_log_jobs.is_empty() {
+        with_ui_state(&ui_state, use_tui, |state| {
+            state.phase = "File logs".to_string();
+            state.status_message = format!("Generating {} file logs", file_log_jobs.len());
+        });
+        if !use_tui {
+            index_log!("Generating file AI logs ({} entries)...", file_log_jobs.len());
+        }
+        let failures =
+            generate_file_ai_logs(file_log_jobs, &project_id, worker_count, &cancel_flag, client).await?;
+        for failure in failures {
+            warnings.push(format!("File AI log failed: {}", failure));
+            with_ui_state(&ui_state, use_tui, |state| state.warnings += 1);
+        }
+    }
     
+    let relationships = client.relationships_created().since(&relationships_before);
     with_ui_state(&ui_state, use_tui, |state| {
         state.phase = "Complete".to_string();
         state.status_message = "Indexing complete".to_string();
         state.processed_files = processed_files;
         state.created_symbols = created_symbols;
         state.created_directories = created_directories;
+        state.created_relationships = relationships.total();
         state.done = true;
     });
 
@@ -456,7 +552,17 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
         index_log!("   Directories: {} nodes", created_directories);
         index_log!("   Files processed: {}", processed_files);
         index_log!("   Code symbols: {}", created_symbols);
+        if vanished_files > 0 {
+            index_log!("   Deleted during indexing: {}", vanished_files);
+        }
         index_log!("   Total nodes: {}", 1 + created_directories + processed_files + created_symbols);
+        index_log!(
+            "   Relationships: {} (defined_in: {}, depends_on: {}, calls: {})",
+            relationships.total(),
+            relationships.defined_in,
+            relationships.depends_on,
+            relationships.calls
+        );
 
         // Show project name detection info
         index_log!("\nProject Name Detection:");
@@ -500,8 +606,53 @@ pub async fn run_index(path: &str, exclude: &[String], init_root: bool, client:
             handle.wait_for_exit()?;
         }
     }
-    
-    Ok(())
+
+    let summary = IndexSummary {
+        project_id,
+        directories: created_directories,
+        files_processed: processed_files,
+        symbols: created_symbols,
+        relationships,
+        deleted_during_indexing: vanished_files,
+        skipped: skipped_files,
+        errors,
+        warnings,
+    };
+    if json_output {
+        println!("{}", output::json(&summary)?);
+    }
+    Ok(summary)
+}
+
+/// Read a newline-separated `--files-from` list from `source` (`-` for stdin)
+pub fn read_file_list(source: &str, root: &str) -> Result<Vec<PathBuf>> {
+    let root_path = Path::new(root);
+    let root_path = root_path.canonicalize().unwrap_or_else(|_| root_path.to_path_buf());
+    if source == "-" {
+        return parse_file_list(std::io::stdin().lock(), &root_path);
+    }
+    let file = std::fs::File::open(source)
+        .map_err(|e| anyhow::anyhow!("Failed to read file list {}: {}", source, e))?;
+    parse_file_list(std::io::BufReader::new(file), &root_path)
+}
+
+/// Paths in a file list, relative ones resolved against `root`; blank lines are ignored
+fn parse_file_list(reader: impl std::io::BufRead, root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let path = root.join(line);
+        let path = path.canonicalize().unwrap_or(path);
+        if seen.insert(path.clone()) {
+            files.push(path);
+        }
+    }
+    Ok(files)
 }
 
 async fn ensure_directory_chain(
@@ -553,13 +704,13 @@ async fn ensure_directory_chain(
     Ok(())
 }
 
-struct IndexSettings {
+pub(crate) struct IndexSettings {
     worker_count: usize,
     ai_enabled: bool,
-    respect_gitignore: bool,
+    pub(crate) respect_gitignore: bool,
 }
 
-async fn get_index_settings(client: &AmpClient) -> Result<IndexSettings> {
+pub(crate) async fn get_index_settings(client: &AmpClient) -> Result<IndexSettings> {
     let settings = client.get_settings().await?;
     let workers = settings
         .get("indexWorkers")
@@ -582,40 +733,253 @@ async fn get_index_settings(client: &AmpClient) -> Result<IndexSettings> {
 }
 
 
-pub fn should_exclude(path: &Path, exclude_patterns: &[String]) -> bool {
-    for pattern in exclude_patterns {
-        // Handle wildcard patterns like *.log or *.egg-info
-        if pattern.starts_with('*') {
-            let suffix = &pattern[1..];
-            // Check if any path component ends with this suffix
-            for component in path.components() {
-                if let Some(comp_str) = component.as_os_str().to_str() {
-                    if comp_str.ends_with(suffix) {
-                        return true;
-                    }
-                }
+/// Project-level ignore file using gitignore syntax.
+const AMP_IGNORE_FILE: &str = ".amp-ignore";
+
+/// Always-on exclusions: VCS metadata, caches, and build output.
+const DEFAULT_EXCLUDE_PATTERNS: &[&str] = &[
+    ".git",
+    ".venv",
+    "venv",
+    ".env",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "__pycache__",
+    ".pytest_cache",
+    ".mypy_cache",
+    ".tox",
+    "*.pyc",
+    "*.pyo",
+    "*.log",
+    "*.tmp",
+    ".DS_Store",
+    "Thumbs.db",
+    ".idea",
+    ".vscode",
+    "amp-core",
+    "*.egg-info",
+    ".coverage",
+    "htmlcov",
+];
+
+/// Exclusions that can hide real source (e.g. projects keeping code in lib/).
+/// Only applied when the project has no .amp-ignore.
+const AGGRESSIVE_EXCLUDE_PATTERNS: &[&str] = &["env", "lib", "Lib", "libs"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExcludeRule {
+    Default,
+    CliFlag,
+    AmpIgnore,
+    GitIgnore,
+}
+
+impl ExcludeRule {
+    fn label(&self) -> &'static str {
+        match self {
+            ExcludeRule::Default => "default",
+            ExcludeRule::CliFlag => "CLI flag",
+            ExcludeRule::AmpIgnore => AMP_IGNORE_FILE,
+            ExcludeRule::GitIgnore => ".gitignore",
+        }
+    }
+}
+
+pub(crate) struct ExcludeRules {
+    root: PathBuf,
+    defaults: ExcludePatterns,
+    cli: ExcludePatterns,
+    amp_ignore: Option<Gitignore>,
+    respect_gitignore: bool,
+    /// Per-directory .gitignore matchers, loaded on first use.
+    gitignores: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+impl ExcludeRules {
+    pub(crate) fn new(root: &Path, cli: &[String], respect_gitignore: bool) -> Self {
+        let amp_ignore_path = root.join(AMP_IGNORE_FILE);
+        let amp_ignore = if amp_ignore_path.is_file() {
+            let mut builder = GitignoreBuilder::new(root);
+            if let Some(err) = builder.add(&amp_ignore_path) {
+                index_log!("Warning: failed to parse {}: {}", amp_ignore_path.display(), err);
             }
+            builder.build().ok()
         } else {
-            // Check if pattern matches any path component exactly
-            for component in path.components() {
-                if let Some(comp_str) = component.as_os_str().to_str() {
-                    if comp_str == pattern {
-                        return true;
-                    }
+            None
+        };
+
+        let mut defaults: Vec<String> = DEFAULT_EXCLUDE_PATTERNS.iter().map(|p| p.to_string()).collect();
+        if amp_ignore.is_none() {
+            defaults.extend(AGGRESSIVE_EXCLUDE_PATTERNS.iter().map(|p| p.to_string()));
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            defaults: ExcludePatterns::new(&defaults),
+            cli: ExcludePatterns::new(cli),
+            amp_ignore,
+            respect_gitignore,
+            gitignores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn has_amp_ignore(&self) -> bool {
+        self.amp_ignore.is_some()
+    }
+
+    fn patterns(&self) -> Vec<String> {
+        self.defaults.patterns.iter().chain(self.cli.patterns.iter()).cloned().collect()
+    }
+
+    pub(crate) fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.classify(path, is_dir).is_some()
+    }
+
+    fn classify(&self, path: &Path, is_dir: bool) -> Option<ExcludeRule> {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        // A CLI `!pattern` re-includes what later rules would exclude
+        match self.cli.decide(relative) {
+            Some(true) => return Some(ExcludeRule::CliFlag),
+            Some(false) => return None,
+            None => {}
+        }
+        if let Some(amp_ignore) = &self.amp_ignore {
+            if amp_ignore.matched_path_or_any_parents(path, is_dir).is_ignore() {
+                return Some(ExcludeRule::AmpIgnore);
+            }
+        }
+        if self.defaults.decide(relative) == Some(true) {
+            return Some(ExcludeRule::Default);
+        }
+        if self.respect_gitignore && self.is_gitignored(path, is_dir) {
+            return Some(ExcludeRule::GitIgnore);
+        }
+        None
+    }
+
+    /// Check .gitignore files from the entry's directory up to the root; the
+    /// nearest file with a matching rule decides, as in git.
+    fn is_gitignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut dir = path.parent();
+        while let Some(current) = dir {
+            if !current.starts_with(&self.root) {
+                break;
+            }
+            if let Some(gitignore) = self.gitignore_for(current) {
+                let matched = gitignore.matched(path, is_dir);
+                if matched.is_ignore() {
+                    return true;
+                }
+                if matched.is_whitelist() {
+                    return false;
                 }
             }
+            dir = current.parent();
         }
+        false
     }
-    
-    false
+
+    fn gitignore_for(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        let mut cache = self.gitignores.lock().unwrap();
+        cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let file = dir.join(".gitignore");
+                if !file.is_file() {
+                    return None;
+                }
+                let (gitignore, _) = Gitignore::new(&file);
+                Some(Arc::new(gitignore))
+            })
+            .clone()
+    }
+}
+
+pub fn should_exclude(path: &Path, exclude_patterns: &[String]) -> bool {
+    ExcludePatterns::new(exclude_patterns).decide(path) == Some(true)
+}
+
+/// Ordered exclude globs. A pattern without `/` (`target`, `*.log`) matches any
+/// path component; one with `/` (`src/**/generated/*`) matches the relative
+/// path or one of its parents. A leading `!` re-includes, and the last
+/// matching pattern wins.
+pub(crate) struct ExcludePatterns {
+    patterns: Vec<String>,
+    negated: Vec<bool>,
+    components: GlobSet,
+    component_rules: Vec<usize>,
+    paths: GlobSet,
+    path_rules: Vec<usize>,
 }
 
-fn is_text_file(path: &Path) -> bool {
+impl ExcludePatterns {
+    pub(crate) fn new(patterns: &[String]) -> Self {
+        let mut negated = Vec::new();
+        let mut components = GlobSetBuilder::new();
+        let mut component_rules = Vec::new();
+        let mut paths = GlobSetBuilder::new();
+        let mut path_rules = Vec::new();
+        for pattern in patterns {
+            let (glob, negate) = match pattern.strip_prefix('!') {
+                Some(rest) => (rest, true),
+                None => (pattern.as_str(), false),
+            };
+            let glob = glob.trim_end_matches('/');
+            let compiled = match GlobBuilder::new(glob).literal_separator(true).build() {
+                Ok(compiled) => compiled,
+                Err(err) => {
+                    index_log!("Warning: ignoring exclude pattern {:?}: {}", pattern, err);
+                    continue;
+                }
+            };
+            let rule = negated.len();
+            negated.push(negate);
+            if glob.contains('/') {
+                paths.add(compiled);
+                path_rules.push(rule);
+            } else {
+                components.add(compiled);
+                component_rules.push(rule);
+            }
+        }
+
+        Self {
+            patterns: patterns.to_vec(),
+            negated,
+            components: components.build().unwrap_or_else(|_| GlobSet::empty()),
+            component_rules,
+            paths: paths.build().unwrap_or_else(|_| GlobSet::empty()),
+            path_rules,
+        }
+    }
+
+    /// `Some(true)` when the last matching pattern excludes `path`,
+    /// `Some(false)` when it re-includes it, `None` when nothing matches.
+    pub(crate) fn decide(&self, path: &Path) -> Option<bool> {
+        let component_matches = path
+            .components()
+            .flat_map(|c| self.components.matches(c.as_os_str()))
+            .map(|i| self.component_rules[i]);
+        let path_matches = path
+            .ancestors()
+            .filter(|p| !p.as_os_str().is_empty())
+            .flat_map(|p| self.paths.matches(p))
+            .map(|i| self.path_rules[i]);
+        component_matches
+            .chain(path_matches)
+            .max()
+            .map(|rule| !self.negated[rule])
+    }
+}
+
+pub(crate) fn is_text_file(path: &Path) -> bool {
     // Check by extension first
     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
         let text_extensions = [
             "txt", "md", "json", "yaml", "yml", "toml", "xml", "html", "css", "scss",
-            "js", "jsx", "ts", "tsx", "py", "rs", "go", "java", "c", "cpp", "h", "hpp",
+            "js", "jsx", "ts", "tsx", "py", "rs", "go", "java", "c", "cpp", "h", "hpp", "php",
             "sh", "bash", "zsh", "fish", "ps1", "bat", "cmd",
             "sql", "graphql", "proto", "thrift",
             "env", "gitignore", "dockerignore", "editorconfig",
@@ -892,18 +1256,24 @@ async fn process_file_hierarchical(
         file_index.insert(key, file_id.clone());
     }
 
-    process_file_hierarchical_with_id(
+    let (symbols_count, file_log_job, _) = process_file_hierarchical_with_id(
         file_path,
         &file_id,
         project_id,
         root_path,
         file_index,
         index_ai_enabled,
+        None,
         client,
     )
-    .await
+    .await?;
+    if let Some(job) = file_log_job {
+        create_file_ai_log_and_link(&job, project_id, client).await?;
+    }
+    Ok(symbols_count)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn process_file_hierarchical_with_id(
     file_path: &Path,
     file_id: &str,
@@ -911,34 +1281,56 @@ async fn process_file_hierarchical_with_id(
     root_path: &Path,
     file_index: &HashMap<String, String>,
     index_ai_enabled: bool,
+    output_dir: Option<&Path>,
     client: &AmpClient,
-) -> Result<usize> {
+) -> Result<(usize, Option<FileLogJob>, FileCalls)> {
     // Parse and create symbols with relationships
-    let (symbol_count, dependency_paths, symbol_names) = match use_codebase_parser_hierarchical(file_path, file_id, project_id, client).await {
-        Ok((count, deps, names)) => {
+    let (symbol_count, dependency_paths, symbol_names, markdown, symbol_chunks, file_calls) = match use_codebase_parser_hierarchical(file_path, file_id, project_id, client).await {
+        Ok((count, deps, names, markdown, chunks, calls)) => {
             index_log!("Codebase parser created {} symbols", count);
-            (count, deps, names)
+            (count, deps, names, markdown, chunks, calls)
         }
         Err(e) => {
             index_log!("Codebase parser failed: {}", e);
-            (0, Vec::new(), Vec::new())
+            (0, Vec::new(), Vec::new(), None, Vec::new(), FileCalls::default())
         }
     };
 
+    if let (Some(output_dir), Some(markdown)) = (output_dir, markdown.as_deref()) {
+        let written = write_file_log_markdown(output_dir, root_path, file_path, markdown)?;
+        index_log!("Wrote file log: {}", written.display());
+    }
+
     // Create FileChunks and FileLog in batch (for embeddings)
     let mut batch = Vec::new();
-    let chunks = create_file_chunks_objects(file_path, file_id, project_id)?;
+    let chunks = match create_file_chunks_objects(file_path, file_id, project_id, &symbol_chunks) {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            let e = vanished_or(file_path, e);
+            if e.is::<FileVanished>() {
+                // Don't leave an empty file node behind for a file that no longer exists
+                let _ = client.delete_object(file_id).await;
+            }
+            return Err(e);
+        }
+    };
     if chunks.len() > 1 {
         index_log!("Created {} chunks", chunks.len());
     }
     batch.extend(chunks);
-    
-    let file_log = if index_ai_enabled {
-        create_file_log_object_ai(file_path, file_id, project_id, &symbol_names, &dependency_paths, client).await?
+
+    // AI logs are generated afterwards in the shared worker pool
+    let file_log_job = if index_ai_enabled {
+        Some(FileLogJob {
+            file_path: file_path.to_path_buf(),
+            file_id: file_id.to_string(),
+            symbol_names,
+            dependency_paths: dependency_paths.clone(),
+        })
     } else {
-        create_file_log_object(file_path, file_id, project_id, &[])?
+        batch.push(create_file_log_object(file_path, file_id, project_id, &[])?);
+        None
     };
-    batch.push(file_log);
     
     let mut file_artifact_ids: Vec<String> = Vec::new();
     for obj in &batch {
@@ -983,14 +1375,153 @@ async fn process_file_hierarchical_with_id(
         }
     }
 
-    Ok(symbol_count + 1)
+    Ok((symbol_count + 1, file_log_job, file_calls))
+}
+
+/// Calls parsed from one file. Edges to callees defined in the same file are
+/// created while parsing; the rest wait for the project-wide symbol index.
+#[derive(Debug, Default)]
+struct FileCalls {
+    /// Symbol name -> id for the symbols created from the file
+    symbols: HashMap<String, String>,
+    /// (caller id, callee name) for callees not defined in the file
+    unresolved: Vec<(String, String)>,
+}
+
+type CallPairs = Vec<(String, String)>;
+
+/// Split parsed `(caller, callee)` names into `(caller id, callee id)` edges within
+/// the file and `(caller id, callee name)` pairs left for the project pass.
+/// Calls from callers that did not become symbols are dropped.
+fn resolve_file_calls(calls: &[(String, String)], symbols: &HashMap<String, String>) -> (CallPairs, CallPairs) {
+    let mut edges = Vec::new();
+    let mut unresolved = Vec::new();
+    for (caller, callee) in calls {
+        let Some(caller_id) = symbols.get(caller) else {
+            continue;
+        };
+        match symbols.get(callee) {
+            Some(callee_id) if callee_id != caller_id => edges.push((caller_id.clone(), callee_id.clone())),
+            Some(_) => {}
+            None => unresolved.push((caller_id.clone(), callee.clone())),
+        }
+    }
+    (edges, unresolved)
+}
+
+/// Edges for calls into other files. Names defined more than once in the
+/// project are ambiguous and left unlinked.
+fn resolve_project_calls(pending: &[(String, String)], project_symbols: &HashMap<String, Vec<String>>) -> Vec<(String, String)> {
+    pending
+        .iter()
+        .filter_map(|(caller_id, callee)| match project_symbols.get(callee).map(Vec::as_slice) {
+            Some([callee_id]) if callee_id != caller_id => Some((caller_id.clone(), callee_id.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// AI file log deferred from the parse pass so provider calls are bounded by
+/// the worker pool rather than serialized behind each file's parse.
+struct FileLogJob {
+    file_path: PathBuf,
+    file_id: String,
+    symbol_names: Vec<String>,
+    dependency_paths: Vec<String>,
+}
+
+/// Generate, store and link AI file logs with at most `worker_count`
+/// in flight. Returns the failures so the caller can report them.
+async fn generate_file_ai_logs(
+    jobs: Vec<FileLogJob>,
+    project_id: &str,
+    worker_count: usize,
+    cancel_flag: &AtomicBool,
+    client: &AmpClient,
+) -> Result<Vec<String>> {
+    let semaphore = Arc::new(Semaphore::new(worker_count));
+    let mut join_set = JoinSet::new();
+    for job in jobs {
+        check_cancel(cancel_flag)?;
+        let permit = semaphore.clone().acquire_owned().await?;
+        let client = client.clone();
+        let project_id = project_id.to_string();
+        join_set.spawn(async move {
+            let _permit = permit;
+            create_file_ai_log_and_link(&job, &project_id, &client)
+                .await
+                .map_err(|e| format!("{}: {}", job.file_path.display(), e))
+        });
+    }
+
+    let mut failures = Vec::new();
+    while let Some(result) = join_set.join_next().await {
+        if cancel_flag.load(Ordering::Relaxed) {
+            join_set.abort_all();
+            anyhow::bail!("Indexing cancelled by user.");
+        }
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => failures.push(e),
+            Err(e) => failures.push(format!("Failed to join file log task: {}", e)),
+        }
+    }
+    Ok(failures)
+}
+
+async fn create_file_ai_log_and_link(job: &FileLogJob, project_id: &str, client: &AmpClient) -> Result<()> {
+    let file_log = create_file_log_object_ai(
+        &job.file_path,
+        &job.file_id,
+        project_id,
+        &job.symbol_names,
+        &job.dependency_paths,
+        client,
+    )
+    .await?;
+    let log_id = file_log.get("id").and_then(|v| v.as_str()).map(|v| v.to_string());
+    if let Err(e) = client.batch_create_objects(vec![file_log]).await {
+        anyhow::bail!("Failed to create file log: {}", e);
+    }
+    if let Some(log_id) = log_id {
+        let _ = client.create_relationship_direct(&job.file_id, &log_id, "defined_in").await;
+        let _ = client.create_relationship_direct(&log_id, &job.file_id, "defined_in").await;
+    }
+    Ok(())
+}
+
+/// Mirror `file_path` under `output_dir` relative to `root_path`, e.g.
+/// `src/main.rs` -> `<output_dir>/src/main.rs.md`.
+fn file_log_output_path(output_dir: &Path, root_path: &Path, file_path: &Path) -> PathBuf {
+    let relative = file_path
+        .strip_prefix(root_path)
+        .ok()
+        .or_else(|| file_path.file_name().map(Path::new))
+        .unwrap_or(file_path);
+    let mut target = output_dir.join(relative).into_os_string();
+    target.push(".md");
+    PathBuf::from(target)
+}
+
+fn write_file_log_markdown(output_dir: &Path, root_path: &Path, file_path: &Path, markdown: &str) -> Result<PathBuf> {
+    let target = file_log_output_path(output_dir, root_path, file_path);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&target, markdown)?;
+    Ok(target)
 }
 
+/// Lookup key for a local path; case is only folded where the filesystem ignores it,
+/// matching the server's path resolver.
 fn path_key(path: &Path) -> Option<String> {
     let canonical = path.canonicalize().ok().unwrap_or_else(|| path.to_path_buf());
-    let mut key = canonical.to_string_lossy().to_string();
-    key = key.replace('/', "\\").to_lowercase();
-    Some(key)
+    let key = canonical.to_string_lossy().replace('/', "\\");
+    if cfg!(any(windows, target_os = "macos")) {
+        Some(key.to_lowercase())
+    } else {
+        Some(key)
+    }
 }
 
 fn resolve_dependency_id(
@@ -1010,6 +1541,13 @@ fn resolve_dependency_path(dep: &str, file_path: &Path, root_path: &Path) -> Opt
         return dep_path.canonicalize().ok().or_else(|| Some(dep_path.to_path_buf()));
     }
 
+    // Dotted module paths like `.utils` or `pkg.module` are not path-like
+    if file_path.extension().is_some_and(|ext| ext == "py" || ext == "pyi") {
+        if let Some(resolved) = resolve_python_module(dep, file_path, root_path) {
+            return Some(resolved);
+        }
+    }
+
     let extensions = ["py", "ts", "tsx", "js", "jsx", "rs", "json", "toml", "yaml", "yml"];
     let looks_like_path = dep.contains('\\') || dep.contains('/') || dep_path.extension().is_some();
     if !looks_like_path {
@@ -1038,6 +1576,71 @@ fn resolve_dependency_path(dep: &str, file_path: &Path, root_path: &Path) -> Opt
     None
 }
 
+/// Resolve a Python module path to its file. Relative imports (`.utils`, `..pkg.mod`)
+/// start from the importing file's package, one level up per extra dot; absolute
+/// ones are tried from the file's directory and then the project root.
+fn resolve_python_module(dep: &str, file_path: &Path, root_path: &Path) -> Option<PathBuf> {
+    let module = dep.trim_start_matches('.');
+    let dots = dep.len() - module.len();
+    let parts: Vec<&str> = if module.is_empty() { Vec::new() } else { module.split('.').collect() };
+    let valid = parts.iter().all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_'));
+    if !valid || (dots == 0 && parts.is_empty()) {
+        return None;
+    }
+
+    let bases = if dots > 0 {
+        let mut base = file_path.parent()?;
+        for _ in 1..dots {
+            base = base.parent()?;
+        }
+        vec![base.to_path_buf()]
+    } else {
+        vec![file_path.parent()?.to_path_buf(), root_path.to_path_buf()]
+    };
+
+    for base in bases {
+        let package = parts.iter().fold(base, |path, part| path.join(part));
+        let mut candidates = vec![package.join("__init__.py")];
+        if let Some((last, _)) = parts.split_last() {
+            candidates.insert(0, package.with_file_name(format!("{}.py", last)));
+        }
+        for candidate in candidates {
+            if candidate.is_file() {
+                return candidate.canonicalize().ok().or(Some(candidate));
+            }
+        }
+    }
+
+    None
+}
+
+/// A file that was deleted between the walk and being processed. Active
+/// repos do this routinely, so it is reported as a skip rather than an error.
+#[derive(Debug)]
+struct FileVanished(PathBuf);
+
+impl std::fmt::Display for FileVanished {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Deleted during indexing: {}", self.0.display())
+    }
+}
+
+impl std::error::Error for FileVanished {}
+
+/// Turn a not-found IO failure for a file that is gone from disk into `FileVanished`.
+fn vanished_or(file_path: &Path, err: anyhow::Error) -> anyhow::Error {
+    let not_found = err.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|io| io.kind() == std::io::ErrorKind::NotFound)
+    });
+    if not_found && !file_path.exists() {
+        FileVanished(file_path.to_path_buf()).into()
+    } else {
+        err
+    }
+}
+
 async fn create_file_node(
     file_path: &Path,
     project_object_id: &str,
@@ -1046,7 +1649,8 @@ async fn create_file_node(
     client: &AmpClient,
 ) -> Result<String> {
     let file_id = Uuid::new_v4().to_string();
-    let file_symbol = create_file_node_object(file_path, &file_id, project_id)?;
+    let file_symbol = create_file_node_object(file_path, &file_id, project_id)
+        .map_err(|e| vanished_or(file_path, e))?;
     let file_name = file_path.file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("unknown");
@@ -1103,7 +1707,7 @@ fn create_amp_symbol_from_parsed_hierarchical(symbol_data: &serde_json::Value, f
         _ => "unknown",
     };
     
-    let symbol = serde_json::json!({
+    let mut symbol = serde_json::json!({
         "id": uuid::Uuid::new_v4().to_string(),
         "type": "symbol",
         "tenant_id": "default",
@@ -1124,6 +1728,10 @@ fn create_amp_symbol_from_parsed_hierarchical(symbol_data: &serde_json::Value, f
         "signature": signature,
         "documentation": format!("{} {} at line {} in {}", kind, name, line_start + 1, file_path.display())
     });
+
+    if let Some(visibility) = symbol_data.get("visibility").and_then(|v| v.as_str()) {
+        symbol["visibility"] = json!(visibility);
+    }
     
     Ok(symbol)
 }
@@ -1208,19 +1816,568 @@ mod tests {
         assert!(should_exclude(&PathBuf::from("app.log"), &exclude_patterns));
         assert!(!should_exclude(&PathBuf::from("src/main.rs"), &exclude_patterns));
     }
+
+    #[test]
+    fn test_should_exclude_nested_globs() {
+        let exclude_patterns = vec![
+            "src/**/generated/*".to_string(),
+            "*.egg-info".to_string(),
+            "[Bb]uild".to_string(),
+        ];
+
+        assert!(should_exclude(&PathBuf::from("src/api/v1/generated/types.rs"), &exclude_patterns));
+        assert!(should_exclude(&PathBuf::from("src/generated/types.rs"), &exclude_patterns));
+        assert!(!should_exclude(&PathBuf::from("tests/generated/types.rs"), &exclude_patterns));
+        assert!(!should_exclude(&PathBuf::from("src/api/generated.rs"), &exclude_patterns));
+        assert!(should_exclude(&PathBuf::from("pkg/amp.egg-info/PKG-INFO"), &exclude_patterns));
+        assert!(should_exclude(&PathBuf::from("Build/out.txt"), &exclude_patterns));
+        assert!(should_exclude(&PathBuf::from("web/build"), &exclude_patterns));
+    }
+
+    #[test]
+    fn test_should_exclude_negation_overrides_earlier_patterns() {
+        let exclude_patterns = vec![
+            "*.log".to_string(),
+            "!important.log".to_string(),
+            "vendor".to_string(),
+            "!vendor/amp/**".to_string(),
+        ];
+
+        assert!(should_exclude(&PathBuf::from("logs/debug.log"), &exclude_patterns));
+        assert!(!should_exclude(&PathBuf::from("logs/important.log"), &exclude_patterns));
+        assert!(should_exclude(&PathBuf::from("vendor/serde/lib.rs"), &exclude_patterns));
+        assert!(!should_exclude(&PathBuf::from("vendor/amp/lib.rs"), &exclude_patterns));
+
+        // Order matters: a later exclude wins over an earlier re-include
+        let reordered = vec!["!important.log".to_string(), "*.log".to_string()];
+        assert!(should_exclude(&PathBuf::from("important.log"), &reordered));
+    }
     
+    #[test]
+    fn test_resolve_python_imports_relative_and_dotted() {
+        let root = tempfile::tempdir().unwrap();
+        for file in ["app.py", "pkg/__init__.py", "pkg/utils.py", "pkg/sub/__init__.py", "pkg/sub/mod.py", "pkg/sub/deep.py"] {
+            let path = root.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, "").unwrap();
+        }
+        let file = |rel: &str| root.path().join(rel).canonicalize().unwrap();
+        let resolve = |dep: &str, from: &str| resolve_dependency_path(dep, &root.path().join(from), root.path());
+
+        // Relative: one dot is the importing package, each extra dot climbs a level
+        assert_eq!(resolve(".deep", "pkg/sub/mod.py"), Some(file("pkg/sub/deep.py")));
+        assert_eq!(resolve(".", "pkg/sub/mod.py"), Some(file("pkg/sub/__init__.py")));
+        assert_eq!(resolve("..utils", "pkg/sub/mod.py"), Some(file("pkg/utils.py")));
+        assert_eq!(resolve("..", "pkg/sub/mod.py"), Some(file("pkg/__init__.py")));
+        assert_eq!(resolve(".missing", "pkg/sub/mod.py"), None);
+
+        // Absolute dotted: modules and packages from the file's directory or the root
+        assert_eq!(resolve("pkg.sub.deep", "app.py"), Some(file("pkg/sub/deep.py")));
+        assert_eq!(resolve("pkg.sub", "app.py"), Some(file("pkg/sub/__init__.py")));
+        assert_eq!(resolve("pkg.utils", "pkg/sub/mod.py"), Some(file("pkg/utils.py")));
+        assert_eq!(resolve("sub.deep", "pkg/utils.py"), Some(file("pkg/sub/deep.py")));
+        assert_eq!(resolve("os", "app.py"), None);
+    }
+
     #[test]
     fn test_create_file_symbol() {
         let path = PathBuf::from("src/main.py");
         let content = "def hello():\n    print('Hello, world!')";
         
-        let symbol = create_file_symbol(&path, content).unwrap();
+        let symbol = create_file_symbol(&path, content, "test-project").unwrap();
         
-        assert_eq!(symbol["type"], "Symbol");
+        assert_eq!(symbol["type"], "symbol");
         assert_eq!(symbol["name"], "main.py");
         assert_eq!(symbol["language"], "python");
         assert_eq!(symbol["kind"], "file");
     }
+
+    #[test]
+    fn test_parsed_symbol_keeps_visibility() {
+        let path = PathBuf::from("src/lib.rs");
+        let parsed = json!({ "name": "run", "symbol_type": "function", "start_line": 3, "visibility": "public" });
+
+        let symbol = create_amp_symbol_from_parsed_hierarchical(&parsed, &path, "file-1", "test-project").unwrap();
+        assert_eq!(symbol["visibility"], "public");
+
+        let parsed = json!({ "name": "main", "symbol_type": "function", "start_line": 0 });
+        let symbol = create_amp_symbol_from_parsed_hierarchical(&parsed, &path, "file-1", "test-project").unwrap();
+        assert!(symbol.get("visibility").is_none());
+    }
+
+    #[test]
+    fn test_file_chunks_use_server_symbol_chunks() {
+        let path = PathBuf::from("src/lib.rs");
+        let symbol_chunks = vec![
+            json!({ "content": "const A: u8 = 1;", "start_line": 1, "end_line": 1, "symbols": ["A"], "symbol_name": "A", "symbol_kind": "constant" }),
+            json!({ "content": "fn parse_file() {}", "start_line": 3, "end_line": 3, "symbols": ["parse_file"], "symbol_name": "parse_file", "symbol_kind": "function" }),
+        ];
+
+        let chunks = create_file_chunks_objects(&path, "file-1", "test-project", &symbol_chunks).unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1]["chunk_index"], 1);
+        assert_eq!(chunks[1]["start_line"], 3);
+        assert_eq!(chunks[1]["symbol_name"], "parse_file");
+        assert_eq!(chunks[1]["symbol_kind"], "function");
+        assert_eq!(chunks[1]["provenance"]["method"], "symbol-chunking");
+    }
+
+    #[test]
+    fn test_file_chunk_hashes_are_pinned() {
+        // Stored hashes decide what gets re-embedded, so a change to window slicing must show up here
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        let content: String = (0..6)
+            .map(|line| {
+                let words: Vec<String> = (0..100).map(|w| format!("w{}_{}", line, w)).collect();
+                format!("    {}\n\n", words.join(" "))
+            })
+            .collect();
+        std::fs::write(&path, &content).unwrap();
+
+        let chunks = create_file_chunks_objects(&path, "file-1", "test-project", &[]).unwrap();
+        let hashes: Vec<&str> = chunks.iter().map(|chunk| chunk["content_hash"].as_str().unwrap()).collect();
+        assert_eq!(hashes, vec!["778093fdf4326afabf910b32f73bb6fd", "2612742db828c332700ff86d8e7b9cfa"]);
+    }
+
+    #[test]
+    fn test_file_chunks_without_symbols_report_exact_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        // 100 words a line, then a blank line, so a word ratio misplaces later windows
+        let content: String = (0..12)
+            .map(|line| {
+                let words: Vec<String> = (0..100).map(|w| format!("w{}_{}", line, w)).collect();
+                format!("    {}\n\n", words.join(" "))
+            })
+            .collect();
+        std::fs::write(&path, &content).unwrap();
+
+        let chunks = create_file_chunks_objects(&path, "file-1", "test-project", &[]).unwrap();
+        assert_eq!(chunks.len(), 3);
+        // Windows start at words 0, 400 and 800: lines 1, 9 and 17
+        let lines: Vec<(u64, u64)> = chunks
+            .iter()
+            .map(|c| (c["start_line"].as_u64().unwrap(), c["end_line"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(lines, vec![(1, 9), (9, 17), (17, 23)]);
+        assert!(chunks[1]["content"].as_str().unwrap().starts_with("w4_0 w4_1"));
+    }
+
+    #[test]
+    fn test_file_chunks_without_symbols_match_the_server_windows() {
+        // The server pins the same windows for the same input in
+        // `services::chunking::tests`; both must change together
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        let content = (0..1200).map(|i| format!("w{}", i)).collect::<Vec<_>>().join(" ");
+        std::fs::write(&path, &content).unwrap();
+
+        let chunks = create_file_chunks_objects(&path, "file-1", "test-project", &[]).unwrap();
+        let bounds: Vec<(&str, &str)> = chunks
+            .iter()
+            .map(|chunk| {
+                let mut words = chunk["content"].as_str().unwrap().split_whitespace();
+                (words.next().unwrap(), words.last().unwrap())
+            })
+            .collect();
+        assert_eq!(bounds, vec![("w0", "w499"), ("w400", "w899"), ("w800", "w1199")]);
+    }
+
+    #[test]
+    fn test_file_list_names_only_the_listed_files() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        for name in ["src/changed.rs", "src/untouched.rs", "README.md"] {
+            std::fs::write(root.join(name), "fn main() {}\n").unwrap();
+        }
+
+        // As piped from `git diff --name-only`
+        let piped = format!("src/changed.rs\n\n  README.md  \n{}\nsrc/changed.rs\n", root.join("src/deleted.rs").display());
+        let files = parse_file_list(std::io::Cursor::new(piped), &root).unwrap();
+        assert_eq!(
+            files,
+            vec![root.join("src/changed.rs"), root.join("README.md"), root.join("src/deleted.rs")]
+        );
+        assert!(!files.contains(&root.join("src/untouched.rs")));
+    }
+
+    #[test]
+    fn test_exclude_rules_without_amp_ignore_uses_aggressive_defaults() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        std::fs::write(root.join(".gitignore"), "secret.txt\n").unwrap();
+        let rules = ExcludeRules::new(root, &["docs".to_string()], true);
+
+        assert!(!rules.has_amp_ignore());
+        assert_eq!(rules.classify(&root.join("lib"), true), Some(ExcludeRule::Default));
+        assert_eq!(rules.classify(&root.join("node_modules"), true), Some(ExcludeRule::Default));
+        assert_eq!(rules.classify(&root.join("docs"), true), Some(ExcludeRule::CliFlag));
+        assert_eq!(rules.classify(&root.join("secret.txt"), false), Some(ExcludeRule::GitIgnore));
+        assert_eq!(rules.classify(&root.join("src").join("main.rs"), false), None);
+    }
+
+    #[test]
+    fn test_exclude_rules_with_amp_ignore_keeps_lib() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        std::fs::write(root.join(AMP_IGNORE_FILE), "generated/\n*.snap\n").unwrap();
+        std::fs::create_dir_all(root.join("src").join("nested")).unwrap();
+        std::fs::write(root.join("src").join(".gitignore"), "fixtures/\n").unwrap();
+        let rules = ExcludeRules::new(root, &[], true);
+
+        assert!(rules.has_amp_ignore());
+        assert_eq!(rules.classify(&root.join("lib"), true), None);
+        assert_eq!(rules.classify(&root.join("lib").join("core.py"), false), None);
+        assert_eq!(rules.classify(&root.join("generated"), true), Some(ExcludeRule::AmpIgnore));
+        assert_eq!(rules.classify(&root.join("src").join("a.snap"), false), Some(ExcludeRule::AmpIgnore));
+        assert_eq!(rules.classify(&root.join("target"), true), Some(ExcludeRule::Default));
+        assert_eq!(
+            rules.classify(&root.join("src").join("nested").join("fixtures"), true),
+            Some(ExcludeRule::GitIgnore)
+        );
+        assert_eq!(rules.classify(&root.join("fixtures"), true), None);
+    }
+
+    #[test]
+    fn test_exclude_rules_ignore_gitignore_when_disabled() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        std::fs::write(root.join(".gitignore"), "secret.txt\n").unwrap();
+        let rules = ExcludeRules::new(root, &[], false);
+
+        assert_eq!(rules.classify(&root.join("secret.txt"), false), None);
+    }
+
+    #[test]
+    fn test_exclude_rules_cli_negation_overrides_defaults() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        let rules = ExcludeRules::new(root, &["!build".to_string(), "docs/**/*.md".to_string()], true);
+
+        assert_eq!(rules.classify(&root.join("build"), true), None);
+        assert_eq!(rules.classify(&root.join("dist"), true), Some(ExcludeRule::Default));
+        assert_eq!(
+            rules.classify(&root.join("docs").join("api").join("index.md"), false),
+            Some(ExcludeRule::CliFlag)
+        );
+    }
+
+    #[test]
+    fn test_file_log_output_path_mirrors_relative_path() {
+        let target = file_log_output_path(
+            Path::new("/tmp/logs"),
+            Path::new("/repo"),
+            Path::new("/repo/src/lib.rs"),
+        );
+        assert_eq!(target, PathBuf::from("/tmp/logs/src/lib.rs.md"));
+    }
+
+    #[tokio::test]
+    async fn test_index_with_output_dir_writes_file_log_markdown() {
+        use axum::{routing::post, Json, Router};
+
+        async fn parse_file() -> Json<Value> {
+            Json(json!({
+                "file_log": { "symbols": [], "dependencies": [] },
+                "markdown": "# FILE_LOG: src/lib.rs\n"
+            }))
+        }
+
+        let app = Router::new()
+            .route("/v1/codebase/parse-file", post(parse_file))
+            .fallback(|| async { Json(json!({})) });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let repo = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let file_path = repo.path().join("src").join("lib.rs");
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        std::fs::write(&file_path, "pub fn hello() {}\n").unwrap();
+
+        process_file_hierarchical_with_id(
+            &file_path,
+            "file-1",
+            "test-project",
+            repo.path(),
+            &HashMap::new(),
+            false,
+            Some(out.path()),
+            &client,
+        )
+        .await
+        .unwrap();
+
+        let written = std::fs::read_to_string(out.path().join("src").join("lib.rs.md")).unwrap();
+        assert_eq!(written, "# FILE_LOG: src/lib.rs\n");
+    }
+
+    #[tokio::test]
+    async fn test_parsed_calls_become_calls_edges() {
+        use axum::{extract::State, routing::post, Json, Router};
+
+        type Edges = Arc<Mutex<Vec<Value>>>;
+
+        async fn parse_file() -> Json<Value> {
+            Json(json!({
+                "file_log": {
+                    "symbols": [
+                        { "name": "load", "symbol_type": "function", "start_line": 0 },
+                        { "name": "main", "symbol_type": "function", "start_line": 3 }
+                    ],
+                    "dependencies": [],
+                    "calls": [["main", "load"], ["main", "helper"], ["module_level", "load"]]
+                }
+            }))
+        }
+
+        async fn relationship(State(edges): State<Edges>, Json(body): Json<Value>) -> Json<Value> {
+            edges.lock().unwrap().push(body);
+            Json(json!({}))
+        }
+
+        let edges: Edges = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route("/v1/codebase/parse-file", post(parse_file))
+            .route("/v1/relationships", post(relationship))
+            .fallback(|| async { Json(json!({})) })
+            .with_state(edges.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let repo = tempfile::tempdir().unwrap();
+        let file_path = repo.path().join("main.py");
+        std::fs::write(&file_path, "def load(): pass\n\ndef main():\n    load()\n    helper()\n").unwrap();
+
+        let (_, _, file_calls) = process_file_hierarchical_with_id(
+            &file_path,
+            "file-1",
+            "test-project",
+            repo.path(),
+            &HashMap::new(),
+            false,
+            None,
+            &client,
+        )
+        .await
+        .unwrap();
+
+        let load_id = file_calls.symbols["load"].clone();
+        let main_id = file_calls.symbols["main"].clone();
+        let calls: Vec<(String, String)> = edges
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|edge| edge["type"] == "calls")
+            .map(|edge| (edge["source_id"].as_str().unwrap().to_string(), edge["target_id"].as_str().unwrap().to_string()))
+            .collect();
+        assert_eq!(calls, vec![(main_id.clone(), load_id)]);
+        assert_eq!(file_calls.unresolved, vec![(main_id.clone(), "helper".to_string())]);
+
+        // Another file's `helper` links once the whole project is parsed, unless the name is ambiguous
+        let mut project_symbols = HashMap::from([("helper".to_string(), vec!["helper-1".to_string()])]);
+        assert_eq!(
+            resolve_project_calls(&file_calls.unresolved, &project_symbols),
+            vec![(main_id, "helper-1".to_string())]
+        );
+        project_symbols.get_mut("helper").unwrap().push("helper-2".to_string());
+        assert!(resolve_project_calls(&file_calls.unresolved, &project_symbols).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_file_deleted_mid_index_is_skipped_not_errored() {
+        use axum::{routing::post, Json, Router};
+
+        // The file disappears while the server is parsing it
+        async fn parse_file(Json(body): Json<Value>) -> Json<Value> {
+            let path = body["file_path"].as_str().unwrap();
+            std::fs::remove_file(path).unwrap();
+            Json(json!({ "file_log": { "symbols": [], "dependencies": [] } }))
+        }
+
+        let app = Router::new()
+            .route("/v1/codebase/parse-file", post(parse_file))
+            .fallback(|| async { Json(json!({})) });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let repo = tempfile::tempdir().unwrap();
+        let file_path = repo.path().join("gone.rs");
+        std::fs::write(&file_path, "fn gone() {}\n").unwrap();
+
+        let file_id = create_file_node(&file_path, "project-1", "test-project", None, &client)
+            .await
+            .unwrap();
+        let err = process_file_hierarchical_with_id(
+            &file_path,
+            &file_id,
+            "test-project",
+            repo.path(),
+            &HashMap::new(),
+            false,
+            None,
+            &client,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(err.is::<FileVanished>());
+        assert_eq!(err.to_string(), format!("Deleted during indexing: {}", file_path.display()));
+
+        // Gone before its node was created: skipped the same way
+        let err = create_file_node(&file_path, "project-1", "test-project", None, &client)
+            .await
+            .unwrap_err();
+        assert!(err.is::<FileVanished>());
+
+        // Other failures stay real errors
+        let err = vanished_or(repo.path(), anyhow::anyhow!("parse failed"));
+        assert!(!err.is::<FileVanished>());
+    }
+
+    #[tokio::test]
+    async fn test_file_ai_logs_run_concurrently_up_to_worker_limit() {
+        use axum::{extract::State, routing::post, Json, Router};
+        use std::sync::atomic::AtomicUsize;
+
+        #[derive(Clone, Default)]
+        struct InFlight {
+            current: Arc<AtomicUsize>,
+            peak: Arc<AtomicUsize>,
+        }
+
+        async fn ai_file_log(State(in_flight): State<InFlight>) -> Json<Value> {
+            let now = in_flight.current.fetch_add(1, Ordering::SeqCst) + 1;
+            in_flight.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            in_flight.current.fetch_sub(1, Ordering::SeqCst);
+            Json(json!({ "file_log": { "summary_markdown": "summary" } }))
+        }
+
+        let in_flight = InFlight::default();
+        let app = Router::new()
+            .route("/v1/codebase/ai-file-log", post(ai_file_log))
+            .fallback(|| async { Json(json!({})) })
+            .with_state(in_flight.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let jobs = (0..7)
+            .map(|i| FileLogJob {
+                file_path: PathBuf::from(format!("src/file_{}.rs", i)),
+                file_id: format!("file-{}", i),
+                symbol_names: Vec::new(),
+                dependency_paths: Vec::new(),
+            })
+            .collect();
+        let failures = generate_file_ai_logs(jobs, "test-project", 3, &AtomicBool::new(false), &client)
+            .await
+            .unwrap();
+
+        assert!(failures.is_empty());
+        assert_eq!(in_flight.peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_index_summary_counts_only_relationships_the_server_accepted() {
+        use axum::{extract::State, http::StatusCode, routing::{get, post}, Json, Router};
+        use std::sync::atomic::AtomicUsize;
+
+        #[derive(Clone, Default)]
+        struct Relationships {
+            requests: Arc<AtomicUsize>,
+            accepted: Arc<Mutex<HashMap<String, usize>>>,
+        }
+
+        // Every third request is rejected
+        async fn create_relationship(
+            State(relationships): State<Relationships>,
+            Json(body): Json<Value>,
+        ) -> (StatusCode, Json<Value>) {
+            if relationships.requests.fetch_add(1, Ordering::SeqCst) % 3 == 2 {
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({})));
+            }
+            let relation_type = body["type"].as_str().unwrap().to_string();
+            *relationships.accepted.lock().unwrap().entry(relation_type).or_default() += 1;
+            (StatusCode::CREATED, Json(json!({})))
+        }
+
+        async fn parse_file(Json(body): Json<Value>) -> Json<Value> {
+            let dependencies = if body["file_path"].as_str().unwrap().ends_with("lib.rs") {
+                json!(["util.rs"])
+            } else {
+                json!([])
+            };
+            Json(json!({
+                "file_log": {
+                    "symbols": [{ "name": "run", "symbol_type": "function", "start_line": 0 }],
+                    "dependencies": dependencies
+                }
+            }))
+        }
+
+        let relationships = Relationships::default();
+        let app = Router::new()
+            .route("/v1/relationships", post(create_relationship))
+            .route("/v1/codebase/parse-file", post(parse_file))
+            .route(
+                "/v1/settings",
+                get(|| async { Json(json!({ "indexProvider": "none", "indexWorkers": 2 })) }),
+            )
+            .fallback(|| async { Json(json!({})) })
+            .with_state(relationships.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(repo.path().join("src")).unwrap();
+        std::fs::write(repo.path().join("src").join("lib.rs"), "mod util;\npub fn run() {}\n").unwrap();
+        std::fs::write(repo.path().join("src").join("util.rs"), "pub fn run() {}\n").unwrap();
+
+        let summary = run_index(
+            repo.path().to_str().unwrap(),
+            &[],
+            false,
+            None,
+            None,
+            OutputOptions::default(),
+            &client,
+        )
+        .await
+        .unwrap();
+
+        let accepted = relationships.accepted.lock().unwrap().clone();
+        let accepted_of = |relation_type: &str| accepted.get(relation_type).copied().unwrap_or(0);
+        let requests = relationships.requests.load(Ordering::SeqCst);
+        assert_eq!(summary.files_processed, 2);
+        assert!(requests >= 3, "expected several relationship requests, got {}", requests);
+        assert_eq!(summary.relationships.defined_in, accepted_of("defined_in"));
+        assert_eq!(summary.relationships.depends_on, accepted_of("depends_on"));
+        assert_eq!(summary.relationships.total(), requests - requests / 3);
+        assert_eq!(client.relationships_created(), summary.relationships);
+    }
 }
 
 
@@ -1278,7 +2435,7 @@ fn create_chunk_object(file_path: &Path, file_id: &str, project_id: &str, conten
     let token_count = content.split_whitespace().count() as u32;
 
     serde_json::json!({
-        "id": uuid::Uuid::new_v4().to_string(),
+        "id": chunk_id(file_id, chunk_index, &content_hash),
         "type": "FileChunk",
         "tenant_id": "default",
         "project_id": project_id,
@@ -1298,6 +2455,21 @@ fn create_chunk_object(file_path: &Path, file_id: &str, project_id: &str, conten
     })
 }
 
+/// Stable chunk id so re-chunking unchanged content keeps the same ids.
+fn chunk_id(file_id: &str, chunk_index: u32, content_hash: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}:{}", file_id, chunk_index, content_hash).as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    Uuid::from_bytes(bytes).to_string()
+}
+
+/// Hex SHA256 of a file's bytes, matching the `content_hash` the server stores
+/// on FileLogs it writes itself. `None` for directories and unreadable files.
+pub(crate) fn file_content_hash(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(&bytes)))
+}
+
 #[allow(dead_code)]
 async fn create_file_log(file_path: &Path, file_id: &str, project_id: &str, symbols: &[serde_json::Value], client: &AmpClient) -> Result<()> {
     let language = match file_path.extension().and_then(|e| e.to_str()) {
@@ -1344,7 +2516,8 @@ async fn create_file_log(file_path: &Path, file_id: &str, project_id: &str, symb
         "dependencies": [],
         "last_modified": now.to_rfc3339(),
         "change_count": 0,
-        "linked_changesets": []
+        "linked_changesets": [],
+        "content_hash": file_content_hash(file_path)
     });
 
     client.create_object(file_log).await?;
@@ -1364,9 +2537,7 @@ fn create_file_node_object(file_path: &Path, file_id: &str, project_id: &str) ->
         _ => "unknown",
     };
 
-    let file_size = std::fs::metadata(file_path)
-        .map(|meta| meta.len())
-        .unwrap_or(0);
+    let file_size = std::fs::metadata(file_path)?.len();
     let line_count = std::fs::read_to_string(file_path)
         .map(|content| content.lines().count() as u64)
         .unwrap_or(0);
@@ -1396,7 +2567,11 @@ fn create_file_node_object(file_path: &Path, file_id: &str, project_id: &str) ->
     }))
 }
 
-async fn use_codebase_parser_hierarchical(file_path: &Path, file_id: &str, project_id: &str, client: &AmpClient) -> Result<(usize, Vec<String>, Vec<String>)> {
+/// Symbols created, dependency paths, symbol names, FileLog markdown and the
+/// server's symbol-boundary chunks.
+type ParsedFileResult = (usize, Vec<String>, Vec<String>, Option<String>, Vec<Value>, FileCalls);
+
+async fn use_codebase_parser_hierarchical(file_path: &Path, file_id: &str, project_id: &str, client: &AmpClient) -> Result<ParsedFileResult> {
     let absolute_path = file_path.canonicalize()?;
     
     let parse_request = serde_json::json!({
@@ -1409,6 +2584,16 @@ async fn use_codebase_parser_hierarchical(file_path: &Path, file_id: &str, proje
     
     let mut dependencies: Vec<String> = Vec::new();
     let mut symbol_names: Vec<String> = Vec::new();
+    let mut file_calls = FileCalls::default();
+    let markdown = response
+        .get("markdown")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let chunks = response
+        .get("chunks")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
 
     if let Some(file_log) = response.get("file_log") {
         if let Some(symbols) = file_log.get("symbols") {
@@ -1420,6 +2605,9 @@ async fn use_codebase_parser_hierarchical(file_path: &Path, file_id: &str, proje
                             Ok(_) => {
                                 created_count += 1;
                                 if let Some(symbol_id) = amp_symbol.get("id").and_then(|v| v.as_str()) {
+                                    if let Some(name) = symbol_data.get("name").and_then(|v| v.as_str()) {
+                                        file_calls.symbols.entry(name.to_string()).or_insert_with(|| symbol_id.to_string());
+                                    }
                                     match client.create_relationship_direct(file_id, symbol_id, "defined_in").await {
                                         Ok(_) => {},
                                         Err(e) => index_log!("  Failed to create relationship: {}", e),
@@ -1447,16 +2635,30 @@ async fn use_codebase_parser_hierarchical(file_path: &Path, file_id: &str, proje
                     }
                 }
 
-                return Ok((created_count, dependencies, symbol_names));
+                let calls: Vec<(String, String)> = file_log
+                    .get("calls")
+                    .cloned()
+                    .and_then(|calls| serde_json::from_value(calls).ok())
+                    .unwrap_or_default();
+                let (edges, unresolved) = resolve_file_calls(&calls, &file_calls.symbols);
+                for (caller_id, callee_id) in edges {
+                    if let Err(e) = client.create_relationship_direct(&caller_id, &callee_id, "calls").await {
+                        index_log!("  Failed to create call relationship: {}", e);
+                    }
+                }
+                file_calls.unresolved = unresolved;
+
+                return Ok((created_count, dependencies, symbol_names, markdown, chunks, file_calls));
             }
         }
     }
     
-    Ok((0, dependencies, symbol_names))
+    Ok((0, dependencies, symbol_names, markdown, chunks, file_calls))
 }
 
-fn create_file_chunks_objects(file_path: &Path, file_id: &str, project_id: &str) -> Result<Vec<Value>> {
-    let content = std::fs::read_to_string(file_path)?;
+/// FileChunks for a file: the server's symbol-boundary chunks when it parsed
+/// any, otherwise the same word windows the server's `chunk_file` cuts.
+fn create_file_chunks_objects(file_path: &Path, file_id: &str, project_id: &str, symbol_chunks: &[Value]) -> Result<Vec<Value>> {
     let language = match file_path.extension().and_then(|e| e.to_str()) {
         Some("py") => "python",
         Some("ts") | Some("tsx") => "typescript",
@@ -1465,34 +2667,41 @@ fn create_file_chunks_objects(file_path: &Path, file_id: &str, project_id: &str)
         _ => "text",
     };
 
-    let words: Vec<&str> = content.split_whitespace().collect();
-    let chunk_size = 500;
-    let overlap = 50;
-    
-    if words.len() <= chunk_size {
-        let chunk = create_chunk_object(file_path, file_id, project_id, &content, 0, 1, content.lines().count() as u32, language);
-        return Ok(vec![chunk]);
+    if !symbol_chunks.is_empty() {
+        let chunks = symbol_chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, data)| {
+                let content = data.get("content")?.as_str()?;
+                let line = |key: &str| data.get(key).and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+                let mut chunk = create_chunk_object(file_path, file_id, project_id, content, idx as u32, line("start_line"), line("end_line"), language);
+                chunk["provenance"]["method"] = json!("symbol-chunking");
+                for key in ["symbols", "symbol_name", "symbol_kind"] {
+                    chunk[key] = data.get(key).cloned().unwrap_or(Value::Null);
+                }
+                Some(chunk)
+            })
+            .collect();
+        return Ok(chunks);
     }
 
-    let mut chunks = Vec::new();
-    let mut start_idx = 0;
-    let mut chunk_idx = 0;
-
-    while start_idx < words.len() {
-        let end_idx = (start_idx + chunk_size).min(words.len());
-        let chunk_words = &words[start_idx..end_idx];
-        let chunk_content = chunk_words.join(" ");
-        
-        let lines = content.lines().count();
-        let start_line = ((start_idx as f32 / words.len() as f32) * lines as f32) as u32 + 1;
-        let end_line = ((end_idx as f32 / words.len() as f32) * lines as f32) as u32 + 1;
-
-        let chunk = create_chunk_object(file_path, file_id, project_id, &chunk_content, chunk_idx, start_line, end_line, language);
-        chunks.push(chunk);
+    // Only runs when the server returned no chunks, e.g. because it cannot read the file
+    let content = std::fs::read_to_string(file_path)?;
+    let Some(windows) = word_windows::word_windows(&content, word_windows::CHUNK_SIZE, word_windows::OVERLAP_SIZE) else {
+        let chunk = create_chunk_object(file_path, file_id, project_id, &content, 0, 1, content.lines().count() as u32, language);
+        return Ok(vec![chunk]);
+    };
 
-        chunk_idx += 1;
-        start_idx = if end_idx < words.len() { end_idx - overlap } else { break };
-    }
+    let line_at = |idx: usize| word_windows::line_at(&content, idx);
+    let chunks = windows
+        .iter()
+        .enumerate()
+        .map(|(idx, window)| {
+            // Slice the file so the chunk keeps its layout and its line numbers are exact
+            let text = &content[window.start..window.end];
+            create_chunk_object(file_path, file_id, project_id, text, idx as u32, line_at(window.start), line_at(window.end), language)
+        })
+        .collect();
 
     Ok(chunks)
 }
@@ -1540,7 +2749,8 @@ fn create_file_log_object(file_path: &Path, file_id: &str, project_id: &str, sym
         "dependencies": [],
         "last_modified": now.to_rfc3339(),
         "change_count": 0,
-        "linked_changesets": []
+        "linked_changesets": [],
+        "content_hash": file_content_hash(file_path)
     }))
 }
 
@@ -1575,6 +2785,7 @@ async fn create_file_log_object_ai(
         "content": prepared_content,
         "symbols": symbols,
         "dependencies": dependencies,
+        "project_id": project_id,
     });
 
     match client.generate_ai_file_log(payload).await {
@@ -1663,7 +2874,8 @@ fn create_file_log_object_from_ai(
         "notes": notes,
         "last_modified": now.to_rfc3339(),
         "change_count": 0,
-        "linked_changesets": []
+        "linked_changesets": [],
+        "content_hash": file_content_hash(file_path)
     }))
 }
 
@@ -1698,6 +2910,7 @@ async fn create_directory_log_ai(
         "content": content,
         "symbols": [],
         "dependencies": entries,
+        "project_id": project_id,
     });
 
     match client.generate_ai_file_log(payload).await {
@@ -30,6 +30,7 @@ pub struct IndexUiState {
     pub processed_files: usize,
     pub created_symbols: usize,
     pub created_directories: usize,
+    pub created_relationships: usize,
     pub errors: usize,
     pub warnings: usize,
     pub current_path: String,
@@ -216,6 +217,10 @@ pub fn start_index_ui(
                         snapshot.created_directories
                     )),
                     Line::from(format!("Symbols: {}", snapshot.created_symbols)),
+                    Line::from(format!(
+                        "Relationships: {}",
+                        snapshot.created_relationships
+                    )),
                     Line::from(format!("Errors: {}", snapshot.errors)),
                     Line::from(format!("Warnings: {}", snapshot.warnings)),
                     Line::from(""),
@@ -1,8 +1,13 @@
+pub mod archive;
 pub mod clear;
+pub mod diff;
+pub mod doctor;
 pub mod history;
 pub mod index;
 pub mod index_ui;
+pub mod output;
 pub mod query;
 pub mod start;
 pub mod status;
 pub mod tui;
+pub mod watch;
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use comfy_table::{presets::UTF8_FULL_CONDENSED, Table};
+use serde::Serialize;
+
+/// Output format shared by the read-only commands (query, history, status).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for reading in a terminal
+    #[default]
+    Table,
+    /// Full records as pretty-printed JSON
+    Json,
+    /// GitHub-flavoured Markdown table
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    pub quiet: bool,
+}
+
+impl OutputOptions {
+    /// Headers and summary lines are for humans; JSON and `--quiet` output
+    /// must stay clean so it can be piped into other tools.
+    pub fn show_summary(&self) -> bool {
+        !self.quiet && self.format != OutputFormat::Json
+    }
+}
+
+pub fn json<T: Serialize + ?Sized>(records: &T) -> Result<String> {
+    Ok(serde_json::to_string_pretty(records)?)
+}
+
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_header(headers.to_vec());
+    for row in rows {
+        table.add_row(row.clone());
+    }
+    table.to_string()
+}
+
+pub fn markdown(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let escape = |cell: &str| cell.replace('|', "\\|").replace('\n', " ");
+    let mut out = format!("| {} |\n", headers.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape(cell)).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out.trim_end().to_string()
+}
+
+/// Render `rows` as a table or Markdown, or `records` as JSON.
+pub fn render<T: Serialize + ?Sized>(
+    format: OutputFormat,
+    headers: &[&str],
+    rows: &[Vec<String>],
+    records: &T,
+) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(table(headers, rows)),
+        OutputFormat::Json => json(records),
+        OutputFormat::Markdown => Ok(markdown(headers, rows)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> (Vec<Vec<String>>, serde_json::Value) {
+        let rows = vec![vec!["symbol".to_string(), "run|query".to_string()]];
+        let records = json!([{ "type": "symbol", "name": "run|query" }]);
+        (rows, records)
+    }
+
+    #[test]
+    fn test_render_table_aligns_columns() {
+        let (rows, records) = sample();
+        let out = render(OutputFormat::Table, &["Type", "Name"], &rows, &records).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines
+            .iter()
+            .any(|l| l.contains("Type") && l.contains("Name")));
+        assert!(lines
+            .iter()
+            .any(|l| l.contains("symbol") && l.contains("run|query")));
+        assert!(lines
+            .iter()
+            .all(|l| l.chars().count() == lines[0].chars().count()));
+    }
+
+    #[test]
+    fn test_render_json_emits_records() {
+        let (rows, records) = sample();
+        let out = render(OutputFormat::Json, &["Type", "Name"], &rows, &records).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed, records);
+    }
+
+    #[test]
+    fn test_render_markdown_escapes_pipes() {
+        let (rows, records) = sample();
+        let out = render(OutputFormat::Markdown, &["Type", "Name"], &rows, &records).unwrap();
+        assert_eq!(
+            out,
+            "| Type | Name |\n| --- | --- |\n| symbol | run\\|query |"
+        );
+    }
+
+    #[test]
+    fn test_summary_hidden_for_json_and_quiet() {
+        let table = OutputOptions::default();
+        let quiet = OutputOptions {
+            quiet: true,
+            ..table
+        };
+        let json = OutputOptions {
+            format: OutputFormat::Json,
+            ..table
+        };
+        assert!(table.show_summary());
+        assert!(!quiet.show_summary());
+        assert!(!json.show_summary());
+    }
+}
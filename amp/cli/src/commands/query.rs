@@ -1,89 +1,462 @@
 use crate::client::AmpClient;
+use crate::commands::output::{self, OutputFormat, OutputOptions};
 use anyhow::Result;
-use serde_json::json;
+use clap::Args;
+use serde_json::{json, Value};
 
-pub async fn run_query(text: Option<&str>, relationships: bool, client: &AmpClient) -> Result<()> {
+/// Result count and scope for `amp query` and `amp search`.
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    pub top_k: usize,
+    pub project: Option<String>,
+    pub object_types: Vec<String>,
+    pub language: Option<String>,
+    pub path: Option<String>,
+}
+
+/// Flags for `amp search`, a terse hybrid search over indexed code and memory.
+#[derive(Debug, Clone, Args)]
+pub struct SearchArgs {
+    /// Text to search for
+    pub text: String,
+    /// Only these object types (comma-separated, e.g. symbol,FileChunk)
+    #[arg(long = "type", value_delimiter = ',')]
+    pub object_types: Vec<String>,
+    /// Only code in this language (e.g. rust, python)
+    #[arg(long)]
+    pub lang: Option<String>,
+    /// Maximum number of results
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub limit: usize,
+    /// Only objects whose path contains this
+    #[arg(long = "in", value_name = "PATH")]
+    pub path: Option<String>,
+    /// Print machine-readable JSON (same as `--output-format json`)
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl SearchArgs {
+    pub fn query_options(&self) -> QueryOptions {
+        QueryOptions {
+            top_k: self.limit,
+            project: None,
+            object_types: self.object_types.clone(),
+            language: self.lang.clone(),
+            path: self.path.clone(),
+        }
+    }
+}
+
+pub async fn run_search(
+    args: &SearchArgs,
+    options: OutputOptions,
+    client: &AmpClient,
+) -> Result<()> {
+    let options = if args.json {
+        OutputOptions {
+            format: OutputFormat::Json,
+            ..options
+        }
+    } else {
+        options
+    };
+    run_query(
+        Some(&args.text),
+        false,
+        &args.query_options(),
+        options,
+        client,
+    )
+    .await
+}
+
+const PREVIEW_CHARS: usize = 80;
+
+pub async fn run_query(
+    text: Option<&str>,
+    relationships: bool,
+    query: &QueryOptions,
+    options: OutputOptions,
+    client: &AmpClient,
+) -> Result<()> {
     if !client.health_check().await? {
         anyhow::bail!("AMP server is not available. Please start the server first.");
     }
 
     if relationships {
-        println!("🔍 Checking relationships in database...");
-        
+        if options.show_summary() {
+            println!("🔍 Checking relationships in database...");
+        }
+
         // Check for relationship tables
         let queries = vec![
             "SELECT * FROM contains LIMIT 5",
-            "SELECT * FROM defines LIMIT 5", 
+            "SELECT * FROM defines LIMIT 5",
             "INFO FOR DB",
         ];
-        
+
+        let mut records = Vec::new();
         for query in queries {
-            println!("\n📋 Query: {}", query);
             match client.query(query).await {
+                Ok(result) if options.format == OutputFormat::Json => {
+                    records.push(json!({ "query": query, "result": result }));
+                }
                 Ok(result) => {
+                    println!("\n📋 Query: {}", query);
                     println!("✅ Result: {}", serde_json::to_string_pretty(&result)?);
                 }
                 Err(e) => {
-                    println!("⚠️  Error: {}", e);
+                    eprintln!("⚠️  Error in {}: {}", query, e);
                 }
             }
         }
+        if options.format == OutputFormat::Json {
+            println!("{}", output::json(&records)?);
+        }
     } else if let Some(search_text) = text {
-        println!("🔍 Searching for: {}", search_text);
-        
-        let query_request = json!({
-            "text": search_text,
-            "limit": 10
-        });
-        
-        match client.query_objects(query_request).await {
+        if options.show_summary() {
+            println!("🔍 Searching for: {}", search_text);
+        }
+
+        match client
+            .query_objects(build_request(Some(search_text), query))
+            .await
+        {
             Ok(result) => {
-                if let Some(results) = result.get("results") {
-                    if let Some(array) = results.as_array() {
-                        println!("📊 Found {} results:", array.len());
-                        for (i, item) in array.iter().enumerate() {
-                            if let Some(object) = item.get("object") {
-                                let name = object.get("name").and_then(|v| v.as_str()).unwrap_or("unknown");
-                                let kind = object.get("kind").and_then(|v| v.as_str()).unwrap_or("unknown");
-                                println!("  {}. {} ({})", i + 1, name, kind);
-                            }
-                        }
+                let results = result_array(&result);
+                if text_search_only(&result) && !options.quiet {
+                    eprintln!(
+                        "ℹ️  No vector results (embeddings disabled or unavailable); showing text and graph matches only"
+                    );
+                }
+                if options.show_summary() {
+                    if results.is_empty() {
+                        println!("📊 No results found");
+                    } else {
+                        println!("📊 Found {} results:", results.len());
                     }
-                } else {
-                    println!("📊 No results found");
                 }
+                print_results(results, options)?;
             }
             Err(e) => {
-                println!("⚠️  Query failed: {}", e);
+                eprintln!("⚠️  Query failed: {}", e);
             }
         }
     } else {
-        println!("🔍 Showing database overview...");
-        
-        let query_request = json!({
-            "limit": 10
-        });
-        
-        match client.query_objects(query_request).await {
+        if options.show_summary() {
+            println!("🔍 Showing database overview...");
+        }
+
+        match client.query_objects(build_request(None, query)).await {
             Ok(result) => {
-                if let Some(results) = result.get("results") {
-                    if let Some(array) = results.as_array() {
-                        println!("📊 Sample objects ({} shown):", array.len());
-                        for (i, item) in array.iter().enumerate() {
-                            if let Some(object) = item.get("object") {
-                                let name = object.get("name").and_then(|v| v.as_str()).unwrap_or("unknown");
-                                let kind = object.get("kind").and_then(|v| v.as_str()).unwrap_or("unknown");
-                                println!("  {}. {} ({})", i + 1, name, kind);
-                            }
-                        }
-                    }
+                let results = result_array(&result);
+                if options.show_summary() {
+                    println!("📊 Sample objects ({} shown):", results.len());
                 }
+                print_results(results, options)?;
             }
             Err(e) => {
-                println!("⚠️  Query failed: {}", e);
+                eprintln!("⚠️  Query failed: {}", e);
             }
         }
     }
 
     Ok(())
 }
+
+/// Text queries go through hybrid retrieval, matching what agents get from `amp_query`.
+fn build_request(text: Option<&str>, query: &QueryOptions) -> Value {
+    let mut request = json!({ "limit": query.top_k });
+    if let Some(text) = text {
+        request["text"] = json!(text);
+        request["hybrid"] = json!(true);
+    }
+    let mut filters = serde_json::Map::new();
+    if let Some(project) = &query.project {
+        filters.insert("project_id".to_string(), json!(project));
+    }
+    if !query.object_types.is_empty() {
+        filters.insert("type".to_string(), json!(query.object_types));
+    }
+    if let Some(language) = &query.language {
+        filters.insert("language".to_string(), json!(language));
+    }
+    if let Some(path) = &query.path {
+        filters.insert("path".to_string(), json!(path));
+    }
+    if !filters.is_empty() {
+        request["filters"] = Value::Object(filters);
+    }
+    request
+}
+
+/// Hybrid retrieval falls back to text search when no query embedding can be made.
+fn text_search_only(result: &Value) -> bool {
+    result
+        .get("vector_results_count")
+        .and_then(|v| v.as_u64())
+        .is_some_and(|count| count == 0)
+}
+
+fn result_array(result: &Value) -> &[Value] {
+    result
+        .get("results")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+fn print_results(results: &[Value], options: OutputOptions) -> Result<()> {
+    if results.is_empty() && options.format != OutputFormat::Json {
+        return Ok(());
+    }
+    println!("{}", render_results(results, options.format)?);
+    Ok(())
+}
+
+fn render_results(results: &[Value], format: OutputFormat) -> Result<String> {
+    let field = |object: &Value, key: &str| {
+        object
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string()
+    };
+
+    match format {
+        OutputFormat::Json => output::json(results),
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = results
+                .iter()
+                .enumerate()
+                .filter_map(|(i, item)| {
+                    let object = item.get("object")?;
+                    Some(vec![
+                        (i + 1).to_string(),
+                        field(object, "type"),
+                        location(object),
+                        score(item),
+                        preview(object),
+                    ])
+                })
+                .collect();
+            Ok(output::table(
+                &["#", "Type", "Path / Title", "Score", "Preview"],
+                &rows,
+            ))
+        }
+        OutputFormat::Markdown => {
+            let rows: Vec<Vec<String>> = results
+                .iter()
+                .filter_map(|item| {
+                    let object = item.get("object")?;
+                    let name = object
+                        .get("name")
+                        .or_else(|| object.get("title"))
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown");
+                    let path = object
+                        .get("path")
+                        .or_else(|| object.get("file_path"))
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("");
+                    Some(vec![
+                        field(object, "type"),
+                        name.to_string(),
+                        path.to_string(),
+                        score(item),
+                    ])
+                })
+                .collect();
+            Ok(output::markdown(&["Type", "Name", "Path", "Score"], &rows))
+        }
+    }
+}
+
+fn score(item: &Value) -> String {
+    item.get("score")
+        .and_then(|v| v.as_f64())
+        .map(|score| format!("{:.3}", score))
+        .unwrap_or_default()
+}
+
+/// File path for code objects, title or name for everything else.
+fn location(object: &Value) -> String {
+    ["path", "file_path", "title", "name"]
+        .iter()
+        .find_map(|key| object.get(*key).and_then(|v| v.as_str()))
+        .filter(|value| !value.is_empty())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// First line of the object's most descriptive text field. Chunks cut at a
+/// symbol name it instead, e.g. "function parse_file (lines 120-180)".
+fn preview(object: &Value) -> String {
+    let field = |key: &str| object.get(key).and_then(|v| v.as_str());
+    if let (Some(kind), Some(name)) = (field("symbol_kind"), field("symbol_name")) {
+        let line = |key: &str| object.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        return format!(
+            "{} {} (lines {}-{})",
+            kind,
+            name,
+            line("start_line"),
+            line("end_line")
+        );
+    }
+
+    let text = [
+        "summary",
+        "documentation",
+        "signature",
+        "description",
+        "decision",
+        "content",
+        "kind",
+    ]
+    .iter()
+    .filter_map(|key| object.get(*key).and_then(|v| v.as_str()))
+    .find_map(|value| value.lines().map(str::trim).find(|line| !line.is_empty()))
+    .unwrap_or("");
+
+    if text.chars().count() > PREVIEW_CHARS {
+        let truncated: String = text.chars().take(PREVIEW_CHARS - 1).collect();
+        format!("{}…", truncated)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_results() -> Vec<Value> {
+        vec![
+            json!({
+                "object": { "type": "symbol", "name": "run_query", "kind": "function", "path": "src/commands/query.rs" },
+                "score": 0.91234
+            }),
+            json!({
+                "object": { "type": "decision", "title": "Use RRF" },
+                "score": 0.5
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_render_results_markdown_uses_title_and_score() {
+        let out = render_results(&sample_results(), OutputFormat::Markdown).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "| Type | Name | Path | Score |");
+        assert_eq!(
+            lines[2],
+            "| symbol | run_query | src/commands/query.rs | 0.912 |"
+        );
+        assert_eq!(lines[3], "| decision | Use RRF |  | 0.500 |");
+    }
+
+    #[test]
+    fn test_render_results_json_keeps_full_results() {
+        let results = sample_results();
+        let out = render_results(&results, OutputFormat::Json).unwrap();
+        let parsed: Vec<Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed, results);
+    }
+
+    #[test]
+    fn test_render_results_table_numbers_rows() {
+        let out = render_results(&sample_results(), OutputFormat::Table).unwrap();
+        assert!(out.contains("src/commands/query.rs"));
+        assert!(out.contains("function"));
+        assert!(out.contains("0.912"));
+        assert!(out
+            .lines()
+            .any(|line| line.contains(" 2 ") && line.contains("Use RRF")));
+    }
+
+    #[test]
+    fn test_build_request_uses_hybrid_for_text() {
+        let query = QueryOptions {
+            top_k: 5,
+            project: Some("amp".to_string()),
+            ..QueryOptions::default()
+        };
+        let request = build_request(Some("lease renewal"), &query);
+        assert_eq!(request["hybrid"], json!(true));
+        assert_eq!(request["limit"], json!(5));
+        assert_eq!(request["filters"]["project_id"], json!("amp"));
+
+        let overview = build_request(
+            None,
+            &QueryOptions {
+                top_k: 10,
+                ..QueryOptions::default()
+            },
+        );
+        assert!(overview.get("hybrid").is_none());
+        assert!(overview.get("filters").is_none());
+    }
+
+    #[test]
+    fn test_search_flags_map_to_query_filters() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Search {
+            #[command(flatten)]
+            args: SearchArgs,
+        }
+
+        let search = Search::try_parse_from([
+            "search",
+            "lease renewal",
+            "--type",
+            "symbol,FileChunk",
+            "--lang",
+            "rust",
+            "--limit",
+            "3",
+            "--in",
+            "server/src",
+            "--json",
+        ])
+        .unwrap();
+        assert!(search.args.json);
+
+        let request = build_request(Some(&search.args.text), &search.args.query_options());
+        assert_eq!(
+            request,
+            json!({
+                "text": "lease renewal",
+                "hybrid": true,
+                "limit": 3,
+                "filters": {
+                    "type": ["symbol", "FileChunk"],
+                    "language": "rust",
+                    "path": "server/src",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_preview_takes_first_line_and_truncates() {
+        let object = json!({ "content": "\n  fn main() {\n}", "kind": "function" });
+        assert_eq!(preview(&object), "fn main() {");
+
+        let long = json!({ "summary": "x".repeat(200) });
+        assert_eq!(preview(&long).chars().count(), PREVIEW_CHARS);
+
+        let chunk = json!({ "type": "FileChunk", "content": "fn parse_file() {}", "symbol_kind": "function", "symbol_name": "parse_file", "start_line": 120, "end_line": 180 });
+        assert_eq!(preview(&chunk), "function parse_file (lines 120-180)");
+    }
+
+    #[test]
+    fn test_text_search_only_detects_missing_vector_results() {
+        assert!(text_search_only(&json!({ "vector_results_count": 0 })));
+        assert!(!text_search_only(&json!({ "vector_results_count": 3 })));
+        assert!(!text_search_only(&json!({})));
+    }
+}
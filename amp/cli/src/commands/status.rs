@@ -1,33 +1,352 @@
+use crate::commands::output::{self, OutputFormat, OutputOptions};
 use crate::{client::AmpClient, config::Config, session::Session};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 
-pub async fn show_status(client: &AmpClient) -> Result<()> {
-    println!("AMP Bridge Status");
-    println!("================");
-    
-    // Check server health
-    match client.health_check().await {
-        Ok(true) => println!("✓ AMP Server: Connected"),
-        Ok(false) => println!("✗ AMP Server: Disconnected"),
-        Err(e) => println!("✗ AMP Server: Error - {}", e),
+/// Per-project index health, as reported by `/v1/analytics`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectHealth {
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub name: Option<String>,
+    pub files: i64,
+    pub symbols: i64,
+    pub chunks: ChunkStats,
+    #[serde(rename = "lastIndexed")]
+    pub last_indexed: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkStats {
+    pub embedded: i64,
+    pub unembedded: i64,
+}
+
+impl ChunkStats {
+    fn embedded_percent(&self) -> Option<f64> {
+        let total = self.embedded + self.unembedded;
+        (total > 0).then(|| self.embedded as f64 * 100.0 / total as f64)
     }
-    
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectedAgent {
+    pub agent_name: String,
+    pub project_id: Option<String>,
+    pub last_heartbeat: String,
+}
+
+/// What the server holds, beyond whether it answers.
+#[derive(Debug, Default, Serialize)]
+pub struct MemoryHealth {
+    pub projects: Vec<ProjectHealth>,
+    pub cache_blocks_by_scope: BTreeMap<String, i64>,
+    pub agents: Vec<ConnectedAgent>,
+    /// Conditions that quietly degrade query quality
+    pub warnings: Vec<String>,
+}
+
+pub async fn show_status(
+    client: &AmpClient,
+    project: Option<&str>,
+    options: OutputOptions,
+) -> Result<()> {
+    if options.show_summary() {
+        println!("AMP Bridge Status");
+        println!("================");
+    }
+
+    // Check server health
+    let health = client.health_check().await;
+    let server = match &health {
+        Ok(true) => "✓ Connected".to_string(),
+        Ok(false) => "✗ Disconnected".to_string(),
+        Err(e) => format!("✗ Error - {}", e),
+    };
+    let memory = match health {
+        Ok(true) => Some(memory_health(client, project).await),
+        _ => None,
+    };
+
     // Check for active sessions
     let config = Config::from_env()?;
-    println!("Session directory: {:?}", config.session_dir);
     let sessions = Session::list_sessions(&config.session_dir).await?;
-    
-    let active_sessions: Vec<_> = sessions.iter()
+
+    let active_sessions: Vec<_> = sessions
+        .iter()
         .filter(|s| matches!(s.status, crate::session::SessionStatus::Active))
         .collect();
-    
-    println!("Active Sessions: {}", active_sessions.len());
-    
-    for session in active_sessions {
-        println!("  - {} ({})", session.id, session.agent_command);
-    }
-    
-    println!("Total Sessions: {}", sessions.len());
-    
+
+    let mut rows = vec![
+        vec!["AMP Server".to_string(), server.clone()],
+        vec![
+            "Session directory".to_string(),
+            config.session_dir.display().to_string(),
+        ],
+        vec![
+            "Active Sessions".to_string(),
+            active_sessions.len().to_string(),
+        ],
+    ];
+    for session in &active_sessions {
+        rows.push(vec![
+            "  - Session".to_string(),
+            format!("{} ({})", session.id, session.agent_command),
+        ]);
+    }
+    rows.push(vec![
+        "Total Sessions".to_string(),
+        sessions.len().to_string(),
+    ]);
+
+    let record = json!({
+        "server": server.trim_start_matches(['✓', '✗', ' ']),
+        "session_dir": config.session_dir,
+        "active_sessions": active_sessions,
+        "total_sessions": sessions.len(),
+        "memory": memory,
+    });
+    if options.format == OutputFormat::Json {
+        println!("{}", output::json(&record)?);
+        return Ok(());
+    }
+    println!(
+        "{}",
+        output::render(options.format, &["Field", "Value"], &rows, &record)?
+    );
+    if let Some(memory) = &memory {
+        print_memory_health(memory, options)?;
+    }
+
+    Ok(())
+}
+
+fn print_memory_health(memory: &MemoryHealth, options: OutputOptions) -> Result<()> {
+    let section = |title: &str| {
+        if options.show_summary() {
+            println!("\n{}", title);
+        }
+    };
+
+    section("Projects");
+    if memory.projects.is_empty() {
+        println!("No indexed projects");
+    } else {
+        let rows: Vec<Vec<String>> = memory
+            .projects
+            .iter()
+            .map(|p| {
+                vec![
+                    p.name.clone().unwrap_or_else(|| p.project_id.clone()),
+                    p.files.to_string(),
+                    p.symbols.to_string(),
+                    (p.chunks.embedded + p.chunks.unembedded).to_string(),
+                    p.chunks
+                        .embedded_percent()
+                        .map(|percent| format!("{:.0}%", percent))
+                        .unwrap_or_else(|| "-".to_string()),
+                    p.last_indexed.clone().unwrap_or_else(|| "never".to_string()),
+                ]
+            })
+            .collect();
+        println!(
+            "{}",
+            output::render(
+                options.format,
+                &["Project", "Files", "Symbols", "Chunks", "Embedded", "Last indexed"],
+                &rows,
+                &memory.projects,
+            )?
+        );
+    }
+
+    if !memory.cache_blocks_by_scope.is_empty() {
+        section("Cache blocks");
+        let rows: Vec<Vec<String>> = memory
+            .cache_blocks_by_scope
+            .iter()
+            .map(|(scope, count)| vec![scope.clone(), count.to_string()])
+            .collect();
+        println!(
+            "{}",
+            output::render(
+                options.format,
+                &["Scope", "Blocks"],
+                &rows,
+                &memory.cache_blocks_by_scope,
+            )?
+        );
+    }
+
+    section("Connected agents");
+    if memory.agents.is_empty() {
+        println!("No agents connected");
+    } else {
+        let rows: Vec<Vec<String>> = memory
+            .agents
+            .iter()
+            .map(|a| {
+                vec![
+                    a.agent_name.clone(),
+                    a.project_id.clone().unwrap_or_default(),
+                    a.last_heartbeat.clone(),
+                ]
+            })
+            .collect();
+        println!(
+            "{}",
+            output::render(
+                options.format,
+                &["Agent", "Project", "Last heartbeat"],
+                &rows,
+                &memory.agents,
+            )?
+        );
+    }
+
+    for warning in &memory.warnings {
+        println!("⚠️  {}", warning);
+    }
     Ok(())
 }
+
+/// Gather project, cache, agent and embedding health from the server. A
+/// failing endpoint becomes a warning instead of failing the whole status.
+pub async fn memory_health(client: &AmpClient, project: Option<&str>) -> MemoryHealth {
+    let (analytics, connections, settings) = tokio::join!(
+        client.get_analytics(project),
+        client.list_connections(project),
+        client.get_settings(),
+    );
+    let mut health = MemoryHealth::default();
+
+    match analytics {
+        Ok(analytics) => {
+            let detailed = &analytics["detailed"];
+            health.projects =
+                serde_json::from_value(detailed["projects"].clone()).unwrap_or_default();
+            health.cache_blocks_by_scope =
+                serde_json::from_value(detailed["cacheBlocksByScope"].clone()).unwrap_or_default();
+            if analytics["embeddingStatus"]["restartRequired"].as_bool() == Some(true) {
+                health.warnings.push(
+                    "Embedding settings changed since the server loaded them; restart it before indexing"
+                        .to_string(),
+                );
+            }
+        }
+        Err(e) => health.warnings.push(format!("Could not load analytics: {}", e)),
+    }
+
+    match connections {
+        Ok(Value::Array(connections)) => {
+            health.agents = connections
+                .iter()
+                .map(|c| ConnectedAgent {
+                    agent_name: c["agent_name"].as_str().unwrap_or("unknown").to_string(),
+                    project_id: c["project_id"].as_str().map(str::to_string),
+                    last_heartbeat: c["last_heartbeat"].as_str().unwrap_or_default().to_string(),
+                })
+                .collect();
+        }
+        Ok(_) => {}
+        Err(e) => health.warnings.push(format!("Could not list connections: {}", e)),
+    }
+
+    match settings {
+        Ok(settings) => health.warnings.extend(embedding_warning(&settings)),
+        Err(e) => health.warnings.push(format!("Could not load settings: {}", e)),
+    }
+
+    health
+}
+
+/// Disabled embeddings or a missing provider key make queries text-only
+/// without any error.
+fn embedding_warning(settings: &Value) -> Option<String> {
+    let provider = settings["embeddingProvider"].as_str().unwrap_or("none");
+    let key_field = match provider {
+        "none" | "" => {
+            return Some(
+                "Embeddings are disabled; queries fall back to text and graph retrieval".to_string(),
+            )
+        }
+        "openai" => "openaiApiKey",
+        "openrouter" => "openrouterApiKey",
+        "cohere" => "cohereApiKey",
+        _ => return None,
+    };
+    settings[key_field]
+        .as_str()
+        .unwrap_or_default()
+        .is_empty()
+        .then(|| {
+            format!(
+                "Embedding provider {} has no API key; chunks are stored without embeddings",
+                provider
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Json, Router};
+
+    #[tokio::test]
+    async fn test_memory_health_reads_projects_agents_and_warns_on_missing_key() {
+        let app = Router::new()
+            .route(
+                "/v1/analytics",
+                get(|| async {
+                    Json(json!({
+                        "detailed": {
+                            "cacheBlocksByScope": { "project:amp": 3 },
+                            "projects": [{
+                                "projectId": "amp", "name": "AMP", "files": 4, "symbols": 20,
+                                "chunks": { "embedded": 3, "unembedded": 1 },
+                                "lastIndexed": "2026-01-03T00:00:00Z"
+                            }]
+                        },
+                        "embeddingStatus": { "restartRequired": false }
+                    }))
+                }),
+            )
+            .route(
+                "/v1/connections",
+                get(|| async {
+                    Json(json!([{ "agent_name": "claude", "project_id": "amp", "last_heartbeat": "2026-01-03T00:00:05Z" }]))
+                }),
+            )
+            .route(
+                "/v1/settings",
+                get(|| async { Json(json!({ "embeddingProvider": "openai", "openaiApiKey": "" })) }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let health = memory_health(&client, Some("amp")).await;
+
+        assert_eq!(health.projects.len(), 1);
+        assert_eq!(health.projects[0].chunks.embedded_percent(), Some(75.0));
+        assert_eq!(health.cache_blocks_by_scope["project:amp"], 3);
+        assert_eq!(health.agents[0].agent_name, "claude");
+        assert_eq!(health.warnings.len(), 1);
+        assert!(health.warnings[0].contains("openai has no API key"));
+    }
+
+    #[test]
+    fn test_embedding_warning() {
+        assert!(embedding_warning(&json!({ "embeddingProvider": "none" })).is_some());
+        assert!(embedding_warning(&json!({ "embeddingProvider": "ollama" })).is_none());
+        assert!(embedding_warning(
+            &json!({ "embeddingProvider": "cohere", "cohereApiKey": "key" })
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,343 @@
+use crate::client::AmpClient;
+use crate::commands::index::{get_index_settings, is_text_file, ExcludeRules};
+use anyhow::Result;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::time::{Duration, Instant};
+
+/// Events for the same path closer together than this are synced once.
+pub const DEBOUNCE_WINDOW: Duration = Duration::from_millis(500);
+
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    Create,
+    Edit,
+    Delete,
+}
+
+impl SyncAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SyncAction::Create => "create",
+            SyncAction::Edit => "edit",
+            SyncAction::Delete => "delete",
+        }
+    }
+
+    /// Combine a pending action with a newer event for the same path.
+    fn then(self, next: SyncAction) -> SyncAction {
+        match (self, next) {
+            // Editors often write a new file in several steps
+            (SyncAction::Create, SyncAction::Edit) => SyncAction::Create,
+            // Save-by-rename replaces the file; the server still has the old one
+            (SyncAction::Delete, SyncAction::Create) => SyncAction::Edit,
+            (_, next) => next,
+        }
+    }
+}
+
+struct Pending {
+    action: SyncAction,
+    last_event: Instant,
+}
+
+/// Coalesces file events per path until the path has been quiet for the window.
+pub struct Debouncer {
+    window: Duration,
+    pending: HashMap<PathBuf, Pending>,
+}
+
+impl Debouncer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, path: PathBuf, action: SyncAction, now: Instant) {
+        self.pending
+            .entry(path)
+            .and_modify(|pending| {
+                pending.action = pending.action.then(action);
+                pending.last_event = now;
+            })
+            .or_insert(Pending {
+                action,
+                last_event: now,
+            });
+    }
+
+    /// Remove and return the paths whose last event is at least a window old.
+    pub fn drain_ready(&mut self, now: Instant) -> Vec<(PathBuf, SyncAction)> {
+        let ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.last_event) >= self.window)
+            .map(|(path, _)| path.clone())
+            .collect();
+        let mut ready: Vec<(PathBuf, SyncAction)> = ready
+            .into_iter()
+            .filter_map(|path| {
+                let pending = self.pending.remove(&path)?;
+                Some((path, pending.action))
+            })
+            .collect();
+        ready.sort_by(|a, b| a.0.cmp(&b.0));
+        ready
+    }
+}
+
+/// Watch `path` after the initial index and sync each changed file until Ctrl+C.
+pub async fn run_watch(path: &str, exclude: &[String], client: &AmpClient) -> Result<()> {
+    let root = Path::new(path).canonicalize()?;
+    let respect_gitignore = match get_index_settings(client).await {
+        Ok(settings) => settings.respect_gitignore,
+        Err(_) => true,
+    };
+    let rules = ExcludeRules::new(&root, exclude, respect_gitignore);
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let _ = tx.send(event);
+    })?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
+    println!("\nWatching {} for changes (Ctrl+C to stop)", root.display());
+
+    let mut debouncer = Debouncer::new(DEBOUNCE_WINDOW);
+    let mut synced = 0;
+    let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            Some(event) = rx.recv() => match event {
+                Ok(event) => {
+                    for (path, action) in event_actions(&event) {
+                        if is_watched(&rules, &path, action) {
+                            debouncer.record(path, action, Instant::now());
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Watch error: {}", e),
+            },
+            _ = flush.tick() => {
+                synced += flush_ready(&mut debouncer, Instant::now(), &root, client).await;
+            }
+        }
+    }
+
+    // Don't drop changes made just before Ctrl+C
+    synced += flush_ready(
+        &mut debouncer,
+        Instant::now() + DEBOUNCE_WINDOW,
+        &root,
+        client,
+    )
+    .await;
+    println!(
+        "Stopped watching {} ({} file{} synced)",
+        root.display(),
+        synced,
+        if synced == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Sync every path that has settled, printing one line per sync.
+/// Returns how many sync calls were made.
+async fn flush_ready(
+    debouncer: &mut Debouncer,
+    now: Instant,
+    root: &Path,
+    client: &AmpClient,
+) -> usize {
+    let ready = debouncer.drain_ready(now);
+    for (path, action) in &ready {
+        let display = path.strip_prefix(root).unwrap_or(path).display();
+        match client
+            .sync_file(&path.to_string_lossy(), action.as_str())
+            .await
+        {
+            Ok(result) => {
+                let count = |key: &str| result.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+                println!(
+                    "  {:<6} {}  (+{} -{} ~{} symbols)",
+                    action.as_str(),
+                    display,
+                    count("symbols_added"),
+                    count("symbols_removed"),
+                    count("symbols_changed")
+                );
+            }
+            Err(e) => {
+                println!("  {:<6} {}  failed: {}", action.as_str(), display, e);
+                continue;
+            }
+        }
+        // The delete sync keeps the FileLog as an audit record; the file is gone
+        if *action == SyncAction::Delete {
+            if let Err(e) = client.delete_file_objects(&path.to_string_lossy()).await {
+                println!(
+                    "  {:<6} {}  cleanup failed: {}",
+                    action.as_str(),
+                    display,
+                    e
+                );
+            }
+        }
+    }
+    ready.len()
+}
+
+/// Map a notify event onto the sync actions it implies for each path.
+fn event_actions(event: &Event) -> Vec<(PathBuf, SyncAction)> {
+    let action = match event.kind {
+        EventKind::Create(_) => SyncAction::Create,
+        EventKind::Remove(_) => SyncAction::Delete,
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            return vec![
+                (event.paths[0].clone(), SyncAction::Delete),
+                (event.paths[1].clone(), SyncAction::Create),
+            ];
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => SyncAction::Delete,
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => SyncAction::Create,
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            return event
+                .paths
+                .iter()
+                .map(|path| {
+                    let action = if path.exists() {
+                        SyncAction::Create
+                    } else {
+                        SyncAction::Delete
+                    };
+                    (path.clone(), action)
+                })
+                .collect();
+        }
+        EventKind::Modify(ModifyKind::Metadata(_)) => return Vec::new(),
+        EventKind::Modify(_) => SyncAction::Edit,
+        _ => return Vec::new(),
+    };
+    event
+        .paths
+        .iter()
+        .map(|path| (path.clone(), action))
+        .collect()
+}
+
+/// Apply the initial walk's exclusions; deleted files can't be sniffed for text.
+fn is_watched(rules: &ExcludeRules, path: &Path, action: SyncAction) -> bool {
+    if rules.is_excluded(path, path.is_dir()) {
+        return false;
+    }
+    match action {
+        SyncAction::Delete => true,
+        SyncAction::Create | SyncAction::Edit => path.is_file() && is_text_file(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    type Calls = Arc<Mutex<Vec<Value>>>;
+
+    async fn sync(State(calls): State<Calls>, Json(body): Json<Value>) -> Json<Value> {
+        calls.lock().unwrap().push(body);
+        Json(json!({ "symbols_added": 1, "symbols_removed": 0, "symbols_changed": 0 }))
+    }
+
+    async fn delete_file(State(calls): State<Calls>, Json(body): Json<Value>) -> Json<Value> {
+        let path = body["path"].clone();
+        calls
+            .lock()
+            .unwrap()
+            .push(json!({ "path": path, "action": "delete-file" }));
+        Json(json!({ "objects_deleted": 2, "relationships_deleted": 0 }))
+    }
+
+    #[tokio::test]
+    async fn test_debouncer_syncs_each_coalesced_path_once() {
+        let calls: Calls = Arc::default();
+        let app = Router::new()
+            .route("/v1/codebase/sync", post(sync))
+            .route("/v1/codebase/delete-file", post(delete_file))
+            .with_state(calls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = AmpClient::new(&format!("http://{}", addr));
+
+        let root = PathBuf::from("/repo");
+        let start = Instant::now();
+        let ms = |n: u64| start + Duration::from_millis(n);
+        let mut debouncer = Debouncer::new(DEBOUNCE_WINDOW);
+        for n in [0, 100, 300] {
+            debouncer.record(root.join("src/lib.rs"), SyncAction::Edit, ms(n));
+        }
+        debouncer.record(root.join("src/new.rs"), SyncAction::Create, ms(50));
+        debouncer.record(root.join("src/new.rs"), SyncAction::Edit, ms(200));
+        debouncer.record(root.join("src/old.rs"), SyncAction::Delete, ms(0));
+
+        // Every path saw an event within the last 500ms
+        assert_eq!(
+            flush_ready(&mut debouncer, ms(450), &root, &client).await,
+            0
+        );
+        assert_eq!(
+            flush_ready(&mut debouncer, ms(800), &root, &client).await,
+            3
+        );
+        assert_eq!(
+            flush_ready(&mut debouncer, ms(2000), &root, &client).await,
+            0
+        );
+
+        let calls = calls.lock().unwrap();
+        let synced: Vec<(&str, &str)> = calls
+            .iter()
+            .map(|c| (c["path"].as_str().unwrap(), c["action"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            synced,
+            [
+                ("/repo/src/lib.rs", "edit"),
+                ("/repo/src/new.rs", "create"),
+                ("/repo/src/old.rs", "delete"),
+                ("/repo/src/old.rs", "delete-file"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rename_becomes_delete_and_create() {
+        let event = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path(PathBuf::from("/repo/a.rs"))
+            .add_path(PathBuf::from("/repo/b.rs"));
+        assert_eq!(
+            event_actions(&event),
+            [
+                (PathBuf::from("/repo/a.rs"), SyncAction::Delete),
+                (PathBuf::from("/repo/b.rs"), SyncAction::Create),
+            ]
+        );
+        assert_eq!(
+            SyncAction::Delete.then(SyncAction::Create),
+            SyncAction::Edit
+        );
+    }
+}
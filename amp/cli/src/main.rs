@@ -15,6 +15,7 @@ pub mod git;
 
 use config::Config;
 use client::AmpClient;
+use commands::output::{OutputFormat, OutputOptions};
 
 #[derive(Parser)]
 #[command(name = "amp")]
@@ -22,6 +23,12 @@ use client::AmpClient;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Output format for query, history, status and index
+    #[arg(short = 'f', long, value_enum, global = true, default_value_t = OutputFormat::Table)]
+    output_format: OutputFormat,
+    /// Suppress header and summary lines for scripting
+    #[arg(short, long, global = true)]
+    quiet: bool,
 }
 
 #[derive(Subcommand)]
@@ -33,12 +40,33 @@ enum Commands {
         /// Directory to index (defaults to current directory)
         #[arg(short, long, default_value = ".")]
         path: String,
-        /// Skip files matching these patterns
+        /// Skip files matching these globs; `!pattern` re-includes
         #[arg(long, value_delimiter = ',')]
         exclude: Vec<String>,
         /// Create a .amp-root marker in the target directory if missing
         #[arg(long, default_value_t = false)]
         init_root: bool,
+        /// Also write each file's FILE_LOG markdown to a mirrored path under this directory
+        #[arg(long)]
+        output_dir: Option<String>,
+        /// Keep running after the initial index and sync files as they change
+        #[arg(short, long, default_value_t = false)]
+        watch: bool,
+        /// Index only the newline-separated paths in this file (`-` for stdin) instead of walking the tree
+        #[arg(long)]
+        files_from: Option<String>,
+    },
+    /// Compare stored file hashes with the working tree
+    Diff {
+        /// Directory to compare (defaults to current directory)
+        #[arg(short, long, default_value = ".")]
+        path: String,
+        /// Skip files matching these globs; `!pattern` re-includes
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+        /// Sync each added, modified or deleted file
+        #[arg(long, default_value_t = false)]
+        fix: bool,
     },
     /// Clear all objects from the AMP database
     Clear {
@@ -51,17 +79,54 @@ enum Commands {
         /// Agent command to run
         agent: String 
     },
-    /// Show current session status
-    Status,
+    /// Show server, session and per-project memory health
+    Status {
+        /// Only report this project
+        #[arg(long)]
+        project: Option<String>,
+        /// Print machine-readable JSON (same as `--output-format json`)
+        #[arg(long)]
+        json: bool,
+    },
     /// Query objects and relationships from the AMP database
     Query {
-        /// Query text to search for
+        /// Query text to search for (hybrid text, vector and graph retrieval)
         #[arg(short, long)]
         text: Option<String>,
+        /// Maximum number of results
+        #[arg(short = 'k', long, default_value_t = 10)]
+        top_k: usize,
+        /// Only return objects from this project
+        #[arg(long)]
+        project: Option<String>,
+        /// Print machine-readable JSON (same as `--output-format json`)
+        #[arg(long)]
+        json: bool,
         /// Show relationships
         #[arg(long)]
         relationships: bool,
     },
+    /// Search code and memory with type, language and path filters
+    Search(commands::query::SearchArgs),
+    /// Export a project's memory to a portable archive
+    Export {
+        /// Project to export
+        #[arg(required_unless_present = "project_flag")]
+        project: Option<String>,
+        /// Project to export (alternative to the positional argument)
+        #[arg(long = "project", id = "project_flag", value_name = "PROJECT", conflicts_with = "project")]
+        project_flag: Option<String>,
+        /// Archive path (defaults to <project>.amp.gz); .jsonl paths get plain NDJSON
+        #[arg(short, long, visible_alias = "out")]
+        output: Option<String>,
+    },
+    /// Import a project archive or .jsonl dump created by `amp export`
+    Import {
+        /// Archive path
+        file: String,
+    },
+    /// Check server, database, embedding and MCP configuration
+    Doctor,
     /// Launch interactive TUI
     Tui,
 }
@@ -74,29 +139,80 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
     let config = Config::from_env()?;
     let client = AmpClient::new(&config.server_url);
+    let output = OutputOptions {
+        format: cli.output_format,
+        quiet: cli.quiet,
+    };
 
     match cli.command {
         Commands::Clear { confirm } => {
             commands::clear::run_clear(confirm, &client).await?;
         }
         Commands::History => {
-            commands::history::show_history(&client).await?;
+            commands::history::show_history(&client, output).await?;
         }
-        Commands::Index { path, exclude, init_root } => {
+        Commands::Index { path, exclude, init_root, output_dir, watch, files_from } => {
             if should_run_index_in_container(&path)? {
-                run_index_in_container(&path, &exclude, init_root)?;
+                run_index_in_container(&path, &exclude, init_root, output_dir.as_deref(), files_from.as_deref())?;
             } else {
-                commands::index::run_index(&path, &exclude, init_root, &client).await?;
+                commands::index::run_index(
+                    &path,
+                    &exclude,
+                    init_root,
+                    output_dir.as_deref(),
+                    files_from.as_deref(),
+                    output,
+                    &client,
+                )
+                .await?;
             }
+            if watch {
+                commands::watch::run_watch(&path, &exclude, &client).await?;
+            }
+        }
+        Commands::Diff { path, exclude, fix } => {
+            commands::diff::run_diff(&path, &exclude, fix, &client).await?;
         }
-        Commands::Query { text, relationships } => {
-            commands::query::run_query(text.as_deref(), relationships, &client).await?;
+        Commands::Query { text, top_k, project, json, relationships } => {
+            let output = if json {
+                OutputOptions { format: OutputFormat::Json, ..output }
+            } else {
+                output
+            };
+            let query = commands::query::QueryOptions {
+                top_k,
+                project,
+                ..Default::default()
+            };
+            commands::query::run_query(text.as_deref(), relationships, &query, output, &client)
+                .await?;
+        }
+        Commands::Search(args) => {
+            commands::query::run_search(&args, output, &client).await?;
+        }
+        Commands::Export { project, project_flag, output: archive_path } => {
+            // clap requires one of the two
+            let project = project.or(project_flag).unwrap_or_default();
+            commands::archive::run_export(&project, archive_path.as_deref(), &client).await?;
+        }
+        Commands::Import { file } => {
+            commands::archive::run_import(&file, &client, output).await?;
         }
         Commands::Start { agent } => {
             commands::start::start_session(&agent, &client).await?;
         }
-        Commands::Status => {
-            commands::status::show_status(&client).await?;
+        Commands::Status { project, json } => {
+            let output = if json {
+                OutputOptions { format: OutputFormat::Json, ..output }
+            } else {
+                output
+            };
+            commands::status::show_status(&client, project.as_deref(), output).await?;
+        }
+        Commands::Doctor => {
+            if !commands::doctor::run_doctor(&client, output).await? {
+                std::process::exit(1);
+            }
         }
         Commands::Tui => {
             commands::tui::run_tui().await?;
@@ -143,7 +259,13 @@ fn should_run_index_in_container(path: &str) -> Result<bool> {
     Ok(false)
 }
 
-fn run_index_in_container(path: &str, exclude: &[String], init_root: bool) -> Result<()> {
+fn run_index_in_container(
+    path: &str,
+    exclude: &[String],
+    init_root: bool,
+    output_dir: Option<&str>,
+    files_from: Option<&str>,
+) -> Result<()> {
     let compose_file = find_compose_file(&env::current_dir()?)
         .ok_or_else(|| anyhow::anyhow!("docker-compose.yml not found"))?;
     let compose_root = compose_file
@@ -160,14 +282,50 @@ fn run_index_in_container(path: &str, exclude: &[String], init_root: bool) -> Re
     }
 
     let container_path = to_container_path(compose_root, &abs_path);
+    let container_output_dir = match output_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            let abs_dir = std::fs::canonicalize(dir)?;
+            if !abs_dir.starts_with(compose_root) {
+                anyhow::bail!(
+                    "Output dir {} is outside the compose root {}; choose a directory inside the repo.",
+                    abs_dir.display(),
+                    compose_root.display()
+                );
+            }
+            Some(to_container_path(compose_root, &abs_dir))
+        }
+        None => None,
+    };
+    // The list is resolved on the host and piped to the container's stdin
+    let container_file_list = match files_from {
+        Some(source) => {
+            let mut list = String::new();
+            for file in commands::index::read_file_list(source, path)? {
+                if !file.starts_with(compose_root) {
+                    anyhow::bail!(
+                        "Listed file {} is outside the compose root {}",
+                        file.display(),
+                        compose_root.display()
+                    );
+                }
+                list.push_str(&to_container_path(compose_root, &file));
+                list.push('\n');
+            }
+            Some(list)
+        }
+        None => None,
+    };
     let Some(compose_cmd) = detect_compose_command() else {
         anyhow::bail!("Docker Compose not found in PATH");
     };
 
     let mut cmd = build_compose_command(&compose_cmd, &compose_file);
-    cmd.arg("run")
-        .arg("--rm")
-        .arg("-e")
+    cmd.arg("run").arg("--rm");
+    if container_file_list.is_some() {
+        cmd.arg("-T");
+    }
+    cmd.arg("-e")
         .arg("AMP_INDEX_IN_CONTAINER=1")
         .arg("amp-cli")
         .arg("cargo")
@@ -184,8 +342,22 @@ fn run_index_in_container(path: &str, exclude: &[String], init_root: bool) -> Re
     if init_root {
         cmd.arg("--init-root");
     }
+    if let Some(dir) = container_output_dir {
+        cmd.arg("--output-dir").arg(dir);
+    }
 
-    let status = cmd.status()?;
+    let status = match container_file_list {
+        Some(list) => {
+            cmd.arg("--files-from").arg("-").stdin(std::process::Stdio::piped());
+            let mut child = cmd.spawn()?;
+            if let Some(mut stdin) = child.stdin.take() {
+                use std::io::Write;
+                stdin.write_all(list.as_bytes())?;
+            }
+            child.wait()?
+        }
+        None => cmd.status()?,
+    };
     if !status.success() {
         anyhow::bail!("Containerized index failed with status {}", status);
     }
@@ -1,14 +1,90 @@
 #![allow(dead_code)]
+use crate::retry::{BreakerStatus, CircuitBreaker, RetryPolicy};
 use anyhow::{Context, Result};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Lets the server recognise a retried create and return the first result.
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Consecutive failed calls that open the circuit breaker.
+pub const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+pub const DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Non-success response from the AMP server. Kept as a typed error so tool
+/// handlers can classify failures by status code instead of parsing strings.
+#[derive(Debug)]
+pub struct ApiError {
+    pub operation: &'static str,
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl ApiError {
+    pub async fn from_response(operation: &'static str, response: Response) -> Self {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Self {
+            operation,
+            status,
+            body,
+        }
+    }
+
+    /// Status code of an `ApiError` wrapped in an anyhow chain, if any
+    pub fn status_of(err: &anyhow::Error) -> Option<StatusCode> {
+        err.downcast_ref::<ApiError>().map(|api| api.status)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} failed ({}): {}",
+            self.operation, self.status, self.body
+        )
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Returned without contacting the server while the circuit breaker is open.
+#[derive(Debug)]
+pub struct ServerBackingOff {
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for ServerBackingOff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "AMP server unavailable, backing off (retrying in {}s)",
+            self.retry_in.as_secs().max(1)
+        )
+    }
+}
+
+impl std::error::Error for ServerBackingOff {}
+
+/// Statuses a restarting server or proxy answers with.
+fn is_unavailable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
 #[derive(Clone)]
 pub struct AmpClient {
     client: Client,
     base_url: String,
     timeout: Duration,
+    retry: RetryPolicy,
+    breaker: Arc<CircuitBreaker>,
 }
 
 impl AmpClient {
@@ -23,13 +99,82 @@ impl AmpClient {
             client,
             base_url,
             timeout: Duration::from_secs(timeout_secs),
+            retry: RetryPolicy::default(),
+            breaker: Arc::new(CircuitBreaker::new(
+                DEFAULT_BREAKER_THRESHOLD,
+                DEFAULT_BREAKER_COOLDOWN,
+            )),
         })
     }
 
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.breaker = Arc::new(CircuitBreaker::new(threshold, cooldown));
+        self
+    }
+
+    pub fn breaker_status(&self) -> BreakerStatus {
+        self.breaker.status()
+    }
+
+    /// Send a request built by `build` through the circuit breaker. Requests
+    /// that are safe to repeat (`retry`) are retried with backoff on transport
+    /// errors and 502/503/504; the breaker counts a call as failed only once
+    /// its attempts are used up.
+    async fn send<F>(&self, retry: bool, build: F) -> Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        if let Err(retry_in) = self.breaker.check() {
+            return Err(ServerBackingOff { retry_in }.into());
+        }
+        let max_attempts = if retry {
+            self.retry.max_attempts.max(1)
+        } else {
+            1
+        };
+        let mut attempt = 1;
+
+        loop {
+            let error = match build().send().await {
+                Ok(response) if !is_unavailable(response.status()) => {
+                    self.breaker.record_success();
+                    return Ok(response);
+                }
+                Ok(response) if attempt >= max_attempts => {
+                    self.breaker.record_failure();
+                    return Ok(response);
+                }
+                Ok(response) => response.status().to_string(),
+                Err(e) if !(e.is_connect() || e.is_timeout()) => return Err(e.into()),
+                Err(e) if attempt >= max_attempts => {
+                    self.breaker.record_failure();
+                    return Err(e.into());
+                }
+                Err(e) => e.to_string(),
+            };
+
+            let delay = self.retry.backoff(attempt - 1);
+            tracing::warn!(
+                "AMP request failed (attempt {}/{}), retrying in {:?}: {}",
+                attempt,
+                max_attempts,
+                delay,
+                error
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
     // Health check
     pub async fn health(&self) -> Result<Value> {
         let url = format!("{}/health", self.base_url);
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(true, || self.client.get(&url)).await?;
         let data = response.json().await?;
         Ok(data)
     }
@@ -37,7 +182,7 @@ impl AmpClient {
     // Analytics
     pub async fn analytics(&self) -> Result<Value> {
         let url = format!("{}/v1/analytics", self.base_url);
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(true, || self.client.get(&url)).await?;
         let data = response.json().await?;
         Ok(data)
     }
@@ -45,7 +190,65 @@ impl AmpClient {
     // Query endpoint
     pub async fn query(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/query", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(true, || self.client.post(&url).json(&payload))
+            .await?;
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    // Find objects similar to an existing object by embedding
+    pub async fn query_similar(&self, payload: Value) -> Result<Value> {
+        let url = format!("{}/v1/query/similar", self.base_url);
+        let response = self
+            .send(true, || self.client.post(&url).json(&payload))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("query_similar", response)
+                .await
+                .into());
+        }
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    // Shortest relationship path between two objects
+    pub async fn path(
+        &self,
+        from: &str,
+        to: &str,
+        max_depth: Option<usize>,
+        edge_types: &[String],
+    ) -> Result<Value> {
+        let url = format!("{}/v1/graph/path", self.base_url);
+        let mut query = vec![("from", from.to_string()), ("to", to.to_string())];
+        if let Some(depth) = max_depth {
+            query.push(("max_depth", depth.to_string()));
+        }
+        if !edge_types.is_empty() {
+            query.push(("edge_types", edge_types.join(",")));
+        }
+        let response = self
+            .send(true, || self.client.get(&url).query(&query))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("path", response).await.into());
+        }
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    pub async fn common_neighbors(&self, ids: &[String], edge_type: &str) -> Result<Value> {
+        let url = format!("{}/v1/graph/common", self.base_url);
+        let query = [("ids", ids.join(",")), ("edge_type", edge_type.to_string())];
+        let response = self
+            .send(true, || self.client.get(&url).query(&query))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("common neighbors", response)
+                .await
+                .into());
+        }
         let data = response.json().await?;
         Ok(data)
     }
@@ -53,7 +256,15 @@ impl AmpClient {
     // Create object
     pub async fn create_object(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/objects", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let key = uuid::Uuid::new_v4().to_string();
+        let response = self
+            .send(true, || {
+                self.client
+                    .post(&url)
+                    .header(IDEMPOTENCY_KEY_HEADER, &key)
+                    .json(&payload)
+            })
+            .await?;
         let data = response.json().await?;
         Ok(data)
     }
@@ -61,7 +272,55 @@ impl AmpClient {
     // Get object
     pub async fn get_object(&self, id: &str) -> Result<Value> {
         let url = format!("{}/v1/objects/{}", self.base_url, id);
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(true, || self.client.get(&url)).await?;
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    // Get an object's audit trail, newest first
+    pub async fn get_object_history(&self, id: &str, limit: Option<usize>) -> Result<Value> {
+        let url = format!("{}/v1/objects/{}/history", self.base_url, id);
+        let mut params = Vec::new();
+        if let Some(limit) = limit {
+            params.push(("limit", limit.to_string()));
+        }
+        let response = self
+            .send(true, || self.client.get(&url).query(&params))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("get_object_history", response)
+                .await
+                .into());
+        }
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    // List an object's versions, newest first
+    pub async fn get_object_versions(&self, id: &str) -> Result<Value> {
+        let url = format!("{}/v1/objects/{}/versions", self.base_url, id);
+        let response = self.send(true, || self.client.get(&url)).await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("get_object_versions", response)
+                .await
+                .into());
+        }
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    // Get the full snapshot of one version of an object
+    pub async fn get_object_version(&self, id: &str, version_number: u64) -> Result<Value> {
+        let url = format!(
+            "{}/v1/objects/{}/versions/{}",
+            self.base_url, id, version_number
+        );
+        let response = self.send(true, || self.client.get(&url)).await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("get_object_version", response)
+                .await
+                .into());
+        }
         let data = response.json().await?;
         Ok(data)
     }
@@ -69,7 +328,24 @@ impl AmpClient {
     // Update object
     pub async fn update_object(&self, id: &str, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/objects/{}", self.base_url, id);
-        let response = self.client.put(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.put(&url).json(&payload))
+            .await?;
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    // Create relationship
+    pub async fn create_relationship(&self, payload: Value) -> Result<Value> {
+        let url = format!("{}/v1/relationships", self.base_url);
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("create_relationship", response)
+                .await
+                .into());
+        }
         let data = response.json().await?;
         Ok(data)
     }
@@ -77,7 +353,9 @@ impl AmpClient {
     // Get relationships
     pub async fn get_relationships(&self, params: Value) -> Result<Value> {
         let url = format!("{}/v1/relationships", self.base_url);
-        let response = self.client.get(&url).query(&params).send().await?;
+        let response = self
+            .send(true, || self.client.get(&url).query(&params))
+            .await?;
         let data = response.json().await?;
         Ok(data)
     }
@@ -86,7 +364,7 @@ impl AmpClient {
     pub async fn get_file_log(&self, path: &str) -> Result<Value> {
         let encoded = urlencoding::encode(path);
         let url = format!("{}/v1/codebase/file-log-objects/{}", self.base_url, encoded);
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(true, || self.client.get(&url)).await?;
 
         let status = response.status();
 
@@ -95,22 +373,21 @@ impl AmpClient {
             return Ok(data);
         }
 
-        // Handle 409 Conflict (ambiguous path) as a successful response with file list
-        if status.as_u16() == 409 {
-            let error_data: Value = response.json().await?;
-            // Transform the error into an informative success response
-            return Ok(serde_json::json!({
-                "status": "ambiguous",
-                "message": error_data.get("error").and_then(|v| v.as_str()).unwrap_or("Multiple files match"),
-                "input_path": error_data.get("input_path"),
-                "matching_files": error_data.get("matching_files"),
-                "hint": error_data.get("hint").and_then(|v| v.as_str()).unwrap_or("Please use a more specific path (e.g., include parent directory)")
-            }));
+        // 409 Conflict (ambiguous path) carries the matching files in its body
+        if status == StatusCode::CONFLICT {
+            return Err(ApiError::from_response("get_file_log", response)
+                .await
+                .into());
         }
 
         // Only fall back for other errors (404, 500, etc.)
         let fallback_url = format!("{}/v1/codebase/file-logs/{}", self.base_url, encoded);
-        let response = self.client.get(&fallback_url).send().await?;
+        let response = self.send(true, || self.client.get(&fallback_url)).await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("get_file_log", response)
+                .await
+                .into());
+        }
         let data = response.json().await?;
         Ok(data)
     }
@@ -118,31 +395,43 @@ impl AmpClient {
     // Update file log
     pub async fn update_file_log(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/codebase/update-file-log", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    /// Regenerate a stored FileLog's AI summary from the file on disk
+    pub async fn regenerate_file_log(&self, path: &str) -> Result<Value> {
+        let url = format!("{}/v1/codebase/regenerate-ai-log", self.base_url);
+        let payload = serde_json::json!({ "path": path });
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("regenerate_file_log", response)
+                .await
+                .into());
+        }
         let data = response.json().await?;
         Ok(data)
     }
 
     // Get stored file content from FileChunk objects
-    pub async fn get_file_content(&self, path: &str, max_chars: Option<usize>) -> Result<Value> {
+    pub async fn get_file_content(&self, path: &str, params: &Value) -> Result<Value> {
         let encoded = urlencoding::encode(path);
-        let mut url = format!("{}/v1/codebase/file-contents/{}", self.base_url, encoded);
-        if let Some(limit) = max_chars {
-            url = format!("{}?max_chars={}", url, limit);
-        }
-        let response = self.client.get(&url).send().await?;
+        let url = format!("{}/v1/codebase/file-contents/{}", self.base_url, encoded);
+        let response = self
+            .send(true, || self.client.get(&url).query(params))
+            .await?;
         let status = response.status();
 
-        // Handle 409 Conflict (ambiguous path) as a successful response with file list
-        if status.as_u16() == 409 {
-            let error_data: Value = response.json().await?;
-            return Ok(serde_json::json!({
-                "status": "ambiguous",
-                "message": error_data.get("error").and_then(|v| v.as_str()).unwrap_or("Multiple files match"),
-                "input_path": error_data.get("input_path"),
-                "matching_files": error_data.get("matching_files"),
-                "hint": error_data.get("hint").and_then(|v| v.as_str()).unwrap_or("Please use a more specific path (e.g., include parent directory)")
-            }));
+        // 409 Conflict (ambiguous path) carries the matching files in its body
+        if status == StatusCode::CONFLICT {
+            return Err(ApiError::from_response("get_file_content", response)
+                .await
+                .into());
         }
 
         let data = response.json().await?;
@@ -152,7 +441,9 @@ impl AmpClient {
     // Acquire lease
     pub async fn acquire_lease(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/leases/acquire", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         let data = response.json().await?;
         Ok(data)
     }
@@ -160,7 +451,9 @@ impl AmpClient {
     // Release lease
     pub async fn release_lease(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/leases/release", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         let data = response.json().await?;
         Ok(data)
     }
@@ -168,7 +461,50 @@ impl AmpClient {
     // Write artifact
     pub async fn write_artifact(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/artifacts", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let key = uuid::Uuid::new_v4().to_string();
+        let response = self
+            .send(true, || {
+                self.client
+                    .post(&url)
+                    .header(IDEMPOTENCY_KEY_HEADER, &key)
+                    .json(&payload)
+            })
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("write_artifact", response)
+                .await
+                .into());
+        }
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    // List artifacts
+    pub async fn list_artifacts(&self, params: Value) -> Result<Value> {
+        let url = format!("{}/v1/artifacts", self.base_url);
+        let response = self
+            .send(true, || self.client.get(&url).query(&params))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("list_artifacts", response)
+                .await
+                .into());
+        }
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    // Update task
+    pub async fn update_task(&self, id: &str, payload: Value) -> Result<Value> {
+        let url = format!("{}/v1/tasks/{}", self.base_url, id);
+        let response = self
+            .send(false, || self.client.patch(&url).json(&payload))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("update_task", response)
+                .await
+                .into());
+        }
         let data = response.json().await?;
         Ok(data)
     }
@@ -176,7 +512,9 @@ impl AmpClient {
     // Cache get pack
     pub async fn cache_get_pack(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/cache/pack", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         let data = response.json().await?;
         Ok(data)
     }
@@ -184,7 +522,9 @@ impl AmpClient {
     // Cache write items
     pub async fn cache_write_items(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/cache/write", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         let data = response.json().await?;
         Ok(data)
     }
@@ -192,7 +532,9 @@ impl AmpClient {
     // File sync - synchronize file state across all memory layers
     pub async fn file_sync(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/codebase/sync", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         let status = response.status();
 
         if status.is_success() {
@@ -200,30 +542,24 @@ impl AmpClient {
             return Ok(data);
         }
 
-        // Handle 409 Conflict (ambiguous path) as a successful response with file list
-        if status.as_u16() == 409 {
-            let error_data: Value = response.json().await?;
-            return Ok(serde_json::json!({
-                "status": "ambiguous",
-                "message": error_data.get("error").and_then(|v| v.as_str()).unwrap_or("Multiple files match"),
-                "input_path": error_data.get("input_path"),
-                "matching_files": error_data.get("matching_files"),
-                "hint": error_data.get("hint").and_then(|v| v.as_str()).unwrap_or("Please use a more specific path (e.g., include parent directory)")
-            }));
+        // 409 Conflict (ambiguous path) carries the matching files in its body
+        if status == StatusCode::CONFLICT {
+            return Err(ApiError::from_response("file_sync", response).await.into());
         }
 
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("file_sync failed ({}): {}", status, body);
+        Err(ApiError::from_response("file_sync", response).await.into())
     }
 
     // Cache block operations for episodic memory
     pub async fn cache_block_write(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/cache/block/write", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("cache_block_write failed ({}): {}", status, body);
+            return Err(ApiError::from_response("cache_block_write", response)
+                .await
+                .into());
         }
         let data = response.json().await?;
         Ok(data)
@@ -231,11 +567,13 @@ impl AmpClient {
 
     pub async fn cache_block_compact(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/cache/block/compact", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("cache_block_compact failed ({}): {}", status, body);
+            return Err(ApiError::from_response("cache_block_compact", response)
+                .await
+                .into());
         }
         let data = response.json().await?;
         Ok(data)
@@ -243,11 +581,13 @@ impl AmpClient {
 
     pub async fn cache_block_search(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/cache/block/search", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("cache_block_search failed ({}): {}", status, body);
+            return Err(ApiError::from_response("cache_block_search", response)
+                .await
+                .into());
         }
         let data = response.json().await?;
         Ok(data)
@@ -255,11 +595,11 @@ impl AmpClient {
 
     pub async fn cache_block_get(&self, block_id: &str) -> Result<Value> {
         let url = format!("{}/v1/cache/block/{}", self.base_url, block_id);
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(true, || self.client.get(&url)).await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("cache_block_get failed ({}): {}", status, body);
+            return Err(ApiError::from_response("cache_block_get", response)
+                .await
+                .into());
         }
         let data = response.json().await?;
         Ok(data)
@@ -268,7 +608,7 @@ impl AmpClient {
     pub async fn cache_block_current(&self, scope_id: &str) -> Result<Option<Value>> {
         let encoded = urlencoding::encode(scope_id);
         let url = format!("{}/v1/cache/block/current/{}", self.base_url, encoded);
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(true, || self.client.get(&url)).await?;
         let status = response.status();
 
         if status.is_success() {
@@ -276,12 +616,41 @@ impl AmpClient {
             return Ok(Some(data));
         }
 
-        if status.as_u16() == 404 {
+        if status == StatusCode::NOT_FOUND {
             return Ok(None);
         }
 
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("cache_block_current failed ({}): {}", status, body);
+        Err(ApiError::from_response("cache_block_current", response)
+            .await
+            .into())
+    }
+
+    // Focus transitions and timeline for a run
+    pub async fn focus(&self, payload: Value) -> Result<Value> {
+        let url = format!("{}/v1/focus", self.base_url);
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("focus", response).await.into());
+        }
+        let data = response.json().await?;
+        Ok(data)
+    }
+
+    /// List active focus sessions across agents
+    pub async fn focus_sessions(&self, params: Value) -> Result<Value> {
+        let url = format!("{}/v1/focus/sessions", self.base_url);
+        let response = self
+            .send(true, || self.client.get(&url).query(&params))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("focus_sessions", response)
+                .await
+                .into());
+        }
+        let data = response.json().await?;
+        Ok(data)
     }
 
     // Connection tracking endpoints
@@ -289,11 +658,13 @@ impl AmpClient {
     /// Register a new agent connection
     pub async fn register_connection(&self, payload: Value) -> Result<Value> {
         let url = format!("{}/v1/connections/register", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("register_connection failed ({}): {}", status, body);
+            return Err(ApiError::from_response("register_connection", response)
+                .await
+                .into());
         }
         let data = response.json().await?;
         Ok(data)
@@ -302,11 +673,13 @@ impl AmpClient {
     /// Send heartbeat to keep connection alive
     pub async fn connection_heartbeat(&self, payload: Value) -> Result<()> {
         let url = format!("{}/v1/connections/heartbeat", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("connection_heartbeat failed ({}): {}", status, body);
+            return Err(ApiError::from_response("connection_heartbeat", response)
+                .await
+                .into());
         }
         Ok(())
     }
@@ -314,25 +687,143 @@ impl AmpClient {
     /// Disconnect the connection
     pub async fn disconnect_connection(&self, payload: Value) -> Result<()> {
         let url = format!("{}/v1/connections/disconnect", self.base_url);
-        let response = self.client.post(&url).json(&payload).send().await?;
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("disconnect_connection failed ({}): {}", status, body);
+            return Err(ApiError::from_response("disconnect_connection", response)
+                .await
+                .into());
         }
         Ok(())
     }
 
+    /// Release the connection at session end, finishing its run and closing its cache blocks
+    pub async fn release_connection(&self, payload: Value) -> Result<Value> {
+        let url = format!("{}/v1/connections/release", self.base_url);
+        let response = self
+            .send(false, || self.client.post(&url).json(&payload))
+            .await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("release_connection", response)
+                .await
+                .into());
+        }
+        let data = response.json().await?;
+        Ok(data)
+    }
+
     /// List active connections
     pub async fn list_connections(&self) -> Result<Value> {
         let url = format!("{}/v1/connections", self.base_url);
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(true, || self.client.get(&url)).await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("list_connections failed ({}): {}", status, body);
+            return Err(ApiError::from_response("list_connections", response)
+                .await
+                .into());
         }
         let data = response.json().await?;
         Ok(data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::error::AmpToolError;
+    use axum::{extract::State, http, routing::get, Json, Router};
+    use std::sync::Mutex;
+
+    /// Idempotency keys seen by the mock, one entry per request
+    type Seen = Arc<Mutex<Vec<Option<String>>>>;
+
+    /// Answers 503 to the first two requests, as a restarting server would
+    async fn flaky(
+        State(seen): State<Seen>,
+        headers: http::HeaderMap,
+    ) -> (http::StatusCode, Json<Value>) {
+        let mut seen = seen.lock().unwrap();
+        seen.push(
+            headers
+                .get(IDEMPOTENCY_KEY_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+        );
+        if seen.len() <= 2 {
+            return (http::StatusCode::SERVICE_UNAVAILABLE, Json(Value::Null));
+        }
+        (
+            http::StatusCode::OK,
+            Json(serde_json::json!({ "id": "obj-1" })),
+        )
+    }
+
+    async fn spawn_flaky(seen: Seen) -> String {
+        let app = Router::new()
+            .route("/v1/objects", axum::routing::post(flaky))
+            .route("/v1/objects/{id}", get(flaky))
+            .with_state(seen);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_and_creates_retry_through_unavailable_responses() {
+        let seen: Seen = Arc::default();
+        let client = AmpClient::new(spawn_flaky(seen.clone()).await, 5)
+            .unwrap()
+            .with_retry(fast_retry());
+        assert_eq!(client.get_object("obj-1").await.unwrap()["id"], "obj-1");
+        assert_eq!(seen.lock().unwrap().len(), 3);
+
+        let seen: Seen = Arc::default();
+        let client = AmpClient::new(spawn_flaky(seen.clone()).await, 5)
+            .unwrap()
+            .with_retry(fast_retry());
+        client
+            .create_object(serde_json::json!({ "type": "note" }))
+            .await
+            .unwrap();
+        let keys = seen.lock().unwrap().clone();
+        assert_eq!(keys.len(), 3);
+        assert!(keys[0].is_some());
+        assert!(keys.iter().all(|key| *key == keys[0]), "{:?}", keys);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_fails_fast_after_consecutive_failures() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let client = AmpClient::new(format!("http://127.0.0.1:{}", port), 5)
+            .unwrap()
+            .with_retry(RetryPolicy {
+                max_attempts: 1,
+                ..fast_retry()
+            })
+            .with_breaker(2, Duration::from_secs(60));
+
+        for _ in 0..2 {
+            let err = client.get_object("obj-1").await.unwrap_err();
+            assert!(err.downcast_ref::<reqwest::Error>().is_some(), "{}", err);
+        }
+        let err = AmpToolError::from(client.get_object("obj-1").await.unwrap_err());
+        assert_eq!(err.kind(), "server_unavailable");
+        assert!(err.message().contains("backing off"), "{}", err);
+        assert_eq!(
+            client.breaker_status().state,
+            crate::retry::BreakerState::Open
+        );
+    }
+}
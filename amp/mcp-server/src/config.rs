@@ -5,6 +5,13 @@ use std::env;
 pub struct Config {
     pub amp_server_url: String,
     pub amp_server_timeout: u64,
+    /// Attempts per read or create call, including the first
+    pub amp_retry_attempts: u32,
+    /// Consecutive failed calls before the client stops calling the server
+    pub amp_breaker_threshold: u32,
+    pub amp_breaker_cooldown_secs: u64,
+    /// Edge types amp_relationship_create accepts
+    pub relationship_types: Vec<String>,
     pub server_name: String,
     pub server_version: String,
 }
@@ -20,6 +27,29 @@ impl Config {
                 .unwrap_or_else(|_| "30".to_string())
                 .parse()
                 .context("Invalid AMP_SERVER_TIMEOUT")?,
+            amp_retry_attempts: env::var("AMP_RETRY_ATTEMPTS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .context("Invalid AMP_RETRY_ATTEMPTS")?,
+            amp_breaker_threshold: env::var("AMP_BREAKER_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .context("Invalid AMP_BREAKER_THRESHOLD")?,
+            amp_breaker_cooldown_secs: env::var("AMP_BREAKER_COOLDOWN_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .context("Invalid AMP_BREAKER_COOLDOWN_SECS")?,
+            relationship_types: match env::var("AMP_RELATIONSHIP_TYPES") {
+                Ok(types) => types
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect(),
+                Err(_) => crate::tools::memory::RELATIONSHIP_TYPES
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect(),
+            },
             server_name: env::var("AMP_AGENT_NAME")
                 .or_else(|_| env::var("AGENT_NAME"))
                 .or_else(|_| env::var("MCP_SERVER_NAME"))
@@ -6,28 +6,106 @@ use rmcp::model::{
 use rmcp::service::{RequestContext, RoleServer, ServiceExt};
 use rmcp::ErrorData as McpError;
 use rmcp::ServerHandler;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{OnceCell, RwLock};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod amp_client;
 mod config;
+mod retry;
 mod tools;
 
 use amp_client::AmpClient;
 use config::Config;
+use retry::RetryPolicy;
 
 /// Connection state tracked per MCP session
 #[derive(Debug, Clone, Default)]
 struct ConnectionState {
     /// Connection ID returned from register
     connection_id: Option<String>,
-    /// Current run ID (updated when amp_run_start is called)
+    /// Current run ID (replaced when amp_run starts a new run)
     run_id: Option<String>,
     /// Project ID derived from working directory
     project_id: Option<String>,
-    /// Whether we've registered with the server
-    registered: bool,
+    /// Agent label used for run provenance (e.g. "claude-code-1a2b")
+    agent_label: Option<String>,
+}
+
+/// Releases the session once the last handler for it is dropped (or the stdio
+/// transport stops): finishes the run, drops the connection and closes the run's
+/// open cache block. Best-effort: explicitly finished runs are left untouched
+/// and errors are only logged.
+struct SessionGuard {
+    client: Arc<AmpClient>,
+    connection_state: Arc<RwLock<ConnectionState>>,
+    /// Tool calls started but not finished; a call cut off by the client going
+    /// away never finishes, so a non-zero count at shutdown marks the run cancelled
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl SessionGuard {
+    /// Take the run and connection out of the session state so only the first
+    /// caller releases them
+    fn take_release_payload(&self, state: &mut ConnectionState) -> Option<serde_json::Value> {
+        let connection_id = state.connection_id.take();
+        let run_id = state.run_id.take();
+        if connection_id.is_none() && run_id.is_none() {
+            return None;
+        }
+        let run_status = if self.in_flight.load(Ordering::SeqCst) > 0 {
+            tools::memory::RunStatus::Cancelled
+        } else {
+            tools::memory::RunStatus::Completed
+        };
+        Some(serde_json::json!({
+            "connection_id": connection_id,
+            "run_id": run_id,
+            "run_status": run_status.as_str(),
+            "summary": "MCP session ended"
+        }))
+    }
+
+    async fn release(&self) {
+        let payload = self.take_release_payload(&mut *self.connection_state.write().await);
+        if let Some(payload) = payload {
+            release_session(&self.client, payload).await;
+        }
+    }
+}
+
+async fn release_session(client: &AmpClient, payload: serde_json::Value) {
+    match client.release_connection(payload).await {
+        Ok(result) => tracing::info!("Released MCP session on shutdown: {}", result),
+        Err(e) => tracing::debug!("Failed to release session (non-fatal): {}", e),
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        let payload = match self.connection_state.try_write() {
+            Ok(mut state) => self.take_release_payload(&mut state),
+            Err(_) => None,
+        };
+        let Some(payload) = payload else {
+            return;
+        };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let client = self.client.clone();
+        let task = async move { release_session(&client, payload).await };
+
+        // Block on the multi-threaded runtime so the request finishes before the
+        // process exits; otherwise fall back to a detached task.
+        if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread {
+            tokio::task::block_in_place(|| handle.block_on(task));
+        } else {
+            handle.spawn(task);
+        }
+    }
 }
 
 /// Extract project name from a scope_id like "project:myrepo" → Some("myrepo")
@@ -44,6 +122,104 @@ struct AmpMcpHandler {
     config: Arc<Config>,
     /// Shared connection state for this handler
     connection_state: Arc<RwLock<ConnectionState>>,
+    /// Set once the session has registered, so repeated or concurrent
+    /// `list_tools` calls never register twice
+    registration: Arc<OnceCell<()>>,
+    /// Releases the session once every clone of this handler is gone
+    session_guard: Arc<SessionGuard>,
+}
+
+impl AmpMcpHandler {
+    fn new(client: Arc<AmpClient>, config: Arc<Config>) -> Self {
+        let connection_state = Arc::new(RwLock::new(ConnectionState::default()));
+        let session_guard = Arc::new(SessionGuard {
+            client: client.clone(),
+            connection_state: connection_state.clone(),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        });
+        Self {
+            client,
+            config,
+            connection_state,
+            registration: Arc::new(OnceCell::new()),
+            session_guard,
+        }
+    }
+
+    /// Release the session now rather than waiting for the last handler to drop
+    async fn shutdown(&self) {
+        self.session_guard.release().await;
+    }
+
+    /// Register this session with the server on first use; later and
+    /// concurrent callers wait for that one registration to finish.
+    async fn ensure_registered(&self, client_label: Option<String>) {
+        self.registration
+            .get_or_init(|| self.register_session(client_label))
+            .await;
+    }
+
+    /// Auto-create the session run and register the connection. Failures are
+    /// tolerated: the session still works, it just won't show up in the UI.
+    async fn register_session(&self, client_label: Option<String>) {
+        let agent_id = format!(
+            "mcp-{}",
+            uuid::Uuid::new_v4()
+                .to_string()
+                .split('-')
+                .next()
+                .unwrap_or("unknown")
+        );
+        let agent_suffix = agent_id.split('-').nth(1).unwrap_or("unknown").to_string();
+
+        // Prefer explicit AMP_AGENT_NAME for UI labeling, fall back to client metadata.
+        let base_label = std::env::var("AMP_AGENT_NAME")
+            .ok()
+            .filter(|label| !label.trim().is_empty())
+            .or(client_label)
+            .unwrap_or_else(|| self.config.server_name.clone());
+        // Ensure uniqueness per connection by appending a short suffix.
+        let agent_label = format!("{}-{}", base_label, agent_suffix);
+        self.connection_state.write().await.agent_label = Some(agent_label.clone());
+
+        // Auto-create a run so the session appears in the UI immediately
+        let run_payload = serde_json::json!({
+            "type": "run",
+            "input_summary": format!("{} session", agent_label),
+            "status": "running",
+            "provenance": {
+                "agent": agent_label.clone(),
+                "summary": "MCP session auto-created on connect"
+            }
+        });
+
+        if let Ok(run_response) = self.client.create_object(run_payload).await {
+            if let Some(run_id) = run_response.get("id").and_then(|v| v.as_str()) {
+                let clean_run_id = run_id.trim_start_matches("objects:").to_string();
+                self.connection_state.write().await.run_id = Some(clean_run_id.clone());
+                tracing::info!("Auto-created run for MCP session: {}", clean_run_id);
+
+                let register_payload = serde_json::json!({
+                    "agent_id": agent_id,
+                    "agent_name": agent_label,
+                    "run_id": clean_run_id,
+                    "ttl_seconds": 600
+                });
+
+                if let Ok(response) = self.client.register_connection(register_payload).await {
+                    if let Some(conn_id) = response.get("connection_id").and_then(|v| v.as_str()) {
+                        self.connection_state.write().await.connection_id =
+                            Some(conn_id.to_string());
+                        tracing::info!(
+                            "Registered connection on handshake: {} -> run: {}",
+                            conn_id,
+                            clean_run_id
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl ServerHandler for AmpMcpHandler {
@@ -76,97 +252,35 @@ impl ServerHandler for AmpMcpHandler {
         use std::sync::Arc;
 
         // === Register connection on handshake (list_tools is called right after init) ===
-        {
-            let mut state = self.connection_state.write().await;
-            if !state.registered {
-                let agent_id = format!(
-                    "mcp-{}",
-                    uuid::Uuid::new_v4()
-                        .to_string()
-                        .split('-')
-                        .next()
-                        .unwrap_or("unknown")
-                );
-                let agent_suffix = agent_id
-                    .split('-')
-                    .nth(1)
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                let meta_label = context
+        let meta_label = context
+            .meta
+            .0
+            .get("agentName")
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+            .or_else(|| {
+                context
                     .meta
                     .0
-                    .get("agentName")
+                    .get("agent_name")
                     .and_then(|value| value.as_str())
                     .map(|value| value.to_string())
-                    .or_else(|| {
-                        context
-                            .meta
-                            .0
-                            .get("agent_name")
-                            .and_then(|value| value.as_str())
-                            .map(|value| value.to_string())
-                    });
-
-                let client_label = meta_label
-                    .or_else(|| {
-                        context
-                            .peer
-                            .peer_info()
-                            .and_then(|info| info.client_info.title.clone())
-                    })
-                    .or_else(|| {
-                        context
-                            .peer
-                            .peer_info()
-                            .map(|info| info.client_info.name.clone())
-                    })
-                    .filter(|label| !label.trim().is_empty());
-
-                // Prefer explicit AMP_AGENT_NAME for UI labeling, fall back to client metadata.
-                let base_label = std::env::var("AMP_AGENT_NAME")
-                    .ok()
-                    .filter(|label| !label.trim().is_empty())
-                    .or(client_label)
-                    .unwrap_or_else(|| self.config.server_name.clone());
-                // Ensure uniqueness per connection by appending a short suffix.
-                let agent_label = format!("{}-{}", base_label, agent_suffix);
-
-                // Auto-create a run so the session appears in the UI immediately
-                let run_payload = serde_json::json!({
-                    "type": "run",
-                    "input_summary": format!("{} session", agent_label),
-                    "status": "running",
-                    "provenance": {
-                        "agent": agent_label.clone(),
-                        "summary": "MCP session auto-created on connect"
-                    }
-                });
-
-                if let Ok(run_response) = self.client.create_object(run_payload).await {
-                    if let Some(run_id) = run_response.get("id").and_then(|v| v.as_str()) {
-                        let clean_run_id = run_id.trim_start_matches("objects:").to_string();
-                        state.run_id = Some(clean_run_id.clone());
-                        tracing::info!("Auto-created run for MCP session: {}", clean_run_id);
-
-                        let register_payload = serde_json::json!({
-                            "agent_id": agent_id,
-                            "agent_name": agent_label,
-                            "run_id": clean_run_id,
-                            "ttl_seconds": 600
-                        });
-
-                        if let Ok(response) = self.client.register_connection(register_payload).await {
-                            if let Some(conn_id) = response.get("connection_id").and_then(|v| v.as_str()) {
-                                state.connection_id = Some(conn_id.to_string());
-                                tracing::info!("Registered connection on handshake: {} -> run: {}", conn_id, clean_run_id);
-                            }
-                        }
-                    }
-                }
-                state.registered = true;
-            }
-        }
+            });
+        let client_label = meta_label
+            .or_else(|| {
+                context
+                    .peer
+                    .peer_info()
+                    .and_then(|info| info.client_info.title.clone())
+            })
+            .or_else(|| {
+                context
+                    .peer
+                    .peer_info()
+                    .map(|info| info.client_info.name.clone())
+            })
+            .filter(|label| !label.trim().is_empty());
+        self.ensure_registered(client_label).await;
 
         // Helper to convert schema to Arc<Map> (schemars 1.0 API)
         let to_schema =
@@ -211,7 +325,7 @@ impl ServerHandler for AmpMcpHandler {
                 },
                 Tool {
                     name: "amp_query".into(),
-                    description: Some("Search AMP memory with hybrid retrieval".into()),
+                    description: Some("Search AMP memory with hybrid retrieval (optionally filtered by type, project and creation date), or find objects similar to an object ID".into()),
                     input_schema: to_schema(schemars::schema_for!(tools::query::AmpQueryInput)),
                     annotations: None,
                     icons: None,
@@ -221,7 +335,7 @@ impl ServerHandler for AmpMcpHandler {
                 },
                 Tool {
                     name: "amp_trace".into(),
-                    description: Some("Trace object provenance and relationships".into()),
+                    description: Some("Trace object provenance and relationships, the shortest path to another object, neighbors shared with other objects, or an object's version history".into()),
                     input_schema: to_schema(schemars::schema_for!(tools::query::AmpTraceInput)),
                     annotations: None,
                     icons: None,
@@ -229,10 +343,22 @@ impl ServerHandler for AmpMcpHandler {
                     title: None,
                     output_schema: None,
                 },
+                Tool {
+                    name: "amp_path".into(),
+                    description: Some(
+                        "Find the shortest relationship path between two objects (edges followed in either direction)".into(),
+                    ),
+                    input_schema: to_schema(schemars::schema_for!(tools::query::AmpPathInput)),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
                 Tool {
                     name: "amp_write_artifact".into(),
                     description: Some(
-                        "Write artifact (decision, changeset, note, filelog) to all memory layers with graph relationships".into(),
+                        "Write artifact (decision, changeset, note, warning, filelog) to all memory layers with graph relationships".into(),
                     ),
                     input_schema: to_schema(schemars::schema_for!(
                         tools::memory::AmpWriteArtifactInput
@@ -243,9 +369,77 @@ impl ServerHandler for AmpMcpHandler {
                     title: None,
                     output_schema: None,
                 },
+                Tool {
+                    name: "amp_decision_create".into(),
+                    description: Some(
+                        "Record an architectural decision (title, problem, rationale, outcome, options) with session provenance; optionally link a run via justified_by".into(),
+                    ),
+                    input_schema: to_schema(schemars::schema_for!(
+                        tools::memory::AmpDecisionCreateInput
+                    )),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
+                Tool {
+                    name: "amp_changeset_create".into(),
+                    description: Some(
+                        "Record a code change (title, files_changed, diff, commit_hash) with session provenance; links a run via produced and changed files via modifies".into(),
+                    ),
+                    input_schema: to_schema(schemars::schema_for!(
+                        tools::memory::AmpChangesetCreateInput
+                    )),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
+                Tool {
+                    name: "amp_relationship_create".into(),
+                    description: Some(
+                        "Explicitly link two memory objects (defined_in, depends_on, calls, justified_by, modifies, implements, produced, supersedes)".into(),
+                    ),
+                    input_schema: to_schema(schemars::schema_for!(
+                        tools::memory::AmpRelationshipCreateInput
+                    )),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
+                Tool {
+                    name: "amp_run_complete".into(),
+                    description: Some(
+                        "Deprecated: use amp_run with action complete, fail or cancel. Mark a run as finished (completed, failed, cancelled). Defaults to the current session run.".into(),
+                    ),
+                    input_schema: to_schema(schemars::schema_for!(
+                        tools::memory::AmpRunCompleteInput
+                    )),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
+                Tool {
+                    name: "amp_run".into(),
+                    description: Some(
+                        "Run lifecycle: start a named run (becomes the session run), complete it with an outputs summary and confidence, fail it with an error, or cancel it. Finishing a run closes its open cache block.".into(),
+                    ),
+                    input_schema: to_schema(schemars::schema_for!(tools::runs::AmpRunInput)),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
                 Tool {
                     name: "amp_focus".into(),
-                    description: Some("Manage agent focus/session state (list, get, set, complete, end)".into()),
+                    description: Some("Manage agent focus/session state (list, get, set, complete, end, history)".into()),
                     input_schema: to_schema(schemars::schema_for!(tools::focus::AmpFocusInput)),
                     annotations: None,
                     icons: None,
@@ -253,6 +447,30 @@ impl ServerHandler for AmpMcpHandler {
                     title: None,
                     output_schema: None,
                 },
+                Tool {
+                    name: "amp_task".into(),
+                    description: Some(
+                        "Track outstanding work as tasks (create, list by status, update status/assignee/due). A done task can only be reopened.".into(),
+                    ),
+                    input_schema: to_schema(schemars::schema_for!(tools::tasks::AmpTaskInput)),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
+                Tool {
+                    name: "amp_filelog_regenerate".into(),
+                    description: Some("Regenerate an indexed file's AI summary (FileLog) from its current content using the index model".into()),
+                    input_schema: to_schema(schemars::schema_for!(
+                        tools::files::AmpFilelogRegenerateInput
+                    )),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
                 Tool {
                     name: "amp_filelog_get".into(),
                     description: Some("Get file log with symbols and dependencies".into()),
@@ -281,7 +499,10 @@ impl ServerHandler for AmpMcpHandler {
                 },
                 Tool {
                     name: "amp_file_content_get".into(),
-                    description: Some("Get stored file content from indexed chunks".into()),
+                    description: Some(
+                        "Get stored file content from indexed chunks, optionally a line range or one symbol's definition"
+                            .into(),
+                    ),
                     input_schema: to_schema(schemars::schema_for!(
                         tools::files::AmpFileContentGetInput
                     )),
@@ -303,10 +524,24 @@ impl ServerHandler for AmpMcpHandler {
                     title: None,
                     output_schema: None,
                 },
+                Tool {
+                    name: "amp_file_history".into(),
+                    description: Some(
+                        "Get a file's sync audit trail (action, summary, run, agent), newest first".into(),
+                    ),
+                    input_schema: to_schema(schemars::schema_for!(
+                        tools::files::AmpFileHistoryInput
+                    )),
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                    title: None,
+                    output_schema: None,
+                },
                 Tool {
                     name: "amp_cache_write".into(),
                     description: Some(
-                        "Write to episodic cache (fact/decision/snippet/warning). Auto-closes block at ~1800 tokens.".into(),
+                        "Write to episodic cache (fact/decision/snippet/warning); pass items to write several in one call. Auto-closes block at ~1800 tokens.".into(),
                     ),
                     input_schema: to_schema(schemars::schema_for!(
                         tools::cache::AmpCacheWriteInput
@@ -346,6 +581,7 @@ impl ServerHandler for AmpMcpHandler {
                     output_schema: None,
                 },
             ],
+            // Every tool fits in one page; a cursor from the client gets the same full list
             next_cursor: None,
             meta: None,
         })
@@ -355,11 +591,24 @@ impl ServerHandler for AmpMcpHandler {
         &self,
         params: CallToolRequestParam,
         _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        // Not decremented when the call is cancelled, so shutdown sees the interruption
+        let in_flight = &self.session_guard.in_flight;
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        let result = self.dispatch_tool(params).await;
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+        result
+    }
+}
+
+impl AmpMcpHandler {
+    async fn dispatch_tool(
+        &self,
+        params: CallToolRequestParam,
     ) -> Result<CallToolResult, McpError> {
         let client = &self.client;
 
         // Helper to convert errors - use String directly
-        let to_internal_error = |e: anyhow::Error| McpError::internal_error(e.to_string(), None);
         let to_invalid_params =
             |e: serde_json::Error| McpError::invalid_params(e.to_string(), None);
 
@@ -441,41 +690,139 @@ impl ServerHandler for AmpMcpHandler {
             }
         }
 
-        let contents = match params.name.as_ref() {
-            "amp_status" => tools::discovery::handle_amp_status(client)
-                .await
-                .map_err(to_internal_error)?,
+        let result = match params.name.as_ref() {
+            "amp_status" => tools::discovery::handle_amp_status(client).await,
             "amp_list" => {
                 let input: tools::discovery::AmpListInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::discovery::handle_amp_list(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::discovery::handle_amp_list(client, input).await
             }
             "amp_query" => {
                 let input: tools::query::AmpQueryInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::query::handle_amp_query(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::query::handle_amp_query(client, input).await
             }
             "amp_trace" => {
                 let input: tools::query::AmpTraceInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::query::handle_amp_trace(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::query::handle_amp_trace(client, input).await
+            }
+            "amp_path" => {
+                let input: tools::query::AmpPathInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                tools::query::handle_amp_path(client, input).await
             }
             "amp_write_artifact" => {
                 let input: tools::memory::AmpWriteArtifactInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::memory::handle_write_artifact(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::memory::handle_write_artifact(client, input).await
+            }
+            "amp_decision_create" => {
+                let input: tools::memory::AmpDecisionCreateInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                let (agent_label, project_id) = {
+                    let state = self.connection_state.read().await;
+                    (
+                        state
+                            .agent_label
+                            .clone()
+                            .unwrap_or_else(|| self.config.server_name.clone()),
+                        state.project_id.clone(),
+                    )
+                };
+                tools::memory::handle_decision_create(
+                    client,
+                    &agent_label,
+                    project_id.as_deref(),
+                    input,
+                )
+                .await
+            }
+            "amp_relationship_create" => {
+                let input: tools::memory::AmpRelationshipCreateInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                let project_id = {
+                    let state = self.connection_state.read().await;
+                    state.project_id.clone()
+                };
+                tools::memory::handle_relationship_create(
+                    client,
+                    project_id.as_deref(),
+                    &self.config.relationship_types,
+                    input,
+                )
+                .await
+            }
+            "amp_changeset_create" => {
+                let input: tools::memory::AmpChangesetCreateInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                let (agent_label, project_id) = {
+                    let state = self.connection_state.read().await;
+                    (
+                        state
+                            .agent_label
+                            .clone()
+                            .unwrap_or_else(|| self.config.server_name.clone()),
+                        state.project_id.clone(),
+                    )
+                };
+                tools::memory::handle_changeset_create(
+                    client,
+                    &agent_label,
+                    project_id.as_deref(),
+                    input,
+                )
+                .await
+            }
+            "amp_run_complete" => {
+                let input: tools::memory::AmpRunCompleteInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                let run_id = {
+                    let state = self.connection_state.read().await;
+                    state.run_id.clone()
+                };
+                tools::memory::handle_run_complete(client, run_id.as_deref(), input).await
+            }
+            "amp_run" => {
+                let input: tools::runs::AmpRunInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                let (agent_label, project_id, run_id) = {
+                    let state = self.connection_state.read().await;
+                    (
+                        state
+                            .agent_label
+                            .clone()
+                            .unwrap_or_else(|| self.config.server_name.clone()),
+                        state.project_id.clone(),
+                        state.run_id.clone(),
+                    )
+                };
+                let outcome = tools::runs::handle_run(
+                    client,
+                    &agent_label,
+                    project_id.as_deref(),
+                    run_id.as_deref(),
+                    input,
+                )
+                .await;
+                if let Ok(tools::runs::RunOutcome {
+                    started_run_id: Some(started),
+                    ..
+                }) = &outcome
+                {
+                    self.connection_state.write().await.run_id = Some(started.clone());
+                }
+                outcome.map(|outcome| outcome.content)
             }
             "amp_focus" => {
                 let input: tools::focus::AmpFocusInput =
@@ -485,41 +832,59 @@ impl ServerHandler for AmpMcpHandler {
                     let state = self.connection_state.read().await;
                     state.run_id.clone()
                 };
-                tools::focus::handle_focus(client, run_id.as_deref(), input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::focus::handle_focus(client, run_id.as_deref(), input).await
+            }
+            "amp_task" => {
+                let input: tools::tasks::AmpTaskInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                let (agent_label, project_id) = {
+                    let state = self.connection_state.read().await;
+                    (
+                        state
+                            .agent_label
+                            .clone()
+                            .unwrap_or_else(|| self.config.server_name.clone()),
+                        state.project_id.clone(),
+                    )
+                };
+                tools::tasks::handle_task(client, &agent_label, project_id.as_deref(), input).await
             }
             "amp_filelog_get" => {
                 let input: tools::files::AmpFilelogGetInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::files::handle_filelog_get(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::files::handle_filelog_get(client, input).await
+            }
+            "amp_filelog_regenerate" => {
+                let input: tools::files::AmpFilelogRegenerateInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                tools::files::handle_filelog_regenerate(client, input).await
             }
             "amp_file_sync" => {
                 let input: tools::files::AmpFileSyncInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::files::handle_file_sync(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::files::handle_file_sync(client, input).await
             }
             "amp_file_content_get" => {
                 let input: tools::files::AmpFileContentGetInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::files::handle_file_content_get(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::files::handle_file_content_get(client, input).await
             }
             "amp_file_path_resolve" => {
                 let input: tools::files::AmpFilePathResolveInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::files::handle_file_path_resolve(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::files::handle_file_path_resolve(client, input).await
+            }
+            "amp_file_history" => {
+                let input: tools::files::AmpFileHistoryInput =
+                    serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
+                        .map_err(to_invalid_params)?;
+                tools::files::handle_file_history(client, input).await
             }
             "amp_cache_write" => {
                 let input: tools::cache::AmpCacheWriteInput =
@@ -529,9 +894,7 @@ impl ServerHandler for AmpMcpHandler {
                     let state = self.connection_state.read().await;
                     state.run_id.clone()
                 };
-                tools::cache::handle_cache_write(client, run_id.as_deref(), input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::cache::handle_cache_write(client, run_id.as_deref(), input).await
             }
             "amp_cache_compact" => {
                 let input: tools::cache::AmpCacheCompactInput =
@@ -541,17 +904,13 @@ impl ServerHandler for AmpMcpHandler {
                     let state = self.connection_state.read().await;
                     state.run_id.clone()
                 };
-                tools::cache::handle_cache_compact(client, run_id.as_deref(), input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::cache::handle_cache_compact(client, run_id.as_deref(), input).await
             }
             "amp_cache_read" => {
                 let input: tools::cache::AmpCacheReadInput =
                     serde_json::from_value(serde_json::to_value(params.arguments).unwrap())
                         .map_err(to_invalid_params)?;
-                tools::cache::handle_cache_read(client, input)
-                    .await
-                    .map_err(to_internal_error)?
+                tools::cache::handle_cache_read(client, input).await
             }
             _ => {
                 return Err(McpError::invalid_request(
@@ -561,7 +920,13 @@ impl ServerHandler for AmpMcpHandler {
             }
         };
 
-        Ok(CallToolResult::success(contents))
+        match result {
+            Ok(contents) => Ok(CallToolResult::success(contents)),
+            Err(err) => {
+                tracing::warn!("Tool {} failed: {}", params.name, err);
+                err.into_call_result()
+            }
+        }
     }
 }
 
@@ -574,11 +939,14 @@ async fn run_stdio_transport(handler: AmpMcpHandler) -> Result<()> {
     let transport = (stdin(), stdout());
 
     // Start server
+    let session = handler.clone();
     let server = handler.serve(transport).await?;
     tracing::info!("MCP server started (stdio)");
 
-    // Wait for shutdown
-    server.waiting().await?;
+    // Wait for shutdown, then end the run and release the connection
+    let quit = server.waiting().await;
+    session.shutdown().await;
+    quit?;
     tracing::info!("MCP server shutdown");
 
     Ok(())
@@ -606,11 +974,10 @@ async fn run_http_transport(handler: AmpMcpHandler, port: u16) -> Result<()> {
     let handler_base = handler.clone();
     let service = StreamableHttpService::new(
         move || {
-            Ok(AmpMcpHandler {
-                client: handler_base.client.clone(),
-                config: handler_base.config.clone(),
-                connection_state: Arc::new(RwLock::new(ConnectionState::default())),
-            })
+            Ok(AmpMcpHandler::new(
+                handler_base.client.clone(),
+                handler_base.config.clone(),
+            ))
         },
         session_manager,
         config,
@@ -644,18 +1011,21 @@ async fn main() -> Result<()> {
     tracing::info!("Configuration loaded: {:?}", config);
 
     // Initialize AMP client
-    let client = Arc::new(AmpClient::new(
-        config.amp_server_url.clone(),
-        config.amp_server_timeout,
-    )?);
+    let client = Arc::new(
+        AmpClient::new(config.amp_server_url.clone(), config.amp_server_timeout)?
+            .with_retry(RetryPolicy {
+                max_attempts: config.amp_retry_attempts,
+                ..RetryPolicy::default()
+            })
+            .with_breaker(
+                config.amp_breaker_threshold,
+                std::time::Duration::from_secs(config.amp_breaker_cooldown_secs),
+            ),
+    );
     tracing::info!("AMP client initialized");
 
     // Create handler with connection state
-    let handler = AmpMcpHandler {
-        client: client.clone(),
-        config: config.clone(),
-        connection_state: Arc::new(RwLock::new(ConnectionState::default())),
-    };
+    let handler = AmpMcpHandler::new(client.clone(), config.clone());
 
     tracing::info!("MCP handler created");
 
@@ -677,3 +1047,126 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::post, Json, Router};
+    use serde_json::Value;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Clone, Default)]
+    struct Calls {
+        runs: Arc<AtomicUsize>,
+        registrations: Arc<AtomicUsize>,
+        releases: Arc<std::sync::Mutex<Vec<Value>>>,
+    }
+
+    async fn create_run(State(calls): State<Calls>) -> Json<Value> {
+        calls.runs.fetch_add(1, Ordering::SeqCst);
+        // Hold the first registration open so the other callers pile up behind it
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        Json(serde_json::json!({ "id": "objects:run-1" }))
+    }
+
+    async fn register(State(calls): State<Calls>) -> Json<Value> {
+        calls.registrations.fetch_add(1, Ordering::SeqCst);
+        Json(serde_json::json!({ "connection_id": "conn-1" }))
+    }
+
+    async fn release(State(calls): State<Calls>, Json(body): Json<Value>) -> Json<Value> {
+        calls.releases.lock().unwrap().push(body);
+        Json(serde_json::json!({ "connection_released": true, "closed_blocks": [] }))
+    }
+
+    fn test_config(amp_server_url: String) -> Config {
+        Config {
+            amp_server_url,
+            amp_server_timeout: 5,
+            amp_retry_attempts: 1,
+            amp_breaker_threshold: 5,
+            amp_breaker_cooldown_secs: 30,
+            relationship_types: Vec::new(),
+            server_name: "amp-mcp-server".to_string(),
+            server_version: "test".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_list_tools_register_exactly_once() {
+        let calls = Calls::default();
+        let app = Router::new()
+            .route("/v1/objects", post(create_run))
+            .route("/v1/connections/register", post(register))
+            .with_state(calls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let config = Arc::new(test_config(format!("http://{}", addr)));
+        let client = Arc::new(AmpClient::new(config.amp_server_url.clone(), 5).unwrap());
+        let handler = AmpMcpHandler::new(client, config);
+
+        let callers: Vec<_> = (0..8)
+            .map(|_| {
+                let handler = handler.clone();
+                tokio::spawn(
+                    async move { handler.ensure_registered(Some("client".to_string())).await },
+                )
+            })
+            .collect();
+        for caller in callers {
+            caller.await.unwrap();
+        }
+        handler.ensure_registered(None).await;
+
+        assert_eq!(calls.runs.load(Ordering::SeqCst), 1);
+        assert_eq!(calls.registrations.load(Ordering::SeqCst), 1);
+        let state = handler.connection_state.read().await;
+        assert_eq!(state.run_id.as_deref(), Some("run-1"));
+        assert_eq!(state.connection_id.as_deref(), Some("conn-1"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_session_release_marks_interrupted_runs_cancelled() {
+        let calls = Calls::default();
+        let app = Router::new()
+            .route("/v1/objects", post(create_run))
+            .route("/v1/connections/register", post(register))
+            .route("/v1/connections/release", post(release))
+            .with_state(calls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let config = Arc::new(test_config(format!("http://{}", addr)));
+        let client = Arc::new(AmpClient::new(config.amp_server_url.clone(), 5).unwrap());
+
+        // Dropping the last handler of an idle session completes its run
+        let handler = AmpMcpHandler::new(client.clone(), config.clone());
+        handler.ensure_registered(None).await;
+        drop(handler);
+
+        // A tool call still in flight at shutdown cancels it, exactly once
+        let handler = AmpMcpHandler::new(client, config);
+        handler.ensure_registered(None).await;
+        handler
+            .session_guard
+            .in_flight
+            .fetch_add(1, Ordering::SeqCst);
+        handler.shutdown().await;
+        handler.shutdown().await;
+        drop(handler);
+
+        let releases = calls.releases.lock().unwrap().clone();
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0]["run_status"], "completed");
+        assert_eq!(releases[1]["run_status"], "cancelled");
+        for released in &releases {
+            assert_eq!(released["connection_id"], "conn-1");
+            assert_eq!(released["run_id"], "run-1");
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Retry policy for idempotent calls to the AMP server.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first request; 1 disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each subsequent retry.
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter in `[delay / 2, delay]`.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.min(16)));
+        let half = delay / 2;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        half + half.mul_f64(f64::from(nanos % 1000) / 1000.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    /// Calls fail fast until the cooldown elapses
+    Open,
+    /// Cooldown elapsed; the next call decides whether to close or reopen
+    HalfOpen,
+}
+
+/// Point-in-time view of the breaker, reported by `amp_status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerStatus {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_secs: Option<u64>,
+}
+
+/// Stops calling the AMP server after `threshold` consecutive failed calls,
+/// so tools fail fast instead of each waiting out the request timeout.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerInner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            inner: Mutex::new(BreakerInner::default()),
+        }
+    }
+
+    /// `Err` with the remaining cooldown while the breaker is open.
+    pub fn check(&self) -> Result<(), Duration> {
+        match self.inner.lock().unwrap().opened_at {
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => {
+                Err(self.cooldown - opened_at.elapsed())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record_success(&self) {
+        *self.inner.lock().unwrap() = BreakerInner::default();
+    }
+
+    /// Count a failed call; a failure while half-open reopens immediately.
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        if inner.consecutive_failures >= self.threshold {
+            if inner.opened_at.is_none() {
+                tracing::warn!(
+                    "AMP server failed {} calls in a row, backing off for {:?}",
+                    inner.consecutive_failures,
+                    self.cooldown
+                );
+            }
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+
+    pub fn status(&self) -> BreakerStatus {
+        let inner = self.inner.lock().unwrap();
+        let (state, retry_in) = match inner.opened_at {
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => (
+                BreakerState::Open,
+                Some(self.cooldown - opened_at.elapsed()),
+            ),
+            Some(_) => (BreakerState::HalfOpen, None),
+            None => (BreakerState::Closed, None),
+        };
+        BreakerStatus {
+            state,
+            consecutive_failures: inner.consecutive_failures,
+            retry_in_secs: retry_in.map(|d| d.as_secs().max(1)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_within_jitter_bounds() {
+        let retry = RetryPolicy::default();
+        for n in 0..4 {
+            let full = Duration::from_millis(200 * 2u64.pow(n));
+            let delay = retry.backoff(n);
+            assert!(
+                delay >= full / 2 && delay <= full,
+                "{:?} vs {:?}",
+                delay,
+                full
+            );
+        }
+    }
+
+    #[test]
+    fn test_breaker_opens_after_threshold_and_half_opens_after_cooldown() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(50));
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+        breaker.record_failure();
+        assert!(breaker.check().is_err());
+        assert_eq!(breaker.status().state, BreakerState::Open);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        assert_eq!(breaker.status().state, BreakerState::HalfOpen);
+        // The trial call failed: straight back to open
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, BreakerState::Open);
+
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.record_success();
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert_eq!(breaker.status().consecutive_failures, 0);
+    }
+}
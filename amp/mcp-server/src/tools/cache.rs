@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+use super::error::{AmpToolError, ToolResult};
 use anyhow::Result;
 use rmcp::model::Content;
 use schemars::{JsonSchema, Schema, SchemaGenerator};
@@ -26,6 +27,29 @@ pub struct AmpCacheWriteInput {
     /// Scope ID (e.g., "project:amp", "workspace:default")
     #[serde(default)]
     pub scope_id: Option<String>,
+    /// Type of item: fact, decision, snippet, warning (required unless items is set)
+    #[serde(default)]
+    pub kind: Option<CacheItemKind>,
+    /// Content of the cache item (required unless items is set)
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Importance score 0.0-1.0 (default: 0.5)
+    #[serde(default)]
+    pub importance: Option<f32>,
+    /// Optional file reference (for snippets)
+    #[serde(default)]
+    pub file_ref: Option<String>,
+    /// Several items written in order in one call; replaces kind/content
+    #[serde(default)]
+    pub items: Vec<AmpCacheWriteItem>,
+    /// Expire the block this many seconds after it is created, once closed
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+}
+
+/// One item of a batched cache write
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmpCacheWriteItem {
     /// Type of item: fact, decision, snippet, warning
     pub kind: CacheItemKind,
     /// Content of the cache item
@@ -78,6 +102,9 @@ pub struct AmpCacheGetInput {
     /// Version for delta pack (legacy support)
     #[serde(default)]
     pub since_version: Option<u64>,
+    /// Append summaries of this many recent closed blocks, within the token budget
+    #[serde(default)]
+    pub include_recent_blocks: Option<usize>,
 }
 
 // ============================================================================
@@ -158,34 +185,22 @@ fn cache_item_list_schema(gen: &mut SchemaGenerator) -> Schema {
 // Handler implementations
 // ============================================================================
 
-/// Write an item to the current open cache block
+/// Write an item (or a batch of items) to the current open cache block
 /// Automatically closes block and opens new one if token threshold reached
 pub async fn handle_cache_write(
     client: &crate::amp_client::AmpClient,
     run_id: Option<&str>,
     input: AmpCacheWriteInput,
-) -> Result<Vec<Content>> {
-    let kind_str = match input.kind {
-        CacheItemKind::Fact => "fact",
-        CacheItemKind::Decision => "decision",
-        CacheItemKind::Snippet => "snippet",
-        CacheItemKind::Warning => "warning",
-    };
-
+) -> ToolResult<Vec<Content>> {
     let scope_id = input
         .scope_id
+        .clone()
         .filter(|scope| !scope.trim().is_empty())
         .or_else(|| run_id.map(|id| format!("run:{}", id)))
         .unwrap_or_else(|| "project:amp".to_string());
 
-    let payload = serde_json::json!({
-        "scope_id": scope_id,
-        "kind": kind_str,
-        "content": input.content,
-        "importance": input.importance.unwrap_or(0.5),
-        "file_ref": input.file_ref,
-    });
-
+    let batched = !input.items.is_empty();
+    let payload = block_write_payload(&scope_id, input)?;
     let result = client.cache_block_write(payload).await?;
 
     let block_id = result.get("block_id").and_then(|v| v.as_str()).unwrap_or("unknown");
@@ -208,15 +223,89 @@ pub async fn handle_cache_write(
         }
     }
 
+    if batched {
+        let placements = result.get("items").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        response.push_str(&format!("\n  Batch ({} items):", placements.len()));
+        for (idx, placement) in placements.iter().enumerate() {
+            let landed = placement.get("block_id").and_then(|v| v.as_str()).unwrap_or("unknown");
+            response.push_str(&format!("\n    {}. {}", idx + 1, landed));
+            if let Some(closed) = placement.get("closed_block_id").and_then(|v| v.as_str()) {
+                response.push_str(&format!(" (compacted {})", closed));
+            }
+        }
+    }
+
+    let filtered = result
+        .get("filtered")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    if !filtered.is_empty() {
+        response.push_str(&format!(
+            "\n  Filtered as low value ({} items):",
+            filtered.len()
+        ));
+        for content in filtered.iter().filter_map(|v| v.as_str()) {
+            response.push_str(&format!("\n    - {}", content));
+        }
+    }
+
     Ok(vec![Content::text(response)])
 }
 
+fn cache_kind_str(kind: &CacheItemKind) -> &'static str {
+    match kind {
+        CacheItemKind::Fact => "fact",
+        CacheItemKind::Decision => "decision",
+        CacheItemKind::Snippet => "snippet",
+        CacheItemKind::Warning => "warning",
+    }
+}
+
+/// Request body for `/v1/cache/block/write`: an `items` array for batches,
+/// otherwise the single inline item.
+fn block_write_payload(scope_id: &str, input: AmpCacheWriteInput) -> ToolResult<Value> {
+    if !input.items.is_empty() {
+        let items: Vec<Value> = input
+            .items
+            .iter()
+            .map(|item| {
+                serde_json::json!({
+                    "kind": cache_kind_str(&item.kind),
+                    "content": item.content,
+                    "importance": item.importance.unwrap_or(0.5),
+                    "file_ref": item.file_ref,
+                })
+            })
+            .collect();
+        return Ok(serde_json::json!({
+            "scope_id": scope_id,
+            "items": items,
+            "ttl_seconds": input.ttl_seconds,
+        }));
+    }
+
+    let (Some(kind), Some(content)) = (input.kind, input.content) else {
+        return Err(AmpToolError::invalid_input(
+            "Provide kind and content, or a non-empty items array",
+        ));
+    };
+    Ok(serde_json::json!({
+        "scope_id": scope_id,
+        "kind": cache_kind_str(&kind),
+        "content": content,
+        "importance": input.importance.unwrap_or(0.5),
+        "file_ref": input.file_ref,
+        "ttl_seconds": input.ttl_seconds,
+    }))
+}
+
 /// Manually close the current block (e.g., on conversation compact)
 pub async fn handle_cache_compact(
     client: &crate::amp_client::AmpClient,
     run_id: Option<&str>,
     input: AmpCacheCompactInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let scope_id = input
         .scope_id
         .filter(|scope| !scope.trim().is_empty())
@@ -245,7 +334,7 @@ pub async fn handle_cache_compact(
 pub async fn handle_cache_search(
     client: &crate::amp_client::AmpClient,
     input: AmpCacheSearchInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let limit = input.limit.unwrap_or(5);
     let include_open = input.include_open.unwrap_or(false);
 
@@ -273,8 +362,8 @@ pub async fn handle_cache_search(
                 let created = m.get("created_at").and_then(|v| v.as_str()).unwrap_or("");
 
                 output.push_str(&format!(
-                    "\n{}. Block: {} (relevance: {:.2})\n   Created: {}\n   Summary: {}\n",
-                    idx + 1, block_id, relevance, created,
+                    "\n{}. Block: {} (#{}, {}, relevance: {:.2})\n   Created: {}\n   Summary: {}\n",
+                    idx + 1, block_id, block_sequence(m), block_status(m, relevance), relevance, created,
                     if summary.len() > 200 { &summary[..200] } else { summary }
                 ));
             }
@@ -289,7 +378,7 @@ pub async fn handle_cache_search(
 pub async fn handle_cache_get(
     client: &crate::amp_client::AmpClient,
     input: AmpCacheGetInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     // If block_id specified, get that specific block
     if let Some(block_id) = &input.block_id {
         let result = client.cache_block_get(block_id).await?;
@@ -312,6 +401,10 @@ pub async fn handle_cache_get(
         request["since_version"] = serde_json::Value::Number(version.into());
     }
 
+    if let Some(count) = input.include_recent_blocks {
+        request["include_recent_blocks"] = serde_json::Value::Number(count.into());
+    }
+
     let result = client.cache_get_pack(request).await?;
     let summary = format_memory_pack(&result, &input)?;
     Ok(vec![Content::text(summary)])
@@ -332,7 +425,7 @@ pub async fn handle_cache_get(
 pub async fn handle_cache_read(
     client: &crate::amp_client::AmpClient,
     input: AmpCacheReadInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     // Case 1: Get specific block by ID
     if let Some(block_id) = &input.block_id {
         let result = client.cache_block_get(block_id).await?;
@@ -373,12 +466,13 @@ pub async fn handle_cache_read(
             "query": query,
             "limit": limit,
             "include_open": include_open,
+            "include_content": include_content,
         });
 
         let result = client.cache_block_search(payload).await?;
 
         if include_content {
-            // Fetch full content for matching blocks
+            // Matches carry their items inline
             return format_search_with_content(client, &result, query).await;
         } else {
             // Return summaries only
@@ -420,11 +514,15 @@ fn format_list_summaries(result: &serde_json::Value, scope_id: &str) -> Result<S
                 let relevance = m.get("relevance").and_then(|v| v.as_f64()).unwrap_or(0.0);
                 let created = m.get("created_at").and_then(|v| v.as_str()).unwrap_or("");
 
-                // Determine status: open blocks have relevance=1.0 and dynamic summary
-                let is_open = relevance == 1.0 && (summary.starts_with("[") || summary.contains("[open block"));
-                let status = if is_open { "open" } else { "closed" };
+                let status = block_status(m, relevance);
 
-                output.push_str(&format!("{}. [{}] {}\n", idx + 1, status, block_id));
+                output.push_str(&format!(
+                    "{}. [{}] #{} {}\n",
+                    idx + 1,
+                    status,
+                    block_sequence(m),
+                    block_id
+                ));
 
                 if !created.is_empty() {
                     output.push_str(&format!("   Created: {}\n", created));
@@ -456,7 +554,7 @@ async fn format_list_with_content(
     client: &crate::amp_client::AmpClient,
     result: &serde_json::Value,
     scope_id: &str,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let mut output = format!("Cache Blocks for scope: {} (with content)\n", scope_id);
     output.push_str(&"=".repeat(50));
     output.push('\n');
@@ -472,13 +570,12 @@ async fn format_list_with_content(
                 let block_id = m.get("block_id").and_then(|v| v.as_str()).unwrap_or("?");
                 let relevance = m.get("relevance").and_then(|v| v.as_f64()).unwrap_or(0.0);
 
-                // Determine status from relevance (open blocks get 1.0)
-                let is_open = relevance == 1.0;
-                let status = if is_open { "open" } else { "closed" };
+                let status = block_status(m, relevance);
+                let is_open = status == "open";
 
                 output.push_str(&format!(
-                    "\n[{}/{}] {} [{}]\n",
-                    idx + 1, matches.len(), block_id, status
+                    "\n[{}/{}] #{} {} [{}]\n",
+                    idx + 1, matches.len(), block_sequence(m), block_id, status
                 ));
                 output.push_str(&"-".repeat(40));
                 output.push('\n');
@@ -547,8 +644,8 @@ fn format_search_summaries(result: &serde_json::Value, query: &str) -> Result<St
                 let created = m.get("created_at").and_then(|v| v.as_str()).unwrap_or("");
 
                 output.push_str(&format!(
-                    "\n{}. Block: {} (relevance: {:.2})\n   Created: {}\n   Summary: {}\n",
-                    idx + 1, block_id, relevance, created,
+                    "\n{}. Block: {} (#{}, {}, relevance: {:.2})\n   Created: {}\n   Summary: {}\n",
+                    idx + 1, block_id, block_sequence(m), block_status(m, relevance), relevance, created,
                     if summary.len() > 200 { &summary[..200] } else { summary }
                 ));
             }
@@ -561,12 +658,13 @@ fn format_search_summaries(result: &serde_json::Value, query: &str) -> Result<St
     Ok(output)
 }
 
-/// Format search results with full block content
+/// Format search results with full block content, taken from each match's
+/// inline `items`; servers that don't inline them get a `block_get` per match
 async fn format_search_with_content(
     client: &crate::amp_client::AmpClient,
     result: &serde_json::Value,
     query: &str,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let mut output = format!("Cache search results for: \"{}\" (with content)\n", query);
     output.push_str(&"=".repeat(50));
     output.push('\n');
@@ -580,14 +678,18 @@ async fn format_search_with_content(
                 let relevance = m.get("relevance").and_then(|v| v.as_f64()).unwrap_or(0.0);
 
                 output.push_str(&format!(
-                    "\n[{}/{}] Block: {} (relevance: {:.2})\n",
-                    idx + 1, matches.len(), block_id, relevance
+                    "\n[{}/{}] Block: {} (#{}, {}, relevance: {:.2})\n",
+                    idx + 1, matches.len(), block_id, block_sequence(m), block_status(m, relevance), relevance
                 ));
                 output.push_str(&"-".repeat(40));
                 output.push('\n');
 
-                // Fetch full block content
-                match client.cache_block_get(block_id).await {
+                let block = if m.get("items").is_some_and(Value::is_array) {
+                    Ok(m.clone())
+                } else {
+                    client.cache_block_get(block_id).await
+                };
+                match block {
                     Ok(block) => {
                         if let Some(summary) = block.get("summary").and_then(|v| v.as_str()) {
                             if !summary.is_empty() {
@@ -621,13 +723,36 @@ async fn format_search_with_content(
     Ok(vec![Content::text(output)])
 }
 
+/// Position of a block in its scope's rolling window; higher is newer
+fn block_sequence(block: &Value) -> String {
+    block
+        .get("sequence")
+        .and_then(|v| v.as_u64())
+        .map(|sequence| sequence.to_string())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// Server-reported block status; older servers omit it, and there only the
+/// open block scores a relevance of 1.0
+fn block_status(block: &Value, relevance: f64) -> &str {
+    block
+        .get("status")
+        .and_then(|v| v.as_str())
+        .unwrap_or(if relevance == 1.0 { "open" } else { "closed" })
+}
+
 fn format_block(result: &serde_json::Value) -> Result<String> {
     let block_id = result.get("block_id").and_then(|v| v.as_str()).unwrap_or("?");
     let status = result.get("status").and_then(|v| v.as_str()).unwrap_or("?");
     let token_count = result.get("token_count").and_then(|v| v.as_u64()).unwrap_or(0);
 
     let mut output = format!("Cache Block: {}\n", block_id);
-    output.push_str(&format!("Status: {} | Tokens: {}\n", status, token_count));
+    output.push_str(&format!(
+        "Status: {} | Sequence: {} | Tokens: {}\n",
+        status,
+        block_sequence(result),
+        token_count
+    ));
     output.push_str(&"-".repeat(50));
     output.push('\n');
 
@@ -724,6 +849,19 @@ fn format_memory_pack(result: &serde_json::Value, input: &AmpCacheGetInput) -> R
         }
     }
 
+    // Recent episodic context
+    if let Some(blocks) = result.get("recent_blocks").and_then(|v| v.as_array()) {
+        if !blocks.is_empty() {
+            output.push_str("Recent blocks:\n");
+            for block in blocks {
+                let sequence = block.get("sequence").and_then(|v| v.as_u64()).unwrap_or(0);
+                let summary = block.get("summary").and_then(|v| v.as_str()).unwrap_or("");
+                output.push_str(&format!("  #{} {}\n", sequence, summary));
+            }
+            output.push('\n');
+        }
+    }
+
     // Metadata
     if let Some(token_count) = result.get("token_count").and_then(|v| v.as_u64()) {
         output.push_str(&format!(
@@ -743,3 +881,122 @@ fn format_memory_pack(result: &serde_json::Value, input: &AmpCacheGetInput) -> R
 
     Ok(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        routing::{get, post},
+        Json, Router,
+    };
+
+    #[test]
+    fn test_block_listing_shows_sequence_and_status() {
+        let result = serde_json::json!({
+            "matches": [
+                { "block_id": "cache_block:b", "sequence": 4, "status": "open", "summary": "[fact] new", "relevance": 1.0 },
+                { "block_id": "cache_block:a", "sequence": 3, "status": "closed", "summary": "older work", "relevance": 0.5 }
+            ]
+        });
+        let listing = format_list_summaries(&result, "run:abc").unwrap();
+        assert!(listing.contains("1. [open] #4 cache_block:b"));
+        assert!(listing.contains("2. [closed] #3 cache_block:a"));
+
+        let search = format_search_summaries(&result, "work").unwrap();
+        assert!(search.contains("Block: cache_block:a (#3, closed, relevance: 0.50)"));
+
+        let block = serde_json::json!({
+            "block_id": "cache_block:b", "sequence": 4, "status": "open", "token_count": 12, "items": []
+        });
+        assert!(format_block(&block)
+            .unwrap()
+            .contains("Status: open | Sequence: 4 | Tokens: 12"));
+    }
+
+    #[test]
+    fn test_block_write_payload_batches_items() {
+        let input: AmpCacheWriteInput = serde_json::from_value(serde_json::json!({
+            "items": [
+                { "kind": "fact", "content": "Parser streams tokens" },
+                { "kind": "warning", "content": "Index is stale", "importance": 0.9, "file_ref": "src/lib.rs" }
+            ]
+        }))
+        .unwrap();
+        let payload = block_write_payload("run:abc", input).unwrap();
+
+        assert_eq!(payload["scope_id"], "run:abc");
+        assert!(payload.get("kind").is_none());
+        let items = payload["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["kind"], "fact");
+        assert_eq!(items[0]["importance"], 0.5);
+        assert_eq!(items[1]["kind"], "warning");
+        assert_eq!(items[1]["file_ref"], "src/lib.rs");
+
+        let single: AmpCacheWriteInput =
+            serde_json::from_value(serde_json::json!({ "kind": "decision", "content": "Use RRF", "ttl_seconds": 60 }))
+                .unwrap();
+        let payload = block_write_payload("project:amp", single).unwrap();
+        assert_eq!(payload["kind"], "decision");
+        assert_eq!(payload["ttl_seconds"], 60);
+        assert!(payload.get("items").is_none());
+
+        let empty: AmpCacheWriteInput = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(
+            block_write_payload("project:amp", empty)
+                .unwrap_err()
+                .kind(),
+            "invalid_input"
+        );
+    }
+
+    async fn inline_search(Json(payload): Json<Value>) -> Json<Value> {
+        assert_eq!(payload["include_content"], true);
+        Json(serde_json::json!({
+            "matches": [
+                {
+                    "block_id": "cache_block:b", "sequence": 2, "status": "open", "summary": "", "relevance": 1.0,
+                    "items": [{ "kind": "warning", "content": "Index is stale" }]
+                },
+                { "block_id": "cache_block:a", "sequence": 1, "status": "closed", "summary": "older work", "relevance": 0.5 }
+            ]
+        }))
+    }
+
+    async fn fallback_block(axum::extract::Path(id): axum::extract::Path<String>) -> Json<Value> {
+        assert_eq!(id, "cache_block:a");
+        Json(serde_json::json!({
+            "block_id": id, "summary": "older work", "items": [{ "kind": "fact", "content": "Parser streams tokens" }]
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_cache_read_inlines_search_content_in_one_call() {
+        let app = Router::new()
+            .route("/v1/cache/block/search", post(inline_search))
+            .route("/v1/cache/block/{id}", get(fallback_block));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = crate::amp_client::AmpClient::new(format!("http://{}", addr), 5).unwrap();
+
+        let input: AmpCacheReadInput = serde_json::from_value(serde_json::json!({
+            "scope_id": "run:abc", "query": "work", "include_content": true, "include_open": true
+        }))
+        .unwrap();
+        let content = handle_cache_read(&client, input).await.unwrap();
+        let text = serde_json::to_value(&content[0]).unwrap()["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        assert!(text.contains("(with content)"));
+        assert!(text.contains("[1/2] Block: cache_block:b (#2, open, relevance: 1.00)"));
+        assert!(text.contains("  ! [warning] Index is stale"));
+        // Matches without inline items still resolve through block_get
+        assert!(text.contains("Summary: older work"));
+        assert!(text.contains("  - [fact] Parser streams tokens"));
+    }
+}
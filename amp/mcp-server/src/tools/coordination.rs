@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use anyhow::Result;
+use super::error::ToolResult;
 use rmcp::model::Content;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -19,7 +19,7 @@ pub struct AmpLeaseReleaseInput {
 pub async fn handle_lease_acquire(
     client: &crate::amp_client::AmpClient,
     input: AmpLeaseAcquireInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let payload = serde_json::json!({
         "resource": input.resource,
         "duration": input.duration,
@@ -37,7 +37,7 @@ pub async fn handle_lease_acquire(
 pub async fn handle_lease_release(
     client: &crate::amp_client::AmpClient,
     input: AmpLeaseReleaseInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let payload = serde_json::json!({
         "lease_id": input.lease_id
     });
@@ -1,3 +1,5 @@
+use super::error::ToolResult;
+use crate::retry::BreakerState;
 use anyhow::Result;
 use rmcp::model::Content;
 use schemars::JsonSchema;
@@ -15,7 +17,18 @@ pub struct AmpListInput {
     pub sort: Option<String>,
 }
 
-pub async fn handle_amp_status(client: &crate::amp_client::AmpClient) -> Result<Vec<Content>> {
+pub async fn handle_amp_status(client: &crate::amp_client::AmpClient) -> ToolResult<Vec<Content>> {
+    // While backing off, report that instead of failing like every other tool
+    let breaker = client.breaker_status();
+    if breaker.state == BreakerState::Open {
+        let result = serde_json::json!({
+            "health": "unavailable",
+            "message": "AMP server unavailable, backing off; memory reads and writes fail until the breaker closes",
+            "breaker": breaker,
+        });
+        return Ok(vec![Content::text(serde_json::to_string_pretty(&result)?)]);
+    }
+
     let health = client.health().await?;
     let analytics = client.analytics().await?;
 
@@ -40,6 +53,26 @@ pub async fn handle_amp_status(client: &crate::amp_client::AmpClient) -> Result<
         .map(|a| a.iter().take(3).cloned().collect())
         .unwrap_or_default();
 
+    // Query hit rates and chunk embedding coverage over the last 24h
+    let usage = analytics.get("detailed").map(|detailed| {
+        serde_json::json!({
+            "window": detailed.get("window"),
+            "queries": detailed.get("queries"),
+            "chunks": detailed.get("chunks"),
+        })
+    });
+
+    // Flag settings the running embedding service has not picked up
+    let embedding = analytics.get("embeddingStatus").cloned();
+    let embedding_warning = embedding
+        .as_ref()
+        .and_then(|status| status.get("restartRequired"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+        .then_some(
+            "Embedding settings changed since the server loaded them; restart it (or re-save settings) before indexing, or new vectors use the old model",
+        );
+
     let result = serde_json::json!({
         "health": health,
         "totalObjects": analytics.get("totalObjects"),
@@ -49,6 +82,11 @@ pub async fn handle_amp_status(client: &crate::amp_client::AmpClient) -> Result<
         "indexingStats": analytics.get("indexingStats"),
         "latency": latency_summary,
         "recentActivity": recent_activity,
+        "activeAgents": analytics.get("activeAgents"),
+        "usage": usage,
+        "embedding": embedding,
+        "embeddingWarning": embedding_warning,
+        "breaker": client.breaker_status(),
     });
 
     Ok(vec![Content::text(serde_json::to_string_pretty(&result)?)])
@@ -57,7 +95,7 @@ pub async fn handle_amp_status(client: &crate::amp_client::AmpClient) -> Result<
 pub async fn handle_amp_list(
     client: &crate::amp_client::AmpClient,
     input: AmpListInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let requested_limit = input.limit.unwrap_or(10).min(20);
     let mut object_type = input.object_type.clone();
     let mut symbol_kind = input.symbol_kind.clone();
@@ -171,16 +209,11 @@ fn summarize_list_results(result: &serde_json::Value, input: &AmpListInput) -> R
                         .get("file_path")
                         .and_then(|p| p.as_str())
                         .unwrap_or("unknown");
-                    let lines = format!(
-                        "{}-{}",
-                        obj.get("start_line").and_then(|l| l.as_u64()).unwrap_or(0),
-                        obj.get("end_line").and_then(|l| l.as_u64()).unwrap_or(0)
-                    );
                     summary.push_str(&format!(
-                        "{}. FileChunk: {} (lines {})\n",
+                        "{}. FileChunk: {} {}\n",
                         i + 1,
                         path,
-                        lines
+                        super::query::chunk_span(obj)
                     ));
                 }
                 "FileLog" | "filelog" => {
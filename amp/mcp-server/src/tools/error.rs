@@ -0,0 +1,176 @@
+use crate::amp_client::{ApiError, ServerBackingOff};
+use reqwest::StatusCode;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::Value;
+use std::fmt;
+
+/// Classified tool failure, so agents can tell a missing file from an
+/// unreachable server or an ambiguous path without reading logs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AmpToolError {
+    NotFound(String),
+    /// The input matched several stored paths; retry with one of `candidates`
+    Ambiguous {
+        message: String,
+        candidates: Vec<String>,
+    },
+    ServerUnavailable(String),
+    InvalidInput(String),
+    Conflict(String),
+    Internal(String),
+}
+
+pub type ToolResult<T> = std::result::Result<T, AmpToolError>;
+
+impl AmpToolError {
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::InvalidInput(message.into())
+    }
+
+    /// Stable identifier reported to agents as `error_kind`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "not_found",
+            Self::Ambiguous { .. } => "ambiguous",
+            Self::ServerUnavailable(_) => "server_unavailable",
+            Self::InvalidInput(_) => "invalid_input",
+            Self::Conflict(_) => "conflict",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NotFound(message)
+            | Self::Ambiguous { message, .. }
+            | Self::ServerUnavailable(message)
+            | Self::InvalidInput(message)
+            | Self::Conflict(message)
+            | Self::Internal(message) => message,
+        }
+    }
+
+    /// Structured payload returned to the agent alongside the error
+    pub fn to_json(&self) -> Value {
+        let mut body = serde_json::json!({
+            "error_kind": self.kind(),
+            "message": self.message(),
+        });
+        if let Self::Ambiguous { candidates, .. } = self {
+            body["candidates"] = serde_json::json!(candidates);
+            body["hint"] = serde_json::json!(
+                "Retry with one of the candidate paths (e.g., include parent directory)"
+            );
+        }
+        body
+    }
+
+    /// Malformed input and internal faults become MCP protocol errors; the
+    /// rest are tool-level failures the agent can act on, so they come back
+    /// as an error result carrying the structured payload.
+    pub fn into_call_result(self) -> Result<CallToolResult, McpError> {
+        let data = self.to_json();
+        match self {
+            Self::InvalidInput(message) => Err(McpError::invalid_params(message, Some(data))),
+            Self::Internal(message) => Err(McpError::internal_error(message, Some(data))),
+            _ => {
+                let text = serde_json::to_string_pretty(&data)
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                Ok(CallToolResult::error(vec![Content::text(text)]))
+            }
+        }
+    }
+
+    fn from_api_error(api: &ApiError) -> Self {
+        let body: Value = serde_json::from_str(&api.body).unwrap_or(Value::Null);
+        let message = body
+            .get("error")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| api.to_string());
+
+        match api.status {
+            StatusCode::NOT_FOUND => Self::NotFound(message),
+            StatusCode::CONFLICT => match body.get("matching_files").and_then(|v| v.as_array()) {
+                Some(files) => Self::Ambiguous {
+                    message,
+                    candidates: files
+                        .iter()
+                        .filter_map(|f| f.as_str().map(str::to_string))
+                        .collect(),
+                },
+                None => Self::Conflict(message),
+            },
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+                Self::InvalidInput(message)
+            }
+            StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT => Self::ServerUnavailable(message),
+            _ => Self::Internal(message),
+        }
+    }
+}
+
+impl fmt::Display for AmpToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.message())
+    }
+}
+
+impl std::error::Error for AmpToolError {}
+
+impl From<anyhow::Error> for AmpToolError {
+    fn from(err: anyhow::Error) -> Self {
+        if let Some(api) = err.downcast_ref::<ApiError>() {
+            return Self::from_api_error(api);
+        }
+        if let Some(backoff) = err.downcast_ref::<ServerBackingOff>() {
+            return Self::ServerUnavailable(backoff.to_string());
+        }
+        if let Some(tool) = err.downcast_ref::<AmpToolError>() {
+            return tool.clone();
+        }
+        if let Some(http) = err.downcast_ref::<reqwest::Error>() {
+            if http.is_connect() || http.is_timeout() {
+                return Self::ServerUnavailable(format!("AMP server unreachable: {}", http));
+            }
+        }
+        Self::Internal(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AmpToolError {
+    fn from(err: reqwest::Error) -> Self {
+        anyhow::Error::from(err).into()
+    }
+}
+
+impl From<serde_json::Error> for AmpToolError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Internal(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_codes_map_to_error_kinds() {
+        let kind = |status: StatusCode| {
+            AmpToolError::from(anyhow::Error::from(ApiError {
+                operation: "op",
+                status,
+                body: String::new(),
+            }))
+            .kind()
+        };
+        assert_eq!(kind(StatusCode::NOT_FOUND), "not_found");
+        assert_eq!(kind(StatusCode::CONFLICT), "conflict");
+        assert_eq!(kind(StatusCode::BAD_REQUEST), "invalid_input");
+        assert_eq!(kind(StatusCode::SERVICE_UNAVAILABLE), "server_unavailable");
+        assert_eq!(kind(StatusCode::INTERNAL_SERVER_ERROR), "internal");
+    }
+}
@@ -1,5 +1,8 @@
 #![allow(dead_code)]
+use super::error::{AmpToolError, ToolResult};
+use crate::amp_client::ApiError;
 use anyhow::Result;
+use reqwest::StatusCode;
 use rmcp::model::Content;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -11,6 +14,13 @@ pub struct AmpFilelogGetInput {
     pub path: String,
 }
 
+/// Input for amp_filelog_regenerate - rebuild a FileLog's AI summary
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmpFilelogRegenerateInput {
+    /// Path of an indexed file
+    pub path: String,
+}
+
 /// Action type for file sync operations
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -51,6 +61,12 @@ pub struct AmpFilelogUpdateInput {
 pub struct AmpFileContentGetInput {
     pub path: String,
     pub max_chars: Option<usize>,
+    /// First line to return (1-based, inclusive)
+    pub start_line: Option<u32>,
+    /// Last line to return (inclusive)
+    pub end_line: Option<u32>,
+    /// Return only the definition of this symbol (function, struct, class, ...)
+    pub symbol: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -58,19 +74,46 @@ pub struct AmpFilePathResolveInput {
     pub path: String,
 }
 
+/// Input for amp_file_history - the sync audit trail of a file
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmpFileHistoryInput {
+    pub path: String,
+    /// Most recent entries to return (default: all)
+    pub limit: Option<usize>,
+}
+
 pub async fn handle_filelog_get(
     client: &crate::amp_client::AmpClient,
     input: AmpFilelogGetInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let result = client.get_file_log(&input.path).await?;
 
     Ok(vec![Content::text(serde_json::to_string_pretty(&result)?)])
 }
 
+pub async fn handle_filelog_regenerate(
+    client: &crate::amp_client::AmpClient,
+    input: AmpFilelogRegenerateInput,
+) -> ToolResult<Vec<Content>> {
+    let result = client.regenerate_file_log(&input.path).await?;
+    let file_log = &result["file_log"];
+
+    let mut output = format!(
+        "FileLog regenerated: {}\n",
+        result["file_path"].as_str().unwrap_or(&input.path)
+    );
+    if let Some(purpose) = file_log["purpose"].as_str() {
+        output.push_str(&format!("Purpose: {}\n", purpose));
+    }
+    output.push('\n');
+    output.push_str(file_log["summary_markdown"].as_str().unwrap_or_default());
+    Ok(vec![Content::text(output)])
+}
+
 pub async fn handle_filelog_update(
     client: &crate::amp_client::AmpClient,
     input: AmpFilelogUpdateInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let payload = serde_json::json!({
         "path": input.path,
         "summary": input.summary,
@@ -89,14 +132,26 @@ pub async fn handle_filelog_update(
 pub async fn handle_file_content_get(
     client: &crate::amp_client::AmpClient,
     input: AmpFileContentGetInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let normalized = normalize_request_path(&input.path);
-    let mut result = client
-        .get_file_content(&normalized, input.max_chars)
-        .await?;
+    let mut params = serde_json::Map::new();
+    if let Some(max_chars) = input.max_chars {
+        params.insert("max_chars".to_string(), max_chars.into());
+    }
+    if let Some(start_line) = input.start_line {
+        params.insert("start_line".to_string(), start_line.into());
+    }
+    if let Some(end_line) = input.end_line {
+        params.insert("end_line".to_string(), end_line.into());
+    }
+    if let Some(symbol) = &input.symbol {
+        params.insert("symbol".to_string(), symbol.clone().into());
+    }
+    let params = Value::Object(params);
+    let mut result = client.get_file_content(&normalized, &params).await?;
     if is_not_found(&result) {
         if let Some(alt) = alternate_path(&input.path, &normalized) {
-            let retry = client.get_file_content(&alt, input.max_chars).await?;
+            let retry = client.get_file_content(&alt, &params).await?;
             if !is_not_found(&retry) {
                 result = retry;
             }
@@ -108,17 +163,79 @@ pub async fn handle_file_content_get(
 pub async fn handle_file_path_resolve(
     client: &crate::amp_client::AmpClient,
     input: AmpFilePathResolveInput,
-) -> Result<Vec<Content>> {
-    let normalized = normalize_request_path(&input.path);
+) -> ToolResult<Vec<Content>> {
+    let resolution = resolve_file_path(client, &input.path).await?;
+
+    let response = serde_json::json!({
+        "input_path": input.path,
+        "normalized_path": resolution.normalized_path,
+        "tried_paths": resolution.tried_paths,
+        "resolved_path": resolution.resolved_path,
+        "error": resolution.result.get("error").cloned(),
+    });
+
+    Ok(vec![Content::text(serde_json::to_string_pretty(
+        &response,
+    )?)])
+}
+
+pub async fn handle_file_history(
+    client: &crate::amp_client::AmpClient,
+    input: AmpFileHistoryInput,
+) -> ToolResult<Vec<Content>> {
+    let resolution = resolve_file_path(client, &input.path).await?;
+    let Some(resolved_path) = resolution.resolved_path else {
+        return Err(AmpToolError::NotFound(format!(
+            "No file log found for {} (tried: {})",
+            input.path,
+            resolution.tried_paths.join(", ")
+        )));
+    };
+    let file_log_id = resolution
+        .result
+        .get("file_log")
+        .and_then(|log| log.get("id"))
+        .and_then(|id| id.as_str())
+        .map(|id| id.trim_start_matches("objects:").to_string())
+        .ok_or_else(|| {
+            AmpToolError::Internal(format!("File log for {} has no id", resolved_path))
+        })?;
+
+    let history = client.get_object_history(&file_log_id, input.limit).await?;
+    let response = serde_json::json!({
+        "path": resolved_path,
+        "file_log_id": file_log_id,
+        "entries": history.get("entries").cloned().unwrap_or_else(|| serde_json::json!([])),
+    });
+
+    Ok(vec![Content::text(serde_json::to_string_pretty(
+        &response,
+    )?)])
+}
+
+/// Outcome of resolving a user-supplied path to the canonical stored path
+pub struct FilePathResolution {
+    pub normalized_path: String,
+    pub tried_paths: Vec<String>,
+    pub resolved_path: Option<String>,
+    pub result: Value,
+}
+
+/// Resolve a path against stored file logs, trying separator variants and project roots
+pub async fn resolve_file_path(
+    client: &crate::amp_client::AmpClient,
+    path: &str,
+) -> Result<FilePathResolution> {
+    let normalized = normalize_request_path(path);
     let mut tried = vec![normalized.clone()];
 
-    let mut result = client.get_file_log(&normalized).await?;
+    let mut result = probe_file_log(client, &normalized).await?;
     let mut resolved = extract_file_path(&result);
 
     if resolved.is_none() {
-        if let Some(alt) = alternate_path(&input.path, &normalized) {
+        if let Some(alt) = alternate_path(path, &normalized) {
             tried.push(alt.clone());
-            let retry = client.get_file_log(&alt).await?;
+            let retry = probe_file_log(client, &alt).await?;
             if let Some(found) = extract_file_path(&retry) {
                 resolved = Some(found);
                 result = retry;
@@ -126,16 +243,16 @@ pub async fn handle_file_path_resolve(
         }
     }
 
-    if resolved.is_none() && !Path::new(&input.path).is_absolute() {
+    if resolved.is_none() && !Path::new(path).is_absolute() {
         let project_roots = fetch_project_roots(client).await?;
         for root in project_roots {
             if root.trim().is_empty() || root == "." {
                 continue;
             }
-            let candidate = Path::new(&root).join(&input.path);
+            let candidate = Path::new(&root).join(path);
             let candidate_str = candidate.to_string_lossy().to_string();
             tried.push(candidate_str.clone());
-            let attempt = client.get_file_log(&candidate_str).await?;
+            let attempt = probe_file_log(client, &candidate_str).await?;
             if let Some(found) = extract_file_path(&attempt) {
                 resolved = Some(found);
                 result = attempt;
@@ -144,17 +261,23 @@ pub async fn handle_file_path_resolve(
         }
     }
 
-    let response = serde_json::json!({
-        "input_path": input.path,
-        "normalized_path": normalized,
-        "tried_paths": tried,
-        "resolved_path": resolved,
-        "error": result.get("error").cloned(),
-    });
+    Ok(FilePathResolution {
+        normalized_path: normalized,
+        tried_paths: tried,
+        resolved_path: resolved,
+        result,
+    })
+}
 
-    Ok(vec![Content::text(serde_json::to_string_pretty(
-        &response,
-    )?)])
+/// A missing file log is an expected miss while probing path variants, so it
+/// is returned as an error body rather than failing the whole resolution.
+async fn probe_file_log(client: &crate::amp_client::AmpClient, path: &str) -> Result<Value> {
+    match client.get_file_log(path).await {
+        Err(err) if ApiError::status_of(&err) == Some(StatusCode::NOT_FOUND) => {
+            Ok(serde_json::json!({ "error": "File not found", "path": path }))
+        }
+        other => other,
+    }
 }
 
 fn is_not_found(result: &Value) -> bool {
@@ -250,7 +373,7 @@ async fn fetch_project_roots(client: &crate::amp_client::AmpClient) -> Result<Ve
 pub async fn handle_file_sync(
     client: &crate::amp_client::AmpClient,
     input: AmpFileSyncInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let action_str = match input.action {
         FileSyncAction::Create => "create",
         FileSyncAction::Edit => "edit",
@@ -265,13 +388,9 @@ pub async fn handle_file_sync(
         "agent_id": input.agent_id
     });
 
+    // Ambiguous paths surface as AmpToolError::Ambiguous with the candidates
     let result = client.file_sync(payload).await?;
 
-    // Check if result indicates ambiguous path - return that directly
-    if result.get("status").and_then(|s| s.as_str()) == Some("ambiguous") {
-        return Ok(vec![Content::text(serde_json::to_string_pretty(&result)?)]);
-    }
-
     // Format response based on what was synced
     let layers = result.get("layers_updated").cloned().unwrap_or_else(|| {
         serde_json::json!({
@@ -293,3 +412,140 @@ pub async fn handle_file_sync(
 
     Ok(vec![Content::text(serde_json::to_string_pretty(&response)?)])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::error::AmpToolError;
+    use axum::{
+        http::StatusCode as HttpStatus,
+        routing::{get, post},
+        Json, Router,
+    };
+
+    async fn ambiguous_file_log() -> (HttpStatus, Json<Value>) {
+        (
+            HttpStatus::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Multiple files match",
+                "input_path": "lib.rs",
+                "matching_files": ["server/src/lib.rs", "cli/src/lib.rs"]
+            })),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_filelog_get_reports_ambiguous_candidates() {
+        let app = Router::new().route(
+            "/v1/codebase/file-log-objects/{path}",
+            get(ambiguous_file_log),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = crate::amp_client::AmpClient::new(format!("http://{}", addr), 5).unwrap();
+
+        let err = handle_filelog_get(
+            &client,
+            AmpFilelogGetInput {
+                path: "lib.rs".to_string(),
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            AmpToolError::Ambiguous {
+                message: "Multiple files match".to_string(),
+                candidates: vec![
+                    "server/src/lib.rs".to_string(),
+                    "cli/src/lib.rs".to_string()
+                ],
+            }
+        );
+        let result = err.into_call_result().unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    async fn file_log_object() -> Json<Value> {
+        Json(serde_json::json!({
+            "file_log": { "id": "objects:log-1", "file_path": "src/lib.rs" }
+        }))
+    }
+
+    async fn object_history(
+        axum::extract::Path(id): axum::extract::Path<String>,
+        axum::extract::Query(query): axum::extract::Query<
+            std::collections::HashMap<String, String>,
+        >,
+    ) -> Json<Value> {
+        let entries = [("edit", "second"), ("create", "first")]
+            .iter()
+            .map(|(action, summary)| serde_json::json!({ "action": action, "summary": summary }))
+            .take(query.get("limit").map_or(2, |limit| limit.parse().unwrap()))
+            .collect::<Vec<_>>();
+        Json(serde_json::json!({ "id": id, "entries": entries }))
+    }
+
+    #[tokio::test]
+    async fn test_file_history_reads_audit_trail_of_resolved_file_log() {
+        let app = Router::new()
+            .route("/v1/codebase/file-log-objects/{path}", get(file_log_object))
+            .route("/v1/objects/{id}/history", get(object_history));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = crate::amp_client::AmpClient::new(format!("http://{}", addr), 5).unwrap();
+
+        let input = AmpFileHistoryInput {
+            path: "src/lib.rs".to_string(),
+            limit: Some(1),
+        };
+        let content = handle_file_history(&client, input).await.unwrap();
+        let text = serde_json::to_value(&content[0]).unwrap()["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let history: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(history["file_log_id"], "log-1");
+        assert_eq!(history["path"], "src/lib.rs");
+        assert_eq!(
+            history["entries"],
+            serde_json::json!([{ "action": "edit", "summary": "second" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filelog_regenerate_sends_path_and_shows_new_summary() {
+        let app = Router::new().route(
+            "/v1/codebase/regenerate-ai-log",
+            post(|Json(body): Json<Value>| async move {
+                Json(serde_json::json!({
+                    "file_id": "f-1",
+                    "file_path": body["path"],
+                    "file_log": { "summary_markdown": "# FILE_LOG v1\nFresh", "purpose": "Parses input" }
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = crate::amp_client::AmpClient::new(format!("http://{}", addr), 5).unwrap();
+
+        let input = AmpFilelogRegenerateInput {
+            path: "src/parser.rs".to_string(),
+        };
+        let content = handle_filelog_regenerate(&client, input).await.unwrap();
+        let text = &content[0].as_text().unwrap().text;
+        assert!(text.starts_with("FileLog regenerated: src/parser.rs"));
+        assert!(text.contains("Purpose: Parses input"));
+        assert!(text.ends_with("# FILE_LOG v1\nFresh"));
+    }
+}
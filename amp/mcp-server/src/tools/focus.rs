@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Result};
+use super::error::{AmpToolError, ToolResult};
 use rmcp::model::Content;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -12,14 +12,15 @@ pub enum FocusAction {
     Set,
     Complete,
     End,
+    History,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct AmpFocusInput {
-    /// Action to perform: list | get | set | complete | end
+    /// Action to perform: list | get | set | complete | end | history
     pub action: FocusAction,
-    /// Optional run/session ID (defaults to current connection run)
+    /// Optional run/session ID (defaults to current connection run; list spans all runs unless given)
     #[serde(default)]
     pub run_id: Option<String>,
     /// Focus title / task summary
@@ -34,84 +35,73 @@ pub struct AmpFocusInput {
     /// Files touched or created during the focus
     #[serde(default)]
     pub files_changed: Option<Vec<String>>,
-    /// Optional project ID filter (list)
+    /// Optional project ID filter (list, history)
     #[serde(default)]
     pub project_id: Option<String>,
-}
-
-fn now_rfc3339() -> String {
-    chrono::Utc::now().to_rfc3339()
-}
-
-fn extract_array(value: &Value) -> Vec<Value> {
-    if let Some(results) = value.get("results").and_then(|v| v.as_array()) {
-        return results
-            .iter()
-            .map(|entry| entry.get("object").cloned().unwrap_or_else(|| entry.clone()))
-            .collect();
-    }
-    if let Some(arr) = value.as_array() {
-        return arr.clone();
-    }
-    Vec::new()
-}
-
-fn get_outputs(run: &Value) -> Vec<Value> {
-    run.get("outputs")
-        .and_then(|v| v.as_array())
-        .cloned()
-        .unwrap_or_default()
+    /// Only return history entries at or after this RFC3339 timestamp
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Only return history entries at or before this RFC3339 timestamp
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Maximum number of sessions to return (list, default 50)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Number of sessions to skip (list)
+    #[serde(default)]
+    pub offset: Option<usize>,
 }
 
 pub async fn handle_focus(
     client: &crate::amp_client::AmpClient,
     current_run_id: Option<&str>,
     input: AmpFocusInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     match input.action {
         FocusAction::List => {
-            let connections = client.list_connections().await?;
-            let mut output = String::from("Active sessions:\n");
-            let list = extract_array(&connections);
-
-            for connection in &list {
-                if let Some(filter_project) = &input.project_id {
-                    let connection_project = connection.get("project_id").and_then(|v| v.as_str());
-                    if connection_project != Some(filter_project.as_str()) {
-                        continue;
-                    }
-                }
-                let agent_name = connection
-                    .get("agent_name")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-                let run_id = connection.get("run_id").and_then(|v| v.as_str());
-                let status = connection
-                    .get("status")
+            // Only an explicit run_id narrows the list; otherwise every agent's session is shown
+            let mut params = serde_json::json!({
+                "limit": input.limit.unwrap_or(50),
+                "offset": input.offset.unwrap_or(0),
+            });
+            if let Some(run_id) = &input.run_id {
+                params["run_id"] = Value::String(run_id.clone());
+            }
+            if let Some(project_id) = &input.project_id {
+                params["project_id"] = Value::String(project_id.clone());
+            }
+            let response = client.focus_sessions(params).await?;
+            let sessions = response
+                .get("sessions")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+            let field = |session: &Value, key: &str| {
+                session
+                    .get(key)
                     .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-
-                let mut focus_summary = None;
-                if let Some(run_id) = run_id {
-                    if let Ok(run) = client.get_object(run_id).await {
-                        focus_summary = run
-                            .get("input_summary")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                    }
-                }
+                    .unwrap_or("unknown")
+                    .to_string()
+            };
 
+            let mut output = format!(
+                "Active focus sessions ({} of {}):\n",
+                sessions.len(),
+                response.get("total").and_then(|v| v.as_u64()).unwrap_or(0)
+            );
+            for session in &sessions {
                 output.push_str(&format!(
-                    "- {} | status: {} | run: {} | focus: {}\n",
-                    agent_name,
-                    status,
-                    run_id.unwrap_or("none"),
-                    focus_summary.unwrap_or_else(|| "unknown".to_string())
+                    "- {} | agent: {} | task: {} | created: {} | updated: {}\n",
+                    field(session, "session_id"),
+                    field(session, "agent_name"),
+                    field(session, "current_task"),
+                    field(session, "created_at"),
+                    field(session, "last_updated_at"),
                 ));
             }
 
-            if list.is_empty() {
-                output.push_str("No active connections found.\n");
+            if sessions.is_empty() {
+                output.push_str("No active focus sessions found.\n");
             }
 
             return Ok(vec![Content::text(output)]);
@@ -121,7 +111,7 @@ pub async fn handle_focus(
                 .run_id
                 .as_deref()
                 .or(current_run_id)
-                .ok_or_else(|| anyhow!("run_id required for get"))?;
+                .ok_or_else(|| AmpToolError::invalid_input("run_id required for get"))?;
             let run = client.get_object(run_id).await?;
             let focus = run.get("focus").cloned().unwrap_or(Value::Null);
             let outputs = run.get("outputs").cloned().unwrap_or(Value::Null);
@@ -142,87 +132,41 @@ pub async fn handle_focus(
                 .run_id
                 .as_deref()
                 .or(current_run_id)
-                .ok_or_else(|| anyhow!("run_id required for set"))?;
+                .ok_or_else(|| AmpToolError::invalid_input("run_id required for set"))?;
             let title = input
                 .title
                 .clone()
-                .ok_or_else(|| anyhow!("title required for set"))?;
-
-            let focus = serde_json::json!({
-                "title": title,
-                "plan": input.plan,
-                "status": "active",
-                "started_at": now_rfc3339()
-            });
+                .ok_or_else(|| AmpToolError::invalid_input("title required for set"))?;
 
-            let mut payload = serde_json::json!({
-                "input_summary": focus.get("title"),
-                "focus": focus,
-                "status": "running"
-            });
-
-            if let Some(project_id) = &input.project_id {
-                payload["project_id"] = serde_json::Value::String(project_id.clone());
-            }
-
-            client.update_object(run_id, payload).await?;
-            return Ok(vec![Content::text(format!(
-                "Focus set for run {}",
-                run_id
-            ))]);
+            client
+                .focus(serde_json::json!({
+                    "action": "set",
+                    "run_id": run_id,
+                    "title": title,
+                    "plan": input.plan,
+                    "project_id": input.project_id,
+                }))
+                .await?;
+            Ok(vec![Content::text(format!("Focus set for run {}", run_id))])
         }
         FocusAction::Complete => {
             let run_id = input
                 .run_id
                 .as_deref()
                 .or(current_run_id)
-                .ok_or_else(|| anyhow!("run_id required for complete"))?;
+                .ok_or_else(|| AmpToolError::invalid_input("run_id required for complete"))?;
 
-            let run = client.get_object(run_id).await?;
-            let mut outputs = get_outputs(&run);
-
-            let title = input.title.clone().or_else(|| {
-                run.get("focus")
-                    .and_then(|v| v.get("title"))
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-            });
-
-            let summary = input
-                .summary
-                .clone()
-                .or_else(|| title.clone())
-                .unwrap_or_else(|| "Focus completed".to_string());
-
-            let output_entry = serde_json::json!({
-                "type": "focus",
-                "content": summary,
-                "metadata": {
-                    "kind": "focus",
-                    "status": "completed",
-                    "title": title,
+            client
+                .focus(serde_json::json!({
+                    "action": "complete",
+                    "run_id": run_id,
+                    "title": input.title,
                     "plan": input.plan,
+                    "summary": input.summary,
                     "files_changed": input.files_changed,
-                    "completed_at": now_rfc3339()
-                }
-            });
-
-            outputs.push(output_entry);
-
-            let focus = serde_json::json!({
-                "title": title,
-                "plan": input.plan,
-                "status": "completed",
-                "completed_at": now_rfc3339()
-            });
-
-            let payload = serde_json::json!({
-                "input_summary": "No active focus",
-                "focus": focus,
-                "outputs": outputs
-            });
-
-            client.update_object(run_id, payload).await?;
+                    "project_id": input.project_id,
+                }))
+                .await?;
             return Ok(vec![Content::text(format!(
                 "Focus marked complete for run {}",
                 run_id
@@ -233,15 +177,102 @@ pub async fn handle_focus(
                 .run_id
                 .as_deref()
                 .or(current_run_id)
-                .ok_or_else(|| anyhow!("run_id required for end"))?;
-            let payload = serde_json::json!({
-                "status": "completed"
-            });
-            client.update_object(run_id, payload).await?;
+                .ok_or_else(|| AmpToolError::invalid_input("run_id required for end"))?;
+            client
+                .focus(serde_json::json!({
+                    "action": "end",
+                    "run_id": run_id,
+                    "project_id": input.project_id,
+                }))
+                .await?;
             return Ok(vec![Content::text(format!(
                 "Session ended for run {}",
                 run_id
             ))]);
         }
+        FocusAction::History => {
+            // A project filter without an explicit run spans every run in the project
+            let run_id = match (&input.run_id, &input.project_id) {
+                (Some(run_id), _) => Some(run_id.as_str()),
+                (None, Some(_)) => None,
+                (None, None) => Some(current_run_id.ok_or_else(|| {
+                    AmpToolError::invalid_input("run_id or project_id required for history")
+                })?),
+            };
+
+            let history = client
+                .focus(serde_json::json!({
+                    "action": "history",
+                    "run_id": run_id,
+                    "project_id": input.project_id,
+                    "since": input.since,
+                    "until": input.until,
+                }))
+                .await?;
+            Ok(vec![Content::text(serde_json::to_string_pretty(&history)?)])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, routing::get, Json, Router};
+    use std::collections::HashMap;
+
+    async fn sessions(Query(params): Query<HashMap<String, String>>) -> Json<Value> {
+        let all = vec![
+            serde_json::json!({ "session_id": "run-a", "agent_name": "claude", "current_task": "Fix parser", "created_at": "2026-01-01T00:00:00Z", "last_updated_at": "2026-01-01T00:05:00Z" }),
+            serde_json::json!({ "session_id": "run-b", "agent_name": "codex", "current_task": "Write docs", "created_at": "2026-01-01T00:01:00Z", "last_updated_at": "2026-01-01T00:02:00Z" }),
+        ];
+        let matching: Vec<Value> = all
+            .into_iter()
+            .filter(|s| {
+                params
+                    .get("run_id")
+                    .is_none_or(|id| s["session_id"] == id.as_str())
+            })
+            .collect();
+        Json(serde_json::json!({ "total": matching.len(), "sessions": matching }))
+    }
+
+    fn input(run_id: Option<&str>) -> AmpFocusInput {
+        AmpFocusInput {
+            action: FocusAction::List,
+            run_id: run_id.map(str::to_string),
+            title: None,
+            plan: None,
+            summary: None,
+            files_changed: None,
+            project_id: None,
+            since: None,
+            until: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_shows_every_agent_unless_run_id_given() {
+        let app = Router::new().route("/v1/focus/sessions", get(sessions));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = crate::amp_client::AmpClient::new(format!("http://{}", addr), 5).unwrap();
+        let text = |content: Vec<Content>| content[0].as_text().unwrap().text.clone();
+
+        // The caller's own run does not narrow the listing
+        let all = handle_focus(&client, Some("run-a"), input(None)).await;
+        let all = text(all.unwrap());
+        assert!(all.contains("(2 of 2)"));
+        assert!(all.contains("run-a | agent: claude | task: Fix parser"));
+        assert!(all.contains("run-b | agent: codex | task: Write docs"));
+
+        let one = handle_focus(&client, None, input(Some("run-b"))).await;
+        let one = text(one.unwrap());
+        assert!(one.contains("(1 of 1)"));
+        assert!(!one.contains("run-a"));
     }
 }
@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use anyhow::Result;
+use super::error::{AmpToolError, ToolResult};
 use rmcp::model::Content;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -18,18 +18,30 @@ pub struct AmpWriteArtifactInput {
     pub consequences: Option<String>,
     pub alternatives: Option<Vec<String>>,
     pub status: Option<String>,
+    /// Decision this one replaces; the old one is marked superseded
+    pub supersedes: Option<String>,
+    /// With `supersedes`, also link this decision to the old one's files
+    pub copy_file_links: Option<bool>,
     pub file_path: Option<String>,
     pub summary: Option<String>,
     pub symbols: Option<Vec<String>>,
     pub dependencies: Option<Vec<String>>,
+    /// Body of a note or warning (markdown)
+    #[serde(alias = "body")]
     pub content: Option<String>,
     pub category: Option<String>,
+    /// Warning severity: low, medium, high or critical
+    pub severity: Option<String>,
+    /// RFC 3339 time after which a warning no longer shows up in queries
+    pub expires_at: Option<String>,
     pub description: Option<String>,
     pub diff_summary: Option<String>,
     pub files_changed: Option<Vec<String>>,
     pub linked_objects: Option<Vec<String>>,
     pub linked_decisions: Option<Vec<String>>,
     pub linked_files: Option<Vec<String>>,
+    /// File paths this artifact concerns; resolved and linked at write time
+    pub related_files: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -47,10 +59,122 @@ pub struct AmpRunEndInput {
     pub summary: String,
 }
 
+/// Final status for a completed run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Completed => "completed",
+            RunStatus::Failed => "failed",
+            RunStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Input for amp_run_complete - transitions a run out of `running`.
+/// Deprecated in favour of amp_run's complete, fail and cancel actions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmpRunCompleteInput {
+    /// Run ID (defaults to the current session run)
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Final status: completed | failed | cancelled
+    pub status: RunStatus,
+    /// Optional summary of what the run produced
+    #[serde(default)]
+    pub output_summary: Option<String>,
+    /// Optional run duration in milliseconds
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+}
+
+/// One option considered for a decision (mirrors models::DecisionOption)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DecisionOption {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub pros: Option<Vec<String>>,
+    #[serde(default)]
+    pub cons: Option<Vec<String>>,
+}
+
+/// Input for amp_decision_create - a typed Decision object
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmpDecisionCreateInput {
+    pub title: String,
+    pub problem: String,
+    pub rationale: String,
+    pub outcome: String,
+    #[serde(default)]
+    pub options: Option<Vec<DecisionOption>>,
+    /// proposed | accepted | rejected | superseded (default: accepted)
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Run whose work this decision justifies; linked with `justified_by`
+    #[serde(default)]
+    pub related_run_id: Option<String>,
+}
+
+const DECISION_STATUSES: &[&str] = &["proposed", "accepted", "rejected", "superseded"];
+
+/// Input for amp_changeset_create - a typed ChangeSet object
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmpChangesetCreateInput {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Paths of the files touched; each indexed file is linked with `modifies`
+    pub files_changed: Vec<String>,
+    #[serde(default)]
+    pub diff: Option<String>,
+    #[serde(default)]
+    pub commit_hash: Option<String>,
+    /// draft | review | approved | merged | rejected (default: draft)
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Run that produced this change; linked with `produced`
+    #[serde(default)]
+    pub run_id: Option<String>,
+}
+
+const CHANGESET_STATUSES: &[&str] = &["draft", "review", "approved", "merged", "rejected"];
+
+/// Input for amp_relationship_create - an explicit edge between two objects
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmpRelationshipCreateInput {
+    pub from_id: String,
+    pub to_id: String,
+    /// defined_in | depends_on | calls | justified_by | modifies | implements | produced | supersedes
+    pub relationship_type: String,
+    #[serde(default)]
+    #[schemars(schema_with = "super::query::schema_any_object")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Default edge types accepted by amp_relationship_create (see AMP_RELATIONSHIP_TYPES)
+pub const RELATIONSHIP_TYPES: &[&str] = &[
+    "defined_in",
+    "depends_on",
+    "calls",
+    "justified_by",
+    "modifies",
+    "implements",
+    "produced",
+    "supersedes",
+];
+
 pub async fn handle_run_start(
     client: &crate::amp_client::AmpClient,
     input: AmpRunStartInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let payload = serde_json::json!({
         "type": "run",
         "tenant_id": "default",
@@ -76,7 +200,7 @@ pub async fn handle_run_start(
 pub async fn handle_run_end(
     client: &crate::amp_client::AmpClient,
     input: AmpRunEndInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let mut outputs = Vec::new();
     if !input.summary.trim().is_empty() {
         outputs.push(serde_json::json!({
@@ -105,10 +229,370 @@ pub async fn handle_run_end(
     ))])
 }
 
+pub async fn handle_run_complete(
+    client: &crate::amp_client::AmpClient,
+    current_run_id: Option<&str>,
+    input: AmpRunCompleteInput,
+) -> ToolResult<Vec<Content>> {
+    let run_id = input
+        .run_id
+        .as_deref()
+        .or(current_run_id)
+        .ok_or_else(|| AmpToolError::invalid_input("run_id required for amp_run_complete"))?;
+
+    let result = complete_run(
+        client,
+        run_id,
+        input.status,
+        RunCompletion {
+            output_summary: input.output_summary,
+            duration_ms: input.duration_ms,
+            ..RunCompletion::default()
+        },
+    )
+    .await?;
+
+    Ok(vec![Content::text(format!(
+        "Run {} marked {}: {}",
+        run_id,
+        input.status.as_str(),
+        serde_json::to_string_pretty(&result)?
+    ))])
+}
+
+/// How a run finished, beyond its status
+#[derive(Debug, Default)]
+pub struct RunCompletion {
+    /// Appended to the run's outputs
+    pub output_summary: Option<String>,
+    /// Run duration; defaults to the time since the run was created
+    pub duration_ms: Option<i64>,
+    /// Confidence in the outcome, 0.0-1.0
+    pub confidence: Option<f32>,
+    /// Appended to the run's errors
+    pub error: Option<String>,
+}
+
+/// Move a Run object out of `running`: set its final status and duration, and
+/// append the summary and error to its existing outputs and errors
+pub async fn complete_run(
+    client: &crate::amp_client::AmpClient,
+    run_id: &str,
+    status: RunStatus,
+    completion: RunCompletion,
+) -> ToolResult<serde_json::Value> {
+    if completion
+        .confidence
+        .is_some_and(|c| !(0.0..=1.0).contains(&c))
+    {
+        return Err(AmpToolError::invalid_input(
+            "confidence must be between 0.0 and 1.0",
+        ));
+    }
+    let run = client.get_object(run_id).await?;
+    if run.is_null() {
+        return Err(AmpToolError::NotFound(format!("Run {} not found", run_id)));
+    }
+
+    let mut outputs = existing(&run, "outputs");
+    if let Some(summary) = completion.output_summary.filter(|s| !s.trim().is_empty()) {
+        outputs.push(serde_json::json!({
+            "type": "response",
+            "content": summary,
+            "metadata": { "kind": "summary", "status": status.as_str() }
+        }));
+    }
+
+    let mut payload = serde_json::json!({
+        "status": status.as_str(),
+        "outputs": outputs
+    });
+    if let Some(duration_ms) = completion.duration_ms.or_else(|| elapsed_ms(&run)) {
+        payload["duration_ms"] = serde_json::json!(duration_ms);
+    }
+    if let Some(confidence) = completion.confidence {
+        payload["confidence"] = serde_json::json!(confidence);
+    }
+    if let Some(message) = completion.error.filter(|e| !e.trim().is_empty()) {
+        let mut errors = existing(&run, "errors");
+        errors.push(serde_json::json!({ "message": message }));
+        payload["errors"] = serde_json::Value::Array(errors);
+    }
+
+    Ok(client.update_object(run_id, payload).await?)
+}
+
+fn existing(run: &serde_json::Value, key: &str) -> Vec<serde_json::Value> {
+    run.get(key)
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Milliseconds since the run was created, when its timestamp parses
+fn elapsed_ms(run: &serde_json::Value) -> Option<i64> {
+    let created = run.get("created_at").and_then(|v| v.as_str())?;
+    let created = chrono::DateTime::parse_from_rfc3339(created).ok()?;
+    Some((chrono::Utc::now() - created.with_timezone(&chrono::Utc)).num_milliseconds())
+}
+
+pub async fn handle_decision_create(
+    client: &crate::amp_client::AmpClient,
+    agent_label: &str,
+    project_id: Option<&str>,
+    input: AmpDecisionCreateInput,
+) -> ToolResult<Vec<Content>> {
+    let status = input
+        .status
+        .as_deref()
+        .map(|s| s.trim().to_lowercase())
+        .unwrap_or_else(|| "accepted".to_string());
+    if !DECISION_STATUSES.contains(&status.as_str()) {
+        return Err(AmpToolError::InvalidInput(format!(
+            "Invalid decision status '{}'. Expected one of: {}",
+            status,
+            DECISION_STATUSES.join(", ")
+        )));
+    }
+
+    let decision_id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    let option_count = input.options.as_ref().map(|o| o.len()).unwrap_or(0);
+    let payload = serde_json::json!({
+        "id": decision_id,
+        "type": "decision",
+        "tenant_id": "default",
+        "project_id": project_id.unwrap_or("default"),
+        "created_at": now,
+        "updated_at": now,
+        "provenance": {
+            "agent": agent_label,
+            "model": null,
+            "tools": ["amp_decision_create"],
+            "summary": input.title
+        },
+        "links": [],
+        "title": input.title,
+        "problem": input.problem,
+        "options": input.options,
+        "rationale": input.rationale,
+        "outcome": input.outcome,
+        "status": status
+    });
+
+    let result = client.create_object(payload).await?;
+    let created_id = result
+        .get("id")
+        .and_then(|v| v.as_str())
+        .map(|id| id.trim_start_matches("objects:").to_string())
+        .ok_or_else(|| AmpToolError::Internal(format!("Decision create failed: {}", result)))?;
+
+    let mut summary = format!(
+        "Decision created: {}\nTitle: {}\nStatus: {}\nOptions considered: {}\nAgent: {}\n",
+        created_id, input.title, status, option_count, agent_label
+    );
+
+    if let Some(run_id) = input.related_run_id.filter(|id| !id.trim().is_empty()) {
+        let relationship = serde_json::json!({
+            "type": "justified_by",
+            "source_id": run_id,
+            "target_id": created_id,
+            "project_id": project_id
+        });
+        match client.create_relationship(relationship).await {
+            Ok(_) => summary.push_str(&format!("Linked: run {} -justified_by-> decision\n", run_id)),
+            Err(e) => summary.push_str(&format!("Warning: failed to link run {}: {}\n", run_id, e)),
+        }
+    }
+
+    Ok(vec![Content::text(summary)])
+}
+
+pub async fn handle_changeset_create(
+    client: &crate::amp_client::AmpClient,
+    agent_label: &str,
+    project_id: Option<&str>,
+    input: AmpChangesetCreateInput,
+) -> ToolResult<Vec<Content>> {
+    let status = input
+        .status
+        .as_deref()
+        .map(|s| s.trim().to_lowercase())
+        .unwrap_or_else(|| "draft".to_string());
+    if !CHANGESET_STATUSES.contains(&status.as_str()) {
+        return Err(AmpToolError::InvalidInput(format!(
+            "Invalid changeset status '{}'. Expected one of: {}",
+            status,
+            CHANGESET_STATUSES.join(", ")
+        )));
+    }
+
+    let changeset_id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    let payload = serde_json::json!({
+        "id": changeset_id,
+        "type": "changeset",
+        "tenant_id": "default",
+        "project_id": project_id.unwrap_or("default"),
+        "created_at": now,
+        "updated_at": now,
+        "provenance": {
+            "agent": agent_label,
+            "model": null,
+            "tools": ["amp_changeset_create"],
+            "summary": input.title
+        },
+        "links": [],
+        "title": input.title,
+        "description": input.description,
+        "diff": input.diff,
+        "files_changed": input.files_changed,
+        "tests": null,
+        "status": status,
+        "commit_hash": input.commit_hash
+    });
+
+    let result = client.create_object(payload).await?;
+    let created_id = result
+        .get("id")
+        .and_then(|v| v.as_str())
+        .map(|id| id.trim_start_matches("objects:").to_string())
+        .ok_or_else(|| AmpToolError::Internal(format!("Changeset create failed: {}", result)))?;
+
+    let mut summary = format!(
+        "Changeset created: {}\nTitle: {}\nStatus: {}\nFiles changed: {}\nAgent: {}\n",
+        created_id,
+        input.title,
+        status,
+        input.files_changed.len(),
+        agent_label
+    );
+
+    if let Some(run_id) = input.run_id.filter(|id| !id.trim().is_empty()) {
+        let relationship = serde_json::json!({
+            "type": "produced",
+            "source_id": run_id,
+            "target_id": created_id,
+            "project_id": project_id
+        });
+        match client.create_relationship(relationship).await {
+            Ok(_) => summary.push_str(&format!("Linked: run {} -produced-> changeset\n", run_id)),
+            Err(e) => summary.push_str(&format!("Warning: failed to link run {}: {}\n", run_id, e)),
+        }
+    }
+
+    let mut unlinked = Vec::new();
+    for path in &input.files_changed {
+        let file_log_id = match client.get_file_log(path).await {
+            Ok(file_log) => file_log
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(|id| id.trim_start_matches("objects:").to_string()),
+            Err(e) => {
+                tracing::debug!("No file log for changed file {}: {}", path, e);
+                None
+            }
+        };
+        let Some(file_log_id) = file_log_id else {
+            unlinked.push(path.as_str());
+            continue;
+        };
+        let relationship = serde_json::json!({
+            "type": "modifies",
+            "source_id": created_id,
+            "target_id": file_log_id,
+            "project_id": project_id
+        });
+        match client.create_relationship(relationship).await {
+            Ok(_) => summary.push_str(&format!("Linked: changeset -modifies-> {}\n", path)),
+            Err(e) => {
+                tracing::debug!("Failed to link changed file {}: {}", path, e);
+                unlinked.push(path.as_str());
+            }
+        }
+    }
+    if !unlinked.is_empty() {
+        summary.push_str(&format!(
+            "Not indexed (unlinked): {}\n",
+            unlinked.join(", ")
+        ));
+    }
+
+    Ok(vec![Content::text(summary)])
+}
+
+/// "Symbol parse_file" style label for a fetched object
+fn object_label(object: &serde_json::Value, id: &str) -> String {
+    let kind = object
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("object");
+    let mut chars = kind.chars();
+    let kind = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+    let name = ["name", "title", "file_path"]
+        .iter()
+        .find_map(|key| object.get(*key).and_then(|v| v.as_str()))
+        .unwrap_or(id);
+    format!("{} {}", kind, name)
+}
+
+pub async fn handle_relationship_create(
+    client: &crate::amp_client::AmpClient,
+    project_id: Option<&str>,
+    allowed_types: &[String],
+    input: AmpRelationshipCreateInput,
+) -> ToolResult<Vec<Content>> {
+    let relationship_type = input.relationship_type.trim().to_lowercase();
+    if !allowed_types.contains(&relationship_type) {
+        return Err(AmpToolError::InvalidInput(format!(
+            "Invalid relationship type '{}'. Expected one of: {}",
+            input.relationship_type,
+            allowed_types.join(", ")
+        )));
+    }
+
+    let from_id = input.from_id.trim_start_matches("objects:");
+    let to_id = input.to_id.trim_start_matches("objects:");
+    let mut labels = Vec::new();
+    for id in [from_id, to_id] {
+        let object = client.get_object(id).await?;
+        if object.is_null() || object.get("error").is_some() {
+            return Err(AmpToolError::NotFound(format!("Object not found: {}", id)));
+        }
+        labels.push(object_label(&object, id));
+    }
+
+    // The server stores supersession as old -superseded_by-> new
+    let (edge_type, source_id, target_id) = match relationship_type.as_str() {
+        "supersedes" => ("superseded_by", to_id, from_id),
+        other => (other, from_id, to_id),
+    };
+    let relationship = serde_json::json!({
+        "type": edge_type,
+        "source_id": source_id,
+        "target_id": target_id,
+        "metadata": input.metadata,
+        "project_id": project_id
+    });
+    let result = client.create_relationship(relationship).await?;
+    let relationship_id = result
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AmpToolError::Internal(format!("Relationship create failed: {}", result)))?;
+
+    Ok(vec![Content::text(format!(
+        "Relationship created: {}\n{} {} {}\n",
+        relationship_id, labels[0], relationship_type, labels[1]
+    ))])
+}
+
 pub async fn handle_write_artifact(
     client: &crate::amp_client::AmpClient,
     input: AmpWriteArtifactInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
     let mut payload = serde_json::Map::new();
     payload.insert(
         "type".to_string(),
@@ -140,6 +624,11 @@ pub async fn handle_write_artifact(
         input.alternatives.map(|value| serde_json::json!(value)),
     );
     insert_optional("status", input.status.map(serde_json::Value::String));
+    insert_optional("supersedes", input.supersedes.map(serde_json::Value::String));
+    insert_optional(
+        "copy_file_links",
+        input.copy_file_links.map(serde_json::Value::Bool),
+    );
     insert_optional("file_path", input.file_path.map(serde_json::Value::String));
     insert_optional("summary", input.summary.map(serde_json::Value::String));
     insert_optional(
@@ -152,6 +641,11 @@ pub async fn handle_write_artifact(
     );
     insert_optional("content", input.content.map(serde_json::Value::String));
     insert_optional("category", input.category.map(serde_json::Value::String));
+    insert_optional("severity", input.severity.map(serde_json::Value::String));
+    insert_optional(
+        "expires_at",
+        input.expires_at.map(serde_json::Value::String),
+    );
     insert_optional(
         "description",
         input.description.map(serde_json::Value::String),
@@ -177,12 +671,373 @@ pub async fn handle_write_artifact(
         input.linked_files.map(|value| serde_json::json!(value)),
     );
 
-    let result = client
+    // Resolve related paths the same way amp_file_path_resolve does, so the
+    // server links against canonical stored paths.
+    let mut unresolved_files = Vec::new();
+    if let Some(related_files) = input.related_files {
+        let mut resolved_files = Vec::new();
+        for path in related_files {
+            match crate::tools::files::resolve_file_path(client, &path).await {
+                Ok(resolution) => match resolution.resolved_path {
+                    Some(resolved) => resolved_files.push(resolved),
+                    None => unresolved_files.push(path),
+                },
+                Err(e) => {
+                    tracing::debug!("Failed to resolve related file {}: {}", path, e);
+                    unresolved_files.push(path);
+                }
+            }
+        }
+        payload.insert("related_files".to_string(), serde_json::json!(resolved_files));
+    }
+
+    let mut result = client
         .write_artifact(serde_json::Value::Object(payload))
         .await?;
 
+    if let Some(server_unresolved) = result
+        .get("unresolved_files")
+        .and_then(|value| value.as_array())
+    {
+        unresolved_files.extend(
+            server_unresolved
+                .iter()
+                .filter_map(|value| value.as_str())
+                .map(|value| value.to_string()),
+        );
+    }
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert(
+            "unresolved_files".to_string(),
+            serde_json::json!(unresolved_files),
+        );
+    }
+
     Ok(vec![Content::text(format!(
         "Artifact created: {}",
         serde_json::to_string_pretty(&result)?
     ))])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::{Path, State},
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    type Store = Arc<Mutex<HashMap<String, Value>>>;
+
+    async fn get_object(State(store): State<Store>, Path(id): Path<String>) -> Json<Value> {
+        Json(store.lock().unwrap().get(&id).cloned().unwrap_or(Value::Null))
+    }
+
+    async fn put_object(
+        State(store): State<Store>,
+        Path(id): Path<String>,
+        Json(patch): Json<Value>,
+    ) -> Json<Value> {
+        let mut store = store.lock().unwrap();
+        let object = store.entry(id).or_insert_with(|| serde_json::json!({}));
+        if let (Some(target), Some(fields)) = (object.as_object_mut(), patch.as_object()) {
+            for (key, value) in fields {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+        Json(object.clone())
+    }
+
+    async fn create_object(State(store): State<Store>, Json(object): Json<Value>) -> Json<Value> {
+        let id = object["id"].as_str().unwrap().to_string();
+        store.lock().unwrap().insert(id.clone(), object);
+        Json(serde_json::json!({ "id": id }))
+    }
+
+    async fn create_relationship(
+        State(store): State<Store>,
+        Json(relationship): Json<Value>,
+    ) -> Json<Value> {
+        let mut store = store.lock().unwrap();
+        let edges = store
+            .entry("relationships".to_string())
+            .or_insert_with(|| serde_json::json!([]));
+        edges.as_array_mut().unwrap().push(relationship);
+        Json(serde_json::json!({ "id": "rel-1" }))
+    }
+
+    /// File logs are stored under `filelog:<path>`; anything else is a 404
+    async fn get_file_log(
+        State(store): State<Store>,
+        Path(path): Path<String>,
+    ) -> Result<Json<Value>, axum::http::StatusCode> {
+        store
+            .lock()
+            .unwrap()
+            .get(&format!("filelog:{}", path))
+            .cloned()
+            .map(Json)
+            .ok_or(axum::http::StatusCode::NOT_FOUND)
+    }
+
+    async fn spawn_mock(store: Store) -> String {
+        let app = Router::new()
+            .route("/v1/objects", post(create_object))
+            .route("/v1/objects/{id}", get(get_object).put(put_object))
+            .route("/v1/relationships", post(create_relationship))
+            .route("/v1/codebase/file-log-objects/{path}", get(get_file_log))
+            .with_state(store);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_run_complete_transitions_running_run() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        store.lock().unwrap().insert(
+            "run-1".to_string(),
+            serde_json::json!({
+                "type": "run",
+                "status": "running",
+                "outputs": [{ "type": "focus", "content": "Fix cache UI" }]
+            }),
+        );
+        let base_url = spawn_mock(store.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let input = AmpRunCompleteInput {
+            run_id: None,
+            status: RunStatus::Completed,
+            output_summary: Some("Shipped the fix".to_string()),
+            duration_ms: Some(1200),
+        };
+        handle_run_complete(&client, Some("run-1"), input)
+            .await
+            .unwrap();
+
+        let run = store.lock().unwrap().get("run-1").cloned().unwrap();
+        assert_eq!(run["status"], "completed");
+        assert_eq!(run["duration_ms"], 1200);
+        let outputs = run["outputs"].as_array().unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[1]["content"], "Shipped the fix");
+    }
+
+    #[tokio::test]
+    async fn test_decision_create_builds_decision_and_links_run() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        let base_url = spawn_mock(store.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let input = AmpDecisionCreateInput {
+            title: "Use RRF for hybrid ranking".to_string(),
+            problem: "Scores from text and vector search are not comparable".to_string(),
+            rationale: "Rank fusion needs no score calibration".to_string(),
+            outcome: "Hybrid retrieval merges by reciprocal rank".to_string(),
+            options: Some(vec![DecisionOption {
+                name: "Weighted sum".to_string(),
+                description: "Normalize and add scores".to_string(),
+                pros: None,
+                cons: Some(vec!["Needs tuning".to_string()]),
+            }]),
+            status: None,
+            related_run_id: Some("run-1".to_string()),
+        };
+        let content = handle_decision_create(&client, "codex-1a2b", Some("amp"), input)
+            .await
+            .unwrap();
+        assert_eq!(content.len(), 1);
+
+        let store = store.lock().unwrap();
+        let (decision_id, decision) = store
+            .iter()
+            .find(|(_, value)| value["type"] == "decision")
+            .map(|(id, value)| (id.clone(), value.clone()))
+            .unwrap();
+        assert_eq!(decision["status"], "accepted");
+        assert_eq!(decision["project_id"], "amp");
+        assert_eq!(decision["provenance"]["agent"], "codex-1a2b");
+        assert_eq!(decision["options"][0]["name"], "Weighted sum");
+
+        let edges = store["relationships"].as_array().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0]["type"], "justified_by");
+        assert_eq!(edges[0]["source_id"], "run-1");
+        assert_eq!(edges[0]["target_id"], decision_id.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_decision_create_rejects_unknown_status() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        let base_url = spawn_mock(store.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let input = AmpDecisionCreateInput {
+            title: "t".to_string(),
+            problem: "p".to_string(),
+            rationale: "r".to_string(),
+            outcome: "o".to_string(),
+            options: None,
+            status: Some("maybe".to_string()),
+            related_run_id: None,
+        };
+        assert!(handle_decision_create(&client, "agent", None, input).await.is_err());
+        assert!(store.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changeset_create_links_run_and_file_logs() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        store.lock().unwrap().insert(
+            "filelog:src/lib.rs".to_string(),
+            serde_json::json!({ "id": "objects:log-lib", "file_path": "src/lib.rs" }),
+        );
+        let base_url = spawn_mock(store.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let input = AmpChangesetCreateInput {
+            title: "Stream parser tokens".to_string(),
+            description: Some("Avoid buffering whole files".to_string()),
+            files_changed: vec!["src/lib.rs".to_string(), "src/new.rs".to_string()],
+            diff: None,
+            commit_hash: Some("abc123".to_string()),
+            status: None,
+            run_id: Some("run-1".to_string()),
+        };
+        let content = handle_changeset_create(&client, "codex-1a2b", Some("amp"), input)
+            .await
+            .unwrap();
+        assert_eq!(content.len(), 1);
+
+        let store = store.lock().unwrap();
+        let (changeset_id, changeset) = store
+            .iter()
+            .find(|(_, value)| value["type"] == "changeset")
+            .map(|(id, value)| (id.clone(), value.clone()))
+            .unwrap();
+        assert_eq!(changeset["status"], "draft");
+        assert_eq!(changeset["commit_hash"], "abc123");
+        assert_eq!(changeset["files_changed"][1], "src/new.rs");
+
+        let edges: Vec<(&str, &str, &str)> = store["relationships"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| {
+                (
+                    edge["type"].as_str().unwrap(),
+                    edge["source_id"].as_str().unwrap(),
+                    edge["target_id"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("produced", "run-1", changeset_id.as_str()),
+                ("modifies", changeset_id.as_str(), "log-lib"),
+            ]
+        );
+    }
+
+    fn relationship_types() -> Vec<String> {
+        RELATIONSHIP_TYPES.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_relationship_create_links_existing_objects() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        store.lock().unwrap().extend([
+            (
+                "sym-a".to_string(),
+                serde_json::json!({ "type": "symbol", "name": "parse_file" }),
+            ),
+            (
+                "sym-b".to_string(),
+                serde_json::json!({ "type": "symbol", "name": "tokenize" }),
+            ),
+        ]);
+        let base_url = spawn_mock(store.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let input = AmpRelationshipCreateInput {
+            from_id: "objects:sym-a".to_string(),
+            to_id: "sym-b".to_string(),
+            relationship_type: "depends_on".to_string(),
+            metadata: Some(serde_json::json!({ "reason": "manual" })),
+        };
+        let content =
+            handle_relationship_create(&client, Some("amp"), &relationship_types(), input)
+                .await
+                .unwrap();
+        let text = serde_json::to_value(&content[0]).unwrap()["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.contains("rel-1"));
+        assert!(text.contains("Symbol parse_file depends_on Symbol tokenize"));
+
+        let input = AmpRelationshipCreateInput {
+            from_id: "sym-b".to_string(),
+            to_id: "sym-a".to_string(),
+            relationship_type: "supersedes".to_string(),
+            metadata: None,
+        };
+        handle_relationship_create(&client, None, &relationship_types(), input)
+            .await
+            .unwrap();
+
+        let store = store.lock().unwrap();
+        let edges = store["relationships"].as_array().unwrap();
+        assert_eq!(edges[0]["type"], "depends_on");
+        assert_eq!(edges[0]["source_id"], "sym-a");
+        assert_eq!(edges[0]["target_id"], "sym-b");
+        assert_eq!(edges[0]["metadata"]["reason"], "manual");
+        assert_eq!(edges[0]["project_id"], "amp");
+        assert_eq!(edges[1]["type"], "superseded_by");
+        assert_eq!(edges[1]["source_id"], "sym-a");
+        assert_eq!(edges[1]["target_id"], "sym-b");
+    }
+
+    #[tokio::test]
+    async fn test_relationship_create_rejects_unknown_type_and_missing_objects() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        store
+            .lock()
+            .unwrap()
+            .insert("sym-a".to_string(), serde_json::json!({ "type": "symbol" }));
+        let base_url = spawn_mock(store.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let input = AmpRelationshipCreateInput {
+            from_id: "sym-a".to_string(),
+            to_id: "sym-a".to_string(),
+            relationship_type: "likes".to_string(),
+            metadata: None,
+        };
+        let err = handle_relationship_create(&client, None, &relationship_types(), input)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "invalid_input");
+
+        let input = AmpRelationshipCreateInput {
+            from_id: "sym-a".to_string(),
+            to_id: "missing".to_string(),
+            relationship_type: "calls".to_string(),
+            metadata: None,
+        };
+        let err = handle_relationship_create(&client, None, &relationship_types(), input)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "not_found");
+        assert!(!store.lock().unwrap().contains_key("relationships"));
+    }
+}
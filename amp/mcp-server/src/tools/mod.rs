@@ -3,9 +3,12 @@ pub mod cache;
 pub mod coordination;
 pub mod focus;
 pub mod discovery;
+pub mod error;
 pub mod files;
 pub mod memory;
 pub mod query;
+pub mod runs;
+pub mod tasks;
 
 use anyhow::Result;
 
@@ -1,3 +1,4 @@
+use super::error::{AmpToolError, ToolResult};
 use anyhow::Result;
 use rmcp::model::Content;
 use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
@@ -6,7 +7,15 @@ use serde_json::Value;
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AmpQueryInput {
-    pub query: String,
+    /// Free-text query (mutually exclusive with similar_to_id)
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Find objects similar to this object ID by embedding (mutually exclusive with query)
+    #[serde(default)]
+    pub similar_to_id: Option<String>,
+    /// Minimum similarity score for similar_to_id results
+    #[serde(default)]
+    pub min_score: Option<f32>,
     #[serde(default = "default_mode")]
     pub mode: String,
     #[schemars(schema_with = "schema_any_object")]
@@ -16,13 +25,101 @@ pub struct AmpQueryInput {
     pub graph_intersect: Option<bool>,
     pub graph_autoseed: Option<bool>,
     pub limit: Option<u64>,
+    /// Include per-result score breakdown and retrieval source (hybrid mode)
+    pub explain: Option<bool>,
+    /// Return the query embedding and each result's stored embedding (server must
+    /// set AMP_ALLOW_DEBUG_VECTORS)
+    #[serde(default)]
+    pub debug_vectors: Option<bool>,
+    /// Collapse chunk hits from the same file into one result (default true)
+    #[serde(default)]
+    pub group_by_file: Option<bool>,
+    /// Only return these object types, e.g. ["decision", "note"]
+    #[serde(default)]
+    pub object_types: Vec<String>,
+    /// Only return objects from this project
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Only return objects created at or after this RFC 3339 timestamp
+    #[serde(default)]
+    pub created_after: Option<String>,
+    /// Only return objects created at or before this RFC 3339 timestamp
+    #[serde(default)]
+    pub created_before: Option<String>,
+    /// Also return warnings past their expiry (hidden by default)
+    #[serde(default)]
+    pub include_expired: Option<bool>,
+}
+
+/// Merge the top-level metadata filters into the free-form `filters` object.
+fn metadata_filters(input: &AmpQueryInput) -> ToolResult<Option<serde_json::Map<String, Value>>> {
+    let mut filters = input
+        .filters
+        .as_ref()
+        .and_then(|f| f.as_object().cloned())
+        .unwrap_or_default();
+
+    if let Some(type_value) = filters.get_mut("type") {
+        if let Some(type_str) = type_value.as_str() {
+            *type_value = serde_json::json!([type_str]);
+        }
+    }
+    if !input.object_types.is_empty() {
+        filters.insert("type".to_string(), serde_json::json!(input.object_types));
+    }
+    if let Some(project_id) = &input.project_id {
+        filters.insert("project_id".to_string(), serde_json::json!(project_id));
+    }
+    if let Some(include_expired) = input.include_expired {
+        filters.insert(
+            "include_expired".to_string(),
+            serde_json::json!(include_expired),
+        );
+    }
+
+    let created_after = parse_timestamp("created_after", input.created_after.as_deref())?;
+    let created_before = parse_timestamp("created_before", input.created_before.as_deref())?;
+    if let (Some(after), Some(before)) = (created_after, created_before) {
+        if after > before {
+            return Err(AmpToolError::invalid_input(
+                "created_after must not be later than created_before",
+            ));
+        }
+    }
+    for (key, timestamp) in [
+        ("created_after", created_after),
+        ("created_before", created_before),
+    ] {
+        if let Some(timestamp) = timestamp {
+            filters.insert(key.to_string(), serde_json::json!(timestamp.to_rfc3339()));
+        }
+    }
+
+    Ok((!filters.is_empty()).then_some(filters))
+}
+
+fn parse_timestamp(
+    field: &str,
+    value: Option<&str>,
+) -> ToolResult<Option<chrono::DateTime<chrono::Utc>>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    chrono::DateTime::parse_from_rfc3339(value.trim())
+        .map(|timestamp| Some(timestamp.with_timezone(&chrono::Utc)))
+        .map_err(|e| {
+            AmpToolError::invalid_input(format!(
+                "{} must be an RFC 3339 timestamp such as 2024-06-01T00:00:00Z (got '{}': {})",
+                field, value, e
+            ))
+        })
 }
 
 fn default_mode() -> String {
     "hybrid".to_string()
 }
 
-fn schema_any_object(_gen: &mut SchemaGenerator) -> Schema {
+pub(crate) fn schema_any_object(_gen: &mut SchemaGenerator) -> Schema {
     json_schema!({
         "type": "object",
         "additionalProperties": true
@@ -34,20 +131,69 @@ pub struct AmpTraceInput {
     pub object_id: String,
     #[serde(default = "default_depth")]
     pub depth: i32,
+    /// relationships (default), path (to target_id), common (neighbors shared with
+    /// object_ids) or versions (snapshots taken before each update)
+    #[serde(default)]
+    pub mode: TraceMode,
+    /// Path mode: object id the path should reach
+    pub target_id: Option<String>,
+    /// Common mode: further object ids to intersect with object_id
+    pub object_ids: Option<Vec<String>>,
+    /// Path mode: relation names to follow (all when omitted); common mode: the first
+    /// entry is the edge type to intersect on (default depends_on)
+    pub edge_types: Option<Vec<String>>,
+    /// Versions mode: return this version's full snapshot instead of the list
+    #[serde(default)]
+    pub version_number: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceMode {
+    #[default]
+    Relationships,
+    Path,
+    Common,
+    Versions,
 }
 
 fn default_depth() -> i32 {
     2
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AmpPathInput {
+    /// Object id the path starts from
+    pub from_id: String,
+    /// Object id the path should reach
+    pub to_id: String,
+    /// Maximum number of hops to search (server default 6, max 10)
+    pub max_depth: Option<usize>,
+}
+
 pub async fn handle_amp_query(
     client: &crate::amp_client::AmpClient,
     input: AmpQueryInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
+    let text = match (input.query.as_deref(), input.similar_to_id.as_deref()) {
+        (Some(_), Some(_)) => {
+            return Err(AmpToolError::invalid_input(
+                "query and similar_to_id are mutually exclusive",
+            ))
+        }
+        (None, Some(object_id)) => return handle_similar_query(client, object_id, &input).await,
+        (Some(text), None) => text.to_string(),
+        (None, None) => {
+            return Err(AmpToolError::invalid_input(
+                "either query or similar_to_id is required",
+            ))
+        }
+    };
+
     let mode = input.mode.as_str();
     let is_hybrid = mode == "hybrid";
     let mut query = serde_json::json!({
-        "text": input.query,
+        "text": text,
         "hybrid": is_hybrid,
         "limit": input.limit.unwrap_or(5)
     });
@@ -64,17 +210,20 @@ pub async fn handle_amp_query(
         query["graph_autoseed"] = serde_json::json!(graph_autoseed);
     }
 
-    if let Some(filters) = input.filters {
-        if let Some(mut filters_obj) = filters.as_object().cloned() {
-            if let Some(type_value) = filters_obj.get_mut("type") {
-                if let Some(type_str) = type_value.as_str() {
-                    *type_value = serde_json::json!([type_str]);
-                }
-            }
-            if !filters_obj.is_empty() {
-                query["filters"] = serde_json::Value::Object(filters_obj);
-            }
-        }
+    if let Some(explain) = input.explain {
+        query["explain"] = serde_json::json!(explain);
+    }
+
+    if let Some(debug_vectors) = input.debug_vectors {
+        query["debug_vectors"] = serde_json::json!(debug_vectors);
+    }
+
+    if let Some(group_by_file) = input.group_by_file {
+        query["group_by_file"] = serde_json::json!(group_by_file);
+    }
+
+    if let Some(filters) = metadata_filters(&input)? {
+        query["filters"] = serde_json::Value::Object(filters);
     }
 
     // Enable graph traversal when we have start_nodes, or pass overrides for autoseed
@@ -86,7 +235,9 @@ pub async fn handle_amp_query(
                 .map(|arr| !arr.is_empty())
                 .unwrap_or(false);
             if mode == "graph" && !has_start_nodes {
-                anyhow::bail!("graph mode requires graph_options.start_nodes");
+                return Err(AmpToolError::invalid_input(
+                    "graph mode requires graph_options.start_nodes",
+                ));
             }
             if has_start_nodes {
                 // Explicit graph mode: pass full graph options including start_nodes
@@ -113,7 +264,9 @@ pub async fn handle_amp_query(
             }
         }
     } else if mode == "graph" {
-        anyhow::bail!("graph mode requires graph_options");
+        return Err(AmpToolError::invalid_input(
+            "graph mode requires graph_options",
+        ));
     }
 
     if mode == "text" {
@@ -135,18 +288,101 @@ pub async fn handle_amp_query(
     let result = client.query(query).await?;
 
     // Summarize RRF results with scoring details
-    let summary = summarize_rrf_results(&result, &input.query)?;
+    let summary = summarize_results(
+        &result,
+        &format!("Hybrid Query (RRF): {}", text),
+        "Reciprocal Rank Fusion",
+        "RRF Score",
+    )?;
+
+    let mut content = vec![Content::text(summary)];
+    if let Some(vectors) = debug_vectors(&result) {
+        content.push(Content::text(vectors));
+    }
+    Ok(content)
+}
+
+/// Pretty-printed query and result embeddings, when the server returned any.
+fn debug_vectors(result: &Value) -> Option<String> {
+    let results: Vec<Value> = result
+        .get("results")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let embedding = item.get("embedding")?;
+            Some(serde_json::json!({
+                "id": item.get("object").and_then(|o| o.get("id")).cloned().unwrap_or(Value::Null),
+                "embedding": embedding,
+            }))
+        })
+        .collect();
+    let query_embedding = result.get("query_embedding");
+    if query_embedding.is_none() && results.is_empty() {
+        return None;
+    }
+    let vectors = serde_json::json!({
+        "query_embedding": query_embedding.cloned().unwrap_or(Value::Null),
+        "results": results,
+    });
+    serde_json::to_string_pretty(&vectors)
+        .ok()
+        .map(|json| format!("Debug vectors:\n{}", json))
+}
+
+async fn handle_similar_query(
+    client: &crate::amp_client::AmpClient,
+    object_id: &str,
+    input: &AmpQueryInput,
+) -> ToolResult<Vec<Content>> {
+    let mut request = serde_json::json!({
+        "object_id": object_id,
+        "limit": input.limit.unwrap_or(5),
+    });
+
+    if let Some(min_score) = input.min_score {
+        request["min_score"] = serde_json::json!(min_score);
+    }
+
+    // Reuse the type/project_id filters accepted by text queries
+    if let Some(filters) = metadata_filters(input)? {
+        if filters.contains_key("created_after") || filters.contains_key("created_before") {
+            return Err(AmpToolError::invalid_input(
+                "created_after/created_before are not supported with similar_to_id",
+            ));
+        }
+        if let Some(types) = filters.get("type") {
+            request["types"] = types.clone();
+        }
+        if let Some(project_id) = filters.get("project_id") {
+            request["project_id"] = project_id.clone();
+        }
+    }
+
+    let result = client.query_similar(request).await?;
+    let summary = summarize_results(
+        &result,
+        &format!("Similar to: {}", object_id),
+        "embedding similarity",
+        "Similarity",
+    )?;
 
     Ok(vec![Content::text(summary)])
 }
 
-fn summarize_rrf_results(result: &Value, query: &str) -> Result<String> {
-    let mut summary = format!("Hybrid Query (RRF): {}\n\n", query);
+fn summarize_results(
+    result: &Value,
+    heading: &str,
+    ranking: &str,
+    score_label: &str,
+) -> Result<String> {
+    let mut summary = format!("{}\n\n", heading);
 
     if let Some(results) = result.get("results").and_then(|r| r.as_array()) {
         summary.push_str(&format!(
-            "Found {} results (ranked by Reciprocal Rank Fusion):\n\n",
-            results.len()
+            "Found {} results (ranked by {}):\n\n",
+            results.len(),
+            ranking
         ));
 
         for (i, item) in results.iter().take(5).enumerate() {
@@ -217,22 +453,49 @@ fn summarize_rrf_results(result: &Value, query: &str) -> Result<String> {
                             ));
                             summary.push_str(&format!("   id: {}\n", obj_id));
                         }
-                        "filechunk" => {
+                        "note" => {
+                            let title = obj
+                                .get("title")
+                                .and_then(|t| t.as_str())
+                                .unwrap_or("unknown");
+                            summary.push_str(&format!("{}. Note: {}\n", i + 1, title));
+                            summary.push_str(&format!("   id: {}\n", obj_id));
+                        }
+                        "warning" => {
+                            let title = obj
+                                .get("title")
+                                .and_then(|t| t.as_str())
+                                .unwrap_or("unknown");
+                            let severity = obj
+                                .get("severity")
+                                .and_then(|s| s.as_str())
+                                .unwrap_or("unknown");
+                            summary.push_str(&format!(
+                                "{}. Warning [{}]: {}\n",
+                                i + 1,
+                                severity.to_uppercase(),
+                                title
+                            ));
+                            if let Some(expires_at) = obj.get("expires_at").and_then(|e| e.as_str())
+                            {
+                                summary.push_str(&format!("   expires: {}\n", expires_at));
+                            }
+                            summary.push_str(&format!("   id: {}\n", obj_id));
+                        }
+                        "FileChunk" | "filechunk" => {
                             let path = obj
                                 .get("file_path")
                                 .and_then(|p| p.as_str())
                                 .unwrap_or("unknown");
-                            let lines = format!(
-                                "{}-{}",
-                                obj.get("start_line").and_then(|l| l.as_u64()).unwrap_or(0),
-                                obj.get("end_line").and_then(|l| l.as_u64()).unwrap_or(0)
-                            );
                             summary.push_str(&format!(
-                                "{}. FileChunk: {} (lines {})\n",
+                                "{}. FileChunk: {} {}\n",
                                 i + 1,
                                 path,
-                                lines
+                                chunk_span(obj)
                             ));
+                            if let Some(group) = file_group(item) {
+                                summary.push_str(&group);
+                            }
                             summary.push_str(&format!("   id: {}\n", obj_id));
                         }
                         _ => {
@@ -250,7 +513,7 @@ fn summarize_rrf_results(result: &Value, query: &str) -> Result<String> {
             }
 
             // Add RRF scoring breakdown
-            summary.push_str(&format!("   RRF Score: {:.4}", total_score));
+            summary.push_str(&format!("   {}: {:.4}", score_label, total_score));
             if text_score.is_some() || vector_score.is_some() || graph_score.is_some() {
                 summary.push_str(" (");
                 let mut parts = Vec::new();
@@ -266,7 +529,29 @@ fn summarize_rrf_results(result: &Value, query: &str) -> Result<String> {
                 summary.push_str(&parts.join(", "));
                 summary.push_str(")");
             }
-            summary.push_str("\n\n");
+            summary.push('\n');
+
+            // Present only when the query was sent with explain: true
+            if let Some(source) = item.get("source").and_then(|s| s.as_str()) {
+                summary.push_str(&format!("   source: {}\n", source));
+            }
+            if let Some(breakdown) = item.get("score_breakdown") {
+                let fmt_score = |key: &str| {
+                    breakdown
+                        .get(key)
+                        .and_then(|v| v.as_f64())
+                        .map(|v| format!("{:.4}", v))
+                        .unwrap_or_else(|| "-".to_string())
+                };
+                summary.push_str(&format!(
+                    "   breakdown: vector={} text={} graph_boost={} final={}\n",
+                    fmt_score("vector_score"),
+                    fmt_score("text_score"),
+                    fmt_score("graph_boost"),
+                    fmt_score("final_score")
+                ));
+            }
+            summary.push('\n');
         }
 
         if results.len() > 5 {
@@ -282,7 +567,47 @@ fn summarize_rrf_results(result: &Value, query: &str) -> Result<String> {
 pub async fn handle_amp_trace(
     client: &crate::amp_client::AmpClient,
     input: AmpTraceInput,
-) -> Result<Vec<Content>> {
+) -> ToolResult<Vec<Content>> {
+    let edge_types = input.edge_types.clone().unwrap_or_default();
+    match input.mode {
+        TraceMode::Relationships => {}
+        TraceMode::Path => {
+            let target_id = input
+                .target_id
+                .as_deref()
+                .ok_or_else(|| AmpToolError::invalid_input("path mode requires target_id"))?;
+            let max_depth = usize::try_from(input.depth).ok().filter(|depth| *depth > 0);
+            let result = client
+                .path(&input.object_id, target_id, max_depth, &edge_types)
+                .await?;
+            return Ok(vec![Content::text(summarize_path(&result))]);
+        }
+        TraceMode::Common => {
+            let mut ids = vec![input.object_id.clone()];
+            ids.extend(input.object_ids.clone().unwrap_or_default());
+            if ids.len() < 2 {
+                return Err(AmpToolError::invalid_input(
+                    "common mode requires at least one id in object_ids",
+                ));
+            }
+            let edge_type = edge_types.first().map_or("depends_on", String::as_str);
+            let result = client.common_neighbors(&ids, edge_type).await?;
+            return Ok(vec![Content::text(summarize_common_neighbors(&result))]);
+        }
+        TraceMode::Versions => {
+            let summary = match input.version_number {
+                Some(version_number) => {
+                    let result = client
+                        .get_object_version(&input.object_id, version_number)
+                        .await?;
+                    summarize_version(&result)?
+                }
+                None => summarize_versions(&client.get_object_versions(&input.object_id).await?),
+            };
+            return Ok(vec![Content::text(summary)]);
+        }
+    }
+
     let params = serde_json::json!({
         "object_id": input.object_id,
         "depth": input.depth.min(2)  // Limit depth to prevent massive responses
@@ -296,6 +621,166 @@ pub async fn handle_amp_trace(
     Ok(vec![Content::text(summary)])
 }
 
+pub async fn handle_amp_path(
+    client: &crate::amp_client::AmpClient,
+    input: AmpPathInput,
+) -> ToolResult<Vec<Content>> {
+    let result = client
+        .path(&input.from_id, &input.to_id, input.max_depth, &[])
+        .await?;
+
+    Ok(vec![Content::text(summarize_path(&result))])
+}
+
+fn summarize_versions(result: &Value) -> String {
+    let id = result
+        .get("id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    let versions = result
+        .get("versions")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    if versions.is_empty() {
+        return format!("No versions of {} (never updated)\n", id);
+    }
+
+    let mut summary = format!("{} versions of {} (newest first):\n", versions.len(), id);
+    for version in &versions {
+        summary.push_str(&format!(
+            "  v{}  {}\n",
+            version
+                .get("version_number")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0),
+            version
+                .get("changed_at")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+        ));
+    }
+    summary.push_str("Pass version_number to see a full snapshot.\n");
+    summary
+}
+
+fn summarize_version(result: &Value) -> Result<String> {
+    let snapshot = result.get("snapshot").cloned().unwrap_or(Value::Null);
+    Ok(format!(
+        "Version {} of {} (replaced at {}):\n{}\n",
+        result
+            .get("version_number")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0),
+        result
+            .get("id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown"),
+        result
+            .get("changed_at")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown"),
+        serde_json::to_string_pretty(&snapshot)?
+    ))
+}
+
+fn summarize_path(result: &Value) -> String {
+    let field = |value: &Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string()
+    };
+    let from = field(result, "from");
+    let to = field(result, "to");
+
+    let found = result
+        .get("found")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if !found {
+        let truncated = result
+            .get("truncated")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if truncated {
+            return format!(
+                "No path found from {} to {} before the search hit the server's node budget\n",
+                from, to
+            );
+        }
+        return format!("No path found from {} to {}\n", from, to);
+    }
+
+    let edges = result
+        .get("edges")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let mut summary = format!("Path from {} to {} ({} hops):\n\n", from, to, edges.len());
+    for edge in &edges {
+        // Show each edge the way it is stored, even when walked backwards
+        let relation = field(edge, "relation");
+        let arrow = if field(edge, "direction") == "inbound" {
+            format!("<-[{}]-", relation)
+        } else {
+            format!("-[{}]->", relation)
+        };
+        summary.push_str(&format!(
+            "  {} {} {}\n",
+            field(edge, "from"),
+            arrow,
+            field(edge, "to")
+        ));
+    }
+
+    summary
+}
+
+fn summarize_common_neighbors(result: &Value) -> String {
+    let ids: Vec<&str> = result
+        .get("ids")
+        .and_then(|v| v.as_array())
+        .map(|ids| ids.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let edge_type = result
+        .get("edge_type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    let neighbors = result
+        .get("neighbors")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    if neighbors.is_empty() {
+        return format!("No shared {} neighbors for {}\n", edge_type, ids.join(", "));
+    }
+
+    let mut summary = format!(
+        "{} shared {} neighbors for {}:\n\n",
+        neighbors.len(),
+        edge_type,
+        ids.join(", ")
+    );
+    for neighbor in &neighbors {
+        let text = |key: &str| neighbor.get(key).and_then(|v| v.as_str());
+        let name = text("name")
+            .or_else(|| text("title"))
+            .or_else(|| text("file_path"))
+            .unwrap_or("unnamed");
+        summary.push_str(&format!(
+            "  - {} [{}] {}\n",
+            name,
+            text("type").unwrap_or("unknown"),
+            text("id").unwrap_or("unknown")
+        ));
+    }
+
+    summary
+}
+
 fn summarize_trace_results(result: &Value, object_id: &str, depth: i32) -> Result<String> {
     let mut summary = format!("Trace for object: {} (depth: {})\n\n", object_id, depth);
 
@@ -353,3 +838,417 @@ fn summarize_trace_results(result: &Value, object_id: &str, depth: i32) -> Resul
 
     Ok(summary)
 }
+
+/// "function parse_file (lines 120-180)" for chunks cut at a symbol,
+/// otherwise just the line range.
+pub(crate) fn chunk_span(chunk: &Value) -> String {
+    let line = |key: &str| chunk.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    let lines = format!("(lines {}-{})", line("start_line"), line("end_line"));
+    let field = |key: &str| chunk.get(key).and_then(|v| v.as_str());
+    match (field("symbol_kind"), field("symbol_name")) {
+        (Some(kind), Some(name)) => format!("{} {} {}", kind, name, lines),
+        _ => lines,
+    }
+}
+
+/// Matched-chunk lines for a result grouped by file, if the server grouped it.
+fn file_group(item: &Value) -> Option<String> {
+    let matched = item.get("matched_chunks").and_then(|v| v.as_u64())?;
+    let ranges: Vec<String> = item
+        .get("line_ranges")
+        .and_then(|v| v.as_array())
+        .map(|ranges| {
+            ranges
+                .iter()
+                .filter_map(|range| {
+                    let start = range.get(0).and_then(|v| v.as_u64())?;
+                    let end = range.get(1).and_then(|v| v.as_u64())?;
+                    Some(format!("{}-{}", start, end))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut group = format!(
+        "   matched {} chunks (lines {})\n",
+        matched,
+        ranges.join(", ")
+    );
+    if let Some(file_summary) = item.get("file_summary").and_then(|v| v.as_str()) {
+        group.push_str(&format!("   file: {}\n", file_summary));
+    }
+    Some(group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::post, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    type Requests = Arc<Mutex<Vec<Value>>>;
+
+    async fn similar(State(requests): State<Requests>, Json(body): Json<Value>) -> Json<Value> {
+        requests.lock().unwrap().push(body);
+        Json(serde_json::json!({
+            "results": [{
+                "object": { "id": "close", "type": "decision", "title": "Use RRF", "status": "accepted" },
+                "score": 0.97
+            }]
+        }))
+    }
+
+    async fn spawn_mock(requests: Requests) -> String {
+        let app = Router::new()
+            .route("/v1/query/similar", post(similar))
+            .route("/v1/query", post(similar))
+            .with_state(requests);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn input(query: Option<&str>, similar_to_id: Option<&str>) -> AmpQueryInput {
+        AmpQueryInput {
+            query: query.map(str::to_string),
+            similar_to_id: similar_to_id.map(str::to_string),
+            min_score: Some(0.7),
+            mode: default_mode(),
+            filters: Some(serde_json::json!({ "type": "decision" })),
+            graph_options: None,
+            graph_intersect: None,
+            graph_autoseed: None,
+            limit: None,
+            explain: None,
+            debug_vectors: None,
+            group_by_file: None,
+            object_types: Vec::new(),
+            project_id: None,
+            created_after: None,
+            created_before: None,
+            include_expired: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_similar_to_id_posts_to_similar_endpoint() {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+        let base_url = spawn_mock(requests.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let content = handle_amp_query(&client, input(None, Some("source")))
+            .await
+            .unwrap();
+        let text = content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("Similar to: source"));
+        assert!(text.contains("Decision: Use RRF"));
+        assert!(text.contains("Similarity: 0.9700"));
+
+        let sent = requests.lock().unwrap()[0].clone();
+        assert_eq!(sent["object_id"], "source");
+        assert_eq!(sent["types"], serde_json::json!(["decision"]));
+        assert_eq!(sent["limit"], 5);
+
+        let err = handle_amp_query(&client, input(Some("cache"), Some("source")))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("mutually exclusive"));
+    }
+
+    async fn sent_filters(
+        client: &crate::amp_client::AmpClient,
+        requests: &Requests,
+        input: AmpQueryInput,
+    ) -> Value {
+        handle_amp_query(client, input).await.unwrap();
+        let sent = requests.lock().unwrap().pop().unwrap();
+        sent["filters"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_metadata_filters_are_sent_to_query() {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+        let base_url = spawn_mock(requests.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+        let base = || AmpQueryInput {
+            filters: None,
+            ..input(Some("lease"), None)
+        };
+
+        let by_type = AmpQueryInput {
+            object_types: vec!["decision".to_string(), "note".to_string()],
+            ..base()
+        };
+        assert_eq!(
+            sent_filters(&client, &requests, by_type).await,
+            serde_json::json!({ "type": ["decision", "note"] })
+        );
+
+        let by_project = AmpQueryInput {
+            project_id: Some("amp".to_string()),
+            ..base()
+        };
+        assert_eq!(
+            sent_filters(&client, &requests, by_project).await,
+            serde_json::json!({ "project_id": "amp" })
+        );
+
+        let by_after = AmpQueryInput {
+            created_after: Some("2024-06-01T02:00:00+02:00".to_string()),
+            ..base()
+        };
+        assert_eq!(
+            sent_filters(&client, &requests, by_after).await,
+            serde_json::json!({ "created_after": "2024-06-01T00:00:00+00:00" })
+        );
+
+        let by_before = AmpQueryInput {
+            created_before: Some("2024-06-08T00:00:00Z".to_string()),
+            ..base()
+        };
+        assert_eq!(
+            sent_filters(&client, &requests, by_before).await,
+            serde_json::json!({ "created_before": "2024-06-08T00:00:00+00:00" })
+        );
+
+        let combined = AmpQueryInput {
+            object_types: vec!["decision".to_string()],
+            project_id: Some("amp".to_string()),
+            created_after: Some("2024-06-01T00:00:00Z".to_string()),
+            created_before: Some("2024-06-08T00:00:00Z".to_string()),
+            ..base()
+        };
+        assert_eq!(
+            sent_filters(&client, &requests, combined).await,
+            serde_json::json!({
+                "type": ["decision"],
+                "project_id": "amp",
+                "created_after": "2024-06-01T00:00:00+00:00",
+                "created_before": "2024-06-08T00:00:00+00:00",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_include_expired_is_sent_as_a_filter() {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+        let base_url = spawn_mock(requests.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let with_expired = AmpQueryInput {
+            filters: None,
+            include_expired: Some(true),
+            ..input(Some("lease"), None)
+        };
+        assert_eq!(
+            sent_filters(&client, &requests, with_expired).await,
+            serde_json::json!({ "include_expired": true })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain_flag_is_passed_through_to_query() {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+        let base_url = spawn_mock(requests.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        for explain in [Some(true), Some(false), None] {
+            let with_explain = AmpQueryInput {
+                explain,
+                ..input(Some("lease"), None)
+            };
+            handle_amp_query(&client, with_explain).await.unwrap();
+            let sent = requests.lock().unwrap().pop().unwrap();
+            assert_eq!(sent.get("explain").and_then(Value::as_bool), explain);
+        }
+    }
+
+    #[test]
+    fn test_summarize_results_shows_note_titles_and_warning_severity() {
+        let result = serde_json::json!({
+            "results": [
+                {
+                    "score": 0.9,
+                    "object": { "id": "w1", "type": "warning", "title": "Staging is read-only",
+                                "severity": "high", "expires_at": "2030-01-01T00:00:00+00:00" }
+                },
+                { "score": 0.8, "object": { "id": "n1", "type": "note", "title": "Lease renewal" } }
+            ]
+        });
+
+        let summary = summarize_results(&result, "Query: lease", "RRF", "Score").unwrap();
+
+        assert!(summary.contains("1. Warning [HIGH]: Staging is read-only"));
+        assert!(summary.contains("expires: 2030-01-01T00:00:00+00:00"));
+        assert!(summary.contains("2. Note: Lease renewal"));
+    }
+
+    #[test]
+    fn test_summarize_results_shows_grouped_chunk_ranges() {
+        let result = serde_json::json!({
+            "results": [{
+                "score": 0.9,
+                "object": { "id": "c1", "type": "FileChunk", "file_path": "src/lease.rs",
+                            "start_line": 1, "end_line": 20 },
+                "matched_chunks": 3,
+                "line_ranges": [[1, 40], [60, 80]],
+                "file_summary": "Renewal bookkeeping"
+            }]
+        });
+
+        let summary = summarize_results(&result, "Query: lease", "RRF", "Score").unwrap();
+
+        assert!(summary.contains("1. FileChunk: src/lease.rs (lines 1-20)"));
+        assert!(summary.contains("matched 3 chunks (lines 1-40, 60-80)"));
+        assert!(summary.contains("file: Renewal bookkeeping"));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_or_inverted_dates_are_rejected() {
+        let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+        let base_url = spawn_mock(requests.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        let malformed = AmpQueryInput {
+            created_after: Some("last week".to_string()),
+            ..input(Some("lease"), None)
+        };
+        let err = handle_amp_query(&client, malformed).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("created_after must be an RFC 3339 timestamp"));
+
+        let inverted = AmpQueryInput {
+            created_after: Some("2024-06-08T00:00:00Z".to_string()),
+            created_before: Some("2024-06-01T00:00:00Z".to_string()),
+            ..input(Some("lease"), None)
+        };
+        let err = handle_amp_query(&client, inverted).await.unwrap_err();
+        assert!(err.to_string().contains("must not be later"));
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_chunk_span_names_the_chunk_symbol() {
+        let chunk = serde_json::json!({ "start_line": 120, "end_line": 180, "symbol_kind": "function", "symbol_name": "parse_file" });
+        assert_eq!(chunk_span(&chunk), "function parse_file (lines 120-180)");
+        let window = serde_json::json!({ "start_line": 1, "end_line": 40 });
+        assert_eq!(chunk_span(&window), "(lines 1-40)");
+    }
+
+    #[test]
+    fn test_summarize_path_marks_reversed_edges() {
+        let result = serde_json::json!({
+            "from": "a",
+            "to": "d",
+            "found": true,
+            "nodes": ["a", "c", "d"],
+            "edges": [
+                { "from": "a", "to": "c", "relation": "calls", "direction": "outbound" },
+                { "from": "c", "to": "d", "relation": "defined_in", "direction": "inbound" }
+            ]
+        });
+        assert_eq!(
+            summarize_path(&result),
+            "Path from a to d (2 hops):\n\n  a -[calls]-> c\n  c <-[defined_in]- d\n"
+        );
+
+        let missing = serde_json::json!({ "from": "a", "to": "e", "found": false });
+        assert_eq!(summarize_path(&missing), "No path found from a to e\n");
+    }
+
+    #[tokio::test]
+    async fn test_trace_common_mode_queries_shared_neighbors() {
+        async fn common(
+            axum::extract::Query(query): axum::extract::Query<
+                std::collections::HashMap<String, String>,
+            >,
+        ) -> Json<Value> {
+            let ids: Vec<&str> = query["ids"].split(',').collect();
+            Json(serde_json::json!({
+                "ids": ids,
+                "edge_type": query["edge_type"],
+                "count": 1,
+                "neighbors": [{ "id": "cache", "type": "symbol", "name": "cache.rs" }]
+            }))
+        }
+        let app = Router::new().route("/v1/graph/common", axum::routing::get(common));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = crate::amp_client::AmpClient::new(format!("http://{}", addr), 5).unwrap();
+
+        let trace = |object_ids: Option<Vec<String>>| AmpTraceInput {
+            object_id: "a".to_string(),
+            depth: default_depth(),
+            mode: TraceMode::Common,
+            target_id: None,
+            object_ids,
+            edge_types: None,
+            version_number: None,
+        };
+        let content = handle_amp_trace(&client, trace(Some(vec!["b".to_string()])))
+            .await
+            .unwrap();
+        assert_eq!(
+            content[0].as_text().unwrap().text,
+            "1 shared depends_on neighbors for a, b:\n\n  - cache.rs [symbol] cache\n"
+        );
+
+        let err = handle_amp_trace(&client, trace(None)).await.unwrap_err();
+        assert_eq!(err.kind(), "invalid_input");
+    }
+
+    #[tokio::test]
+    async fn test_trace_versions_mode_lists_and_fetches_snapshots() {
+        async fn versions() -> Json<Value> {
+            Json(serde_json::json!({
+                "id": "dec-1",
+                "versions": [
+                    { "id": "v2", "version_number": 2, "changed_at": "2026-01-02T00:00:00Z" },
+                    { "id": "v1", "version_number": 1, "changed_at": "2026-01-01T00:00:00Z" }
+                ]
+            }))
+        }
+        async fn version() -> Json<Value> {
+            Json(serde_json::json!({
+                "id": "dec-1",
+                "version_number": 1,
+                "changed_at": "2026-01-01T00:00:00Z",
+                "snapshot": { "id": "dec-1", "status": "proposed" }
+            }))
+        }
+        let app = Router::new()
+            .route("/v1/objects/dec-1/versions", axum::routing::get(versions))
+            .route("/v1/objects/dec-1/versions/1", axum::routing::get(version));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        let client = crate::amp_client::AmpClient::new(format!("http://{}", addr), 5).unwrap();
+
+        let trace = |version_number: Option<u64>| AmpTraceInput {
+            object_id: "dec-1".to_string(),
+            depth: default_depth(),
+            mode: TraceMode::Versions,
+            target_id: None,
+            object_ids: None,
+            edge_types: None,
+            version_number,
+        };
+        let content = handle_amp_trace(&client, trace(None)).await.unwrap();
+        let text = &content[0].as_text().unwrap().text;
+        assert!(text.starts_with("2 versions of dec-1"));
+        assert!(text.contains("  v2  2026-01-02T00:00:00Z\n  v1  2026-01-01T00:00:00Z\n"));
+
+        let content = handle_amp_trace(&client, trace(Some(1))).await.unwrap();
+        let text = &content[0].as_text().unwrap().text;
+        assert!(text.starts_with("Version 1 of dec-1 (replaced at 2026-01-01T00:00:00Z)"));
+        assert!(text.contains("\"status\": \"proposed\""));
+    }
+}
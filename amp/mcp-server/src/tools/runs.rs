@@ -0,0 +1,373 @@
+use super::error::{AmpToolError, ToolResult};
+use super::memory::{complete_run, RunCompletion, RunStatus};
+use rmcp::model::Content;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunAction {
+    Start,
+    Complete,
+    Fail,
+    Cancel,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AmpRunInput {
+    /// Action to perform: start | complete | fail | cancel
+    pub action: RunAction,
+    /// What the run sets out to do (required for start)
+    #[serde(default)]
+    pub input_summary: Option<String>,
+    /// Run this one is a sub-task of (start only)
+    #[serde(default)]
+    pub parent_run_id: Option<String>,
+    /// Run to finish (complete/fail/cancel; defaults to the current session run)
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Summary of what the run produced (complete)
+    #[serde(default)]
+    pub outputs_summary: Option<String>,
+    /// Confidence in the outcome, 0.0-1.0 (complete)
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// Why the run failed (required for fail)
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Project of a new run (defaults to the session's project)
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+/// Tool output plus the run that became current, if a new one was started
+pub struct RunOutcome {
+    pub content: Vec<Content>,
+    pub started_run_id: Option<String>,
+}
+
+pub async fn handle_run(
+    client: &crate::amp_client::AmpClient,
+    agent_label: &str,
+    project_id: Option<&str>,
+    current_run_id: Option<&str>,
+    input: AmpRunInput,
+) -> ToolResult<RunOutcome> {
+    match input.action {
+        RunAction::Start => {
+            let input_summary = input
+                .input_summary
+                .filter(|s| !s.trim().is_empty())
+                .ok_or_else(|| AmpToolError::invalid_input("input_summary required for start"))?;
+            let links: Vec<Value> = input
+                .parent_run_id
+                .iter()
+                .map(|parent| serde_json::json!({ "type": "parent_run", "target": parent }))
+                .collect();
+            let payload = serde_json::json!({
+                "type": "run",
+                "project_id": input.project_id.as_deref().or(project_id).unwrap_or("default"),
+                "input_summary": input_summary,
+                "status": "running",
+                "links": links,
+                "provenance": {
+                    "agent": agent_label,
+                    "summary": input_summary,
+                    "tools": ["amp_run"]
+                }
+            });
+            let result = client.create_object(payload).await?;
+            let run_id = result
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(|id| id.trim_start_matches("objects:").to_string())
+                .ok_or_else(|| AmpToolError::Internal(format!("Run start failed: {}", result)))?;
+
+            let mut text = format!("Run started: {}\nGoal: {}\n", run_id, input_summary);
+            if let Some(parent) = &input.parent_run_id {
+                text.push_str(&format!("Parent run: {}\n", parent));
+            }
+            Ok(RunOutcome {
+                content: vec![Content::text(text)],
+                started_run_id: Some(run_id),
+            })
+        }
+        RunAction::Complete | RunAction::Fail | RunAction::Cancel => {
+            let run_id =
+                input.run_id.as_deref().or(current_run_id).ok_or_else(|| {
+                    AmpToolError::invalid_input("run_id required: no session run")
+                })?;
+            let status = match input.action {
+                RunAction::Complete => RunStatus::Completed,
+                RunAction::Fail => RunStatus::Failed,
+                _ => RunStatus::Cancelled,
+            };
+            let error = input.error.filter(|e| !e.trim().is_empty());
+            if status == RunStatus::Failed && error.is_none() {
+                return Err(AmpToolError::invalid_input("error required for fail"));
+            }
+            complete_run(
+                client,
+                run_id,
+                status,
+                RunCompletion {
+                    output_summary: input.outputs_summary,
+                    confidence: input.confidence,
+                    error,
+                    ..RunCompletion::default()
+                },
+            )
+            .await?;
+
+            let mut text = format!("Run {} marked {}\n", run_id, status.as_str());
+            // Close the run's open cache block so episodic memory ends with the run
+            let compact = serde_json::json!({ "scope_id": format!("run:{}", run_id) });
+            match client.cache_block_compact(compact).await {
+                Ok(result) => {
+                    if let Some(closed) = result.get("closed_block_id").and_then(|v| v.as_str()) {
+                        text.push_str(&format!("Closed cache block: {}\n", closed));
+                    }
+                }
+                Err(e) => text.push_str(&format!("Warning: failed to close cache block: {}\n", e)),
+            }
+            Ok(RunOutcome {
+                content: vec![Content::text(text)],
+                started_run_id: None,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::{Path, State},
+        routing::{get, post},
+        Json, Router,
+    };
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    type Store = Arc<Mutex<HashMap<String, Value>>>;
+
+    async fn get_object(State(store): State<Store>, Path(id): Path<String>) -> Json<Value> {
+        Json(
+            store
+                .lock()
+                .unwrap()
+                .get(&id)
+                .cloned()
+                .unwrap_or(Value::Null),
+        )
+    }
+
+    async fn put_object(
+        State(store): State<Store>,
+        Path(id): Path<String>,
+        Json(patch): Json<Value>,
+    ) -> Json<Value> {
+        let mut store = store.lock().unwrap();
+        let object = store.entry(id).or_insert_with(|| serde_json::json!({}));
+        for (key, value) in patch.as_object().unwrap() {
+            object[key] = value.clone();
+        }
+        Json(object.clone())
+    }
+
+    async fn create_object(
+        State(store): State<Store>,
+        Json(mut object): Json<Value>,
+    ) -> Json<Value> {
+        let mut store = store.lock().unwrap();
+        let id = format!("run-{}", store.len() + 1);
+        object["created_at"] = Value::String(chrono::Utc::now().to_rfc3339());
+        store.insert(id.clone(), object);
+        Json(serde_json::json!({ "id": format!("objects:{}", id) }))
+    }
+
+    /// Records compacted scopes under `compacted`
+    async fn compact(State(store): State<Store>, Json(body): Json<Value>) -> Json<Value> {
+        let mut store = store.lock().unwrap();
+        let scopes = store
+            .entry("compacted".to_string())
+            .or_insert_with(|| serde_json::json!([]));
+        scopes
+            .as_array_mut()
+            .unwrap()
+            .push(body["scope_id"].clone());
+        Json(
+            serde_json::json!({ "closed_block_id": "cache_block:b1", "new_block_id": "cache_block:b2" }),
+        )
+    }
+
+    async fn spawn_mock(store: Store) -> crate::amp_client::AmpClient {
+        let app = Router::new()
+            .route("/v1/objects", post(create_object))
+            .route("/v1/objects/{id}", get(get_object).put(put_object))
+            .route("/v1/cache/block/compact", post(compact))
+            .with_state(store);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        crate::amp_client::AmpClient::new(format!("http://{}", addr), 5).unwrap()
+    }
+
+    fn input(action: RunAction) -> AmpRunInput {
+        AmpRunInput {
+            action,
+            input_summary: None,
+            parent_run_id: None,
+            run_id: None,
+            outputs_summary: None,
+            confidence: None,
+            error: None,
+            project_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_start_then_complete_closes_cache_block() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        let client = spawn_mock(store.clone()).await;
+
+        let start = AmpRunInput {
+            input_summary: Some("Fix cache eviction".to_string()),
+            parent_run_id: Some("session-run".to_string()),
+            ..input(RunAction::Start)
+        };
+        let outcome = handle_run(&client, "agent-1", Some("amp"), Some("session-run"), start)
+            .await
+            .unwrap();
+        let run_id = outcome.started_run_id.unwrap();
+        assert_eq!(run_id, "run-1");
+        {
+            let store = store.lock().unwrap();
+            let run = &store["run-1"];
+            assert_eq!(run["status"], "running");
+            assert_eq!(run["project_id"], "amp");
+            assert_eq!(run["input_summary"], "Fix cache eviction");
+            assert_eq!(
+                run["links"],
+                serde_json::json!([{ "type": "parent_run", "target": "session-run" }])
+            );
+        }
+
+        let complete = AmpRunInput {
+            outputs_summary: Some("Eviction keeps the newest 20 blocks".to_string()),
+            confidence: Some(0.8),
+            ..input(RunAction::Complete)
+        };
+        let outcome = handle_run(&client, "agent-1", Some("amp"), Some(&run_id), complete)
+            .await
+            .unwrap();
+        assert!(outcome.started_run_id.is_none());
+
+        let store = store.lock().unwrap();
+        let run = &store["run-1"];
+        assert_eq!(run["status"], "completed");
+        assert!(run["duration_ms"].as_i64().unwrap() >= 0);
+        assert_eq!(
+            run["outputs"][0]["content"],
+            "Eviction keeps the newest 20 blocks"
+        );
+        assert!((run["confidence"].as_f64().unwrap() - 0.8).abs() < 1e-6);
+        assert_eq!(store["compacted"], serde_json::json!(["run:run-1"]));
+    }
+
+    #[tokio::test]
+    async fn test_run_fail_records_error_and_validates_input() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        store.lock().unwrap().insert(
+            "run-9".to_string(),
+            serde_json::json!({ "type": "run", "status": "running", "errors": [{ "message": "flaky test" }] }),
+        );
+        let client = spawn_mock(store.clone()).await;
+
+        let err = handle_run(
+            &client,
+            "agent-1",
+            None,
+            Some("run-9"),
+            input(RunAction::Fail),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(err.kind(), "invalid_input");
+        let err = handle_run(&client, "agent-1", None, None, input(RunAction::Complete))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), "invalid_input");
+        assert_eq!(store.lock().unwrap()["run-9"]["status"], "running");
+
+        let fail = AmpRunInput {
+            run_id: Some("run-9".to_string()),
+            error: Some("Migration failed".to_string()),
+            ..input(RunAction::Fail)
+        };
+        handle_run(&client, "agent-1", None, None, fail)
+            .await
+            .unwrap();
+
+        let store = store.lock().unwrap();
+        let run = &store["run-9"];
+        assert_eq!(run["status"], "failed");
+        assert_eq!(
+            run["errors"],
+            serde_json::json!([{ "message": "flaky test" }, { "message": "Migration failed" }])
+        );
+        assert!(run.get("duration_ms").is_none());
+        assert_eq!(store["compacted"], serde_json::json!(["run:run-9"]));
+    }
+
+    #[tokio::test]
+    async fn test_run_cancel_and_confidence_go_through_complete_run() {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        store.lock().unwrap().insert(
+            "run-3".to_string(),
+            serde_json::json!({ "type": "run", "status": "running", "created_at": chrono::Utc::now().to_rfc3339() }),
+        );
+        let client = spawn_mock(store.clone()).await;
+
+        let overconfident = AmpRunInput {
+            confidence: Some(1.5),
+            ..input(RunAction::Complete)
+        };
+        let err = handle_run(&client, "agent-1", None, Some("run-3"), overconfident)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), "invalid_input");
+        assert_eq!(store.lock().unwrap()["run-3"]["status"], "running");
+
+        let missing = AmpRunInput {
+            run_id: Some("run-404".to_string()),
+            ..input(RunAction::Cancel)
+        };
+        let err = handle_run(&client, "agent-1", None, None, missing)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), "not_found");
+
+        handle_run(
+            &client,
+            "agent-1",
+            None,
+            Some("run-3"),
+            input(RunAction::Cancel),
+        )
+        .await
+        .unwrap();
+        let store = store.lock().unwrap();
+        assert_eq!(store["run-3"]["status"], "cancelled");
+        assert!(store["run-3"]["duration_ms"].as_i64().unwrap() >= 0);
+        assert_eq!(store["compacted"], serde_json::json!(["run:run-3"]));
+    }
+}
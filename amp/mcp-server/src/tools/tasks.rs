@@ -0,0 +1,325 @@
+use super::error::{AmpToolError, ToolResult};
+use rmcp::model::Content;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskAction {
+    Create,
+    List,
+    Update,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AmpTaskInput {
+    /// Action to perform: create | list | update
+    pub action: TaskAction,
+    /// Task ID (required for update)
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Task title (required for create)
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// open | in_progress | blocked | done. New tasks default to open; list filters by it.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Agent or person the task is assigned to
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Due date as an RFC3339 timestamp
+    #[serde(default)]
+    pub due: Option<String>,
+    /// Maximum tasks to list (default: 50)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Project of the task (defaults to the session's project)
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+const TASK_STATUSES: &[&str] = &["open", "inprogress", "blocked", "done"];
+
+/// Normalize `in_progress` / `In Progress` style input to the stored status
+fn normalize_status(status: &str) -> ToolResult<String> {
+    let normalized: String = status
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .collect();
+    if TASK_STATUSES.contains(&normalized.as_str()) {
+        Ok(normalized)
+    } else {
+        Err(AmpToolError::InvalidInput(format!(
+            "Invalid task status '{}'. Expected one of: open, in_progress, blocked, done",
+            status
+        )))
+    }
+}
+
+fn task_line(task: &Value) -> String {
+    let field = |key: &str| task.get(key).and_then(|v| v.as_str());
+    let mut line = format!(
+        "- [{}] {} ({})",
+        field("status").unwrap_or("open"),
+        field("title").unwrap_or("untitled"),
+        field("id").unwrap_or("unknown")
+    );
+    if let Some(assignee) = field("assignee") {
+        line.push_str(&format!(" | assignee: {}", assignee));
+    }
+    if let Some(due) = field("due") {
+        line.push_str(&format!(" | due: {}", due));
+    }
+    line.push('\n');
+    line
+}
+
+pub async fn handle_task(
+    client: &crate::amp_client::AmpClient,
+    agent_label: &str,
+    project_id: Option<&str>,
+    input: AmpTaskInput,
+) -> ToolResult<Vec<Content>> {
+    let status = input.status.as_deref().map(normalize_status).transpose()?;
+    let project_id = input.project_id.as_deref().or(project_id);
+
+    match input.action {
+        TaskAction::Create => {
+            let title = input
+                .title
+                .filter(|t| !t.trim().is_empty())
+                .ok_or_else(|| AmpToolError::invalid_input("title required for create"))?;
+            let status = status.unwrap_or_else(|| "open".to_string());
+            let payload = serde_json::json!({
+                "type": "task",
+                "title": title,
+                "description": input.description,
+                "status": status,
+                "assignee": input.assignee,
+                "due": input.due,
+                "project_id": project_id,
+                "agent_id": agent_label,
+            });
+            let result = client.write_artifact(payload).await?;
+            let id = result
+                .get("id")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| AmpToolError::Internal(format!("Task create failed: {}", result)))?;
+            Ok(vec![Content::text(format!(
+                "Task created: {}\nTitle: {}\nStatus: {}\n",
+                id, title, status
+            ))])
+        }
+        TaskAction::List => {
+            let mut params = serde_json::json!({
+                "type": "task",
+                "limit": input.limit.unwrap_or(50),
+            });
+            if let Some(project_id) = project_id {
+                params["project_id"] = Value::String(project_id.to_string());
+            }
+            if let Some(status) = &status {
+                params["status"] = Value::String(status.clone());
+            }
+            let tasks = client.list_artifacts(params).await?;
+            let tasks = tasks.as_array().cloned().unwrap_or_default();
+
+            let mut output = match &status {
+                Some(status) => format!("Tasks ({}): {}\n", status, tasks.len()),
+                None => format!("Tasks: {}\n", tasks.len()),
+            };
+            for task in &tasks {
+                output.push_str(&task_line(task));
+            }
+            Ok(vec![Content::text(output)])
+        }
+        TaskAction::Update => {
+            let id = input
+                .id
+                .ok_or_else(|| AmpToolError::invalid_input("id required for update"))?;
+            let mut patch = serde_json::Map::new();
+            if let Some(status) = status {
+                patch.insert("status".to_string(), Value::String(status));
+            }
+            for (key, value) in [
+                ("assignee", input.assignee),
+                ("description", input.description),
+                ("due", input.due),
+            ] {
+                if let Some(value) = value {
+                    patch.insert(key.to_string(), Value::String(value));
+                }
+            }
+            if patch.is_empty() {
+                return Err(AmpToolError::invalid_input(
+                    "update needs at least one of status, assignee, description or due",
+                ));
+            }
+
+            let task = client.update_task(&id, Value::Object(patch)).await?;
+            Ok(vec![Content::text(format!(
+                "Task updated:\n{}",
+                task_line(&task)
+            ))])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+        routing::{patch, post},
+        Json, Router,
+    };
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    type Tasks = Arc<Mutex<Vec<Value>>>;
+
+    async fn write_artifact(State(tasks): State<Tasks>, Json(body): Json<Value>) -> Json<Value> {
+        let mut tasks = tasks.lock().unwrap();
+        let id = format!("task-{}", tasks.len() + 1);
+        let mut task = body;
+        task["id"] = Value::String(id.clone());
+        tasks.push(task);
+        Json(serde_json::json!({ "id": id, "artifact_type": "task" }))
+    }
+
+    async fn list_artifacts(
+        State(tasks): State<Tasks>,
+        Query(params): Query<HashMap<String, String>>,
+    ) -> Json<Value> {
+        let tasks = tasks.lock().unwrap();
+        Json(Value::Array(
+            tasks
+                .iter()
+                .filter(|t| {
+                    params
+                        .get("status")
+                        .is_none_or(|s| t["status"] == s.as_str())
+                })
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    /// Mirrors the server rule that a done task can only be reopened
+    async fn update_task(
+        State(tasks): State<Tasks>,
+        Path(id): Path<String>,
+        Json(patch): Json<Value>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let mut tasks = tasks.lock().unwrap();
+        let task = tasks
+            .iter_mut()
+            .find(|t| t["id"] == id.as_str())
+            .ok_or(StatusCode::NOT_FOUND)?;
+        if task["status"] == "done"
+            && !matches!(patch["status"].as_str(), None | Some("open" | "done"))
+        {
+            return Err(StatusCode::CONFLICT);
+        }
+        for (key, value) in patch.as_object().unwrap() {
+            task[key] = value.clone();
+        }
+        Ok(Json(task.clone()))
+    }
+
+    async fn spawn_mock(tasks: Tasks) -> String {
+        let app = Router::new()
+            .route("/v1/artifacts", post(write_artifact).get(list_artifacts))
+            .route("/v1/tasks/{id}", patch(update_task))
+            .with_state(tasks);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn input(action: TaskAction) -> AmpTaskInput {
+        AmpTaskInput {
+            action,
+            id: None,
+            title: None,
+            description: None,
+            status: None,
+            assignee: None,
+            due: None,
+            limit: None,
+            project_id: None,
+        }
+    }
+
+    fn text(contents: &[Content]) -> String {
+        contents[0].as_text().unwrap().text.clone()
+    }
+
+    #[test]
+    fn test_status_normalization() {
+        assert_eq!(normalize_status("In Progress").unwrap(), "inprogress");
+        assert_eq!(normalize_status("in_progress").unwrap(), "inprogress");
+        assert_eq!(normalize_status("DONE").unwrap(), "done");
+        assert!(matches!(
+            normalize_status("later"),
+            Err(AmpToolError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_task_create_list_and_update() {
+        let tasks: Tasks = Arc::default();
+        let base_url = spawn_mock(tasks.clone()).await;
+        let client = crate::amp_client::AmpClient::new(base_url, 5).unwrap();
+
+        for title in ["Write docs", "Fix flaky test"] {
+            let create = AmpTaskInput {
+                title: Some(title.to_string()),
+                assignee: Some("agent-1".to_string()),
+                ..input(TaskAction::Create)
+            };
+            handle_task(&client, "agent-1", Some("proj"), create)
+                .await
+                .unwrap();
+        }
+        assert_eq!(tasks.lock().unwrap()[0]["status"], "open");
+        assert_eq!(tasks.lock().unwrap()[0]["project_id"], "proj");
+
+        let done = AmpTaskInput {
+            id: Some("task-2".to_string()),
+            status: Some("done".to_string()),
+            ..input(TaskAction::Update)
+        };
+        let updated = handle_task(&client, "agent-1", None, done).await.unwrap();
+        assert!(text(&updated).contains("[done] Fix flaky test"));
+
+        let open = AmpTaskInput {
+            status: Some("open".to_string()),
+            ..input(TaskAction::List)
+        };
+        let listed = text(&handle_task(&client, "agent-1", None, open).await.unwrap());
+        assert!(listed.contains("Tasks (open): 1"), "{}", listed);
+        assert!(listed.contains("Write docs"));
+        assert!(!listed.contains("Fix flaky test"));
+
+        let blocked = AmpTaskInput {
+            id: Some("task-2".to_string()),
+            status: Some("blocked".to_string()),
+            ..input(TaskAction::Update)
+        };
+        let err = handle_task(&client, "agent-1", None, blocked)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AmpToolError::Conflict(_)), "{:?}", err);
+    }
+}
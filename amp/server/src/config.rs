@@ -1,3 +1,4 @@
+use crate::services::graph::DEFAULT_NODE_BUDGET;
 use std::env;
 
 #[derive(Debug, Clone)]
@@ -12,6 +13,25 @@ pub struct Config {
     pub ollama_url: String,
     pub embedding_dimension: usize,
     pub embedding_model: String,
+    pub metrics_enabled: bool,
+    /// Max concurrent run/session fan-out writes per cache block write
+    pub cache_fanout_concurrency: usize,
+    /// Return from block writes before fan-out completes
+    pub cache_fanout_background: bool,
+    /// Max nodes a single graph path search may visit before giving up
+    pub graph_node_budget: usize,
+    /// Relationships a source keeps inline in `links` before moving to edge tables; 0 disables
+    pub inline_link_threshold: usize,
+    /// Seconds between sweeps that delete expired leases
+    pub lease_gc_interval_secs: u64,
+    /// Seconds between sweeps that delete objects past their `expires_at`
+    pub object_gc_interval_secs: u64,
+    /// Log query text verbatim; otherwise only its hash and length are logged
+    pub log_query_content: bool,
+    /// Hybrid query score multiplier for superseded decisions; 1.0 disables the down-rank
+    pub superseded_decision_weight: f32,
+    /// Honour `debug_vectors` on queries; off by default since vectors bloat responses
+    pub allow_debug_vectors: bool,
 }
 
 impl Config {
@@ -49,6 +69,47 @@ impl Config {
                 .unwrap_or(1536),
             embedding_model: env::var("EMBEDDING_MODEL")
                 .unwrap_or_else(|_| "text-embedding-3-small".to_string()),
+            metrics_enabled: env::var("AMP_METRICS_ENABLED")
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+            cache_fanout_concurrency: env::var("AMP_CACHE_FANOUT_CONCURRENCY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|n: &usize| *n > 0)
+                .unwrap_or(8),
+            cache_fanout_background: env::var("AMP_CACHE_FANOUT_BACKGROUND")
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+            graph_node_budget: env::var("AMP_GRAPH_NODE_BUDGET")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|n: &usize| *n > 0)
+                .unwrap_or(DEFAULT_NODE_BUDGET),
+            inline_link_threshold: env::var("AMP_INLINE_LINK_THRESHOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            lease_gc_interval_secs: env::var("LEASE_GC_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|n: &u64| *n > 0)
+                .unwrap_or(60),
+            object_gc_interval_secs: env::var("OBJECT_GC_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|n: &u64| *n > 0)
+                .unwrap_or(300),
+            log_query_content: env::var("AMP_LOG_QUERY_CONTENT")
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+            superseded_decision_weight: env::var("AMP_SUPERSEDED_DECISION_WEIGHT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|w: &f32| (0.0..=1.0).contains(w))
+                .unwrap_or(0.5),
+            allow_debug_vectors: env::var("AMP_ALLOW_DEBUG_VECTORS")
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
         })
     }
 }
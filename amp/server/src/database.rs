@@ -1,8 +1,36 @@
+use crate::surreal_json::take_json_values;
 use anyhow::Result;
+use std::collections::HashMap;
 use surrealdb::{engine::any::Any, Surreal};
 
+/// HNSW indexes backing KNN search, as (index, table, field)
+pub const VECTOR_INDEXES: [(&str, &str, &str); 2] = [
+    ("idx_objects_embedding", "objects", "embedding"),
+    (
+        "idx_cache_block_summary_embedding",
+        "cache_block",
+        "summary_embedding",
+    ),
+];
+
+/// Minimum HNSW search breadth; wider than `k` keeps filtered KNN accurate
+const KNN_EF: usize = 64;
+
+/// Condition selecting the `k` vectors in `field` nearest to `vector` through
+/// its HNSW index.
+pub fn knn_condition(field: &str, k: usize, vector: &[f32]) -> String {
+    let vector = vector
+        .iter()
+        .map(|f| f.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} <|{},{}|> [{}]", field, k, k.max(KNN_EF), vector)
+}
+
 pub struct Database {
     pub client: Surreal<Any>,
+    /// HNSW index dimension per table, read once instead of on every KNN query
+    vector_dimensions: std::sync::RwLock<HashMap<String, Option<usize>>>,
 }
 
 impl Database {
@@ -41,7 +69,10 @@ impl Database {
 
         tracing::info!("Database connection established");
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            vector_dimensions: std::sync::RwLock::new(HashMap::new()),
+        })
     }
 
     pub async fn initialize_schema(&self) -> Result<()> {
@@ -71,4 +102,127 @@ impl Database {
         tracing::info!("Database schema initialized");
         Ok(())
     }
+
+    /// Dimension of the HNSW index on `table`, or `None` when it has none
+    /// (databases created before vector indexes). Cached until the indexes
+    /// are redefined or removed.
+    pub async fn vector_index_dimension(&self, table: &str) -> Option<usize> {
+        if let Some(cached) = self.vector_dimensions.read().unwrap().get(table) {
+            return *cached;
+        }
+        let (name, _, _) = VECTOR_INDEXES.iter().find(|(_, t, _)| *t == table)?;
+        let mut response = self
+            .client
+            .query(format!("INFO FOR TABLE {}", table))
+            .await
+            .ok()?;
+        let info = take_json_values(&mut response, 0).into_iter().next()?;
+        let dimension = info["indexes"][name].as_str().and_then(|definition| {
+            definition
+                .split_whitespace()
+                .skip_while(|word| *word != "DIMENSION")
+                .nth(1)?
+                .parse()
+                .ok()
+        });
+        self.vector_dimensions
+            .write()
+            .unwrap()
+            .insert(table.to_string(), dimension);
+        dimension
+    }
+
+    fn forget_vector_dimension(&self, table: &str) {
+        self.vector_dimensions.write().unwrap().remove(table);
+    }
+
+    /// Define any missing vector index for `dimension`. An index built for
+    /// another dimension is dropped, since it would reject new embeddings;
+    /// until `rebuild_vector_indexes` succeeds, vector search scans instead.
+    /// With no `dimension` (code and prose vectors differ in length) every
+    /// vector index is dropped.
+    pub async fn ensure_vector_indexes(&self, dimension: Option<usize>) {
+        let Some(dimension) = dimension else {
+            self.remove_vector_indexes().await;
+            return;
+        };
+        for (name, table, field) in VECTOR_INDEXES {
+            match self.vector_index_dimension(table).await {
+                Some(existing) if existing == dimension => continue,
+                Some(existing) => tracing::warn!(
+                    "Dropping {} built for {} dimensions; embeddings now have {}",
+                    name,
+                    existing,
+                    dimension
+                ),
+                None => {}
+            }
+            if let Err(e) = self
+                .define_vector_index(name, table, field, dimension)
+                .await
+            {
+                tracing::warn!(
+                    "Vector index {} unavailable, falling back to a full scan: {}",
+                    name,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Drop every vector index, so writes of any dimension succeed and vector
+    /// search scans instead.
+    pub async fn remove_vector_indexes(&self) {
+        for (name, table, _) in VECTOR_INDEXES {
+            if let Some(existing) = self.vector_index_dimension(table).await {
+                tracing::warn!(
+                    "Dropping {} built for {} dimensions; no single embedding dimension to index",
+                    name,
+                    existing
+                );
+            }
+            let removed = self
+                .client
+                .query(format!("REMOVE INDEX IF EXISTS {name} ON {table}"))
+                .await
+                .and_then(surrealdb::Response::check);
+            self.forget_vector_dimension(table);
+            if let Err(e) = removed {
+                tracing::warn!("Failed to remove vector index {}: {}", name, e);
+            }
+        }
+    }
+
+    /// Drop and rebuild every vector index for `dimension`. Fails while stored
+    /// vectors still have another dimension.
+    pub async fn rebuild_vector_indexes(&self, dimension: usize) -> Result<()> {
+        for (name, table, field) in VECTOR_INDEXES {
+            self.define_vector_index(name, table, field, dimension)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn define_vector_index(
+        &self,
+        name: &str,
+        table: &str,
+        field: &str,
+        dimension: usize,
+    ) -> Result<()> {
+        let query = format!(
+            "REMOVE INDEX IF EXISTS {name} ON {table}; \
+             DEFINE INDEX {name} ON {table} FIELDS {field} HNSW DIMENSION {dimension} DIST COSINE"
+        );
+        self.forget_vector_dimension(table);
+        let result = self.client.query(query).await?.check();
+        if result.is_err() {
+            // Leave no half-built index behind to reject writes
+            self.client
+                .query(format!("REMOVE INDEX IF EXISTS {name} ON {table}"))
+                .await?;
+        }
+        result?;
+        Ok(())
+    }
 }
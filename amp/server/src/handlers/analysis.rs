@@ -0,0 +1,273 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+
+use crate::{
+    services::graph::GraphTraversalError,
+    surreal_json::{normalize_object_ids, take_json_values},
+    tenant::Tenant,
+    AppState,
+};
+
+/// Symbol kinds that can be the target of a call edge.
+const CALLABLE_KINDS: [&str; 2] = ["function", "method"];
+
+/// Names invoked by a runtime or harness rather than by other code.
+const ENTRY_POINT_NAMES: [&str; 4] = ["main", "__main__", "__init__", "init"];
+
+#[derive(Debug, Deserialize)]
+pub struct UnreferencedQuery {
+    pub project_id: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreferencedResponse {
+    pub project_id: String,
+    pub count: usize,
+    pub symbols: Vec<Value>,
+}
+
+/// Entry points and exported symbols are reached from outside the graph, so
+/// a missing incoming edge says nothing about whether they are used.
+fn is_externally_reachable(symbol: &Value) -> bool {
+    let name = symbol.get("name").and_then(|v| v.as_str()).unwrap_or("");
+    let visibility = symbol.get("visibility").and_then(|v| v.as_str());
+    ENTRY_POINT_NAMES.contains(&name) || name.starts_with("test") || visibility == Some("public")
+}
+
+/// Heuristic dead-code report: callable symbols with no incoming `calls` or
+/// `depends_on` edges, excluding entry points and exports.
+pub async fn get_unreferenced_symbols(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Query(query): Query<UnreferencedQuery>,
+) -> Result<Json<UnreferencedResponse>, StatusCode> {
+    let limit = query.limit.unwrap_or(200).clamp(1, 1000);
+    // ORDER BY fields must be projected, so order in the inner query
+    let query_str = "SELECT VALUE { id: string::concat(id), name: name, kind: kind, path: path, language: language, visibility: visibility, signature: signature } FROM (SELECT * FROM objects WHERE string::lowercase(type) = 'symbol' AND project_id = $project_id AND tenant_id = $tenant_id AND kind IN $kinds AND array::len(<-calls) = 0 AND array::len(<-depends_on) = 0 ORDER BY path, name)";
+
+    let result = timeout(
+        Duration::from_secs(10),
+        state
+            .db
+            .client
+            .query(query_str)
+            .bind(("project_id", query.project_id.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string()))
+            .bind(("kinds", CALLABLE_KINDS.map(str::to_string).to_vec())),
+    )
+    .await;
+
+    let mut symbols = match result {
+        Ok(Ok(mut response)) => take_json_values(&mut response, 0),
+        Ok(Err(e)) => {
+            tracing::error!("Unreferenced symbol query failed: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Err(_) => {
+            tracing::error!("Unreferenced symbol query timed out");
+            return Err(StatusCode::GATEWAY_TIMEOUT);
+        }
+    };
+    normalize_object_ids(&mut symbols);
+    symbols.retain(|symbol| !is_externally_reachable(symbol));
+    symbols.truncate(limit);
+
+    Ok(Json(UnreferencedResponse {
+        project_id: query.project_id,
+        count: symbols.len(),
+        symbols,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CyclesQuery {
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyCyclesResponse {
+    pub project_id: String,
+    pub count: usize,
+    /// File paths of each `depends_on` cycle, sorted within the cycle
+    pub cycles: Vec<Vec<String>>,
+}
+
+/// Circular imports: strongly-connected components of the project's
+/// `depends_on` graph, reported as the file paths involved.
+pub async fn get_dependency_cycles(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Query(query): Query<CyclesQuery>,
+) -> Result<Json<DependencyCyclesResponse>, StatusCode> {
+    let cycles = match state
+        .graph_service
+        .find_cycles(&query.project_id, tenant.as_str())
+        .await
+    {
+        Ok(cycles) => cycles,
+        Err(GraphTraversalError::Timeout) => return Err(StatusCode::GATEWAY_TIMEOUT),
+        Err(e) => {
+            tracing::error!("Cycle detection for {} failed: {}", query.project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let ids: Vec<String> = cycles.iter().flatten().cloned().collect();
+    let query_str = "SELECT meta::id(id) AS id, path, file_path FROM objects WHERE project_id = $project_id AND tenant_id = $tenant_id AND meta::id(id) IN $ids";
+    let result = timeout(
+        Duration::from_secs(10),
+        state
+            .db
+            .client
+            .query(query_str)
+            .bind(("project_id", query.project_id.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string()))
+            .bind(("ids", ids)),
+    )
+    .await;
+
+    let paths: std::collections::HashMap<String, String> = match result {
+        Ok(Ok(mut response)) => take_json_values(&mut response, 0)
+            .into_iter()
+            .filter_map(|node| {
+                let id = node.get("id")?.as_str()?.to_string();
+                let path = node
+                    .get("path")
+                    .or_else(|| node.get("file_path"))
+                    .and_then(|v| v.as_str())?
+                    .to_string();
+                Some((id, path))
+            })
+            .collect(),
+        Ok(Err(e)) => {
+            tracing::error!("Cycle path lookup failed: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Err(_) => {
+            tracing::error!("Cycle path lookup timed out");
+            return Err(StatusCode::GATEWAY_TIMEOUT);
+        }
+    };
+
+    // Nodes without a path (never expected for indexed files) keep their id
+    let mut cycles: Vec<Vec<String>> = cycles
+        .into_iter()
+        .map(|cycle| {
+            let mut files: Vec<String> = cycle
+                .into_iter()
+                .map(|id| paths.get(&id).cloned().unwrap_or(id))
+                .collect();
+            files.sort();
+            files
+        })
+        .collect();
+    cycles.sort();
+
+    Ok(Json(DependencyCyclesResponse {
+        project_id: query.project_id,
+        count: cycles.len(),
+        cycles,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    async fn insert_symbol(state: &AppState, id: &str, name: &str, visibility: &str) {
+        state
+            .db
+            .client
+            .query(format!(
+                "CREATE objects:`{}` SET type = 'symbol', name = $name, kind = 'function', path = 'src/lib.rs', \
+                 language = 'rust', visibility = $visibility, project_id = 'proj', tenant_id = 'default'",
+                id
+            ))
+            .bind(("name", name.to_string()))
+            .bind(("visibility", visibility.to_string()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unreferenced_report_lists_only_uncalled_private_functions() {
+        let state = memory_state(test_config()).await;
+        insert_symbol(&state, "main", "main", "private").await;
+        insert_symbol(&state, "called", "parse_config", "private").await;
+        insert_symbol(&state, "dead", "legacy_parse", "private").await;
+        insert_symbol(&state, "exported", "public_api", "public").await;
+        state
+            .db
+            .client
+            .query("RELATE objects:main->calls->objects:called SET created_at = time::now()")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let Json(response) = get_unreferenced_symbols(
+            State(state),
+            Tenant::default(),
+            Query(UnreferencedQuery {
+                project_id: "proj".to_string(),
+                limit: None,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let names: Vec<&str> = response
+            .symbols
+            .iter()
+            .filter_map(|s| s["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["legacy_parse"]);
+        assert_eq!(response.symbols[0]["id"], "dead");
+    }
+
+    #[tokio::test]
+    async fn test_dependency_cycles_report_file_paths() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "CREATE objects:a SET type = 'symbol', kind = 'file', path = 'src/a.rs', project_id = 'proj', tenant_id = 'default';
+                 CREATE objects:b SET type = 'symbol', kind = 'file', path = 'src/b.rs', project_id = 'proj', tenant_id = 'default';
+                 CREATE objects:main SET type = 'symbol', kind = 'file', path = 'src/main.rs', project_id = 'proj', tenant_id = 'default';
+                 RELATE objects:a->depends_on->objects:b;
+                 RELATE objects:b->depends_on->objects:a;
+                 RELATE objects:main->depends_on->objects:a;",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let Json(response) = get_dependency_cycles(
+            State(state),
+            Tenant::default(),
+            Query(CyclesQuery {
+                project_id: "proj".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.count, 1);
+        assert_eq!(
+            response.cycles,
+            vec![vec!["src/a.rs".to_string(), "src/b.rs".to_string()]]
+        );
+    }
+}
@@ -1,14 +1,78 @@
-use crate::{models::analytics::AnalyticsData, AppState};
-use axum::{extract::State, http::StatusCode, response::Json};
+use crate::{
+    handlers::settings::embedding_status,
+    models::analytics::AnalyticsData,
+    services::{analytics::AnalyticsWindow, graph::GraphTraversalError},
+    tenant::Tenant,
+    AppState,
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
 use tokio::time::{timeout, Duration};
 
+#[derive(Debug, Default, Deserialize)]
+pub struct AnalyticsQuery {
+    pub project_id: Option<String>,
+    /// `24h` (default), `7d` or `30d`
+    pub window: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CyclesQuery {
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CyclesResponse {
+    pub project_id: String,
+    pub count: usize,
+    /// Object ids of each `depends_on` cycle
+    pub cycles: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RankRequest {
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RankedNode {
+    pub id: String,
+    pub importance: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RankResponse {
+    pub project_id: String,
+    pub nodes_scored: usize,
+    /// Highest-scoring objects, at most [`RANK_TOP_N`]
+    pub top: Vec<RankedNode>,
+}
+
+const RANK_TOP_N: usize = 10;
+
 pub async fn get_analytics(
     State(state): State<AppState>,
+    Query(query): Query<AnalyticsQuery>,
 ) -> Result<Json<AnalyticsData>, StatusCode> {
-    let result = timeout(
-        Duration::from_secs(5),
-        state.analytics_service.get_analytics(),
-    )
+    let window = match query.window.as_deref() {
+        Some(window) => AnalyticsWindow::parse(window).ok_or(StatusCode::BAD_REQUEST)?,
+        None => AnalyticsWindow::Day,
+    };
+    let analytics = &state.analytics_service;
+    let result = timeout(Duration::from_secs(5), async {
+        let mut data = analytics.get_analytics().await?;
+        data.embedding_status = embedding_status(&state).await;
+        data.detailed = Some(
+            analytics
+                .get_detailed(query.project_id.as_deref(), window)
+                .await?,
+        );
+        anyhow::Ok(data)
+    })
     .await
     .map_err(|_| {
         tracing::error!("Analytics request timeout after 5 seconds");
@@ -21,3 +85,63 @@ pub async fn get_analytics(
 
     Ok(Json(result))
 }
+
+/// Dependency cycles among one project's indexed files.
+pub async fn get_cycles(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Query(query): Query<CyclesQuery>,
+) -> Result<Json<CyclesResponse>, StatusCode> {
+    let cycles = match state
+        .graph_service
+        .find_cycles(&query.project_id, tenant.as_str())
+        .await
+    {
+        Ok(cycles) => cycles,
+        Err(GraphTraversalError::Timeout) => return Err(StatusCode::GATEWAY_TIMEOUT),
+        Err(e) => {
+            tracing::error!("Cycle detection for {} failed: {}", query.project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    Ok(Json(CyclesResponse {
+        project_id: query.project_id,
+        count: cycles.len(),
+        cycles,
+    }))
+}
+
+/// Recompute and store PageRank importance for one project's objects.
+pub async fn rank_project(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Json(request): Json<RankRequest>,
+) -> Result<Json<RankResponse>, StatusCode> {
+    let ranked = match state
+        .graph_service
+        .compute_importance(&request.project_id, tenant.as_str())
+        .await
+    {
+        Ok(ranked) => ranked,
+        Err(GraphTraversalError::Timeout) => return Err(StatusCode::GATEWAY_TIMEOUT),
+        Err(e) => {
+            tracing::error!(
+                "Importance ranking for {} failed: {}",
+                request.project_id,
+                e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    Ok(Json(RankResponse {
+        project_id: request.project_id,
+        nodes_scored: ranked.len(),
+        top: ranked
+            .into_iter()
+            .take(RANK_TOP_N)
+            .map(|(id, importance)| RankedNode { id, importance })
+            .collect(),
+    }))
+}
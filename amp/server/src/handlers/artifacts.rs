@@ -1,10 +1,15 @@
 use axum::extract::Path;
+use axum::response::{IntoResponse, Response};
 use axum::{extract::State, http::StatusCode, response::Json};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
+use crate::idempotency::{existing_object, IdempotencyKey};
+use crate::models::{TaskStatus, WarningSeverity};
+use crate::services::embedding::EmbeddingService;
+use crate::tenant::Tenant;
 use crate::AppState;
 
 /// Artifact types supported by the system
@@ -15,6 +20,8 @@ pub enum ArtifactType {
     FileLog,
     Note,
     ChangeSet,
+    Task,
+    Warning,
 }
 
 impl std::fmt::Display for ArtifactType {
@@ -24,6 +31,8 @@ impl std::fmt::Display for ArtifactType {
             ArtifactType::FileLog => write!(f, "filelog"),
             ArtifactType::Note => write!(f, "note"),
             ArtifactType::ChangeSet => write!(f, "changeset"),
+            ArtifactType::Task => write!(f, "task"),
+            ArtifactType::Warning => write!(f, "warning"),
         }
     }
 }
@@ -35,7 +44,9 @@ pub struct WriteArtifactRequest {
     #[serde(rename = "type")]
     pub artifact_type: ArtifactType,
 
-    /// Title of the artifact
+    /// Title of the artifact; defaults to empty so a missing title is
+    /// reported by validation alongside any other missing fields
+    #[serde(default)]
     pub title: String,
 
     /// Project ID this artifact belongs to
@@ -59,8 +70,12 @@ pub struct WriteArtifactRequest {
     pub consequences: Option<String>,
     /// Alternatives that were considered
     pub alternatives: Option<Vec<String>>,
-    /// Status of the decision
+    /// Status of the decision, or of the task (`open`, `inprogress`, `blocked`, `done`)
     pub status: Option<String>,
+    /// Decision this one replaces; it is marked superseded and linked with `superseded_by`
+    pub supersedes: Option<String>,
+    /// With `supersedes`, also link this decision to the old decision's files
+    pub copy_file_links: Option<bool>,
 
     // === FileLog-specific fields ===
     /// Path to the file
@@ -73,19 +88,32 @@ pub struct WriteArtifactRequest {
     pub dependencies: Option<Vec<String>>,
 
     // === Note-specific fields ===
-    /// Content of the note (markdown)
+    /// Content of the note or warning (markdown)
+    #[serde(alias = "body")]
     pub content: Option<String>,
     /// Category of the note
     pub category: Option<String>,
 
     // === ChangeSet-specific fields ===
-    /// Description of the changes
+    /// Description of the changes (or of the task)
     pub description: Option<String>,
     /// Diff summary
     pub diff_summary: Option<String>,
     /// Files that were changed
     pub files_changed: Option<Vec<String>>,
 
+    // === Task-specific fields ===
+    /// Agent or person the task is assigned to
+    pub assignee: Option<String>,
+    /// When the task is due
+    pub due: Option<chrono::DateTime<chrono::Utc>>,
+
+    // === Warning-specific fields ===
+    /// How serious the warning is: `low`, `medium`, `high` or `critical`
+    pub severity: Option<String>,
+    /// When the warning stops applying; expired warnings are hidden from queries
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+
     // === Relationship fields (for graph layer) ===
     /// IDs of objects this artifact relates to
     pub linked_objects: Option<Vec<String>>,
@@ -93,6 +121,8 @@ pub struct WriteArtifactRequest {
     pub linked_decisions: Option<Vec<String>>,
     /// IDs of files this artifact modifies or references
     pub linked_files: Option<Vec<String>>,
+    /// File paths this artifact concerns, resolved to indexed file nodes at write time
+    pub related_files: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -104,6 +134,8 @@ pub struct WriteArtifactResponse {
     pub memory_layers: MemoryLayersWritten,
     /// Relationships created in graph layer
     pub relationships_created: usize,
+    /// Related file paths that could not be resolved to an indexed file
+    pub unresolved_files: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -160,6 +192,11 @@ fn extract_embedding_text(request: &WriteArtifactRequest) -> String {
                 parts.push(category.clone());
             }
         }
+        ArtifactType::Warning => {
+            if let Some(content) = &request.content {
+                parts.push(content.clone());
+            }
+        }
         ArtifactType::ChangeSet => {
             if let Some(description) = &request.description {
                 parts.push(description.clone());
@@ -171,6 +208,11 @@ fn extract_embedding_text(request: &WriteArtifactRequest) -> String {
                 parts.extend(files_changed.clone());
             }
         }
+        ArtifactType::Task => {
+            if let Some(description) = &request.description {
+                parts.push(description.clone());
+            }
+        }
     }
 
     parts
@@ -180,6 +222,130 @@ fn extract_embedding_text(request: &WriteArtifactRequest) -> String {
         .join("\n")
 }
 
+/// Why an artifact write was refused
+#[derive(Debug)]
+pub enum ArtifactError {
+    /// Required fields are absent or blank; a 422 naming them
+    MissingFields {
+        artifact_type: String,
+        fields: Vec<&'static str>,
+    },
+    Status(StatusCode),
+}
+
+impl ArtifactError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ArtifactError::MissingFields { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ArtifactError::Status(status) => *status,
+        }
+    }
+}
+
+impl From<StatusCode> for ArtifactError {
+    fn from(status: StatusCode) -> Self {
+        ArtifactError::Status(status)
+    }
+}
+
+impl IntoResponse for ArtifactError {
+    fn into_response(self) -> Response {
+        match self {
+            ArtifactError::MissingFields {
+                artifact_type,
+                fields,
+            } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(serde_json::json!({
+                    "error": format!(
+                        "{} artifacts require {}",
+                        artifact_type,
+                        fields.join(", ")
+                    ),
+                    "missing_fields": fields,
+                })),
+            )
+                .into_response(),
+            ArtifactError::Status(status) => status.into_response(),
+        }
+    }
+}
+
+/// Required fields that are absent or blank for this artifact's type
+fn missing_fields(request: &WriteArtifactRequest) -> Vec<&'static str> {
+    let blank = |value: Option<&str>| value.is_none_or(|value| value.trim().is_empty());
+    let mut missing = Vec::new();
+    if blank(Some(&request.title)) {
+        missing.push("title");
+    }
+    if matches!(
+        request.artifact_type,
+        ArtifactType::Note | ArtifactType::Warning
+    ) && blank(request.content.as_deref())
+    {
+        missing.push("body");
+    }
+    if matches!(request.artifact_type, ArtifactType::Warning) && blank(request.severity.as_deref())
+    {
+        missing.push("severity");
+    }
+    missing
+}
+
+/// Reject artifacts missing the fields their type cannot do without
+fn validate_artifact(request: &WriteArtifactRequest) -> Result<(), ArtifactError> {
+    let missing = missing_fields(request);
+    if !missing.is_empty() {
+        tracing::warn!(
+            "Rejected {} artifact: missing {}",
+            request.artifact_type,
+            missing.join(", ")
+        );
+        return Err(ArtifactError::MissingFields {
+            artifact_type: request.artifact_type.to_string(),
+            fields: missing,
+        });
+    }
+    let invalid = |reason: String| {
+        tracing::warn!("Rejected {} artifact: {}", request.artifact_type, reason);
+        ArtifactError::Status(StatusCode::BAD_REQUEST)
+    };
+    match request.artifact_type {
+        ArtifactType::Task => {
+            task_status(request).map_err(invalid)?;
+        }
+        ArtifactType::Warning => {
+            warning_severity(request).map_err(invalid)?;
+        }
+        _ => {}
+    }
+    if request.supersedes.is_some() && !matches!(request.artifact_type, ArtifactType::Decision) {
+        return Err(invalid(
+            "only decisions can supersede another artifact".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn warning_severity(request: &WriteArtifactRequest) -> Result<WarningSeverity, String> {
+    let severity = request.severity.as_deref().unwrap_or_default();
+    serde_json::from_value(Value::String(severity.trim().to_lowercase()))
+        .map_err(|_| format!("unknown warning severity '{}'", severity))
+}
+
+/// Status of a task artifact; new tasks start out open
+fn task_status(request: &WriteArtifactRequest) -> Result<TaskStatus, String> {
+    match &request.status {
+        Some(status) => parse_task_status(status),
+        None => Ok(TaskStatus::Open),
+    }
+}
+
+fn parse_task_status(status: &str) -> Result<TaskStatus, String> {
+    serde_json::from_value(Value::String(status.to_lowercase()))
+        .map_err(|_| format!("unknown task status '{}'", status))
+}
+
 /// Build the object payload for storage
 fn build_artifact_object(request: &WriteArtifactRequest, _object_id: &str) -> Value {
     let now = chrono::Utc::now().to_rfc3339();
@@ -219,6 +385,9 @@ fn build_artifact_object(request: &WriteArtifactRequest, _object_id: &str) -> Va
     if let Some(tags) = &request.tags {
         map.insert("tags".to_string(), serde_json::json!(tags));
     }
+    if let Some(related_files) = &request.related_files {
+        map.insert("related_files".to_string(), serde_json::json!(related_files));
+    }
 
     // Add type-specific fields
     match request.artifact_type {
@@ -263,8 +432,13 @@ fn build_artifact_object(request: &WriteArtifactRequest, _object_id: &str) -> Va
         }
         ArtifactType::Note => {
             if let Some(content) = &request.content {
+                // `body` matches the Note model; `content` stays for existing readers
+                map.insert("body".to_string(), Value::String(content.clone()));
                 map.insert("content".to_string(), Value::String(content.clone()));
             }
+            if request.tags.is_none() {
+                map.insert("tags".to_string(), serde_json::json!([]));
+            }
             if let Some(category) = &request.category {
                 map.insert("category".to_string(), Value::String(category.clone()));
             }
@@ -275,6 +449,30 @@ fn build_artifact_object(request: &WriteArtifactRequest, _object_id: &str) -> Va
                 );
             }
         }
+        ArtifactType::Warning => {
+            if let Some(content) = &request.content {
+                map.insert("body".to_string(), Value::String(content.clone()));
+                map.insert("content".to_string(), Value::String(content.clone()));
+            }
+            if request.tags.is_none() {
+                map.insert("tags".to_string(), serde_json::json!([]));
+            }
+            if let Ok(severity) = warning_severity(request) {
+                map.insert("severity".to_string(), serde_json::json!(severity));
+            }
+            if let Some(expires_at) = &request.expires_at {
+                map.insert(
+                    "expires_at".to_string(),
+                    Value::String(expires_at.to_rfc3339()),
+                );
+            }
+            if let Some(linked_objects) = &request.linked_objects {
+                map.insert(
+                    "linked_objects".to_string(),
+                    serde_json::json!(linked_objects),
+                );
+            }
+        }
         ArtifactType::ChangeSet => {
             if let Some(description) = &request.description {
                 map.insert(
@@ -301,6 +499,22 @@ fn build_artifact_object(request: &WriteArtifactRequest, _object_id: &str) -> Va
                 );
             }
         }
+        ArtifactType::Task => {
+            if let Some(description) = &request.description {
+                map.insert(
+                    "description".to_string(),
+                    Value::String(description.clone()),
+                );
+            }
+            let status = task_status(request).unwrap_or(TaskStatus::Open);
+            map.insert("status".to_string(), serde_json::json!(status));
+            if let Some(assignee) = &request.assignee {
+                map.insert("assignee".to_string(), Value::String(assignee.clone()));
+            }
+            if let Some(due) = &request.due {
+                map.insert("due".to_string(), Value::String(due.to_rfc3339()));
+            }
+        }
     }
 
     obj
@@ -315,11 +529,47 @@ fn build_artifact_object(request: &WriteArtifactRequest, _object_id: &str) -> Va
 /// 3. Graph layer (creates relationships to linked objects)
 pub async fn write_artifact(
     State(state): State<AppState>,
+    tenant: Tenant,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
     Json(request): Json<WriteArtifactRequest>,
-) -> Result<(StatusCode, Json<WriteArtifactResponse>), StatusCode> {
-    let object_id = Uuid::new_v4().to_string();
+) -> Result<(StatusCode, Json<WriteArtifactResponse>), ArtifactError> {
+    let replaying = idempotency_key.is_some();
+    let object_id = idempotency_key.unwrap_or_else(|| Uuid::new_v4().to_string());
     let artifact_type_str = request.artifact_type.to_string();
 
+    validate_artifact(&request)?;
+
+    if replaying {
+        if let Some(existing) = existing_object(&state.db, &tenant, &object_id).await {
+            tracing::info!("Replaying write for existing artifact: {}", object_id);
+            let layer = |name: &str| existing["memory_layers"][name].as_bool().unwrap_or(false);
+            return Ok((
+                StatusCode::OK,
+                Json(WriteArtifactResponse {
+                    id: object_id,
+                    artifact_type: artifact_type_str,
+                    created_at: existing["created_at"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    memory_layers: MemoryLayersWritten {
+                        graph: layer("graph"),
+                        vector: layer("vector"),
+                        temporal: true,
+                    },
+                    relationships_created: 0,
+                    unresolved_files: Vec::new(),
+                }),
+            ));
+        }
+    }
+
+    // Check the old decision first so a bad id doesn't leave an orphaned replacement
+    let supersedes = request.supersedes.as_deref().map(normalize_object_id);
+    if let Some(old_id) = &supersedes {
+        check_supersedable(&state, &tenant, old_id).await?;
+    }
+
     tracing::info!(
         "Writing {} artifact: {} (id: {})",
         artifact_type_str,
@@ -332,10 +582,11 @@ pub async fn write_artifact(
 
     // === LAYER 2: Vector Layer - Generate embedding ===
     let mut vector_written = false;
-    if state.embedding_service.is_enabled() {
+    let embedding_service = state.embedding_for(&artifact_type_str);
+    if embedding_service.is_enabled() {
         let text = extract_embedding_text(&request);
         if !text.trim().is_empty() {
-            match state.embedding_service.generate_embedding(&text).await {
+            match embedding_service.generate_embedding(&text).await {
                 Ok(embedding) => {
                     if let Some(map) = artifact_obj.as_object_mut() {
                         map.insert("embedding".to_string(), serde_json::json!(embedding));
@@ -354,8 +605,12 @@ pub async fn write_artifact(
         }
     }
 
-    // Update memory_layers to reflect actual state
+    // Stamp the request tenant and update memory_layers to reflect actual state
     if let Some(map) = artifact_obj.as_object_mut() {
+        map.insert(
+            "tenant_id".to_string(),
+            Value::String(tenant.as_str().to_string()),
+        );
         map.insert(
             "memory_layers".to_string(),
             serde_json::json!({
@@ -380,11 +635,11 @@ pub async fn write_artifact(
         }
         Ok(Err(e)) => {
             tracing::error!("Failed to create artifact {}: {}", object_id, e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
         }
         Err(_) => {
             tracing::error!("Timeout creating artifact {}", object_id);
-            return Err(StatusCode::GATEWAY_TIMEOUT);
+            return Err(StatusCode::GATEWAY_TIMEOUT.into());
         }
     }
 
@@ -608,6 +863,50 @@ pub async fn write_artifact(
         }
     }
 
+    // Link related files by path. Decisions justify the file; other artifacts modify it.
+    // Unresolved paths are reported back instead of failing the write.
+    let mut unresolved_files = Vec::new();
+    if let Some(related_files) = &request.related_files {
+        for file_path in related_files {
+            let Some(file_id) = find_file_node_id(&state, file_path).await else {
+                tracing::warn!("Related file not found for artifact {}: {}", object_id, file_path);
+                unresolved_files.push(file_path.clone());
+                continue;
+            };
+
+            let created = match request.artifact_type {
+                ArtifactType::Decision => {
+                    create_relationship(&state, &file_id, "justified_by", &object_id).await
+                }
+                _ => create_relationship(&state, &object_id, "modifies", &file_id).await,
+            };
+            if created {
+                relationships_created += 1;
+                linked_to_file = true;
+            }
+        }
+    }
+
+    // A changeset modifies every file it lists; report paths that are not indexed
+    if matches!(request.artifact_type, ArtifactType::ChangeSet) {
+        let mut linked_files = std::collections::HashSet::new();
+        for file_path in request.files_changed.iter().flatten() {
+            let Some(file_id) = find_file_node_id(&state, file_path).await else {
+                tracing::warn!("Changed file not found for changeset {}: {}", object_id, file_path);
+                if !unresolved_files.contains(file_path) {
+                    unresolved_files.push(file_path.clone());
+                }
+                continue;
+            };
+            if linked_files.insert(file_id.clone())
+                && create_relationship(&state, &object_id, "modifies", &file_id).await
+            {
+                relationships_created += 1;
+                linked_to_file = true;
+            }
+        }
+    }
+
     // Conservative auto-link: if file_path is provided and no linked_files were given,
     // attempt an exact path match to a file object and link it.
     let has_linked_files = request
@@ -638,6 +937,14 @@ pub async fn write_artifact(
         }
     }
 
+    if let Some(old_id) = &supersedes {
+        let copy_file_links = request.copy_file_links.unwrap_or(false);
+        let (created, copied) =
+            link_superseded(&state, &tenant, old_id, &object_id, copy_file_links).await?;
+        relationships_created += created;
+        linked_to_file |= copied > 0;
+    }
+
     // Link to a single global artifact core only when not tied to a file.
     if !linked_to_file {
         if let Some(core_id) = find_or_create_artifact_core(&state).await {
@@ -661,6 +968,7 @@ pub async fn write_artifact(
                 temporal: true,
             },
             relationships_created,
+            unresolved_files,
         }),
     ))
 }
@@ -672,6 +980,8 @@ pub struct ListArtifactsQuery {
     pub artifact_type: Option<String>,
     pub project_id: Option<String>,
     pub agent_id: Option<String>,
+    /// Task status, e.g. `open` or `blocked`
+    pub status: Option<String>,
     pub limit: Option<usize>,
 }
 
@@ -681,7 +991,8 @@ pub async fn list_artifacts(
 ) -> Result<Json<Vec<Value>>, StatusCode> {
     let limit = query.limit.unwrap_or(100);
 
-    let mut conditions = vec!["type IN ['decision', 'filelog', 'note', 'changeset']".to_string()];
+    let mut conditions =
+        vec!["type IN ['decision', 'filelog', 'note', 'changeset', 'task']".to_string()];
 
     if let Some(artifact_type) = &query.artifact_type {
         conditions.push(format!("type = '{}'", artifact_type.to_lowercase()));
@@ -692,9 +1003,13 @@ pub async fn list_artifacts(
     if let Some(agent_id) = &query.agent_id {
         conditions.push(format!("agent_id = '{}'", agent_id));
     }
+    if let Some(status) = &query.status {
+        let status = parse_task_status(status).map_err(|_| StatusCode::BAD_REQUEST)?;
+        conditions.push(format!("status = '{}'", status.as_str()));
+    }
 
     let query_str = format!(
-        "SELECT * FROM objects WHERE {} ORDER BY created_at DESC LIMIT {}",
+        "SELECT *, string::concat(id) AS id_string OMIT id, embedding FROM objects WHERE {} ORDER BY created_at DESC LIMIT {}",
         conditions.join(" AND "),
         limit
     );
@@ -705,7 +1020,11 @@ pub async fn list_artifacts(
 
     match result {
         Ok(Ok(mut response)) => {
-            let artifacts: Vec<Value> = crate::surreal_json::take_json_values(&mut response, 0);
+            let mut artifacts: Vec<Value> =
+                crate::surreal_json::take_json_values(&mut response, 0);
+            artifacts
+                .iter_mut()
+                .for_each(crate::surreal_json::normalize_object_id);
             tracing::debug!("Found {} artifacts", artifacts.len());
             Ok(Json(artifacts))
         }
@@ -726,7 +1045,7 @@ pub async fn delete_artifact(
 ) -> Result<StatusCode, StatusCode> {
     let raw_id = id.trim().trim_start_matches("objects:").to_string();
 
-    let delete_rels_query = "DELETE FROM [depends_on, defined_in, calls, justified_by, modifies, implements, produced] WHERE in = type::thing('objects', $id) OR out = type::thing('objects', $id)";
+    let delete_rels_query = "DELETE FROM [depends_on, defined_in, calls, justified_by, modifies, implements, produced, superseded_by] WHERE in = type::thing('objects', $id) OR out = type::thing('objects', $id)";
     let rels_result: Result<Result<surrealdb::Response, _>, _> = timeout(
         Duration::from_secs(5),
         state
@@ -764,3 +1083,914 @@ pub async fn delete_artifact(
         }
     }
 }
+
+fn normalize_object_id(id: &str) -> String {
+    id.trim()
+        .trim_start_matches("objects:")
+        .trim_matches('`')
+        .trim_matches('\u{27E8}')
+        .trim_matches('\u{27E9}')
+        .to_string()
+}
+
+/// Status of decision `id`; `NOT_FOUND` if `id` is not one of the tenant's decisions.
+async fn decision_status(
+    state: &AppState,
+    tenant: &Tenant,
+    id: &str,
+) -> Result<Option<String>, StatusCode> {
+    let rows = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query("SELECT type, status FROM type::thing('objects', $id) WHERE tenant_id = $tenant_id")
+            .bind(("id", id.to_string()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .map_err(|_| StatusCode::GATEWAY_TIMEOUT)?
+    .map_err(|e| {
+        tracing::error!("Failed to load decision {}: {}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+    .map(|mut response| crate::surreal_json::take_json_values(&mut response, 0))?;
+
+    match rows.first().filter(|row| row["type"] == "decision") {
+        Some(row) => Ok(row["status"].as_str().map(str::to_string)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// A decision can be superseded once; a second attempt is a conflict.
+async fn check_supersedable(state: &AppState, tenant: &Tenant, id: &str) -> Result<(), StatusCode> {
+    if decision_status(state, tenant, id).await?.as_deref() == Some("superseded") {
+        tracing::warn!("Decision {} is already superseded", id);
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(())
+}
+
+async fn relate(state: &AppState, from: &str, relation: &str, to: &str) -> Result<(), StatusCode> {
+    let query = format!(
+        "LET $from = type::thing('objects', $from_id);
+         LET $to = type::thing('objects', $to_id);
+         RELATE $from->{}->$to SET created_at = time::now() RETURN NONE",
+        relation
+    );
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("from_id", from.to_string()))
+            .bind(("to_id", to.to_string())),
+    )
+    .await
+    .map_err(|_| StatusCode::GATEWAY_TIMEOUT)?;
+    match result.and_then(surrealdb::Response::check) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::error!("Failed to relate {} -{}-> {}: {}", from, relation, to, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Mark `old_id` superseded by `new_id` and link the two. With `copy_file_links`
+/// the new decision also gets the old one's file edges: `modifies` edges it
+/// points at files and `justified_by` edges files point at it.
+///
+/// Returns the relationships created and how many of them were copied file links.
+async fn link_superseded(
+    state: &AppState,
+    tenant: &Tenant,
+    old_id: &str,
+    new_id: &str,
+    copy_file_links: bool,
+) -> Result<(usize, usize), StatusCode> {
+    let mut patch = serde_json::Map::new();
+    patch.insert(
+        "status".to_string(),
+        Value::String("superseded".to_string()),
+    );
+    patch.insert(
+        "superseded_by".to_string(),
+        Value::String(new_id.to_string()),
+    );
+    patch.insert(
+        "updated_at".to_string(),
+        Value::String(chrono::Utc::now().to_rfc3339()),
+    );
+    timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query("UPDATE type::thing('objects', $id) MERGE $patch WHERE tenant_id = $tenant_id RETURN NONE")
+            .bind(("id", old_id.to_string()))
+            .bind(("patch", Value::Object(patch)))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .map_err(|_| StatusCode::GATEWAY_TIMEOUT)?
+    .map_err(|e| {
+        tracing::error!("Failed to mark decision {} superseded: {}", old_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    relate(state, old_id, "superseded_by", new_id).await?;
+    tracing::info!("Decision {} superseded by {}", old_id, new_id);
+
+    if !copy_file_links {
+        return Ok((1, 0));
+    }
+
+    let query = "SELECT VALUE meta::id(out) FROM modifies WHERE in = type::thing('objects', $old);
+                 SELECT VALUE meta::id(in) FROM justified_by WHERE out = type::thing('objects', $old) AND (in.kind = 'file' OR in.type = 'FileLog');
+                 SELECT VALUE meta::id(out) FROM modifies WHERE in = type::thing('objects', $new);
+                 SELECT VALUE meta::id(in) FROM justified_by WHERE out = type::thing('objects', $new);";
+    let mut response = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("old", old_id.to_string()))
+            .bind(("new", new_id.to_string())),
+    )
+    .await
+    .map_err(|_| StatusCode::GATEWAY_TIMEOUT)?
+    .map_err(|e| {
+        tracing::error!("Failed to load file links of decision {}: {}", old_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let mut ids = |index: usize| -> Vec<String> {
+        crate::surreal_json::take_json_values(&mut response, index)
+            .iter()
+            .filter_map(|id| id.as_str().map(str::to_string))
+            .collect()
+    };
+    let (modified, justifying) = (ids(0), ids(1));
+    let (already_modified, already_justifying) = (ids(2), ids(3));
+
+    let mut copied = 0;
+    for file_id in modified.iter().filter(|id| !already_modified.contains(id)) {
+        relate(state, new_id, "modifies", file_id).await?;
+        copied += 1;
+    }
+    for file_id in justifying
+        .iter()
+        .filter(|id| !already_justifying.contains(id))
+    {
+        relate(state, file_id, "justified_by", new_id).await?;
+        copied += 1;
+    }
+    Ok((1 + copied, copied))
+}
+
+/// Replace a decision with a new one written from `replacement`, or with an
+/// existing decision named by `decision_id`.
+#[derive(Debug, Deserialize)]
+pub struct SupersedeDecisionRequest {
+    pub decision_id: Option<String>,
+    /// New decision in the `POST /v1/artifacts` shape
+    pub replacement: Option<WriteArtifactRequest>,
+    /// Link the replacement to the old decision's files
+    #[serde(default)]
+    pub copy_file_links: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupersedeDecisionResponse {
+    pub superseded_id: String,
+    pub superseded_by: String,
+    pub relationships_created: usize,
+    pub file_links_copied: usize,
+}
+
+/// Mark a decision superseded and link it to its replacement with `superseded_by`.
+pub async fn supersede_decision(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+    Json(request): Json<SupersedeDecisionRequest>,
+) -> Result<Json<SupersedeDecisionResponse>, StatusCode> {
+    let old_id = normalize_object_id(&id);
+    check_supersedable(&state, &tenant, &old_id).await?;
+
+    let new_id = match (request.decision_id, request.replacement) {
+        (Some(decision_id), None) => {
+            let decision_id = normalize_object_id(&decision_id);
+            if decision_id == old_id {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            decision_status(&state, &tenant, &decision_id)
+                .await
+                .map_err(|status| match status {
+                    StatusCode::NOT_FOUND => StatusCode::BAD_REQUEST,
+                    status => status,
+                })?;
+            decision_id
+        }
+        (None, Some(mut replacement)) => {
+            if !matches!(replacement.artifact_type, ArtifactType::Decision) {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            // Linked below, with this request's copy_file_links
+            replacement.supersedes = None;
+            let (_, Json(written)) = write_artifact(
+                State(state.clone()),
+                tenant.clone(),
+                IdempotencyKey::default(),
+                Json(replacement),
+            )
+            .await
+            .map_err(|e| e.status())?;
+            written.id
+        }
+        _ => return Err(StatusCode::BAD_REQUEST),
+    };
+
+    let (relationships_created, file_links_copied) =
+        link_superseded(&state, &tenant, &old_id, &new_id, request.copy_file_links).await?;
+    Ok(Json(SupersedeDecisionResponse {
+        superseded_id: old_id,
+        superseded_by: new_id,
+        relationships_created,
+        file_links_copied,
+    }))
+}
+
+/// Fields of a task that can change after it is created
+#[derive(Debug, Deserialize)]
+pub struct UpdateTaskRequest {
+    pub status: Option<String>,
+    pub assignee: Option<String>,
+    pub description: Option<String>,
+    pub due: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Update a task's status, assignee, description or due date.
+///
+/// A done task can only be reopened; moving it anywhere else is a conflict.
+pub async fn update_task(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateTaskRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let raw_id = id.trim().trim_start_matches("objects:").to_string();
+
+    let next_status = match &request.status {
+        Some(status) => Some(parse_task_status(status).map_err(|reason| {
+            tracing::warn!("Rejected task update {}: {}", raw_id, reason);
+            StatusCode::BAD_REQUEST
+        })?),
+        None => None,
+    };
+
+    let current = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query("SELECT type, status FROM type::thing('objects', $id) WHERE tenant_id = $tenant_id")
+            .bind(("id", raw_id.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .map_err(|_| StatusCode::GATEWAY_TIMEOUT)?
+    .map_err(|e| {
+        tracing::error!("Failed to load task {}: {}", raw_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+    .map(|mut response| crate::surreal_json::take_json_values(&mut response, 0))?;
+
+    let Some(task) = current.first().filter(|row| row["type"] == "task") else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let current_status = task["status"]
+        .as_str()
+        .and_then(|status| parse_task_status(status).ok())
+        .unwrap_or(TaskStatus::Open);
+
+    let mut patch = serde_json::Map::new();
+    if let Some(next) = next_status {
+        if !current_status.can_transition_to(next) {
+            tracing::warn!(
+                "Rejected task {} transition {} -> {}",
+                raw_id,
+                current_status.as_str(),
+                next.as_str()
+            );
+            return Err(StatusCode::CONFLICT);
+        }
+        patch.insert("status".to_string(), Value::String(next.as_str().to_string()));
+    }
+    if let Some(assignee) = request.assignee {
+        patch.insert("assignee".to_string(), Value::String(assignee));
+    }
+    if let Some(description) = request.description {
+        patch.insert("description".to_string(), Value::String(description));
+    }
+    if let Some(due) = request.due {
+        patch.insert("due".to_string(), Value::String(due.to_rfc3339()));
+    }
+    patch.insert(
+        "updated_at".to_string(),
+        Value::String(chrono::Utc::now().to_rfc3339()),
+    );
+
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(
+                "UPDATE type::thing('objects', $id) MERGE $patch WHERE tenant_id = $tenant_id RETURN NONE;
+                 SELECT *, string::concat(id) AS id_string OMIT id, embedding FROM type::thing('objects', $id) WHERE tenant_id = $tenant_id",
+            )
+            .bind(("id", raw_id.clone()))
+            .bind(("patch", Value::Object(patch)))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(mut response)) => {
+            let mut updated = crate::surreal_json::take_json_values(&mut response, 1);
+            let mut task = updated.pop().ok_or(StatusCode::NOT_FOUND)?;
+            crate::surreal_json::normalize_object_id(&mut task);
+            Ok(Json(task))
+        }
+        Ok(Err(e)) => {
+            tracing::error!("Failed to update task {}: {}", raw_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(_) => {
+            tracing::error!("Timeout updating task {}", raw_id);
+            Err(StatusCode::GATEWAY_TIMEOUT)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        memory_state, memory_state_with_embedding, test_config, MarkerEmbedding,
+    };
+
+    fn note_request(body: serde_json::Value) -> WriteArtifactRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_note_artifact_is_stored_with_body_tags_and_embedding() {
+        let state = memory_state_with_embedding(
+            test_config(),
+            Box::new(MarkerEmbedding {
+                model: "prose",
+                marker: 1.0,
+            }),
+        )
+        .await;
+
+        let request = note_request(serde_json::json!({
+            "type": "note",
+            "title": "Lease renewal",
+            "body": "Renew leases at half their TTL.",
+            "tags": ["leases"],
+        }));
+        let (status, Json(response)) = write_artifact(
+            State(state.clone()),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(response.memory_layers.vector);
+
+        let mut result = state
+            .db
+            .client
+            .query("SELECT body, tags, embedding FROM type::thing('objects', $id)")
+            .bind(("id", response.id))
+            .await
+            .unwrap();
+        let rows: Vec<Value> = crate::surreal_json::take_json_values(&mut result, 0);
+        assert_eq!(rows[0]["body"], "Renew leases at half their TTL.");
+        assert_eq!(rows[0]["tags"], serde_json::json!(["leases"]));
+        assert_eq!(rows[0]["embedding"], serde_json::json!([1.0, 1.0]));
+    }
+
+    #[tokio::test]
+    async fn test_note_artifact_without_body_is_rejected() {
+        let state = memory_state_with_embedding(
+            test_config(),
+            Box::new(MarkerEmbedding {
+                model: "prose",
+                marker: 1.0,
+            }),
+        )
+        .await;
+
+        let request = note_request(serde_json::json!({
+            "type": "note",
+            "title": "Empty",
+            "body": "   ",
+        }));
+        let err = write_artifact(
+            State(state),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(request),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(matches!(err, ArtifactError::MissingFields { fields, .. } if fields == ["body"]));
+    }
+
+    #[tokio::test]
+    async fn test_warning_artifact_lists_every_missing_field() {
+        let state = memory_state(test_config()).await;
+        let request = note_request(serde_json::json!({ "type": "warning" }));
+        let err = write_artifact(
+            State(state),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(request),
+        )
+        .await
+        .unwrap_err();
+
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["missing_fields"],
+            serde_json::json!(["title", "body", "severity"])
+        );
+        assert_eq!(
+            body["error"],
+            "warning artifacts require title, body, severity"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_warning_artifact_is_stored_with_severity_and_expiry() {
+        let state = memory_state(test_config()).await;
+        let request = note_request(serde_json::json!({
+            "type": "warning",
+            "title": "Staging database is read-only",
+            "body": "Migrations fail until the failover finishes.",
+            "severity": "High",
+            "expires_at": "2030-01-01T00:00:00Z",
+        }));
+        let (status, Json(response)) = write_artifact(
+            State(state.clone()),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(response.artifact_type, "warning");
+
+        let mut result = state
+            .db
+            .client
+            .query("SELECT body, severity, expires_at, tags FROM type::thing('objects', $id)")
+            .bind(("id", response.id))
+            .await
+            .unwrap();
+        let rows: Vec<Value> = crate::surreal_json::take_json_values(&mut result, 0);
+        assert_eq!(
+            rows[0]["body"],
+            "Migrations fail until the failover finishes."
+        );
+        assert_eq!(rows[0]["severity"], "high");
+        assert_eq!(rows[0]["expires_at"], "2030-01-01T00:00:00+00:00");
+        assert_eq!(rows[0]["tags"], serde_json::json!([]));
+
+        let unknown = note_request(serde_json::json!({
+            "type": "warning",
+            "title": "Flaky",
+            "body": "Sometimes fails.",
+            "severity": "urgent",
+        }));
+        let err = write_artifact(
+            State(state),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(unknown),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_changeset_links_changed_files_with_modifies_edges() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query("CREATE objects:`file-lib` CONTENT { type: 'Symbol', kind: 'file', name: 'lib.rs', path: 'src/lib.rs', tenant_id: 'default' }")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let request = note_request(serde_json::json!({
+            "type": "changeset",
+            "title": "Stream tokens from the parser",
+            "description": "Parser no longer buffers the whole file",
+            "files_changed": ["src/lib.rs", "src/missing.rs"],
+        }));
+        let (_, Json(response)) = write_artifact(
+            State(state.clone()),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.unresolved_files, vec!["src/missing.rs".to_string()]);
+
+        let mut result = state
+            .db
+            .client
+            .query("SELECT VALUE meta::id(out) FROM modifies WHERE in = type::thing('objects', $id)")
+            .bind(("id", response.id))
+            .await
+            .unwrap();
+        let targets: Vec<String> = result.take(0).unwrap();
+        assert_eq!(targets, vec!["file-lib".to_string()]);
+    }
+
+    async fn create_task(state: &AppState, title: &str) -> String {
+        let request = note_request(serde_json::json!({
+            "type": "task",
+            "title": title,
+            "description": "Follow up",
+            "assignee": "agent-1",
+            "due": "2024-07-01T12:00:00Z",
+        }));
+        let (_, Json(response)) = write_artifact(
+            State(state.clone()),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        response.id
+    }
+
+    fn task_update(body: serde_json::Value) -> Json<UpdateTaskRequest> {
+        Json(serde_json::from_value(body).unwrap())
+    }
+
+    fn status_query(status: &str) -> axum::extract::Query<ListArtifactsQuery> {
+        axum::extract::Query(ListArtifactsQuery {
+            artifact_type: Some("task".to_string()),
+            project_id: None,
+            agent_id: None,
+            status: Some(status.to_string()),
+            limit: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_task_artifact_starts_open_and_lists_by_status() {
+        let state = memory_state(test_config()).await;
+        create_task(&state, "Rotate keys").await;
+
+        let Json(open) = list_artifacts(State(state.clone()), status_query("open"))
+            .await
+            .unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0]["status"], "open");
+        assert_eq!(open[0]["assignee"], "agent-1");
+        assert_eq!(open[0]["due"], "2024-07-01T12:00:00+00:00");
+
+        let Json(done) = list_artifacts(State(state), status_query("done"))
+            .await
+            .unwrap();
+        assert!(done.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_task_artifact_with_unknown_status_is_rejected() {
+        let state = memory_state(test_config()).await;
+        let request = note_request(serde_json::json!({
+            "type": "task",
+            "title": "Someday",
+            "status": "later",
+        }));
+        let err = write_artifact(
+            State(state),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(request),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_update_task_enforces_status_transitions() {
+        let state = memory_state(test_config()).await;
+        let id = create_task(&state, "Ship release").await;
+
+        let Json(updated) = update_task(
+            State(state.clone()),
+            Tenant::default(),
+            Path(id.clone()),
+            task_update(serde_json::json!({ "status": "in_progress", "assignee": "agent-2" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated["status"], "inprogress");
+        assert_eq!(updated["assignee"], "agent-2");
+
+        let Json(done) = update_task(
+            State(state.clone()),
+            Tenant::default(),
+            Path(id.clone()),
+            task_update(serde_json::json!({ "status": "done" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(done["status"], "done");
+        let conflict = update_task(
+            State(state.clone()),
+            Tenant::default(),
+            Path(id.clone()),
+            task_update(serde_json::json!({ "status": "blocked" })),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(conflict, StatusCode::CONFLICT);
+
+        let Json(reopened) = update_task(
+            State(state.clone()),
+            Tenant::default(),
+            Path(id),
+            task_update(serde_json::json!({ "status": "open" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(reopened["status"], "open");
+
+        let missing = update_task(
+            State(state),
+            Tenant::default(),
+            Path("no-such-task".to_string()),
+            task_update(serde_json::json!({ "status": "done" })),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(missing, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_artifacts_are_scoped_to_the_request_tenant() {
+        let state = memory_state(test_config()).await;
+        let task_id = create_task(&state, "Default tenant task").await;
+        let decision_id = create_decision(
+            &state,
+            serde_json::json!({
+                "type": "decision",
+                "title": "Default tenant decision",
+                "decision": "Keep it",
+            }),
+        )
+        .await;
+
+        let mut stored = state
+            .db
+            .client
+            .query("SELECT VALUE tenant_id FROM type::thing('objects', $id)")
+            .bind(("id", task_id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(
+            crate::surreal_json::take_json_values(&mut stored, 0),
+            vec![serde_json::json!("default")]
+        );
+
+        let other = || Tenant("tenant-b".to_string());
+        let update = update_task(
+            State(state.clone()),
+            other(),
+            Path(task_id),
+            task_update(serde_json::json!({ "status": "done" })),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(update, StatusCode::NOT_FOUND);
+
+        let request = serde_json::json!({
+            "replacement": { "type": "decision", "title": "Hijack", "decision": "Replace it" },
+        });
+        let supersede = supersede_decision(
+            State(state.clone()),
+            other(),
+            Path(decision_id.clone()),
+            Json(serde_json::from_value(request).unwrap()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(supersede, StatusCode::NOT_FOUND);
+        assert_eq!(decision_row(&state, &decision_id).await["status"], Value::Null);
+
+        let (_, Json(written)) = write_artifact(
+            State(state.clone()),
+            other(),
+            IdempotencyKey::default(),
+            Json(note_request(serde_json::json!({
+                "type": "decision",
+                "title": "Tenant B decision",
+                "decision": "Theirs",
+            }))),
+        )
+        .await
+        .unwrap();
+        let mine = supersede_decision(
+            State(state),
+            Tenant::default(),
+            Path(decision_id),
+            Json(serde_json::from_value(serde_json::json!({ "decision_id": written.id })).unwrap()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(mine, StatusCode::BAD_REQUEST);
+    }
+
+    async fn create_decision(state: &AppState, body: serde_json::Value) -> String {
+        let (_, Json(response)) = write_artifact(
+            State(state.clone()),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(note_request(body)),
+        )
+        .await
+        .unwrap();
+        response.id
+    }
+
+    async fn decision_row(state: &AppState, id: &str) -> serde_json::Value {
+        let mut result = state
+            .db
+            .client
+            .query("SELECT VALUE { status: status, superseded_by: superseded_by } FROM type::thing('objects', $id)")
+            .bind(("id", id.to_string()))
+            .await
+            .unwrap();
+        crate::surreal_json::take_json_values(&mut result, 0).remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_supersede_decision_marks_old_and_copies_file_links() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query("CREATE objects:`file-auth` CONTENT { type: 'Symbol', kind: 'file', name: 'auth.rs', path: 'src/auth.rs', tenant_id: 'default' }")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let old_id = create_decision(
+            &state,
+            serde_json::json!({
+                "type": "decision",
+                "title": "Sessions in cookies",
+                "decision": "Store sessions in signed cookies",
+                "related_files": ["src/auth.rs"],
+            }),
+        )
+        .await;
+
+        let request = serde_json::json!({
+            "replacement": {
+                "type": "decision",
+                "title": "Sessions in Redis",
+                "decision": "Move sessions to Redis",
+            },
+            "copy_file_links": true,
+        });
+        let Json(response) = supersede_decision(
+            State(state.clone()),
+            Tenant::default(),
+            Path(old_id.clone()),
+            Json(serde_json::from_value(request.clone()).unwrap()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.superseded_id, old_id);
+        assert_eq!(response.file_links_copied, 1);
+        assert_eq!(response.relationships_created, 2);
+        let new_id = response.superseded_by;
+
+        let old = decision_row(&state, &old_id).await;
+        assert_eq!(old["status"], "superseded");
+        assert_eq!(old["superseded_by"], new_id.as_str());
+
+        let Json(edges) = crate::handlers::relationships::get_relationships(
+            State(state.clone()),
+            axum::extract::Query(crate::handlers::relationships::RelationshipQuery {
+                object_id: Some(old_id.clone()),
+                source_id: None,
+                target_id: None,
+                relation_type: Some("superseded_by".to_string()),
+                project_id: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(edges.len(), 1, "{:?}", edges);
+
+        let mut result = state
+            .db
+            .client
+            .query("SELECT VALUE meta::id(in) FROM justified_by WHERE out = type::thing('objects', $id)")
+            .bind(("id", new_id.clone()))
+            .await
+            .unwrap();
+        let sources: Vec<String> = result.take(0).unwrap();
+        assert_eq!(sources, vec!["file-auth".to_string()]);
+
+        // A decision is only superseded once
+        let again = supersede_decision(
+            State(state),
+            Tenant::default(),
+            Path(old_id),
+            Json(serde_json::from_value(request).unwrap()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(again, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_decision_written_with_supersedes_replaces_the_old_one() {
+        let state = memory_state(test_config()).await;
+        let old_id = create_decision(
+            &state,
+            serde_json::json!({ "type": "decision", "title": "Use REST", "decision": "REST everywhere" }),
+        )
+        .await;
+        let new_id = create_decision(
+            &state,
+            serde_json::json!({
+                "type": "decision",
+                "title": "Use gRPC internally",
+                "decision": "gRPC between services",
+                "supersedes": old_id,
+            }),
+        )
+        .await;
+        assert_eq!(decision_row(&state, &old_id).await["status"], "superseded");
+
+        let missing = write_artifact(
+            State(state.clone()),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(note_request(serde_json::json!({
+                "type": "decision",
+                "title": "Orphan",
+                "supersedes": "no-such-decision",
+            }))),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+        let not_a_decision = supersede_decision(
+            State(state),
+            Tenant::default(),
+            Path(new_id),
+            Json(
+                serde_json::from_value(serde_json::json!({
+                    "replacement": { "type": "note", "title": "n", "body": "b" },
+                }))
+                .unwrap(),
+            ),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(not_a_decision, StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,8 +1,16 @@
 use axum::{extract::State, http::StatusCode, response::Json};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-
-use crate::services::cache::{CacheItem, CacheItemKind, CacheService};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::database::knn_condition;
+use crate::services::cache::{token_estimate, CacheItem, CacheItemKind, CacheService};
+use crate::services::embedding::EmbeddingService;
+use crate::services::index_llm::IndexLlmService;
+use crate::tenant::Tenant;
 use crate::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +21,9 @@ pub struct GetPackRequest {
     pub query: Option<String>,
     #[allow(dead_code)] // Reserved for delta pack feature
     pub since_version: Option<u64>,
+    /// Append summaries of this many of the scope's most recent closed blocks
+    #[serde(default)]
+    pub include_recent_blocks: usize,
 }
 
 fn default_token_budget() -> usize {
@@ -28,11 +39,22 @@ pub struct GetPackResponse {
     pub snippets: Vec<PackItem>,
     pub warnings: Vec<PackItem>,
     pub artifact_pointers: Vec<String>,
+    /// Newest first; only as many as fit in the token budget left by the pack
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recent_blocks: Vec<RecentBlock>,
     pub token_count: usize,
     pub version: u64,
     pub is_fresh: bool,
 }
 
+#[derive(Debug, Serialize)]
+pub struct RecentBlock {
+    pub block_id: String,
+    pub sequence: u64,
+    pub summary: String,
+    pub created_at: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct PackItem {
     pub preview: String,
@@ -54,12 +76,13 @@ impl From<CacheItem> for PackItem {
 
 pub async fn get_pack(
     State(state): State<AppState>,
+    tenant: Tenant,
     Json(request): Json<GetPackRequest>,
 ) -> Result<Json<GetPackResponse>, (StatusCode, String)> {
     // Get query embedding if query provided
     let query_embedding = if let Some(ref query) = request.query {
         if state.embedding_service.is_enabled() {
-            state.embedding_service.generate_embedding(query).await.ok()
+            state.embedding_service.generate_query_embedding(query).await.ok()
         } else {
             None
         }
@@ -81,6 +104,27 @@ pub async fn get_pack(
             (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
         })?;
 
+    let mut token_count = pack.token_count;
+    let mut recent_blocks = Vec::new();
+    if request.include_recent_blocks > 0 {
+        let blocks = recent_closed_blocks(
+            &state,
+            &tenant,
+            &request.scope_id,
+            request.include_recent_blocks,
+        )
+        .await?;
+        for block in blocks {
+            let tokens = state.token_estimator.estimate(&block.summary, "summary");
+            // Stop at the first that doesn't fit so the included blocks stay contiguous
+            if token_count + tokens > request.token_budget {
+                break;
+            }
+            token_count += tokens;
+            recent_blocks.push(block);
+        }
+    }
+
     Ok(Json(GetPackResponse {
         scope_id: pack.scope_id,
         summary: pack.summary,
@@ -89,12 +133,55 @@ pub async fn get_pack(
         snippets: pack.snippets.into_iter().map(PackItem::from).collect(),
         warnings: pack.warnings.into_iter().map(PackItem::from).collect(),
         artifact_pointers: pack.artifact_pointers,
-        token_count: pack.token_count,
+        recent_blocks,
+        token_count,
         version: pack.version,
         is_fresh: pack.is_fresh,
     }))
 }
 
+/// Summarized closed blocks of `scope_id`, newest first.
+async fn recent_closed_blocks(
+    state: &AppState,
+    tenant: &Tenant,
+    scope_id: &str,
+    limit: usize,
+) -> Result<Vec<RecentBlock>, (StatusCode, String)> {
+    let query = "SELECT <string>id AS block_id, sequence, summary, <string>created_at AS created_at FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'closed' AND summary IS NOT NONE ORDER BY sequence DESC LIMIT $limit";
+    let mut response = state
+        .db
+        .client
+        .query(query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
+        .bind(("scope_id", scope_id.to_string()))
+        .bind(("limit", limit as i64))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(take_json_values(&mut response, 0)
+        .iter()
+        .map(|v| RecentBlock {
+            block_id: v
+                .get("block_id")
+                .and_then(|s| s.as_str())
+                .unwrap_or("")
+                .to_string(),
+            sequence: v.get("sequence").and_then(|s| s.as_u64()).unwrap_or(0),
+            summary: v
+                .get("summary")
+                .and_then(|s| s.as_str())
+                .unwrap_or("")
+                .to_string(),
+            created_at: v
+                .get("created_at")
+                .and_then(|s| s.as_str())
+                .unwrap_or("")
+                .to_string(),
+        })
+        .filter(|block| !block.summary.is_empty())
+        .collect())
+}
+
 #[derive(Debug, Deserialize)]
 pub struct WriteItemsRequest {
     pub scope_id: String,
@@ -120,6 +207,9 @@ fn default_importance() -> f32 {
 pub struct WriteItemsResponse {
     pub written: usize,
     pub merged: usize,
+    /// Content of items dropped by the low-value filter
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filtered: Vec<String>,
 }
 
 pub async fn write_items(
@@ -127,9 +217,19 @@ pub async fn write_items(
     Json(request): Json<WriteItemsRequest>,
 ) -> Result<Json<WriteItemsResponse>, (StatusCode, String)> {
     let cache_service = CacheService::new(state.db.clone(), state.embedding_service.clone());
+    let settings = state
+        .settings_service
+        .load_settings()
+        .await
+        .unwrap_or_default();
 
-    let items: Vec<CacheItem> = request
+    let (inputs, filtered): (Vec<_>, Vec<_>) = request
         .items
+        .into_iter()
+        .partition(|input| !settings.is_low_value_cache_item(&input.preview));
+    let filtered: Vec<String> = filtered.into_iter().map(|input| input.preview).collect();
+
+    let items: Vec<CacheItem> = inputs
         .into_iter()
         .map(|input| {
             let kind = match input.kind.to_lowercase().as_str() {
@@ -156,6 +256,13 @@ pub async fn write_items(
         .collect();
 
     let total = items.len();
+    if total == 0 {
+        return Ok(Json(WriteItemsResponse {
+            written: 0,
+            merged: 0,
+            filtered,
+        }));
+    }
     let written = cache_service
         .write_items(&request.scope_id, items)
         .await
@@ -167,7 +274,11 @@ pub async fn write_items(
     // Items that weren't written were merged with existing
     let merged = total - written;
 
-    Ok(Json(WriteItemsResponse { written, merged }))
+    Ok(Json(WriteItemsResponse {
+        written,
+        merged,
+        filtered,
+    }))
 }
 
 pub async fn gc(State(state): State<AppState>) -> Result<Json<Value>, (StatusCode, String)> {
@@ -192,7 +303,10 @@ pub async fn gc(State(state): State<AppState>) -> Result<Json<Value>, (StatusCod
 use crate::surreal_json::take_json_values;
 
 const MAX_BLOCKS: usize = 20;
+const GC_KEEP_NEWEST: usize = 3;
 const TOKEN_THRESHOLD: usize = 1800;
+const FANOUT_WRITE_ATTEMPTS: usize = 3;
+const LLM_SUMMARY_TIMEOUT_SECS: u64 = 20;
 
 /// Escape a cache_block record ID for use in queries
 /// SurrealDB requires backticks around IDs containing hyphens
@@ -215,9 +329,26 @@ fn escape_block_id(id: &str) -> String {
     format!("cache_block:`{}`", clean_uuid)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct BlockWriteRequest {
     pub scope_id: String,
+    #[serde(default)]
+    pub kind: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default = "default_importance")]
+    pub importance: f32,
+    pub file_ref: Option<String>,
+    /// Let `block_gc` delete the block this many seconds after it was created, once closed
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+    /// Items appended in order; when set, the single `kind`/`content` item is ignored
+    #[serde(default)]
+    pub items: Vec<BlockWriteItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockWriteItem {
     pub kind: String,
     pub content: String,
     #[serde(default = "default_importance")]
@@ -225,6 +356,21 @@ pub struct BlockWriteRequest {
     pub file_ref: Option<String>,
 }
 
+impl BlockWriteRequest {
+    /// Items to append: the batch when given, otherwise the single inline item
+    fn entries(&self) -> Vec<BlockWriteItem> {
+        if !self.items.is_empty() {
+            return self.items.clone();
+        }
+        vec![BlockWriteItem {
+            kind: self.kind.clone(),
+            content: self.content.clone(),
+            importance: self.importance,
+            file_ref: self.file_ref.clone(),
+        }]
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct BlockWriteResponse {
     pub block_id: String,
@@ -233,6 +379,22 @@ pub struct BlockWriteResponse {
     pub items_in_block: usize,
     pub new_block_id: Option<String>,
     pub evicted_block: Option<String>,
+    /// True when at least one block was closed during this write
+    pub compacted: bool,
+    /// Where each batch item landed, in request order; empty for single writes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<BlockWriteItemResult>,
+    /// Content of items dropped by the low-value filter
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filtered: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockWriteItemResult {
+    pub block_id: String,
+    /// Block closed to make room for this item
+    pub closed_block_id: Option<String>,
+    pub evicted_block: Option<String>,
 }
 
 fn normalize_run_id(raw: &str) -> String {
@@ -268,19 +430,18 @@ async fn fetch_active_run_ids_for_project(state: &AppState, project_id: &str) ->
 
 async fn write_block_for_scope(
     state: &AppState,
+    tenant: &Tenant,
     scope_id: &str,
     request: &BlockWriteRequest,
 ) -> Result<BlockWriteResponse, (StatusCode, String)> {
-    // Estimate tokens for this item
-    let item_tokens = request.content.len() / 4;
-
     // Find or create open block for this scope
-    let find_query = "SELECT <string>id AS id_str, scope_id, sequence, status, items, token_count FROM cache_block WHERE scope_id = $scope_id AND status = 'open' LIMIT 1";
+    let find_query = "SELECT <string>id AS id_str, scope_id, sequence, status, items, token_count FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'open' LIMIT 1";
 
     tracing::debug!("Looking for open cache_block with scope_id = '{}'", scope_id);
 
     let mut response = state.db.client
         .query(find_query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .bind(("scope_id", scope_id.to_string()))
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -288,7 +449,7 @@ async fn write_block_for_scope(
     let values = take_json_values(&mut response, 0);
     tracing::debug!("Found {} cache_block records", values.len());
 
-    let (block_id, mut token_count, mut items, sequence) = if let Some(block) = values.first() {
+    let (mut block_id, mut token_count, mut items, mut sequence) = if let Some(block) = values.first() {
         tracing::debug!("Found existing block: {:?}", block);
         let id = block.get("id_str").and_then(|v| v.as_str()).unwrap_or("").to_string();
         let tokens = block.get("token_count").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
@@ -302,115 +463,266 @@ async fn write_block_for_scope(
         let uuid = uuid::Uuid::new_v4();
         let new_id = format!("cache_block:`{}`", uuid);
         let create_query = format!(
-            "CREATE {} SET scope_id = $scope_id, sequence = 1, status = 'open', items = [], token_count = 0, created_at = time::now()",
+            "CREATE {} SET tenant_id = $tenant_id, scope_id = $scope_id, sequence = 1, status = 'open', items = [], token_count = 0, created_at = time::now()",
             new_id
         );
         tracing::debug!("Creating block with query: {}", create_query);
         state.db.client
             .query(&create_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("scope_id", scope_id.to_string()))
             .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .check()
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
         tracing::debug!("Created new block: {}", new_id);
         (new_id, 0, Vec::new(), 1)
     };
 
-    // Check if adding this item would exceed threshold
+    // Append items in order, rolling to a new block whenever one would cross the threshold
+    let batched = !request.items.is_empty();
     let mut new_block_id = None;
     let mut evicted_block = None;
-    let mut final_block_id = block_id.clone();
-    let mut final_status = "open".to_string();
-
-    if token_count + item_tokens >= TOKEN_THRESHOLD {
-        // Close current block
-        let close_result = close_block(state, &block_id, scope_id).await;
-        if let Err(e) = close_result {
-            tracing::warn!("Failed to close block: {}", e);
-        }
+    let mut compacted = false;
+    let mut placements = Vec::new();
+    let mut unsaved = false;
+
+    for entry in request.entries() {
+        // Estimate tokens for this item
+        let item_tokens = state.token_estimator.estimate(&entry.content, &entry.kind);
+        let mut closed_block_id = None;
+        let mut evicted_for_item = None;
+
+        if token_count + item_tokens >= TOKEN_THRESHOLD {
+            // Earlier batch items must be stored before the block is summarized
+            if unsaved {
+                save_block_items(state, &block_id, &items, token_count, request.ttl_seconds).await?;
+            }
 
-        // Check if we need to evict oldest block
-        evicted_block = evict_oldest_if_needed(state, scope_id).await.ok().flatten();
+            // Close current block
+            let close_result = close_block(state, &block_id, scope_id).await;
+            if let Err(e) = close_result {
+                tracing::warn!("Failed to close block: {}", e);
+            }
 
-        // Create new block - use backticks to escape UUID with hyphens
-        let new_seq = sequence + 1;
-        let uuid = uuid::Uuid::new_v4();
-        let created_id = format!("cache_block:`{}`", uuid);
-        let create_query = format!(
-            "CREATE {} SET scope_id = $scope_id, sequence = $seq, status = 'open', items = [], token_count = 0, created_at = time::now()",
-            created_id
-        );
-        state.db.client
-            .query(&create_query)
-            .bind(("scope_id", scope_id.to_string()))
-            .bind(("seq", new_seq as i32))
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            // Check if we need to evict oldest block
+            evicted_for_item = evict_oldest_if_needed(state, tenant, scope_id)
+                .await
+                .ok()
+                .flatten();
+
+            // Create new block - use backticks to escape UUID with hyphens
+            sequence += 1;
+            let uuid = uuid::Uuid::new_v4();
+            let created_id = format!("cache_block:`{}`", uuid);
+            let create_query = format!(
+                "CREATE {} SET tenant_id = $tenant_id, scope_id = $scope_id, sequence = $seq, status = 'open', items = [], token_count = 0, created_at = time::now()",
+                created_id
+            );
+            state.db.client
+                .query(&create_query)
+                .bind(("tenant_id", tenant.as_str().to_string()))
+                .bind(("scope_id", scope_id.to_string()))
+                .bind(("seq", sequence as i32))
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+            closed_block_id = Some(std::mem::replace(&mut block_id, created_id.clone()));
+            new_block_id = Some(created_id);
+            if evicted_for_item.is_some() {
+                evicted_block = evicted_for_item.clone();
+            }
+            compacted = true;
+            token_count = 0;
+            items = Vec::new();
+        }
 
-        new_block_id = Some(created_id.clone());
-        final_block_id = created_id;
-        final_status = "closed".to_string(); // Previous block was closed
-        token_count = 0;
-        items = Vec::new();
+        // Add item to the block
+        items.push(serde_json::json!({
+            "kind": entry.kind,
+            "content": entry.content,
+            "importance": entry.importance,
+            "file_ref": entry.file_ref,
+            "created_at": chrono::Utc::now().to_rfc3339()
+        }));
+        token_count += item_tokens;
+        unsaved = true;
+
+        if batched {
+            placements.push(BlockWriteItemResult {
+                block_id: block_id.clone(),
+                closed_block_id,
+                evicted_block: evicted_for_item,
+            });
+        }
     }
 
-    // Add item to the block
-    let new_item = serde_json::json!({
-        "kind": request.kind,
-        "content": request.content,
-        "importance": request.importance,
-        "file_ref": request.file_ref,
-        "created_at": chrono::Utc::now().to_rfc3339()
-    });
-    items.push(new_item);
-    token_count += item_tokens;
-
-    // Update the block - escape ID for SurrealDB
-    let escaped_id = escape_block_id(&final_block_id);
-    let update_query = format!(
-        "UPDATE {} SET items = $items, token_count = $tokens",
-        escaped_id
-    );
-    state.db.client
-        .query(&update_query)
-        .bind(("items", items.clone()))
-        .bind(("tokens", token_count as i32))
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    save_block_items(state, &block_id, &items, token_count, request.ttl_seconds).await?;
 
     Ok(BlockWriteResponse {
-        block_id: final_block_id,
-        block_status: final_status,
+        block_id,
+        // "closed" reports that a previous block was closed during this write
+        block_status: if compacted { "closed" } else { "open" }.to_string(),
         token_count,
         items_in_block: items.len(),
         new_block_id,
         evicted_block,
+        compacted,
+        items: placements,
+        filtered: Vec::new(),
     })
 }
 
-/// Write an item to the current open cache block
+/// Store a block's items and token count - escape ID for SurrealDB
+async fn save_block_items(
+    state: &AppState,
+    block_id: &str,
+    items: &[Value],
+    token_count: usize,
+    ttl_seconds: Option<u64>,
+) -> Result<(), (StatusCode, String)> {
+    let escaped_id = escape_block_id(block_id);
+    let ttl_clause = if ttl_seconds.is_some() {
+        ", ttl_seconds = $ttl"
+    } else {
+        ""
+    };
+    let update_query = format!(
+        "UPDATE {} SET items = $items, token_count = $tokens{}",
+        escaped_id, ttl_clause
+    );
+    state.db.client
+        .query(&update_query)
+        .bind(("items", items.to_vec()))
+        .bind(("tokens", token_count as i32))
+        .bind(("ttl", ttl_seconds.map(|ttl| ttl as i64)))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .check()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(())
+}
+
+/// Write an item, or a batch of items in order, to the current open cache block
 pub async fn block_write(
     State(state): State<AppState>,
-    Json(request): Json<BlockWriteRequest>,
+    tenant: Tenant,
+    Json(mut request): Json<BlockWriteRequest>,
 ) -> Result<Json<BlockWriteResponse>, (StatusCode, String)> {
-    let primary = write_block_for_scope(&state, &request.scope_id, &request).await?;
+    if request.items.is_empty() && request.kind.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "kind and content are required unless items is set".to_string(),
+        ));
+    }
+    let settings = state
+        .settings_service
+        .load_settings()
+        .await
+        .unwrap_or_default();
+    let (kept, filtered): (Vec<_>, Vec<_>) = request
+        .entries()
+        .into_iter()
+        .partition(|entry| !settings.is_low_value_cache_item(&entry.content));
+    let filtered: Vec<String> = filtered.into_iter().map(|entry| entry.content).collect();
+    if kept.is_empty() {
+        // Nothing left to write: leave the open block (and fan-out scopes) untouched
+        return Ok(Json(BlockWriteResponse {
+            block_id: String::new(),
+            block_status: "filtered".to_string(),
+            token_count: 0,
+            items_in_block: 0,
+            new_block_id: None,
+            evicted_block: None,
+            compacted: false,
+            items: Vec::new(),
+            filtered,
+        }));
+    }
+    if !filtered.is_empty() {
+        request.items = kept;
+    }
+
+    let mut primary = write_block_for_scope(&state, &tenant, &request.scope_id, &request).await?;
+    primary.filtered = filtered;
 
     if let Some(project_id) = request.scope_id.strip_prefix("project:") {
         let run_ids = fetch_active_run_ids_for_project(&state, project_id).await;
-        for run_id in run_ids {
-            let normalized_run = normalize_run_id(&run_id);
-            if normalized_run.is_empty() {
-                continue;
+        let scopes = fan_out_scopes(&run_ids);
+        if !scopes.is_empty() {
+            let fan_out = fan_out_block_writes(state.clone(), tenant, scopes, Arc::new(request));
+            if state.config.cache_fanout_background {
+                tokio::spawn(fan_out);
+            } else {
+                fan_out.await;
             }
-            let run_scope = format!("run:{}", normalized_run);
-            let session_scope = format!("session:{}", normalized_run);
-            let _ = write_block_for_scope(&state, &run_scope, &request).await;
-            let _ = write_block_for_scope(&state, &session_scope, &request).await;
         }
     }
 
     Ok(Json(primary))
 }
 
+/// Run and session scopes mirroring a project write. Deduplicated so no two
+/// concurrent fan-out writes ever touch the same scope's open block.
+fn fan_out_scopes(run_ids: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut scopes = Vec::new();
+    for run_id in run_ids {
+        let normalized_run = normalize_run_id(run_id);
+        if normalized_run.is_empty() || !seen.insert(normalized_run.clone()) {
+            continue;
+        }
+        scopes.push(format!("run:{}", normalized_run));
+        scopes.push(format!("session:{}", normalized_run));
+    }
+    scopes
+}
+
+/// Write to each fan-out scope concurrently (bounded by config). Runs only
+/// after the primary write; failures are logged and never surface to the caller.
+async fn fan_out_block_writes(
+    state: AppState,
+    tenant: Tenant,
+    scopes: Vec<String>,
+    request: Arc<BlockWriteRequest>,
+) -> usize {
+    let semaphore = Arc::new(Semaphore::new(state.config.cache_fanout_concurrency.max(1)));
+    let mut join_set = JoinSet::new();
+    for scope in scopes {
+        let state = state.clone();
+        let tenant = tenant.clone();
+        let request = request.clone();
+        let semaphore = semaphore.clone();
+        join_set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let mut attempt = 1;
+            loop {
+                let result = write_block_for_scope(&state, &tenant, &scope, &request).await;
+                match &result {
+                    // Concurrent block creation can conflict in SurrealDB; the
+                    // failed transaction is rolled back, so retrying is safe.
+                    Err((_, e)) if attempt < FANOUT_WRITE_ATTEMPTS && e.contains("can be retried") => {
+                        attempt += 1;
+                    }
+                    _ => break (scope, result),
+                }
+            }
+        });
+    }
+
+    let mut succeeded = 0;
+    while let Some(joined) = join_set.join_next().await {
+        match joined {
+            Ok((_, Ok(_))) => succeeded += 1,
+            Ok((scope, Err((_, e)))) => {
+                tracing::warn!("Cache fan-out write to {} failed: {}", scope, e);
+            }
+            Err(e) => tracing::warn!("Cache fan-out task failed: {}", e),
+        }
+    }
+    succeeded
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BlockCompactRequest {
     pub scope_id: String,
@@ -426,32 +738,40 @@ pub struct BlockCompactResponse {
 /// Close current block and open a new one
 pub async fn block_compact(
     State(state): State<AppState>,
+    tenant: Tenant,
     Json(request): Json<BlockCompactRequest>,
 ) -> Result<Json<BlockCompactResponse>, (StatusCode, String)> {
     // Find open block
-    let find_query = "SELECT <string>id AS id_str, sequence FROM cache_block WHERE scope_id = $scope_id AND status = 'open' LIMIT 1";
+    let find_query = "SELECT <string>id AS id_str, sequence FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'open' LIMIT 1";
 
     let mut response = state.db.client
         .query(find_query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .bind(("scope_id", request.scope_id.clone()))
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     let values = take_json_values(&mut response, 0);
 
-    let (closed_id, sequence) = if let Some(block) = values.first() {
+    let (closed_id, sequence, summary_generated) = if let Some(block) = values.first() {
         let id = block.get("id_str").and_then(|v| v.as_str()).unwrap_or("").to_string();
         let seq = block.get("sequence").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
 
         // Close the block
-        let _summary_generated = close_block(&state, &id, &request.scope_id).await.is_ok();
+        let summary_generated = match close_block(&state, &id, &request.scope_id).await {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Failed to close block {}: {}", id, e);
+                false
+            }
+        };
 
         // Evict if needed
-        let _ = evict_oldest_if_needed(&state, &request.scope_id).await;
+        let _ = evict_oldest_if_needed(&state, &tenant, &request.scope_id).await;
 
-        (Some(id), seq)
+        (Some(id), seq, summary_generated)
     } else {
-        (None, 0)
+        (None, 0, false)
     };
 
     // Create new block - use backticks to escape UUID with hyphens
@@ -459,11 +779,12 @@ pub async fn block_compact(
     let uuid = uuid::Uuid::new_v4();
     let new_id = format!("cache_block:`{}`", uuid);
     let create_query = format!(
-        "CREATE {} SET scope_id = $scope_id, sequence = $seq, status = 'open', items = [], token_count = 0, created_at = time::now()",
+        "CREATE {} SET tenant_id = $tenant_id, scope_id = $scope_id, sequence = $seq, status = 'open', items = [], token_count = 0, created_at = time::now()",
         new_id
     );
     state.db.client
         .query(&create_query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .bind(("scope_id", request.scope_id.clone()))
         .bind(("seq", new_seq as i32))
         .await
@@ -472,7 +793,7 @@ pub async fn block_compact(
     Ok(Json(BlockCompactResponse {
         closed_block_id: closed_id,
         new_block_id: new_id,
-        summary_generated: true,
+        summary_generated,
     }))
 }
 
@@ -485,6 +806,9 @@ pub struct BlockSearchRequest {
     /// Include the current open block in search results (default: false)
     #[serde(default)]
     pub include_open: bool,
+    /// Attach each matching block's items, saving a `block_get` per match
+    #[serde(default)]
+    pub include_content: bool,
 }
 
 fn default_search_limit() -> usize {
@@ -499,9 +823,16 @@ pub struct BlockSearchResponse {
 #[derive(Debug, Serialize)]
 pub struct BlockMatch {
     pub block_id: String,
+    /// Position in the scope's rolling window; higher is newer
+    pub sequence: usize,
+    /// `open` for the block still being written, otherwise `closed`
+    pub status: String,
     pub summary: String,
     pub relevance: f64,
     pub created_at: String,
+    /// Block items, only when the search asked for `include_content`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -551,6 +882,7 @@ pub struct BlockReadResponse {
 
 pub async fn block_read_get(
     State(state): State<AppState>,
+    tenant: Tenant,
     axum::extract::Query(query): axum::extract::Query<BlockReadQuery>,
 ) -> Result<Json<BlockReadResponse>, (StatusCode, String)> {
     let request = BlockReadRequest {
@@ -562,18 +894,20 @@ pub async fn block_read_get(
         limit: query.limit,
         block_id: query.block_id,
     };
-    block_read_impl(&state, request).await
+    block_read_impl(&state, &tenant, request).await
 }
 
 pub async fn block_read_post(
     State(state): State<AppState>,
+    tenant: Tenant,
     Json(request): Json<BlockReadRequest>,
 ) -> Result<Json<BlockReadResponse>, (StatusCode, String)> {
-    block_read_impl(&state, request).await
+    block_read_impl(&state, &tenant, request).await
 }
 
 pub async fn block_list_get(
     State(state): State<AppState>,
+    tenant: Tenant,
     axum::extract::Query(query): axum::extract::Query<BlockReadQuery>,
 ) -> Result<Json<BlockReadResponse>, (StatusCode, String)> {
     let request = BlockReadRequest {
@@ -585,26 +919,28 @@ pub async fn block_list_get(
         limit: query.limit,
         block_id: None,
     };
-    block_read_impl(&state, request).await
+    block_read_impl(&state, &tenant, request).await
 }
 
 pub async fn block_list_post(
     State(state): State<AppState>,
+    tenant: Tenant,
     Json(mut request): Json<BlockReadRequest>,
 ) -> Result<Json<BlockReadResponse>, (StatusCode, String)> {
     request.list_all = Some(true);
     request.query = None;
     request.block_id = None;
-    block_read_impl(&state, request).await
+    block_read_impl(&state, &tenant, request).await
 }
 
 async fn block_read_impl(
     state: &AppState,
+    tenant: &Tenant,
     request: BlockReadRequest,
 ) -> Result<Json<BlockReadResponse>, (StatusCode, String)> {
     // Case 1: Get a specific block by ID
     if let Some(block_id) = request.block_id.as_deref() {
-        let block = get_block_by_id(state, block_id).await?;
+        let block = get_block_by_id(state, tenant, block_id).await?;
         return Ok(Json(BlockReadResponse {
             scope_id: request.scope_id,
             block: Some(block),
@@ -624,14 +960,16 @@ async fn block_read_impl(
             query: "*".to_string(),
             limit,
             include_open,
+            include_content: false,
         };
 
-        let Json(search_result) = block_search(State(state.clone()), Json(search_request)).await?;
+        let Json(search_result) =
+            block_search(State(state.clone()), tenant.clone(), Json(search_request)).await?;
 
         if include_content {
             let mut blocks = Vec::new();
             for m in &search_result.matches {
-                blocks.push(get_block_by_id(state, &m.block_id).await?);
+                blocks.push(get_block_by_id(state, tenant, &m.block_id).await?);
             }
             return Ok(Json(BlockReadResponse {
                 scope_id: request.scope_id,
@@ -660,14 +998,16 @@ async fn block_read_impl(
             query,
             limit,
             include_open,
+            include_content: false,
         };
 
-        let Json(search_result) = block_search(State(state.clone()), Json(search_request)).await?;
+        let Json(search_result) =
+            block_search(State(state.clone()), tenant.clone(), Json(search_request)).await?;
 
         if include_content {
             let mut blocks = Vec::new();
             for m in &search_result.matches {
-                blocks.push(get_block_by_id(state, &m.block_id).await?);
+                blocks.push(get_block_by_id(state, tenant, &m.block_id).await?);
             }
             return Ok(Json(BlockReadResponse {
                 scope_id: request.scope_id,
@@ -686,7 +1026,7 @@ async fn block_read_impl(
     }
 
     // Case 4: default to current open block for scope
-    let block = get_or_create_open_block(state, &request.scope_id).await?;
+    let block = get_or_create_open_block(state, tenant, &request.scope_id).await?;
     Ok(Json(BlockReadResponse {
         scope_id: request.scope_id,
         block: Some(block),
@@ -698,16 +1038,18 @@ async fn block_read_impl(
 /// Search cache blocks by summary
 pub async fn block_search(
     State(state): State<AppState>,
+    tenant: Tenant,
     Json(request): Json<BlockSearchRequest>,
 ) -> Result<Json<BlockSearchResponse>, (StatusCode, String)> {
     let mut matches: Vec<BlockMatch> = Vec::new();
 
     // If include_open is true, first add the current open block (if it exists and matches)
     if request.include_open {
-        let open_query = "SELECT <string>id AS block_id, items, <string>created_at AS created_at FROM cache_block WHERE scope_id = $scope_id AND status = 'open' LIMIT 1";
+        let open_query = "SELECT <string>id AS block_id, sequence, items, <string>created_at AS created_at FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'open' LIMIT 1";
 
         let mut open_response = state.db.client
             .query(open_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("scope_id", request.scope_id.clone()))
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -745,9 +1087,12 @@ pub async fn block_search(
 
                 matches.push(BlockMatch {
                     block_id: open_block.get("block_id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    sequence: open_block.get("sequence").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+                    status: "open".to_string(),
                     summary,
                     relevance: 1.0, // Open block gets highest relevance since it's current
                     created_at: open_block.get("created_at").and_then(|c| c.as_str()).unwrap_or("").to_string(),
+                    items: request.include_content.then_some(items),
                 });
             }
         }
@@ -755,7 +1100,7 @@ pub async fn block_search(
 
     // Generate embedding for query
     let query_embedding = if state.embedding_service.is_enabled() && request.query != "*" {
-        state.embedding_service.generate_embedding(&request.query).await.ok()
+        state.embedding_service.generate_query_embedding(&request.query).await.ok()
     } else {
         None
     };
@@ -763,13 +1108,23 @@ pub async fn block_search(
     let closed_matches: Vec<BlockMatch> = if let Some(embedding) = query_embedding {
         // Semantic search on summaries
         let vec_str = embedding.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(", ");
+        let indexed = state.db.vector_index_dimension("cache_block").await == Some(embedding.len());
+        let candidates = if indexed {
+            format!(
+                "{} AND ",
+                knn_condition("summary_embedding", request.limit, &embedding)
+            )
+        } else {
+            String::new()
+        };
         let search_query = format!(
-            "SELECT <string>id AS block_id, summary, vector::similarity::cosine(summary_embedding, [{}]) AS relevance, <string>created_at AS created_at FROM cache_block WHERE scope_id = $scope_id AND status = 'closed' AND summary_embedding IS NOT NONE ORDER BY relevance DESC LIMIT $limit",
-            vec_str
+            "SELECT <string>id AS block_id, sequence, status, summary, vector::similarity::cosine(summary_embedding, [{}]) AS relevance, <string>created_at AS created_at, items FROM cache_block WHERE {}tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'closed' AND summary_embedding IS NOT NONE ORDER BY relevance DESC LIMIT $limit",
+            vec_str, candidates
         );
 
         let mut response = state.db.client
             .query(&search_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("scope_id", request.scope_id.clone()))
             .bind(("limit", request.limit as i32))
             .await
@@ -779,21 +1134,25 @@ pub async fn block_search(
         values.into_iter().filter_map(|v| {
             Some(BlockMatch {
                 block_id: v.get("block_id")?.as_str()?.to_string(),
+                sequence: v.get("sequence").and_then(|s| s.as_u64()).unwrap_or(0) as usize,
+                status: v.get("status").and_then(|s| s.as_str()).unwrap_or("closed").to_string(),
                 summary: v.get("summary").and_then(|s| s.as_str()).unwrap_or("").to_string(),
                 relevance: v.get("relevance").and_then(|r| r.as_f64()).unwrap_or(0.0),
                 created_at: v.get("created_at").and_then(|c| c.as_str()).unwrap_or("").to_string(),
+                items: request.include_content.then(|| v.get("items").and_then(|i| i.as_array()).cloned().unwrap_or_default()),
             })
         }).collect()
     } else {
         // Fallback: text search (or wildcard)
         let search_query = if request.query == "*" {
-            "SELECT <string>id AS block_id, summary, 0.5 AS relevance, <string>created_at AS created_at FROM cache_block WHERE scope_id = $scope_id AND status = 'closed' ORDER BY created_at DESC LIMIT $limit"
+            "SELECT <string>id AS block_id, sequence, status, summary, 0.5 AS relevance, <string>created_at AS created_at, items FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'closed' ORDER BY created_at DESC LIMIT $limit"
         } else {
-            "SELECT <string>id AS block_id, summary, 0.5 AS relevance, <string>created_at AS created_at FROM cache_block WHERE scope_id = $scope_id AND status = 'closed' AND summary CONTAINS $query ORDER BY created_at DESC LIMIT $limit"
+            "SELECT <string>id AS block_id, sequence, status, summary, 0.5 AS relevance, <string>created_at AS created_at, items FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'closed' AND summary CONTAINS $query ORDER BY created_at DESC LIMIT $limit"
         };
 
         let mut response = state.db.client
             .query(search_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("scope_id", request.scope_id.clone()))
             .bind(("query", request.query.clone()))
             .bind(("limit", request.limit as i32))
@@ -804,9 +1163,12 @@ pub async fn block_search(
         values.into_iter().filter_map(|v| {
             Some(BlockMatch {
                 block_id: v.get("block_id")?.as_str()?.to_string(),
+                sequence: v.get("sequence").and_then(|s| s.as_u64()).unwrap_or(0) as usize,
+                status: v.get("status").and_then(|s| s.as_str()).unwrap_or("closed").to_string(),
                 summary: v.get("summary").and_then(|s| s.as_str()).unwrap_or("").to_string(),
                 relevance: v.get("relevance").and_then(|r| r.as_f64()).unwrap_or(0.5),
                 created_at: v.get("created_at").and_then(|c| c.as_str()).unwrap_or("").to_string(),
+                items: request.include_content.then(|| v.get("items").and_then(|i| i.as_array()).cloned().unwrap_or_default()),
             })
         }).collect()
     };
@@ -820,6 +1182,8 @@ pub async fn block_search(
 #[derive(Debug, Serialize)]
 pub struct BlockGetResponse {
     pub block_id: String,
+    /// Position in the scope's rolling window; higher is newer
+    pub sequence: usize,
     pub status: String,
     pub summary: Option<String>,
     pub items: Vec<Value>,
@@ -830,23 +1194,28 @@ pub struct BlockGetResponse {
 /// Get the current open block for a scope
 pub async fn block_current(
     State(state): State<AppState>,
+    tenant: Tenant,
     axum::extract::Path(scope_id): axum::extract::Path<String>,
 ) -> Result<Json<BlockGetResponse>, (StatusCode, String)> {
-    get_or_create_open_block(&state, &scope_id).await.map(Json)
+    get_or_create_open_block(&state, &tenant, &scope_id)
+        .await
+        .map(Json)
 }
 
 /// Get a specific cache block by ID
 pub async fn block_get(
     State(state): State<AppState>,
+    tenant: Tenant,
     axum::extract::Path(block_id): axum::extract::Path<String>,
 ) -> Result<Json<BlockGetResponse>, (StatusCode, String)> {
     // Escape the block ID for SurrealDB
     let escaped_id = escape_block_id(&block_id);
 
-    let query = format!("SELECT <string>id AS id_str, status, summary, items, token_count, <string>created_at AS created_at FROM {}", escaped_id);
+    let query = format!("SELECT <string>id AS id_str, sequence, status, summary, items, token_count, <string>created_at AS created_at FROM {} WHERE tenant_id = $tenant_id", escaped_id);
 
     let mut response = state.db.client
         .query(&query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -855,6 +1224,7 @@ pub async fn block_get(
     if let Some(block) = values.first() {
         Ok(Json(BlockGetResponse {
             block_id: block.get("id_str").and_then(|v| v.as_str()).unwrap_or(&block_id).to_string(),
+            sequence: block.get("sequence").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
             status: block.get("status").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
             summary: block.get("summary").and_then(|v| v.as_str()).map(|s| s.to_string()),
             items: block.get("items").and_then(|v| v.as_array()).cloned().unwrap_or_default(),
@@ -866,14 +1236,19 @@ pub async fn block_get(
     }
 }
 
-async fn get_block_by_id(state: &AppState, block_id: &str) -> Result<BlockGetResponse, (StatusCode, String)> {
+async fn get_block_by_id(
+    state: &AppState,
+    tenant: &Tenant,
+    block_id: &str,
+) -> Result<BlockGetResponse, (StatusCode, String)> {
     // Escape the block ID for SurrealDB
     let escaped_id = escape_block_id(block_id);
 
-    let query = format!("SELECT <string>id AS id_str, status, summary, items, token_count, <string>created_at AS created_at FROM {}", escaped_id);
+    let query = format!("SELECT <string>id AS id_str, sequence, status, summary, items, token_count, <string>created_at AS created_at FROM {} WHERE tenant_id = $tenant_id", escaped_id);
 
     let mut response = state.db.client
         .query(&query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -882,6 +1257,7 @@ async fn get_block_by_id(state: &AppState, block_id: &str) -> Result<BlockGetRes
     if let Some(block) = values.first() {
         Ok(BlockGetResponse {
             block_id: block.get("id_str").and_then(|v| v.as_str()).unwrap_or(block_id).to_string(),
+            sequence: block.get("sequence").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
             status: block.get("status").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
             summary: block.get("summary").and_then(|v| v.as_str()).map(|s| s.to_string()),
             items: block.get("items").and_then(|v| v.as_array()).cloned().unwrap_or_default(),
@@ -895,12 +1271,14 @@ async fn get_block_by_id(state: &AppState, block_id: &str) -> Result<BlockGetRes
 
 async fn get_or_create_open_block(
     state: &AppState,
+    tenant: &Tenant,
     scope_id: &str,
 ) -> Result<BlockGetResponse, (StatusCode, String)> {
-    let query = "SELECT <string>id AS id_str, status, summary, items, token_count, <string>created_at AS created_at FROM cache_block WHERE scope_id = $scope_id AND status = 'open' LIMIT 1";
+    let query = "SELECT <string>id AS id_str, sequence, status, summary, items, token_count, <string>created_at AS created_at FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'open' LIMIT 1";
 
     let mut response = state.db.client
         .query(query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .bind(("scope_id", scope_id.to_string()))
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -910,6 +1288,7 @@ async fn get_or_create_open_block(
     if let Some(block) = values.first() {
         Ok(BlockGetResponse {
             block_id: block.get("id_str").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            sequence: block.get("sequence").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
             status: block.get("status").and_then(|v| v.as_str()).unwrap_or("open").to_string(),
             summary: block.get("summary").and_then(|v| v.as_str()).map(|s| s.to_string()),
             items: block.get("items").and_then(|v| v.as_array()).cloned().unwrap_or_default(),
@@ -918,9 +1297,10 @@ async fn get_or_create_open_block(
         })
     } else {
         // No open block exists - create a new empty block and return it
-        let seq_query = "SELECT sequence FROM cache_block WHERE scope_id = $scope_id ORDER BY sequence DESC LIMIT 1";
+        let seq_query = "SELECT sequence FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id ORDER BY sequence DESC LIMIT 1";
         let mut seq_response = state.db.client
             .query(seq_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("scope_id", scope_id.to_string()))
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -937,12 +1317,13 @@ async fn get_or_create_open_block(
         let new_id = format!("cache_block:`{}`", uuid);
         let created_at = chrono::Utc::now().to_rfc3339();
         let create_query = format!(
-            "CREATE {} SET scope_id = $scope_id, sequence = $seq, status = 'open', items = [], token_count = 0, created_at = time::now()",
+            "CREATE {} SET tenant_id = $tenant_id, scope_id = $scope_id, sequence = $seq, status = 'open', items = [], token_count = 0, created_at = time::now()",
             new_id
         );
 
         state.db.client
             .query(&create_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("scope_id", scope_id.to_string()))
             .bind(("seq", new_seq as i32))
             .await
@@ -950,6 +1331,7 @@ async fn get_or_create_open_block(
 
         Ok(BlockGetResponse {
             block_id: new_id,
+            sequence: new_seq,
             status: "open".to_string(),
             summary: None,
             items: Vec::new(),
@@ -959,6 +1341,34 @@ async fn get_or_create_open_block(
     }
 }
 
+/// Close a scope's open block without opening a new one; returns the closed block's id
+pub(crate) async fn close_open_block(
+    state: &AppState,
+    tenant: &Tenant,
+    scope_id: &str,
+) -> Result<Option<String>, String> {
+    let find_query = "SELECT <string>id AS id_str FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'open' LIMIT 1";
+    let mut response = state
+        .db
+        .client
+        .query(find_query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
+        .bind(("scope_id", scope_id.to_string()))
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(block_id) = take_json_values(&mut response, 0)
+        .first()
+        .and_then(|block| block.get("id_str"))
+        .and_then(|v| v.as_str())
+        .map(String::from)
+    else {
+        return Ok(None);
+    };
+
+    close_block(state, &block_id, scope_id).await?;
+    Ok(Some(block_id))
+}
+
 /// Close a block and generate summary
 async fn close_block(state: &AppState, block_id: &str, _scope_id: &str) -> Result<(), String> {
     // Escape the block ID for SurrealDB
@@ -978,26 +1388,17 @@ async fn close_block(state: &AppState, block_id: &str, _scope_id: &str) -> Resul
         .cloned()
         .unwrap_or_default();
 
-    // Generate summary from items (combine content, max ~200 tokens)
-    let mut summary_parts: Vec<String> = Vec::new();
-    let mut summary_tokens = 0;
-    for item in &items {
-        if let Some(content) = item.get("content").and_then(|c| c.as_str()) {
-            let kind = item.get("kind").and_then(|k| k.as_str()).unwrap_or("item");
-            let part = format!("[{}] {}", kind, content);
-            let part_tokens = part.len() / 4;
-            if summary_tokens + part_tokens > 200 {
-                break;
-            }
-            summary_parts.push(part);
-            summary_tokens += part_tokens;
-        }
-    }
-    let summary = summary_parts.join("; ");
+    let summary = generate_block_summary(state, &items).await;
 
     // Generate embedding for summary
     let summary_embedding = if state.embedding_service.is_enabled() && !summary.is_empty() {
-        state.embedding_service.generate_embedding(&summary).await.ok()
+        match state.embedding_service.generate_embedding(&summary).await {
+            Ok(embedding) => Some(embedding),
+            Err(e) => {
+                tracing::warn!("Failed to embed summary for block {}: {}", block_id, e);
+                None
+            }
+        }
     } else {
         None
     };
@@ -1016,19 +1417,92 @@ async fn close_block(state: &AppState, block_id: &str, _scope_id: &str) -> Resul
         .query(&update_query)
         .bind(("summary", summary))
         .await
+        .map_err(|e| e.to_string())?
+        .check()
         .map_err(|e| e.to_string())?;
 
     Ok(())
 }
 
+/// Summarize a closing block with the configured index model, falling back to
+/// the concatenated item text when no provider is configured or the call fails.
+async fn generate_block_summary(state: &AppState, items: &[Value]) -> String {
+    match llm_block_summary(state, items).await {
+        Some(summary) => summary,
+        None => concatenated_block_summary(items),
+    }
+}
+
+/// Combine item content into a summary of at most ~200 tokens
+fn concatenated_block_summary(items: &[Value]) -> String {
+    let mut summary_parts: Vec<String> = Vec::new();
+    let mut summary_tokens = 0;
+    for item in items {
+        if let Some(content) = item.get("content").and_then(|c| c.as_str()) {
+            let kind = item.get("kind").and_then(|k| k.as_str()).unwrap_or("item");
+            let part = format!("[{}] {}", kind, content);
+            let part_tokens = token_estimate(&part, kind);
+            if summary_tokens + part_tokens > 200 {
+                break;
+            }
+            summary_parts.push(part);
+            summary_tokens += part_tokens;
+        }
+    }
+    summary_parts.join("; ")
+}
+
+/// Returns None when disabled or on any failure so closing falls back to concatenation.
+async fn llm_block_summary(state: &AppState, items: &[Value]) -> Option<String> {
+    let settings = state.settings_service.load_settings().await.ok()?;
+    if !settings.cache_llm_summaries || settings.index_provider == "none" {
+        return None;
+    }
+
+    let parts: Vec<String> = items
+        .iter()
+        .filter_map(|item| {
+            let content = item.get("content").and_then(|c| c.as_str())?;
+            let kind = item.get("kind").and_then(|k| k.as_str()).unwrap_or("item");
+            Some(format!("[{}] {}", kind, content))
+        })
+        .collect();
+    if parts.is_empty() {
+        return None;
+    }
+
+    let service = IndexLlmService::new();
+    match tokio::time::timeout(
+        tokio::time::Duration::from_secs(LLM_SUMMARY_TIMEOUT_SECS),
+        service.summarize_cache_block(&settings, &parts),
+    )
+    .await
+    {
+        Ok(Ok(summary)) => Some(summary),
+        Ok(Err(e)) => {
+            tracing::warn!("LLM block summary failed, using concatenation: {}", e);
+            None
+        }
+        Err(_) => {
+            tracing::warn!("LLM block summary timed out, using concatenation");
+            None
+        }
+    }
+}
+
 /// Evict oldest block if we have more than MAX_BLOCKS
-async fn evict_oldest_if_needed(state: &AppState, scope_id: &str) -> Result<Option<String>, String> {
+async fn evict_oldest_if_needed(
+    state: &AppState,
+    tenant: &Tenant,
+    scope_id: &str,
+) -> Result<Option<String>, String> {
     let scope_id_owned = scope_id.to_string();
 
     // Count blocks for this scope
-    let count_query = "SELECT VALUE count() FROM cache_block WHERE scope_id = $scope_id";
+    let count_query = "SELECT VALUE count() FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id";
     let mut response = state.db.client
         .query(count_query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .bind(("scope_id", scope_id_owned.clone()))
         .await
         .map_err(|e| e.to_string())?;
@@ -1038,9 +1512,10 @@ async fn evict_oldest_if_needed(state: &AppState, scope_id: &str) -> Result<Opti
 
     if count > MAX_BLOCKS {
         // Find and delete oldest block
-        let find_query = "SELECT <string>id AS id_str FROM cache_block WHERE scope_id = $scope_id ORDER BY sequence ASC LIMIT 1";
+        let find_query = "SELECT <string>id AS id_str FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id ORDER BY sequence ASC LIMIT 1";
         let mut response = state.db.client
             .query(find_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("scope_id", scope_id_owned))
             .await
             .map_err(|e| e.to_string())?;
@@ -1061,3 +1536,742 @@ async fn evict_oldest_if_needed(state: &AppState, scope_id: &str) -> Result<Opti
 
     Ok(None)
 }
+
+#[derive(Debug, Deserialize)]
+pub struct BlockGcRequest {
+    /// Limit the sweep to one scope; all scopes when absent
+    #[serde(default)]
+    pub scope_id: Option<String>,
+    /// TTL for blocks written without their own `ttl_seconds`; such blocks never expire when absent
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+    /// Closed blocks per scope that are never deleted, however old
+    #[serde(default)]
+    pub keep_newest: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockGcResponse {
+    pub deleted: usize,
+    pub deleted_block_ids: Vec<String>,
+}
+
+/// Delete expired closed blocks, always keeping the newest `keep_newest` per scope
+pub async fn block_gc(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Json(request): Json<BlockGcRequest>,
+) -> Result<Json<BlockGcResponse>, (StatusCode, String)> {
+    let scope_clause = if request.scope_id.is_some() {
+        " AND scope_id = $scope_id"
+    } else {
+        ""
+    };
+    let query = format!(
+        "SELECT <string>id AS id_str, scope_id, sequence, ttl_seconds, <string>created_at AS created_at FROM cache_block WHERE tenant_id = $tenant_id AND status = 'closed'{}",
+        scope_clause
+    );
+
+    let mut response = state.db.client
+        .query(&query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
+        .bind(("scope_id", request.scope_id.clone()))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut by_scope: HashMap<String, Vec<Value>> = HashMap::new();
+    for block in take_json_values(&mut response, 0) {
+        let scope = block
+            .get("scope_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        by_scope.entry(scope).or_default().push(block);
+    }
+
+    let keep_newest = request.keep_newest.unwrap_or(GC_KEEP_NEWEST);
+    let now = chrono::Utc::now();
+    let mut deleted_block_ids = Vec::new();
+    for blocks in by_scope.values_mut() {
+        blocks.sort_by_key(|b| {
+            std::cmp::Reverse(b.get("sequence").and_then(|v| v.as_u64()).unwrap_or(0))
+        });
+        for block in blocks.iter().skip(keep_newest) {
+            let ttl = block
+                .get("ttl_seconds")
+                .and_then(|v| v.as_u64())
+                .or(request.ttl_seconds);
+            let created_at = block
+                .get("created_at")
+                .and_then(|v| v.as_str())
+                .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok());
+            let (Some(ttl), Some(created_at)) = (ttl, created_at) else {
+                continue;
+            };
+            if now.signed_duration_since(created_at).num_seconds() < ttl as i64 {
+                continue;
+            }
+            let Some(block_id) = block.get("id_str").and_then(|v| v.as_str()) else {
+                continue;
+            };
+
+            let delete_query = format!("DELETE {}", escape_block_id(block_id));
+            match state.db.client.query(&delete_query).await {
+                Ok(_) => deleted_block_ids.push(block_id.to_string()),
+                Err(e) => tracing::warn!("Failed to delete expired block {}: {}", block_id, e),
+            }
+        }
+    }
+
+    Ok(Json(BlockGcResponse {
+        deleted: deleted_block_ids.len(),
+        deleted_block_ids,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::tenant::DEFAULT_TENANT;
+    use crate::test_support::{memory_state, test_config};
+
+    async fn items_in_open_block(state: &AppState, scope_id: &str) -> usize {
+        let mut response = state
+            .db
+            .client
+            .query("SELECT items FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'open'")
+            .bind(("tenant_id", DEFAULT_TENANT.to_string()))
+            .bind(("scope_id", scope_id.to_string()))
+            .await
+            .unwrap();
+        take_json_values(&mut response, 0)
+            .first()
+            .and_then(|block| block.get("items"))
+            .and_then(|items| items.as_array())
+            .map(|items| items.len())
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_fan_out_scopes_dedupes_runs() {
+        let run_ids = vec![
+            "run-a".to_string(),
+            "objects:run-a".to_string(),
+            " ".to_string(),
+            "run-b".to_string(),
+        ];
+        assert_eq!(
+            fan_out_scopes(&run_ids),
+            vec!["run:run-a", "session:run-a", "run:run-b", "session:run-b"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_block_write_fans_out_to_active_runs() {
+        let config = Config {
+            cache_fanout_concurrency: 2,
+            ..test_config()
+        };
+        let state = memory_state(config).await;
+
+        let run_ids = ["run-a", "run-b", "run-c", "run-d"];
+        for (idx, run_id) in run_ids.iter().enumerate() {
+            state
+                .db
+                .client
+                .query(
+                    "CREATE agent_connections SET connection_id = $conn, agent_id = $conn, agent_name = 'test', \
+                     run_id = $run_id, project_id = 'amp', status = 'connected', expires_at = time::now() + 10m",
+                )
+                .bind(("conn", format!("conn-{}", idx)))
+                .bind(("run_id", run_id.to_string()))
+                .await
+                .unwrap();
+        }
+
+        let request = BlockWriteRequest {
+            scope_id: "project:amp".to_string(),
+            kind: "fact".to_string(),
+            content: "Fan-out writes run concurrently".to_string(),
+            importance: 0.5,
+            file_ref: None,
+            ttl_seconds: None,
+            items: Vec::new(),
+        };
+        let Json(primary) = block_write(State(state.clone()), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+
+        assert_eq!(primary.items_in_block, 1);
+        assert_eq!(items_in_open_block(&state, "project:amp").await, 1);
+        for run_id in run_ids {
+            assert_eq!(items_in_open_block(&state, &format!("run:{}", run_id)).await, 1);
+            assert_eq!(items_in_open_block(&state, &format!("session:{}", run_id)).await, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_write_batch_rolls_block_mid_batch() {
+        let state = memory_state(test_config()).await;
+        let item = |content: String| BlockWriteItem {
+            kind: "fact".to_string(),
+            content,
+            importance: 0.5,
+            file_ref: None,
+        };
+        // 750 tokens each, so the third item crosses the 1800 token threshold
+        let request = BlockWriteRequest {
+            scope_id: "run:batch".to_string(),
+            kind: String::new(),
+            content: String::new(),
+            importance: 0.5,
+            file_ref: None,
+            ttl_seconds: None,
+            items: vec![
+                item("a".repeat(3000)),
+                item("b".repeat(3000)),
+                item("c".repeat(3000)),
+                item("short follow-up".to_string()),
+            ],
+        };
+        let Json(response) = block_write(State(state.clone()), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+
+        assert!(response.compacted);
+        assert_eq!(response.items.len(), 4);
+        let first_block = response.items[0].block_id.clone();
+        assert_eq!(response.items[1].block_id, first_block);
+        assert_eq!(response.items[1].closed_block_id, None);
+        assert_eq!(response.items[2].closed_block_id, Some(first_block.clone()));
+        assert_eq!(response.items[2].block_id, response.block_id);
+        assert_eq!(response.items[3].block_id, response.block_id);
+        assert_eq!(response.new_block_id.as_ref(), Some(&response.block_id));
+        assert_eq!(response.items_in_block, 2);
+        assert_eq!(items_in_open_block(&state, "run:batch").await, 2);
+
+        let mut closed = state
+            .db
+            .client
+            .query(format!("SELECT status, items FROM {}", escape_block_id(&first_block)))
+            .await
+            .unwrap();
+        let closed = take_json_values(&mut closed, 0);
+        assert_eq!(closed[0]["status"], "closed");
+        assert_eq!(closed[0]["items"].as_array().map(Vec::len), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_block_write_requires_kind_without_items() {
+        let state = memory_state(test_config()).await;
+        let request: BlockWriteRequest =
+            serde_json::from_value(serde_json::json!({ "scope_id": "run:empty" })).unwrap();
+        let (status, _) = block_write(State(state), Tenant::default(), Json(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_block_write_drops_low_value_items_when_filter_enabled() {
+        let item = |content: &str| BlockWriteItem {
+            kind: "fact".to_string(),
+            content: content.to_string(),
+            importance: 0.5,
+            file_ref: None,
+        };
+        let request = |scope_id: &str| BlockWriteRequest {
+            scope_id: scope_id.to_string(),
+            kind: String::new(),
+            content: String::new(),
+            importance: 0.5,
+            file_ref: None,
+            ttl_seconds: None,
+            items: vec![item("Done."), item("Parser now streams tokens")],
+        };
+        let state = memory_state(test_config()).await;
+
+        let Json(unfiltered) = block_write(
+            State(state.clone()),
+            Tenant::default(),
+            Json(request("run:off")),
+        )
+        .await
+        .unwrap();
+        assert!(unfiltered.filtered.is_empty());
+        assert_eq!(items_in_open_block(&state, "run:off").await, 2);
+
+        let settings = crate::models::settings::SettingsConfig {
+            cache_filter_low_value: true,
+            ..Default::default()
+        };
+        state
+            .settings_service
+            .save_settings(settings)
+            .await
+            .unwrap();
+
+        let Json(filtered) = block_write(
+            State(state.clone()),
+            Tenant::default(),
+            Json(request("run:on")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(filtered.filtered, vec!["Done."]);
+        assert_eq!(filtered.items.len(), 1);
+        assert_eq!(items_in_open_block(&state, "run:on").await, 1);
+
+        // A lone trivial item writes nothing
+        let single: BlockWriteRequest = serde_json::from_value(serde_json::json!({
+            "scope_id": "run:on",
+            "kind": "fact",
+            "content": "ok",
+        }))
+        .unwrap();
+        let Json(skipped) = block_write(State(state.clone()), Tenant::default(), Json(single))
+            .await
+            .unwrap();
+        assert_eq!(skipped.block_status, "filtered");
+        assert_eq!(skipped.filtered, vec!["ok"]);
+        assert_eq!(items_in_open_block(&state, "run:on").await, 1);
+    }
+
+    /// Ollama-compatible chat mock; `ok == false` answers 500. Returns the request count.
+    async fn spawn_chat_mock(ok: bool) -> (String, Arc<std::sync::Mutex<usize>>) {
+        use axum::routing::post;
+
+        let calls = Arc::new(std::sync::Mutex::new(0usize));
+        let app = axum::Router::new()
+            .route(
+                "/api/chat",
+                post(move |State(calls): State<Arc<std::sync::Mutex<usize>>>| async move {
+                    *calls.lock().unwrap() += 1;
+                    if ok {
+                        (
+                            StatusCode::OK,
+                            Json(serde_json::json!({
+                                "message": { "content": " Refactored the parser to stream tokens. " }
+                            })),
+                        )
+                    } else {
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(serde_json::json!({ "error": "model unavailable" })),
+                        )
+                    }
+                }),
+            )
+            .with_state(calls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        (format!("http://{}", addr), calls)
+    }
+
+    async fn close_block_with_settings(
+        ollama_url: String,
+        cache_llm_summaries: bool,
+    ) -> Option<String> {
+        let state = memory_state(test_config()).await;
+        let block_id = close_test_block(&state, ollama_url, cache_llm_summaries).await;
+        get_block_by_id(&state, &Tenant::default(), &block_id).await.unwrap().summary
+    }
+
+    /// Write one item to a fresh block and close it; returns the closed block id.
+    async fn close_test_block(
+        state: &AppState,
+        ollama_url: String,
+        cache_llm_summaries: bool,
+    ) -> String {
+        let settings = crate::models::settings::SettingsConfig {
+            index_provider: "ollama".to_string(),
+            ollama_url,
+            cache_llm_summaries,
+            ..Default::default()
+        };
+        state.settings_service.save_settings(settings).await.unwrap();
+
+        let request = BlockWriteRequest {
+            scope_id: "project:summary".to_string(),
+            kind: "decision".to_string(),
+            content: "Stream tokens from the parser".to_string(),
+            importance: 0.5,
+            file_ref: None,
+            ttl_seconds: None,
+            items: Vec::new(),
+        };
+        let written = write_block_for_scope(state, &Tenant::default(), &request.scope_id, &request)
+            .await
+            .unwrap();
+        close_block(state, &written.block_id, &request.scope_id)
+            .await
+            .unwrap();
+
+        written.block_id
+    }
+
+    #[tokio::test]
+    async fn test_closed_block_gets_llm_summary_and_embedding() {
+        let (url, _calls) = spawn_chat_mock(true).await;
+        let state = crate::test_support::memory_state_with_embedding(
+            test_config(),
+            Box::new(crate::test_support::CountingEmbedding::default()),
+        )
+        .await;
+        let block_id = close_test_block(&state, url, true).await;
+
+        let query = format!(
+            "SELECT summary, summary_embedding, status FROM {}",
+            escape_block_id(&block_id)
+        );
+        let mut response = state.db.client.query(&query).await.unwrap();
+        let block = take_json_values(&mut response, 0).remove(0);
+
+        let summary = "Refactored the parser to stream tokens.";
+        assert_eq!(block["status"], "closed");
+        assert_eq!(block["summary"], summary);
+        assert_eq!(
+            block["summary_embedding"],
+            serde_json::json!([summary.len() as f64, 1.0])
+        );
+
+        // Closed blocks with an embedding are what block_search ranks
+        let Json(results) = block_search(
+            State(state.clone()),
+            Tenant::default(),
+            Json(BlockSearchRequest {
+                scope_id: "project:summary".to_string(),
+                query: summary.to_string(),
+                limit: 5,
+                include_open: false,
+                include_content: false,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.matches.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_close_block_uses_llm_summary() {
+        let (url, calls) = spawn_chat_mock(true).await;
+        let summary = close_block_with_settings(url, true).await;
+        assert_eq!(
+            summary.as_deref(),
+            Some("Refactored the parser to stream tokens.")
+        );
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_close_block_falls_back_to_concatenation() {
+        let (url, calls) = spawn_chat_mock(false).await;
+        let summary = close_block_with_settings(url, true).await;
+        assert_eq!(
+            summary.as_deref(),
+            Some("[decision] Stream tokens from the parser")
+        );
+        assert_eq!(*calls.lock().unwrap(), 1);
+
+        let (url, calls) = spawn_chat_mock(true).await;
+        let summary = close_block_with_settings(url, false).await;
+        assert_eq!(
+            summary.as_deref(),
+            Some("[decision] Stream tokens from the parser")
+        );
+        assert_eq!(*calls.lock().unwrap(), 0);
+    }
+
+    async fn insert_block(
+        state: &AppState,
+        scope_id: &str,
+        sequence: i64,
+        status: &str,
+        age_secs: i64,
+        ttl_seconds: Option<i64>,
+    ) -> String {
+        let id = format!("cache_block:`{}`", uuid::Uuid::new_v4());
+        state
+            .db
+            .client
+            .query(format!(
+                "CREATE {} SET tenant_id = 'default', scope_id = $scope_id, sequence = $seq, status = $status, \
+                 items = [], token_count = 0, ttl_seconds = $ttl, created_at = time::now() - duration::from::secs($age)",
+                id
+            ))
+            .bind(("scope_id", scope_id.to_string()))
+            .bind(("seq", sequence))
+            .bind(("status", status.to_string()))
+            .bind(("ttl", ttl_seconds))
+            .bind(("age", age_secs))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        id
+    }
+
+    async fn remaining_sequences(state: &AppState, scope_id: &str) -> Vec<i64> {
+        let mut response = state
+            .db
+            .client
+            .query("SELECT VALUE sequence FROM cache_block WHERE scope_id = $scope_id ORDER BY sequence")
+            .bind(("scope_id", scope_id.to_string()))
+            .await
+            .unwrap();
+        take_json_values(&mut response, 0)
+            .iter()
+            .filter_map(|v| v.as_i64())
+            .collect()
+    }
+
+    async fn gc(state: &AppState, request: BlockGcRequest) -> BlockGcResponse {
+        let Json(response) = block_gc(State(state.clone()), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_get_pack_appends_recent_block_summaries_within_budget() {
+        let state = memory_state(test_config()).await;
+        for sequence in 1..=4 {
+            let id = insert_block(&state, "run:pack", sequence, "closed", 60, None).await;
+            state
+                .db
+                .client
+                .query(format!("UPDATE {} SET summary = $summary", id))
+                .bind((
+                    "summary",
+                    format!("Block {} summary: parser streams tokens", sequence),
+                ))
+                .await
+                .unwrap()
+                .check()
+                .unwrap();
+        }
+        insert_block(&state, "run:pack", 5, "open", 0, None).await;
+
+        let request = |include_recent_blocks: usize, token_budget: usize| GetPackRequest {
+            scope_id: "run:pack".to_string(),
+            token_budget,
+            query: None,
+            since_version: None,
+            include_recent_blocks,
+        };
+        let Json(pack) = get_pack(
+            State(state.clone()),
+            Tenant::default(),
+            Json(request(3, 600)),
+        )
+        .await
+        .unwrap();
+        let sequences: Vec<u64> = pack.recent_blocks.iter().map(|b| b.sequence).collect();
+        assert_eq!(sequences, vec![4, 3, 2]);
+        assert!(pack.recent_blocks[0].summary.starts_with("Block 4 summary"));
+        assert!(pack.token_count <= 600);
+
+        // Each summary is ~10 tokens; only two fit in a 25-token budget
+        let Json(pack) = get_pack(
+            State(state.clone()),
+            Tenant::default(),
+            Json(request(3, 25)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(pack.recent_blocks.len(), 2);
+        assert!(pack.token_count <= 25, "{}", pack.token_count);
+
+        let Json(pack) = get_pack(State(state), Tenant::default(), Json(request(0, 600)))
+            .await
+            .unwrap();
+        assert!(pack.recent_blocks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_block_gc_removes_only_expired_closed_blocks() {
+        let state = memory_state(test_config()).await;
+        let hour = 3600;
+        insert_block(&state, "run:gc", 1, "closed", 3 * hour, None).await;
+        insert_block(&state, "run:gc", 2, "closed", 3 * hour, None).await;
+        // Own TTL outlives its age even though the sweep TTL would expire it
+        insert_block(&state, "run:gc", 3, "closed", 3 * hour, Some(4 * hour)).await;
+        insert_block(&state, "run:gc", 4, "closed", 60, None).await;
+        insert_block(&state, "run:gc", 5, "closed", 3 * hour, None).await;
+        insert_block(&state, "run:gc", 6, "open", 3 * hour, None).await;
+        insert_block(&state, "run:other", 1, "closed", 3 * hour, None).await;
+
+        let response = gc(
+            &state,
+            BlockGcRequest {
+                scope_id: Some("run:gc".to_string()),
+                ttl_seconds: Some(hour as u64),
+                keep_newest: Some(1),
+            },
+        )
+        .await;
+
+        assert_eq!(response.deleted, 2);
+        assert_eq!(
+            remaining_sequences(&state, "run:gc").await,
+            vec![3, 4, 5, 6]
+        );
+        assert_eq!(remaining_sequences(&state, "run:other").await, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_block_gc_uses_ttl_from_block_write() {
+        let state = memory_state(test_config()).await;
+        for sequence in 1..=3 {
+            insert_block(&state, "run:ttl", sequence, "closed", 120, None).await;
+        }
+        let request = BlockWriteRequest {
+            scope_id: "run:ttl".to_string(),
+            kind: "fact".to_string(),
+            content: "Short-lived context".to_string(),
+            importance: 0.5,
+            file_ref: None,
+            ttl_seconds: Some(60),
+            items: Vec::new(),
+        };
+        let written = write_block_for_scope(&state, &Tenant::default(), "run:ttl", &request)
+            .await
+            .unwrap();
+
+        // Blocks without a TTL never expire when the sweep gives none
+        let request = || BlockGcRequest {
+            scope_id: None,
+            ttl_seconds: None,
+            keep_newest: Some(0),
+        };
+        assert_eq!(gc(&state, request()).await.deleted, 0);
+
+        state
+            .db
+            .client
+            .query(format!(
+                "UPDATE {} SET status = 'closed', created_at = time::now() - 2m",
+                escape_block_id(&written.block_id)
+            ))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let response = gc(&state, request()).await;
+        assert_eq!(response.deleted_block_ids, vec![written.block_id]);
+        assert_eq!(remaining_sequences(&state, "run:ttl").await, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_block_read_reports_sequence_and_status() {
+        let state = memory_state(test_config()).await;
+        insert_block(&state, "run:window", 1, "closed", 300, None).await;
+        let closed = insert_block(&state, "run:window", 2, "closed", 200, None).await;
+        insert_block(&state, "run:window", 3, "open", 100, None).await;
+
+        let request: BlockReadRequest = serde_json::from_value(serde_json::json!({
+            "scope_id": "run:window",
+            "list_all": true,
+            "include_content": true
+        }))
+        .unwrap();
+        let Json(response) = block_read_impl(&state, &Tenant::default(), request)
+            .await
+            .unwrap();
+
+        let matches: Vec<(usize, &str)> = response
+            .matches
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|m| (m.sequence, m.status.as_str()))
+            .collect();
+        assert_eq!(matches, vec![(3, "open"), (2, "closed"), (1, "closed")]);
+        let blocks: Vec<(usize, &str)> = response
+            .blocks
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|b| (b.sequence, b.status.as_str()))
+            .collect();
+        assert_eq!(blocks, matches);
+
+        let request: BlockReadRequest = serde_json::from_value(serde_json::json!({
+            "scope_id": "run:window",
+            "block_id": closed
+        }))
+        .unwrap();
+        let Json(response) = block_read_impl(&state, &Tenant::default(), request)
+            .await
+            .unwrap();
+        let block = response.block.unwrap();
+        assert_eq!((block.sequence, block.status.as_str()), (2, "closed"));
+
+        let current = get_or_create_open_block(&state, &Tenant::default(), "run:window")
+            .await
+            .unwrap();
+        assert_eq!((current.sequence, current.status.as_str()), (3, "open"));
+        let fresh = get_or_create_open_block(&state, &Tenant::default(), "run:empty")
+            .await
+            .unwrap();
+        assert_eq!((fresh.sequence, fresh.status.as_str()), (1, "open"));
+    }
+
+    #[tokio::test]
+    async fn test_block_search_inlines_items_on_request() {
+        let state = memory_state(test_config()).await;
+        let closed = insert_block(&state, "run:inline", 1, "closed", 200, None).await;
+        let open = insert_block(&state, "run:inline", 2, "open", 100, None).await;
+        state
+            .db
+            .client
+            .query(format!(
+                "UPDATE {} SET items = [{{ kind: 'fact', content: 'parser streams' }}];
+                 UPDATE {} SET items = [{{ kind: 'warning', content: 'index is stale' }}];",
+                closed, open
+            ))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let search = |include_content| BlockSearchRequest {
+            scope_id: "run:inline".to_string(),
+            query: "*".to_string(),
+            limit: 5,
+            include_open: true,
+            include_content,
+        };
+
+        let Json(results) =
+            block_search(State(state.clone()), Tenant::default(), Json(search(true)))
+                .await
+                .unwrap();
+        let contents: Vec<(&str, &Value)> = results
+            .matches
+            .iter()
+            .map(|m| (m.status.as_str(), &m.items.as_ref().unwrap()[0]["content"]))
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                ("open", &Value::from("index is stale")),
+                ("closed", &Value::from("parser streams"))
+            ]
+        );
+
+        let Json(results) = block_search(State(state), Tenant::default(), Json(search(false)))
+            .await
+            .unwrap();
+        assert_eq!(results.matches.len(), 2);
+        assert!(results.matches.iter().all(|m| m.items.is_none()));
+        assert!(serde_json::to_value(&results.matches[0])
+            .unwrap()
+            .get("items")
+            .is_none());
+    }
+}
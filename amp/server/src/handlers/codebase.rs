@@ -5,13 +5,18 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::env;
 use std::path::PathBuf;
 
+use crate::services::chunking::{ChunkData, ChunkingService};
 use crate::services::codebase_parser::{CodebaseParser, FileLog};
+use crate::services::embedding::EmbeddingService;
+use crate::services::embedding_cache::embed_with_cache;
+use crate::services::git_blame::{self, SymbolBlame};
 use crate::services::index_llm::{AiFileLogInput, AiFileLogOutput, IndexLlmService};
+use crate::services::path_resolver::BasenameMatch;
 use crate::{
-    surreal_json::{normalize_object_ids, take_json_values},
+    surreal_json::{normalize_object_ids, take_json_value, take_json_values},
+    tenant::{Tenant, DEFAULT_TENANT},
     AppState,
 };
 use uuid::Uuid;
@@ -54,6 +59,8 @@ pub struct AiFileLogRequest {
     pub content: String,
     pub symbols: Option<Vec<String>>,
     pub dependencies: Option<Vec<String>>,
+    /// Use this project's settings overrides for the index model
+    pub project_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -61,6 +68,18 @@ pub struct AiFileLogResponse {
     pub file_log: AiFileLogOutput,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RegenerateAiLogRequest {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegenerateAiLogResponse {
+    pub file_id: String,
+    pub file_path: String,
+    pub file_log: AiFileLogOutput,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ParseCodebaseResponse {
     pub success: bool,
@@ -73,6 +92,9 @@ pub struct ParseCodebaseResponse {
 pub struct FileLogResponse {
     pub file_log: FileLog,
     pub markdown: String,
+    /// Chunks cut at the parsed symbols, for clients that store their own FileChunks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<ChunkData>,
 }
 
 #[derive(Debug, Serialize)]
@@ -85,6 +107,15 @@ pub struct FileContentResponse {
     pub path: String,
     pub content: String,
     pub chunks: Vec<String>,
+    /// Stable ids of the chunks, in the same order as `chunks`
+    pub chunk_ids: Vec<String>,
+    /// Lines actually returned when a line range or symbol was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -97,7 +128,7 @@ pub struct GetFileLogsQuery {
 
 /// Parse entire codebase and create file logs
 pub async fn parse_codebase(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(request): Json<ParseCodebaseRequest>,
 ) -> Result<Json<ParseCodebaseResponse>, StatusCode> {
     tracing::info!("Parsing codebase at: {}", request.root_path);
@@ -107,8 +138,10 @@ pub async fn parse_codebase(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let root_path =
-        map_windows_mount(&request.root_path).unwrap_or_else(|| PathBuf::from(&request.root_path));
+    let root_path = state
+        .path_resolver
+        .to_container(&request.root_path)
+        .unwrap_or_else(|| PathBuf::from(&request.root_path));
     if !root_path.exists() {
         tracing::error!("Path does not exist: {}", request.root_path);
         return Err(StatusCode::BAD_REQUEST);
@@ -137,7 +170,7 @@ pub async fn parse_codebase(
 
 /// Parse single file and create/update file log
 pub async fn parse_file(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(request): Json<ParseFileRequest>,
 ) -> Result<Json<FileLogResponse>, StatusCode> {
     tracing::info!("Parsing file: {}", request.file_path);
@@ -149,7 +182,7 @@ pub async fn parse_file(
 
     let mut file_path = PathBuf::from(&request.file_path);
     if !file_path.exists() {
-        if let Some(mapped) = map_windows_mount(&request.file_path) {
+        if let Some(mapped) = state.path_resolver.to_container(&request.file_path) {
             file_path = mapped;
         }
     }
@@ -169,11 +202,25 @@ pub async fn parse_file(
     })?;
 
     let markdown = parser.generate_file_log_markdown(&file_log);
+    let chunks = std::fs::read_to_string(&file_path)
+        .map(|content| {
+            ChunkingService::new().chunk_file_by_symbols(
+                &parser,
+                &content,
+                &language,
+                &file_log.symbols,
+            )
+        })
+        .unwrap_or_default();
 
     // TODO: Store file log in AMP database
     tracing::debug!("Would store file log for: {}", request.file_path);
 
-    Ok(Json(FileLogResponse { file_log, markdown }))
+    Ok(Json(FileLogResponse {
+        file_log,
+        markdown,
+        chunks,
+    }))
 }
 
 /// Update file log with new change information
@@ -254,14 +301,33 @@ pub async fn update_file_log(
     // TODO: Store updated file log
     tracing::debug!("Would store updated file log for: {}", request.file_path);
 
-    Ok(Json(FileLogResponse { file_log, markdown }))
+    Ok(Json(FileLogResponse {
+        file_log,
+        markdown,
+        chunks: Vec::new(),
+    }))
 }
 
 pub async fn generate_ai_file_log(
     State(state): State<AppState>,
+    tenant: Tenant,
     Json(request): Json<AiFileLogRequest>,
 ) -> Result<Json<AiFileLogResponse>, (StatusCode, Json<serde_json::Value>)> {
-    let settings = match state.settings_service.load_settings().await {
+    let settings = match &request.project_id {
+        Some(project_id) => {
+            state
+                .settings_service
+                .load_settings_for_project(tenant.as_str(), project_id)
+                .await
+        }
+        None => {
+            state
+                .settings_service
+                .load_settings_for_tenant(tenant.as_str())
+                .await
+        }
+    };
+    let settings = match settings {
         Ok(settings) => settings,
         Err(err) => {
             return Err((
@@ -290,6 +356,159 @@ pub async fn generate_ai_file_log(
     }
 }
 
+/// Matches the CLI's limits: longer files keep their head and tail.
+const MAX_AI_LOG_CONTENT_CHARS: usize = 20000;
+const AI_LOG_CONTENT_HEAD_CHARS: usize = 12000;
+const AI_LOG_CONTENT_TAIL_CHARS: usize = 6000;
+
+fn truncate_ai_log_content(content: &str) -> String {
+    let length = content.chars().count();
+    if length <= MAX_AI_LOG_CONTENT_CHARS {
+        return content.to_string();
+    }
+    let head: String = content.chars().take(AI_LOG_CONTENT_HEAD_CHARS).collect();
+    let tail: String = content
+        .chars()
+        .skip(length - AI_LOG_CONTENT_TAIL_CHARS)
+        .collect();
+    format!(
+        "{}\n\n... [truncated for AI log generation] ...\n\n{}",
+        head, tail
+    )
+}
+
+/// Re-read a file and replace its stored FileLog's AI summary, purpose,
+/// key symbols, dependencies and notes, without re-indexing anything else.
+pub async fn regenerate_ai_file_log(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Json(request): Json<RegenerateAiLogRequest>,
+) -> Result<Json<RegenerateAiLogResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let error = |status: StatusCode, message: String| {
+        (
+            status,
+            Json(serde_json::json!({ "error": message, "path": request.path })),
+        )
+    };
+
+    let file_path = resolve_file_path(&request.path, &state)
+        .await
+        .map_err(|status| error(status, "File not found".to_string()))?;
+    let raw_file_path = file_path.to_string_lossy().to_string();
+    let storage_path = state
+        .path_resolver
+        .to_host(&raw_file_path)
+        .unwrap_or_else(|| raw_file_path.clone());
+    let tenant_id = tenant.as_str().to_string();
+
+    let lookup = "SELECT record::id(id) AS id, file_id, file_path, project_id, updated_at FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND string::lowercase(string::replace(file_path, '/', '\\\\')) = $path ORDER BY updated_at DESC LIMIT 1";
+    let stored = state
+        .db
+        .client
+        .query(lookup)
+        .bind(("path", state.path_resolver.lookup_key(&storage_path)))
+        .bind(("tenant_id", tenant_id.clone()))
+        .await
+        .map(|mut response| take_json_values(&mut response, 0))
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let Some(stored) = stored.into_iter().next() else {
+        return Err(error(
+            StatusCode::NOT_FOUND,
+            "No FileLog stored for this file; index it first".to_string(),
+        ));
+    };
+    let field = |key: &str| stored.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let log_id = field("id").unwrap_or_default();
+    let stored_path = field("file_path").unwrap_or_else(|| storage_path.clone());
+
+    let settings = match field("project_id") {
+        Some(project_id) => {
+            state
+                .settings_service
+                .load_settings_for_project(&tenant_id, &project_id)
+                .await
+        }
+        None => {
+            state
+                .settings_service
+                .load_settings_for_tenant(&tenant_id)
+                .await
+        }
+    }
+    .map_err(|e| {
+        error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to load settings: {}", e),
+        )
+    })?;
+
+    let content = std::fs::read_to_string(&file_path).map_err(|e| {
+        error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read file: {}", e),
+        )
+    })?;
+    let language = detect_language(&file_path);
+    let parsed = CodebaseParser::new()
+        .and_then(|parser| parser.parse_file(&file_path, &language))
+        .map_err(|e| {
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to parse file: {}", e),
+            )
+        })?;
+    let content_hash = content_sha256(&content);
+    let input = AiFileLogInput {
+        path: stored_path.clone(),
+        language,
+        content_hash: content_hash.clone(),
+        content: truncate_ai_log_content(&content),
+        symbols: parsed
+            .symbols
+            .iter()
+            .map(|symbol| format!("{}:{}", symbol.symbol_type, symbol.name))
+            .collect(),
+        dependencies: parsed.dependencies.imports,
+    };
+
+    let file_log = IndexLlmService::new()
+        .generate_file_log(&settings, input)
+        .await
+        .map_err(|e| {
+            error(
+                StatusCode::BAD_REQUEST,
+                format!("AI file log generation failed: {}", e),
+            )
+        })?;
+
+    let update = "UPDATE type::thing('objects', $id) SET summary = $summary, summary_markdown = $summary, purpose = $purpose, key_symbols = $key_symbols, dependencies = $dependencies, notes = $notes, content_hash = $content_hash, updated_at = time::now()";
+    state
+        .db
+        .client
+        .query(update)
+        .bind(("id", log_id))
+        .bind(("summary", file_log.summary_markdown.clone()))
+        .bind(("purpose", file_log.purpose.clone()))
+        .bind(("key_symbols", file_log.key_symbols.clone()))
+        .bind(("dependencies", file_log.dependencies.clone()))
+        .bind(("notes", file_log.notes.clone()))
+        .bind(("content_hash", content_hash))
+        .await
+        .and_then(surrealdb::Response::check)
+        .map_err(|e| {
+            error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update FileLog: {}", e),
+            )
+        })?;
+
+    Ok(Json(RegenerateAiLogResponse {
+        file_id: field("file_id").unwrap_or_default(),
+        file_path: stored_path,
+        file_log,
+    }))
+}
+
 /// Get file logs with optional filtering
 pub async fn get_file_logs(
     State(_state): State<AppState>,
@@ -299,10 +518,49 @@ pub async fn get_file_logs(
     Ok(Json(Vec::new()))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FileHashesQuery {
+    pub project_id: Option<String>,
+}
+
+/// Path and `content_hash` of every stored FileLog, for comparing the index
+/// against a working tree. Logs written before hashes were recorded have none.
+pub async fn get_file_hashes(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Query(query): Query<FileHashesQuery>,
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let mut sql = "SELECT file_path, file_id, content_hash FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id".to_string();
+    if query.project_id.is_some() {
+        sql.push_str(" AND project_id = $project_id");
+    }
+    let mut response = state
+        .db
+        .client
+        .query(sql)
+        .bind(("tenant_id", tenant.as_str().to_string()))
+        .bind(("project_id", query.project_id))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list file hashes: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(take_json_values(&mut response, 0)))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GetFileLogQuery {
+    /// Attach per-symbol `git blame` to the FileLog
+    #[serde(default)]
+    pub include_blame: bool,
+}
+
 /// Get specific file log by path
 pub async fn get_file_log(
     State(state): State<AppState>,
+    tenant: Tenant,
     Path(file_path): Path<String>,
+    Query(query): Query<GetFileLogQuery>,
 ) -> Result<Json<FileLogResponse>, (StatusCode, Json<serde_json::Value>)> {
     tracing::info!("Getting file log for: {}", file_path);
 
@@ -331,29 +589,98 @@ pub async fn get_file_log(
 
     let language = detect_language(&resolved_path);
 
-    let file_log = parser.parse_file(&resolved_path, &language).map_err(|e| {
+    let mut file_log = parser.parse_file(&resolved_path, &language).map_err(|e| {
         tracing::error!("Failed to parse file: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({"error": "Failed to parse file", "details": e.to_string()})),
         )
     })?;
+    if query.include_blame {
+        file_log.blame = file_blame(&state, &tenant, &resolved_path, &file_log).await;
+    }
 
     let markdown = parser.generate_file_log_markdown(&file_log);
 
-    Ok(Json(FileLogResponse { file_log, markdown }))
+    Ok(Json(FileLogResponse {
+        file_log,
+        markdown,
+        chunks: Vec::new(),
+    }))
+}
+
+/// Blame for `file_log`'s symbols, reusing the result cached on the stored
+/// FileLog while its content hash still matches. Blame of uncommitted lines
+/// is not cached, since committing them changes it without touching the file.
+async fn file_blame(
+    state: &AppState,
+    tenant: &Tenant,
+    resolved_path: &std::path::Path,
+    file_log: &FileLog,
+) -> Vec<SymbolBlame> {
+    let raw_path = resolved_path.to_string_lossy().to_string();
+    let storage_path = state
+        .path_resolver
+        .to_host(&raw_path)
+        .unwrap_or_else(|| raw_path.clone());
+    let canonical_path = state.path_resolver.canonicalize(&storage_path);
+
+    let cached = state
+        .db
+        .client
+        .query("SELECT VALUE blame FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND file_path = $path AND blame_hash = $hash LIMIT 1")
+        .bind(("tenant_id", tenant.as_str().to_string()))
+        .bind(("path", canonical_path.clone()))
+        .bind(("hash", file_log.content_hash.clone()))
+        .await;
+    if let Ok(mut response) = cached {
+        if let Some(blame) = take_json_values(&mut response, 0)
+            .into_iter()
+            .next()
+            .and_then(|value| serde_json::from_value(value).ok())
+        {
+            return blame;
+        }
+    }
+
+    let path = resolved_path.to_path_buf();
+    let symbols = file_log.symbols.clone();
+    let blame = tokio::task::spawn_blocking(move || git_blame::blame_symbols(&path, &symbols))
+        .await
+        .unwrap_or_default();
+    let committed = blame
+        .iter()
+        .all(|b| b.commit_hash.bytes().any(|c| c != b'0'));
+    if committed && !blame.is_empty() {
+        let stored = state
+            .db
+            .client
+            .query("UPDATE objects SET blame = $blame, blame_hash = $hash WHERE type = 'FileLog' AND tenant_id = $tenant_id AND file_path = $path")
+            .bind(("blame", serde_json::to_value(&blame).unwrap_or_default()))
+            .bind(("hash", file_log.content_hash.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string()))
+            .bind(("path", canonical_path))
+            .await
+            .and_then(surrealdb::Response::check);
+        if let Err(e) = stored {
+            tracing::warn!("Failed to cache blame for {}: {}", raw_path, e);
+        }
+    }
+    blame
 }
 
 /// Get stored AI file log object by path
 pub async fn get_file_log_object(
     State(state): State<AppState>,
+    tenant: Tenant,
     Path(file_path): Path<String>,
 ) -> Result<Json<FileLogObjectResponse>, (StatusCode, Json<serde_json::Value>)> {
     if let Some(object_id) = parse_object_id(&file_path) {
         let mut response = match state
             .db
             .client
-            .query("SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM objects WHERE type = 'FileLog' AND id = type::thing('objects', $id)")
+            .query("SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND id = type::thing('objects', $id)")
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("id", object_id.clone()))
             .await
         {
@@ -383,7 +710,8 @@ pub async fn get_file_log_object(
         let mut response = match state
             .db
             .client
-            .query("SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM objects WHERE type = 'FileLog' AND file_id = $id LIMIT 1")
+            .query("SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND file_id = $id LIMIT 1")
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("id", object_id))
             .await
         {
@@ -411,28 +739,25 @@ pub async fn get_file_log_object(
         }
     }
 
-    let normalized = normalize_lookup_path(&file_path);
-    let basename = extract_basename(&file_path);
+    let resolver = &state.path_resolver;
+    let normalized = resolver.canonicalize(&file_path);
+    let folded = resolver.lookup_key(&file_path);
+    let basename = resolver.basename(&file_path).to_lowercase();
 
-    // Check if input is basename-only (no path separators) - needs early ambiguity check
-    let is_basename_only = !file_path.contains('/') && !file_path.contains('\\');
-
-    if is_basename_only {
-        // Query all matching file_paths - HashSet will deduplicate
-        let ambiguity_query = "SELECT VALUE file_path FROM objects WHERE type = 'FileLog' AND file_path CONTAINS $basename";
+    // Basename-only input needs an early ambiguity check
+    if resolver.is_basename_only(&file_path) {
+        let ambiguity_query = "SELECT VALUE file_path FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND string::lowercase(file_path) CONTAINS $basename";
         if let Ok(mut response) = state.db.client
             .query(ambiguity_query)
-            .bind(("basename", file_path.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string()))
+            .bind(("basename", basename.clone()))
             .await
         {
             let values = take_json_values(&mut response, 0);
-            // Values are raw strings from SELECT VALUE, collect unique ones
-            let unique_paths: std::collections::HashSet<String> = values.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect();
-
-            if unique_paths.len() > 1 {
-                let paths_list: Vec<String> = unique_paths.into_iter().collect();
+            let stored = values.iter().filter_map(|v| v.as_str());
+            if let BasenameMatch::Ambiguous(paths_list) =
+                resolver.match_basename(&file_path, stored)
+            {
                 return Err((
                     StatusCode::CONFLICT,
                     Json(serde_json::json!({
@@ -448,65 +773,75 @@ pub async fn get_file_log_object(
 
     // Tier 1: Try specific path matches first (exact, contains path/norm)
     // Use SELECT VALUE with string::concat(id) to avoid Thing enum serialization errors
-    let specific_query = "SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM (SELECT * FROM objects WHERE type = 'FileLog' AND (file_path = $path OR file_path CONTAINS $path OR file_path = $norm OR file_path CONTAINS $norm) ORDER BY updated_at DESC LIMIT 1)";
+    let specific_query = "SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM (SELECT * FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND (file_path = $path OR file_path CONTAINS $path OR file_path = $norm OR file_path CONTAINS $norm OR file_path CONTAINS $folded) ORDER BY updated_at DESC LIMIT 1)";
     let mut values = match state
         .db
         .client
         .query(specific_query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .bind(("path", file_path.clone()))
         .bind(("norm", normalized.clone()))
+        .bind(("folded", folded.clone()))
         .await
     {
         Ok(mut response) => take_json_values(&mut response, 0),
         Err(err) => {
             tracing::warn!("File log query failed, falling back to scan: {}", err);
-            fetch_file_log_fallback(&state, &file_path, &normalized, &basename).await?
+            fetch_file_log_fallback(&state, &tenant, &file_path, &folded, &basename).await?
         }
     };
 
     // Tier 2: If no specific match, try basename with ambiguity check
     // Use SELECT VALUE with string::concat(id) to avoid Thing enum serialization errors
     if values.is_empty() {
-        let basename_query = "SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM (SELECT * FROM objects WHERE type = 'FileLog' AND file_path CONTAINS $basename ORDER BY updated_at DESC)";
+        let basename_query = "SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM (SELECT * FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND string::lowercase(file_path) CONTAINS $basename ORDER BY updated_at DESC)";
 
         if let Ok(mut response) = state.db.client
             .query(basename_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("basename", basename.clone()))
             .await
         {
             let basename_values = take_json_values(&mut response, 0);
-
-            // Check for ambiguity - multiple different file paths
-            let unique_paths: std::collections::HashSet<String> = basename_values.iter()
-                .filter_map(|v| v.get("file_path").and_then(|p| p.as_str()).map(|s| s.to_string()))
-                .collect();
-
-            if unique_paths.len() > 1 {
-                let paths_list: Vec<String> = unique_paths.into_iter().collect();
-                return Err((
-                    StatusCode::CONFLICT,
-                    Json(serde_json::json!({
-                        "error": "Ambiguous path - multiple files match",
-                        "input_path": file_path,
-                        "matching_files": paths_list,
-                        "hint": "Please use a more specific path (e.g., include parent directory)"
-                    })),
-                ));
+            let stored = basename_values
+                .iter()
+                .filter_map(|v| v.get("file_path").and_then(|p| p.as_str()));
+            match resolver.match_basename(&file_path, stored) {
+                BasenameMatch::Ambiguous(paths_list) => {
+                    return Err((
+                        StatusCode::CONFLICT,
+                        Json(serde_json::json!({
+                            "error": "Ambiguous path - multiple files match",
+                            "input_path": file_path,
+                            "matching_files": paths_list,
+                            "hint": "Please use a more specific path (e.g., include parent directory)"
+                        })),
+                    ));
+                }
+                BasenameMatch::Unique(matched) => {
+                    values = basename_values
+                        .into_iter()
+                        .filter(|v| {
+                            v.get("file_path").and_then(|p| p.as_str()) == Some(matched.as_str())
+                        })
+                        .collect();
+                }
+                BasenameMatch::None => {}
             }
-
-            values = basename_values;
         }
     }
 
     // Tier 3: Try FileChunk lookup if FileLog not found
     if values.is_empty() {
-        let chunk_query = "SELECT file_id, file_path FROM objects WHERE type = 'FileChunk' AND (file_path = $path OR file_path CONTAINS $path OR file_path CONTAINS $norm OR file_path CONTAINS $basename) GROUP BY file_id, file_path";
+        let chunk_query = "SELECT file_id, file_path FROM objects WHERE type = 'FileChunk' AND tenant_id = $tenant_id AND (file_path = $path OR file_path CONTAINS $path OR file_path CONTAINS $norm OR file_path CONTAINS $folded OR string::lowercase(file_path) CONTAINS $basename) GROUP BY file_id, file_path";
         let mut chunk_response = match state
             .db
             .client
             .query(chunk_query)
+            .bind(("tenant_id", tenant.as_str().to_string()))
             .bind(("path", file_path.clone()))
             .bind(("norm", normalized.clone()))
+            .bind(("folded", folded.clone()))
             .bind(("basename", basename.clone()))
             .await
         {
@@ -524,12 +859,22 @@ pub async fn get_file_log_object(
         let chunk_values = take_json_values(&mut chunk_response, 0);
 
         // Check for ambiguity in chunk matches
-        let unique_chunk_paths: std::collections::HashSet<String> = chunk_values.iter()
-            .filter_map(|v| v.get("file_path").and_then(|p| p.as_str()).map(|s| s.to_string()))
-            .collect();
+        let mut unique_chunk_paths: Vec<&str> = Vec::new();
+        for path in chunk_values
+            .iter()
+            .filter_map(|v| v.get("file_path").and_then(|p| p.as_str()))
+        {
+            if !unique_chunk_paths
+                .iter()
+                .any(|seen| resolver.same_path(seen, path))
+            {
+                unique_chunk_paths.push(path);
+            }
+        }
 
         if unique_chunk_paths.len() > 1 {
-            let paths_list: Vec<String> = unique_chunk_paths.into_iter().collect();
+            let paths_list: Vec<String> =
+                unique_chunk_paths.iter().map(|p| p.to_string()).collect();
             return Err((
                 StatusCode::CONFLICT,
                 Json(serde_json::json!({
@@ -551,14 +896,15 @@ pub async fn get_file_log_object(
             values = match state
                 .db
                 .client
-                .query("SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM objects WHERE type = 'FileLog' AND file_id = $file_id LIMIT 1")
+                .query("SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND file_id = $file_id LIMIT 1")
+                .bind(("tenant_id", tenant.as_str().to_string()))
                 .bind(("file_id", file_id))
                 .await
             {
                 Ok(mut response) => take_json_values(&mut response, 0),
                 Err(err) => {
                     tracing::warn!("File log id query failed, falling back to scan: {}", err);
-                    fetch_file_log_fallback(&state, &file_path, &normalized, &basename).await?
+                    fetch_file_log_fallback(&state, &tenant, &file_path, &folded, &basename).await?
                 }
             };
         }
@@ -601,50 +947,12 @@ pub async fn get_file_log_object(
     Ok(Json(FileLogObjectResponse { file_log }))
 }
 
-fn normalize_lookup_path(path: &str) -> String {
-    let mut normalized = path.replace('/', "\\");
-    if let Some(stripped) = normalized.strip_prefix(r"\\?\") {
-        normalized = stripped.to_string();
-    }
-    if let Some(stripped) = normalized.strip_prefix(".\\") {
-        normalized = stripped.to_string();
-    }
-    normalized.to_lowercase()
-}
-
-fn normalize_file_content_path(path: &str) -> String {
-    let mut normalized = path.replace('/', "\\");
-    if let Some(stripped) = normalized.strip_prefix(r"\\?\") {
-        normalized = stripped.to_string();
-    }
-    if let Some(stripped) = normalized.strip_prefix(".\\") {
-        normalized = stripped.to_string();
-    }
-    normalized
-}
-
 fn parse_object_id(input: &str) -> Option<String> {
     let trimmed = input.trim();
     let candidate = trimmed.strip_prefix("objects:").unwrap_or(trimmed);
     Uuid::parse_str(candidate).ok().map(|id| id.to_string())
 }
 
-fn extract_basename(input: &str) -> String {
-    input
-        .rsplit(['\\', '/'])
-        .next()
-        .unwrap_or(input)
-        .to_lowercase()
-}
-
-fn extract_basename_raw(input: &str) -> String {
-    input
-        .rsplit(['\\', '/'])
-        .next()
-        .unwrap_or(input)
-        .to_string()
-}
-
 fn normalize_object_id(raw: &str) -> String {
     raw.trim()
         .strip_prefix("objects:")
@@ -652,15 +960,16 @@ fn normalize_object_id(raw: &str) -> String {
         .to_string()
 }
 
-async fn find_file_node_id(state: &AppState, raw_path: &str, project_id: Option<&str>, file_id: Option<&str>) -> Option<String> {
+async fn find_file_node_id(state: &AppState, tenant_id: &str, raw_path: &str, project_id: Option<&str>, file_id: Option<&str>) -> Option<String> {
     // Phase 1: Try file_id-based lookup first (most reliable)
     if let Some(fid) = file_id {
         // Use the same pattern as the working file_id lookups elsewhere
-        let file_id_query = "SELECT VALUE string::concat(id) FROM objects WHERE kind = 'file' AND file_id = $file_id LIMIT 1";
+        let file_id_query = "SELECT VALUE string::concat(id) FROM objects WHERE kind = 'file' AND file_id = $file_id AND tenant_id = $tenant_id LIMIT 1";
         
         match state.db.client
             .query(file_id_query)
             .bind(("file_id", fid.to_string()))
+            .bind(("tenant_id", tenant_id.to_string()))
             .await
         {
             Ok(mut response) => {
@@ -689,16 +998,17 @@ async fn find_file_node_id(state: &AppState, raw_path: &str, project_id: Option<
     }
     
     // Phase 2: Fall back to path-based lookup
-    let normalized = normalize_lookup_path(raw_path);
+    let normalized = state.path_resolver.lookup_key(raw_path);
     let normalized_forward = normalized.replace('\\', "/");
     let raw_forward = raw_path.replace('\\', "/");
-    let basename = extract_basename_raw(raw_path);
+    let basename = state.path_resolver.basename(raw_path).to_string();
     let project_id = project_id.unwrap_or("");
 
     let query = r#"
         SELECT VALUE string::concat(id) FROM objects
         WHERE kind = 'file'
           AND (type = 'Symbol' OR type = 'symbol' OR type = 'file' OR type = 'File')
+          AND tenant_id = $tenant_id
           AND (
             path = $raw OR path CONTAINS $raw OR
             path = $raw_fwd OR path CONTAINS $raw_fwd OR
@@ -722,6 +1032,7 @@ async fn find_file_node_id(state: &AppState, raw_path: &str, project_id: Option<
         .bind(("norm_fwd", normalized_forward))
         .bind(("basename", basename))
         .bind(("project", project_id.to_string()))
+        .bind(("tenant_id", tenant_id.to_string()))
         .await
     {
         Ok(response) => response,
@@ -742,16 +1053,17 @@ async fn find_file_node_id(state: &AppState, raw_path: &str, project_id: Option<
     result
 }
 
-async fn find_directory_node_id(state: &AppState, raw_path: &str) -> Option<String> {
-    let normalized = normalize_lookup_path(raw_path);
+async fn find_directory_node_id(state: &AppState, tenant_id: &str, raw_path: &str) -> Option<String> {
+    let normalized = state.path_resolver.lookup_key(raw_path);
     let normalized_forward = normalized.replace('\\', "/");
     let raw_forward = raw_path.replace('\\', "/");
-    let basename = extract_basename_raw(raw_path);
+    let basename = state.path_resolver.basename(raw_path).to_string();
 
     let query = r#"
         SELECT VALUE string::concat(id) FROM objects
         WHERE kind = 'directory'
           AND (type = 'Symbol' OR type = 'symbol')
+          AND tenant_id = $tenant_id
           AND (
             path = $raw OR path CONTAINS $raw OR
             path = $raw_fwd OR path CONTAINS $raw_fwd OR
@@ -773,6 +1085,7 @@ async fn find_directory_node_id(state: &AppState, raw_path: &str) -> Option<Stri
         .bind(("norm", normalized))
         .bind(("norm_fwd", normalized_forward))
         .bind(("basename", basename))
+        .bind(("tenant_id", tenant_id.to_string()))
         .await
     {
         Ok(response) => response,
@@ -885,7 +1198,6 @@ async fn find_project_for_path(
         return None;
     }
 
-    let file_norm = normalize_lookup_path(raw_path);
     let mut best_match: Option<(usize, String, String, String)> = None;
 
     for value in values {
@@ -894,8 +1206,7 @@ async fn find_project_for_path(
             continue;
         }
 
-        let project_norm = normalize_lookup_path(path);
-        if !file_norm.starts_with(&project_norm) {
+        if !state.path_resolver.is_within(raw_path, path) {
             continue;
         }
 
@@ -922,7 +1233,7 @@ async fn find_project_for_path(
             });
         if let Some(id) = id {
             if let Some(project_id) = project_id {
-                let match_len = project_norm.len();
+                let match_len = state.path_resolver.canonicalize(path).len();
                 let should_replace = best_match
                     .as_ref()
                     .map_or(true, |(best_len, _, _, _)| match_len > *best_len);
@@ -951,7 +1262,7 @@ async fn ensure_project_node_for_path(
 
     let root = detect_project_root(file_path);
     let root_raw = root.to_string_lossy().to_string();
-    let project_path = map_container_mount(&root_raw).unwrap_or(root_raw);
+    let project_path = state.path_resolver.to_host(&root_raw).unwrap_or(root_raw);
     let project_name = root
         .file_name()
         .and_then(|n| n.to_str())
@@ -1100,6 +1411,7 @@ async fn ensure_defined_in_relationship(state: &AppState, source_id: &str, targe
 
 async fn fetch_file_log_fallback(
     state: &AppState,
+    tenant: &Tenant,
     raw_path: &str,
     normalized: &str,
     basename: &str,
@@ -1107,7 +1419,8 @@ async fn fetch_file_log_fallback(
     let mut response = match state
         .db
         .client
-        .query("SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM objects WHERE type = 'FileLog' LIMIT 2000")
+        .query("SELECT VALUE { id: string::concat(id), type: type, file_path: file_path, file_id: file_id, summary: summary, summary_markdown: summary_markdown, purpose: purpose, key_symbols: key_symbols, dependencies: dependencies, notes: notes, updated_at: updated_at, created_at: created_at, project_id: project_id, tenant_id: tenant_id } FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id LIMIT 2000")
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .await
     {
         Ok(response) => response,
@@ -1151,25 +1464,133 @@ async fn fetch_file_log_fallback(
     Ok(best.into_iter().take(1).map(|(_, value)| value).collect())
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct FileContentQuery {
     pub max_chars: Option<usize>,
+    /// First line to return (1-based, inclusive)
+    pub start_line: Option<u32>,
+    /// Last line to return (inclusive)
+    pub end_line: Option<u32>,
+    /// Return only this symbol's definition
+    pub symbol: Option<String>,
+}
+
+struct StoredChunk {
+    id: String,
+    content: String,
+    start_line: Option<u32>,
+}
+
+/// Lines of the file keyed by line number, rebuilt from chunk line metadata.
+/// Chunks without it (older indexes) are laid end to end from line 1.
+fn chunk_lines(chunks: &[StoredChunk]) -> std::collections::BTreeMap<u32, &str> {
+    let mut lines = std::collections::BTreeMap::new();
+    let mut next_line = 1;
+    for chunk in chunks {
+        let start = chunk.start_line.unwrap_or(next_line);
+        for (offset, line) in chunk.content.lines().enumerate() {
+            // Overlapping word windows repeat lines; the first copy wins
+            lines.entry(start + offset as u32).or_insert(line);
+        }
+        next_line = start + chunk.content.lines().count().max(1) as u32;
+    }
+    lines
+}
+
+/// Line range of `symbol`'s definition. Stored Symbol objects name the
+/// file's symbols and the line of each name; the syntax tree of the
+/// reassembled file gives the definition's full extent.
+async fn symbol_line_range(
+    state: &AppState,
+    tenant: &Tenant,
+    file_path: &str,
+    text: &str,
+    symbol: &str,
+) -> Result<(u32, u32), (StatusCode, Json<serde_json::Value>)> {
+    let query = "SELECT name, start_line, end_line FROM objects WHERE type = 'symbol' AND tenant_id = $tenant_id AND path = $path";
+    let stored = match state
+        .db
+        .client
+        .query(query)
+        .bind(("tenant_id", tenant.as_str().to_string()))
+        .bind(("path", file_path.to_string()))
+        .await
+    {
+        Ok(mut response) => take_json_values(&mut response, 0),
+        Err(err) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": format!("Failed to query symbols: {}", err) })),
+            ))
+        }
+    };
+    let line = |value: &serde_json::Value, key: &str| {
+        value.get(key).and_then(|v| v.as_u64()).map(|v| v as u32)
+    };
+    let stored_symbol = stored
+        .iter()
+        .find(|value| value.get("name").and_then(|v| v.as_str()) == Some(symbol));
+    let name_line = stored_symbol.and_then(|value| line(value, "start_line"));
+
+    let parser = CodebaseParser::new().ok();
+    let language = detect_language(&PathBuf::from(file_path));
+    let definitions = parser
+        .as_ref()
+        .map(|parser| crate::services::chunking::definition_lines(parser, text, &language))
+        .unwrap_or_default();
+    let definition = definitions
+        .iter()
+        .filter(|(name, _, _)| name == symbol)
+        .find(|(_, start, end)| name_line.is_none_or(|line| (*start..=*end).contains(&line)))
+        .or_else(|| definitions.iter().find(|(name, _, _)| name == symbol));
+    if let Some((_, start, end)) = definition {
+        return Ok((*start, *end));
+    }
+    if let Some((Some(start), Some(end))) =
+        stored_symbol.map(|value| (line(value, "start_line"), line(value, "end_line")))
+    {
+        return Ok((start, end));
+    }
+
+    let mut available: Vec<String> = stored
+        .iter()
+        .filter_map(|value| {
+            value
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        })
+        .chain(definitions.into_iter().map(|(name, _, _)| name))
+        .collect();
+    available.sort();
+    available.dedup();
+    Err((
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({
+            "error": "Symbol not found",
+            "path": file_path,
+            "symbol": symbol,
+            "available_symbols": available,
+        })),
+    ))
 }
 
 /// Get stored file content by path (assembled from FileChunk objects)
 pub async fn get_file_content(
     State(state): State<AppState>,
+    tenant: Tenant,
     Path(file_path): Path<String>,
     Query(query): Query<FileContentQuery>,
 ) -> Result<Json<FileContentResponse>, (StatusCode, Json<serde_json::Value>)> {
-    let normalized = normalize_file_content_path(&file_path);
-    let basename = extract_basename_raw(&file_path);
+    let normalized = state.path_resolver.display_path(&file_path);
+    let basename = state.path_resolver.basename(&file_path).to_string();
     let basename_lower = basename.to_lowercase();
-    let query_str = "SELECT content, chunk_index FROM objects WHERE type = 'FileChunk' AND (file_path = $path OR file_path CONTAINS $path OR file_path = $norm OR file_path CONTAINS $norm OR file_path CONTAINS $basename OR file_path CONTAINS $basename_lower) ORDER BY chunk_index ASC";
+    let query_str = "SELECT string::concat(id) AS id, content, chunk_index, start_line, file_path FROM objects WHERE type = 'FileChunk' AND tenant_id = $tenant_id AND (file_path = $path OR file_path CONTAINS $path OR file_path = $norm OR file_path CONTAINS $norm OR file_path CONTAINS $basename OR file_path CONTAINS $basename_lower) ORDER BY chunk_index ASC";
     let mut response = match state
         .db
         .client
         .query(query_str)
+        .bind(("tenant_id", tenant.as_str().to_string()))
         .bind(("path", file_path.clone()))
         .bind(("norm", normalized.clone()))
         .bind(("basename", basename.clone()))
@@ -1203,44 +1624,153 @@ pub async fn get_file_content(
             .unwrap_or(0)
     });
 
-    let mut chunks = Vec::new();
-    let mut combined = String::new();
-    for value in values {
-        if let Some(content) = value.get("content").and_then(|v| v.as_str()) {
-            chunks.push(content.to_string());
-            combined.push_str(content);
-        }
+    let stored_paths: Vec<&str> = values
+        .iter()
+        .filter_map(|value| value.get("file_path").and_then(|v| v.as_str()))
+        .collect();
+    let stored_path = stored_paths
+        .iter()
+        .find(|path| **path == file_path || **path == normalized)
+        .or(stored_paths.first())
+        .copied()
+        .unwrap_or(&file_path)
+        .to_string();
+    let chunks: Vec<StoredChunk> = values
+        .iter()
+        .filter_map(|value| {
+            Some(StoredChunk {
+                id: value
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                content: value.get("content")?.as_str()?.to_string(),
+                start_line: value
+                    .get("start_line")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u32),
+            })
+        })
+        .collect();
+
+    let ranged = query.start_line.is_some() || query.end_line.is_some() || query.symbol.is_some();
+    if !ranged {
+        let combined: String = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+        let limited = match query.max_chars {
+            Some(limit) => combined.chars().take(limit).collect(),
+            None => combined,
+        };
+        return Ok(Json(FileContentResponse {
+            path: file_path,
+            content: limited,
+            chunk_ids: chunks.iter().map(|chunk| chunk.id.clone()).collect(),
+            chunks: chunks.into_iter().map(|chunk| chunk.content).collect(),
+            start_line: None,
+            end_line: None,
+            symbol: None,
+        }));
     }
 
+    // Slices only make sense within one file; a basename match can pull in others
+    let chunks: Vec<StoredChunk> = values
+        .iter()
+        .zip(chunks)
+        .filter(|(value, _)| {
+            value
+                .get("file_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&file_path)
+                == stored_path
+        })
+        .map(|(_, chunk)| chunk)
+        .collect();
+    let lines = chunk_lines(&chunks);
+    let last_line = lines.keys().next_back().copied().unwrap_or(0);
+
+    let (mut first, mut last) = (
+        query.start_line.unwrap_or(1).max(1),
+        query.end_line.unwrap_or(last_line),
+    );
+    if let Some(symbol) = &query.symbol {
+        let text = lines.values().copied().collect::<Vec<_>>().join("\n");
+        let (start, end) = symbol_line_range(&state, &tenant, &stored_path, &text, symbol).await?;
+        // An explicit range narrows the symbol's lines
+        first = first.max(start);
+        last = last.min(end);
+    }
+    if first > last {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "start_line is after end_line",
+                "start_line": first,
+                "end_line": last,
+            })),
+        ));
+    }
+
+    let selected: Vec<(u32, &str)> = lines
+        .range(first..=last)
+        .map(|(line, text)| (*line, *text))
+        .collect();
+    let (Some((start_line, _)), Some((end_line, _))) = (selected.first(), selected.last()) else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "Requested lines not found",
+                "path": file_path,
+                "last_line": last_line,
+            })),
+        ));
+    };
+    let (start_line, end_line) = (*start_line, *end_line);
+    let content = selected
+        .iter()
+        .map(|(_, text)| *text)
+        .collect::<Vec<_>>()
+        .join("\n");
     let limited = match query.max_chars {
-        Some(limit) => combined.chars().take(limit).collect(),
-        None => combined,
+        Some(limit) => content.chars().take(limit).collect(),
+        None => content,
     };
 
+    // Only the chunks that overlap the returned lines
+    let mut next_line = 1;
+    let mut overlapping = Vec::new();
+    for chunk in chunks {
+        let start = chunk.start_line.unwrap_or(next_line);
+        let end = start + chunk.content.lines().count().max(1) as u32 - 1;
+        next_line = end + 1;
+        if start <= end_line && end >= start_line {
+            overlapping.push(chunk);
+        }
+    }
+
     Ok(Json(FileContentResponse {
         path: file_path,
         content: limited,
-        chunks,
+        chunk_ids: overlapping.iter().map(|chunk| chunk.id.clone()).collect(),
+        chunks: overlapping.into_iter().map(|chunk| chunk.content).collect(),
+        start_line: Some(start_line),
+        end_line: Some(end_line),
+        symbol: query.symbol,
     }))
 }
 
 /// Resolve file path using multiple strategies
 async fn resolve_file_path(file_path: &str, state: &AppState) -> Result<PathBuf, StatusCode> {
-    if let Some(mapped) = map_windows_mount(file_path) {
-        if mapped.exists() {
-            return Ok(mapped);
-        }
-    }
-    let normalized_input = if cfg!(windows) {
-        file_path.to_string()
-    } else {
-        file_path.replace('\\', "/")
-    };
-    // Strategy 1: Try as absolute path
-    let path = PathBuf::from(&normalized_input);
-    if path.is_absolute() && path.exists() {
-        return Ok(path);
+    // Strategy 1: Try the mount mappings and the path itself as absolute paths
+    let candidates = state.path_resolver.disk_candidates(file_path);
+    if let Some(path) = candidates
+        .iter()
+        .find(|path| path.is_absolute() && path.exists())
+    {
+        return Ok(path.clone());
     }
+    let normalized_input = candidates
+        .last()
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from(file_path));
 
     // Strategy 2: Try relative to current working directory
     if let Ok(cwd) = std::env::current_dir() {
@@ -1267,10 +1797,11 @@ async fn resolve_file_path(file_path: &str, state: &AppState) -> Result<PathBuf,
             if candidate.exists() {
                 return Ok(candidate);
             }
-            if let Some(mapped) = map_windows_mount(&candidate.to_string_lossy()) {
-                if mapped.exists() {
-                    return Ok(mapped);
-                }
+            let mapped = state
+                .path_resolver
+                .disk_candidates(&candidate.to_string_lossy());
+            if let Some(mapped) = mapped.into_iter().find(|path| path.exists()) {
+                return Ok(mapped);
             }
         }
     }
@@ -1313,53 +1844,6 @@ async fn fetch_project_roots(state: &AppState) -> Result<Vec<PathBuf>, StatusCod
     Ok(roots)
 }
 
-fn map_windows_mount(path: &str) -> Option<PathBuf> {
-    let host_root = env::var("AMP_WINDOWS_MOUNT_ROOT").unwrap_or_else(|_| "C:\\Users".to_string());
-    let container_root =
-        env::var("AMP_WORKSPACE_MOUNT").unwrap_or_else(|_| "/workspace".to_string());
-
-    let normalized = path.strip_prefix(r"\\?\").unwrap_or(path);
-    let normalized = normalized.replace('/', "\\");
-    let host_root_norm = host_root.replace('/', "\\");
-
-    if !normalized
-        .to_lowercase()
-        .starts_with(&host_root_norm.to_lowercase())
-    {
-        return None;
-    }
-
-    let rel = normalized[host_root_norm.len()..].trim_start_matches('\\');
-    let container_root = container_root.trim_end_matches('/');
-    let mapped = if rel.is_empty() {
-        container_root.to_string()
-    } else {
-        format!("{}/{}", container_root, rel.replace('\\', "/"))
-    };
-    Some(PathBuf::from(mapped))
-}
-
-fn map_container_mount(path: &str) -> Option<String> {
-    let host_root = env::var("AMP_WINDOWS_MOUNT_ROOT").unwrap_or_else(|_| "C:\\Users".to_string());
-    let container_root =
-        env::var("AMP_WORKSPACE_MOUNT").unwrap_or_else(|_| "/workspace".to_string());
-
-    let normalized = path.replace('\\', "/");
-    let container_root = container_root.trim_end_matches('/');
-    if !normalized.starts_with(container_root) {
-        return None;
-    }
-
-    let rel = normalized[container_root.len()..].trim_start_matches('/');
-    let host_root = host_root.trim_end_matches('\\');
-    let mapped = if rel.is_empty() {
-        host_root.to_string()
-    } else {
-        format!("{}\\{}", host_root, rel.replace('/', "\\"))
-    };
-    Some(mapped)
-}
-
 // Helper functions
 
 fn detect_language(file_path: &std::path::PathBuf) -> String {
@@ -1447,6 +1931,7 @@ pub async fn delete_codebase(
         "modifies",
         "implements",
         "produced",
+        "superseded_by",
         "relationships",
     ];
 
@@ -1534,6 +2019,10 @@ pub struct FileSyncResponse {
     pub audit_entry_added: bool,
     pub chunks_replaced: usize,
     pub relationships_updated: usize,
+    /// Symbol differences from the previous parse of this file
+    pub symbols_added: usize,
+    pub symbols_removed: usize,
+    pub symbols_changed: usize,
 }
 
 #[derive(Debug, Serialize)]
@@ -1547,10 +2036,9 @@ pub struct LayersUpdated {
 /// This is the unified write endpoint that keeps the codebase index in sync
 pub async fn sync_file(
     State(state): State<AppState>,
+    tenant: Tenant,
     Json(request): Json<FileSyncRequest>,
 ) -> Result<Json<FileSyncResponse>, (StatusCode, Json<serde_json::Value>)> {
-    use crate::services::chunking::ChunkingService;
-
     tracing::info!("Syncing file: {} (action: {})", request.path, request.action);
 
     let action = request.action.to_lowercase();
@@ -1563,32 +2051,29 @@ pub async fn sync_file(
     let mut relationships_updated = 0;
     let mut file_symbol_updated = false;
     let mut file_symbol_id: Option<String> = None;
+    let tenant_id = tenant.as_str().to_string();
 
     // Try to find existing file_id and file_path by flexible path matching
     // Use tiered matching: exact/specific first, then basename (with ambiguity check)
-    let normalized = normalize_lookup_path(&request.path);
-    let basename = extract_basename(&request.path);
-
-    // Check if input is basename-only (no path separators) - needs ambiguity check
-    let is_basename_only = !request.path.contains('/') && !request.path.contains('\\');
+    let resolver = &state.path_resolver;
+    let normalized = resolver.canonicalize(&request.path);
+    let folded = resolver.lookup_key(&request.path);
+    let basename = resolver.basename(&request.path).to_lowercase();
 
     // If basename-only, check for ambiguity FIRST before any matching
-    if is_basename_only {
-        // Query all matching file_paths - HashSet will deduplicate
-        let ambiguity_query = "SELECT VALUE file_path FROM objects WHERE type = 'FileLog' AND file_path CONTAINS $basename";
+    if resolver.is_basename_only(&request.path) {
+        let ambiguity_query = "SELECT VALUE file_path FROM objects WHERE type = 'FileLog' AND string::lowercase(file_path) CONTAINS $basename AND tenant_id = $tenant_id";
         if let Ok(mut response) = state.db.client
             .query(ambiguity_query)
-            .bind(("basename", request.path.clone()))
+            .bind(("basename", basename.clone()))
+            .bind(("tenant_id", tenant_id.clone()))
             .await
         {
             let values = take_json_values(&mut response, 0);
-            // Values are raw strings from SELECT VALUE, collect unique ones
-            let unique_paths: std::collections::HashSet<String> = values.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect();
-
-            if unique_paths.len() > 1 {
-                let paths_list: Vec<String> = unique_paths.into_iter().collect();
+            let stored = values.iter().filter_map(|v| v.as_str());
+            if let BasenameMatch::Ambiguous(paths_list) =
+                resolver.match_basename(&request.path, stored)
+            {
                 tracing::warn!("Ambiguous basename '{}' matches {} files", request.path, paths_list.len());
                 return Err((
                     StatusCode::CONFLICT,
@@ -1604,12 +2089,14 @@ pub async fn sync_file(
     }
 
     // Tier 1: Try exact or specific path matches first
-    let specific_query = "SELECT file_id, file_path FROM objects WHERE (type = 'FileLog' OR type = 'FileChunk') AND (file_path = $path OR file_path CONTAINS $path OR file_path = $norm OR file_path CONTAINS $norm) LIMIT 1";
+    let specific_query = "SELECT file_id, file_path FROM objects WHERE (type = 'FileLog' OR type = 'FileChunk') AND (file_path = $path OR file_path CONTAINS $path OR file_path = $norm OR file_path CONTAINS $norm OR file_path CONTAINS $folded) AND tenant_id = $tenant_id LIMIT 1";
 
     let (mut existing_file_id, mut existing_file_path) = match state.db.client
         .query(specific_query)
         .bind(("path", request.path.clone()))
         .bind(("norm", normalized.clone()))
+        .bind(("folded", folded.clone()))
+        .bind(("tenant_id", tenant_id.clone()))
         .await
     {
         Ok(mut response) => {
@@ -1627,82 +2114,80 @@ pub async fn sync_file(
 
     // Tier 2: If no specific match, try basename - but check for ambiguity
     if existing_file_id.is_none() {
-        let basename_query = "SELECT file_id, file_path FROM objects WHERE (type = 'FileLog' OR type = 'FileChunk') AND file_path CONTAINS $basename GROUP BY file_id, file_path";
+        let basename_query = "SELECT file_id, file_path FROM objects WHERE (type = 'FileLog' OR type = 'FileChunk') AND string::lowercase(file_path) CONTAINS $basename AND tenant_id = $tenant_id GROUP BY file_id, file_path";
 
         if let Ok(mut response) = state.db.client
             .query(basename_query)
             .bind(("basename", basename.clone()))
+            .bind(("tenant_id", tenant_id.clone()))
             .await
         {
             let values = take_json_values(&mut response, 0);
 
-            // Deduplicate by file_path (FileLog and FileChunk may have same path)
-            let unique_paths: std::collections::HashSet<String> = values.iter()
-                .filter_map(|v| v.get("file_path").and_then(|p| p.as_str()).map(|s| s.to_string()))
-                .collect();
-
-            if unique_paths.len() > 1 {
-                // Ambiguous match - multiple files with same basename
-                let paths_list: Vec<String> = unique_paths.into_iter().collect();
-                tracing::warn!("Ambiguous path '{}' matches {} files", request.path, paths_list.len());
-                return Err((
-                    StatusCode::CONFLICT,
-                    Json(serde_json::json!({
-                        "error": "Ambiguous path - multiple files match",
-                        "input_path": request.path,
-                        "matching_files": paths_list,
-                        "hint": "Please use a more specific path (e.g., include parent directory)"
-                    })),
-                ));
-            } else if let Some(record) = values.first() {
-                // Single match - safe to use
-                existing_file_id = record.get("file_id").and_then(|v| v.as_str()).map(|s| s.to_string());
-                existing_file_path = record.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string());
+            // FileLog and FileChunk share a path, so match on distinct files
+            let stored = values
+                .iter()
+                .filter_map(|v| v.get("file_path").and_then(|p| p.as_str()));
+            match resolver.match_basename(&request.path, stored) {
+                BasenameMatch::Ambiguous(paths_list) => {
+                    tracing::warn!(
+                        "Ambiguous path '{}' matches {} files",
+                        request.path,
+                        paths_list.len()
+                    );
+                    return Err((
+                        StatusCode::CONFLICT,
+                        Json(serde_json::json!({
+                            "error": "Ambiguous path - multiple files match",
+                            "input_path": request.path,
+                            "matching_files": paths_list,
+                            "hint": "Please use a more specific path (e.g., include parent directory)"
+                        })),
+                    ));
+                }
+                BasenameMatch::Unique(matched) => {
+                    // Single match - safe to use
+                    let record = values.iter().find(|v| {
+                        v.get("file_path").and_then(|p| p.as_str()) == Some(matched.as_str())
+                    });
+                    existing_file_id = record
+                        .and_then(|r| r.get("file_id"))
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    existing_file_path = Some(matched);
+                }
+                BasenameMatch::None => {}
             }
         }
     }
 
     // Use existing file_id if found, otherwise generate new one from normalized input
-    use sha2::{Digest, Sha256};
     let mut file_id = if let Some(id) = existing_file_id.clone() {
         tracing::debug!("Found existing file_id: {}", id);
         id
     } else {
         tracing::debug!("No existing file_id found, generating new one for: {}", normalized);
-        let mut hasher = Sha256::new();
-        hasher.update(normalized.as_bytes());
-        format!("file-{}", hex::encode(&hasher.finalize()[..16]))
+        file_id_for_path(&tenant_id, &normalized)
     };
 
     // Handle delete action
     if action == "delete" {
-        // Delete FileChunks
-        let delete_chunks_query = "DELETE FROM objects WHERE type = 'FileChunk' AND file_id = $file_id";
-        if let Ok(mut response) = state.db.client
-            .query(delete_chunks_query)
-            .bind(("file_id", file_id.clone()))
-            .await
-        {
-            chunks_replaced = take_json_values(&mut response, 0).len();
-            layers_updated.vector = true;
-        }
-
-        // Delete relationships for this file
-        let relationship_tables = ["defined_in", "depends_on", "calls", "modifies"];
-        for table in relationship_tables {
-            let query = format!(
-                "DELETE FROM {} WHERE in IN (SELECT id FROM objects WHERE file_id = $file_id) OR out IN (SELECT id FROM objects WHERE file_id = $file_id)",
-                table
-            );
-            if let Ok(mut response) = state.db.client
-                .query(&query)
-                .bind(("file_id", file_id.clone()))
-                .await
-            {
-                relationships_updated += take_json_values(&mut response, 0).len();
+        // Drop chunks, symbols and edges; the FileLog stays for its audit trail
+        let stored_path = existing_file_path.as_deref().unwrap_or(&request.path);
+        let path_key = resolver.lookup_key(
+            &resolver
+                .to_host(stored_path)
+                .unwrap_or_else(|| stored_path.to_string()),
+        );
+        match delete_file_state(&state, &tenant_id, &file_id, &path_key, true).await {
+            Ok(teardown) => {
+                chunks_replaced = teardown.chunks_deleted;
+                relationships_updated = teardown.relationships_deleted;
+                layers_updated.vector = true;
+                layers_updated.graph = true;
             }
+            Err(e) => tracing::warn!("Failed to clean up deleted file {}: {}", file_id, e),
         }
-        layers_updated.graph = true;
 
         // Update FileLog with deletion audit entry (soft delete - keep the log)
         let audit_entry = serde_json::json!({
@@ -1713,11 +2198,12 @@ pub async fn sync_file(
             "agent_id": request.agent_id
         });
 
-        let update_query = "UPDATE objects SET audit_trail = array::push(audit_trail, $entry), updated_at = time::now() WHERE type = 'FileLog' AND file_id = $file_id";
+        let update_query = "UPDATE objects SET audit_trail = array::push(audit_trail, $entry), updated_at = time::now() WHERE type = 'FileLog' AND file_id = $file_id AND tenant_id = $tenant_id";
         if state.db.client
             .query(update_query)
             .bind(("file_id", file_id.clone()))
             .bind(("entry", audit_entry))
+            .bind(("tenant_id", tenant_id.clone()))
             .await
             .is_ok()
         {
@@ -1731,6 +2217,9 @@ pub async fn sync_file(
             audit_entry_added: true,
             chunks_replaced,
             relationships_updated,
+            symbols_added: 0,
+            symbols_removed: 0,
+            symbols_changed: 0,
         }));
     }
 
@@ -1772,15 +2261,19 @@ pub async fn sync_file(
 
     // Resolve canonical path for storage and project inference
     let raw_file_path = file_path.to_string_lossy().to_string();
-    let storage_path = map_container_mount(&raw_file_path).unwrap_or_else(|| raw_file_path.clone());
-    let canonical_path = normalize_lookup_path(&storage_path);
+    let storage_path = state
+        .path_resolver
+        .to_host(&raw_file_path)
+        .unwrap_or_else(|| raw_file_path.clone());
+    let canonical_path = state.path_resolver.canonicalize(&storage_path);
 
     // If we didn't find an existing file_id, retry lookup with canonical path for metadata
     if existing_file_id.is_none() {
-        let canonical_query = "SELECT file_id, file_path FROM objects WHERE (type = 'FileLog' OR type = 'FileChunk') AND (file_path = $path OR file_path CONTAINS $path) LIMIT 1";
+        let canonical_query = "SELECT file_id, file_path FROM objects WHERE (type = 'FileLog' OR type = 'FileChunk') AND (file_path = $path OR file_path CONTAINS $path) AND tenant_id = $tenant_id LIMIT 1";
         if let Ok(mut response) = state.db.client
             .query(canonical_query)
             .bind(("path", canonical_path.clone()))
+            .bind(("tenant_id", tenant_id.clone()))
             .await
         {
             let values = take_json_values(&mut response, 0);
@@ -1806,7 +2299,6 @@ pub async fn sync_file(
     };
 
     let language = detect_language(&file_path);
-    let tenant_id = "default".to_string();
     let project_info = ensure_project_node_for_path(&state, &file_path, &storage_path, &tenant_id).await;
     let (project_id, project_node) = if let Some((project_id, project_node_id, project_path)) = project_info {
         (project_id, Some((project_node_id, project_path)))
@@ -1820,8 +2312,8 @@ pub async fn sync_file(
         .to_string();
 
     // Normalize any existing records for this path to avoid duplicate codebases
-    // CRITICAL: Filter by project_id to prevent cross-project contamination
-    let normalize_filelog_query = "UPDATE objects SET file_path = $path, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'FileLog' AND file_path CONTAINS $path AND project_id = $project_id";
+    // CRITICAL: Filter by project_id and tenant_id to prevent cross-project contamination
+    let normalize_filelog_query = "UPDATE objects SET file_path = $path, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'FileLog' AND file_path CONTAINS $path AND project_id = $project_id AND tenant_id = $tenant_id";
     let _ = state.db.client
         .query(normalize_filelog_query)
         .bind(("path", canonical_path.clone()))
@@ -1829,7 +2321,7 @@ pub async fn sync_file(
         .bind(("tenant_id", tenant_id.clone()))
         .await;
 
-    let normalize_chunk_query = "UPDATE objects SET file_path = $path, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'FileChunk' AND file_path CONTAINS $path AND project_id = $project_id";
+    let normalize_chunk_query = "UPDATE objects SET file_path = $path, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'FileChunk' AND file_path CONTAINS $path AND project_id = $project_id AND tenant_id = $tenant_id";
     let _ = state.db.client
         .query(normalize_chunk_query)
         .bind(("path", canonical_path.clone()))
@@ -1837,7 +2329,7 @@ pub async fn sync_file(
         .bind(("tenant_id", tenant_id.clone()))
         .await;
 
-    let normalize_symbol_query = "UPDATE objects SET path = $path, name = $name, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'Symbol' AND kind = 'file' AND path CONTAINS $path AND project_id = $project_id";
+    let normalize_symbol_query = "UPDATE objects SET path = $path, name = $name, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'Symbol' AND kind = 'file' AND path CONTAINS $path AND project_id = $project_id AND tenant_id = $tenant_id";
     let _ = state.db.client
         .query(normalize_symbol_query)
         .bind(("path", canonical_path.clone()))
@@ -1846,7 +2338,7 @@ pub async fn sync_file(
         .bind(("tenant_id", tenant_id.clone()))
         .await;
 
-    let normalize_filelog_ci = "UPDATE objects SET file_path = $path, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'FileLog' AND string::lowercase(file_path) CONTAINS string::lowercase($path) AND project_id = $project_id";
+    let normalize_filelog_ci = "UPDATE objects SET file_path = $path, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'FileLog' AND string::lowercase(file_path) CONTAINS string::lowercase($path) AND project_id = $project_id AND tenant_id = $tenant_id";
     let _ = state.db.client
         .query(normalize_filelog_ci)
         .bind(("path", canonical_path.clone()))
@@ -1854,7 +2346,7 @@ pub async fn sync_file(
         .bind(("tenant_id", tenant_id.clone()))
         .await;
 
-    let normalize_chunk_ci = "UPDATE objects SET file_path = $path, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'FileChunk' AND string::lowercase(file_path) CONTAINS string::lowercase($path) AND project_id = $project_id";
+    let normalize_chunk_ci = "UPDATE objects SET file_path = $path, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'FileChunk' AND string::lowercase(file_path) CONTAINS string::lowercase($path) AND project_id = $project_id AND tenant_id = $tenant_id";
     let _ = state.db.client
         .query(normalize_chunk_ci)
         .bind(("path", canonical_path.clone()))
@@ -1862,7 +2354,7 @@ pub async fn sync_file(
         .bind(("tenant_id", tenant_id.clone()))
         .await;
 
-    let normalize_symbol_ci = "UPDATE objects SET path = $path, name = $name, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'Symbol' AND kind = 'file' AND string::lowercase(path) CONTAINS string::lowercase($path) AND project_id = $project_id";
+    let normalize_symbol_ci = "UPDATE objects SET path = $path, name = $name, tenant_id = $tenant_id, updated_at = time::now() WHERE type = 'Symbol' AND kind = 'file' AND string::lowercase(path) CONTAINS string::lowercase($path) AND project_id = $project_id AND tenant_id = $tenant_id";
     let _ = state.db.client
         .query(normalize_symbol_ci)
         .bind(("path", canonical_path.clone()))
@@ -1877,12 +2369,14 @@ pub async fn sync_file(
           AND (type = 'Symbol' OR type = 'symbol' OR type = 'file' OR type = 'File')
           AND string::lowercase(path) = string::lowercase($path)
           AND project_id = $project_id
+          AND tenant_id = $tenant_id
         ORDER BY updated_at DESC
     "#;
     if let Ok(mut response) = state.db.client
         .query(dedupe_symbol_query)
         .bind(("path", canonical_path.clone()))
         .bind(("project_id", project_id.clone()))
+        .bind(("tenant_id", tenant_id.clone()))
         .await
     {
         let ids = take_json_values(&mut response, 0);
@@ -1895,10 +2389,11 @@ pub async fn sync_file(
                 .collect();
             if !delete_ids.is_empty() {
                 let delete_count = delete_ids.len();
-                let delete_query = "DELETE FROM objects WHERE id IN $ids";
+                let delete_query = "DELETE FROM objects WHERE id IN $ids AND tenant_id = $tenant_id";
                 let _ = state.db.client
                     .query(delete_query)
                     .bind(("ids", delete_ids))
+                    .bind(("tenant_id", tenant_id.clone()))
                     .await;
                 tracing::info!(
                     "Deduped file symbols for {} (kept {}, removed {})",
@@ -1910,11 +2405,12 @@ pub async fn sync_file(
         }
     }
     // Remove duplicate FileLogs for the same path (keep current file_id)
-    let dedupe_filelog_query = "DELETE FROM objects WHERE type = 'FileLog' AND file_path CONTAINS $path AND file_id != $file_id";
+    let dedupe_filelog_query = "DELETE FROM objects WHERE type = 'FileLog' AND file_path CONTAINS $path AND file_id != $file_id AND tenant_id = $tenant_id";
     let _ = state.db.client
         .query(dedupe_filelog_query)
         .bind(("path", canonical_path.clone()))
         .bind(("file_id", file_id.clone()))
+        .bind(("tenant_id", tenant_id.clone()))
         .await;
 
     let parser = CodebaseParser::new().map_err(|e| {
@@ -1924,12 +2420,20 @@ pub async fn sync_file(
         )
     })?;
 
-    let file_log = parser.parse_file(&file_path, &language).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": format!("Failed to parse file: {}", e) })),
-        )
-    })?;
+    // Edits reparse from the cached tree; creates and cache misses parse in full
+    let previous = if action == "edit" { state.parse_cache.take(&file_id) } else { None };
+    let (file_log, parsed, symbol_changes) = parser
+        .parse_content_incremental(&file_path, &language, content.clone(), previous)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": format!("Failed to parse file: {}", e) })),
+            )
+        })?;
+    match parsed {
+        Some(parsed) => state.parse_cache.insert(file_id.clone(), parsed),
+        None => state.parse_cache.remove(&file_id),
+    }
 
     // Extract symbol names and dependencies from parsed FileLog
     let symbol_names: Vec<String> = file_log.symbols.iter().map(|s| s.name.clone()).collect();
@@ -1953,10 +2457,11 @@ pub async fn sync_file(
     });
 
     // Check if FileLog exists
-    let check_query = "SELECT VALUE count() FROM objects WHERE type = 'FileLog' AND file_id = $file_id";
+    let check_query = "SELECT VALUE count() FROM objects WHERE type = 'FileLog' AND file_id = $file_id AND tenant_id = $tenant_id";
     let exists = match state.db.client
         .query(check_query)
         .bind(("file_id", file_id.clone()))
+        .bind(("tenant_id", tenant_id.clone()))
         .await
     {
         Ok(mut response) => {
@@ -1974,12 +2479,13 @@ pub async fn sync_file(
                 summary = $summary,
                 key_symbols = $symbols,
                 dependencies = $deps,
+                content_hash = $content_hash,
                 project_id = $project_id,
                 tenant_id = $tenant_id,
                 audit_trail = array::push(audit_trail, $entry),
                 change_count = change_count + 1,
                 updated_at = time::now()
-            WHERE type = 'FileLog' AND file_id = $file_id
+            WHERE type = 'FileLog' AND file_id = $file_id AND tenant_id = $tenant_id
         "#;
 
         if state.db.client
@@ -1989,6 +2495,7 @@ pub async fn sync_file(
             .bind(("summary", summary.clone()))
             .bind(("symbols", symbol_names.clone()))
             .bind(("deps", deps.clone()))
+            .bind(("content_hash", content_sha256(&content)))
             .bind(("entry", audit_entry))
             .bind(("project_id", project_id.clone()))
             .bind(("tenant_id", tenant_id.clone()))
@@ -2008,6 +2515,7 @@ pub async fn sync_file(
                 summary = $summary,
                 key_symbols = $symbols,
                 dependencies = $deps,
+                content_hash = $content_hash,
                 project_id = $project_id,
                 tenant_id = $tenant_id,
                 audit_trail = [$entry],
@@ -2025,6 +2533,7 @@ pub async fn sync_file(
             .bind(("summary", summary.clone()))
             .bind(("symbols", symbol_names.clone()))
             .bind(("deps", deps.clone()))
+            .bind(("content_hash", content_sha256(&content)))
             .bind(("entry", audit_entry))
             .bind(("project_id", project_id.clone()))
             .bind(("tenant_id", tenant_id.clone()))
@@ -2036,7 +2545,7 @@ pub async fn sync_file(
     }
 
     // Ensure a Symbol node exists for the file so it appears in the graph UI
-    let symbol_id = find_file_node_id(&state, &canonical_path, Some(&project_id), Some(&file_id)).await;
+    let symbol_id = find_file_node_id(&state, &tenant_id, &canonical_path, Some(&project_id), Some(&file_id)).await;
 
     if let Some(existing_id) = symbol_id {
         let update_symbol = r#"
@@ -2047,7 +2556,7 @@ pub async fn sync_file(
                 tenant_id = $tenant_id,
                 file_id = $file_id,
                 updated_at = time::now()
-            WHERE id = $id
+            WHERE id = $id AND tenant_id = $tenant_id
         "#;
         if state.db.client
             .query(update_symbol)
@@ -2099,7 +2608,7 @@ pub async fn sync_file(
     // --- GRAPH LAYER: Ensure directory/project relationships for the file node ---
     let parent_dir = file_path.parent().map(|p| {
         let raw = p.to_string_lossy().to_string();
-        map_container_mount(&raw).unwrap_or(raw)
+        state.path_resolver.to_host(&raw).unwrap_or(raw)
     });
     let project_node = project_node;
 
@@ -2116,10 +2625,10 @@ pub async fn sync_file(
         if let Some(dir_path) = parent_dir.as_ref() {
             let skip_dir = project_node
                 .as_ref()
-                .map(|(_, project_path)| normalize_lookup_path(project_path) == normalize_lookup_path(dir_path))
+                .map(|(_, project_path)| state.path_resolver.same_path(project_path, dir_path))
                 .unwrap_or(false);
             if !skip_dir {
-                let mut dir_node_id = find_directory_node_id(&state, dir_path).await;
+                let mut dir_node_id = find_directory_node_id(&state, &tenant_id, dir_path).await;
                 if dir_node_id.is_none() {
                     let dir_name = PathBuf::from(dir_path)
                         .file_name()
@@ -2175,36 +2684,149 @@ pub async fn sync_file(
         }
     }
 
-    // --- VECTOR LAYER: Re-chunk and generate embeddings ---
+    // --- VECTOR LAYER: Re-chunk and embed only chunks whose content changed ---
 
-    // First, delete existing chunks for this file
-    let delete_chunks_query = "DELETE FROM objects WHERE type = 'FileChunk' AND file_id = $file_id";
-    let _ = state.db.client
-        .query(delete_chunks_query)
+    // One chunk per top-level symbol, falling back to 100-token-overlap word windows
+    let chunking_service = ChunkingService::new();
+    let chunks =
+        chunking_service.chunk_file_by_symbols(&parser, &content, &language, &file_log.symbols);
+
+    // Load stored chunks so unchanged content keeps its row and embedding
+    let stored_query = "SELECT meta::id(id) AS chunk_id, content_hash, chunk_index, start_line, end_line, token_count FROM objects WHERE type = 'FileChunk' AND file_id = $file_id AND tenant_id = $tenant_id";
+    let stored_rows = match state.db.client
+        .query(stored_query)
         .bind(("file_id", file_id.clone()))
-        .await;
+        .bind(("tenant_id", tenant_id.clone()))
+        .await
+    {
+        Ok(mut response) => take_json_values(&mut response, 0),
+        Err(e) => {
+            tracing::warn!("Failed to load stored chunks for {}: {}", file_id, e);
+            Vec::new()
+        }
+    };
 
-    // Chunk the content with 100-token overlap
-    let chunking_service = ChunkingService::new();
-    let chunks = chunking_service.chunk_file(&content, &language);
+    let mut stored_chunks = Vec::new();
+    let mut stored_ids: HashMap<String, Vec<(String, i64)>> = HashMap::new();
+    for row in &stored_rows {
+        let (Some(chunk_id), Some(hash)) = (
+            row.get("chunk_id").and_then(|v| v.as_str()),
+            row.get("content_hash").and_then(|v| v.as_str()),
+        ) else {
+            continue;
+        };
+        let line = |key: &str| row.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        stored_chunks.push(ChunkData {
+            content: String::new(),
+            start_line: line("start_line"),
+            end_line: line("end_line"),
+            token_count: line("token_count"),
+            hash: hash.to_string(),
+            symbols: Vec::new(),
+            symbol_name: None,
+            symbol_kind: None,
+        });
+        let chunk_index = row.get("chunk_index").and_then(|v| v.as_i64()).unwrap_or(-1);
+        stored_ids
+            .entry(hash.to_string())
+            .or_default()
+            .push((chunk_id.to_string(), chunk_index));
+    }
+
+    let (changed, unchanged) = ChunkingService::diff_chunks(&stored_chunks, &chunks);
+    let mut unchanged_counts: HashMap<String, usize> = HashMap::new();
+    for hash in unchanged {
+        *unchanged_counts.entry(hash).or_insert(0) += 1;
+    }
 
-    // Generate embeddings and store chunks
+    // Pair each new chunk with a stored row of the same content, or queue it for writing
+    let mut to_write: Vec<(usize, &ChunkData)> = Vec::with_capacity(changed.len());
+    let mut moved: Vec<(String, usize, &ChunkData)> = Vec::new();
     for (idx, chunk) in chunks.iter().enumerate() {
-        let embedding = if state.embedding_service.is_enabled() {
-            state.embedding_service.generate_embedding(&chunk.content).await.ok()
-        } else {
-            None
+        let reuse = match unchanged_counts.get_mut(&chunk.hash) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                // Prefer the row already at this index so ids stay aligned with positions
+                stored_ids.get_mut(&chunk.hash).and_then(|ids| {
+                    let pos = ids
+                        .iter()
+                        .position(|(_, stored_index)| *stored_index == idx as i64)
+                        .unwrap_or(ids.len().saturating_sub(1));
+                    (!ids.is_empty()).then(|| ids.remove(pos))
+                })
+            }
+            _ => None,
         };
+        match reuse {
+            Some((chunk_id, stored_index)) => {
+                if stored_index != idx as i64 {
+                    moved.push((chunk_id, idx, chunk));
+                }
+            }
+            None => to_write.push((idx, chunk)),
+        }
+    }
+
+    // Whatever was not reused is stale
+    let stale_ids: Vec<String> = stored_ids
+        .into_values()
+        .flatten()
+        .map(|(chunk_id, _)| chunk_id)
+        .collect();
+    if !stale_ids.is_empty() {
+        let delete_chunks_query = "DELETE FROM objects WHERE type = 'FileChunk' AND file_id = $file_id AND tenant_id = $tenant_id AND meta::id(id) IN $ids";
+        if let Err(e) = state.db.client
+            .query(delete_chunks_query)
+            .bind(("file_id", file_id.clone()))
+            .bind(("tenant_id", tenant_id.clone()))
+            .bind(("ids", stale_ids.clone()))
+            .await
+        {
+            tracing::warn!("Failed to delete stale chunks for {}: {}", file_id, e);
+        }
+    }
+
+    // Unchanged content that shifted position keeps its embedding
+    for (chunk_id, idx, chunk) in moved {
+        let move_query = "UPDATE type::thing('objects', $id) SET chunk_index = $idx, start_line = $start, end_line = $end, updated_at = time::now() WHERE tenant_id = $tenant_id";
+        let _ = state.db.client
+            .query(move_query)
+            .bind(("id", chunk_id))
+            .bind(("tenant_id", tenant_id.clone()))
+            .bind(("idx", idx as i32))
+            .bind(("start", chunk.start_line as i32))
+            .bind(("end", chunk.end_line as i32))
+            .await;
+    }
+
+    tracing::debug!(
+        "Chunk diff for {}: {} changed, {} stale, {} total",
+        file_id,
+        to_write.len(),
+        stale_ids.len(),
+        chunks.len()
+    );
+
+    // Generate embeddings for changed chunks in as few round-trips as possible
+    let mut embeddings: Vec<Option<Vec<f32>>> = vec![None; to_write.len()];
+    if state.code_embedding_service.is_enabled() && !to_write.is_empty() {
+        let texts: Vec<String> = to_write.iter().map(|(_, c)| c.content.clone()).collect();
+        match embed_with_cache(&state.db, state.code_embedding_service.as_ref(), &texts).await {
+            Ok(batch) => embeddings = batch.into_iter().map(Some).collect(),
+            Err(e) => tracing::warn!("Failed to generate chunk embeddings: {}", e),
+        }
+    }
 
-        let chunk_id = Uuid::new_v4().to_string();
+    // Store changed chunks
+    for ((idx, chunk), embedding) in to_write.into_iter().zip(embeddings) {
+        let chunk_id = ChunkingService::chunk_id(&file_id, idx, &chunk.hash);
         let embedding_str = embedding
             .as_ref()
             .map(|e| format!("[{}]", e.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(", ")))
             .unwrap_or_else(|| "NONE".to_string());
 
         let insert_query = format!(r#"
-            CREATE objects SET
-                id = type::thing('objects', $id),
+            UPSERT type::thing('objects', $id) SET
                 type = 'FileChunk',
                 file_path = $path,
                 file_id = $file_id,
@@ -2215,6 +2837,9 @@ pub async fn sync_file(
                 content = $content,
                 content_hash = $hash,
                 language = $lang,
+                symbols = $symbols,
+                symbol_name = $symbol_name,
+                symbol_kind = $symbol_kind,
                 embedding = {},
                 project_id = $project_id,
                 tenant_id = $tenant_id,
@@ -2222,9 +2847,9 @@ pub async fn sync_file(
                 updated_at = time::now()
         "#, embedding_str);
 
-        if state.db.client
+        let written = state.db.client
             .query(&insert_query)
-            .bind(("id", chunk_id))
+            .bind(("id", chunk_id.clone()))
             .bind(("path", canonical_path.clone()))
             .bind(("file_id", file_id.clone()))
             .bind(("idx", idx as i32))
@@ -2234,12 +2859,16 @@ pub async fn sync_file(
             .bind(("content", chunk.content.clone()))
             .bind(("hash", chunk.hash.clone()))
             .bind(("lang", language.clone()))
+            .bind(("symbols", chunk.symbols.clone()))
+            .bind(("symbol_name", chunk.symbol_name.clone()))
+            .bind(("symbol_kind", chunk.symbol_kind.clone()))
             .bind(("project_id", project_id.clone()))
             .bind(("tenant_id", tenant_id.clone()))
             .await
-            .is_ok()
-        {
-            chunks_replaced += 1;
+            .and_then(surrealdb::Response::check);
+        match written {
+            Ok(_) => chunks_replaced += 1,
+            Err(e) => tracing::warn!("Failed to write chunk {} of {}: {}", chunk_id, file_id, e),
         }
     }
 
@@ -2253,33 +2882,36 @@ pub async fn sync_file(
     let relationship_tables = ["depends_on", "calls"];
     for table in &relationship_tables {
         let query = format!(
-            "DELETE FROM {} WHERE in IN (SELECT id FROM objects WHERE file_id = $file_id)",
+            "DELETE FROM {} WHERE in IN (SELECT id FROM objects WHERE file_id = $file_id AND tenant_id = $tenant_id)",
             table
         );
         let _ = state.db.client
             .query(&query)
             .bind(("file_id", file_id.clone()))
+            .bind(("tenant_id", tenant_id.clone()))
             .await;
     }
 
     // Create new dependency relationships
     for dep in &deps {
         // Try to find the target file by dependency name
-        let find_query = "SELECT VALUE id FROM objects WHERE type = 'FileLog' AND (file_path CONTAINS $dep OR key_symbols CONTAINS $dep) LIMIT 1";
+        let find_query = "SELECT VALUE id FROM objects WHERE type = 'FileLog' AND (file_path CONTAINS $dep OR key_symbols CONTAINS $dep) AND tenant_id = $tenant_id LIMIT 1";
         if let Ok(mut response) = state.db.client
             .query(find_query)
             .bind(("dep", dep.clone()))
+            .bind(("tenant_id", tenant_id.clone()))
             .await
         {
             let values = take_json_values(&mut response, 0);
             if let Some(target_id) = values.first().and_then(|v| v.as_str()) {
                 let relate_query = format!(
-                    "RELATE (SELECT id FROM objects WHERE type = 'FileLog' AND file_id = $file_id LIMIT 1)->depends_on->{} SET created_at = time::now()",
+                    "RELATE (SELECT id FROM objects WHERE type = 'FileLog' AND file_id = $file_id AND tenant_id = $tenant_id LIMIT 1)->depends_on->{} SET created_at = time::now()",
                     target_id
                 );
                 if state.db.client
                     .query(&relate_query)
                     .bind(("file_id", file_id.clone()))
+                    .bind(("tenant_id", tenant_id.clone()))
                     .await
                     .is_ok()
                 {
@@ -2310,5 +2942,1261 @@ pub async fn sync_file(
         audit_entry_added: true,
         chunks_replaced,
         relationships_updated,
+        symbols_added: symbol_changes.added,
+        symbols_removed: symbol_changes.removed,
+        symbols_changed: symbol_changes.changed,
+    }))
+}
+
+// ============================================================================
+// File Delete - Drop the FileChunk and FileLog objects of a removed file
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteFileRequest {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteFileResponse {
+    pub file_path: String,
+    pub objects_deleted: usize,
+    pub relationships_deleted: usize,
+}
+
+/// Hard-delete the FileChunk and FileLog objects stored for `path`, and their
+/// edges. Unlike the `delete` sync action no audit entry is kept, so this
+/// suits files that are gone for good. The file need not exist on disk.
+pub async fn delete_file(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Json(request): Json<DeleteFileRequest>,
+) -> Result<Json<DeleteFileResponse>, (StatusCode, Json<serde_json::Value>)> {
+    use crate::services::graph::DEFAULT_RELATIONS;
+
+    let storage_path = state
+        .path_resolver
+        .to_host(&request.path)
+        .unwrap_or_else(|| request.path.clone());
+    let canonical_path = state.path_resolver.canonicalize(&storage_path);
+
+    let delete_edges: String = DEFAULT_RELATIONS
+        .iter()
+        .map(|table| format!("DELETE {} WHERE in IN $ids OR out IN $ids;", table))
+        .collect::<Vec<_>>()
+        .join("\n");
+    // The CLI stores paths as given, the sync endpoint in canonical form
+    let query = format!(
+        r#"
+        LET $ids = (SELECT VALUE id FROM objects WHERE tenant_id = $tenant_id
+            AND (type = 'FileChunk' OR type = 'FileLog')
+            AND (file_path = $raw_path OR string::lowercase(string::replace(file_path, '/', '\\')) = $path));
+        RETURN count($ids);
+        RETURN count(SELECT id FROM {} WHERE in IN $ids OR out IN $ids);
+        RETURN array::distinct(SELECT VALUE file_id FROM objects WHERE id IN $ids);
+        {}
+        DELETE objects WHERE id IN $ids;
+        "#,
+        DEFAULT_RELATIONS.join(", "),
+        delete_edges
+    );
+    let mut response = state
+        .db
+        .client
+        .query(query)
+        .bind(("raw_path", request.path.clone()))
+        .bind(("path", state.path_resolver.lookup_key(&storage_path)))
+        .bind(("tenant_id", tenant.as_str().to_string()))
+        .await
+        .and_then(surrealdb::Response::check)
+        .map_err(|e| {
+            tracing::error!("Failed to delete file objects for {}: {}", request.path, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(
+                    serde_json::json!({ "error": format!("Failed to delete file objects: {}", e) }),
+                ),
+            )
+        })?;
+    let mut count = |index: usize| {
+        take_json_value(&mut response, index)
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize
+    };
+    let objects_deleted = count(1);
+    let relationships_deleted = count(2);
+    for file_id in take_json_values(&mut response, 3) {
+        if let Some(file_id) = file_id.as_str() {
+            state.parse_cache.remove(file_id);
+        }
+    }
+
+    Ok(Json(DeleteFileResponse {
+        file_path: canonical_path,
+        objects_deleted,
+        relationships_deleted,
     }))
 }
+
+/// What `delete_file_state` removed.
+struct FileTeardown {
+    objects_deleted: usize,
+    chunks_deleted: usize,
+    relationships_deleted: usize,
+}
+
+/// Delete every object carrying `file_id`, plus symbols stored only by path
+/// (`path_key` is a `PathResolver::lookup_key`), and every edge touching them.
+/// With `keep_file_log` the FileLog and its edges survive, so a soft delete
+/// keeps the file's audit trail.
+async fn delete_file_state(
+    state: &AppState,
+    tenant_id: &str,
+    file_id: &str,
+    path_key: &str,
+    keep_file_log: bool,
+) -> Result<FileTeardown, surrealdb::Error> {
+    use crate::services::graph::DEFAULT_RELATIONS;
+
+    let delete_edges: String = DEFAULT_RELATIONS
+        .iter()
+        .map(|table| format!("DELETE {} WHERE in IN $ids OR out IN $ids;", table))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let teardown = format!(
+        r#"
+        LET $ids = (SELECT VALUE id FROM objects WHERE tenant_id = $tenant_id AND (
+            file_id = $file_id OR (
+                (type = 'Symbol' OR type = 'symbol')
+                AND kind NOT IN ['project', 'directory']
+                AND string::lowercase(string::replace(path, '/', '\\')) = $path
+            )
+        ){});
+        RETURN count($ids);
+        RETURN count(SELECT id FROM {} WHERE in IN $ids OR out IN $ids);
+        RETURN count(SELECT id FROM objects WHERE id IN $ids AND type = 'FileChunk');
+        {}
+        DELETE objects WHERE id IN $ids;
+        "#,
+        if keep_file_log {
+            " AND type != 'FileLog'"
+        } else {
+            ""
+        },
+        DEFAULT_RELATIONS.join(", "),
+        delete_edges
+    );
+    let mut response = state
+        .db
+        .client
+        .query(teardown)
+        .bind(("file_id", file_id.to_string()))
+        .bind(("path", path_key.to_string()))
+        .bind(("tenant_id", tenant_id.to_string()))
+        .await
+        .and_then(surrealdb::Response::check)?;
+    let mut count = |index: usize| {
+        take_json_value(&mut response, index)
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize
+    };
+    state.parse_cache.remove(file_id);
+    Ok(FileTeardown {
+        objects_deleted: count(1),
+        relationships_deleted: count(2),
+        chunks_deleted: count(3),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteFileByIdResponse {
+    pub file_id: String,
+    pub file_path: Option<String>,
+    pub objects_deleted: usize,
+    pub chunks_deleted: usize,
+    pub relationships_deleted: usize,
+}
+
+/// Hard-delete everything stored for one file: its file node and symbols,
+/// FileChunks, FileLog and their edges. 404 when nothing carries `file_id`.
+pub async fn delete_file_by_id(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(file_id): Path<String>,
+) -> Result<Json<DeleteFileByIdResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let tenant_id = tenant.as_str().to_string();
+    let internal = |e: surrealdb::Error| {
+        tracing::error!("Failed to delete file {}: {}", file_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("Failed to delete file: {}", e) })),
+        )
+    };
+
+    // Symbols the parser stored without a file_id are found by the file's path
+    let lookup = "SELECT VALUE file_path ?? path FROM objects WHERE tenant_id = $tenant_id AND file_id = $file_id";
+    let mut response = state
+        .db
+        .client
+        .query(lookup)
+        .bind(("file_id", file_id.clone()))
+        .bind(("tenant_id", tenant_id.clone()))
+        .await
+        .map_err(internal)?;
+    let paths = take_json_values(&mut response, 0);
+    if paths.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "File not found", "file_id": file_id })),
+        ));
+    }
+    let file_path = paths.iter().find_map(|v| v.as_str()).map(str::to_string);
+    let path_key = file_path
+        .as_deref()
+        .map(|path| state.path_resolver.lookup_key(path))
+        .unwrap_or_default();
+
+    let teardown = delete_file_state(&state, &tenant_id, &file_id, &path_key, false)
+        .await
+        .map_err(internal)?;
+
+    Ok(Json(DeleteFileByIdResponse {
+        file_id,
+        file_path,
+        objects_deleted: teardown.objects_deleted,
+        chunks_deleted: teardown.chunks_deleted,
+        relationships_deleted: teardown.relationships_deleted,
+    }))
+}
+
+// ============================================================================
+// File Reindex - Drop everything stored for one file and rebuild it
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ReindexFileRequest {
+    pub path: String,
+    /// Reuse this file_id instead of looking one up by path
+    pub file_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReindexFileResponse {
+    pub file_id: String,
+    pub file_path: String,
+    pub objects_deleted: usize,
+    pub relationships_deleted: usize,
+    pub symbols_created: usize,
+    pub chunks_created: usize,
+    pub file_log_created: bool,
+    pub relationships_created: usize,
+}
+
+/// Stable file_id for a normalized path, shared with `sync_file`. Other
+/// tenants hash their id in too so two tenants never share chunk ids.
+fn file_id_for_path(tenant_id: &str, normalized: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    if tenant_id != DEFAULT_TENANT {
+        hasher.update(tenant_id.as_bytes());
+        hasher.update(b":");
+    }
+    hasher.update(normalized.as_bytes());
+    format!("file-{}", hex::encode(&hasher.finalize()[..16]))
+}
+
+/// SHA256 of a file's content, stored on its FileLog as `content_hash` so
+/// `amp diff` can spot files that changed since they were indexed.
+fn content_sha256(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Delete every object and edge stored for a file, then rebuild the file node,
+/// symbols, chunks, FileLog and relationships from the file on disk. Unlike
+/// `sync_file` nothing is diffed or reused, so it also repairs corrupted state.
+pub async fn reindex_file(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Json(request): Json<ReindexFileRequest>,
+) -> Result<Json<ReindexFileResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let internal = |message: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": message })),
+        )
+    };
+
+    let file_path = resolve_file_path(&request.path, &state)
+        .await
+        .map_err(|status| {
+            (
+                status,
+                Json(serde_json::json!({ "error": "File not found", "path": request.path })),
+            )
+        })?;
+    let raw_file_path = file_path.to_string_lossy().to_string();
+    let storage_path = state
+        .path_resolver
+        .to_host(&raw_file_path)
+        .unwrap_or_else(|| raw_file_path.clone());
+    let canonical_path = state.path_resolver.canonicalize(&storage_path);
+    let tenant_id = tenant.as_str().to_string();
+
+    let file_id = match request.file_id.clone() {
+        Some(file_id) => file_id,
+        None => {
+            let lookup = "SELECT VALUE file_id FROM objects WHERE (type = 'FileLog' OR type = 'FileChunk') AND file_path = $path AND tenant_id = $tenant_id LIMIT 1";
+            let existing = match state
+                .db
+                .client
+                .query(lookup)
+                .bind(("path", canonical_path.clone()))
+                .bind(("tenant_id", tenant_id.clone()))
+                .await
+            {
+                Ok(mut response) => take_json_values(&mut response, 0)
+                    .first()
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                Err(_) => None,
+            };
+            existing.unwrap_or_else(|| file_id_for_path(&tenant_id, &canonical_path))
+        }
+    };
+
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|err| internal(format!("Failed to read file: {}", err)))?;
+    let language = detect_language(&file_path);
+    let parser =
+        CodebaseParser::new().map_err(|e| internal(format!("Failed to create parser: {}", e)))?;
+    let file_log = parser
+        .parse_file(&file_path, &language)
+        .map_err(|e| internal(format!("Failed to parse file: {}", e)))?;
+
+    // --- Tear down: everything carrying the file_id, plus symbols stored by path ---
+    let path_key = state.path_resolver.lookup_key(&storage_path);
+    let teardown = delete_file_state(&state, &tenant_id, &file_id, &path_key, false)
+        .await
+        .map_err(|e| internal(format!("Failed to clear file state: {}", e)))?;
+    let (objects_deleted, relationships_deleted) =
+        (teardown.objects_deleted, teardown.relationships_deleted);
+
+    // --- Rebuild: file node ---
+    let project_info =
+        ensure_project_node_for_path(&state, &file_path, &storage_path, &tenant_id).await;
+    let (project_id, project_node_id) = match project_info {
+        Some((project_id, project_node_id, _)) => (project_id, Some(project_node_id)),
+        None => ("default".to_string(), None),
+    };
+    let file_name = state.path_resolver.basename(&storage_path).to_string();
+    let file_node_id = Uuid::new_v4().to_string();
+    let create_file_node = r#"
+        CREATE type::thing('objects', $id) SET
+            type = 'Symbol',
+            kind = 'file',
+            name = $name,
+            path = $path,
+            language = $lang,
+            project_id = $project_id,
+            tenant_id = $tenant_id,
+            file_id = $file_id,
+            created_at = time::now(),
+            updated_at = time::now()
+    "#;
+    state
+        .db
+        .client
+        .query(create_file_node)
+        .bind(("id", file_node_id.clone()))
+        .bind(("name", file_name))
+        .bind(("path", canonical_path.clone()))
+        .bind(("lang", language.clone()))
+        .bind(("project_id", project_id.clone()))
+        .bind(("tenant_id", tenant_id.clone()))
+        .bind(("file_id", file_id.clone()))
+        .await
+        .and_then(surrealdb::Response::check)
+        .map_err(|e| internal(format!("Failed to create file node: {}", e)))?;
+
+    let mut relationships_created = 0;
+    let parent_dir = file_path.parent().map(|p| {
+        let raw = p.to_string_lossy().to_string();
+        state.path_resolver.to_host(&raw).unwrap_or(raw)
+    });
+    let dir_node_id = match parent_dir {
+        Some(dir) => find_directory_node_id(&state, &tenant_id, &dir).await,
+        None => None,
+    };
+    for container in project_node_id.iter().chain(dir_node_id.iter()) {
+        for (source, target) in [(container, &file_node_id), (&file_node_id, container)] {
+            if ensure_defined_in_relationship(&state, source, target).await {
+                relationships_created += 1;
+            }
+        }
+    }
+
+    // --- Rebuild: symbols, linked to the file node both ways ---
+    let mut symbols_created = 0;
+    for parsed in &file_log.symbols {
+        let signature = format!("{}: {}", parsed.symbol_type, parsed.name);
+        let symbol = serde_json::json!({
+            "type": "symbol",
+            "tenant_id": tenant_id,
+            "project_id": project_id,
+            "file_id": file_id,
+            "name": parsed.name,
+            "kind": parsed.symbol_type,
+            "path": canonical_path,
+            "language": language,
+            "visibility": parsed.visibility,
+            "start_line": parsed.start_line + 1,
+            "end_line": parsed.end_line + 1,
+            "signature": signature,
+            "documentation": format!("{} {} at line {} in {}", parsed.symbol_type, parsed.name, parsed.start_line + 1, storage_path),
+        });
+        let create_symbol = r#"
+            LET $symbol = type::thing('objects', $id);
+            LET $file = type::thing('objects', $file_node);
+            CREATE $symbol CONTENT $data;
+            UPDATE $symbol SET created_at = time::now(), updated_at = time::now();
+            RELATE $symbol->defined_in->$file SET created_at = time::now();
+            RELATE $file->defined_in->$symbol SET created_at = time::now();
+        "#;
+        let created = state
+            .db
+            .client
+            .query(create_symbol)
+            .bind(("id", Uuid::new_v4().to_string()))
+            .bind(("file_node", file_node_id.clone()))
+            .bind(("data", symbol))
+            .await
+            .and_then(surrealdb::Response::check);
+        match created {
+            Ok(_) => {
+                symbols_created += 1;
+                relationships_created += 2;
+            }
+            Err(e) => tracing::warn!("Failed to recreate symbol {}: {}", parsed.name, e),
+        }
+    }
+
+    // --- Rebuild: chunks ---
+    let chunks = ChunkingService::new().chunk_file_by_symbols(
+        &parser,
+        &content,
+        &language,
+        &file_log.symbols,
+    );
+    let mut embeddings: Vec<Option<Vec<f32>>> = vec![None; chunks.len()];
+    if state.code_embedding_service.is_enabled() && !chunks.is_empty() {
+        let texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
+        match embed_with_cache(&state.db, state.code_embedding_service.as_ref(), &texts).await {
+            Ok(batch) => embeddings = batch.into_iter().map(Some).collect(),
+            Err(e) => tracing::warn!("Failed to generate chunk embeddings: {}", e),
+        }
+    }
+    let mut chunks_created = 0;
+    for (idx, (chunk, embedding)) in chunks.iter().zip(embeddings).enumerate() {
+        let embedding_str = embedding
+            .map(|e| {
+                format!(
+                    "[{}]",
+                    e.iter()
+                        .map(|f| f.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+            .unwrap_or_else(|| "NONE".to_string());
+        let create_chunk = format!(
+            r#"
+            UPSERT type::thing('objects', $id) SET
+                type = 'FileChunk',
+                file_path = $path,
+                file_id = $file_id,
+                chunk_index = $idx,
+                start_line = $start,
+                end_line = $end,
+                token_count = $tokens,
+                content = $content,
+                content_hash = $hash,
+                language = $lang,
+                symbols = $symbols,
+                symbol_name = $symbol_name,
+                symbol_kind = $symbol_kind,
+                embedding = {},
+                project_id = $project_id,
+                tenant_id = $tenant_id,
+                created_at = time::now(),
+                updated_at = time::now()
+        "#,
+            embedding_str
+        );
+        if state
+            .db
+            .client
+            .query(&create_chunk)
+            .bind(("id", ChunkingService::chunk_id(&file_id, idx, &chunk.hash)))
+            .bind(("path", canonical_path.clone()))
+            .bind(("file_id", file_id.clone()))
+            .bind(("idx", idx as i32))
+            .bind(("start", chunk.start_line as i32))
+            .bind(("end", chunk.end_line as i32))
+            .bind(("tokens", chunk.token_count as i32))
+            .bind(("content", chunk.content.clone()))
+            .bind(("hash", chunk.hash.clone()))
+            .bind(("lang", language.clone()))
+            .bind(("symbols", chunk.symbols.clone()))
+            .bind(("symbol_name", chunk.symbol_name.clone()))
+            .bind(("symbol_kind", chunk.symbol_kind.clone()))
+            .bind(("project_id", project_id.clone()))
+            .bind(("tenant_id", tenant_id.clone()))
+            .await
+            .and_then(surrealdb::Response::check)
+            .is_ok()
+        {
+            chunks_created += 1;
+        }
+    }
+
+    // --- Rebuild: FileLog and its dependency edges ---
+    let symbol_names: Vec<String> = file_log.symbols.iter().map(|s| s.name.clone()).collect();
+    let deps = file_log.dependencies.imports.clone();
+    let summary = if symbol_names.is_empty() {
+        format!("{} file", language)
+    } else {
+        let top_symbols: Vec<&str> = symbol_names.iter().take(5).map(|s| s.as_str()).collect();
+        format!("{} file with: {}", language, top_symbols.join(", "))
+    };
+    let audit_entry = serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "action": "reindex",
+        "summary": "Rebuilt from the file on disk",
+    });
+    let create_file_log = r#"
+        CREATE type::thing('objects', $id) SET
+            type = 'FileLog',
+            file_path = $path,
+            file_id = $file_id,
+            summary = $summary,
+            key_symbols = $symbols,
+            dependencies = $deps,
+            content_hash = $content_hash,
+            project_id = $project_id,
+            tenant_id = $tenant_id,
+            audit_trail = [$entry],
+            change_count = 1,
+            created_at = time::now(),
+            updated_at = time::now()
+    "#;
+    let file_log_id = Uuid::new_v4().to_string();
+    let file_log_created = state
+        .db
+        .client
+        .query(create_file_log)
+        .bind(("id", file_log_id.clone()))
+        .bind(("path", canonical_path.clone()))
+        .bind(("file_id", file_id.clone()))
+        .bind(("summary", summary))
+        .bind(("symbols", symbol_names))
+        .bind(("deps", deps.clone()))
+        .bind(("content_hash", content_sha256(&content)))
+        .bind(("entry", audit_entry))
+        .bind(("project_id", project_id.clone()))
+        .bind(("tenant_id", tenant_id.clone()))
+        .await
+        .and_then(surrealdb::Response::check)
+        .is_ok();
+
+    if file_log_created {
+        for dep in &deps {
+            let relate_query = r#"
+                LET $target = (SELECT VALUE id FROM objects WHERE type = 'FileLog' AND file_id != $file_id AND tenant_id = $tenant_id AND (file_path CONTAINS $dep OR key_symbols CONTAINS $dep) LIMIT 1)[0];
+                IF $target != NONE {
+                    RELATE (type::thing('objects', $log))->depends_on->$target SET created_at = time::now();
+                };
+                RETURN $target != NONE;
+            "#;
+            if let Ok(mut response) = state
+                .db
+                .client
+                .query(relate_query)
+                .bind(("file_id", file_id.clone()))
+                .bind(("tenant_id", tenant_id.clone()))
+                .bind(("dep", dep.clone()))
+                .bind(("log", file_log_id.clone()))
+                .await
+            {
+                if take_json_value(&mut response, 2).and_then(|v| v.as_bool()) == Some(true) {
+                    relationships_created += 1;
+                }
+            }
+        }
+    }
+
+    tracing::info!(
+        "Reindexed {}: removed {} objects and {} edges, created {} symbols, {} chunks, {} edges",
+        canonical_path,
+        objects_deleted,
+        relationships_deleted,
+        symbols_created,
+        chunks_created,
+        relationships_created
+    );
+
+    Ok(Json(ReindexFileResponse {
+        file_id,
+        file_path: canonical_path,
+        objects_deleted,
+        relationships_deleted,
+        symbols_created,
+        chunks_created,
+        file_log_created,
+        relationships_created,
+    }))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state_with_embedding, test_config, CountingEmbedding};
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_sync_file_reuses_cached_chunk_embeddings() {
+        let embedding = CountingEmbedding::default();
+        let calls = embedding.texts_embedded.clone();
+        let state = memory_state_with_embedding(test_config(), Box::new(embedding)).await;
+
+        // Identical content under two paths embeds once
+        let dir = tempfile::tempdir().unwrap();
+        let content = "def greet(name):\n    return f'hello {name}'\n";
+        let mut replaced = Vec::new();
+        for name in ["cached.py", "copy.py"] {
+            let file = dir.path().join(name);
+            std::fs::write(&file, content).unwrap();
+            let request = FileSyncRequest {
+                path: file.to_string_lossy().to_string(),
+                action: "edit".to_string(),
+                summary: "sync".to_string(),
+                run_id: None,
+                agent_id: None,
+            };
+            let Json(response) = sync_file(State(state.clone()), Tenant::default(), Json(request)).await.unwrap();
+            replaced.push(response.chunks_replaced);
+        }
+
+        assert!(replaced[0] > 0);
+        assert_eq!(replaced[0], replaced[1]);
+        assert_eq!(calls.load(Ordering::SeqCst), replaced[0]);
+
+        let analytics = state.analytics_service.get_analytics().await.unwrap();
+        assert_eq!(
+            analytics.indexing_stats.embedding_cache_hits,
+            replaced[1] as i64
+        );
+    }
+
+    async fn stored_chunk_hashes(state: &AppState, file_id: &str) -> Vec<String> {
+        let mut response = state
+            .db
+            .client
+            .query("SELECT chunk_index, content_hash FROM objects WHERE type = 'FileChunk' AND file_id = $file_id ORDER BY chunk_index ASC")
+            .bind(("file_id", file_id.to_string()))
+            .await
+            .unwrap();
+        take_json_values(&mut response, 0)
+            .iter()
+            .filter_map(|row| row.get("content_hash")?.as_str().map(str::to_string))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sync_file_only_rewrites_changed_chunks() {
+        let embedding = CountingEmbedding::default();
+        let calls = embedding.texts_embedded.clone();
+        let state = memory_state_with_embedding(test_config(), Box::new(embedding)).await;
+
+        // 1200 words -> three 500-word chunks with 100-word overlap
+        let mut words: Vec<String> = (0..1200).map(|i| format!("word{}", i)).collect();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("chunks.txt");
+        std::fs::write(&file, words.join(" ")).unwrap();
+
+        let request = || FileSyncRequest {
+            path: file.to_string_lossy().to_string(),
+            action: "edit".to_string(),
+            summary: "sync".to_string(),
+            run_id: None,
+            agent_id: None,
+        };
+
+        let Json(first) = sync_file(State(state.clone()), Tenant::default(), Json(request())).await.unwrap();
+        assert_eq!(first.chunks_replaced, 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        let before = stored_chunk_hashes(&state, &first.file_id).await;
+
+        // Edit a word only the last chunk covers
+        words[1100] = "edited".to_string();
+        std::fs::write(&file, words.join(" ")).unwrap();
+
+        let Json(second) = sync_file(State(state.clone()), Tenant::default(), Json(request())).await.unwrap();
+        assert_eq!(second.chunks_replaced, 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        let after = stored_chunk_hashes(&state, &second.file_id).await;
+        assert_eq!(after.len(), 3);
+        assert_eq!(after[..2], before[..2]);
+        assert_ne!(after[2], before[2]);
+
+        // Unchanged content is a no-op
+        let Json(third) = sync_file(State(state.clone()), Tenant::default(), Json(request())).await.unwrap();
+        assert_eq!(third.chunks_replaced, 0);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(stored_chunk_hashes(&state, &third.file_id).await, after);
+    }
+
+    #[tokio::test]
+    async fn test_sync_file_leaves_other_tenants_rows_untouched() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("shared.py");
+        std::fs::write(&file, "def shared():\n    return 1\n").unwrap();
+        let sync = |tenant: &str, action: &str| {
+            let request = FileSyncRequest {
+                path: file.to_string_lossy().to_string(),
+                action: action.to_string(),
+                summary: "sync".to_string(),
+                run_id: None,
+                agent_id: None,
+            };
+            sync_file(State(state.clone()), Tenant(tenant.to_string()), Json(request))
+        };
+        let rows = |tenant: &str| {
+            let state = state.clone();
+            let tenant = tenant.to_string();
+            async move {
+                let mut response = state
+                    .db
+                    .client
+                    .query("SELECT meta::id(id) AS id, type, file_id, updated_at FROM objects WHERE tenant_id = $tenant_id AND type IN ['FileLog', 'FileChunk', 'Symbol'] AND kind != 'project' ORDER BY id")
+                    .bind(("tenant_id", tenant))
+                    .await
+                    .unwrap();
+                take_json_values(&mut response, 0)
+            }
+        };
+
+        let Json(b) = sync("tenant-b", "create").await.unwrap();
+        let b_rows = rows("tenant-b").await;
+        assert!(b_rows.iter().any(|row| row["type"] == "FileChunk"));
+
+        let Json(a) = sync("tenant-a", "create").await.unwrap();
+        assert_ne!(a.file_id, b.file_id);
+        assert!(a.chunks_replaced > 0);
+        assert!(!rows("tenant-a").await.is_empty());
+        assert_eq!(rows("tenant-b").await, b_rows);
+
+        let Json(deleted) = sync("tenant-a", "delete").await.unwrap();
+        assert_eq!(deleted.chunks_replaced, a.chunks_replaced);
+        assert_eq!(rows("tenant-b").await, b_rows);
+    }
+
+    async fn symbol_names(state: &AppState, file_path: &str) -> Vec<String> {
+        let mut response = state
+            .db
+            .client
+            .query("SELECT VALUE name FROM objects WHERE type = 'symbol' AND string::lowercase(string::replace(path, '/', '\\\\')) = $path ORDER BY name")
+            .bind(("path", state.path_resolver.lookup_key(file_path)))
+            .await
+            .unwrap();
+        take_json_values(&mut response, 0)
+            .iter()
+            .filter_map(|name| name.as_str().map(str::to_string))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_reindex_file_drops_stale_symbols_and_rebuilds_current_ones() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        let path = file.to_string_lossy().to_string();
+        std::fs::write(&file, "pub fn alpha() {}\n\npub fn beta() {}\n").unwrap();
+
+        // A symbol left behind by an older index run, keyed only by its path
+        state
+            .db
+            .client
+            .query("CREATE objects SET type = 'symbol', kind = 'function', name = 'stale_fn', path = $path, tenant_id = 'default'")
+            .bind(("path", path.clone()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let reindex = || {
+            let request = ReindexFileRequest {
+                path: path.clone(),
+                file_id: None,
+            };
+            reindex_file(
+                State(state.clone()),
+                Tenant("default".to_string()),
+                Json(request),
+            )
+        };
+        let Json(first) = reindex().await.unwrap();
+        assert_eq!(first.objects_deleted, 1);
+        assert_eq!(first.symbols_created, 2);
+        assert!(first.chunks_created > 0);
+        assert!(first.file_log_created);
+        assert_eq!(symbol_names(&state, &path).await, vec!["alpha", "beta"]);
+
+        std::fs::write(&file, "pub fn alpha() {}\n\npub fn gamma() {}\n").unwrap();
+        let Json(second) = reindex().await.unwrap();
+        assert_eq!(second.file_id, first.file_id);
+        // file node, two symbols, chunk(s) and the FileLog from the first run
+        assert_eq!(second.objects_deleted, 4 + first.chunks_created);
+        assert_eq!(second.relationships_deleted, first.relationships_created);
+        assert_eq!(symbol_names(&state, &path).await, vec!["alpha", "gamma"]);
+
+        let mut response = state
+            .db
+            .client
+            .query("RETURN count(SELECT id FROM objects WHERE type = 'FileLog' AND file_id = $file_id); RETURN count(SELECT id FROM defined_in)")
+            .bind(("file_id", second.file_id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(
+            take_json_values(&mut response, 0),
+            vec![serde_json::json!(1)]
+        );
+        assert_eq!(
+            take_json_values(&mut response, 1),
+            vec![serde_json::json!(second.relationships_created)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_regenerate_ai_file_log_replaces_the_stored_summary() {
+        use axum::routing::post;
+
+        let reply = serde_json::json!({
+            "summary_markdown": "# FILE_LOG v1\nRegenerated",
+            "purpose": "Adds numbers",
+            "key_symbols": ["function:add"],
+            "dependencies": [],
+            "notes": "Small math helper."
+        });
+        let app = axum::Router::new().route(
+            "/api/chat",
+            post(move || async move {
+                Json(serde_json::json!({ "message": { "content": reply.to_string() } }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        let settings = crate::models::settings::SettingsConfig {
+            index_provider: "ollama".to_string(),
+            ollama_url: format!("http://{}", addr),
+            ..Default::default()
+        };
+        state
+            .settings_service
+            .save_settings(settings)
+            .await
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("math.rs");
+        std::fs::write(&file, "pub fn add(a: i32, b: i32) -> i32 { a + b }\n").unwrap();
+        let path = file.to_string_lossy().to_string();
+        state
+            .db
+            .client
+            .query("CREATE objects:math_log CONTENT { type: 'FileLog', file_path: $path, file_id: 'f-math', summary: 'Symbol-based summary', tenant_id: 'default' }")
+            .bind(("path", path.clone()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let Json(response) = regenerate_ai_file_log(
+            State(state.clone()),
+            Tenant("default".to_string()),
+            Json(RegenerateAiLogRequest { path }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.file_id, "f-math");
+        assert_eq!(response.file_log.purpose.as_deref(), Some("Adds numbers"));
+
+        let mut stored = state
+            .db
+            .client
+            .query("SELECT summary, summary_markdown, purpose, notes, content_hash FROM objects:math_log")
+            .await
+            .unwrap();
+        let stored = take_json_values(&mut stored, 0);
+        assert_eq!(stored[0]["summary"], "# FILE_LOG v1\nRegenerated");
+        assert_eq!(stored[0]["summary_markdown"], stored[0]["summary"]);
+        assert_eq!(stored[0]["purpose"], "Adds numbers");
+        assert_eq!(stored[0]["notes"], "Small math helper.");
+        assert!(stored[0]["content_hash"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_delete_file_by_id_removes_symbols_and_sync_delete_keeps_the_log() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        let dir = tempfile::tempdir().unwrap();
+        let remaining = |file_id: String| {
+            let state = state.clone();
+            async move {
+                let mut response = state
+                    .db
+                    .client
+                    .query("SELECT VALUE type FROM objects WHERE file_id = $file_id ORDER BY type")
+                    .bind(("file_id", file_id))
+                    .await
+                    .unwrap();
+                take_json_values(&mut response, 0)
+            }
+        };
+        let index = |name: &str| {
+            let file = dir.path().join(name);
+            std::fs::write(&file, "pub fn alpha() {}\n\npub fn beta() {}\n").unwrap();
+            let request = ReindexFileRequest {
+                path: file.to_string_lossy().to_string(),
+                file_id: None,
+            };
+            reindex_file(
+                State(state.clone()),
+                Tenant("default".to_string()),
+                Json(request),
+            )
+        };
+
+        let Json(indexed) = index("hard.rs").await.unwrap();
+        let Json(deleted) = delete_file_by_id(
+            State(state.clone()),
+            Tenant("default".to_string()),
+            Path(indexed.file_id.clone()),
+        )
+        .await
+        .unwrap();
+        // file node, two symbols, chunk(s) and the FileLog
+        assert_eq!(deleted.objects_deleted, 4 + indexed.chunks_created);
+        assert_eq!(deleted.chunks_deleted, indexed.chunks_created);
+        assert_eq!(deleted.relationships_deleted, indexed.relationships_created);
+        assert!(remaining(indexed.file_id.clone()).await.is_empty());
+        assert!(symbol_names(&state, &indexed.file_path).await.is_empty());
+
+        let missing = delete_file_by_id(
+            State(state.clone()),
+            Tenant("default".to_string()),
+            Path(indexed.file_id),
+        )
+        .await;
+        assert_eq!(missing.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let Json(indexed) = index("soft.rs").await.unwrap();
+        let request = FileSyncRequest {
+            path: indexed.file_path.clone(),
+            action: "delete".to_string(),
+            summary: "removed".to_string(),
+            run_id: None,
+            agent_id: None,
+        };
+        let Json(synced) = sync_file(State(state.clone()), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(synced.file_id, indexed.file_id);
+        assert_eq!(synced.chunks_replaced, indexed.chunks_created);
+        assert!(symbol_names(&state, &indexed.file_path).await.is_empty());
+        assert_eq!(
+            remaining(indexed.file_id).await,
+            vec![serde_json::json!("FileLog")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_file_drops_chunks_and_file_log_for_the_path() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        state
+            .db
+            .client
+            .query(
+                "CREATE objects:log CONTENT { type: 'FileLog', file_path: '/repo/src/gone.rs', file_id: 'f1', tenant_id: 'default' };
+                 CREATE objects:chunk CONTENT { type: 'FileChunk', file_path: $synced_path, file_id: 'f1', tenant_id: 'default' };
+                 CREATE objects:kept CONTENT { type: 'FileChunk', file_path: '/repo/src/kept.rs', file_id: 'f2', tenant_id: 'default' };
+                 RELATE objects:chunk->defined_in->objects:kept;",
+            )
+            .bind(("synced_path", r"\repo\src\gone.rs"))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let request = DeleteFileRequest {
+            path: "/repo/src/gone.rs".to_string(),
+        };
+        let Json(response) = delete_file(State(state.clone()), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(response.objects_deleted, 2);
+        assert_eq!(response.relationships_deleted, 1);
+
+        let mut remaining = state
+            .db
+            .client
+            .query("SELECT VALUE file_id FROM objects; RETURN count(SELECT id FROM defined_in)")
+            .await
+            .unwrap();
+        let file_ids: Vec<String> = remaining.take(0).unwrap();
+        assert_eq!(file_ids, vec!["f2"]);
+        let edges: Option<usize> = remaining.take(1).unwrap();
+        assert_eq!(edges, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_object_history_lists_sync_audit_trail_newest_first() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("history.py");
+        for (action, summary, body) in [
+            ("create", "first", "a = 1\n"),
+            ("edit", "second", "a = 2\n"),
+            ("edit", "third", "a = 3\n"),
+        ] {
+            std::fs::write(&file, body).unwrap();
+            let request = FileSyncRequest {
+                path: file.to_string_lossy().to_string(),
+                action: action.to_string(),
+                summary: summary.to_string(),
+                run_id: Some("run-1".to_string()),
+                agent_id: None,
+            };
+            let Json(_) = sync_file(State(state.clone()), Tenant::default(), Json(request))
+                .await
+                .unwrap();
+        }
+
+        let mut response = state
+            .db
+            .client
+            .query("SELECT VALUE meta::id(id) FROM objects WHERE type = 'FileLog' AND file_path CONTAINS 'history.py'; CREATE objects:plain SET type = 'note', tenant_id = 'default';")
+            .await
+            .unwrap();
+        let log_ids: Vec<String> = response.take(0).unwrap();
+        assert_eq!(log_ids.len(), 1);
+
+        let history = |id: &str, limit: Option<usize>| {
+            crate::handlers::objects::get_object_history(
+                State(state.clone()),
+                Tenant::default(),
+                Path(id.to_string()),
+                Query(crate::handlers::objects::ObjectHistoryQuery { limit }),
+            )
+        };
+        let Json(full) = history(&log_ids[0], None).await.unwrap();
+        let summaries: Vec<&str> = full
+            .entries
+            .iter()
+            .map(|entry| entry.summary.as_deref().unwrap_or_default())
+            .collect();
+        assert_eq!(summaries, vec!["third", "second", "first"]);
+        assert_eq!(full.entries[0].action, "edit");
+        assert_eq!(full.entries[2].action, "create");
+        assert_eq!(full.entries[0].run_id.as_deref(), Some("run-1"));
+
+        let Json(limited) = history(&log_ids[0], Some(2)).await.unwrap();
+        assert_eq!(limited.entries.len(), 2);
+        assert_eq!(limited.entries[0].summary.as_deref(), Some("third"));
+
+        // Objects without an audit trail have an empty history, unknown ones 404
+        let Json(plain) = history("plain", None).await.unwrap();
+        assert!(plain.entries.is_empty());
+        assert_eq!(
+            history("missing", None).await.unwrap_err(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_content_slices_lines_and_extracts_symbols() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        state
+            .db
+            .client
+            .query(
+                "CREATE objects:c0 CONTENT { type: 'FileChunk', file_path: '/repo/src/math.rs', chunk_index: 0, start_line: 1, end_line: 4, content: 'pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\n', tenant_id: 'default' };
+                 CREATE objects:c1 CONTENT { type: 'FileChunk', file_path: '/repo/src/math.rs', chunk_index: 1, start_line: 5, end_line: 8, content: 'pub fn mul(a: i32, b: i32) -> i32 {\n    let product = a * b;\n    product\n}\n', tenant_id: 'default' };
+                 CREATE objects:s1 CONTENT { type: 'symbol', name: 'mul', path: '/repo/src/math.rs', start_line: 5, end_line: 5, tenant_id: 'default' };",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let path = || Path("/repo/src/math.rs".to_string());
+
+        let query = FileContentQuery {
+            start_line: Some(2),
+            end_line: Some(3),
+            ..Default::default()
+        };
+        let Json(slice) = get_file_content(
+            State(state.clone()),
+            Tenant::default(),
+            path(),
+            Query(query),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slice.content, "    a + b\n}");
+        assert_eq!((slice.start_line, slice.end_line), (Some(2), Some(3)));
+        assert_eq!(slice.chunk_ids.len(), 1);
+
+        let query = FileContentQuery {
+            symbol: Some("mul".to_string()),
+            ..Default::default()
+        };
+        let Json(symbol) = get_file_content(
+            State(state.clone()),
+            Tenant::default(),
+            path(),
+            Query(query),
+        )
+        .await
+        .unwrap();
+        assert!(symbol.content.starts_with("pub fn mul"));
+        assert!(symbol.content.ends_with("product\n}"));
+        assert_eq!((symbol.start_line, symbol.end_line), (Some(5), Some(8)));
+
+        let query = FileContentQuery {
+            symbol: Some("div".to_string()),
+            ..Default::default()
+        };
+        let (status, Json(body)) =
+            get_file_content(State(state), Tenant::default(), path(), Query(query))
+                .await
+                .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["available_symbols"], serde_json::json!(["add", "mul"]));
+    }
+
+    #[tokio::test]
+    async fn test_file_hashes_report_content_hash_of_reindexed_file() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("hashed.rs");
+        std::fs::write(&file, "pub fn hashed() {}\n").unwrap();
+        let request = ReindexFileRequest {
+            path: file.to_string_lossy().to_string(),
+            file_id: None,
+        };
+        let Json(reindexed) = reindex_file(State(state.clone()), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+        assert!(reindexed.file_log_created);
+
+        let query = FileHashesQuery { project_id: None };
+        let Json(hashes) = get_file_hashes(State(state), Tenant::default(), Query(query))
+            .await
+            .unwrap();
+        let hashed = hashes
+            .iter()
+            .find(|log| log["file_path"].as_str().unwrap().ends_with("hashed.rs"))
+            .unwrap();
+        assert_eq!(
+            hashed["content_hash"],
+            content_sha256("pub fn hashed() {}\n").as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_log_blame_is_cached_on_the_stored_file_log() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .current_dir(dir.path())
+                .args(["-c", "user.name=Ada", "-c", "user.email=ada@example.com"])
+                .args(args)
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        if !git(&["init", "-q"]) {
+            // No git in PATH
+            return;
+        }
+        let file = dir.path().join("blamed.rs");
+        let path = file.to_string_lossy().to_string();
+        std::fs::write(&file, "pub fn blamed() {}\n").unwrap();
+        assert!(git(&["add", "blamed.rs"]));
+        assert!(git(&["commit", "-qm", "add blamed"]));
+        let request = ReindexFileRequest {
+            path: path.clone(),
+            file_id: None,
+        };
+        let Json(reindexed) = reindex_file(State(state.clone()), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+        assert!(reindexed.file_log_created);
+
+        let file_log = |include_blame: bool| {
+            get_file_log(
+                State(state.clone()),
+                Tenant::default(),
+                Path(path.clone()),
+                Query(GetFileLogQuery { include_blame }),
+            )
+        };
+        let Json(plain) = file_log(false).await.unwrap();
+        assert!(plain.file_log.blame.is_empty());
+        let Json(blamed) = file_log(true).await.unwrap();
+        assert_eq!(blamed.file_log.blame.len(), 1);
+        assert_eq!(blamed.file_log.blame[0].symbol, "blamed");
+        assert_eq!(blamed.file_log.blame[0].author, "Ada");
+
+        // A second request is served from the stored FileLog
+        state
+            .db
+            .client
+            .query("UPDATE objects SET blame[0].author = 'Cached' WHERE type = 'FileLog'")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let Json(cached) = file_log(true).await.unwrap();
+        assert_eq!(cached.file_log.blame[0].author, "Cached");
+
+        // Editing the file invalidates the cache
+        std::fs::write(&file, "pub fn blamed() { 1 }\n").unwrap();
+        let Json(edited) = file_log(true).await.unwrap();
+        assert_eq!(edited.file_log.blame[0].author, "Not Committed Yet");
+    }
+}
@@ -4,6 +4,9 @@ use serde_json::Value;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
+use crate::handlers::cache::close_open_block;
+use crate::surreal_json::take_json_values;
+use crate::tenant::Tenant;
 use crate::AppState;
 
 /// Default TTL for connections (10 minutes)
@@ -36,6 +39,8 @@ pub struct ConnectionInfo {
     pub last_heartbeat: String,
     pub connected_at: String,
     pub expires_at: String,
+    /// Seconds left before the connection expires without another heartbeat
+    pub ttl_remaining_secs: i64,
 }
 
 fn extract_datetime(value: Option<&Value>) -> String {
@@ -66,7 +71,9 @@ fn extract_connection_info(value: &Value) -> Option<ConnectionInfo> {
                 .to_string()
         })?;
 
+    let expires_at = extract_datetime(value.get("expires_at"));
     Some(ConnectionInfo {
+        ttl_remaining_secs: ttl_remaining_secs(&expires_at),
         connection_id,
         agent_id: value.get("agent_id")?.as_str()?.to_string(),
         agent_name: value.get("agent_name")?.as_str()?.to_string(),
@@ -79,10 +86,19 @@ fn extract_connection_info(value: &Value) -> Option<ConnectionInfo> {
             .to_string(),
         last_heartbeat: extract_datetime(value.get("last_heartbeat")),
         connected_at: extract_datetime(value.get("connected_at")),
-        expires_at: extract_datetime(value.get("expires_at")),
+        expires_at,
     })
 }
 
+fn ttl_remaining_secs(expires_at: &str) -> i64 {
+    chrono::DateTime::parse_from_rfc3339(expires_at)
+        .map(|expires_at| {
+            (expires_at.with_timezone(&chrono::Utc) - chrono::Utc::now()).num_seconds()
+        })
+        .unwrap_or(0)
+        .max(0)
+}
+
 /// Request to send a heartbeat
 #[derive(Debug, Deserialize)]
 pub struct HeartbeatRequest {
@@ -102,6 +118,33 @@ pub struct DisconnectRequest {
     pub connection_id: String,
 }
 
+/// Request to release a connection when its agent session ends
+#[derive(Debug, Deserialize)]
+pub struct ReleaseRequest {
+    pub connection_id: Option<String>,
+    /// Session run to finish; left untouched unless it is still running
+    pub run_id: Option<String>,
+    /// Final run status: "completed" (default), "cancelled" or "failed"
+    #[serde(default = "default_release_status")]
+    pub run_status: String,
+    /// Appended to the run's outputs when it is finished
+    pub summary: Option<String>,
+}
+
+fn default_release_status() -> String {
+    "completed".to_string()
+}
+
+/// Result of releasing a connection
+#[derive(Debug, Serialize)]
+pub struct ReleaseResponse {
+    pub connection_released: bool,
+    /// Status the run was moved to; None when it had already finished
+    pub run_status: Option<String>,
+    /// Open cache blocks closed for the run and session scopes
+    pub closed_blocks: Vec<String>,
+}
+
 /// Register a new agent connection
 ///
 /// Creates a connection record with TTL-based expiry.
@@ -201,6 +244,7 @@ pub async fn register_connection(
                     last_heartbeat: now.to_rfc3339(),
                     connected_at: now.to_rfc3339(),
                     expires_at: expires_at.to_rfc3339(),
+                    ttl_remaining_secs: ttl_seconds.max(0),
                 }),
             ))
         }
@@ -311,9 +355,126 @@ pub async fn disconnect(
     }
 }
 
+/// Release a connection at the end of an agent session
+///
+/// Deletes the connection record, finishes the session run if it is still
+/// running, and closes the open cache blocks of its run and session scopes.
+pub async fn release(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Json(request): Json<ReleaseRequest>,
+) -> Result<Json<ReleaseResponse>, (StatusCode, String)> {
+    if !matches!(
+        request.run_status.as_str(),
+        "completed" | "cancelled" | "failed"
+    ) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Invalid run_status '{}'. Expected completed, cancelled or failed",
+                request.run_status
+            ),
+        ));
+    }
+    let run_id = request
+        .run_id
+        .as_deref()
+        .map(|id| id.trim().trim_start_matches("objects:").to_string())
+        .filter(|id| !id.is_empty());
+
+    tracing::info!(
+        "Releasing connection {:?} (run {:?} -> {})",
+        request.connection_id,
+        run_id,
+        request.run_status
+    );
+
+    // An already finished run is left alone so explicit completions win
+    let output_clause = if request.summary.is_some() {
+        ", outputs = array::append(outputs ?? [], $output)"
+    } else {
+        ""
+    };
+    let mut query = "RETURN array::len((DELETE agent_connections WHERE connection_id = $connection_id RETURN BEFORE));".to_string();
+    if run_id.is_some() {
+        query.push_str(&format!(
+            "UPDATE type::thing('objects', $run_id) SET status = $status{}, updated_at = time::now()
+                WHERE type = 'run' AND status = 'running' RETURN status;",
+            output_clause
+        ));
+    }
+    let output = serde_json::json!({
+        "type": "response",
+        "content": request.summary,
+        "metadata": { "kind": "summary", "status": request.run_status }
+    });
+
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("connection_id", request.connection_id.clone()))
+            .bind(("run_id", run_id.clone()))
+            .bind(("status", request.run_status.clone()))
+            .bind(("output", output)),
+    )
+    .await;
+
+    let mut response = match result {
+        Ok(Ok(response)) => response
+            .check()
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        Ok(Err(e)) => {
+            tracing::error!("Failed to release connection: {}", e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+        }
+        Err(_) => {
+            tracing::error!("Timeout releasing connection");
+            return Err((
+                StatusCode::GATEWAY_TIMEOUT,
+                "Timeout releasing connection".to_string(),
+            ));
+        }
+    };
+    let connection_released = take_json_values(&mut response, 0)
+        .first()
+        .and_then(|deleted| deleted.as_u64())
+        .unwrap_or(0)
+        > 0;
+    let run_status = if run_id.is_some() {
+        take_json_values(&mut response, 1)
+            .first()
+            .and_then(|run| run.get("status"))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    } else {
+        None
+    };
+
+    let mut closed_blocks = Vec::new();
+    if let Some(run_id) = &run_id {
+        for scope_id in [format!("run:{}", run_id), format!("session:{}", run_id)] {
+            match close_open_block(&state, &tenant, &scope_id).await {
+                Ok(Some(block_id)) => closed_blocks.push(block_id),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to close cache block for {}: {}", scope_id, e),
+            }
+        }
+    }
+
+    Ok(Json(ReleaseResponse {
+        connection_released,
+        run_status,
+        closed_blocks,
+    }))
+}
+
 /// List active connections
 ///
-/// Returns connections where expires_at > now (TTL not expired).
+/// Returns connections where expires_at > now (TTL not expired), deleting
+/// expired rows first so stale agents drop out without a cleanup call.
 /// Optionally filter by run_id or project_id.
 #[derive(Debug, Deserialize)]
 pub struct ListConnectionsQuery {
@@ -329,6 +490,10 @@ pub async fn list_connections(
 ) -> Result<Json<Vec<ConnectionInfo>>, StatusCode> {
     let include_expired = query.include_expired.unwrap_or(false);
 
+    if !include_expired {
+        expire_stale_connections(&state).await;
+    }
+
     // Build query with filters
     let mut conditions = Vec::new();
 
@@ -390,6 +555,15 @@ pub async fn list_connections(
     }
 }
 
+async fn expire_stale_connections(state: &AppState) {
+    let query = "DELETE FROM agent_connections WHERE expires_at < time::now()";
+    match timeout(Duration::from_secs(5), state.db.client.query(query)).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => tracing::warn!("Failed to expire stale connections: {}", e),
+        Err(_) => tracing::warn!("Timeout expiring stale connections"),
+    }
+}
+
 /// Cleanup expired connections (optional background task endpoint)
 pub async fn cleanup_expired(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     tracing::info!("Cleaning up expired connections");
@@ -416,3 +590,145 @@ pub async fn cleanup_expired(State(state): State<AppState>) -> Result<Json<Value
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+    use axum::extract::Query;
+
+    async fn register(state: &AppState, agent_name: &str, ttl_seconds: i64) -> ConnectionInfo {
+        let request = RegisterConnectionRequest {
+            agent_id: agent_name.to_string(),
+            agent_name: agent_name.to_string(),
+            run_id: Some(format!("run-{}", agent_name)),
+            project_id: None,
+            ttl_seconds: Some(ttl_seconds),
+        };
+        let (_, Json(info)) = register_connection(State(state.clone()), Json(request))
+            .await
+            .unwrap();
+        info
+    }
+
+    fn list_query(include_expired: bool) -> Query<ListConnectionsQuery> {
+        Query(ListConnectionsQuery {
+            run_id: None,
+            project_id: None,
+            include_expired: Some(include_expired),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_connections_expires_stale_rows_and_reports_ttl() {
+        let state = memory_state(test_config()).await;
+        register(&state, "agent-a", 600).await;
+        register(&state, "agent-b", 600).await;
+        register(&state, "agent-stale", 0).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let Json(active) = list_connections(State(state.clone()), list_query(false))
+            .await
+            .unwrap();
+        let mut names: Vec<&str> = active.iter().map(|c| c.agent_name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["agent-a", "agent-b"]);
+        assert!(active.iter().all(|c| c.ttl_remaining_secs > 590));
+
+        // The stale row was deleted on read, not just filtered out
+        let Json(all) = list_connections(State(state.clone()), list_query(true))
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        let analytics = state.analytics_service.get_analytics().await.unwrap();
+        assert_eq!(analytics.active_agents.count, 2);
+        assert!(analytics
+            .active_agents
+            .agents
+            .iter()
+            .any(|a| a.agent_name == "agent-a" && a.run_id.as_deref() == Some("run-agent-a")));
+    }
+
+    #[tokio::test]
+    async fn test_disconnected_agent_drops_out_of_active_list() {
+        let state = memory_state(test_config()).await;
+        let info = register(&state, "agent-a", 600).await;
+
+        disconnect(
+            State(state.clone()),
+            Json(DisconnectRequest {
+                connection_id: info.connection_id,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let Json(active) = list_connections(State(state.clone()), list_query(false))
+            .await
+            .unwrap();
+        assert!(active.is_empty());
+        let analytics = state.analytics_service.get_analytics().await.unwrap();
+        assert_eq!(analytics.active_agents.count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_release_finishes_running_run_and_closes_its_block() {
+        let state = memory_state(test_config()).await;
+        let info = register(&state, "agent-a", 600).await;
+        state
+            .db
+            .client
+            .query("CREATE type::thing('objects', 'run-agent-a') CONTENT { type: 'run', status: 'running' }")
+            .await
+            .unwrap();
+        state
+            .db
+            .client
+            .query(
+                "CREATE cache_block SET tenant_id = 'default', scope_id = 'run:run-agent-a', \
+                 sequence = 1, status = 'open', items = [], token_count = 0, created_at = time::now()",
+            )
+            .await
+            .unwrap();
+
+        let request = || ReleaseRequest {
+            connection_id: Some(info.connection_id.clone()),
+            run_id: Some("run-agent-a".to_string()),
+            run_status: "cancelled".to_string(),
+            summary: Some("MCP session ended".to_string()),
+        };
+        let Json(released) = release(State(state.clone()), Tenant::default(), Json(request()))
+            .await
+            .unwrap();
+        assert!(released.connection_released);
+        assert_eq!(released.run_status.as_deref(), Some("cancelled"));
+        assert_eq!(released.closed_blocks.len(), 1);
+
+        let mut response = state
+            .db
+            .client
+            .query("SELECT status, outputs FROM type::thing('objects', 'run-agent-a'); SELECT VALUE status FROM cache_block")
+            .await
+            .unwrap();
+        let run = take_json_values(&mut response, 0).remove(0);
+        assert_eq!(run["status"], "cancelled");
+        assert_eq!(run["outputs"][0]["content"], "MCP session ended");
+        assert_eq!(
+            take_json_values(&mut response, 1),
+            vec![Value::from("closed")]
+        );
+        let Json(all) = list_connections(State(state.clone()), list_query(true))
+            .await
+            .unwrap();
+        assert!(all.is_empty());
+
+        // A second release leaves the finished run alone
+        let Json(again) = release(State(state.clone()), Tenant::default(), Json(request()))
+            .await
+            .unwrap();
+        assert!(!again.connection_released);
+        assert_eq!(again.run_status, None);
+        assert!(again.closed_blocks.is_empty());
+    }
+}
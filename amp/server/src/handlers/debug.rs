@@ -0,0 +1,135 @@
+use crate::{surreal_json::take_json_values, AppState};
+use axum::{extract::State, http::StatusCode, response::Json};
+use serde::Serialize;
+use std::time::Instant;
+use tokio::time::{timeout, Duration};
+
+/// Budget for each probe; `amp doctor` gives up on the request after 5s.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(4);
+
+#[derive(Debug, Serialize)]
+pub struct DbHealth {
+    pub status: String,
+    pub latency_ms: u64,
+    /// Project nodes created by `amp index` or the first file sync
+    pub indexed_projects: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbeddingHealth {
+    pub enabled: bool,
+    pub provider: String,
+    pub model: String,
+    pub dimension: usize,
+    /// A test embedding came back with the configured dimension
+    pub ok: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Round-trip a query to SurrealDB and count indexed projects.
+pub async fn db_health(
+    State(state): State<AppState>,
+) -> Result<Json<DbHealth>, (StatusCode, Json<serde_json::Value>)> {
+    let started = Instant::now();
+    let query = "RETURN count(SELECT id FROM objects WHERE type IN ['Symbol', 'symbol'] AND kind = 'project')";
+    let result = timeout(PROBE_TIMEOUT, state.db.client.query(query))
+        .await
+        .map_err(|_| "timed out".to_string())
+        .and_then(|response| response.map_err(|e| e.to_string()));
+
+    match result {
+        Ok(mut response) => Ok(Json(DbHealth {
+            status: "ok".to_string(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            indexed_projects: take_json_values(&mut response, 0)
+                .first()
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0) as usize,
+        })),
+        Err(e) => {
+            tracing::error!("Database health check failed: {}", e);
+            Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "status": "error", "error": e })),
+            ))
+        }
+    }
+}
+
+/// Embed a short test string with the prose embedding service.
+pub async fn embedding_health(State(state): State<AppState>) -> Json<EmbeddingHealth> {
+    let service = state.embedding_service.current();
+    let mut health = EmbeddingHealth {
+        enabled: service.is_enabled(),
+        provider: state.config.embedding_provider.clone(),
+        model: service.model(),
+        dimension: service.dimension(),
+        ok: false,
+        latency_ms: 0,
+        error: None,
+    };
+    if !health.enabled {
+        health.error = Some("Embedding provider is disabled".to_string());
+        return Json(health);
+    }
+
+    let started = Instant::now();
+    let result = timeout(PROBE_TIMEOUT, service.generate_embedding("amp doctor")).await;
+    health.latency_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(Ok(embedding)) if embedding.len() == health.dimension => health.ok = true,
+        Ok(Ok(embedding)) => {
+            health.error = Some(format!(
+                "Expected {} dimensions, provider returned {}",
+                health.dimension,
+                embedding.len()
+            ))
+        }
+        Ok(Err(e)) => health.error = Some(e.to_string()),
+        Err(_) => health.error = Some("Test embedding timed out".to_string()),
+    }
+    Json(health)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        memory_state, memory_state_with_embedding, test_config, CountingEmbedding,
+    };
+
+    #[tokio::test]
+    async fn test_db_health_counts_projects() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query("CREATE objects:proj CONTENT { type: 'symbol', kind: 'project', project_id: 'p1', tenant_id: 'default' }")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let Json(health) = db_health(State(state)).await.unwrap();
+        assert_eq!(health.status, "ok");
+        assert_eq!(health.indexed_projects, 1);
+    }
+
+    #[tokio::test]
+    async fn test_embedding_health_reports_disabled_and_working_providers() {
+        let Json(disabled) = embedding_health(State(memory_state(test_config()).await)).await;
+        assert!(!disabled.enabled);
+        assert!(!disabled.ok);
+        assert!(disabled.error.is_some());
+
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        let Json(working) = embedding_health(State(state)).await;
+        assert!(working.ok);
+        assert_eq!(working.model, "counting");
+        assert_eq!(working.error, None);
+    }
+}
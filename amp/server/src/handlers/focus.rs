@@ -1,4 +1,8 @@
-use axum::{extract::State, http::StatusCode, response::Json};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
 use serde::Deserialize;
 use serde_json::Value;
 use tokio::time::{timeout, Duration};
@@ -14,6 +18,7 @@ pub enum FocusAction {
     Set,
     Complete,
     End,
+    History,
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,6 +39,21 @@ pub struct FocusRequest {
     pub project_id: Option<String>,
     #[serde(default)]
     pub include_expired: Option<bool>,
+    /// RFC3339 lower bound for history entries
+    #[serde(default)]
+    pub since: Option<String>,
+    /// RFC3339 upper bound for history entries
+    #[serde(default)]
+    pub until: Option<String>,
+}
+
+/// Filters for `GET /v1/focus/sessions`
+#[derive(Debug, Default, Deserialize)]
+pub struct FocusSessionsQuery {
+    pub run_id: Option<String>,
+    pub project_id: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
 }
 
 fn now_rfc3339() -> String {
@@ -94,6 +114,188 @@ async fn update_run_object(
     }
 }
 
+async fn run_project_id(state: &AppState, run_id: &str) -> Option<String> {
+    get_run_object(state, run_id).await.ok().and_then(|run| {
+        run.get("project_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    })
+}
+
+/// Persist a focus transition for the run timeline. Failures are logged, not
+/// returned, so the run object update remains the source of truth.
+async fn record_focus_event(state: &AppState, run_id: &str, event: &str, details: Value) {
+    // Drop nulls so absent details are stored as NONE rather than NULL
+    let mut data = serde_json::Map::new();
+    if let Value::Object(details) = details {
+        data.extend(details.into_iter().filter(|(_, value)| !value.is_null()));
+    }
+
+    let query = "CREATE focus_event SET run = type::thing('objects', $run_id), run_id = $run_id, event = $event, project_id = $data.project_id, title = $data.title, plan = $data.plan, summary = $data.summary, files_changed = $data.files_changed";
+    let result: Result<Result<surrealdb::Response, _>, _> = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("data", Value::Object(data)))
+            .bind(("run_id", run_id.to_string()))
+            .bind(("event", event.to_string())),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(response)) => {
+            if let Err(e) = response.check() {
+                tracing::warn!("Failed to record focus event for run {}: {}", run_id, e);
+            }
+        }
+        Ok(Err(e)) => tracing::warn!("Failed to record focus event for run {}: {}", run_id, e),
+        Err(_) => tracing::warn!("Timeout recording focus event for run {}", run_id),
+    }
+}
+
+async fn load_focus_events(
+    state: &AppState,
+    run_id: Option<&str>,
+    project_id: Option<&str>,
+) -> Result<Vec<Value>, (StatusCode, String)> {
+    let (field, value) = match (run_id, project_id) {
+        (Some(run_id), _) => ("run_id", run_id),
+        (None, Some(project_id)) => ("project_id", project_id),
+        (None, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "run_id or project_id required for history".to_string(),
+            ))
+        }
+    };
+
+    let query = format!(
+        "SELECT run_id, event, title, plan, summary, files_changed, created_at FROM focus_event WHERE {} = $value ORDER BY created_at ASC",
+        field
+    );
+    let result: Result<Result<surrealdb::Response, _>, _> = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("value", value.to_string())),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(mut response)) => Ok(take_json_values(&mut response, 0)),
+        Ok(Err(e)) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(_) => Err((
+            StatusCode::GATEWAY_TIMEOUT,
+            "Timeout loading focus history".to_string(),
+        )),
+    }
+}
+
+fn parse_time(value: Option<&Value>) -> Option<chrono::DateTime<chrono::Utc>> {
+    value
+        .and_then(|v| v.as_str())
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+fn close_entry(entry: &mut Value, status: &str, ended_at: Option<&Value>) {
+    entry["status"] = Value::String(status.to_string());
+    entry["ended_at"] = ended_at.cloned().unwrap_or(Value::Null);
+    if let (Some(start), Some(end)) = (parse_time(entry.get("started_at")), parse_time(ended_at)) {
+        let millis = (end - start).num_milliseconds().max(0);
+        entry["duration_secs"] = serde_json::json!(millis as f64 / 1000.0);
+    }
+}
+
+/// Pair ordered focus events into timeline entries. A `set` opens an entry that
+/// is closed by the next `complete`, `end`, or `set` on the same run.
+fn build_focus_history(events: &[Value]) -> Vec<Value> {
+    let mut entries: Vec<Value> = Vec::new();
+    let mut open: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+    for event in events {
+        let run_id = event
+            .get("run_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let at = event.get("created_at");
+
+        match event.get("event").and_then(|v| v.as_str()) {
+            Some("set") => {
+                if let Some(index) = open.remove(&run_id) {
+                    close_entry(&mut entries[index], "replaced", at);
+                }
+                open.insert(run_id.clone(), entries.len());
+                entries.push(serde_json::json!({
+                    "run_id": run_id,
+                    "title": event.get("title"),
+                    "plan": event.get("plan"),
+                    "status": "active",
+                    "started_at": at,
+                    "ended_at": null,
+                    "duration_secs": null,
+                    "summary": null,
+                    "files_changed": null,
+                }));
+            }
+            Some("complete") => {
+                let index = match open.remove(&run_id) {
+                    Some(index) => index,
+                    None => {
+                        // Completion without a recorded start (e.g. focus set before history existed)
+                        entries.push(serde_json::json!({
+                            "run_id": run_id,
+                            "title": event.get("title"),
+                            "plan": event.get("plan"),
+                            "started_at": null,
+                            "duration_secs": null,
+                        }));
+                        entries.len() - 1
+                    }
+                };
+                let entry = &mut entries[index];
+                close_entry(entry, "completed", at);
+                entry["summary"] = event.get("summary").cloned().unwrap_or(Value::Null);
+                entry["files_changed"] = event.get("files_changed").cloned().unwrap_or(Value::Null);
+                if entry.get("title").is_none_or(Value::is_null) {
+                    entry["title"] = event.get("title").cloned().unwrap_or(Value::Null);
+                }
+            }
+            Some("end") => {
+                if let Some(index) = open.remove(&run_id) {
+                    close_entry(&mut entries[index], "ended", at);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+fn parse_bound(
+    value: Option<&str>,
+    name: &str,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, (StatusCode, String)> {
+    value
+        .map(|raw| {
+            chrono::DateTime::parse_from_rfc3339(raw)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .map_err(|e| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        format!("Invalid {} timestamp '{}': {}", name, raw, e),
+                    )
+                })
+        })
+        .transpose()
+}
+
 pub async fn handle_focus(
     State(state): State<AppState>,
     Json(input): Json<FocusRequest>,
@@ -204,6 +406,23 @@ pub async fn handle_focus(
             }
 
             update_run_object(&state, &run_id, payload).await?;
+
+            let project_id = match &input.project_id {
+                Some(project_id) => Some(project_id.clone()),
+                None => run_project_id(&state, &run_id).await,
+            };
+            record_focus_event(
+                &state,
+                &run_id,
+                "set",
+                serde_json::json!({
+                    "project_id": project_id,
+                    "title": title,
+                    "plan": input.plan,
+                }),
+            )
+            .await;
+
             Ok(Json(serde_json::json!({ "ok": true, "message": "Focus set", "run_id": run_id })))
         }
         FocusAction::Complete => {
@@ -263,6 +482,26 @@ pub async fn handle_focus(
             });
 
             update_run_object(&state, &run_id, payload).await?;
+
+            let project_id = input.project_id.clone().or_else(|| {
+                run.get("project_id")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            });
+            record_focus_event(
+                &state,
+                &run_id,
+                "complete",
+                serde_json::json!({
+                    "project_id": project_id,
+                    "title": title,
+                    "plan": input.plan,
+                    "summary": summary,
+                    "files_changed": input.files_changed,
+                }),
+            )
+            .await;
+
             Ok(Json(serde_json::json!({ "ok": true, "message": "Focus marked complete", "run_id": run_id })))
         }
         FocusAction::End => {
@@ -277,7 +516,259 @@ pub async fn handle_focus(
                 "updated_at": now_rfc3339(),
             });
             update_run_object(&state, &run_id, payload).await?;
+
+            let project_id = match &input.project_id {
+                Some(project_id) => Some(project_id.clone()),
+                None => run_project_id(&state, &run_id).await,
+            };
+            record_focus_event(
+                &state,
+                &run_id,
+                "end",
+                serde_json::json!({ "project_id": project_id }),
+            )
+            .await;
+
             Ok(Json(serde_json::json!({ "ok": true, "message": "Session ended", "run_id": run_id })))
         }
+        FocusAction::History => {
+            let since = parse_bound(input.since.as_deref(), "since")?;
+            let until = parse_bound(input.until.as_deref(), "until")?;
+            let run_id = input.run_id.as_deref().map(normalize_run_id);
+
+            let events =
+                load_focus_events(&state, run_id.as_deref(), input.project_id.as_deref()).await?;
+            let entries: Vec<Value> = build_focus_history(&events)
+                .into_iter()
+                .filter(|entry| {
+                    let at = parse_time(entry.get("started_at"))
+                        .or_else(|| parse_time(entry.get("ended_at")));
+                    match at {
+                        Some(at) => {
+                            since.is_none_or(|since| at >= since)
+                                && until.is_none_or(|until| at <= until)
+                        }
+                        None => since.is_none() && until.is_none(),
+                    }
+                })
+                .collect();
+
+            Ok(Json(serde_json::json!({
+                "run_id": run_id,
+                "project_id": input.project_id,
+                "count": entries.len(),
+                "entries": entries,
+            })))
+        }
+    }
+}
+
+/// Active focus sessions across all agents: runs whose focus is still
+/// active and which have not been ended, most recently updated first.
+pub async fn list_focus_sessions(
+    State(state): State<AppState>,
+    Query(query): Query<FocusSessionsQuery>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+    let run_id = query.run_id.as_deref().map(normalize_run_id);
+
+    let mut conditions = vec![
+        "type = 'run'",
+        "focus.status = 'active'",
+        "status != 'completed'",
+    ];
+    if run_id.is_some() {
+        conditions.push("id = type::thing('objects', $run_id)");
+    }
+    if query.project_id.is_some() {
+        conditions.push("project_id = $project_id");
+    }
+    let where_clause = conditions.join(" AND ");
+
+    let sql = format!(
+        "SELECT VALUE {{ session_id: record::id(id), agent_name: provenance.agent, project_id: project_id, current_task: focus.title, created_at: created_at ?? focus.started_at, last_updated_at: updated_at }} FROM (SELECT id, provenance, project_id, focus, created_at, updated_at FROM objects WHERE {} ORDER BY updated_at DESC LIMIT {} START {}); \
+         SELECT count() AS total FROM objects WHERE {} GROUP ALL",
+        where_clause, limit, offset, where_clause
+    );
+    let mut q = state.db.client.query(sql);
+    if let Some(run_id) = run_id {
+        q = q.bind(("run_id", run_id));
+    }
+    if let Some(project_id) = &query.project_id {
+        q = q.bind(("project_id", project_id.clone()));
+    }
+
+    match timeout(Duration::from_secs(5), q).await {
+        Ok(Ok(mut response)) => {
+            let sessions = take_json_values(&mut response, 0);
+            let total = take_json_values(&mut response, 1)
+                .first()
+                .and_then(|row| row["total"].as_u64())
+                .unwrap_or(0);
+            Ok(Json(serde_json::json!({
+                "sessions": sessions,
+                "total": total,
+                "limit": limit,
+                "offset": offset,
+            })))
+        }
+        Ok(Err(e)) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(_) => Err((
+            StatusCode::GATEWAY_TIMEOUT,
+            "Timeout listing focus sessions".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    fn request(action: FocusAction, run_id: &str) -> FocusRequest {
+        FocusRequest {
+            action,
+            run_id: Some(run_id.to_string()),
+            title: None,
+            plan: None,
+            summary: None,
+            files_changed: None,
+            project_id: None,
+            include_expired: None,
+            since: None,
+            until: None,
+        }
+    }
+
+    async fn focus(state: &AppState, input: FocusRequest) -> Value {
+        let Json(response) = handle_focus(State(state.clone()), Json(input))
+            .await
+            .unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_history_returns_completed_focuses_with_durations() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query("CREATE type::thing('objects', 'run-1') CONTENT { type: 'run', project_id: 'proj-a', status: 'running' }")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        for n in 1..=3 {
+            let mut set = request(FocusAction::Set, "run-1");
+            set.title = Some(format!("Task {}", n));
+            focus(&state, set).await;
+
+            let mut complete = request(FocusAction::Complete, "run-1");
+            complete.summary = Some(format!("Finished task {}", n));
+            complete.files_changed = Some(vec![format!("src/task_{}.rs", n)]);
+            focus(&state, complete).await;
+        }
+
+        let history = focus(&state, request(FocusAction::History, "run-1")).await;
+        let entries = history["entries"].as_array().unwrap();
+        assert_eq!(history["count"], 3);
+        for (n, entry) in entries.iter().enumerate() {
+            assert_eq!(entry["title"], format!("Task {}", n + 1));
+            assert_eq!(entry["status"], "completed");
+            assert_eq!(entry["summary"], format!("Finished task {}", n + 1));
+            assert!(entry["duration_secs"].as_f64().unwrap() >= 0.0);
+        }
+
+        // Project-scoped history uses the run's project when none is passed
+        let mut by_project = request(FocusAction::History, "");
+        by_project.run_id = None;
+        by_project.project_id = Some("proj-a".to_string());
+        assert_eq!(focus(&state, by_project).await["count"], 3);
+
+        let mut future = request(FocusAction::History, "run-1");
+        future.since = Some("2999-01-01T00:00:00Z".to_string());
+        assert_eq!(focus(&state, future).await["count"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_focus_sessions_spans_runs_and_skips_ended() {
+        let state = memory_state(test_config()).await;
+        for (run, agent) in [("run-a", "claude"), ("run-b", "codex"), ("run-c", "cursor")] {
+            state
+                .db
+                .client
+                .query(format!(
+                    "CREATE type::thing('objects', '{}') CONTENT {{ type: 'run', status: 'running', provenance: {{ agent: '{}' }} }}",
+                    run, agent
+                ))
+                .await
+                .unwrap()
+                .check()
+                .unwrap();
+            let mut set = request(FocusAction::Set, run);
+            set.title = Some(format!("Work on {}", run));
+            focus(&state, set).await;
+        }
+        focus(&state, request(FocusAction::End, "run-c")).await;
+
+        let list = |query: FocusSessionsQuery| {
+            let state = state.clone();
+            async move {
+                let Json(response) = list_focus_sessions(State(state), Query(query))
+                    .await
+                    .unwrap();
+                response
+            }
+        };
+
+        let all = list(FocusSessionsQuery::default()).await;
+        assert_eq!(all["total"], 2);
+        let mut agents: Vec<&str> = all["sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["agent_name"].as_str().unwrap())
+            .collect();
+        agents.sort();
+        assert_eq!(agents, ["claude", "codex"]);
+
+        let one = list(FocusSessionsQuery {
+            run_id: Some("run-b".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(one["sessions"][0]["session_id"], "run-b");
+        assert_eq!(one["sessions"][0]["current_task"], "Work on run-b");
+        assert!(one["sessions"][0]["last_updated_at"].is_string());
+
+        let page = list(FocusSessionsQuery {
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(page["sessions"].as_array().unwrap().len(), 1);
+        assert_eq!(page["total"], 2);
+    }
+
+    #[test]
+    fn test_build_focus_history_closes_replaced_and_open_entries() {
+        let events = vec![
+            serde_json::json!({ "run_id": "r", "event": "set", "title": "A", "created_at": "2025-01-01T00:00:00Z" }),
+            serde_json::json!({ "run_id": "r", "event": "set", "title": "B", "created_at": "2025-01-01T00:01:30Z" }),
+            serde_json::json!({ "run_id": "r", "event": "end", "created_at": "2025-01-01T00:02:00Z" }),
+            serde_json::json!({ "run_id": "r", "event": "set", "title": "C", "created_at": "2025-01-01T00:03:00Z" }),
+        ];
+
+        let entries = build_focus_history(&events);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["status"], "replaced");
+        assert_eq!(entries[0]["duration_secs"], 90.0);
+        assert_eq!(entries[1]["status"], "ended");
+        assert_eq!(entries[1]["duration_secs"], 30.0);
+        assert_eq!(entries[2]["status"], "active");
+        assert!(entries[2]["duration_secs"].is_null());
     }
 }
@@ -0,0 +1,345 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    services::graph::{GraphTraversalError, PathEdge},
+    tenant::Tenant,
+    AppState,
+};
+
+const DEFAULT_MAX_DEPTH: usize = 6;
+const MAX_PATH_DEPTH: usize = 10;
+const DEFAULT_COMMON_EDGE_TYPE: &str = "depends_on";
+
+#[derive(Debug, Deserialize)]
+pub struct PathQuery {
+    pub max_depth: Option<usize>,
+    /// Comma-separated relation names; every relation when omitted
+    pub edge_types: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphPathQuery {
+    pub from: String,
+    pub to: String,
+    pub max_depth: Option<usize>,
+    pub edge_types: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PathResponse {
+    pub from: String,
+    pub to: String,
+    pub found: bool,
+    /// True when the search hit the node budget before finding `to`
+    pub truncated: bool,
+    /// Node ids from `from` to `to`, in order; empty when no path was found
+    pub nodes: Vec<String>,
+    pub edges: Vec<PathEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommonNeighborsQuery {
+    /// Comma-separated object ids
+    pub ids: String,
+    pub edge_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommonNeighborsResponse {
+    pub ids: Vec<String>,
+    pub edge_type: String,
+    pub count: usize,
+    pub neighbors: Vec<Value>,
+}
+
+/// Shortest relationship path between two objects, ignoring edge direction.
+pub async fn get_path(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path((from, to)): Path<(String, String)>,
+    Query(query): Query<PathQuery>,
+) -> Result<Json<PathResponse>, StatusCode> {
+    find_path(&state, &tenant, from, to, query).await
+}
+
+/// Same search as `get_path`, with both endpoints passed as query parameters.
+pub async fn get_graph_path(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Query(query): Query<GraphPathQuery>,
+) -> Result<Json<PathResponse>, StatusCode> {
+    let options = PathQuery {
+        max_depth: query.max_depth,
+        edge_types: query.edge_types,
+    };
+    find_path(&state, &tenant, query.from, query.to, options).await
+}
+
+async fn find_path(
+    state: &AppState,
+    tenant: &Tenant,
+    from: String,
+    to: String,
+    query: PathQuery,
+) -> Result<Json<PathResponse>, StatusCode> {
+    let from = parse_object_id(&from)?;
+    let to = parse_object_id(&to)?;
+    let max_depth = query
+        .max_depth
+        .unwrap_or(DEFAULT_MAX_DEPTH)
+        .clamp(1, MAX_PATH_DEPTH);
+    let edge_types = split_list(query.edge_types.as_deref());
+
+    let (edges, truncated) = match state
+        .graph_service
+        .shortest_path(&from, &to, max_depth, &edge_types, tenant.as_str())
+        .await
+    {
+        Ok(edges) => (edges, false),
+        Err(GraphTraversalError::NodeBudgetExceeded(budget)) => {
+            tracing::warn!(
+                "Path search from {} to {} stopped at the {} node budget",
+                from,
+                to,
+                budget
+            );
+            (None, true)
+        }
+        Err(GraphTraversalError::InvalidQuery(_)) => return Err(StatusCode::BAD_REQUEST),
+        Err(GraphTraversalError::Timeout) => return Err(StatusCode::GATEWAY_TIMEOUT),
+        Err(e) => {
+            tracing::error!("Path search from {} to {} failed: {}", from, to, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let found = edges.is_some();
+    let edges = edges.unwrap_or_default();
+    let nodes = if found {
+        std::iter::once(from.clone())
+            .chain(edges.iter().map(|edge| edge.to.clone()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(Json(PathResponse {
+        from,
+        to,
+        found,
+        truncated,
+        nodes,
+        edges,
+    }))
+}
+
+/// Objects every id in `ids` reaches through one outbound `edge_type` edge.
+pub async fn get_common_neighbors(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Query(query): Query<CommonNeighborsQuery>,
+) -> Result<Json<CommonNeighborsResponse>, StatusCode> {
+    let ids = split_list(Some(&query.ids))
+        .iter()
+        .map(|id| parse_object_id(id))
+        .collect::<Result<Vec<_>, _>>()?;
+    if ids.len() < 2 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let edge_type = query
+        .edge_type
+        .unwrap_or_else(|| DEFAULT_COMMON_EDGE_TYPE.to_string());
+
+    let neighbors = match state
+        .graph_service
+        .common_neighbors(&ids, &edge_type, tenant.as_str())
+        .await
+    {
+        Ok(neighbors) => neighbors,
+        Err(GraphTraversalError::InvalidQuery(_)) => return Err(StatusCode::BAD_REQUEST),
+        Err(GraphTraversalError::Timeout) => return Err(StatusCode::GATEWAY_TIMEOUT),
+        Err(e) => {
+            tracing::error!("Common neighbor search for {:?} failed: {}", ids, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    Ok(Json(CommonNeighborsResponse {
+        ids,
+        edge_type,
+        count: neighbors.len(),
+        neighbors,
+    }))
+}
+
+/// Canonical form of an object id, or 400 when it is not a UUID.
+fn parse_object_id(raw: &str) -> Result<String, StatusCode> {
+    Uuid::parse_str(raw.trim())
+        .map(|id| id.to_string())
+        .map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+fn split_list(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    async fn create_node(state: &AppState, tenant: &str) -> String {
+        let id = Uuid::new_v4().to_string();
+        state
+            .db
+            .client
+            .query("CREATE type::thing('objects', $id) SET type = 'file', tenant_id = $tenant_id")
+            .bind(("id", id.clone()))
+            .bind(("tenant_id", tenant.to_string()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        id
+    }
+
+    async fn path(
+        state: &AppState,
+        tenant: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<PathResponse, StatusCode> {
+        get_path(
+            State(state.clone()),
+            Tenant(tenant.to_string()),
+            Path((from.to_string(), to.to_string())),
+            Query(PathQuery {
+                max_depth: None,
+                edge_types: None,
+            }),
+        )
+        .await
+        .map(|Json(response)| response)
+    }
+
+    #[tokio::test]
+    async fn test_path_rejects_ids_that_are_not_uuids() {
+        let state = memory_state(test_config()).await;
+        let a = create_node(&state, "default").await;
+        let b = create_node(&state, "default").await;
+        state
+            .graph_service
+            .store_relationship(&a, "depends_on", &b, "")
+            .await
+            .unwrap();
+        assert_eq!(
+            path(&state, "default", &a, &b).await.unwrap().nodes,
+            vec![a.clone(), b.clone()]
+        );
+
+        let injected = format!("{}`] OR true; DELETE objects; SELECT * FROM [objects:`x", a);
+        for (from, to) in [
+            (injected.as_str(), b.as_str()),
+            (a.as_str(), injected.as_str()),
+        ] {
+            let status = path(&state, "default", from, to).await.err();
+            assert_eq!(status, Some(StatusCode::BAD_REQUEST));
+        }
+        assert_eq!(
+            path(&state, "default", "not-a-uuid", &b).await.err(),
+            Some(StatusCode::BAD_REQUEST)
+        );
+        assert!(path(&state, "default", &a, &b).await.unwrap().found);
+    }
+
+    #[tokio::test]
+    async fn test_path_to_itself_needs_a_node_in_the_tenant() {
+        let state = memory_state(test_config()).await;
+        let own = create_node(&state, "default").await;
+        let foreign = create_node(&state, "acme").await;
+        let missing = Uuid::new_v4().to_string();
+
+        let response = path(&state, "default", &own, &own).await.unwrap();
+        assert!(response.found);
+        assert_eq!(response.nodes, vec![own]);
+
+        for id in [&foreign, &missing] {
+            let response = path(&state, "default", id, id).await.unwrap();
+            assert!(!response.found);
+            assert!(response.nodes.is_empty());
+        }
+        assert!(
+            path(&state, "acme", &foreign, &foreign)
+                .await
+                .unwrap()
+                .found
+        );
+    }
+
+    #[tokio::test]
+    async fn test_common_neighbors_and_graph_path_reject_malformed_ids() {
+        let state = memory_state(test_config()).await;
+        let (a, b, shared) = (
+            create_node(&state, "default").await,
+            create_node(&state, "default").await,
+            create_node(&state, "default").await,
+        );
+        for source in [&a, &b] {
+            state
+                .graph_service
+                .store_relationship(source, "depends_on", &shared, "")
+                .await
+                .unwrap();
+        }
+        let common = |ids: String| {
+            get_common_neighbors(
+                State(state.clone()),
+                Tenant::default(),
+                Query(CommonNeighborsQuery {
+                    ids,
+                    edge_type: None,
+                }),
+            )
+        };
+
+        let Json(response) = common(format!("{},{}", a, b)).await.unwrap();
+        assert_eq!(response.count, 1);
+        assert_eq!(response.neighbors[0]["id"], shared.as_str());
+
+        let injected = format!("{}`] OR true; DELETE objects; SELECT * FROM [objects:`x", a);
+        for ids in [format!("{},{}", injected, b), format!("{},not-a-uuid", a)] {
+            assert_eq!(common(ids).await.err(), Some(StatusCode::BAD_REQUEST));
+        }
+
+        let graph_path = |from: &str, to: &str| {
+            get_graph_path(
+                State(state.clone()),
+                Tenant::default(),
+                Query(GraphPathQuery {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    max_depth: None,
+                    edge_types: None,
+                }),
+            )
+        };
+        assert_eq!(
+            graph_path(&injected, &shared).await.err(),
+            Some(StatusCode::BAD_REQUEST)
+        );
+        let Json(response) = graph_path(&a, &b).await.unwrap();
+        assert_eq!(response.nodes, vec![a.clone(), shared.clone(), b.clone()]);
+    }
+}
@@ -1,11 +1,17 @@
 use crate::{
+    database::Database,
     surreal_json::{take_json_value, take_json_values},
     AppState,
 };
-use axum::{extract::State, http::StatusCode, response::Json};
-use chrono::Utc;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
@@ -249,3 +255,299 @@ pub async fn renew_lease(
         }
     }
 }
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListLeasesQuery {
+    pub resource_id: Option<String>,
+    pub holder_id: Option<String>,
+    /// true for expired leases only, false for live leases only; both when omitted
+    pub expired: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaseInfo {
+    pub lease_id: String,
+    pub resource_id: String,
+    pub holder_id: String,
+    pub acquired_at: String,
+    pub expires_at: String,
+    /// Zero once the lease has expired
+    pub ttl_remaining_seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaseListResponse {
+    pub leases: Vec<LeaseInfo>,
+}
+
+const LEASE_FIELDS: &str = "meta::id(id) AS lease_id, resource, holder, <string>created_at AS acquired_at, <string>expires_at AS expires_at";
+
+fn lease_info(row: &Value, now: DateTime<Utc>) -> Option<LeaseInfo> {
+    let field = |key: &str| row.get(key)?.as_str().map(str::to_string);
+    let expires_at = field("expires_at")?;
+    let ttl_remaining_seconds = DateTime::parse_from_rfc3339(&expires_at)
+        .map(|expires| (expires.with_timezone(&Utc) - now).num_seconds().max(0))
+        .unwrap_or(0);
+    Some(LeaseInfo {
+        lease_id: field("lease_id")?,
+        resource_id: field("resource")?,
+        holder_id: field("holder")?,
+        acquired_at: field("acquired_at").unwrap_or_default(),
+        expires_at,
+        ttl_remaining_seconds,
+    })
+}
+
+/// List leases, optionally narrowed to one resource, one holder, or by expiry.
+pub async fn list_leases(
+    State(state): State<AppState>,
+    Query(query): Query<ListLeasesQuery>,
+) -> Result<Json<LeaseListResponse>, StatusCode> {
+    let mut conditions = Vec::new();
+    if query.resource_id.is_some() {
+        conditions.push("resource = $resource");
+    }
+    if query.holder_id.is_some() {
+        conditions.push("holder = $holder");
+    }
+    match query.expired {
+        Some(true) => conditions.push("expires_at <= time::now()"),
+        Some(false) => conditions.push("expires_at > time::now()"),
+        None => {}
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+    let list_query = format!(
+        "SELECT * FROM (SELECT {} FROM leases{}) ORDER BY expires_at ASC",
+        LEASE_FIELDS, where_clause
+    );
+
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(list_query)
+            .bind(("resource", query.resource_id.clone()))
+            .bind(("holder", query.holder_id.clone())),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(mut response)) => {
+            let now = Utc::now();
+            let leases = take_json_values(&mut response, 0)
+                .iter()
+                .filter_map(|row| lease_info(row, now))
+                .collect();
+            Ok(Json(LeaseListResponse { leases }))
+        }
+        Ok(Err(e)) => {
+            tracing::error!("Failed to list leases: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(_) => {
+            tracing::error!("Timeout listing leases");
+            Err(StatusCode::GATEWAY_TIMEOUT)
+        }
+    }
+}
+
+pub async fn get_lease(
+    State(state): State<AppState>,
+    Path(lease_id): Path<Uuid>,
+) -> Result<Json<LeaseInfo>, StatusCode> {
+    let query = format!(
+        "SELECT {} FROM type::thing('leases', $lease_id)",
+        LEASE_FIELDS
+    );
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("lease_id", lease_id.to_string())),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(mut response)) => take_json_value(&mut response, 0)
+            .and_then(|row| lease_info(&row, Utc::now()))
+            .map(Json)
+            .ok_or(StatusCode::NOT_FOUND),
+        Ok(Err(e)) => {
+            tracing::error!("Failed to get lease {}: {}", lease_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(_) => {
+            tracing::error!("Timeout getting lease {}", lease_id);
+            Err(StatusCode::GATEWAY_TIMEOUT)
+        }
+    }
+}
+
+/// Administrative release: drops the lease whoever holds it.
+pub async fn delete_lease(
+    State(state): State<AppState>,
+    Path(lease_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let query = "LET $lease = type::thing('leases', $lease_id); \
+                 LET $found = (SELECT VALUE id FROM $lease); \
+                 DELETE $lease; \
+                 RETURN count($found);";
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("lease_id", lease_id.to_string())),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(mut response)) => {
+            let deleted = take_json_value(&mut response, 3).and_then(|v| v.as_u64());
+            if deleted.unwrap_or(0) == 0 {
+                return Err(StatusCode::NOT_FOUND);
+            }
+            tracing::warn!("Lease {} force-released", lease_id);
+            Ok(Json(serde_json::json!({
+                "success": true,
+                "lease_id": lease_id,
+                "message": "Lease force-released"
+            })))
+        }
+        Ok(Err(e)) => {
+            tracing::error!("Failed to force-release lease {}: {}", lease_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(_) => {
+            tracing::error!("Timeout force-releasing lease {}", lease_id);
+            Err(StatusCode::GATEWAY_TIMEOUT)
+        }
+    }
+}
+
+/// Delete every expired lease, returning how many were removed.
+pub async fn purge_expired_leases(db: &Database) -> Result<usize, surrealdb::Error> {
+    let mut response = db
+        .client
+        .query(
+            "LET $expired = (SELECT VALUE id FROM leases WHERE expires_at <= time::now()); \
+             DELETE $expired; \
+             RETURN count($expired);",
+        )
+        .await?
+        .check()?;
+    let purged: Option<usize> = response.take(2)?;
+    Ok(purged.unwrap_or(0))
+}
+
+/// Purge expired leases every `interval` for the life of the server.
+pub fn spawn_lease_gc(db: Arc<Database>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick fires immediately; skip it so startup is not slowed
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match purge_expired_leases(&db).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} expired leases", purged),
+                Err(e) => tracing::warn!("Lease garbage collection failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    async fn seed_leases(state: &AppState) -> (Uuid, Uuid) {
+        let (live, expired) = (Uuid::new_v4(), Uuid::new_v4());
+        state
+            .db
+            .client
+            .query(
+                "CREATE type::thing('leases', $live) CONTENT { resource: 'src/lib.rs', holder: 'agent-a', expires_at: time::now() + 5m };
+                 CREATE type::thing('leases', $expired) CONTENT { resource: 'src/main.rs', holder: 'agent-b', expires_at: time::now() - 5m };",
+            )
+            .bind(("live", live.to_string()))
+            .bind(("expired", expired.to_string()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        (live, expired)
+    }
+
+    async fn list(state: &AppState, query: ListLeasesQuery) -> Vec<LeaseInfo> {
+        let Json(response) = list_leases(State(state.clone()), Query(query))
+            .await
+            .unwrap();
+        response.leases
+    }
+
+    #[tokio::test]
+    async fn test_list_leases_filters_and_reports_ttl() {
+        let state = memory_state(test_config()).await;
+        let (live, expired) = seed_leases(&state).await;
+
+        let all = list(&state, ListLeasesQuery::default()).await;
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].lease_id, expired.to_string());
+        assert_eq!(all[0].ttl_remaining_seconds, 0);
+        assert!((290..=300).contains(&all[1].ttl_remaining_seconds));
+
+        let by_holder = ListLeasesQuery {
+            holder_id: Some("agent-a".to_string()),
+            ..Default::default()
+        };
+        let held = list(&state, by_holder).await;
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].resource_id, "src/lib.rs");
+
+        let stale = ListLeasesQuery {
+            expired: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(list(&state, stale).await[0].holder_id, "agent-b");
+
+        let Json(single) = get_lease(State(state.clone()), Path(live)).await.unwrap();
+        assert_eq!(single.holder_id, "agent-a");
+        assert_eq!(
+            get_lease(State(state.clone()), Path(Uuid::new_v4()))
+                .await
+                .unwrap_err(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_force_release_and_purge_expired_leases() {
+        let state = memory_state(test_config()).await;
+        let (live, _) = seed_leases(&state).await;
+
+        assert_eq!(purge_expired_leases(&state.db).await.unwrap(), 1);
+        assert_eq!(list(&state, ListLeasesQuery::default()).await.len(), 1);
+
+        let Json(released) = delete_lease(State(state.clone()), Path(live))
+            .await
+            .unwrap();
+        assert_eq!(released["success"], true);
+        assert!(list(&state, ListLeasesQuery::default()).await.is_empty());
+        assert_eq!(
+            delete_lease(State(state.clone()), Path(live))
+                .await
+                .unwrap_err(),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use crate::{
+    database::VECTOR_INDEXES,
+    services::{embedding::indexable_dimension, graph::DEFAULT_RELATIONS},
+    surreal_json::take_json_values,
+    tenant::Tenant,
+    AppState,
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tokio::time::{timeout, Duration};
+
+/// Ids returned per issue category; `count` is always the full total.
+const SAMPLE_LIMIT: usize = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyQuery {
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    pub project_id: String,
+    pub total_issues: usize,
+    /// File nodes with no FileLog
+    pub files_missing_logs: IntegrityIssue,
+    /// FileChunks whose `file_id` matches no file node
+    pub orphaned_chunks: IntegrityIssue,
+    /// Symbols with no `defined_in` edge to or from a file node
+    pub unlinked_symbols: IntegrityIssue,
+    /// Relationships with a missing endpoint; ids are `table:id`
+    pub dangling_edges: IntegrityIssue,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct IntegrityIssue {
+    pub count: usize,
+    pub sample_ids: Vec<String>,
+}
+
+impl IntegrityIssue {
+    fn from_ids(ids: Vec<String>) -> Self {
+        Self {
+            count: ids.len(),
+            sample_ids: ids.into_iter().take(SAMPLE_LIMIT).collect(),
+        }
+    }
+}
+
+/// Read-only integrity check of one project's indexed objects and edges.
+pub async fn verify(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Query(query): Query<VerifyQuery>,
+) -> Result<Json<VerifyReport>, StatusCode> {
+    let dangling_edges: String = DEFAULT_RELATIONS
+        .iter()
+        .map(|table| {
+            format!(
+                "(SELECT VALUE string::concat(id) FROM {} WHERE (in.id = NONE OR out.id = NONE) AND (in.project_id = $project_id OR out.project_id = $project_id) AND (in.tenant_id = $tenant_id OR out.tenant_id = $tenant_id))",
+                table
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        r#"
+        LET $files = (SELECT id, file_id, path FROM objects WHERE tenant_id = $tenant_id AND project_id = $project_id AND type IN ['Symbol', 'symbol'] AND kind = 'file');
+        LET $file_keys = array::union($files.map(|$f| meta::id($f.id)), $files.file_id);
+        LET $log_keys = (SELECT VALUE file_id FROM objects WHERE tenant_id = $tenant_id AND project_id = $project_id AND type = 'FileLog');
+        SELECT VALUE string::concat(id) FROM $files WHERE meta::id(id) NOT IN $log_keys AND (file_id = NONE OR file_id NOT IN $log_keys);
+        SELECT VALUE string::concat(id) FROM objects WHERE tenant_id = $tenant_id AND project_id = $project_id AND type = 'FileChunk' AND (file_id = NONE OR file_id NOT IN $file_keys);
+        SELECT VALUE string::concat(id) FROM objects WHERE tenant_id = $tenant_id AND project_id = $project_id AND type IN ['Symbol', 'symbol'] AND kind NOT IN ['file', 'project', 'directory'] AND array::len(array::concat(->defined_in->objects, <-defined_in<-objects)[WHERE kind = 'file']) = 0;
+        RETURN array::flatten([{}]);
+        "#,
+        dangling_edges
+    );
+
+    let result = timeout(
+        Duration::from_secs(30),
+        state
+            .db
+            .client
+            .query(sql)
+            .bind(("project_id", query.project_id.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .map_err(|_| {
+        tracing::error!("Verify for {} timed out", query.project_id);
+        StatusCode::GATEWAY_TIMEOUT
+    })?
+    .and_then(surrealdb::Response::check);
+
+    let mut response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::error!("Verify for {} failed: {}", query.project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let mut issue = |index: usize| {
+        IntegrityIssue::from_ids(
+            take_json_values(&mut response, index)
+                .into_iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+        )
+    };
+    let files_missing_logs = issue(3);
+    let orphaned_chunks = issue(4);
+    let unlinked_symbols = issue(5);
+    let dangling_edges = issue(6);
+
+    Ok(Json(VerifyReport {
+        project_id: query.project_id,
+        total_issues: files_missing_logs.count
+            + orphaned_chunks.count
+            + unlinked_symbols.count
+            + dangling_edges.count,
+        files_missing_logs,
+        orphaned_chunks,
+        unlinked_symbols,
+        dangling_edges,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebuildVectorIndexResponse {
+    pub dimension: usize,
+    pub indexes: Vec<String>,
+}
+
+/// Rebuild the HNSW vector indexes for the live embedding dimension, e.g.
+/// after re-embedding for a new model. Until it succeeds, vector search
+/// falls back to a full scan.
+pub async fn rebuild_vector_index(
+    State(state): State<AppState>,
+) -> Result<Json<RebuildVectorIndexResponse>, (StatusCode, String)> {
+    let Some(dimension) = indexable_dimension(
+        state.embedding_service.as_ref(),
+        state.code_embedding_service.as_ref(),
+    ) else {
+        // A stale index would reject every write of the new dimension
+        state.db.remove_vector_indexes().await;
+        return Err((
+            StatusCode::CONFLICT,
+            "Vector indexes need embeddings enabled, with prose and code models of one dimension"
+                .to_string(),
+        ));
+    };
+    state
+        .db
+        .rebuild_vector_indexes(dimension)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Vector index rebuild failed: {}", e);
+            (
+                StatusCode::CONFLICT,
+                format!(
+                    "Vector index rebuild failed; re-embed vectors stored with another dimension first: {}",
+                    e
+                ),
+            )
+        })?;
+    tracing::info!("Rebuilt vector indexes for {} dimensions", dimension);
+
+    Ok(Json(RebuildVectorIndexResponse {
+        dimension,
+        indexes: VECTOR_INDEXES
+            .iter()
+            .map(|(name, _, _)| name.to_string())
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct PruneOrphansResponse {
+    /// Edges removed per relationship table
+    pub pruned: BTreeMap<String, usize>,
+    pub total_pruned: usize,
+}
+
+/// Delete edges whose `in` or `out` object no longer exists, across every
+/// tenant. Repairs databases written before object deletes removed edges.
+pub async fn prune_orphans(
+    State(state): State<AppState>,
+) -> Result<Json<PruneOrphansResponse>, StatusCode> {
+    let counts = DEFAULT_RELATIONS
+        .iter()
+        .map(|table| {
+            format!(
+                "{table}: array::len((DELETE {table} WHERE in.id = NONE OR out.id = NONE RETURN BEFORE))"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut response = timeout(
+        Duration::from_secs(60),
+        state.db.client.query(format!("RETURN {{ {} }};", counts)),
+    )
+    .await
+    .map_err(|_| {
+        tracing::error!("Pruning orphaned edges timed out");
+        StatusCode::GATEWAY_TIMEOUT
+    })?
+    .and_then(surrealdb::Response::check)
+    .map_err(|e| {
+        tracing::error!("Pruning orphaned edges failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let pruned: BTreeMap<String, usize> = take_json_values(&mut response, 0)
+        .into_iter()
+        .next()
+        .and_then(|counts| serde_json::from_value(counts).ok())
+        .unwrap_or_default();
+    let total_pruned = pruned.values().sum();
+    if total_pruned > 0 {
+        tracing::info!("Pruned {} orphaned edges", total_pruned);
+    }
+    Ok(Json(PruneOrphansResponse {
+        pruned,
+        total_pruned,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::query::QueryRequest;
+    use crate::test_support::{
+        memory_state, memory_state_with_embedding, test_config, MarkerEmbedding,
+    };
+
+    async fn run(state: &AppState, sql: &str) {
+        state.db.client.query(sql).await.unwrap().check().unwrap();
+    }
+
+    /// One fully linked file: node, FileLog, chunk and a symbol defined in it.
+    async fn healthy_project(state: &AppState) {
+        run(
+            state,
+            "CREATE objects:file1 CONTENT { type: 'Symbol', kind: 'file', path: 'src/a.rs', file_id: 'file-a', project_id: 'p1', tenant_id: 'default' };
+             CREATE objects:log1 CONTENT { type: 'FileLog', file_path: 'src/a.rs', file_id: 'file-a', project_id: 'p1', tenant_id: 'default' };
+             CREATE objects:chunk1 CONTENT { type: 'FileChunk', file_path: 'src/a.rs', file_id: 'file-a', project_id: 'p1', tenant_id: 'default' };
+             CREATE objects:fn1 CONTENT { type: 'symbol', kind: 'function', path: 'src/a.rs', project_id: 'p1', tenant_id: 'default' };
+             RELATE objects:fn1->defined_in->objects:file1;",
+        )
+        .await;
+    }
+
+    async fn report(state: &AppState) -> VerifyReport {
+        let Json(report) = verify(
+            State(state.clone()),
+            Tenant::default(),
+            Query(VerifyQuery {
+                project_id: "p1".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+        report
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_orphaned_chunk() {
+        let state = memory_state(test_config()).await;
+        healthy_project(&state).await;
+        assert_eq!(report(&state).await.total_issues, 0);
+
+        run(
+            &state,
+            "CREATE objects:stray CONTENT { type: 'FileChunk', file_path: 'src/gone.rs', file_id: 'file-gone', project_id: 'p1', tenant_id: 'default' };",
+        )
+        .await;
+
+        let report = report(&state).await;
+        assert_eq!(report.total_issues, 1);
+        assert_eq!(report.orphaned_chunks.count, 1);
+        assert_eq!(report.orphaned_chunks.sample_ids, vec!["objects:stray"]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_missing_logs_unlinked_symbols_and_dangling_edges() {
+        let state = memory_state(test_config()).await;
+        healthy_project(&state).await;
+        run(
+            &state,
+            "CREATE objects:file2 CONTENT { type: 'Symbol', kind: 'file', path: 'src/b.rs', project_id: 'p1', tenant_id: 'default' };
+             CREATE objects:fn2 CONTENT { type: 'symbol', kind: 'function', path: 'src/b.rs', project_id: 'p1', tenant_id: 'default' };
+             RELATE objects:fn1->calls->objects:missing;",
+        )
+        .await;
+
+        let report = report(&state).await;
+        assert_eq!(report.files_missing_logs.sample_ids, vec!["objects:file2"]);
+        assert_eq!(report.unlinked_symbols.sample_ids, vec!["objects:fn2"]);
+        assert_eq!(report.dangling_edges.count, 1);
+        assert!(report.dangling_edges.sample_ids[0].starts_with("calls:"));
+        assert_eq!(report.orphaned_chunks.count, 0);
+        assert_eq!(report.total_issues, 3);
+    }
+
+    #[tokio::test]
+    async fn test_prune_orphans_removes_only_dangling_edges() {
+        let state = memory_state(test_config()).await;
+        healthy_project(&state).await;
+        run(
+            &state,
+            "RELATE objects:fn1->calls->objects:missing;
+             RELATE objects:gone->depends_on->objects:file1;",
+        )
+        .await;
+
+        let Json(first) = prune_orphans(State(state.clone())).await.unwrap();
+        assert_eq!(first.total_pruned, 2);
+        assert_eq!(first.pruned["calls"], 1);
+        assert_eq!(first.pruned["depends_on"], 1);
+        assert_eq!(first.pruned["defined_in"], 0);
+        assert_eq!(report(&state).await.total_issues, 0);
+
+        let Json(second) = prune_orphans(State(state.clone())).await.unwrap();
+        assert_eq!(second.total_pruned, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_vector_index_needs_one_dimension_then_serves_knn() {
+        let embedding = MarkerEmbedding {
+            model: "marker",
+            marker: 1.0,
+        };
+        let state = memory_state_with_embedding(test_config(), Box::new(embedding)).await;
+        run(
+            &state,
+            "CREATE objects:near CONTENT { type: 'note', tenant_id: 'default', embedding: [1.0, 0.0] };
+             CREATE objects:close CONTENT { type: 'note', tenant_id: 'default', embedding: [0.9, 0.2] };
+             CREATE objects:far CONTENT { type: 'note', tenant_id: 'default', embedding: [0.0, 1.0] };
+             CREATE objects:stale CONTENT { type: 'note', tenant_id: 'default', embedding: [1.0, 0.0, 0.0] };",
+        )
+        .await;
+        let request: QueryRequest =
+            serde_json::from_value(serde_json::json!({ "vector": [1.0, 0.0], "limit": 2 }))
+                .unwrap();
+        let nearest = |response: crate::services::hybrid::HybridResponse| {
+            response
+                .results
+                .iter()
+                .map(|r| r.object["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // A vector of another length blocks the index
+        let (status, _) = rebuild_vector_index(State(state.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(state.db.vector_index_dimension("objects").await, None);
+
+        // Without an index, search falls back to scanning
+        run(&state, "DELETE objects:stale;").await;
+        let scanned = state
+            .hybrid_service
+            .execute_hybrid_query(&request)
+            .await
+            .unwrap();
+        assert_eq!(nearest(scanned), vec!["near", "close"]);
+
+        let Json(rebuilt) = rebuild_vector_index(State(state.clone())).await.unwrap();
+        assert_eq!(rebuilt.dimension, 2);
+        assert_eq!(rebuilt.indexes.len(), VECTOR_INDEXES.len());
+        assert_eq!(state.db.vector_index_dimension("objects").await, Some(2));
+        assert_eq!(
+            state.db.vector_index_dimension("cache_block").await,
+            Some(2)
+        );
+        let indexed = state
+            .hybrid_service
+            .execute_hybrid_query(&request)
+            .await
+            .unwrap();
+        assert_eq!(nearest(indexed), vec!["near", "close"]);
+    }
+}
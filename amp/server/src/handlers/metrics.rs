@@ -0,0 +1,71 @@
+use crate::{surreal_json::take_json_values, AppState};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+
+/// Prometheus exposition endpoint. Gauges backed by SurrealDB are sampled on scrape.
+pub async fn get_metrics(State(state): State<AppState>) -> Result<impl IntoResponse, StatusCode> {
+    let Some(metrics) = state.metrics.clone() else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let object_counts = sample_counts(
+        &state,
+        "SELECT string::lowercase(string::concat('', type)) AS label, count() AS count FROM objects GROUP BY label",
+    )
+    .await;
+    let cache_block_counts = sample_counts(
+        &state,
+        "SELECT status AS label, count() AS count FROM cache_block GROUP BY label",
+    )
+    .await;
+
+    let body = {
+        let metrics = metrics.lock().unwrap();
+        if let Some(counts) = object_counts {
+            metrics.set_object_counts(&counts);
+        }
+        if let Some(counts) = cache_block_counts {
+            metrics.set_cache_block_counts(&counts);
+        }
+        metrics.encode().map_err(|e| {
+            tracing::error!("Failed to encode metrics: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+    };
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        body,
+    ))
+}
+
+/// Run a `label, count` GROUP BY query; None keeps the previous gauge values.
+async fn sample_counts(state: &AppState, query: &str) -> Option<Vec<(String, i64)>> {
+    match timeout(Duration::from_secs(5), state.db.client.query(query)).await {
+        Ok(Ok(mut response)) => {
+            let rows: Vec<Value> = take_json_values(&mut response, 0);
+            Some(
+                rows.iter()
+                    .filter_map(|row| {
+                        let label = row.get("label")?.as_str()?.to_string();
+                        let count = row.get("count")?.as_i64()?;
+                        Some((label, count))
+                    })
+                    .collect(),
+            )
+        }
+        Ok(Err(e)) => {
+            tracing::warn!("Failed to sample metrics counts: {}", e);
+            None
+        }
+        Err(_) => {
+            tracing::warn!("Metrics count query timed out");
+            None
+        }
+    }
+}
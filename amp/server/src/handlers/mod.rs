@@ -1,11 +1,17 @@
+pub mod analysis;
 pub mod analytics;
 pub mod artifacts;
 pub mod cache;
 pub mod codebase;
 pub mod connections;
+pub mod debug;
 pub mod focus;
+pub mod graph;
 pub mod leases;
+pub mod maintenance;
+pub mod metrics;
 pub mod objects;
+pub mod projects;
 pub mod query;
 pub mod relationships;
 pub mod settings;
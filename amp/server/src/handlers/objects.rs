@@ -1,16 +1,25 @@
 #![allow(dead_code)]
 use crate::{
+    database::Database,
+    idempotency::{existing_object, IdempotencyKey},
     models::AmpObject,
+    services::{
+        embedding::{EmbeddingIndex, EmbeddingService},
+        embedding_cache::embed_with_cache,
+        graph::DEFAULT_RELATIONS,
+    },
     surreal_json::{normalize_object_id, take_json_values},
+    tenant::Tenant,
     AppState,
 };
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
@@ -22,6 +31,9 @@ fn extract_object_id(obj: &AmpObject) -> Uuid {
         AmpObject::Run(r) => r.base.id,
         AmpObject::FileChunk(f) => f.base.id,
         AmpObject::FileLog(f) => f.base.id,
+        AmpObject::Note(n) => n.base.id,
+        AmpObject::Task(t) => t.base.id,
+        AmpObject::Warning(w) => w.base.id,
     }
 }
 
@@ -34,6 +46,9 @@ fn payload_to_content_value(payload: &AmpObject) -> Result<Value, StatusCode> {
         AmpObject::Run(r) => serde_json::to_value(r),
         AmpObject::FileChunk(f) => serde_json::to_value(f),
         AmpObject::FileLog(f) => serde_json::to_value(f),
+        AmpObject::Note(n) => serde_json::to_value(n),
+        AmpObject::Task(t) => serde_json::to_value(t),
+        AmpObject::Warning(w) => serde_json::to_value(w),
     }
     .map_err(|err| {
         tracing::error!("Failed to serialize payload: {}", err);
@@ -73,6 +88,16 @@ fn payload_to_content_value(payload: &AmpObject) -> Result<Value, StatusCode> {
     Ok(plain_value)
 }
 
+/// Objects created without a tenant belong to the request tenant.
+fn apply_request_tenant(map: &mut serde_json::Map<String, Value>, tenant: &Tenant) {
+    if map.get("tenant_id").is_none_or(|v| v.is_null()) {
+        map.insert(
+            "tenant_id".to_string(),
+            Value::String(tenant.as_str().to_string()),
+        );
+    }
+}
+
 fn set_embedding(mut obj: AmpObject, embedding: Option<Vec<f32>>) -> AmpObject {
     match &mut obj {
         AmpObject::Symbol(s) => s.base.embedding = embedding,
@@ -81,6 +106,9 @@ fn set_embedding(mut obj: AmpObject, embedding: Option<Vec<f32>>) -> AmpObject {
         AmpObject::Run(r) => r.base.embedding = embedding,
         AmpObject::FileChunk(f) => f.base.embedding = embedding,
         AmpObject::FileLog(f) => f.base.embedding = embedding,
+        AmpObject::Note(n) => n.base.embedding = embedding,
+        AmpObject::Task(t) => t.base.embedding = embedding,
+        AmpObject::Warning(w) => w.base.embedding = embedding,
     }
     obj
 }
@@ -191,6 +219,24 @@ fn extract_embedding_text(obj: &AmpObject) -> String {
             parts.extend(log.key_symbols.clone());
             parts.extend(log.dependencies.clone());
         }
+        AmpObject::Note(note) => {
+            parts.push(note.base.provenance.summary.clone());
+            parts.push(note.title.clone());
+            parts.push(note.body.clone());
+            parts.extend(note.tags.clone());
+        }
+        AmpObject::Task(task) => {
+            parts.push(task.title.clone());
+            if let Some(description) = &task.description {
+                parts.push(description.clone());
+            }
+            parts.push(format!("{:?}", task.status));
+        }
+        AmpObject::Warning(warning) => {
+            parts.push(warning.title.clone());
+            parts.push(warning.body.clone());
+            parts.extend(warning.tags.clone());
+        }
     }
 
     parts
@@ -201,7 +247,11 @@ fn extract_embedding_text(obj: &AmpObject) -> String {
 }
 
 async fn apply_embedding(state: &AppState, obj: AmpObject) -> AmpObject {
-    if !state.embedding_service.is_enabled() {
+    let service = state.embedding_for_index(match obj {
+        AmpObject::Symbol(_) | AmpObject::FileChunk(_) => EmbeddingIndex::Code,
+        _ => EmbeddingIndex::Prose,
+    });
+    if !service.is_enabled() {
         return obj;
     }
 
@@ -210,7 +260,7 @@ async fn apply_embedding(state: &AppState, obj: AmpObject) -> AmpObject {
         return obj;
     }
 
-    match state.embedding_service.generate_embedding(&text).await {
+    match service.generate_embedding(&text).await {
         Ok(embedding) => set_embedding(obj, Some(embedding)),
         Err(err) => {
             tracing::warn!("Failed to generate embedding: {}", err);
@@ -219,26 +269,98 @@ async fn apply_embedding(state: &AppState, obj: AmpObject) -> AmpObject {
     }
 }
 
+/// Embed every object with non-empty text in one batched call per embedding index.
+async fn apply_batch_embeddings(state: &AppState, objects: &mut [Value]) {
+    for index in [EmbeddingIndex::Prose, EmbeddingIndex::Code] {
+        let service = state.embedding_for_index(index);
+        if !service.is_enabled() {
+            continue;
+        }
+
+        let mut targets = Vec::new();
+        let mut texts = Vec::new();
+        for (idx, obj) in objects.iter().enumerate() {
+            if EmbeddingIndex::for_object_type(object_type(obj)) != index {
+                continue;
+            }
+            if let Some(text) = extract_text_for_embedding(obj) {
+                if !text.trim().is_empty() {
+                    targets.push(idx);
+                    texts.push(text);
+                }
+            }
+        }
+        if texts.is_empty() {
+            continue;
+        }
+
+        match embed_with_cache(&state.db, service.as_ref(), &texts).await {
+            Ok(embeddings) => {
+                for (idx, embedding) in targets.into_iter().zip(embeddings) {
+                    if let Some(map) = objects[idx].as_object_mut() {
+                        map.insert("embedding".to_string(), serde_json::json!(embedding));
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to generate batch embeddings for {} objects: {}",
+                    texts.len(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+fn object_type(obj: &Value) -> &str {
+    obj.get("type").and_then(|v| v.as_str()).unwrap_or("")
+}
+
 pub async fn create_object(
     State(state): State<AppState>,
+    tenant: Tenant,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
     Json(payload): Json<serde_json::Value>,
 ) -> Result<(StatusCode, Json<Value>), StatusCode> {
     let object_id = payload
         .get("id")
         .and_then(|v| v.as_str())
         .map(|s| s.to_string())
+        .or(idempotency_key.clone())
         .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
+    if idempotency_key.is_some() {
+        if let Some(existing) = existing_object(&state.db, &tenant, &object_id).await {
+            tracing::info!("Replaying create for existing object: {}", object_id);
+            return Ok((
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "id": object_id,
+                    "created_at": existing.get("created_at"),
+                    "expires_at": existing.get("expires_at")
+                })),
+            ));
+        }
+    }
+
     tracing::info!("Creating object: {}", object_id);
 
     // Parse the payload into proper SurrealDB format
     let mut clean_payload = payload.clone();
+    let expires_at = match take_ttl_seconds(&mut clean_payload) {
+        Ok(ttl) => {
+            ttl.map(|secs| (chrono::Utc::now() + chrono::Duration::seconds(secs)).to_rfc3339())
+        }
+        Err(()) => return Err(StatusCode::BAD_REQUEST),
+    };
 
     // Generate embedding if enabled (for hybrid search)
-    if state.embedding_service.is_enabled() {
+    let embedding_service = state.embedding_for(object_type(&payload));
+    if embedding_service.is_enabled() {
         if let Some(text) = extract_text_for_embedding(&clean_payload) {
             if !text.trim().is_empty() {
-                match state.embedding_service.generate_embedding(&text).await {
+                match embedding_service.generate_embedding(&text).await {
                     Ok(embedding) => {
                         if let Some(map) = clean_payload.as_object_mut() {
                             map.insert("embedding".to_string(), serde_json::json!(embedding));
@@ -257,6 +379,7 @@ pub async fn create_object(
         // Remove id from content - CREATE objects:`id` CONTENT {...} sets the ID via the record path,
         // including id in content causes: "Found 'id' for the `id` field, but a specific record has been specified"
         obj.remove("id");
+        apply_request_tenant(obj, &tenant);
 
         let now = chrono::Utc::now().to_rfc3339();
         if !obj.contains_key("created_at")
@@ -272,10 +395,22 @@ pub async fn create_object(
     }
 
     // Create with explicit ID using backtick syntax - but use proper JSON structure
-    let query = format!("CREATE objects:`{}` CONTENT $data", object_id);
+    let mut query = format!("CREATE objects:`{}` CONTENT $data", object_id);
+    if expires_at.is_some() {
+        // JSON has no datetime type, so cast after the insert to keep GC comparisons native
+        query.push_str(&format!(
+            "; UPDATE objects:`{}` SET expires_at = <datetime>$expires_at RETURN NONE",
+            object_id
+        ));
+    }
     let result: Result<Result<surrealdb::Response, _>, _> = timeout(
         Duration::from_secs(5),
-        state.db.client.query(query).bind(("data", clean_payload)),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("data", clean_payload))
+            .bind(("expires_at", expires_at.clone())),
     )
     .await;
 
@@ -284,7 +419,8 @@ pub async fn create_object(
             StatusCode::CREATED,
             Json(serde_json::json!({
                 "id": object_id,
-                "created_at": chrono::Utc::now().to_rfc3339()
+                "created_at": chrono::Utc::now().to_rfc3339(),
+                "expires_at": expires_at
             })),
         )),
         Ok(Err(e)) => {
@@ -298,6 +434,57 @@ pub async fn create_object(
     }
 }
 
+/// Remove `ttl_seconds` from an object payload. Errors unless it is a positive integer.
+fn take_ttl_seconds(payload: &mut Value) -> Result<Option<i64>, ()> {
+    let Some(ttl) = payload
+        .as_object_mut()
+        .and_then(|obj| obj.remove("ttl_seconds"))
+    else {
+        return Ok(None);
+    };
+    match ttl {
+        Value::Null => Ok(None),
+        ttl => ttl.as_i64().filter(|secs| *secs > 0).map(Some).ok_or(()),
+    }
+}
+
+/// Delete objects whose `expires_at` has passed, along with their edges.
+/// Returns how many objects were removed.
+pub async fn purge_expired_objects(db: &Database) -> Result<usize, surrealdb::Error> {
+    let delete_edges: String = DEFAULT_RELATIONS
+        .iter()
+        .map(|table| format!("DELETE {} WHERE in IN $expired OR out IN $expired;", table))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let query = format!(
+        "LET $expired = (SELECT VALUE id FROM objects WHERE expires_at IS NOT NONE AND expires_at < time::now());
+         {}
+         DELETE objects WHERE id IN $expired;
+         RETURN count($expired);",
+        delete_edges
+    );
+    let mut response = db.client.query(query).await?.check()?;
+    let purged: Option<usize> = response.take(DEFAULT_RELATIONS.len() + 2)?;
+    Ok(purged.unwrap_or(0))
+}
+
+/// Purge expired objects every `interval` for the life of the server.
+pub fn spawn_object_gc(db: Arc<Database>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick fires immediately; skip it so startup is not slowed
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match purge_expired_objects(&db).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} expired objects", purged),
+                Err(e) => tracing::warn!("Object garbage collection failed: {}", e),
+            }
+        }
+    });
+}
+
 #[derive(Debug, Serialize)]
 pub struct BatchResult {
     id: Uuid,
@@ -321,13 +508,16 @@ pub struct BatchSummary {
 
 pub async fn create_objects_batch(
     State(state): State<AppState>,
-    Json(payload): Json<Vec<Value>>,
+    tenant: Tenant,
+    Json(mut payload): Json<Vec<Value>>,
 ) -> Result<(StatusCode, Json<BatchResponse>), StatusCode> {
     let mut results = Vec::new();
     let total = payload.len();
     let mut succeeded = 0;
     let mut failed = 0;
 
+    apply_batch_embeddings(&state, &mut payload).await;
+
     for mut obj_value in payload {
         let object_id = obj_value
             .get("id")
@@ -335,25 +525,8 @@ pub async fn create_objects_batch(
             .and_then(|s| Uuid::parse_str(s).ok())
             .unwrap_or_else(Uuid::new_v4);
 
-        // Generate embedding if enabled
-        if state.embedding_service.is_enabled() {
-            if let Some(text) = extract_text_for_embedding(&obj_value) {
-                if !text.trim().is_empty() {
-                    match state.embedding_service.generate_embedding(&text).await {
-                        Ok(embedding) => {
-                            if let Some(map) = obj_value.as_object_mut() {
-                                map.insert("embedding".to_string(), serde_json::json!(embedding));
-                            }
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to generate embedding for {}: {}", object_id, e);
-                        }
-                    }
-                }
-            }
-        }
-
         if let Some(map) = obj_value.as_object_mut() {
+            apply_request_tenant(map, &tenant);
             let now = chrono::Utc::now().to_rfc3339();
             if !map.contains_key("created_at")
                 || map.get("created_at").map(|v| v.is_null()).unwrap_or(true)
@@ -472,16 +645,22 @@ fn extract_text_for_embedding(obj: &Value) -> Option<String> {
 
 pub async fn get_object(
     State(state): State<AppState>,
+    tenant: Tenant,
     Path(id): Path<String>,
 ) -> Result<Json<Value>, StatusCode> {
     let raw_id = id.trim().trim_start_matches("objects:").to_string();
     let raw_id_for_log = raw_id.clone();
     tracing::debug!("Get object: {}", raw_id);
 
-    let query = "SELECT VALUE { id: string::concat(id), type: type, title: title, project_id: project_id, agent_id: agent_id, run_id: run_id, tags: tags, context: context, focus: focus, decision: decision, consequences: consequences, alternatives: alternatives, status: status, file_path: file_path, summary: summary, symbols: symbols, dependencies: dependencies, content: content, category: category, description: description, diff_summary: diff_summary, files_changed: files_changed, linked_objects: linked_objects, linked_decisions: linked_decisions, linked_files: linked_files, memory_layers: memory_layers, created_at: created_at, updated_at: updated_at, provenance: provenance, change_history: change_history, input_summary: input_summary, outputs: outputs, errors: errors, duration_ms: duration_ms, confidence: confidence } FROM objects WHERE id = type::thing('objects', $id)";
+    let query = "SELECT VALUE { id: string::concat(id), type: type, title: title, project_id: project_id, agent_id: agent_id, run_id: run_id, tags: tags, context: context, focus: focus, decision: decision, consequences: consequences, alternatives: alternatives, status: status, file_path: file_path, summary: summary, symbols: symbols, dependencies: dependencies, content: content, category: category, description: description, diff_summary: diff_summary, files_changed: files_changed, linked_objects: linked_objects, linked_decisions: linked_decisions, linked_files: linked_files, memory_layers: memory_layers, created_at: created_at, updated_at: updated_at, expires_at: IF expires_at THEN <string>expires_at END, provenance: provenance, change_history: change_history, input_summary: input_summary, outputs: outputs, errors: errors, duration_ms: duration_ms, confidence: confidence } FROM objects WHERE id = type::thing('objects', $id) AND tenant_id = $tenant_id";
     let result: Result<Result<surrealdb::Response, _>, _> = timeout(
         Duration::from_secs(5),
-        state.db.client.query(query).bind(("id", raw_id)),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("id", raw_id))
+            .bind(("tenant_id", tenant.as_str().to_string())),
     )
     .await;
 
@@ -507,24 +686,382 @@ pub async fn get_object(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ObjectHistoryQuery {
+    pub limit: Option<usize>,
+}
+
+/// One `audit_trail` entry, as written by file sync and reindexing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    pub action: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectHistoryResponse {
+    pub id: String,
+    /// Newest first
+    pub entries: Vec<AuditEntry>,
+}
+
+/// Audit trail of an object, newest first; empty for objects that never had one
+pub async fn get_object_history(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+    Query(query): Query<ObjectHistoryQuery>,
+) -> Result<Json<ObjectHistoryResponse>, StatusCode> {
+    let raw_id = id.trim().trim_start_matches("objects:").to_string();
+    let sql = "SELECT VALUE { trail: audit_trail } FROM objects WHERE id = type::thing('objects', $id) AND tenant_id = $tenant_id";
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(sql)
+            .bind(("id", raw_id.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .map_err(|_| {
+        tracing::error!("Timeout retrieving history for {}", raw_id);
+        StatusCode::GATEWAY_TIMEOUT
+    })?;
+    let mut response = result.map_err(|e| {
+        tracing::error!("Failed to retrieve history for {}: {}", raw_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let Some(object) = take_json_values(&mut response, 0).into_iter().next() else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let mut entries: Vec<AuditEntry> = object
+        .get("trail")
+        .and_then(|trail| trail.as_array())
+        .map(|trail| {
+            trail
+                .iter()
+                .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    // Entries are appended in order; the stable sort keeps that order for equal timestamps
+    entries.reverse();
+    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    if let Some(limit) = query.limit {
+        entries.truncate(limit);
+    }
+
+    Ok(Json(ObjectHistoryResponse {
+        id: raw_id,
+        entries,
+    }))
+}
+
+/// Versions kept per object; older snapshots are evicted on update
+pub const MAX_VERSIONS_PER_OBJECT: i64 = 50;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ObjectVersion {
+    pub id: String,
+    pub version_number: i64,
+    pub changed_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectVersionsResponse {
+    pub id: String,
+    /// Newest first
+    pub versions: Vec<ObjectVersion>,
+}
+
+/// Snapshots taken before each update of an object, newest first; empty for
+/// objects that were never updated. Versions outlive the object itself.
+pub async fn list_object_versions(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<ObjectVersionsResponse>, StatusCode> {
+    let raw_id = id.trim().trim_start_matches("objects:").to_string();
+    let sql = "SELECT record::id(id) AS id, version_number, <string>changed_at AS changed_at FROM versions WHERE object_id = $id AND tenant_id = $tenant_id ORDER BY version_number DESC";
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(sql)
+            .bind(("id", raw_id.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .map_err(|_| {
+        tracing::error!("Timeout listing versions for {}", raw_id);
+        StatusCode::GATEWAY_TIMEOUT
+    })?;
+    let mut response = result.map_err(|e| {
+        tracing::error!("Failed to list versions for {}: {}", raw_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let versions = take_json_values(&mut response, 0)
+        .into_iter()
+        .filter_map(|version| serde_json::from_value(version).ok())
+        .collect();
+    Ok(Json(ObjectVersionsResponse {
+        id: raw_id,
+        versions,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectVersionResponse {
+    pub id: String,
+    pub version_number: i64,
+    pub changed_at: String,
+    /// The object as it was before the update that created this version
+    pub snapshot: Value,
+}
+
+/// Full snapshot of one version of an object
+pub async fn get_object_version(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path((id, version_number)): Path<(String, i64)>,
+) -> Result<Json<ObjectVersionResponse>, StatusCode> {
+    let raw_id = id.trim().trim_start_matches("objects:").to_string();
+    let sql = "SELECT version_number, <string>changed_at AS changed_at, snapshot FROM versions WHERE object_id = $id AND version_number = $version_number AND tenant_id = $tenant_id";
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(sql)
+            .bind(("id", raw_id.clone()))
+            .bind(("version_number", version_number))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .map_err(|_| {
+        tracing::error!(
+            "Timeout retrieving version {} of {}",
+            version_number,
+            raw_id
+        );
+        StatusCode::GATEWAY_TIMEOUT
+    })?;
+    let mut response = result.map_err(|e| {
+        tracing::error!(
+            "Failed to retrieve version {} of {}: {}",
+            version_number,
+            raw_id,
+            e
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let Some(mut version) = take_json_values(&mut response, 0).into_iter().next() else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let mut snapshot = version["snapshot"].take();
+    normalize_object_id(&mut snapshot);
+    Ok(Json(ObjectVersionResponse {
+        id: raw_id,
+        version_number,
+        changed_at: version["changed_at"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        snapshot,
+    }))
+}
+
+const MAX_CHANGED_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct ChangedObjectsQuery {
+    /// RFC 3339 timestamp; objects with `updated_at >= since` are returned.
+    pub since: String,
+    pub project_id: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangedObjectsResponse {
+    pub objects: Vec<Value>,
+    /// Pass back as `since` to continue. Inclusive, so clients should dedupe by id.
+    pub next_since: String,
+    pub count: usize,
+}
+
+/// List objects changed since a timestamp, oldest change first
+pub async fn list_changed_objects(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Query(query): Query<ChangedObjectsQuery>,
+) -> Result<Json<ChangedObjectsResponse>, (StatusCode, String)> {
+    let since = chrono::DateTime::parse_from_rfc3339(query.since.trim())
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid since timestamp (expected RFC 3339): {}", e),
+            )
+        })?
+        .with_timezone(&chrono::Utc);
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_CHANGED_LIMIT);
+
+    let mut conditions = vec![
+        "updated_at != NONE".to_string(),
+        "<datetime>updated_at >= <datetime>$since".to_string(),
+        "tenant_id = $tenant_id".to_string(),
+    ];
+    if query.project_id.is_some() {
+        conditions.push("project_id = $project_id".to_string());
+    }
+
+    // updated_at may be stored as an RFC 3339 string, so order on the cast value.
+    // Raw record ids don't decode as JSON; id_string is normalized back into id below.
+    let query_str = format!(
+        "SELECT *, string::concat(id) AS id_string, <datetime>updated_at AS changed_at OMIT id, embedding FROM objects WHERE {} ORDER BY changed_at ASC LIMIT {}",
+        conditions.join(" AND "),
+        limit
+    );
+
+    let result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query_str)
+            .bind(("since", since.to_rfc3339()))
+            .bind(("project_id", query.project_id.clone()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(mut response)) => {
+            let mut objects = take_json_values(&mut response, 0);
+            let mut next_since = since.to_rfc3339();
+            for object in objects.iter_mut() {
+                normalize_object_id(object);
+                if let Some(map) = object.as_object_mut() {
+                    if let Some(changed_at) = map.remove("changed_at") {
+                        if let Some(changed_at) = changed_at.as_str() {
+                            next_since = changed_at.to_string();
+                        }
+                    }
+                }
+            }
+            Ok(Json(ChangedObjectsResponse {
+                count: objects.len(),
+                objects,
+                next_since,
+            }))
+        }
+        Ok(Err(e)) => {
+            tracing::error!("Failed to list changed objects: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        }
+        Err(_) => {
+            tracing::error!("Timeout listing changed objects");
+            Err((
+                StatusCode::GATEWAY_TIMEOUT,
+                "Changed objects query timed out".to_string(),
+            ))
+        }
+    }
+}
+
+/// 404 unless `id` is one of the tenant's objects.
+async fn ensure_tenant_object(state: &AppState, tenant: &Tenant, id: &Uuid) -> Result<(), StatusCode> {
+    let query = "SELECT VALUE count() FROM objects WHERE id = type::thing('objects', $id) AND tenant_id = $tenant_id";
+    let mut response = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("id", id.to_string()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .map_err(|_| StatusCode::GATEWAY_TIMEOUT)?
+    .map_err(|e| {
+        tracing::error!("Failed to look up object {}: {}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let found = take_json_values(&mut response, 0)
+        .first()
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    if found == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(())
+}
+
 pub async fn update_object(
     State(state): State<AppState>,
+    tenant: Tenant,
     Path(id): Path<Uuid>,
     Json(payload): Json<serde_json::Value>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     tracing::info!("Updating object: {}", id);
+    ensure_tenant_object(&state, &tenant, &id).await?;
 
-    // Support partial updates - remove RETURN to avoid serialization issues
-    let query = format!("UPDATE objects:`{}` MERGE $data", id);
+    // Keep updated_at current so /objects/changed sees every modification
+    let mut payload = payload;
+    if let Some(map) = payload.as_object_mut() {
+        map.insert(
+            "updated_at".to_string(),
+            Value::String(chrono::Utc::now().to_rfc3339()),
+        );
+    }
+
+    // Snapshot the current state into `versions` in the same transaction as
+    // the partial update, evicting the oldest beyond the cap
+    let query = "BEGIN TRANSACTION;
+        LET $rid = type::thing('objects', $id);
+        LET $before = (SELECT *, string::concat(id) AS id_string OMIT id, embedding FROM ONLY $rid);
+        IF $before != NONE AND $before.tenant_id = $tenant_id {
+            LET $next = (math::max((SELECT VALUE version_number FROM versions WHERE object_id = $id)) ?? 0) + 1;
+            CREATE versions CONTENT {
+                object_id: $id,
+                tenant_id: $before.tenant_id,
+                version_number: $next,
+                snapshot: $before
+            };
+            DELETE versions WHERE object_id = $id AND version_number <= $next - $max_versions;
+        };
+        UPDATE $rid MERGE $data WHERE tenant_id = $tenant_id;
+        COMMIT TRANSACTION;";
 
     let result: Result<Result<surrealdb::Response, _>, _> = timeout(
         Duration::from_secs(5),
-        state.db.client.query(query).bind(("data", payload)),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("id", id.to_string()))
+            .bind(("max_versions", MAX_VERSIONS_PER_OBJECT))
+            .bind(("data", payload))
+            .bind(("tenant_id", tenant.as_str().to_string())),
     )
     .await;
 
     match result {
-        Ok(Ok(_)) => {
+        Ok(Ok(response)) => {
+            if let Err(e) = response.check() {
+                tracing::error!("Failed to update object {}: {}", id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
             tracing::info!("Object updated: {}", id);
             Ok(Json(
                 serde_json::json!({"success": true, "message": "Object updated"}),
@@ -541,20 +1078,73 @@ pub async fn update_object(
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct DeleteObjectResponse {
+    pub id: Uuid,
+    /// Edges removed per relationship table
+    pub edges_deleted: std::collections::BTreeMap<String, usize>,
+    pub total_edges_deleted: usize,
+}
+
+/// Delete an object together with every edge that starts or ends at it, in
+/// one transaction, so traces and analytics never see dangling edges.
 pub async fn delete_object(
     State(state): State<AppState>,
+    tenant: Tenant,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
-    let query = "DELETE type::record('objects', $id)";
+) -> Result<Json<DeleteObjectResponse>, StatusCode> {
+    ensure_tenant_object(&state, &tenant, &id).await?;
+
+    let edge_counts = DEFAULT_RELATIONS
+        .iter()
+        .map(|table| {
+            format!(
+                "{table}: array::len((DELETE {table} WHERE in = $rid OR out = $rid RETURN BEFORE))"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let query = format!(
+        "BEGIN TRANSACTION;
+        LET $rid = type::thing('objects', $id);
+        LET $edges = {{ {} }};
+        DELETE $rid WHERE tenant_id = $tenant_id;
+        RETURN $edges;
+        COMMIT TRANSACTION;",
+        edge_counts
+    );
 
     let result: Result<Result<surrealdb::Response, _>, _> = timeout(
         Duration::from_secs(5),
-        state.db.client.query(query).bind(("id", id)),
+        state
+            .db
+            .client
+            .query(query)
+            .bind(("id", id.to_string()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
     )
     .await;
 
     match result {
-        Ok(Ok(_)) => Ok(StatusCode::NO_CONTENT),
+        Ok(Ok(response)) => {
+            let mut response = response.check().map_err(|e| {
+                tracing::error!("Failed to delete object {}: {}", id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            // RETURN inside a transaction is its only result
+            let last = response.num_statements().saturating_sub(1);
+            let edges_deleted: std::collections::BTreeMap<String, usize> =
+                take_json_values(&mut response, last)
+                    .into_iter()
+                    .next()
+                    .and_then(|counts| serde_json::from_value(counts).ok())
+                    .unwrap_or_default();
+            Ok(Json(DeleteObjectResponse {
+                id,
+                total_edges_deleted: edges_deleted.values().sum(),
+                edges_deleted,
+            }))
+        }
         Ok(Err(e)) => {
             tracing::error!("Failed to delete object {}: {}", id, e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -565,3 +1155,407 @@ pub async fn delete_object(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    async fn create_note(state: &AppState, title: &str, project_id: &str) -> String {
+        let (_, Json(created)) = create_object(
+            State(state.clone()),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(serde_json::json!({
+                "type": "note",
+                "title": title,
+                "project_id": project_id,
+            })),
+        )
+        .await
+        .unwrap();
+        created["id"].as_str().unwrap().to_string()
+    }
+
+    async fn changed(
+        state: &AppState,
+        since: &str,
+        project_id: Option<&str>,
+    ) -> ChangedObjectsResponse {
+        let Json(response) = list_changed_objects(
+            State(state.clone()),
+            Tenant::default(),
+            Query(ChangedObjectsQuery {
+                since: since.to_string(),
+                project_id: project_id.map(str::to_string),
+                limit: None,
+            }),
+        )
+        .await
+        .unwrap();
+        response
+    }
+
+    fn titles(response: &ChangedObjectsResponse) -> Vec<&str> {
+        response
+            .objects
+            .iter()
+            .map(|object| object["title"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_updates_keep_a_capped_version_history() {
+        let state = memory_state(test_config()).await;
+        let id = create_note(&state, "proposed", "amp").await;
+        let uuid = Uuid::parse_str(&id).unwrap();
+        let update = |title: String| {
+            update_object(
+                State(state.clone()),
+                Tenant::default(),
+                Path(uuid),
+                Json(serde_json::json!({ "title": title })),
+            )
+        };
+        for title in ["accepted", "superseded", "archived"] {
+            let _ = update(title.to_string()).await.unwrap();
+        }
+
+        let versions = |state: AppState| {
+            list_object_versions(State(state), Tenant::default(), Path(id.clone()))
+        };
+        let Json(listed) = versions(state.clone()).await.unwrap();
+        let numbers: Vec<i64> = listed.versions.iter().map(|v| v.version_number).collect();
+        assert_eq!(numbers, vec![3, 2, 1]);
+
+        let version = |number: i64| {
+            get_object_version(
+                State(state.clone()),
+                Tenant::default(),
+                Path((id.clone(), number)),
+            )
+        };
+        for (number, title) in [(1, "proposed"), (2, "accepted"), (3, "superseded")] {
+            let Json(snapshot) = version(number).await.unwrap();
+            assert_eq!(snapshot.snapshot["title"], title);
+            assert_eq!(snapshot.snapshot["id"], id.as_str());
+        }
+        assert_eq!(version(4).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        // Only the newest MAX_VERSIONS_PER_OBJECT are kept
+        for i in 0..MAX_VERSIONS_PER_OBJECT {
+            let _ = update(format!("edit {}", i)).await.unwrap();
+        }
+        let Json(listed) = versions(state.clone()).await.unwrap();
+        assert_eq!(listed.versions.len() as i64, MAX_VERSIONS_PER_OBJECT);
+        assert_eq!(
+            listed.versions[0].version_number,
+            MAX_VERSIONS_PER_OBJECT + 3
+        );
+        assert_eq!(listed.versions.last().unwrap().version_number, 4);
+    }
+
+    #[tokio::test]
+    async fn test_delete_object_removes_its_edges() {
+        let state = memory_state(test_config()).await;
+        let doomed = create_note(&state, "doomed", "amp").await;
+        let kept = create_note(&state, "kept", "amp").await;
+        let other = create_note(&state, "other", "amp").await;
+        state
+            .db
+            .client
+            .query(
+                "RELATE (type::thing('objects', $doomed))->depends_on->(type::thing('objects', $kept));
+                 RELATE (type::thing('objects', $kept))->calls->(type::thing('objects', $doomed));
+                 RELATE (type::thing('objects', $kept))->calls->(type::thing('objects', $other));",
+            )
+            .bind(("doomed", doomed.clone()))
+            .bind(("kept", kept))
+            .bind(("other", other))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let Json(response) = delete_object(
+            State(state.clone()),
+            Tenant::default(),
+            Path(Uuid::parse_str(&doomed).unwrap()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.total_edges_deleted, 2);
+        assert_eq!(response.edges_deleted["depends_on"], 1);
+        assert_eq!(response.edges_deleted["calls"], 1);
+        assert_eq!(response.edges_deleted["modifies"], 0);
+
+        let mut remaining = state
+            .db
+            .client
+            .query("RETURN count(SELECT id FROM depends_on); RETURN count(SELECT id FROM calls); RETURN count(SELECT id FROM type::thing('objects', $doomed))")
+            .bind(("doomed", doomed))
+            .await
+            .unwrap();
+        assert_eq!(
+            take_json_values(&mut remaining, 0),
+            vec![serde_json::json!(0)]
+        );
+        assert_eq!(
+            take_json_values(&mut remaining, 1),
+            vec![serde_json::json!(1)]
+        );
+        assert_eq!(
+            take_json_values(&mut remaining, 2),
+            vec![serde_json::json!(0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_other_tenants_cannot_update_delete_or_replay_an_object() {
+        let state = memory_state(test_config()).await;
+        let id = create_note(&state, "mine", "amp").await;
+        let uuid = Uuid::parse_str(&id).unwrap();
+        let other = || Tenant("tenant-b".to_string());
+
+        let update = update_object(
+            State(state.clone()),
+            other(),
+            Path(uuid),
+            Json(serde_json::json!({ "title": "theirs" })),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(update, StatusCode::NOT_FOUND);
+        let delete = delete_object(State(state.clone()), other(), Path(uuid))
+            .await
+            .unwrap_err();
+        assert_eq!(delete, StatusCode::NOT_FOUND);
+        assert!(existing_object(&state.db, &other(), &id).await.is_none());
+        assert!(existing_object(&state.db, &Tenant::default(), &id)
+            .await
+            .is_some());
+
+        let mut stored = state
+            .db
+            .client
+            .query("SELECT VALUE title FROM type::thing('objects', $id); SELECT VALUE count() FROM versions WHERE object_id = $id")
+            .bind(("id", id))
+            .await
+            .unwrap();
+        assert_eq!(take_json_values(&mut stored, 0), vec![serde_json::json!("mine")]);
+        assert!(take_json_values(&mut stored, 1).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changed_objects_returns_only_newer_in_order() {
+        let state = memory_state(test_config()).await;
+        let old_id = create_note(&state, "old-1", "amp").await;
+        create_note(&state, "old-2", "amp").await;
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let since = chrono::Utc::now().to_rfc3339();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        create_note(&state, "new-1", "amp").await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        create_note(&state, "new-2", "amp").await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        create_note(&state, "other-project", "elsewhere").await;
+
+        let response = changed(&state, &since, Some("amp")).await;
+        assert_eq!(titles(&response), vec!["new-1", "new-2"]);
+        assert_eq!(response.count, 2);
+        assert!(response.objects.iter().all(|o| o.get("embedding").is_none()));
+
+        // The cursor resumes from the last change (inclusive)
+        let resumed = changed(&state, &response.next_since, Some("amp")).await;
+        assert_eq!(titles(&resumed), vec!["new-2"]);
+
+        // Updates bump updated_at, so an old object shows up again
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let Json(updated) = update_object(
+            State(state.clone()),
+            Tenant::default(),
+            Path(old_id.parse().unwrap()),
+            Json(serde_json::json!({ "title": "old-1-edited" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated["success"], true);
+        let after_update = changed(&state, &since, None).await;
+        assert_eq!(
+            titles(&after_update),
+            vec!["new-1", "new-2", "other-project", "old-1-edited"]
+        );
+        assert_eq!(after_update.objects[3]["id"], Value::String(old_id));
+    }
+
+    #[tokio::test]
+    async fn test_changed_objects_rejects_invalid_since() {
+        let state = memory_state(test_config()).await;
+        let err = list_changed_objects(
+            State(state),
+            Tenant::default(),
+            Query(ChangedObjectsQuery {
+                since: "yesterday".to_string(),
+                project_id: None,
+                limit: None,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_code_and_prose_objects_use_their_own_embedding_model() {
+        use crate::handlers::artifacts::{write_artifact, WriteArtifactRequest};
+        use crate::test_support::{memory_state_with_embeddings, MarkerEmbedding};
+
+        let state = memory_state_with_embeddings(
+            test_config(),
+            Box::new(MarkerEmbedding {
+                model: "prose",
+                marker: 1.0,
+            }),
+            Some(Box::new(MarkerEmbedding {
+                model: "code",
+                marker: 2.0,
+            })),
+        )
+        .await;
+
+        let chunk_id = Uuid::new_v4().to_string();
+        let (status, _) = create_objects_batch(
+            State(state.clone()),
+            Tenant::default(),
+            Json(vec![serde_json::json!({
+                "id": chunk_id,
+                "type": "FileChunk",
+                "content": "fn main() {}",
+                "project_id": "p1",
+            })]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let request: WriteArtifactRequest = serde_json::from_value(serde_json::json!({
+            "type": "note",
+            "title": "Why the parser is lazy",
+            "content": "Parsing everything up front was too slow.",
+        }))
+        .unwrap();
+        let (_, Json(note)) = write_artifact(
+            State(state.clone()),
+            Tenant::default(),
+            IdempotencyKey::default(),
+            Json(request),
+        )
+        .await
+        .unwrap();
+
+        let mut response = state
+            .db
+            .client
+            .query("SELECT VALUE embedding FROM type::thing('objects', $id)")
+            .bind(("id", chunk_id))
+            .query("SELECT VALUE embedding FROM type::thing('objects', $note)")
+            .bind(("note", note.id))
+            .await
+            .unwrap();
+        let chunk_embedding: Vec<Vec<f32>> = response.take(0).unwrap();
+        let note_embedding: Vec<Vec<f32>> = response.take(1).unwrap();
+        assert_eq!(chunk_embedding, vec![vec![2.0, 2.0]]);
+        assert_eq!(note_embedding, vec![vec![1.0, 1.0]]);
+    }
+
+    #[tokio::test]
+    async fn test_objects_with_ttl_are_purged_after_expiry() {
+        let state = memory_state(test_config()).await;
+        let create = |payload: Value| {
+            create_object(
+                State(state.clone()),
+                Tenant::default(),
+                IdempotencyKey::default(),
+                Json(payload),
+            )
+        };
+        let (_, Json(ephemeral)) = create(serde_json::json!({
+            "type": "note",
+            "title": "scratch",
+            "ttl_seconds": 1,
+        }))
+        .await
+        .unwrap();
+        let ephemeral = ephemeral["id"].as_str().unwrap().to_string();
+        let kept = create_note(&state, "keep me", "p1").await;
+        state
+            .db
+            .client
+            .query("RELATE (type::thing('objects', $kept))->depends_on->(type::thing('objects', $ephemeral))")
+            .bind(("kept", kept.clone()))
+            .bind(("ephemeral", ephemeral.clone()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        assert_eq!(
+            create(serde_json::json!({ "type": "note", "ttl_seconds": 0 }))
+                .await
+                .unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+
+        let get = |id: String| get_object(State(state.clone()), Tenant::default(), Path(id));
+        let Json(object) = get(ephemeral.clone()).await.unwrap();
+        assert!(object["expires_at"].is_string(), "{}", object);
+        assert!(object.get("ttl_seconds").is_none());
+        let Json(object) = get(kept.clone()).await.unwrap();
+        assert!(object["expires_at"].is_null());
+
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        assert_eq!(purge_expired_objects(&state.db).await.unwrap(), 1);
+        assert_eq!(get(ephemeral).await.unwrap_err(), StatusCode::NOT_FOUND);
+        assert!(get(kept).await.is_ok());
+        let mut response = state
+            .db
+            .client
+            .query("RETURN count(SELECT id FROM depends_on)")
+            .await
+            .unwrap();
+        let edges: Option<usize> = response.take(0).unwrap();
+        assert_eq!(edges, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_retried_create_with_idempotency_key_reuses_the_object() {
+        let state = memory_state(test_config()).await;
+        let key = Uuid::new_v4().to_string();
+        let create = || {
+            create_object(
+                State(state.clone()),
+                Tenant::default(),
+                IdempotencyKey(Some(key.clone())),
+                Json(serde_json::json!({ "type": "note", "title": "once" })),
+            )
+        };
+
+        let (status, Json(first)) = create().await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(first["id"], key.as_str());
+        let (status, Json(retried)) = create().await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(retried["id"], key.as_str());
+        assert!(retried["created_at"].is_string(), "{}", retried);
+
+        let mut response = state
+            .db
+            .client
+            .query("RETURN count(SELECT id FROM objects)")
+            .await
+            .unwrap();
+        let objects: Option<usize> = response.take(0).unwrap();
+        assert_eq!(objects, Some(1));
+    }
+}
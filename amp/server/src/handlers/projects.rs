@@ -0,0 +1,735 @@
+use crate::{
+    services::graph::DEFAULT_RELATIONS, surreal_json::take_json_values, tenant::Tenant, AppState,
+};
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use uuid::Uuid;
+
+const ARCHIVE_VERSION: u32 = 1;
+const EXPORT_PAGE_SIZE: usize = 500;
+const IMPORT_BATCH_SIZE: usize = 100;
+/// Request body cap for `POST /v1/projects/import`.
+pub const IMPORT_BODY_LIMIT: usize = 512 * 1024 * 1024;
+
+/// Edge and cache block timestamps are datetimes; the archive carries them as strings.
+const RESTORE_DATETIMES: &str = "object::from_entries(object::entries($row.data).map(|$e| IF $e[0] IN ['created_at', 'updated_at', 'closed_at'] AND type::is::string($e[1]) THEN [$e[0], <datetime>$e[1]] ELSE $e END))";
+
+/// One NDJSON line of a project archive. The header comes first, then objects,
+/// relationships and cache blocks, so ids can be remapped before edges refer to them.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ArchiveRecord {
+    Header {
+        version: u32,
+        project_id: String,
+        exported_at: String,
+    },
+    Object {
+        id: String,
+        data: Map<String, Value>,
+    },
+    Relationship {
+        table: String,
+        #[serde(rename = "in")]
+        source: String,
+        #[serde(rename = "out")]
+        target: String,
+        data: Map<String, Value>,
+    },
+    CacheBlock {
+        id: String,
+        data: Map<String, Value>,
+    },
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportCounts {
+    pub created: usize,
+    /// Already present from an earlier import of the same archive
+    pub skipped: usize,
+    /// Created under a new id because the archived id belonged to something else
+    pub remapped: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub project_id: String,
+    pub objects: ImportCounts,
+    pub relationships: ImportCounts,
+    pub cache_blocks: ImportCounts,
+}
+
+/// Relationship endpoints (object ids) and the edge's own fields.
+type ArchivedEdge = (String, String, Map<String, Value>);
+
+/// Where an imported record belongs; an existing record with the same id is the
+/// same record only if it matches on tenant and `field`.
+struct RecordScope<'a> {
+    table: &'static str,
+    field: &'static str,
+    value: String,
+    tenant_id: &'a str,
+}
+
+fn archive_error(message: impl Into<String>) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message.into() })),
+    )
+}
+
+fn internal_error(context: &str, err: impl std::fmt::Display) -> (StatusCode, Json<Value>) {
+    tracing::error!("{}: {}", context, err);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": format!("{}: {}", context, err) })),
+    )
+}
+
+/// Split a selected row into its exported id and the remaining fields.
+fn take_export_row(row: Value, key: &str) -> Option<(String, Map<String, Value>)> {
+    let Value::Object(mut data) = row else {
+        return None;
+    };
+    let id = data.remove(key)?.as_str()?.to_string();
+    Some((id, data))
+}
+
+fn write_record(
+    encoder: &mut GzEncoder<Vec<u8>>,
+    record: &ArchiveRecord,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    serde_json::to_writer(&mut *encoder, record)
+        .map_err(|e| internal_error("Failed to encode archive record", e))?;
+    encoder
+        .write_all(b"\n")
+        .map_err(|e| internal_error("Failed to write archive", e))
+}
+
+/// Run `sql` page by page until it returns fewer than `EXPORT_PAGE_SIZE` rows.
+async fn fetch_all(
+    state: &AppState,
+    sql: &str,
+    project_id: &str,
+    tenant_id: &str,
+) -> Result<Vec<Value>, (StatusCode, Json<Value>)> {
+    let mut rows = Vec::new();
+    loop {
+        let mut response = state
+            .db
+            .client
+            .query(sql)
+            .bind(("project_id", project_id.to_string()))
+            .bind(("scope_id", format!("project:{}", project_id)))
+            .bind(("tenant_id", tenant_id.to_string()))
+            .bind(("start", rows.len()))
+            .bind(("limit", EXPORT_PAGE_SIZE))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| internal_error("Failed to read project for export", e))?;
+        let page = take_json_values(&mut response, 0);
+        let done = page.len() < EXPORT_PAGE_SIZE;
+        rows.extend(page);
+        if done {
+            return Ok(rows);
+        }
+    }
+}
+
+/// Gzip'd NDJSON archive of a project's objects (with embeddings), the
+/// relationships between them, and its project-scoped cache blocks.
+pub async fn export_project(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(project_id): Path<String>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let tenant_id = tenant.as_str();
+    let objects = fetch_all(
+        &state,
+        "SELECT *, <string>meta::id(id) AS export_id OMIT id FROM objects WHERE tenant_id = $tenant_id AND project_id = $project_id ORDER BY export_id LIMIT $limit START $start",
+        &project_id,
+        tenant_id,
+    )
+    .await?;
+    if objects.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Project not found", "project_id": project_id })),
+        ));
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    write_record(
+        &mut encoder,
+        &ArchiveRecord::Header {
+            version: ARCHIVE_VERSION,
+            project_id: project_id.clone(),
+            exported_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )?;
+
+    let object_count = objects.len();
+    for row in objects {
+        if let Some((id, data)) = take_export_row(row, "export_id") {
+            write_record(&mut encoder, &ArchiveRecord::Object { id, data })?;
+        }
+    }
+
+    let mut relationship_count = 0;
+    for table in DEFAULT_RELATIONS {
+        let sql = format!(
+            "SELECT *, <string>meta::id(in) AS export_in, <string>meta::id(out) AS export_out, <string>id AS export_edge OMIT id, in, out FROM {} WHERE in.tenant_id = $tenant_id AND in.project_id = $project_id AND out.tenant_id = $tenant_id AND out.project_id = $project_id ORDER BY export_edge LIMIT $limit START $start",
+            table
+        );
+        for row in fetch_all(&state, &sql, &project_id, tenant_id).await? {
+            let Some((source, mut data)) = take_export_row(row, "export_in") else {
+                continue;
+            };
+            data.remove("export_edge");
+            let Some(target) = data
+                .remove("export_out")
+                .and_then(|v| v.as_str().map(str::to_string))
+            else {
+                continue;
+            };
+            write_record(
+                &mut encoder,
+                &ArchiveRecord::Relationship {
+                    table: table.to_string(),
+                    source,
+                    target,
+                    data,
+                },
+            )?;
+            relationship_count += 1;
+        }
+    }
+
+    let cache_blocks = fetch_all(
+        &state,
+        "SELECT *, <string>meta::id(id) AS export_id OMIT id FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id ORDER BY sequence LIMIT $limit START $start",
+        &project_id,
+        tenant_id,
+    )
+    .await?;
+    let cache_block_count = cache_blocks.len();
+    for row in cache_blocks {
+        if let Some((id, data)) = take_export_row(row, "export_id") {
+            write_record(&mut encoder, &ArchiveRecord::CacheBlock { id, data })?;
+        }
+    }
+
+    let archive = encoder
+        .finish()
+        .map_err(|e| internal_error("Failed to finish archive", e))?;
+    tracing::info!(
+        "Exported project {}: {} objects, {} relationships, {} cache blocks ({} bytes)",
+        project_id,
+        object_count,
+        relationship_count,
+        cache_block_count,
+        archive.len()
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.amp.gz\"", project_id),
+            ),
+        ],
+        archive,
+    )
+        .into_response())
+}
+
+/// Next non-blank archive line, parsed.
+fn next_record(
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+) -> Result<Option<ArchiveRecord>, (StatusCode, Json<Value>)> {
+    for line in lines {
+        let line = line.map_err(|e| archive_error(format!("Archive is not valid gzip: {}", e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        return serde_json::from_str(&line)
+            .map(Some)
+            .map_err(|e| archive_error(format!("Invalid archive record: {}", e)));
+    }
+    Ok(None)
+}
+
+/// Decode an archive into its project id and records, header excluded.
+fn read_archive(body: &[u8]) -> Result<(String, Vec<ArchiveRecord>), (StatusCode, Json<Value>)> {
+    let mut lines = BufReader::new(GzDecoder::new(body)).lines();
+    let project_id = match next_record(&mut lines)? {
+        Some(ArchiveRecord::Header {
+            version,
+            project_id,
+            ..
+        }) => {
+            if version != ARCHIVE_VERSION {
+                return Err(archive_error(format!(
+                    "Unsupported archive version {}",
+                    version
+                )));
+            }
+            project_id
+        }
+        _ => return Err(archive_error("Archive must start with a header record")),
+    };
+
+    let mut records = Vec::new();
+    while let Some(record) = next_record(&mut lines)? {
+        if let ArchiveRecord::Header { .. } = record {
+            return Err(archive_error("Archive contains more than one header"));
+        }
+        records.push(record);
+    }
+    Ok((project_id, records))
+}
+
+/// Create `rows` in `scope.table`, one transaction per batch. Rows already
+/// imported are skipped; rows whose id is taken by a record outside the scope
+/// get a fresh id. Returns the archived id -> stored id mapping for every row.
+async fn import_records(
+    state: &AppState,
+    scope: &RecordScope<'_>,
+    rows: Vec<(String, Map<String, Value>)>,
+    restore_datetimes: bool,
+    counts: &mut ImportCounts,
+) -> Result<HashMap<String, String>, (StatusCode, Json<Value>)> {
+    let mut mapping = HashMap::new();
+    let content = if restore_datetimes {
+        RESTORE_DATETIMES
+    } else {
+        "$row.data"
+    };
+    let insert = format!(
+        "BEGIN TRANSACTION; FOR $row IN $rows {{ CREATE type::thing('{}', $row.id) CONTENT {}; }}; COMMIT TRANSACTION;",
+        scope.table, content
+    );
+    let lookup = format!(
+        "SELECT <string>meta::id(id) AS id, tenant_id, {field} AS scope FROM $ids.map(|$id| type::thing('{table}', $id));
+         SELECT VALUE [imported_from, <string>meta::id(id)] FROM {table} WHERE tenant_id = $tenant_id AND {field} = $scope AND imported_from IN $ids;",
+        table = scope.table,
+        field = scope.field
+    );
+
+    for batch in rows.chunks(IMPORT_BATCH_SIZE) {
+        let ids: Vec<String> = batch.iter().map(|(id, _)| id.clone()).collect();
+        let mut response = state
+            .db
+            .client
+            .query(&lookup)
+            .bind(("ids", ids))
+            .bind(("tenant_id", scope.tenant_id.to_string()))
+            .bind(("scope", scope.value.clone()))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| internal_error("Failed to look up existing records", e))?;
+        let existing: HashMap<String, bool> = take_json_values(&mut response, 0)
+            .into_iter()
+            .filter_map(|row| {
+                let id = row.get("id")?.as_str()?.to_string();
+                let same_scope = row.get("tenant_id").and_then(Value::as_str)
+                    == Some(scope.tenant_id)
+                    && row.get("scope").and_then(Value::as_str) == Some(scope.value.as_str());
+                Some((id, same_scope))
+            })
+            .collect();
+        let previously_remapped: HashMap<String, String> = take_json_values(&mut response, 1)
+            .into_iter()
+            .filter_map(|pair| {
+                let pair = pair.as_array()?;
+                Some((
+                    pair.first()?.as_str()?.to_string(),
+                    pair.get(1)?.as_str()?.to_string(),
+                ))
+            })
+            .collect();
+
+        let mut to_create = Vec::new();
+        for (id, data) in batch {
+            if let Some(stored) = previously_remapped.get(id) {
+                mapping.insert(id.clone(), stored.clone());
+                counts.skipped += 1;
+                continue;
+            }
+            let mut data = data.clone();
+            data.insert(
+                "tenant_id".to_string(),
+                Value::String(scope.tenant_id.to_string()),
+            );
+            data.insert(scope.field.to_string(), Value::String(scope.value.clone()));
+            let stored = match existing.get(id) {
+                Some(true) => {
+                    mapping.insert(id.clone(), id.clone());
+                    counts.skipped += 1;
+                    continue;
+                }
+                Some(false) => {
+                    data.insert("imported_from".to_string(), Value::String(id.clone()));
+                    counts.remapped += 1;
+                    Uuid::new_v4().to_string()
+                }
+                None => {
+                    counts.created += 1;
+                    id.clone()
+                }
+            };
+            mapping.insert(id.clone(), stored.clone());
+            to_create.push(serde_json::json!({ "id": stored, "data": data }));
+        }
+
+        if !to_create.is_empty() {
+            state
+                .db
+                .client
+                .query(&insert)
+                .bind(("rows", to_create))
+                .await
+                .and_then(surrealdb::Response::check)
+                .map_err(|e| internal_error("Failed to import records", e))?;
+        }
+    }
+    Ok(mapping)
+}
+
+/// Ingest an archive produced by `export_project` into the request tenant.
+/// Importing the same archive again creates nothing new.
+pub async fn import_project(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    body: Bytes,
+) -> Result<Json<ImportReport>, (StatusCode, Json<Value>)> {
+    let (project_id, records) = read_archive(&body)?;
+    let tenant_id = tenant.as_str();
+
+    let mut objects = Vec::new();
+    let mut relationships: HashMap<String, Vec<ArchivedEdge>> = HashMap::new();
+    let mut cache_blocks = Vec::new();
+    for record in records {
+        match record {
+            ArchiveRecord::Object { id, data } => objects.push((id, data)),
+            ArchiveRecord::Relationship {
+                table,
+                source,
+                target,
+                data,
+            } => {
+                if !DEFAULT_RELATIONS.contains(&table.as_str()) {
+                    return Err(archive_error(format!(
+                        "Unknown relationship table '{}'",
+                        table
+                    )));
+                }
+                relationships
+                    .entry(table)
+                    .or_default()
+                    .push((source, target, data));
+            }
+            ArchiveRecord::CacheBlock { id, data } => cache_blocks.push((id, data)),
+            ArchiveRecord::Header { .. } => {}
+        }
+    }
+
+    let mut report = ImportReport {
+        project_id: project_id.clone(),
+        objects: ImportCounts::default(),
+        relationships: ImportCounts::default(),
+        cache_blocks: ImportCounts::default(),
+    };
+
+    let object_scope = RecordScope {
+        table: "objects",
+        field: "project_id",
+        value: project_id.clone(),
+        tenant_id,
+    };
+    let object_ids =
+        import_records(&state, &object_scope, objects, false, &mut report.objects).await?;
+
+    // Chunks and FileLogs point at their file node through `file_id`
+    let renamed: Vec<Value> = object_ids
+        .iter()
+        .filter(|(archived, stored)| archived != stored)
+        .map(|(archived, stored)| serde_json::json!({ "old": archived, "new": stored }))
+        .collect();
+    if !renamed.is_empty() {
+        state
+            .db
+            .client
+            .query("FOR $pair IN $pairs { UPDATE objects SET file_id = $pair.new WHERE tenant_id = $tenant_id AND project_id = $project_id AND file_id = $pair.old; };")
+            .bind(("pairs", renamed))
+            .bind(("tenant_id", tenant_id.to_string()))
+            .bind(("project_id", project_id.clone()))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| internal_error("Failed to relink remapped file ids", e))?;
+    }
+
+    for table in DEFAULT_RELATIONS {
+        let Some(edges) = relationships.remove(table) else {
+            continue;
+        };
+        let resolve = |id: &String| object_ids.get(id).cloned().unwrap_or_else(|| id.clone());
+        let edges: Vec<ArchivedEdge> = edges
+            .into_iter()
+            .map(|(source, target, data)| (resolve(&source), resolve(&target), data))
+            .collect();
+
+        let mut sources: Vec<String> = edges.iter().map(|(source, _, _)| source.clone()).collect();
+        sources.sort();
+        sources.dedup();
+        let mut response = state
+            .db
+            .client
+            .query(format!(
+                "SELECT VALUE [<string>meta::id(in), <string>meta::id(out)] FROM {} WHERE in IN $sources.map(|$id| type::thing('objects', $id))",
+                table
+            ))
+            .bind(("sources", sources))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| internal_error("Failed to look up existing relationships", e))?;
+        let mut seen: HashSet<(String, String)> = take_json_values(&mut response, 0)
+            .into_iter()
+            .filter_map(|pair| {
+                let pair = pair.as_array()?;
+                Some((
+                    pair.first()?.as_str()?.to_string(),
+                    pair.get(1)?.as_str()?.to_string(),
+                ))
+            })
+            .collect();
+
+        let mut to_create = Vec::new();
+        for (source, target, data) in edges {
+            if !seen.insert((source.clone(), target.clone())) {
+                report.relationships.skipped += 1;
+                continue;
+            }
+            to_create.push(serde_json::json!({ "in": source, "out": target, "data": data }));
+        }
+        let relate = format!(
+            "BEGIN TRANSACTION; FOR $row IN $rows {{ LET $source = type::thing('objects', $row.in); LET $target = type::thing('objects', $row.out); RELATE $source->{}->$target CONTENT {}; }}; COMMIT TRANSACTION;",
+            table, RESTORE_DATETIMES
+        );
+        for batch in to_create.chunks(IMPORT_BATCH_SIZE) {
+            state
+                .db
+                .client
+                .query(&relate)
+                .bind(("rows", batch.to_vec()))
+                .await
+                .and_then(surrealdb::Response::check)
+                .map_err(|e| internal_error("Failed to import relationships", e))?;
+            report.relationships.created += batch.len();
+        }
+    }
+
+    // A scope has at most one open block; keep the target's if it already has one
+    let scope_id = format!("project:{}", project_id);
+    let mut response = state
+        .db
+        .client
+        .query("SELECT VALUE count() FROM cache_block WHERE tenant_id = $tenant_id AND scope_id = $scope_id AND status = 'open' GROUP ALL")
+        .bind(("tenant_id", tenant_id.to_string()))
+        .bind(("scope_id", scope_id.clone()))
+        .await
+        .and_then(surrealdb::Response::check)
+        .map_err(|e| internal_error("Failed to check open cache blocks", e))?;
+    let has_open_block = take_json_values(&mut response, 0)
+        .first()
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+        > 0;
+    if has_open_block {
+        for (_, data) in cache_blocks.iter_mut() {
+            if data.get("status").and_then(Value::as_str) == Some("open") {
+                data.insert("status".to_string(), Value::String("closed".to_string()));
+            }
+        }
+    }
+    let cache_scope = RecordScope {
+        table: "cache_block",
+        field: "scope_id",
+        value: scope_id,
+        tenant_id,
+    };
+    import_records(
+        &state,
+        &cache_scope,
+        cache_blocks,
+        true,
+        &mut report.cache_blocks,
+    )
+    .await?;
+
+    tracing::info!(
+        "Imported project {}: objects {:?}, relationships {:?}, cache blocks {:?}",
+        project_id,
+        report.objects,
+        report.relationships,
+        report.cache_blocks
+    );
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    async fn run(state: &AppState, sql: &str) {
+        state.db.client.query(sql).await.unwrap().check().unwrap();
+    }
+
+    /// A file node with a chunk pointing at it by id, a symbol defined in the
+    /// file, and one closed cache block.
+    async fn seed_project(state: &AppState) {
+        run(
+            state,
+            "CREATE objects:file1 CONTENT { type: 'Symbol', kind: 'file', path: 'src/a.rs', project_id: 'p1', tenant_id: 'default', embedding: [0.5, 0.25] };
+             CREATE objects:chunk1 CONTENT { type: 'FileChunk', file_id: 'file1', content: 'fn a() {}', project_id: 'p1', tenant_id: 'default' };
+             CREATE objects:fn1 CONTENT { type: 'symbol', kind: 'function', name: 'a', project_id: 'p1', tenant_id: 'default' };
+             CREATE objects:other CONTENT { type: 'symbol', kind: 'function', name: 'b', project_id: 'p2', tenant_id: 'default' };
+             RELATE objects:fn1->defined_in->objects:file1 SET created_at = time::now();
+             RELATE objects:fn1->calls->objects:other;
+             CREATE cache_block:block1 SET tenant_id = 'default', scope_id = 'project:p1', sequence = 1, status = 'closed', items = [], created_at = time::now();",
+        )
+        .await;
+    }
+
+    async fn export(state: &AppState, project_id: &str) -> Bytes {
+        let response = export_project(
+            State(state.clone()),
+            Tenant::default(),
+            Path(project_id.to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/gzip");
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+    }
+
+    async fn import(state: &AppState, archive: Bytes) -> ImportReport {
+        let Json(report) = import_project(State(state.clone()), Tenant::default(), archive)
+            .await
+            .unwrap();
+        report
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_round_trips_and_is_idempotent() {
+        let source = memory_state(test_config()).await;
+        seed_project(&source).await;
+        let archive = export(&source, "p1").await;
+
+        let (project_id, records) = read_archive(&archive).unwrap();
+        assert_eq!(project_id, "p1");
+        // The calls edge leaves the project, so it stays behind
+        let tables: Vec<&str> = records
+            .iter()
+            .filter_map(|r| match r {
+                ArchiveRecord::Relationship { table, .. } => Some(table.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tables, vec!["defined_in"]);
+
+        let target = memory_state(test_config()).await;
+        let report = import(&target, archive.clone()).await;
+        assert_eq!(report.objects.created, 3);
+        assert_eq!(report.relationships.created, 1);
+        assert_eq!(report.cache_blocks.created, 1);
+
+        let mut response = target
+            .db
+            .client
+            .query("SELECT VALUE embedding FROM objects:file1")
+            .query("SELECT VALUE <string>meta::id(out) FROM defined_in WHERE in = objects:fn1")
+            .query("SELECT VALUE type::is::datetime(created_at) FROM cache_block:block1")
+            .await
+            .unwrap();
+        let embedding: Vec<Vec<f32>> = response.take(0).unwrap();
+        let targets: Vec<String> = response.take(1).unwrap();
+        let restored: Vec<bool> = response.take(2).unwrap();
+        assert_eq!(embedding, vec![vec![0.5, 0.25]]);
+        assert_eq!(targets, vec!["file1"]);
+        assert_eq!(restored, vec![true]);
+
+        let again = import(&target, archive).await;
+        assert_eq!(again.objects.created, 0);
+        assert_eq!(again.objects.skipped, 3);
+        assert_eq!(again.relationships.created, 0);
+        assert_eq!(again.cache_blocks.skipped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_remaps_colliding_ids_once() {
+        let source = memory_state(test_config()).await;
+        seed_project(&source).await;
+        let archive = export(&source, "p1").await;
+
+        let target = memory_state(test_config()).await;
+        run(
+            &target,
+            "CREATE objects:file1 CONTENT { type: 'decision', title: 'unrelated', project_id: 'elsewhere', tenant_id: 'default' };",
+        )
+        .await;
+
+        let report = import(&target, archive.clone()).await;
+        assert_eq!(report.objects.remapped, 1);
+        assert_eq!(report.objects.created, 2);
+
+        let mut response = target
+            .db
+            .client
+            .query("SELECT VALUE <string>meta::id(id) FROM objects WHERE imported_from = 'file1'")
+            .query("SELECT VALUE <string>meta::id(out) FROM defined_in WHERE in = objects:fn1")
+            .query("SELECT VALUE file_id FROM objects:chunk1")
+            .await
+            .unwrap();
+        let remapped: Vec<String> = response.take(0).unwrap();
+        let edge_targets: Vec<String> = response.take(1).unwrap();
+        let chunk_file: Vec<String> = response.take(2).unwrap();
+        assert_eq!(remapped.len(), 1);
+        assert_ne!(remapped[0], "file1");
+        assert_eq!(edge_targets, remapped);
+        assert_eq!(chunk_file, remapped);
+
+        let again = import(&target, archive).await;
+        assert_eq!(again.objects.skipped, 3);
+        assert_eq!(again.objects.remapped, 0);
+        assert_eq!(again.relationships.skipped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_archive_without_header() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(b"{\"kind\":\"object\",\"id\":\"a\",\"data\":{}}\n")
+            .unwrap();
+        let archive = Bytes::from(encoder.finish().unwrap());
+
+        let state = memory_state(test_config()).await;
+        let (status, _) = import_project(State(state), Tenant::default(), archive)
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,12 +1,22 @@
 use crate::{
+    database::knn_condition,
+    services::{
+        embedding::{EmbeddingIndex, EmbeddingService},
+        hybrid::{HybridRetrievalError, MergeStrategy, RetrievalMode, ScoreBreakdown},
+    },
     surreal_json::{normalize_object_ids, take_json_values},
+    tenant::Tenant,
     AppState,
 };
 use axum::{extract::State, http::StatusCode, response::Json};
 use serde::de::Error as SerdeError;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
 use tokio::time::{timeout, Duration};
+use tracing::Instrument;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -19,9 +29,36 @@ pub struct QueryRequest {
     pub hybrid: Option<bool>,
     pub graph_intersect: Option<bool>,
     pub graph_autoseed: Option<bool>,
+    /// Include per-result score breakdown and retrieval source (hybrid only)
+    pub explain: Option<bool>,
+    /// Reorder the top hybrid results by relevance to `text` (hybrid only)
+    pub rerank: Option<bool>,
+    /// How text, vector and graph results are fused; defaults to RRF with k=60 (hybrid only)
+    pub merge_strategy: Option<MergeStrategy>,
+    /// Rank with vector similarity, BM25, or both; unset keeps substring text scoring (hybrid only)
+    pub retrieval_mode: Option<RetrievalMode>,
+    /// Include the query embedding and each result's stored embedding; needs
+    /// `AMP_ALLOW_DEBUG_VECTORS` on the server
+    pub debug_vectors: Option<bool>,
+    /// Collapse FileChunk hits into one result per file; defaults to true
+    pub group_by_file: Option<bool>,
 }
 
+/// Find objects whose embeddings are closest to an existing object's embedding
 #[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SimilarRequest {
+    pub object_id: String,
+    pub limit: Option<usize>,
+    pub types: Option<Vec<String>>,
+    pub project_id: Option<String>,
+    /// Minimum cosine similarity for a result to be returned
+    pub min_score: Option<f32>,
+    /// Set from the request tenant, never from the body
+    #[serde(skip)]
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct QueryFilters {
     #[serde(rename = "type")]
     pub object_types: Option<Vec<String>>,
@@ -30,6 +67,12 @@ pub struct QueryFilters {
     pub tenant_id: Option<String>,
     pub created_after: Option<chrono::DateTime<chrono::Utc>>,
     pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Source language of symbols, file chunks and file logs
+    pub language: Option<String>,
+    /// Substring of the object's `path` or `file_path`
+    pub path: Option<String>,
+    /// Also return warnings whose `expires_at` has passed
+    pub include_expired: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -42,6 +85,106 @@ pub struct GraphQuery {
     pub algorithm: Option<TraversalAlgorithm>,
     #[serde(default, deserialize_with = "deserialize_uuidish_opt")]
     pub target_node: Option<Uuid>, // For shortest path algorithm
+    /// Set from the request tenant, never from the body
+    #[serde(skip)]
+    pub tenant_id: Option<String>,
+}
+
+impl GraphQuery {
+    /// Traversal target, restricted to the query tenant when one is set. The
+    /// query running it must bind `$tenant_id` to `tenant_id`.
+    pub fn target_table(&self) -> &'static str {
+        match &self.tenant_id {
+            Some(_) => "(objects WHERE tenant_id = $tenant_id)",
+            None => "objects",
+        }
+    }
+}
+
+impl QueryFilters {
+    /// `created_at` may be stored as an RFC 3339 string, so compare the cast value.
+    pub fn created_conditions(&self) -> Vec<String> {
+        let mut conditions = Vec::new();
+        if let Some(created_after) = &self.created_after {
+            conditions.push(format!(
+                "created_at != NONE AND <datetime>created_at >= <datetime>'{}'",
+                created_after.to_rfc3339()
+            ));
+        }
+        if let Some(created_before) = &self.created_before {
+            conditions.push(format!(
+                "created_at != NONE AND <datetime>created_at <= <datetime>'{}'",
+                created_before.to_rfc3339()
+            ));
+        }
+        conditions
+    }
+
+    /// Language and path scoping, e.g. from `amp search --lang rust --in src/`.
+    pub fn code_conditions(&self) -> Vec<String> {
+        let mut conditions = Vec::new();
+        if let Some(language) = &self.language {
+            conditions.push(format!("language = '{}'", language.replace("'", "\\'")));
+        }
+        if let Some(path) = &self.path {
+            let path = path.replace("'", "\\'");
+            conditions.push(format!(
+                "(path CONTAINS '{}' OR file_path CONTAINS '{}')",
+                path, path
+            ));
+        }
+        conditions
+    }
+}
+
+/// Hides expired warnings unless the filters ask for them. Applies even when
+/// the request has no filters at all.
+pub fn expired_warning_condition(filters: Option<&QueryFilters>) -> Option<String> {
+    if filters.and_then(|f| f.include_expired) == Some(true) {
+        return None;
+    }
+    Some(
+        "(type != 'warning' OR expires_at = NONE OR <datetime>expires_at > time::now())"
+            .to_string(),
+    )
+}
+
+impl QueryRequest {
+    /// Pin the request to `tenant`, overriding any tenant the caller supplied.
+    pub fn scope_to_tenant(&mut self, tenant: &Tenant) {
+        self.filters
+            .get_or_insert_with(QueryFilters::default)
+            .tenant_id = Some(tenant.as_str().to_string());
+        if let Some(graph) = self.graph.as_mut() {
+            graph.tenant_id = Some(tenant.as_str().to_string());
+        }
+    }
+}
+
+/// Query text as it appears in logs and spans: verbatim when
+/// `AMP_LOG_QUERY_CONTENT` is on, otherwise only a hash prefix and length.
+pub struct QueryLog<'a> {
+    text: Option<&'a str>,
+    verbatim: bool,
+}
+
+impl<'a> QueryLog<'a> {
+    pub fn new(text: Option<&'a str>, verbatim: bool) -> Self {
+        Self { text, verbatim }
+    }
+}
+
+impl fmt::Display for QueryLog<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.text {
+            None => write!(f, "none"),
+            Some(text) if self.verbatim => write!(f, "{:?}", text),
+            Some(text) => {
+                let digest = hex::encode(Sha256::digest(text.as_bytes()));
+                write!(f, "sha256:{} len={}", &digest[..12], text.chars().count())
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -72,6 +215,9 @@ pub struct QueryResponse {
     pub vector_results_count: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub graph_results_count: Option<usize>,
+    /// Embedding the query was searched with (`debug_vectors` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_embedding: Option<Vec<f32>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -80,25 +226,119 @@ pub struct QueryResult {
     pub score: f32,
     pub explanation: String,
     pub path: Option<Vec<Value>>, // New field for traversal paths
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<ScoreBreakdown>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Rerank relevance; `score` stays the retrieval score
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_score: Option<f32>,
+    /// Stored embedding of the result object (`debug_vectors` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+    /// Set when `object` is the best chunk standing in for its whole file
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub file_group: Option<FileGroup>,
+}
+
+/// Chunk hits of one file collapsed by `group_by_file`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileGroup {
+    pub matched_chunks: usize,
+    /// Merged `[start_line, end_line]` ranges of the matched chunks
+    pub line_ranges: Vec<(u64, u64)>,
+    /// Summary from the file's FileLog, when there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_summary: Option<String>,
 }
 
 pub async fn query(
     State(state): State<AppState>,
-    Json(request): Json<QueryRequest>,
+    tenant: Tenant,
+    Json(mut request): Json<QueryRequest>,
 ) -> Result<Json<QueryResponse>, StatusCode> {
-    let start_time = std::time::Instant::now();
     let trace_id = Uuid::new_v4();
+    request.scope_to_tenant(&tenant);
+    let span = tracing::info_span!(
+        "query",
+        %trace_id,
+        text = %QueryLog::new(request.text.as_deref(), state.config.log_query_content)
+    );
+    let project_id = request.filters.as_ref().and_then(|f| f.project_id.clone());
+    let analytics = state.analytics_service.clone();
+    let debug_vectors =
+        (request.debug_vectors.unwrap_or(false) && state.config.allow_debug_vectors).then(|| {
+            (
+                request.vector.clone(),
+                request.text.clone(),
+                query_embedding_index(&request),
+            )
+        });
+    // Fetch extra hits so `limit` still applies to files after grouping
+    let group_limit = request
+        .group_by_file
+        .unwrap_or(true)
+        .then(|| request.limit.unwrap_or(10));
+    if let Some(limit) = group_limit {
+        request.limit = Some(limit * GROUP_FETCH_FACTOR);
+    }
+    let tenant_id = tenant.as_str().to_string();
+    let mut response = execute_query(state.clone(), request, trace_id)
+        .instrument(span)
+        .await?;
+    if let Some(limit) = group_limit {
+        let results = std::mem::take(&mut response.results);
+        response.results = group_chunks_by_file(results);
+        response.total_count = response.results.len();
+        response.results.truncate(limit);
+        attach_file_summaries(&state, &tenant_id, &mut response.results).await;
+    }
+    if let Some((vector, text, index)) = debug_vectors {
+        attach_debug_vectors(&state, &mut response, vector, text, index).await;
+    }
+
+    let top_score = response
+        .results
+        .iter()
+        .map(|result| result.score)
+        .reduce(f32::max);
+    let result_count = response.results.len();
+    tokio::spawn(async move {
+        analytics
+            .record_query(project_id.as_deref(), result_count, top_score)
+            .await;
+    });
+    Ok(response)
+}
+
+async fn execute_query(
+    state: AppState,
+    request: QueryRequest,
+    trace_id: Uuid,
+) -> Result<Json<QueryResponse>, StatusCode> {
+    let start_time = std::time::Instant::now();
 
-    tracing::info!("Query request: trace_id={}, text={:?}, has_vector={}, has_graph={}, hybrid={:?}, filters={:?}", 
-        trace_id, request.text, request.vector.is_some(), request.graph.is_some(), request.hybrid, request.filters);
+    tracing::info!("Query request: trace_id={}, has_vector={}, has_graph={}, hybrid={:?}, filters={:?}", 
+        trace_id, request.vector.is_some(), request.graph.is_some(), request.hybrid, request.filters);
 
     // Check if this is a hybrid query
     if request.hybrid.unwrap_or(false) {
         tracing::info!("Executing hybrid query: trace_id={}", trace_id);
 
         match state.hybrid_service.execute_hybrid_query(&request).await {
-            Ok(hybrid_response) => {
+            Ok(mut hybrid_response) => {
+                if let (true, Some(text)) =
+                    (request.rerank.unwrap_or(false), request.text.as_deref())
+                {
+                    let settings = state.settings_service.load_settings().await.ok();
+                    state
+                        .hybrid_service
+                        .rerank(text, &mut hybrid_response.results, settings.as_ref())
+                        .await;
+                }
+
                 // Convert HybridResult to QueryResult for response compatibility
+                let explain = request.explain.unwrap_or(false);
                 let results: Vec<QueryResult> = hybrid_response
                     .results
                     .into_iter()
@@ -107,6 +347,11 @@ pub async fn query(
                         score: hybrid_result.total_score,
                         explanation: hybrid_result.explanation,
                         path: None, // Hybrid results don't have path information yet
+                        score_breakdown: explain.then_some(hybrid_result.score_breakdown),
+                        source: explain.then_some(hybrid_result.source),
+                        rerank_score: hybrid_result.rerank_score,
+                        embedding: None,
+                        file_group: None,
                     })
                     .collect();
 
@@ -118,6 +363,7 @@ pub async fn query(
                     text_results_count: Some(hybrid_response.text_results_count),
                     vector_results_count: Some(hybrid_response.vector_results_count),
                     graph_results_count: Some(hybrid_response.graph_results_count),
+                    query_embedding: None,
                 }));
             }
             Err(e) => {
@@ -180,6 +426,11 @@ pub async fn query(
                                         }).collect()
                                     })
                                 }),
+                                score_breakdown: None,
+                                source: None,
+                                rerank_score: None,
+                                embedding: None,
+                                file_group: None,
                             }
                         })
                         .collect();
@@ -202,6 +453,7 @@ pub async fn query(
                         text_results_count: None,
                         vector_results_count: None,
                         graph_results_count: None,
+                        query_embedding: None,
                     }));
                 }
                 Err(e) => {
@@ -217,7 +469,20 @@ pub async fn query(
 
         tracing::debug!("Executing single-hop graph query: {}", query_str);
 
-        let query_result = timeout(Duration::from_secs(5), state.db.client.query(query_str)).await;
+        let tenant_id = request
+            .filters
+            .as_ref()
+            .and_then(|f| f.tenant_id.clone())
+            .or_else(|| graph.tenant_id.clone());
+        let query_result = timeout(
+            Duration::from_secs(5),
+            state
+                .db
+                .client
+                .query(query_str)
+                .bind(("tenant_id", tenant_id)),
+        )
+        .await;
 
         let objects: Vec<Value> = match query_result {
             Ok(Ok(mut response)) => {
@@ -267,6 +532,11 @@ pub async fn query(
                     score: 1.0,
                     explanation: "Graph traversal result".to_string(),
                     path: None, // TODO: Extract path information from recursive query results
+                    score_breakdown: None,
+                    source: None,
+                    rerank_score: None,
+                    embedding: None,
+                    file_group: None,
                 }
             })
             .collect();
@@ -289,22 +559,25 @@ pub async fn query(
             text_results_count: None,
             vector_results_count: None,
             graph_results_count: None,
+            query_embedding: None,
         }));
     }
 
+    let embedding_service = state.embedding_for_index(query_embedding_index(&request));
+
     // Determine if we should use vector search
     tracing::info!(
         "Non-hybrid query: determining query vector, embedding_enabled={}",
-        state.embedding_service.is_enabled()
+        embedding_service.is_enabled()
     );
     let query_vector = if let Some(vector) = &request.vector {
         tracing::info!("Using provided vector");
         Some(vector.clone())
     } else if let Some(text) = &request.text {
         // Generate embedding from text query if service is enabled
-        if state.embedding_service.is_enabled() {
-            tracing::info!("Generating embedding for text: '{}'", text);
-            match state.embedding_service.generate_embedding(text).await {
+        if embedding_service.is_enabled() {
+            tracing::info!("Generating embedding for query text");
+            match embedding_service.generate_query_embedding(text).await {
                 Ok(vec) => {
                     tracing::info!(
                         "Generated embedding from text query: {} dimensions",
@@ -327,20 +600,32 @@ pub async fn query(
     };
 
     // Build query based on whether we have a vector
-    let query_str = if query_vector.is_some() {
+    let query_str = if let Some(vector) = &query_vector {
         tracing::info!("Building vector query");
-        build_vector_query_string(&request, &query_vector.as_ref().unwrap())
+        let knn = state.db.vector_index_dimension("objects").await == Some(vector.len());
+        build_vector_query_string(&request, vector, knn)
     } else {
         tracing::info!("Building text query");
         build_query_string(&request)
     };
 
     tracing::info!("Executing query length: {} chars", query_str.len());
-    // Log a hash of the query for debugging
-    tracing::debug!("Full query: {}", query_str);
+    // The SQL inlines the query text
+    if state.config.log_query_content {
+        tracing::debug!("Full query: {}", query_str);
+    }
 
     // Execute with timeout
-    let query_result = timeout(Duration::from_secs(5), state.db.client.query(query_str)).await;
+    let tenant_id = request.filters.as_ref().and_then(|f| f.tenant_id.clone());
+    let query_result = timeout(
+        Duration::from_secs(5),
+        state
+            .db
+            .client
+            .query(query_str)
+            .bind(("tenant_id", tenant_id)),
+    )
+    .await;
 
     let objects = match query_result {
         Ok(Ok(mut response)) => {
@@ -370,6 +655,11 @@ pub async fn query(
                 score,
                 explanation,
                 path: None, // Non-graph queries don't have path information
+                score_breakdown: None,
+                source: None,
+                rerank_score: None,
+                embedding: None,
+                file_group: None,
             }
         })
         .collect();
@@ -399,6 +689,296 @@ pub async fn query(
         text_results_count: None,
         vector_results_count: None,
         graph_results_count: None,
+        query_embedding: None,
+    }))
+}
+
+/// Hits fetched per requested result when grouping chunks by file
+const GROUP_FETCH_FACTOR: usize = 4;
+/// Score bonus per extra matching chunk of a file, as a fraction of its best chunk's score
+const GROUP_CHUNK_BONUS: f32 = 0.05;
+/// Cap on the multi-chunk bonus
+const GROUP_MAX_BONUS: f32 = 0.2;
+
+fn is_chunk(object: &Value) -> bool {
+    matches!(
+        object.get("type").and_then(Value::as_str),
+        Some("FileChunk" | "filechunk" | "file_chunk")
+    )
+}
+
+/// Collapse chunk hits of the same file into one result in the slot of its
+/// first hit: the best-scoring chunk is kept as the preview, scored at its own
+/// score plus a small bonus for each further matching chunk. Other results
+/// pass through unchanged.
+fn group_chunks_by_file(results: Vec<QueryResult>) -> Vec<QueryResult> {
+    let mut grouped: Vec<QueryResult> = Vec::with_capacity(results.len());
+    let mut file_slots: HashMap<String, usize> = HashMap::new();
+    // Slot of each file's best chunk -> (matched chunks, their line ranges)
+    let mut matches: HashMap<usize, (usize, Vec<(u64, u64)>)> = HashMap::new();
+
+    for result in results {
+        let file_path = (result.path.is_none() && is_chunk(&result.object))
+            .then(|| result.object.get("file_path").and_then(Value::as_str))
+            .flatten()
+            .map(str::to_string);
+        let Some(file_path) = file_path else {
+            grouped.push(result);
+            continue;
+        };
+        let line = |key: &str| result.object.get(key).and_then(Value::as_u64);
+        let range = line("start_line").zip(line("end_line"));
+
+        let slot = match file_slots.get(&file_path) {
+            Some(&slot) => {
+                if result.score > grouped[slot].score {
+                    grouped[slot] = result;
+                }
+                slot
+            }
+            None => {
+                file_slots.insert(file_path, grouped.len());
+                grouped.push(result);
+                grouped.len() - 1
+            }
+        };
+        let (count, ranges) = matches.entry(slot).or_default();
+        *count += 1;
+        ranges.extend(range);
+    }
+
+    for (slot, (matched_chunks, ranges)) in matches {
+        let result = &mut grouped[slot];
+        let bonus = (GROUP_CHUNK_BONUS * (matched_chunks - 1) as f32).min(GROUP_MAX_BONUS);
+        result.score *= 1.0 + bonus;
+        result.file_group = Some(FileGroup {
+            matched_chunks,
+            line_ranges: merge_line_ranges(ranges),
+            file_summary: None,
+        });
+    }
+    // Reranked results keep the reranker's order
+    if grouped.iter().all(|result| result.rerank_score.is_none()) {
+        grouped.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+    grouped
+}
+
+/// Sort ranges and merge those that overlap or touch.
+fn merge_line_ranges(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Attach the FileLog summary of each grouped file that has one.
+async fn attach_file_summaries(state: &AppState, tenant_id: &str, results: &mut [QueryResult]) {
+    let paths: Vec<String> = results
+        .iter()
+        .filter(|result| result.file_group.is_some())
+        .filter_map(|result| result.object.get("file_path").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+
+    let query = "SELECT file_path, summary, updated_at FROM objects WHERE type = 'FileLog' AND tenant_id = $tenant_id AND file_path IN $paths ORDER BY updated_at DESC";
+    let rows = match state
+        .db
+        .client
+        .query(query)
+        .bind(("tenant_id", tenant_id.to_string()))
+        .bind(("paths", paths))
+        .await
+    {
+        Ok(mut response) => take_json_values(&mut response, 0),
+        Err(e) => {
+            tracing::warn!("Failed to load file summaries: {}", e);
+            return;
+        }
+    };
+    // Newest first, so the first summary seen for a path wins
+    let mut summaries: HashMap<String, String> = HashMap::new();
+    for row in rows {
+        if let (Some(path), Some(summary)) = (
+            row.get("file_path").and_then(Value::as_str),
+            row.get("summary").and_then(Value::as_str),
+        ) {
+            summaries
+                .entry(path.to_string())
+                .or_insert_with(|| summary.to_string());
+        }
+    }
+    for result in results {
+        let Some(path) = result.object.get("file_path").and_then(Value::as_str) else {
+            continue;
+        };
+        if let (Some(group), Some(summary)) = (result.file_group.as_mut(), summaries.get(path)) {
+            group.file_summary = Some(summary.clone());
+        }
+    }
+}
+
+/// Queries restricted to code objects are embedded with the code model
+fn query_embedding_index(request: &QueryRequest) -> EmbeddingIndex {
+    let code_only = request
+        .filters
+        .as_ref()
+        .and_then(|filters| filters.object_types.as_ref())
+        .is_some_and(|types| {
+            !types.is_empty()
+                && types
+                    .iter()
+                    .all(|t| EmbeddingIndex::for_object_type(t) == EmbeddingIndex::Code)
+        });
+    if code_only {
+        EmbeddingIndex::Code
+    } else {
+        EmbeddingIndex::Prose
+    }
+}
+
+/// Fill in `query_embedding` and each result's stored `embedding` for a
+/// `debug_vectors` query. Missing vectors are left unset rather than failing the query.
+async fn attach_debug_vectors(
+    state: &AppState,
+    response: &mut QueryResponse,
+    vector: Option<Vec<f32>>,
+    text: Option<String>,
+    index: EmbeddingIndex,
+) {
+    response.query_embedding = match (vector, text) {
+        (Some(vector), _) => Some(vector),
+        (None, Some(text)) => {
+            let service = state.embedding_for_index(index);
+            if service.is_enabled() {
+                service.generate_query_embedding(&text).await.ok()
+            } else {
+                None
+            }
+        }
+        (None, None) => None,
+    };
+
+    let ids: Vec<Option<String>> = response
+        .results
+        .iter()
+        .map(|result| {
+            let id = result.object.get("id")?.as_str()?;
+            Some(
+                id.trim_start_matches("objects:")
+                    .trim_matches('`')
+                    .trim_matches('⟨')
+                    .trim_matches('⟩')
+                    .to_string(),
+            )
+        })
+        .collect();
+    let lookup: Vec<String> = ids.iter().flatten().cloned().collect();
+    if lookup.is_empty() {
+        return;
+    }
+
+    let rows = match state
+        .db
+        .client
+        .query("SELECT record::id(id) AS id, embedding FROM objects WHERE record::id(id) IN $ids")
+        .bind(("ids", lookup))
+        .await
+    {
+        Ok(mut response) => take_json_values(&mut response, 0),
+        Err(e) => {
+            tracing::warn!("Failed to load result embeddings: {}", e);
+            return;
+        }
+    };
+    let embeddings: HashMap<String, Vec<f32>> = rows
+        .into_iter()
+        .filter_map(|row| {
+            let id = row.get("id")?.as_str()?.to_string();
+            let embedding = serde_json::from_value(row.get("embedding")?.clone()).ok()?;
+            Some((id, embedding))
+        })
+        .collect();
+    for (result, id) in response.results.iter_mut().zip(ids) {
+        result.embedding = id.and_then(|id| embeddings.get(&id).cloned());
+    }
+}
+
+pub async fn find_similar(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Json(mut request): Json<SimilarRequest>,
+) -> Result<Json<QueryResponse>, StatusCode> {
+    let start_time = std::time::Instant::now();
+    let trace_id = Uuid::new_v4();
+    request.tenant_id = Some(tenant.as_str().to_string());
+
+    tracing::info!(
+        "Similar query: trace_id={}, object_id={}, types={:?}, min_score={:?}",
+        trace_id,
+        request.object_id,
+        request.types,
+        request.min_score
+    );
+
+    let matches = match state
+        .hybrid_service
+        .find_similar_by_embedding(&request)
+        .await
+    {
+        Ok(matches) => matches,
+        Err(HybridRetrievalError::NotFound(e)) => {
+            tracing::warn!("Similar query failed: {}", e);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(HybridRetrievalError::InvalidQuery(e)) => {
+            tracing::warn!("Similar query rejected: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        Err(HybridRetrievalError::Timeout) => return Err(StatusCode::GATEWAY_TIMEOUT),
+        Err(e) => {
+            tracing::error!("Similar query failed: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let results: Vec<QueryResult> = matches
+        .into_iter()
+        .map(|(object, score)| QueryResult {
+            object,
+            score,
+            explanation: format!("Embedding similarity to {}", request.object_id),
+            path: None,
+            score_breakdown: None,
+            source: None,
+            rerank_score: None,
+            embedding: None,
+            file_group: None,
+        })
+        .collect();
+
+    let total_count = results.len();
+    Ok(Json(QueryResponse {
+        results,
+        trace_id,
+        total_count,
+        execution_time_ms: start_time.elapsed().as_millis() as u64,
+        text_results_count: None,
+        vector_results_count: Some(total_count),
+        graph_results_count: None,
+        query_embedding: None,
     }))
 }
 
@@ -442,24 +1022,15 @@ fn build_query_string(request: &QueryRequest) -> String {
             conditions.push(format!("project_id = '{}'", project_id.replace("'", "\\'")));
         }
 
-        if let Some(tenant_id) = &filters.tenant_id {
-            conditions.push(format!("tenant_id = '{}'", tenant_id.replace("'", "\\'")));
-        }
-
-        if let Some(created_after) = &filters.created_after {
-            conditions.push(format!(
-                "created_at >= time::from::unix({})",
-                created_after.timestamp()
-            ));
+        // Bound as $tenant_id; the header value is never spliced into the SQL
+        if filters.tenant_id.is_some() {
+            conditions.push("tenant_id = $tenant_id".to_string());
         }
 
-        if let Some(created_before) = &filters.created_before {
-            conditions.push(format!(
-                "created_at <= time::from::unix({})",
-                created_before.timestamp()
-            ));
-        }
+        conditions.extend(filters.created_conditions());
+        conditions.extend(filters.code_conditions());
     }
+    conditions.extend(expired_warning_condition(request.filters.as_ref()));
 
     // Combine conditions
     if !conditions.is_empty() {
@@ -473,19 +1044,25 @@ fn build_query_string(request: &QueryRequest) -> String {
 
     // Wrap in outer projection query
     format!(
-        "SELECT VALUE {{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, title: title, kind: kind, path: path, language: language, signature: signature, documentation: documentation, summary: summary, description: description, content: content, tags: tags, linked_files: linked_files, file_path: file_path, files_changed: files_changed, decision: decision, diff_summary: diff_summary, context: context, category: category, created_at: created_at, updated_at: updated_at, provenance: provenance, links: links, embedding: embedding, input_summary: input_summary, status: status, duration_ms: duration_ms, confidence: confidence }} FROM ({})",
+        "SELECT VALUE {{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, title: title, kind: kind, path: path, language: language, signature: signature, documentation: documentation, summary: summary, description: description, content: content, tags: tags, linked_files: linked_files, file_path: file_path, start_line: start_line, end_line: end_line, files_changed: files_changed, decision: decision, diff_summary: diff_summary, context: context, category: category, created_at: created_at, updated_at: updated_at, provenance: provenance, links: links, embedding: embedding, input_summary: input_summary, status: status, duration_ms: duration_ms, confidence: confidence, body: body, severity: severity, expires_at: expires_at }} FROM ({})",
         inner_query
     )
 }
 
-fn build_vector_query_string(request: &QueryRequest, vector: &[f32]) -> String {
+fn build_vector_query_string(request: &QueryRequest, vector: &[f32], knn: bool) -> String {
     let vector_str = vector
         .iter()
         .map(|f| f.to_string())
         .collect::<Vec<_>>()
         .join(", ");
+    let limit = request.limit.unwrap_or(10);
 
-    let mut inner_query = "SELECT id, type, tenant_id, project_id, name, title, kind, path, language, signature, documentation, summary, description, content, tags, linked_files, file_path, files_changed, decision, diff_summary, context, category, created_at, updated_at, provenance, links, embedding, input_summary, status, duration_ms, confidence FROM objects WHERE embedding IS NOT NONE AND embedding IS NOT NULL".to_string();
+    let candidates = if knn {
+        format!("{} AND ", knn_condition("embedding", limit, vector))
+    } else {
+        String::new()
+    };
+    let mut inner_query = format!("SELECT id, type, tenant_id, project_id, name, title, kind, path, language, signature, documentation, summary, description, content, tags, linked_files, file_path, start_line, end_line, files_changed, decision, diff_summary, context, category, created_at, updated_at, provenance, links, embedding, input_summary, status, duration_ms, confidence, body, severity, expires_at FROM objects WHERE {}embedding IS NOT NONE AND embedding IS NOT NULL", candidates);
 
     let mut conditions = Vec::new();
 
@@ -513,23 +1090,13 @@ fn build_vector_query_string(request: &QueryRequest, vector: &[f32]) -> String {
             conditions.push(format!("project_id = '{}'", project_id.replace("'", "\\'")));
         }
 
-        if let Some(tenant_id) = &filters.tenant_id {
-            conditions.push(format!("tenant_id = '{}'", tenant_id.replace("'", "\\'")));
-        }
-
-        if let Some(created_after) = &filters.created_after {
-            conditions.push(format!(
-                "created_at >= time::from::unix({})",
-                created_after.timestamp()
-            ));
+        // Bound as $tenant_id; the header value is never spliced into the SQL
+        if filters.tenant_id.is_some() {
+            conditions.push("tenant_id = $tenant_id".to_string());
         }
 
-        if let Some(created_before) = &filters.created_before {
-            conditions.push(format!(
-                "created_at <= time::from::unix({})",
-                created_before.timestamp()
-            ));
-        }
+        conditions.extend(filters.created_conditions());
+        conditions.extend(filters.code_conditions());
     }
 
     // Add additional conditions
@@ -537,16 +1104,18 @@ fn build_vector_query_string(request: &QueryRequest, vector: &[f32]) -> String {
         inner_query.push_str(" AND ");
         inner_query.push_str(&conditions.join(" AND "));
     }
+    // Filtered after the nearest-neighbour scan, as in the hybrid vector query
+    let expiry = expired_warning_condition(request.filters.as_ref())
+        .map(|condition| format!(" WHERE {}", condition))
+        .unwrap_or_default();
 
-    // Limit
-    let limit = request.limit.unwrap_or(10);
     let inner_ranked_query = format!(
-        "SELECT id, type, tenant_id, project_id, name, title, kind, path, language, signature, documentation, summary, description, content, tags, linked_files, file_path, files_changed, decision, diff_summary, context, category, created_at, updated_at, provenance, links, embedding, input_summary, status, duration_ms, confidence, vector::similarity::cosine(embedding, [{}]) AS similarity FROM ({}) ORDER BY similarity DESC LIMIT {}",
-        vector_str, inner_query, limit
+        "SELECT id, type, tenant_id, project_id, name, title, kind, path, language, signature, documentation, summary, description, content, tags, linked_files, file_path, start_line, end_line, files_changed, decision, diff_summary, context, category, created_at, updated_at, provenance, links, embedding, input_summary, status, duration_ms, confidence, body, severity, expires_at, vector::similarity::cosine(embedding, [{}]) AS similarity FROM ({}){} ORDER BY similarity DESC LIMIT {}",
+        vector_str, inner_query, expiry, limit
     );
 
     format!(
-        "SELECT VALUE {{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, title: title, kind: kind, path: path, language: language, signature: signature, documentation: documentation, summary: summary, description: description, content: content, tags: tags, linked_files: linked_files, file_path: file_path, files_changed: files_changed, decision: decision, diff_summary: diff_summary, context: context, category: category, created_at: created_at, updated_at: updated_at, provenance: provenance, links: links, embedding: embedding, input_summary: input_summary, status: status, duration_ms: duration_ms, confidence: confidence, similarity: similarity }} FROM ({})",
+        "SELECT VALUE {{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, title: title, kind: kind, path: path, language: language, signature: signature, documentation: documentation, summary: summary, description: description, content: content, tags: tags, linked_files: linked_files, file_path: file_path, start_line: start_line, end_line: end_line, files_changed: files_changed, decision: decision, diff_summary: diff_summary, context: context, category: category, created_at: created_at, updated_at: updated_at, provenance: provenance, links: links, embedding: embedding, input_summary: input_summary, status: status, duration_ms: duration_ms, confidence: confidence, body: body, severity: severity, expires_at: expires_at, similarity: similarity }} FROM ({})",
         inner_ranked_query
     )
 }
@@ -561,7 +1130,8 @@ fn build_graph_query_string(
         .as_ref()
         .unwrap_or(&GraphDirection::Outbound);
     let max_depth = graph.max_depth.unwrap_or(3);
-    let projection = "{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, title: title, kind: kind, path: path, language: language, signature: signature, documentation: documentation, summary: summary, description: description, content: content, tags: tags, linked_files: linked_files, file_path: file_path, files_changed: files_changed, decision: decision, diff_summary: diff_summary, context: context, category: category, created_at: created_at, updated_at: updated_at, provenance: provenance, links: links, embedding: embedding, input_summary: input_summary, status: status, duration_ms: duration_ms, confidence: confidence }";
+    let target = graph.target_table();
+    let projection = "{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, title: title, kind: kind, path: path, language: language, signature: signature, documentation: documentation, summary: summary, description: description, content: content, tags: tags, linked_files: linked_files, file_path: file_path, start_line: start_line, end_line: end_line, files_changed: files_changed, decision: decision, diff_summary: diff_summary, context: context, category: category, created_at: created_at, updated_at: updated_at, provenance: provenance, links: links, embedding: embedding, input_summary: input_summary, status: status, duration_ms: duration_ms, confidence: confidence, body: body, severity: severity, expires_at: expires_at }";
 
     // Build the start node list
     let start_ids_list = graph
@@ -583,6 +1153,7 @@ fn build_graph_query_string(
             "modifies".to_string(),
             "implements".to_string(),
             "produced".to_string(),
+            "superseded_by".to_string(),
         ]
     };
     let relation_clause = if relation_list.len() == 1 {
@@ -616,26 +1187,26 @@ fn build_graph_query_string(
                 GraphDirection::Outbound => {
                     if max_depth <= 1 {
                         format!(
-                            "SELECT VALUE {{ connected: ->{}->objects.{} }} FROM {}",
-                            relation_clause, projection, start_ids_list
+                            "SELECT VALUE {{ connected: ->{}->{}.{} }} FROM {}",
+                            relation_clause, target, projection, start_ids_list
                         )
                     } else {
                         // Multi-hop simulation: traverse multiple levels and collect unique results
                         format!(
-                            "SELECT VALUE {{ connected: ->{}->objects.{} }} FROM {}",
-                            relation_clause, projection, start_ids_list
+                            "SELECT VALUE {{ connected: ->{}->{}.{} }} FROM {}",
+                            relation_clause, target, projection, start_ids_list
                         )
                     }
                 }
                 GraphDirection::Inbound => {
                     format!(
-                        "SELECT VALUE {{ connected: <-{}<-objects.{} }} FROM {}",
-                        relation_clause, projection, start_ids_list
+                        "SELECT VALUE {{ connected: <-{}<-{}.{} }} FROM {}",
+                        relation_clause, target, projection, start_ids_list
                     )
                 }
                 GraphDirection::Both => {
-                    format!("SELECT VALUE {{ outbound: ->{}->objects.{}, inbound: <-{}<-objects.{} }} FROM {}", 
-                        relation_clause, projection, relation_clause, projection, start_ids_list)
+                    format!("SELECT VALUE {{ outbound: ->{}->{}.{}, inbound: <-{}<-{}.{} }} FROM {}", 
+                        relation_clause, target, projection, relation_clause, target, projection, start_ids_list)
                 }
             }
         }
@@ -644,19 +1215,19 @@ fn build_graph_query_string(
             match direction {
                 GraphDirection::Outbound => {
                     format!(
-                        "SELECT VALUE {{ connected: ->{}->objects.{} }} FROM {}",
-                        relation_clause, projection, start_ids_list
+                        "SELECT VALUE {{ connected: ->{}->{}.{} }} FROM {}",
+                        relation_clause, target, projection, start_ids_list
                     )
                 }
                 GraphDirection::Inbound => {
                     format!(
-                        "SELECT VALUE {{ connected: <-{}<-objects.{} }} FROM {}",
-                        relation_clause, projection, start_ids_list
+                        "SELECT VALUE {{ connected: <-{}<-{}.{} }} FROM {}",
+                        relation_clause, target, projection, start_ids_list
                     )
                 }
                 GraphDirection::Both => {
-                    format!("SELECT VALUE {{ outbound: ->{}->objects.{}, inbound: <-{}<-objects.{} }} FROM {}", 
-                        relation_clause, projection, relation_clause, projection, start_ids_list)
+                    format!("SELECT VALUE {{ outbound: ->{}->{}.{}, inbound: <-{}<-{}.{} }} FROM {}", 
+                        relation_clause, target, projection, relation_clause, target, projection, start_ids_list)
                 }
             }
         }
@@ -665,19 +1236,19 @@ fn build_graph_query_string(
             match direction {
                 GraphDirection::Outbound => {
                     format!(
-                        "SELECT VALUE {{ connected: ->{}->objects.{} }} FROM {}",
-                        relation_clause, projection, start_ids_list
+                        "SELECT VALUE {{ connected: ->{}->{}.{} }} FROM {}",
+                        relation_clause, target, projection, start_ids_list
                     )
                 }
                 GraphDirection::Inbound => {
                     format!(
-                        "SELECT VALUE {{ connected: <-{}<-objects.{} }} FROM {}",
-                        relation_clause, projection, start_ids_list
+                        "SELECT VALUE {{ connected: <-{}<-{}.{} }} FROM {}",
+                        relation_clause, target, projection, start_ids_list
                     )
                 }
                 GraphDirection::Both => {
-                    format!("SELECT VALUE {{ outbound: ->{}->objects.{}, inbound: <-{}<-objects.{} }} FROM {}", 
-                        relation_clause, projection, relation_clause, projection, start_ids_list)
+                    format!("SELECT VALUE {{ outbound: ->{}->{}.{}, inbound: <-{}<-{}.{} }} FROM {}", 
+                        relation_clause, target, projection, relation_clause, target, projection, start_ids_list)
                 }
             }
         }
@@ -686,19 +1257,19 @@ fn build_graph_query_string(
             match direction {
                 GraphDirection::Outbound => {
                     format!(
-                        "SELECT VALUE {{ connected: ->{}->objects.{} }} FROM {}",
-                        relation_clause, projection, start_ids_list
+                        "SELECT VALUE {{ connected: ->{}->{}.{} }} FROM {}",
+                        relation_clause, target, projection, start_ids_list
                     )
                 }
                 GraphDirection::Inbound => {
                     format!(
-                        "SELECT VALUE {{ connected: <-{}<-objects.{} }} FROM {}",
-                        relation_clause, projection, start_ids_list
+                        "SELECT VALUE {{ connected: <-{}<-{}.{} }} FROM {}",
+                        relation_clause, target, projection, start_ids_list
                     )
                 }
                 GraphDirection::Both => {
-                    format!("SELECT VALUE {{ outbound: ->{}->objects.{}, inbound: <-{}<-objects.{} }} FROM {}", 
-                        relation_clause, projection, relation_clause, projection, start_ids_list)
+                    format!("SELECT VALUE {{ outbound: ->{}->{}.{}, inbound: <-{}<-{}.{} }} FROM {}", 
+                        relation_clause, target, projection, relation_clause, target, projection, start_ids_list)
                 }
             }
         }
@@ -730,8 +1301,9 @@ fn build_graph_query_string(
             conditions.push(format!("project_id = '{}'", project_id.replace("'", "\\'")));
         }
 
-        if let Some(tenant_id) = &filters.tenant_id {
-            conditions.push(format!("tenant_id = '{}'", tenant_id.replace("'", "\\'")));
+        // Bound as $tenant_id; the header value is never spliced into the SQL
+        if filters.tenant_id.is_some() {
+            conditions.push("tenant_id = $tenant_id".to_string());
         }
 
         if !conditions.is_empty() {
@@ -846,6 +1418,12 @@ fn generate_explanation(obj: &Value, request: &QueryRequest) -> String {
         if let Some(tenant_id) = &filters.tenant_id {
             filter_parts.push(format!("tenant={}", tenant_id));
         }
+        if let Some(language) = &filters.language {
+            filter_parts.push(format!("language={}", language));
+        }
+        if let Some(path) = &filters.path {
+            filter_parts.push(format!("path={}", path));
+        }
 
         if !filter_parts.is_empty() {
             parts.push(format!("Filtered by {}", filter_parts.join(", ")));
@@ -891,3 +1469,497 @@ where
         None => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        memory_state, memory_state_with_embedding, test_config, MarkerEmbedding,
+    };
+
+    async fn insert_object(state: &AppState, id: &str, object_type: &str, embedding: Vec<f32>) {
+        state
+            .db
+            .client
+            .query("CREATE type::thing('objects', $id) CONTENT $data")
+            .bind(("id", id.to_string()))
+            .bind((
+                "data",
+                serde_json::json!({
+                    "type": object_type,
+                    "name": id,
+                    "project_id": "proj",
+                    "embedding": embedding,
+                }),
+            ))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+    }
+
+    fn similar(object_id: &str) -> SimilarRequest {
+        SimilarRequest {
+            object_id: object_id.to_string(),
+            limit: None,
+            types: None,
+            project_id: None,
+            min_score: None,
+            tenant_id: None,
+        }
+    }
+
+    async fn seed(state: &AppState) {
+        insert_object(state, "source", "symbol", vec![1.0, 0.0, 0.0]).await;
+        insert_object(state, "close", "symbol", vec![0.9, 0.1, 0.0]).await;
+        insert_object(state, "decision", "decision", vec![0.7, 0.7, 0.0]).await;
+        insert_object(state, "far", "symbol", vec![0.0, 0.0, 1.0]).await;
+        // Different embedding dimension must be skipped rather than fail the query
+        insert_object(state, "other-dim", "symbol", vec![1.0, 0.0]).await;
+    }
+
+    fn ids(response: &QueryResponse) -> Vec<&str> {
+        response
+            .results
+            .iter()
+            .map(|r| r.object["id"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_ranks_closest_object_first() {
+        let state = memory_state(test_config()).await;
+        seed(&state).await;
+
+        let Json(response) = find_similar(State(state.clone()), Tenant::default(), Json(similar("objects:source")))
+            .await
+            .unwrap();
+        assert_eq!(ids(&response), vec!["close", "decision", "far"]);
+        assert!(response.results[0].score > 0.99);
+
+        let mut filtered = similar("source");
+        filtered.types = Some(vec!["symbol".to_string()]);
+        filtered.min_score = Some(0.5);
+        let Json(response) = find_similar(State(state.clone()), Tenant::default(), Json(filtered))
+            .await
+            .unwrap();
+        assert_eq!(ids(&response), vec!["close"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_unknown_object_is_not_found() {
+        let state = memory_state(test_config()).await;
+        seed(&state).await;
+
+        let err = find_similar(State(state), Tenant::default(), Json(similar("missing")))
+            .await
+            .unwrap_err();
+        assert_eq!(err, StatusCode::NOT_FOUND);
+    }
+
+    async fn insert_dated(
+        state: &AppState,
+        id: &str,
+        object_type: &str,
+        project: &str,
+        created_at: &str,
+    ) {
+        state
+            .db
+            .client
+            .query("CREATE type::thing('objects', $id) CONTENT $data")
+            .bind(("id", id.to_string()))
+            .bind((
+                "data",
+                serde_json::json!({
+                    "type": object_type,
+                    "name": format!("lease {}", id),
+                    "project_id": project,
+                    "tenant_id": "default",
+                    "created_at": created_at,
+                }),
+            ))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+    }
+
+    async fn filtered_ids(state: &AppState, filters: serde_json::Value) -> Vec<String> {
+        let request: QueryRequest = serde_json::from_value(serde_json::json!({
+            "text": "lease",
+            "hybrid": true,
+            "limit": 20,
+            "filters": filters,
+        }))
+        .unwrap();
+        let Json(response) = query(State(state.clone()), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+        let mut ids: Vec<String> = response
+            .results
+            .iter()
+            .map(|r| {
+                r.object["id"]
+                    .as_str()
+                    .unwrap()
+                    .trim_start_matches("objects:")
+                    .to_string()
+            })
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_query_applies_type_project_and_date_filters() {
+        let state = memory_state(test_config()).await;
+        insert_dated(&state, "old_decision", "decision", "amp", "2024-01-01T00:00:00Z").await;
+        insert_dated(&state, "new_decision", "decision", "amp", "2024-06-10T00:00:00Z").await;
+        insert_dated(&state, "new_note", "note", "amp", "2024-06-11T00:00:00Z").await;
+        insert_dated(
+            &state,
+            "other_project",
+            "decision",
+            "web",
+            "2024-06-12T00:00:00Z",
+        )
+        .await;
+
+        assert_eq!(
+            filtered_ids(&state, serde_json::json!({ "type": ["decision"] })).await,
+            vec!["new_decision", "old_decision", "other_project"]
+        );
+        assert_eq!(
+            filtered_ids(&state, serde_json::json!({ "project_id": "amp" })).await,
+            vec!["new_decision", "new_note", "old_decision"]
+        );
+        assert_eq!(
+            filtered_ids(
+                &state,
+                serde_json::json!({ "created_after": "2024-06-01T00:00:00Z" })
+            )
+            .await,
+            vec!["new_decision", "new_note", "other_project"]
+        );
+        assert_eq!(
+            filtered_ids(
+                &state,
+                serde_json::json!({ "created_before": "2024-06-10T12:00:00Z" })
+            )
+            .await,
+            vec!["new_decision", "old_decision"]
+        );
+        assert_eq!(
+            filtered_ids(
+                &state,
+                serde_json::json!({
+                    "type": ["decision"],
+                    "project_id": "amp",
+                    "created_after": "2024-06-01T00:00:00Z",
+                    "created_before": "2024-06-30T00:00:00Z",
+                })
+            )
+            .await,
+            vec!["new_decision"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain_adds_score_breakdown_and_source_to_hybrid_results() {
+        let state = memory_state(test_config()).await;
+        insert_dated(&state, "lease_a", "decision", "amp", "2024-06-10T00:00:00Z").await;
+        insert_dated(&state, "lease_b", "note", "amp", "2024-06-11T00:00:00Z").await;
+
+        let results = |explain: Option<bool>| {
+            let state = state.clone();
+            async move {
+                let mut request = serde_json::json!({ "text": "lease", "hybrid": true });
+                if let Some(explain) = explain {
+                    request["explain"] = serde_json::json!(explain);
+                }
+                let request: QueryRequest = serde_json::from_value(request).unwrap();
+                let Json(response) = query(State(state), Tenant::default(), Json(request))
+                    .await
+                    .unwrap();
+                serde_json::to_value(&response.results).unwrap()
+            }
+        };
+
+        let explained = results(Some(true)).await;
+        let explained = explained.as_array().unwrap();
+        assert_eq!(explained.len(), 2);
+        for result in explained {
+            let breakdown = result["score_breakdown"].as_object().unwrap();
+            for key in ["vector_score", "text_score", "graph_boost", "final_score"] {
+                assert!(breakdown.contains_key(key), "missing {}", key);
+            }
+            assert!(breakdown["text_score"].as_f64().is_some());
+            assert!(
+                (breakdown["final_score"].as_f64().unwrap() - result["score"].as_f64().unwrap())
+                    .abs()
+                    < 1e-6
+            );
+            assert!(result["source"].as_str().unwrap().contains("text"));
+        }
+
+        for explain in [Some(false), None] {
+            let plain = results(explain).await;
+            let plain = plain.as_array().unwrap();
+            assert_eq!(plain.len(), 2);
+            for result in plain {
+                assert!(result.get("score_breakdown").is_none());
+                assert!(result.get("source").is_none());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_warnings_are_hidden_unless_requested() {
+        let state = memory_state(test_config()).await;
+        insert_dated(&state, "note", "note", "amp", "2024-06-01T00:00:00Z").await;
+        for (id, expires_at) in [
+            ("expired", Some("2024-06-02T00:00:00Z")),
+            ("active", Some("2999-01-01T00:00:00Z")),
+            ("open_ended", None),
+        ] {
+            insert_dated(&state, id, "warning", "amp", "2024-06-01T00:00:00Z").await;
+            if let Some(expires_at) = expires_at {
+                state
+                    .db
+                    .client
+                    .query("UPDATE type::thing('objects', $id) SET expires_at = $expires_at")
+                    .bind(("id", id))
+                    .bind(("expires_at", expires_at))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        assert_eq!(
+            filtered_ids(&state, serde_json::json!({})).await,
+            vec!["active", "note", "open_ended"]
+        );
+        assert_eq!(
+            filtered_ids(&state, serde_json::json!({ "include_expired": true })).await,
+            vec!["active", "expired", "note", "open_ended"]
+        );
+
+        // Plain (non-hybrid) queries without any filters hide them too
+        let request: QueryRequest =
+            serde_json::from_value(serde_json::json!({ "text": "lease" })).unwrap();
+        let Json(response) = query(State(state), Tenant::default(), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(response.results.len(), 3);
+        assert!(response
+            .results
+            .iter()
+            .all(|r| r.object["id"].as_str().unwrap() != "objects:expired"));
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_query_applies_language_and_path_filters() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "CREATE objects:rs_symbol CONTENT { type: 'symbol', name: 'lease_renew', language: 'rust', path: 'server/src/handlers/leases.rs', tenant_id: 'default' };
+                 CREATE objects:rs_chunk CONTENT { type: 'FileChunk', name: 'lease chunk', language: 'rust', file_path: 'cli/src/client.rs', tenant_id: 'default' };
+                 CREATE objects:py_symbol CONTENT { type: 'symbol', name: 'lease_renew', language: 'python', path: 'server/lease.py', tenant_id: 'default' };",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        assert_eq!(
+            filtered_ids(&state, serde_json::json!({ "language": "rust" })).await,
+            vec!["rs_chunk", "rs_symbol"]
+        );
+        assert_eq!(
+            filtered_ids(&state, serde_json::json!({ "path": "server/" })).await,
+            vec!["py_symbol", "rs_symbol"]
+        );
+        assert_eq!(
+            filtered_ids(
+                &state,
+                serde_json::json!({ "language": "rust", "path": "cli/src" })
+            )
+            .await,
+            vec!["rs_chunk"]
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Server logs, debug level and up, from a plain and a hybrid query for `text`.
+    async fn query_logs(log_query_content: bool, text: &str) -> String {
+        let config = crate::config::Config {
+            log_query_content,
+            ..test_config()
+        };
+        let state = memory_state(config).await;
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter("amp_server=debug")
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        for hybrid in [false, true] {
+            let request: QueryRequest =
+                serde_json::from_value(serde_json::json!({ "text": text, "hybrid": hybrid }))
+                    .unwrap();
+            let Json(response) = query(State(state.clone()), Tenant::default(), Json(request))
+                .await
+                .unwrap();
+            assert!(response.results.is_empty());
+        }
+        let logs = logs.0.lock().unwrap().clone();
+        String::from_utf8(logs).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_query_text_is_redacted_from_logs_by_default() {
+        let text = "rotate the staging signing key";
+        let redacted = query_logs(false, text).await;
+        assert!(redacted.contains("query{"), "{}", redacted);
+        assert!(!redacted.contains(text), "{}", redacted);
+        let summary = QueryLog::new(Some(text), false).to_string();
+        assert!(summary.starts_with("sha256:"));
+        assert!(summary.ends_with(&format!("len={}", text.len())));
+        assert!(redacted.contains(&summary));
+
+        let verbatim = query_logs(true, text).await;
+        assert!(verbatim.contains(text), "{}", verbatim);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_hits_are_grouped_by_file_before_the_limit() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "CREATE objects:lease_a1 CONTENT { type: 'FileChunk', name: 'lease chunk 1', file_path: 'src/lease.rs', start_line: 1, end_line: 20, tenant_id: 'default' };
+                 CREATE objects:lease_a2 CONTENT { type: 'FileChunk', name: 'lease chunk 2', file_path: 'src/lease.rs', start_line: 21, end_line: 40, tenant_id: 'default' };
+                 CREATE objects:lease_a3 CONTENT { type: 'FileChunk', name: 'lease chunk 3', file_path: 'src/lease.rs', start_line: 60, end_line: 80, tenant_id: 'default' };
+                 CREATE objects:lease_b1 CONTENT { type: 'FileChunk', name: 'lease chunk', file_path: 'src/client.rs', start_line: 1, end_line: 10, tenant_id: 'default' };
+                 CREATE objects:lease_note CONTENT { type: 'note', title: 'lease note', tenant_id: 'default' };
+                 CREATE objects:a_log CONTENT { type: 'FileLog', file_path: 'src/lease.rs', summary: 'Renewal bookkeeping', tenant_id: 'default' };",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let run = |group_by_file: Option<bool>| {
+            let request: QueryRequest = serde_json::from_value(serde_json::json!({
+                "text": "lease",
+                "hybrid": true,
+                "limit": 3,
+                "group_by_file": group_by_file,
+            }))
+            .unwrap();
+            query(State(state.clone()), Tenant::default(), Json(request))
+        };
+
+        let Json(grouped) = run(None).await.unwrap();
+        assert_eq!(grouped.results.len(), 3);
+        let files: Vec<Option<&str>> = grouped
+            .results
+            .iter()
+            .map(|r| r.object.get("file_path").and_then(Value::as_str))
+            .collect();
+        assert_eq!(files[0], Some("src/lease.rs"));
+        assert!(files.contains(&Some("src/client.rs")));
+        assert!(files.contains(&None)); // the note
+
+        let top = &grouped.results[0];
+        let group = top.file_group.as_ref().unwrap();
+        assert_eq!(group.matched_chunks, 3);
+        assert_eq!(group.line_ranges, vec![(1, 40), (60, 80)]);
+        assert_eq!(group.file_summary.as_deref(), Some("Renewal bookkeeping"));
+        assert!(top.score > grouped.results[1].score);
+        let json = serde_json::to_value(top).unwrap();
+        assert_eq!(json["matched_chunks"], 3);
+        assert_eq!(json["line_ranges"], serde_json::json!([[1, 40], [60, 80]]));
+
+        // Raw chunk granularity when disabled: the limit counts chunks
+        let Json(raw) = run(Some(false)).await.unwrap();
+        assert_eq!(raw.results.len(), 3);
+        assert!(raw.results.iter().all(|r| r.file_group.is_none()));
+    }
+
+    #[test]
+    fn test_merge_line_ranges_joins_overlapping_and_adjacent() {
+        assert_eq!(
+            merge_line_ranges(vec![(30, 40), (1, 10), (11, 20), (35, 50), (70, 80)]),
+            vec![(1, 20), (30, 50), (70, 80)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_debug_vectors_need_request_flag_and_server_setting() {
+        async fn run(allow: bool, requested: bool) -> QueryResponse {
+            let mut config = test_config();
+            config.allow_debug_vectors = allow;
+            let state = memory_state_with_embedding(
+                config,
+                Box::new(MarkerEmbedding {
+                    model: "marker",
+                    marker: 0.5,
+                }),
+            )
+            .await;
+            insert_dated(&state, "lease_note", "note", "amp", "2024-06-01T00:00:00Z").await;
+            state
+                .db
+                .client
+                .query("UPDATE objects:lease_note SET embedding = [0.25, 0.75]")
+                .await
+                .unwrap();
+            let request: QueryRequest = serde_json::from_value(serde_json::json!({
+                "text": "lease",
+                "hybrid": true,
+                "debug_vectors": requested,
+            }))
+            .unwrap();
+            let Json(response) = query(State(state), Tenant::default(), Json(request))
+                .await
+                .unwrap();
+            assert_eq!(response.results.len(), 1);
+            response
+        }
+
+        let response = run(true, true).await;
+        assert_eq!(response.query_embedding, Some(vec![0.5, 0.5]));
+        assert_eq!(response.results[0].embedding, Some(vec![0.25, 0.75]));
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(json.get("query_embedding").is_some());
+
+        for (allow, requested) in [(false, true), (true, false), (false, false)] {
+            let response = run(allow, requested).await;
+            assert!(response.query_embedding.is_none());
+            assert!(response.results[0].embedding.is_none());
+            let json = serde_json::to_value(&response).unwrap();
+            assert!(json.get("query_embedding").is_none());
+            assert!(json["results"][0].get("embedding").is_none());
+        }
+    }
+}
@@ -8,7 +8,10 @@ use serde_json::Value;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
-use crate::{models::relationships::*, surreal_json::take_json_values, AppState};
+use crate::{
+    models::relationships::*, services::graph::GraphTraversalError, surreal_json::take_json_values,
+    AppState,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct RelationshipQuery {
@@ -38,6 +41,7 @@ pub async fn create_relationship(
         RelationType::Modifies => "modifies",
         RelationType::Implements => "implements",
         RelationType::Produced => "produced",
+        RelationType::SupersededBy => "superseded_by",
     };
 
     // Verify both objects exist first - use simple SELECT instead of type::record
@@ -50,28 +54,26 @@ pub async fn create_relationship(
         request.project_id
     );
 
-    // Use RELATE statement for graph edges - include project_id for isolation
+    // Low-degree sources keep the link inline; otherwise RELATE an edge with project_id for isolation
     let project_id = request.project_id.clone().unwrap_or_default();
-    let query = format!(
-        "RELATE objects:`{}`->{}->objects:`{}` SET created_at = time::now(), project_id = $project_id",
-        request.source_id, table_name, request.target_id
-    );
-
-    tracing::debug!("Creating relationship: {}", query);
-
-    let result = timeout(
-        Duration::from_secs(5),
-        state.db.client.query(&query).bind(("project_id", project_id)),
-    )
-    .await;
+    let result = state
+        .graph_service
+        .store_relationship(
+            &request.source_id.to_string(),
+            table_name,
+            &request.target_id.to_string(),
+            &project_id,
+        )
+        .await;
 
     match result {
-        Ok(Ok(_response)) => {
+        Ok(storage) => {
             tracing::info!(
-                "Created relationship: {} -> {} ({})",
+                "Created relationship: {} -> {} ({}, {:?})",
                 request.source_id,
                 request.target_id,
-                table_name
+                table_name,
+                storage
             );
 
             Ok((
@@ -85,14 +87,14 @@ pub async fn create_relationship(
                 }),
             ))
         }
-        Ok(Err(e)) => {
-            tracing::error!("Failed to create relationship: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-        Err(_) => {
+        Err(GraphTraversalError::Timeout) => {
             tracing::error!("Timeout creating relationship");
             Err(StatusCode::GATEWAY_TIMEOUT)
         }
+        Err(e) => {
+            tracing::error!("Failed to create relationship: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
 }
 
@@ -116,7 +118,7 @@ pub async fn get_relationships(
         query_str.push_str(rel_type);
     } else {
         query_str.push_str(
-            "depends_on, defined_in, calls, justified_by, modifies, implements, produced",
+            "depends_on, defined_in, calls, justified_by, modifies, implements, produced, superseded_by",
         );
     }
 
@@ -207,3 +209,148 @@ pub async fn delete_relationship(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::handlers::query::{GraphDirection, GraphQuery, TraversalAlgorithm};
+    use crate::test_support::{memory_state, test_config};
+
+    #[tokio::test]
+    async fn test_created_relationship_is_found_by_shortest_path() {
+        let state = memory_state(test_config()).await;
+        let (from, to) = (Uuid::new_v4(), Uuid::new_v4());
+        state
+            .db
+            .client
+            .query(
+                "CREATE type::thing('objects', $from) SET type = 'symbol', tenant_id = 'default';
+                 CREATE type::thing('objects', $to) SET type = 'symbol', tenant_id = 'default';",
+            )
+            .bind(("from", from.to_string()))
+            .bind(("to", to.to_string()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let request = CreateRelationshipRequest {
+            relation_type: RelationType::DependsOn,
+            source_id: from,
+            target_id: to,
+            metadata: None,
+            project_id: None,
+        };
+        let (status, _) = create_relationship(State(state.clone()), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let path = state
+            .graph_service
+            .shortest_path(&from.to_string(), &to.to_string(), 3, &[], "default")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].relation, "depends_on");
+    }
+
+    #[tokio::test]
+    async fn test_low_degree_relationships_stay_inline_until_threshold() {
+        let state = memory_state(Config {
+            inline_link_threshold: 3,
+            ..test_config()
+        })
+        .await;
+        let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        for id in &ids {
+            state
+                .db
+                .client
+                .query(
+                    "CREATE type::thing('objects', $id) SET type = 'symbol', tenant_id = 'default'",
+                )
+                .bind(("id", id.to_string()))
+                .await
+                .unwrap()
+                .check()
+                .unwrap();
+        }
+        let relate = |target: Uuid| CreateRelationshipRequest {
+            relation_type: RelationType::Calls,
+            source_id: ids[0],
+            target_id: target,
+            metadata: None,
+            project_id: Some("amp".to_string()),
+        };
+        let edge_count = || async {
+            let mut response = state
+                .db
+                .client
+                .query("SELECT VALUE meta::id(id) FROM calls")
+                .await
+                .unwrap();
+            take_json_values(&mut response, 0).len()
+        };
+
+        for target in [ids[1], ids[2]] {
+            let (status, _) = create_relationship(State(state.clone()), Json(relate(target)))
+                .await
+                .unwrap();
+            assert_eq!(status, StatusCode::CREATED);
+        }
+        assert_eq!(edge_count().await, 0);
+
+        let path = state
+            .graph_service
+            .shortest_path(&ids[2].to_string(), &ids[0].to_string(), 3, &[], "default")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].relation, "calls");
+        assert_eq!(path[0].direction, GraphDirection::Inbound);
+
+        let collect = GraphQuery {
+            start_nodes: vec![ids[0]],
+            relation_types: Some(vec!["calls".to_string()]),
+            max_depth: Some(1),
+            direction: Some(GraphDirection::Outbound),
+            algorithm: Some(TraversalAlgorithm::Collect),
+            target_node: None,
+            tenant_id: Some("default".to_string()),
+        };
+        let result = state
+            .graph_service
+            .execute_multi_hop(&collect)
+            .await
+            .unwrap();
+        assert_eq!(result.total_count, 2);
+
+        // The third relationship reaches the threshold and promotes the source
+        let (status, _) = create_relationship(State(state.clone()), Json(relate(ids[3])))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(edge_count().await, 3);
+        let mut response = state
+            .db
+            .client
+            .query("SELECT VALUE links FROM type::thing('objects', $id)")
+            .bind(("id", ids[0].to_string()))
+            .await
+            .unwrap();
+        assert_eq!(
+            take_json_values(&mut response, 0),
+            vec![serde_json::json!([])]
+        );
+        let result = state
+            .graph_service
+            .execute_multi_hop(&collect)
+            .await
+            .unwrap();
+        assert_eq!(result.total_count, 3);
+    }
+}
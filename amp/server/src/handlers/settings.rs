@@ -1,11 +1,39 @@
+use crate::models::analytics::{EmbeddingIdentity, EmbeddingServiceStatus, EmbeddingStatus};
 use crate::models::settings::SettingsConfig;
+use crate::services::embedding::{
+    create_embedding_service_from_settings, indexable_dimension, EmbeddingService,
+    ReloadableEmbedding,
+};
+use crate::services::metrics::MeteredEmbedding;
+use crate::services::settings::validate_project_overrides;
+use crate::surreal_json::take_json_values;
+use crate::tenant::Tenant;
 use crate::AppState;
 use axum::{
-    extract::{rejection::JsonRejection, State},
+    extract::{rejection::JsonRejection, Path, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsUpdateResponse {
+    #[serde(flatten)]
+    pub settings: SettingsConfig,
+    /// Set when stored embeddings no longer match the configured dimension
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimension_mismatch: Option<DimensionMismatch>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimensionMismatch {
+    pub stored_dimension: usize,
+    pub configured_dimension: usize,
+    pub message: String,
+}
 
 pub async fn get_settings(State(state): State<AppState>) -> impl IntoResponse {
     match state.settings_service.load_settings().await {
@@ -41,8 +69,65 @@ pub async fn update_settings(
         }
     };
 
+    let previous = state.settings_service.load_settings().await.ok();
+
     match state.settings_service.save_settings(settings).await {
-        Ok(saved_settings) => (StatusCode::OK, Json(saved_settings)).into_response(),
+        Ok(saved_settings) => {
+            let changed = embedding_changed(&saved_settings, previous.as_ref());
+            if changed {
+                reload_embedding_service(
+                    &state,
+                    &state.embedding_service,
+                    "Embedding",
+                    previous.as_ref(),
+                    &saved_settings,
+                );
+            }
+
+            let code_settings = saved_settings.code_embedding_settings();
+            let previous_code = previous
+                .as_ref()
+                .map(SettingsConfig::code_embedding_settings);
+            let code_changed = embedding_changed(&code_settings, previous_code.as_ref());
+            if code_changed {
+                if saved_settings.code_embedding_provider.is_empty() {
+                    state
+                        .code_embedding_service
+                        .replace(state.embedding_service.current());
+                    tracing::info!("Code embedding now shares the prose embedding service");
+                } else {
+                    reload_embedding_service(
+                        &state,
+                        &state.code_embedding_service,
+                        "Code embedding",
+                        previous_code.as_ref(),
+                        &code_settings,
+                    );
+                }
+            }
+
+            if changed || code_changed {
+                let dimension = indexable_dimension(
+                    state.embedding_service.as_ref(),
+                    state.code_embedding_service.as_ref(),
+                );
+                state.db.ensure_vector_indexes(dimension).await;
+            }
+
+            let dimension_mismatch = if changed {
+                check_dimension_mismatch(&state).await
+            } else {
+                None
+            };
+            (
+                StatusCode::OK,
+                Json(SettingsUpdateResponse {
+                    settings: saved_settings,
+                    dimension_mismatch,
+                }),
+            )
+                .into_response()
+        }
         Err(e) => {
             tracing::error!("Failed to save settings: {}", e);
             (
@@ -56,6 +141,325 @@ pub async fn update_settings(
     }
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSettingsResponse {
+    pub tenant_id: String,
+    pub project_id: String,
+    /// Only the fields this project overrides
+    pub overrides: serde_json::Value,
+    /// Global settings with the tenant's and then the project's overrides applied
+    pub settings: SettingsConfig,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantSettingsResponse {
+    pub tenant_id: String,
+    /// Only the fields this tenant overrides
+    pub overrides: serde_json::Value,
+    /// Global settings with the tenant's overrides applied
+    pub settings: SettingsConfig,
+}
+
+async fn project_settings_response(
+    state: &AppState,
+    tenant: &Tenant,
+    project_id: String,
+) -> anyhow::Result<ProjectSettingsResponse> {
+    let overrides = state
+        .settings_service
+        .load_project_overrides(tenant.as_str(), &project_id)
+        .await?
+        .unwrap_or_else(|| serde_json::json!({}));
+    let settings = state
+        .settings_service
+        .load_settings_for_project(tenant.as_str(), &project_id)
+        .await?;
+    Ok(ProjectSettingsResponse {
+        tenant_id: tenant.as_str().to_string(),
+        project_id,
+        overrides,
+        settings,
+    })
+}
+
+async fn tenant_settings_response(
+    state: &AppState,
+    tenant: &Tenant,
+) -> anyhow::Result<TenantSettingsResponse> {
+    let overrides = state
+        .settings_service
+        .load_tenant_overrides(tenant.as_str())
+        .await?
+        .unwrap_or_else(|| serde_json::json!({}));
+    let settings = state
+        .settings_service
+        .load_settings_for_tenant(tenant.as_str())
+        .await?;
+    Ok(TenantSettingsResponse {
+        tenant_id: tenant.as_str().to_string(),
+        overrides,
+        settings,
+    })
+}
+
+pub async fn get_project_settings(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(project_id): Path<String>,
+) -> impl IntoResponse {
+    match project_settings_response(&state, &tenant, project_id).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load project settings: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to load project settings: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Replace a project's overrides with the fields in the body; everything else
+/// keeps following the tenant and global settings.
+pub async fn update_project_settings(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(project_id): Path<String>,
+    payload: Result<Json<serde_json::Value>, JsonRejection>,
+) -> impl IntoResponse {
+    let overrides = match parse_overrides(payload, "project") {
+        Ok(overrides) => overrides,
+        Err(error) => return error.into_response(),
+    };
+
+    let saved = state
+        .settings_service
+        .save_project_overrides(tenant.as_str(), &project_id, overrides)
+        .await;
+    match saved {
+        Ok(_) => get_project_settings(State(state), tenant, Path(project_id))
+            .await
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to save project settings: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to save project settings: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+pub async fn get_tenant_settings(
+    State(state): State<AppState>,
+    tenant: Tenant,
+) -> impl IntoResponse {
+    match tenant_settings_response(&state, &tenant).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load tenant settings: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to load tenant settings: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Replace the request tenant's overrides, which apply to all of its projects
+/// underneath their own overrides.
+pub async fn update_tenant_settings(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    payload: Result<Json<serde_json::Value>, JsonRejection>,
+) -> impl IntoResponse {
+    let overrides = match parse_overrides(payload, "tenant") {
+        Ok(overrides) => overrides,
+        Err(error) => return error.into_response(),
+    };
+
+    let saved = state
+        .settings_service
+        .save_tenant_overrides(tenant.as_str(), overrides)
+        .await;
+    match saved {
+        Ok(_) => get_tenant_settings(State(state), tenant)
+            .await
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to save tenant settings: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to save tenant settings: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// The overrides in a settings body, or the 400 response for a malformed or invalid one
+fn parse_overrides(
+    payload: Result<Json<serde_json::Value>, JsonRejection>,
+    scope: &str,
+) -> Result<serde_json::Value, (StatusCode, Json<serde_json::Value>)> {
+    let Json(overrides) = payload.map_err(|rejection| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("Invalid settings format: {}", rejection)
+            })),
+        )
+    })?;
+    validate_project_overrides(&overrides).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("Invalid {} settings: {}", scope, e)
+            })),
+        )
+    })?;
+    Ok(overrides)
+}
+
+fn embedding_changed(settings: &SettingsConfig, previous: Option<&SettingsConfig>) -> bool {
+    match previous {
+        Some(previous) => settings.embedding_differs(previous),
+        None => true,
+    }
+}
+
+/// Swap in an embedding service built from `settings`; in-flight calls keep the old one.
+fn reload_embedding_service(
+    state: &AppState,
+    target: &ReloadableEmbedding,
+    label: &str,
+    previous: Option<&SettingsConfig>,
+    settings: &SettingsConfig,
+) {
+    let service = MeteredEmbedding::wrap_if_enabled(
+        create_embedding_service_from_settings(settings),
+        state.metrics.as_ref(),
+    );
+    let (model, dimension) = settings.embedding_model_and_dimension();
+    let old = target.replace(service);
+    match previous {
+        Some(previous) => {
+            let (old_model, old_dimension) = previous.embedding_model_and_dimension();
+            tracing::info!(
+                "{} service reloaded: provider={} -> {}, model={} -> {}, dimension={} -> {}",
+                label,
+                previous.embedding_provider,
+                settings.embedding_provider,
+                old_model,
+                model,
+                old_dimension,
+                dimension
+            );
+        }
+        None => tracing::info!(
+            "{} service reloaded: provider={}, model={}, dimension={} (was model={}, dimension={})",
+            label,
+            settings.embedding_provider,
+            model,
+            dimension,
+            old.model(),
+            old.dimension()
+        ),
+    }
+}
+
+/// Compare the live embedding dimension against one stored object embedding.
+async fn check_dimension_mismatch(state: &AppState) -> Option<DimensionMismatch> {
+    let configured_dimension = state.embedding_service.dimension();
+    if !state.embedding_service.is_enabled() || configured_dimension == 0 {
+        return None;
+    }
+
+    let query = "SELECT VALUE array::len(embedding) FROM objects WHERE embedding IS NOT NONE AND embedding IS NOT NULL LIMIT 1";
+    let mut response = match state.db.client.query(query).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!("Failed to read stored embedding dimension: {}", e);
+            return None;
+        }
+    };
+    let stored_dimension = take_json_values(&mut response, 0)
+        .first()
+        .and_then(|value| value.as_u64())? as usize;
+
+    (stored_dimension != configured_dimension).then(|| DimensionMismatch {
+        stored_dimension,
+        configured_dimension,
+        message: format!(
+            "Stored embeddings have {} dimensions but the embedding provider now produces {}; re-embed existing objects or vector search will skip them",
+            stored_dimension, configured_dimension
+        ),
+    })
+}
+
+/// Compare the running prose and code embedding services with what the stored
+/// settings select. `update_settings` reloads them, but settings written any
+/// other way (another server on the same database, an import) leave them stale.
+pub(crate) async fn embedding_status(state: &AppState) -> Option<EmbeddingStatus> {
+    let settings = match state.settings_service.load_settings().await {
+        Ok(settings) => settings,
+        Err(e) => {
+            tracing::warn!("Failed to load settings for embedding status: {}", e);
+            return None;
+        }
+    };
+    let prose = embedding_service_status(&settings, state.embedding_service.as_ref());
+    let code = embedding_service_status(
+        &settings.code_embedding_settings(),
+        state.code_embedding_service.as_ref(),
+    );
+    Some(EmbeddingStatus {
+        restart_required: prose.live != prose.configured || code.live != code.configured,
+        prose,
+        code,
+    })
+}
+
+fn embedding_service_status(
+    settings: &SettingsConfig,
+    live: &dyn EmbeddingService,
+) -> EmbeddingServiceStatus {
+    EmbeddingServiceStatus {
+        provider: settings.embedding_provider.clone(),
+        live: embedding_identity(live),
+        configured: embedding_identity(create_embedding_service_from_settings(settings).as_ref()),
+    }
+}
+
+fn embedding_identity(service: &dyn EmbeddingService) -> EmbeddingIdentity {
+    // A disabled service embeds nothing, whatever model it names
+    if !service.is_enabled() {
+        return EmbeddingIdentity {
+            enabled: false,
+            model: String::new(),
+            dimension: 0,
+        };
+    }
+    EmbeddingIdentity {
+        enabled: true,
+        model: service.model(),
+        dimension: service.dimension(),
+    }
+}
+
 pub async fn nuclear_delete(State(state): State<AppState>) -> impl IntoResponse {
     tracing::warn!("NUCLEAR DELETE initiated - deleting ALL data from AMP");
 
@@ -102,3 +506,266 @@ pub async fn nuclear_delete(State(state): State<AppState>) -> impl IntoResponse
     )
         .into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tenant::DEFAULT_TENANT;
+    use crate::test_support::{
+        memory_state, memory_state_with_embedding, test_config, CountingEmbedding,
+    };
+
+    #[tokio::test]
+    async fn test_update_settings_reloads_embedding_service() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        state
+            .db
+            .client
+            .query("CREATE objects:stored SET type = 'note', tenant_id = 'default', embedding = [1.0, 2.0]")
+            .await
+            .unwrap();
+        assert_eq!(state.embedding_service.model(), "counting");
+        assert!(check_dimension_mismatch(&state).await.is_none());
+
+        let in_flight = state.embedding_service.current();
+        let settings = SettingsConfig {
+            embedding_provider: "ollama".to_string(),
+            ..SettingsConfig::default()
+        };
+        let response = update_settings(State(state.clone()), Ok(Json(settings)))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        assert_eq!(state.embedding_service.model(), "nomic-embed-text");
+        assert_eq!(state.embedding_service.dimension(), 768);
+        assert_eq!(in_flight.model(), "counting");
+
+        let mismatch = check_dimension_mismatch(&state).await.unwrap();
+        assert_eq!(mismatch.stored_dimension, 2);
+        assert_eq!(mismatch.configured_dimension, 768);
+    }
+
+    #[tokio::test]
+    async fn test_update_settings_drops_vector_indexes_without_one_dimension() {
+        let state =
+            memory_state_with_embedding(test_config(), Box::new(CountingEmbedding::default()))
+                .await;
+        state.db.ensure_vector_indexes(Some(3)).await;
+        assert_eq!(state.db.vector_index_dimension("objects").await, Some(3));
+        state.db.ensure_vector_indexes(Some(2)).await;
+        assert_eq!(state.db.vector_index_dimension("objects").await, Some(2));
+
+        // Prose and code vectors of different lengths cannot share an index
+        let settings = SettingsConfig {
+            embedding_provider: "ollama".to_string(),
+            code_embedding_provider: "ollama".to_string(),
+            code_embedding_dimension: 384,
+            ..SettingsConfig::default()
+        };
+        let response = update_settings(State(state.clone()), Ok(Json(settings)))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        for (_, table, _) in crate::database::VECTOR_INDEXES {
+            assert_eq!(state.db.vector_index_dimension(table).await, None);
+        }
+        state
+            .db
+            .client
+            .query("CREATE objects:prose SET type = 'note', tenant_id = 'default', embedding = [1.0, 2.0, 3.0]")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_project_settings_shadow_global_values() {
+        let state = memory_state(test_config()).await;
+        let global = SettingsConfig {
+            index_workers: 9,
+            ..SettingsConfig::default()
+        };
+        state.settings_service.save_settings(global).await.unwrap();
+
+        let overrides = serde_json::json!({
+            "indexProvider": "ollama",
+            "indexOllamaModel": "custom-index"
+        });
+        let response = update_project_settings(
+            State(state.clone()),
+            Tenant::default(),
+            Path("proj-a".to_string()),
+            Ok(Json(overrides.clone())),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let project = state
+            .settings_service
+            .load_settings_for_project(DEFAULT_TENANT, "proj-a")
+            .await
+            .unwrap();
+        assert_eq!(project.index_provider, "ollama");
+        assert_eq!(project.index_ollama_model, "custom-index");
+        assert_eq!(project.index_workers, 9);
+
+        let other = state
+            .settings_service
+            .load_settings_for_project(DEFAULT_TENANT, "proj-b")
+            .await
+            .unwrap();
+        assert_eq!(other.index_provider, "none");
+        assert_eq!(other.index_workers, 9);
+
+        let stored = project_settings_response(&state, &Tenant::default(), "proj-a".to_string())
+            .await
+            .unwrap();
+        assert_eq!(stored.overrides, overrides);
+
+        for rejected in [
+            serde_json::json!({ "noSuchSetting": 1 }),
+            serde_json::json!({ "embeddingProvider": "ollama" }),
+            serde_json::json!({ "codeEmbeddingDimension": 384 }),
+        ] {
+            let response = update_project_settings(
+                State(state.clone()),
+                Tenant::default(),
+                Path("proj-a".to_string()),
+                Ok(Json(rejected)),
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tenant_and_project_overrides_stay_separate_per_tenant() {
+        let state = memory_state(test_config()).await;
+        let acme = Tenant("acme".to_string());
+        let globex = Tenant("globex".to_string());
+        let put_project = |tenant: Tenant, overrides: serde_json::Value| {
+            update_project_settings(
+                State(state.clone()),
+                tenant,
+                Path("shared".to_string()),
+                Ok(Json(overrides)),
+            )
+        };
+
+        let response = update_tenant_settings(
+            State(state.clone()),
+            acme.clone(),
+            Ok(Json(serde_json::json!({
+                "indexProvider": "ollama",
+                "indexOllamaModel": "acme-index",
+                "indexWorkers": 2
+            }))),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = put_project(
+            acme.clone(),
+            serde_json::json!({ "indexOllamaModel": "acme-shared" }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = put_project(
+            globex.clone(),
+            serde_json::json!({ "indexProvider": "openai" }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // global -> tenant -> project
+        let settings = &state.settings_service;
+        let acme_shared = settings
+            .load_settings_for_project("acme", "shared")
+            .await
+            .unwrap();
+        assert_eq!(acme_shared.index_provider, "ollama");
+        assert_eq!(acme_shared.index_ollama_model, "acme-shared");
+        assert_eq!(acme_shared.index_workers, 2);
+        let acme_other = settings
+            .load_settings_for_project("acme", "other")
+            .await
+            .unwrap();
+        assert_eq!(acme_other.index_ollama_model, "acme-index");
+
+        // The same project id under another tenant never sees acme's overrides
+        let globex_shared = settings
+            .load_settings_for_project("globex", "shared")
+            .await
+            .unwrap();
+        assert_eq!(globex_shared.index_provider, "openai");
+        assert_eq!(globex_shared.index_workers, 4);
+        let stored = project_settings_response(&state, &globex, "shared".to_string())
+            .await
+            .unwrap();
+        assert_eq!(stored.tenant_id, "globex");
+        assert_eq!(
+            stored.overrides,
+            serde_json::json!({ "indexProvider": "openai" })
+        );
+        let tenant = tenant_settings_response(&state, &globex).await.unwrap();
+        assert_eq!(tenant.overrides, serde_json::json!({}));
+
+        // Embedding settings stay global for tenants too
+        let response = update_tenant_settings(
+            State(state.clone()),
+            globex,
+            Ok(Json(serde_json::json!({ "ollamaModel": "globex-embed" }))),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_embedding_status_flags_settings_saved_behind_the_live_service() {
+        let state = memory_state(test_config()).await;
+        let status = embedding_status(&state).await.unwrap();
+        assert!(!status.restart_required);
+
+        // Saved without update_settings, as another server on the database would
+        let settings = SettingsConfig {
+            embedding_provider: "ollama".to_string(),
+            ..SettingsConfig::default()
+        };
+        let (model, dimension) = settings.embedding_model_and_dimension();
+        state
+            .settings_service
+            .save_settings(settings)
+            .await
+            .unwrap();
+
+        let status = embedding_status(&state).await.unwrap();
+        assert!(status.restart_required);
+        assert_eq!(status.prose.provider, "ollama");
+        assert!(!status.prose.live.enabled);
+        assert_eq!(
+            status.prose.configured,
+            EmbeddingIdentity {
+                enabled: true,
+                model,
+                dimension,
+            }
+        );
+
+        let Json(analytics) = crate::handlers::analytics::get_analytics(
+            State(state.clone()),
+            axum::extract::Query(Default::default()),
+        )
+        .await
+        .unwrap();
+        assert!(analytics.embedding_status.unwrap().restart_required);
+    }
+}
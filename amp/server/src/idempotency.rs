@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+};
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use uuid::Uuid;
+
+use crate::{database::Database, surreal_json::take_json_values, tenant::Tenant};
+
+pub const IDEMPOTENCY_HEADER: &str = "idempotency-key";
+
+/// Client-chosen id from the `Idempotency-Key` header. Create endpoints use it
+/// as the new object's id, so a retried request finds the record the first
+/// attempt wrote instead of creating a duplicate.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdempotencyKey(pub Option<String>);
+
+impl IdempotencyKey {
+    fn from_header(value: Option<&str>) -> Result<Self, StatusCode> {
+        match value.map(str::trim) {
+            None | Some("") => Ok(Self(None)),
+            Some(key) => Uuid::parse_str(key)
+                .map(|key| Self(Some(key.to_string())))
+                .map_err(|_| StatusCode::BAD_REQUEST),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IdempotencyKey
+where
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Self::from_header(
+            parts
+                .headers
+                .get(IDEMPOTENCY_HEADER)
+                .map(|value| value.to_str().unwrap_or_default()),
+        )
+    }
+}
+
+/// Fields of an object an earlier request from the same tenant with the same
+/// key already created.
+pub async fn existing_object(db: &Database, tenant: &Tenant, id: &str) -> Option<Value> {
+    let query = "SELECT VALUE { created_at: created_at, memory_layers: memory_layers, expires_at: IF expires_at THEN <string>expires_at END } FROM objects WHERE id = type::thing('objects', $id) AND tenant_id = $tenant_id";
+    let mut response = timeout(
+        Duration::from_secs(2),
+        db.client
+            .query(query)
+            .bind(("id", id.to_string()))
+            .bind(("tenant_id", tenant.as_str().to_string())),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    take_json_values(&mut response, 0).into_iter().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idempotency_key_must_be_a_uuid() {
+        let key = "0b7c6f1e-3a51-4c2e-9f3e-6d7c1a2b3c4d";
+        assert_eq!(
+            IdempotencyKey::from_header(Some(key)),
+            Ok(IdempotencyKey(Some(key.to_string())))
+        );
+        assert_eq!(IdempotencyKey::from_header(None), Ok(IdempotencyKey(None)));
+        assert_eq!(
+            IdempotencyKey::from_header(Some("retry-1")),
+            Err(StatusCode::BAD_REQUEST)
+        );
+    }
+}
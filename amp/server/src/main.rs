@@ -1,12 +1,12 @@
 use axum::{
-    extract::State,
+    extract::{DefaultBodyLimit, MatchedPath, State},
     http::StatusCode,
-    middleware::{from_fn_with_state, Next},
+    middleware::{from_fn, from_fn_with_state, Next},
     response::{Json, Response},
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing_subscriber::filter::LevelFilter;
@@ -15,27 +15,56 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 mod config;
 mod database;
 mod handlers;
+mod idempotency;
 mod models;
 mod services;
 mod surreal_json;
+mod tenant;
+#[cfg(test)]
+mod test_support;
 
 use config::Config;
 use database::Database;
 use services::analytics::AnalyticsService;
-use services::embedding::EmbeddingService;
+use services::cache::{HeuristicTokenEstimator, TokenEstimator};
+use services::embedding::{EmbeddingIndex, ReloadableEmbedding};
 use services::graph::GraphTraversalService;
 use services::hybrid::HybridRetrievalService;
+use services::metrics::{MeteredEmbedding, MetricsState};
+use services::parse_cache::ParseCache;
+use services::path_resolver::PathResolver;
 use services::settings::SettingsService;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: Arc<Database>,
     pub config: Arc<Config>,
-    pub embedding_service: Arc<dyn EmbeddingService>,
+    pub embedding_service: Arc<ReloadableEmbedding>,
+    pub code_embedding_service: Arc<ReloadableEmbedding>,
     pub graph_service: Arc<GraphTraversalService>,
     pub hybrid_service: Arc<HybridRetrievalService>,
     pub analytics_service: Arc<AnalyticsService>,
     pub settings_service: Arc<SettingsService>,
+    pub metrics: Option<Arc<Mutex<MetricsState>>>,
+    pub parse_cache: Arc<ParseCache>,
+    /// Token counting for cache block thresholds and pack budgets
+    pub token_estimator: Arc<dyn TokenEstimator>,
+    /// Path canonicalization, mount mapping and case policy for file lookups
+    pub path_resolver: Arc<PathResolver>,
+}
+
+impl AppState {
+    /// Embedding service for objects of `object_type`.
+    pub fn embedding_for(&self, object_type: &str) -> &Arc<ReloadableEmbedding> {
+        self.embedding_for_index(EmbeddingIndex::for_object_type(object_type))
+    }
+
+    pub fn embedding_for_index(&self, index: EmbeddingIndex) -> &Arc<ReloadableEmbedding> {
+        match index {
+            EmbeddingIndex::Code => &self.code_embedding_service,
+            EmbeddingIndex::Prose => &self.embedding_service,
+        }
+    }
 }
 
 #[tokio::main]
@@ -92,30 +121,9 @@ async fn main() -> anyhow::Result<()> {
 
     let settings = settings_service.load_settings().await.unwrap_or_default();
 
-    let (embedding_model, embedding_dimension) = match settings.embedding_provider.as_str() {
-        "openrouter" => (
-            settings.openrouter_model.clone(),
-            settings.openrouter_dimension as usize,
-        ),
-        "ollama" => (
-            settings.ollama_model.clone(),
-            settings.ollama_dimension as usize,
-        ),
-        _ => (
-            settings.openai_model.clone(),
-            settings.openai_dimension as usize,
-        ),
-    };
-
     // Initialize embedding service
-    let embedding_service = services::embedding::create_embedding_service(
-        &settings.embedding_provider,
-        Some(settings.openai_api_key.clone()),
-        Some(settings.openrouter_api_key.clone()),
-        settings.ollama_url.clone(),
-        embedding_dimension,
-        embedding_model.clone(),
-    );
+    let embedding_service = services::embedding::create_embedding_service_from_settings(&settings);
+    let (embedding_model, _) = settings.embedding_model_and_dimension();
 
     tracing::info!(
         "Embedding service initialized: provider={}, model={}, dimension={}, enabled={}",
@@ -125,38 +133,86 @@ async fn main() -> anyhow::Result<()> {
         embedding_service.is_enabled()
     );
 
-    let graph_service = Arc::new(GraphTraversalService::new(db.clone()));
-    tracing::info!("Graph traversal service initialized");
+    let metrics = if config.metrics_enabled {
+        tracing::info!("Prometheus metrics enabled at /metrics");
+        Some(Arc::new(Mutex::new(MetricsState::new()?)))
+    } else {
+        None
+    };
+    let embedding_service = Arc::new(ReloadableEmbedding::new(MeteredEmbedding::wrap_if_enabled(
+        embedding_service,
+        metrics.as_ref(),
+    )));
+    let code_embedding_service = if settings.code_embedding_provider.is_empty() {
+        Arc::new(ReloadableEmbedding::new(embedding_service.current()))
+    } else {
+        let code_settings = settings.code_embedding_settings();
+        let service = services::embedding::create_embedding_service_from_settings(&code_settings);
+        tracing::info!(
+            "Code embedding service initialized: provider={}, model={}, dimension={}",
+            code_settings.embedding_provider,
+            code_settings.embedding_model_and_dimension().0,
+            service.dimension()
+        );
+        Arc::new(ReloadableEmbedding::new(MeteredEmbedding::wrap_if_enabled(
+            service,
+            metrics.as_ref(),
+        )))
+    };
+
+    db.ensure_vector_indexes(services::embedding::indexable_dimension(
+        embedding_service.as_ref(),
+        code_embedding_service.as_ref(),
+    ))
+    .await;
 
-    let embedding_service_arc: Arc<dyn EmbeddingService> = Arc::from(embedding_service);
-    let hybrid_service = HybridRetrievalService::new(
-        db.clone(),
-        embedding_service_arc.clone(),
-        graph_service.clone(),
+    let graph_service = Arc::new(
+        GraphTraversalService::new(db.clone())
+            .with_node_budget(config.graph_node_budget)
+            .with_inline_link_threshold(config.inline_link_threshold),
     );
-    tracing::info!("Hybrid retrieval service initialized");
+    tracing::info!("Graph traversal service initialized");
 
     let analytics_service = Arc::new(AnalyticsService::new(db.clone()));
     tracing::info!("Analytics service initialized");
 
+    let hybrid_service =
+        HybridRetrievalService::new(db.clone(), embedding_service.clone(), graph_service.clone())
+            .with_code_embedding(code_embedding_service.clone())
+            .with_query_logging(config.log_query_content)
+            .with_superseded_weight(config.superseded_decision_weight)
+            .with_analytics(analytics_service.clone());
+    tracing::info!("Hybrid retrieval service initialized");
+
     let state = AppState {
         db,
         config: config.clone(),
-        embedding_service: embedding_service_arc,
+        embedding_service,
+        code_embedding_service,
         graph_service,
         hybrid_service: Arc::new(hybrid_service),
         analytics_service,
         settings_service,
+        metrics,
+        parse_cache: Arc::new(ParseCache::default()),
+        token_estimator: Arc::new(HeuristicTokenEstimator),
+        path_resolver: Arc::new(PathResolver::from_env()),
     };
 
-    // Build router
-    let app = Router::new()
-        .route("/health", get(health_check))
-        .nest("/v1", api_routes())
-        .layer(from_fn_with_state(state.clone(), track_latency))
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
-        .with_state(state);
+    handlers::leases::spawn_lease_gc(
+        state.db.clone(),
+        std::time::Duration::from_secs(config.lease_gc_interval_secs),
+    );
+    handlers::objects::spawn_object_gc(
+        state.db.clone(),
+        std::time::Duration::from_secs(config.object_gc_interval_secs),
+    );
+    services::analytics::spawn_avg_doc_len_refresh(
+        state.analytics_service.clone(),
+        services::analytics::AVG_DOC_LEN_REFRESH,
+    );
+
+    let app = build_router(state);
 
     let addr = format!("{}:{}", config.bind_address, config.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
@@ -166,6 +222,21 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn build_router(state: AppState) -> Router {
+    let mut router = Router::new().route("/health", get(health_check));
+    if state.metrics.is_some() {
+        router = router.route("/metrics", get(handlers::metrics::get_metrics));
+    }
+
+    router
+        .nest("/v1", api_routes())
+        .layer(from_fn(tenant::extract_tenant))
+        .layer(from_fn_with_state(state.clone(), track_latency))
+        .layer(CorsLayer::permissive())
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
 fn api_routes() -> Router<AppState> {
     Router::new()
         .route("/objects", post(handlers::objects::create_object))
@@ -173,11 +244,35 @@ fn api_routes() -> Router<AppState> {
             "/objects/batch",
             post(handlers::objects::create_objects_batch),
         )
+        .route(
+            "/objects/changed",
+            get(handlers::objects::list_changed_objects),
+        )
         .route("/objects/:id", get(handlers::objects::get_object))
         .route("/objects/:id", put(handlers::objects::update_object))
         .route("/objects/:id", delete(handlers::objects::delete_object))
+        .route(
+            "/objects/:id/history",
+            get(handlers::objects::get_object_history),
+        )
+        .route(
+            "/objects/:id/versions",
+            get(handlers::objects::list_object_versions),
+        )
+        .route(
+            "/objects/:id/versions/:version_number",
+            get(handlers::objects::get_object_version),
+        )
         .route("/query", post(handlers::query::query))
+        .route("/query/similar", post(handlers::query::find_similar))
+        .route("/path/:from/:to", get(handlers::graph::get_path))
+        .route("/graph/path", get(handlers::graph::get_graph_path))
+        .route("/graph/common", get(handlers::graph::get_common_neighbors))
         .route("/trace/:id", get(handlers::trace::get_trace))
+        .route("/trace/:id/path/:to", get(handlers::graph::get_path))
+        .route("/leases", get(handlers::leases::list_leases))
+        .route("/leases/:lease_id", get(handlers::leases::get_lease))
+        .route("/leases/:lease_id", delete(handlers::leases::delete_lease))
         .route("/leases/acquire", post(handlers::leases::acquire_lease))
         .route("/leases/release", post(handlers::leases::release_lease))
         .route("/leases/renew", post(handlers::leases::renew_lease))
@@ -204,6 +299,10 @@ fn api_routes() -> Router<AppState> {
             "/codebase/file-logs",
             get(handlers::codebase::get_file_logs),
         )
+        .route(
+            "/codebase/file-hashes",
+            get(handlers::codebase::get_file_hashes),
+        )
         .route(
             "/codebase/file-logs/:path",
             get(handlers::codebase::get_file_log),
@@ -224,15 +323,80 @@ fn api_routes() -> Router<AppState> {
             "/codebase/sync",
             post(handlers::codebase::sync_file),
         )
+        .route(
+            "/codebase/reindex-file",
+            post(handlers::codebase::reindex_file),
+        )
+        .route(
+            "/codebase/delete-file",
+            post(handlers::codebase::delete_file),
+        )
+        .route(
+            "/codebase/files/:file_id",
+            delete(handlers::codebase::delete_file_by_id),
+        )
         .route(
             "/codebase/ai-file-log",
             post(handlers::codebase::generate_ai_file_log),
         )
+        .route(
+            "/codebase/regenerate-ai-log",
+            post(handlers::codebase::regenerate_ai_file_log),
+        )
         // Analytics endpoint
         .route("/analytics", get(handlers::analytics::get_analytics))
+        .route("/analytics/cycles", get(handlers::analytics::get_cycles))
+        .route("/analytics/rank", post(handlers::analytics::rank_project))
+        .route("/maintenance/verify", get(handlers::maintenance::verify))
+        .route(
+            "/admin/rebuild-vector-index",
+            post(handlers::maintenance::rebuild_vector_index),
+        )
+        .route(
+            "/admin/prune-orphans",
+            post(handlers::maintenance::prune_orphans),
+        )
+        .route("/debug/db-health", get(handlers::debug::db_health))
+        .route(
+            "/debug/embedding-health",
+            get(handlers::debug::embedding_health),
+        )
+        .route(
+            "/projects/:project_id/export",
+            get(handlers::projects::export_project),
+        )
+        .route(
+            "/projects/import",
+            post(handlers::projects::import_project)
+                .layer(DefaultBodyLimit::max(handlers::projects::IMPORT_BODY_LIMIT)),
+        )
+        .route(
+            "/analysis/unreferenced",
+            get(handlers::analysis::get_unreferenced_symbols),
+        )
+        .route(
+            "/analysis/cycles",
+            get(handlers::analysis::get_dependency_cycles),
+        )
         // Settings endpoints
         .route("/settings", get(handlers::settings::get_settings))
         .route("/settings", put(handlers::settings::update_settings))
+        .route(
+            "/settings/tenant",
+            get(handlers::settings::get_tenant_settings),
+        )
+        .route(
+            "/settings/tenant",
+            put(handlers::settings::update_tenant_settings),
+        )
+        .route(
+            "/settings/projects/:project_id",
+            get(handlers::settings::get_project_settings),
+        )
+        .route(
+            "/settings/projects/:project_id",
+            put(handlers::settings::update_project_settings),
+        )
         .route("/settings/nuclear-delete", post(handlers::settings::nuclear_delete))
         // Artifact endpoints - unified write across all 3 memory layers
         .route("/artifacts", post(handlers::artifacts::write_artifact))
@@ -241,6 +405,11 @@ fn api_routes() -> Router<AppState> {
             "/artifacts/:id",
             delete(handlers::artifacts::delete_artifact),
         )
+        .route("/tasks/:id", patch(handlers::artifacts::update_task))
+        .route(
+            "/decisions/:id/supersede",
+            post(handlers::artifacts::supersede_decision),
+        )
         // Cache endpoints - semantic cache / unity layer (legacy)
         .route("/cache/pack", post(handlers::cache::get_pack))
         .route("/cache/write", post(handlers::cache::write_items))
@@ -249,6 +418,7 @@ fn api_routes() -> Router<AppState> {
         .route("/cache/block/write", post(handlers::cache::block_write))
         .route("/cache/block/compact", post(handlers::cache::block_compact))
         .route("/cache/block/search", post(handlers::cache::block_search))
+        .route("/cache/block/gc", post(handlers::cache::block_gc))
         // Unified cache block read/list endpoints (avoid collision with /cache/block/:id)
         .route("/cache/block/read", get(handlers::cache::block_read_get))
         .route("/cache/block/read", post(handlers::cache::block_read_post))
@@ -258,6 +428,7 @@ fn api_routes() -> Router<AppState> {
         .route("/cache/block/:id", get(handlers::cache::block_get))
         // Focus endpoint (REST equivalent for amp_focus MCP tool)
         .route("/focus", post(handlers::focus::handle_focus))
+        .route("/focus/sessions", get(handlers::focus::list_focus_sessions))
         // Connection tracking endpoints - real-time agent connection status
         .route(
             "/connections/register",
@@ -271,6 +442,7 @@ fn api_routes() -> Router<AppState> {
             "/connections/disconnect",
             post(handlers::connections::disconnect),
         )
+        .route("/connections/release", post(handlers::connections::release))
         .route("/connections", get(handlers::connections::list_connections))
         .route(
             "/connections/cleanup",
@@ -284,9 +456,25 @@ async fn track_latency(
     next: Next,
 ) -> Response {
     let start = Instant::now();
+    let endpoint = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let method = request.method().clone();
     let response = next.run(request).await;
-    let latency_ms = start.elapsed().as_secs_f32() * 1000.0;
+    let elapsed = start.elapsed();
+    let latency_ms = elapsed.as_secs_f32() * 1000.0;
     state.analytics_service.record_request_latency(latency_ms);
+    if let Some(metrics) = &state.metrics {
+        // Unmatched routes share one label so 404 scans can't grow the label set
+        let endpoint = endpoint.as_deref().unwrap_or("unmatched");
+        metrics.lock().unwrap().record_request(
+            endpoint,
+            method.as_str(),
+            response.status().as_u16(),
+            elapsed.as_secs_f64(),
+        );
+    }
     response
 }
 
@@ -297,3 +485,334 @@ async fn health_check() -> Result<Json<serde_json::Value>, StatusCode> {
         "version": env!("CARGO_PKG_VERSION")
     })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    async fn spawn_app_with_metrics() -> String {
+        let config = Config {
+            metrics_enabled: true,
+            ..test_support::test_config()
+        };
+        spawn_app(config).await
+    }
+
+    async fn spawn_app(config: Config) -> String {
+        let state = test_support::memory_state(config).await;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = build_router(state);
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_metrics_reports_request_counters() {
+        let base_url = spawn_app_with_metrics().await;
+        let client = reqwest::Client::new();
+
+        for _ in 0..3 {
+            let response = client.get(format!("{}/health", base_url)).send().await.unwrap();
+            assert!(response.status().is_success());
+        }
+        let response = client
+            .get(format!("{}/v1/settings", base_url))
+            .send()
+            .await
+            .unwrap();
+        let settings_status = response.status().as_u16();
+        let response = client
+            .post(format!("{}/v1/objects", base_url))
+            .json(&serde_json::json!({ "type": "note", "title": "metrics" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 201);
+        let response = client
+            .get(format!("{}/does-not-exist", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 404);
+
+        let response = client.get(format!("{}/metrics", base_url)).send().await.unwrap();
+        assert!(response.status().is_success());
+        let body = response.text().await.unwrap();
+
+        assert!(body.contains(
+            r#"amp_requests_total{endpoint="/health",method="GET",status="200"} 3"#
+        ));
+        assert!(body.contains(&format!(
+            r#"amp_requests_total{{endpoint="/v1/settings",method="GET",status="{}"}} 1"#,
+            settings_status
+        )));
+        assert!(body.contains(
+            r#"amp_requests_total{endpoint="unmatched",method="GET",status="404"} 1"#
+        ));
+        assert!(body.contains(
+            r#"amp_requests_total{endpoint="/v1/objects",method="POST",status="201"} 1"#
+        ));
+        assert!(body.contains(r#"amp_objects_total{type="note"} 1"#));
+        assert!(body.contains("amp_request_latency_seconds_count 6"));
+        assert!(body.contains("amp_embedding_errors_total 0"));
+    }
+
+    async fn post_as(
+        client: &reqwest::Client,
+        url: String,
+        tenant: &str,
+        body: serde_json::Value,
+    ) -> serde_json::Value {
+        let response = client
+            .post(url)
+            .header("X-AMP-Tenant", tenant)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success(), "{}", response.status());
+        response.json().await.unwrap()
+    }
+
+    async fn get_as(client: &reqwest::Client, url: String, tenant: &str) -> reqwest::Response {
+        client
+            .get(url)
+            .header("X-AMP-Tenant", tenant)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    fn result_names(response: &serde_json::Value) -> Vec<String> {
+        response["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|r| r["object"]["name"].as_str())
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_trace_path_route_returns_shortest_chain() {
+        let base_url = spawn_app(test_support::test_config()).await;
+        let client = reqwest::Client::new();
+
+        let mut ids = Vec::new();
+        for name in ["a", "b", "c", "d", "e"] {
+            let created = post_as(
+                &client,
+                format!("{}/v1/objects", base_url),
+                "default",
+                serde_json::json!({ "type": "symbol", "name": name, "kind": "function" }),
+            )
+            .await;
+            ids.push(created["id"].as_str().unwrap().to_string());
+        }
+        // a -> b -> c -> d -> e: e sits one hop past max_depth=3
+        for (source, target) in [(0, 1), (1, 2), (2, 3), (3, 4)] {
+            post_as(
+                &client,
+                format!("{}/v1/relationships", base_url),
+                "default",
+                serde_json::json!({ "type": "calls", "source_id": ids[source], "target_id": ids[target] }),
+            )
+            .await;
+        }
+
+        let response = get_as(
+            &client,
+            format!("{}/v1/trace/{}/path/{}", base_url, ids[0], ids[3]),
+            "default",
+        )
+        .await;
+        assert!(response.status().is_success());
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["found"], true);
+        assert_eq!(body["nodes"], serde_json::json!(ids[..4]));
+
+        let response = get_as(
+            &client,
+            format!("{}/v1/trace/{}/path/{}?max_depth=3", base_url, ids[0], ids[4]),
+            "default",
+        )
+        .await;
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["found"], false);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_header_isolates_read_paths() {
+        let base_url = spawn_app(test_support::test_config()).await;
+        let client = reqwest::Client::new();
+
+        let mut ids = HashMap::new();
+        for tenant in ["acme", "globex"] {
+            let created = post_as(
+                &client,
+                format!("{}/v1/objects", base_url),
+                tenant,
+                serde_json::json!({ "type": "symbol", "name": format!("{}-secret", tenant), "kind": "function", "project_id": "shared" }),
+            )
+            .await;
+            ids.insert(tenant, created["id"].as_str().unwrap().to_string());
+            post_as(
+                &client,
+                format!("{}/v1/objects", base_url),
+                tenant,
+                serde_json::json!({ "type": "FileLog", "file_path": "src/lib.rs", "summary": format!("{} file log", tenant), "project_id": "shared" }),
+            )
+            .await;
+            post_as(
+                &client,
+                format!("{}/v1/cache/block/write", base_url),
+                tenant,
+                serde_json::json!({ "scope_id": "project:shared", "kind": "fact", "content": format!("{} cache fact", tenant) }),
+            )
+            .await;
+        }
+        // Objects created without a header belong to the default tenant
+        post_as(
+            &client,
+            format!("{}/v1/objects", base_url),
+            "",
+            serde_json::json!({ "type": "symbol", "name": "default-secret", "kind": "function" }),
+        )
+        .await;
+        let helper = post_as(
+            &client,
+            format!("{}/v1/objects", base_url),
+            "acme",
+            serde_json::json!({ "type": "symbol", "name": "acme-helper", "kind": "function" }),
+        )
+        .await;
+        // A cross-tenant edge must not let traversal escape the caller's tenant
+        for target in [ids["globex"].as_str(), helper["id"].as_str().unwrap()] {
+            post_as(
+                &client,
+                format!("{}/v1/relationships", base_url),
+                "acme",
+                serde_json::json!({ "type": "calls", "source_id": ids["acme"], "target_id": target }),
+            )
+            .await;
+        }
+
+        let response = post_as(
+            &client,
+            format!("{}/v1/query", base_url),
+            "acme",
+            serde_json::json!({ "text": "secret" }),
+        )
+        .await;
+        assert_eq!(result_names(&response), vec!["acme-secret"]);
+
+        let response = post_as(
+            &client,
+            format!("{}/v1/query", base_url),
+            "acme",
+            serde_json::json!({ "text": "secret", "filters": { "tenant_id": "globex" } }),
+        )
+        .await;
+        assert_eq!(result_names(&response), vec!["acme-secret"]);
+
+        let response = post_as(
+            &client,
+            format!("{}/v1/query", base_url),
+            "default",
+            serde_json::json!({ "text": "secret" }),
+        )
+        .await;
+        assert_eq!(result_names(&response), vec!["default-secret"]);
+
+        let response = post_as(
+            &client,
+            format!("{}/v1/query", base_url),
+            "acme",
+            serde_json::json!({ "graph": { "start_nodes": [ids["acme"]], "relation_types": ["calls"], "algorithm": "collect" } }),
+        )
+        .await;
+        assert_eq!(result_names(&response), vec!["acme-helper"]);
+
+        let response = post_as(
+            &client,
+            format!("{}/v1/query", base_url),
+            "acme",
+            serde_json::json!({ "graph": { "start_nodes": [ids["acme"]], "relation_types": ["calls"] } }),
+        )
+        .await;
+        assert_eq!(result_names(&response), vec!["acme-helper"]);
+
+        let response = post_as(
+            &client,
+            format!("{}/v1/query", base_url),
+            "globex",
+            serde_json::json!({ "graph": { "start_nodes": [ids["acme"]], "relation_types": ["calls"], "direction": "outbound" } }),
+        )
+        .await;
+        assert!(result_names(&response).is_empty());
+
+        let response = get_as(
+            &client,
+            format!("{}/v1/objects/{}", base_url, ids["globex"]),
+            "acme",
+        )
+        .await;
+        assert_eq!(response.status().as_u16(), 404);
+
+        let response = get_as(
+            &client,
+            format!("{}/v1/codebase/file-log-objects/src%2Flib.rs", base_url),
+            "globex",
+        )
+        .await;
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["file_log"]["summary"], "globex file log");
+
+        let response = get_as(
+            &client,
+            format!("{}/v1/cache/block/list?scope_id=project:shared", base_url),
+            "acme",
+        )
+        .await;
+        let body: serde_json::Value = response.json().await.unwrap();
+        let summaries: Vec<&str> = body["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|m| m["summary"].as_str())
+            .collect();
+        assert_eq!(summaries, vec!["[fact] acme cache fact"]);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_header_with_quotes_cannot_escape_filter() {
+        let base_url = spawn_app(test_support::test_config()).await;
+        let client = reqwest::Client::new();
+
+        post_as(
+            &client,
+            format!("{}/v1/objects", base_url),
+            "acme",
+            serde_json::json!({ "type": "symbol", "name": "acme-secret", "kind": "function" }),
+        )
+        .await;
+
+        for tenant in ["x\\", "x\\' OR true OR tenant_id = '", "' OR true OR '"] {
+            let response = client
+                .post(format!("{}/v1/query", base_url))
+                .header("X-AMP-Tenant", tenant)
+                .json(&serde_json::json!({ "text": "secret" }))
+                .send()
+                .await
+                .unwrap();
+            assert!(response.status().is_success());
+            let body: serde_json::Value = response.json().await.unwrap();
+            assert!(result_names(&body).is_empty(), "tenant {:?} leaked", tenant);
+        }
+    }
+}
@@ -23,6 +23,121 @@ pub struct AnalyticsData {
     pub error_distribution: Vec<ErrorDistributionItem>,
     #[serde(rename = "systemEvents")]
     pub system_events: Vec<SystemEvent>,
+    #[serde(rename = "activeAgents")]
+    pub active_agents: ActiveAgents,
+    /// Persisted per-project and query statistics over a time window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detailed: Option<DetailedAnalytics>,
+    #[serde(
+        rename = "embeddingStatus",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub embedding_status: Option<EmbeddingStatus>,
+}
+
+/// Running embedding services compared with what the stored settings select
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingStatus {
+    pub prose: EmbeddingServiceStatus,
+    pub code: EmbeddingServiceStatus,
+    /// Either running service no longer matches the stored settings
+    #[serde(rename = "restartRequired")]
+    pub restart_required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingServiceStatus {
+    /// Provider named in the stored settings
+    pub provider: String,
+    pub live: EmbeddingIdentity,
+    pub configured: EmbeddingIdentity,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingIdentity {
+    pub enabled: bool,
+    pub model: String,
+    pub dimension: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetailedAnalytics {
+    #[serde(rename = "projectId")]
+    pub project_id: Option<String>,
+    /// `24h`, `7d` or `30d`
+    pub window: String,
+    #[serde(rename = "bucketSeconds")]
+    pub bucket_seconds: i64,
+    #[serde(rename = "objectsByType")]
+    pub objects_by_type: HashMap<String, i64>,
+    pub chunks: ChunkEmbeddingStats,
+    #[serde(rename = "cacheBlocksByScope")]
+    pub cache_blocks_by_scope: HashMap<String, i64>,
+    pub queries: QueryStats,
+    /// Oldest bucket first
+    pub series: Vec<AnalyticsBucket>,
+    /// Indexed projects, most recently indexed first
+    #[serde(default)]
+    pub projects: Vec<ProjectHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectHealth {
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub name: Option<String>,
+    pub files: i64,
+    pub symbols: i64,
+    pub chunks: ChunkEmbeddingStats,
+    /// Latest update to a file node, FileLog or chunk
+    #[serde(rename = "lastIndexed")]
+    pub last_indexed: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkEmbeddingStats {
+    pub embedded: i64,
+    pub unembedded: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryStats {
+    pub total: i64,
+    #[serde(rename = "perHour")]
+    pub per_hour: f32,
+    /// Mean score of the best result, over queries that returned any
+    #[serde(rename = "avgTopScore")]
+    pub avg_top_score: f32,
+    #[serde(rename = "zeroResultRate")]
+    pub zero_result_rate: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsBucket {
+    pub timestamp: String,
+    #[serde(rename = "objectsCreated")]
+    pub objects_created: i64,
+    pub queries: QueryStats,
+}
+
+/// Agents with a live (unexpired) MCP connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveAgents {
+    pub count: usize,
+    pub agents: Vec<ActiveAgent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveAgent {
+    #[serde(rename = "agentName")]
+    pub agent_name: String,
+    #[serde(rename = "runId")]
+    pub run_id: Option<String>,
+    #[serde(rename = "projectId")]
+    pub project_id: Option<String>,
+    #[serde(rename = "lastHeartbeat")]
+    pub last_heartbeat: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +171,8 @@ pub struct IndexingStats {
     pub last_index_time: String,
     #[serde(rename = "indexingSpeed")]
     pub indexing_speed: String,
+    #[serde(rename = "embeddingCacheHits")]
+    pub embedding_cache_hits: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
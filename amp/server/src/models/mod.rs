@@ -31,6 +31,9 @@ pub enum ObjectType {
     Run,
     FileChunk,
     FileLog,
+    Note,
+    Task,
+    Warning,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +62,8 @@ pub struct Symbol {
     pub content_hash: Option<String>,
     pub signature: Option<String>,
     pub documentation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -193,6 +198,11 @@ pub enum AmpObject {
     Run(Run),
     FileChunk(FileChunk),
     FileLog(FileLog),
+    // Before Note: a warning is a note with a severity, and untagged
+    // variants match in order
+    Warning(Warning),
+    Note(Note),
+    Task(Task),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -224,4 +234,226 @@ pub struct FileLog {
     pub last_modified: String,
     pub change_count: u32,
     pub linked_changesets: Vec<String>,
+    /// SHA256 of the file content when the log was written; `amp diff` compares it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+}
+
+/// Free-form agent note; `body` is markdown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    #[serde(flatten)]
+    pub base: BaseObject,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Something agents should be careful about, optionally only until `expires_at`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Warning {
+    #[serde(flatten)]
+    pub base: BaseObject,
+    pub title: String,
+    pub body: String,
+    pub severity: WarningSeverity,
+    #[serde(default)]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Warning {
+    /// Expired warnings are left out of query results unless asked for.
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningSeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// Outstanding work recorded by an agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    #[serde(flatten)]
+    pub base: BaseObject,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TaskStatus,
+    pub assignee: Option<String>,
+    pub due: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Open,
+    #[serde(alias = "in_progress")]
+    InProgress,
+    Blocked,
+    Done,
+}
+
+impl TaskStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskStatus::Open => "open",
+            TaskStatus::InProgress => "inprogress",
+            TaskStatus::Blocked => "blocked",
+            TaskStatus::Done => "done",
+        }
+    }
+
+    /// A done task has to be reopened before it can be worked on again.
+    pub fn can_transition_to(self, next: TaskStatus) -> bool {
+        self != TaskStatus::Done || matches!(next, TaskStatus::Done | TaskStatus::Open)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_json() -> serde_json::Value {
+        serde_json::json!({
+            "id": "6f1c0a4e-2b7d-4c55-9d8e-3f0a1b2c3d4e",
+            "type": "note",
+            "tenant_id": "default",
+            "project_id": "amp",
+            "created_at": "2024-06-01T00:00:00Z",
+            "updated_at": "2024-06-01T00:00:00Z",
+            "provenance": { "agent": "tester", "model": null, "tools": null, "summary": "note" },
+            "title": "Lease renewal",
+            "body": "Renew leases at half their TTL.",
+            "tags": ["leases", "ops"],
+        })
+    }
+
+    #[test]
+    fn test_note_object_type_is_lowercase_on_the_wire() {
+        assert_eq!(serde_json::to_value(ObjectType::Note).unwrap(), "note");
+        let parsed: ObjectType = serde_json::from_value(serde_json::json!("note")).unwrap();
+        assert!(matches!(parsed, ObjectType::Note));
+    }
+
+    #[test]
+    fn test_note_round_trips_through_amp_object() {
+        let parsed: AmpObject = serde_json::from_value(note_json()).unwrap();
+        let AmpObject::Note(note) = &parsed else {
+            panic!("expected a note, got {:?}", parsed);
+        };
+        assert_eq!(note.title, "Lease renewal");
+        assert_eq!(note.body, "Renew leases at half their TTL.");
+        assert_eq!(note.tags, vec!["leases", "ops"]);
+        assert!(matches!(note.base.object_type, ObjectType::Note));
+
+        let value = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(value["type"], "note");
+        assert_eq!(value["body"], note_json()["body"]);
+        assert_eq!(value["tags"], note_json()["tags"]);
+        let again: AmpObject = serde_json::from_value(value).unwrap();
+        assert!(matches!(again, AmpObject::Note(_)));
+    }
+
+    #[test]
+    fn test_warning_round_trips_and_expires() {
+        let mut value = note_json();
+        let map = value.as_object_mut().unwrap();
+        map.insert("type".to_string(), serde_json::json!("warning"));
+        map.insert("severity".to_string(), serde_json::json!("high"));
+        map.insert(
+            "expires_at".to_string(),
+            serde_json::json!("2024-06-02T00:00:00Z"),
+        );
+
+        let parsed: AmpObject = serde_json::from_value(value).unwrap();
+        let AmpObject::Warning(warning) = &parsed else {
+            panic!("expected a warning, got {:?}", parsed);
+        };
+        assert_eq!(warning.severity, WarningSeverity::High);
+        assert!(matches!(warning.base.object_type, ObjectType::Warning));
+        let before: chrono::DateTime<chrono::Utc> = "2024-06-01T12:00:00Z".parse().unwrap();
+        let after: chrono::DateTime<chrono::Utc> = "2024-06-02T00:00:00Z".parse().unwrap();
+        assert!(!warning.is_expired(before));
+        assert!(warning.is_expired(after));
+        assert_eq!(serde_json::to_value(&parsed).unwrap()["severity"], "high");
+
+        assert!(serde_json::from_value::<WarningSeverity>(serde_json::json!("urgent")).is_err());
+    }
+
+    #[test]
+    fn test_note_tags_default_to_empty() {
+        let mut value = note_json();
+        value.as_object_mut().unwrap().remove("tags");
+        let note: Note = serde_json::from_value(value).unwrap();
+        assert!(note.tags.is_empty());
+    }
+
+    fn task_json() -> serde_json::Value {
+        serde_json::json!({
+            "id": "0b6e8a6c-58f4-4f4e-8d0e-2a9c7e5b1d10",
+            "type": "task",
+            "tenant_id": "default",
+            "project_id": "amp",
+            "created_at": "2024-06-01T00:00:00Z",
+            "updated_at": "2024-06-01T00:00:00Z",
+            "provenance": { "agent": "tester", "model": null, "tools": null, "summary": "task" },
+            "title": "Add lease metrics",
+            "description": "Count renewals per agent.",
+            "status": "inprogress",
+            "assignee": "claude-1",
+            "due": "2024-07-01T12:00:00Z",
+        })
+    }
+
+    #[test]
+    fn test_task_round_trips_through_amp_object() {
+        let parsed: AmpObject = serde_json::from_value(task_json()).unwrap();
+        let AmpObject::Task(task) = &parsed else {
+            panic!("expected a task, got {:?}", parsed);
+        };
+        assert_eq!(task.status, TaskStatus::InProgress);
+        assert_eq!(task.assignee.as_deref(), Some("claude-1"));
+        assert_eq!(
+            task.due.unwrap().to_rfc3339(),
+            "2024-07-01T12:00:00+00:00"
+        );
+        assert!(matches!(task.base.object_type, ObjectType::Task));
+
+        let value = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(value["type"], "task");
+        assert_eq!(value["status"], "inprogress");
+        let again: AmpObject = serde_json::from_value(value).unwrap();
+        assert!(matches!(again, AmpObject::Task(_)));
+    }
+
+    #[test]
+    fn test_task_status_is_lowercase_and_accepts_snake_case() {
+        assert_eq!(serde_json::to_value(TaskStatus::Blocked).unwrap(), "blocked");
+        let parsed: TaskStatus = serde_json::from_value(serde_json::json!("in_progress")).unwrap();
+        assert_eq!(parsed, TaskStatus::InProgress);
+        assert!(serde_json::from_value::<TaskStatus>(serde_json::json!("later")).is_err());
+    }
+
+    #[test]
+    fn test_task_status_transitions() {
+        use TaskStatus::*;
+        for from in [Open, InProgress, Blocked] {
+            for to in [Open, InProgress, Blocked, Done] {
+                assert!(from.can_transition_to(to), "{:?} -> {:?}", from, to);
+            }
+        }
+        assert!(Done.can_transition_to(Open));
+        assert!(Done.can_transition_to(Done));
+        assert!(!Done.can_transition_to(InProgress));
+        assert!(!Done.can_transition_to(Blocked));
+    }
 }
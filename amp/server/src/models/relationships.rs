@@ -13,6 +13,7 @@ pub enum RelationType {
     Modifies,
     Implements,
     Produced,
+    SupersededBy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
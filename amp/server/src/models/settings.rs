@@ -13,7 +13,7 @@ pub struct SettingsConfig {
     pub db_pass: String,
 
     // Embedding Provider
-    pub embedding_provider: String, // "openai", "openrouter", "ollama", or "none"
+    pub embedding_provider: String, // "openai", "openrouter", "ollama", "cohere", or "none"
 
     // OpenAI Settings
     pub openai_api_key: String,
@@ -30,6 +30,34 @@ pub struct SettingsConfig {
     pub ollama_model: String,
     pub ollama_dimension: u32,
 
+    // Cohere Settings
+    #[serde(default)]
+    pub cohere_api_key: String,
+    #[serde(default = "default_cohere_model")]
+    pub cohere_model: String,
+    #[serde(default = "default_cohere_dimension")]
+    pub cohere_dimension: u32,
+
+    // Code Embedding Settings (empty provider reuses the prose provider)
+    #[serde(default)]
+    pub code_embedding_provider: String,
+    #[serde(default)]
+    pub code_embedding_model: String,
+    #[serde(default)]
+    pub code_embedding_dimension: u32,
+
+    // Embedding Retry Settings
+    #[serde(default = "default_embedding_retry_attempts")]
+    pub embedding_retry_attempts: u32,
+    #[serde(default = "default_embedding_retry_base_delay_ms")]
+    pub embedding_retry_base_delay_ms: u64,
+
+    // Embedding Batch Settings
+    #[serde(default = "default_embedding_batch_size")]
+    pub embedding_batch_size: usize,
+    #[serde(default = "default_embedding_concurrency")]
+    pub embedding_concurrency: usize,
+
     // Index Model Settings
     pub index_provider: String, // "openai", "openrouter", "ollama", or "none"
     pub index_openai_model: String,
@@ -38,6 +66,16 @@ pub struct SettingsConfig {
     pub index_workers: u32,
     #[serde(default)]
     pub index_respect_gitignore: bool,
+    #[serde(default = "default_cache_llm_summaries")]
+    pub cache_llm_summaries: bool,
+
+    // Cache Item Filter Settings
+    #[serde(default)]
+    pub cache_filter_low_value: bool,
+    #[serde(default = "default_cache_min_item_chars")]
+    pub cache_min_item_chars: usize,
+    #[serde(default = "default_cache_low_value_patterns")]
+    pub cache_low_value_patterns: Vec<String>,
 
     // Legacy
     pub max_embedding_dimension: u32,
@@ -61,13 +99,149 @@ impl Default for SettingsConfig {
             ollama_url: "http://localhost:11434".to_string(),
             ollama_model: "nomic-embed-text".to_string(),
             ollama_dimension: 768,
+            cohere_api_key: String::new(),
+            cohere_model: default_cohere_model(),
+            cohere_dimension: default_cohere_dimension(),
+            code_embedding_provider: String::new(),
+            code_embedding_model: String::new(),
+            code_embedding_dimension: 0,
+            embedding_retry_attempts: default_embedding_retry_attempts(),
+            embedding_retry_base_delay_ms: default_embedding_retry_base_delay_ms(),
+            embedding_batch_size: default_embedding_batch_size(),
+            embedding_concurrency: default_embedding_concurrency(),
             index_provider: "none".to_string(),
             index_openai_model: "gpt-4o-mini".to_string(),
             index_openrouter_model: "openai/gpt-4o-mini".to_string(),
             index_ollama_model: "llama3.1".to_string(),
             index_workers: 4,
             index_respect_gitignore: true,
+            cache_llm_summaries: default_cache_llm_summaries(),
+            cache_filter_low_value: false,
+            cache_min_item_chars: default_cache_min_item_chars(),
+            cache_low_value_patterns: default_cache_low_value_patterns(),
             max_embedding_dimension: 1536,
         }
     }
 }
+
+impl SettingsConfig {
+    /// Model and vector dimension of the selected embedding provider.
+    pub fn embedding_model_and_dimension(&self) -> (String, usize) {
+        match self.embedding_provider.as_str() {
+            "openrouter" => (
+                self.openrouter_model.clone(),
+                self.openrouter_dimension as usize,
+            ),
+            "ollama" => (self.ollama_model.clone(), self.ollama_dimension as usize),
+            "cohere" => (self.cohere_model.clone(), self.cohere_dimension as usize),
+            _ => (self.openai_model.clone(), self.openai_dimension as usize),
+        }
+    }
+
+    /// Settings for the code embedding service: the prose settings with the code
+    /// provider, model and dimension overrides applied.
+    pub fn code_embedding_settings(&self) -> SettingsConfig {
+        let mut code = self.clone();
+        if self.code_embedding_provider.is_empty() {
+            return code;
+        }
+        code.embedding_provider = self.code_embedding_provider.clone();
+        let (model, dimension) = match self.code_embedding_provider.as_str() {
+            "openrouter" => (&mut code.openrouter_model, &mut code.openrouter_dimension),
+            "ollama" => (&mut code.ollama_model, &mut code.ollama_dimension),
+            "cohere" => (&mut code.cohere_model, &mut code.cohere_dimension),
+            _ => (&mut code.openai_model, &mut code.openai_dimension),
+        };
+        if !self.code_embedding_model.is_empty() {
+            *model = self.code_embedding_model.clone();
+        }
+        if self.code_embedding_dimension > 0 {
+            *dimension = self.code_embedding_dimension;
+        }
+        code
+    }
+
+    /// Whether switching from `other` to these settings needs a new embedding service.
+    pub fn embedding_differs(&self, other: &SettingsConfig) -> bool {
+        self.embedding_provider != other.embedding_provider
+            || self.embedding_model_and_dimension() != other.embedding_model_and_dimension()
+            || self.openai_api_key != other.openai_api_key
+            || self.openrouter_api_key != other.openrouter_api_key
+            || self.cohere_api_key != other.cohere_api_key
+            || self.ollama_url != other.ollama_url
+            || self.embedding_retry_attempts != other.embedding_retry_attempts
+            || self.embedding_retry_base_delay_ms != other.embedding_retry_base_delay_ms
+            || self.embedding_batch_size != other.embedding_batch_size
+            || self.embedding_concurrency != other.embedding_concurrency
+    }
+
+    /// Whether a cache item should be dropped by the low-value filter: shorter than
+    /// the minimum length, or (ignoring case and trailing punctuation) one of the patterns.
+    pub fn is_low_value_cache_item(&self, content: &str) -> bool {
+        if !self.cache_filter_low_value {
+            return false;
+        }
+        let normalized = normalize_cache_content(content);
+        normalized.chars().count() < self.cache_min_item_chars
+            || self
+                .cache_low_value_patterns
+                .iter()
+                .any(|pattern| normalize_cache_content(pattern) == normalized)
+    }
+}
+
+fn normalize_cache_content(content: &str) -> String {
+    content
+        .trim()
+        .trim_end_matches(|c: char| c.is_ascii_punctuation())
+        .trim()
+        .to_lowercase()
+}
+
+fn default_cohere_model() -> String {
+    "embed-english-v3.0".to_string()
+}
+
+fn default_cohere_dimension() -> u32 {
+    1024
+}
+
+fn default_embedding_retry_attempts() -> u32 {
+    3
+}
+
+fn default_embedding_retry_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_embedding_batch_size() -> usize {
+    100
+}
+
+fn default_embedding_concurrency() -> usize {
+    4
+}
+
+fn default_cache_llm_summaries() -> bool {
+    true
+}
+
+fn default_cache_min_item_chars() -> usize {
+    8
+}
+
+pub(crate) fn default_cache_low_value_patterns() -> Vec<String> {
+    [
+        "ok",
+        "okay",
+        "done",
+        "thanks",
+        "got it",
+        "on it",
+        "will do",
+        "looking into it",
+    ]
+    .iter()
+    .map(|pattern| pattern.to_string())
+    .collect()
+}
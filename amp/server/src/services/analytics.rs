@@ -1,14 +1,16 @@
 use crate::{
     database::Database,
     models::analytics::{
-        ActivityItem, AnalyticsData, ErrorDistributionItem, IndexingStats, LatencyPoint,
-        RequestLatencyData, SystemEvent, SystemMetrics,
+        ActiveAgent, ActiveAgents, ActivityItem, AnalyticsBucket, AnalyticsData,
+        ChunkEmbeddingStats, DetailedAnalytics, ErrorDistributionItem, IndexingStats, LatencyPoint,
+        ProjectHealth, QueryStats, RequestLatencyData, SystemEvent, SystemMetrics,
     },
+    services::{embedding_cache, hybrid::DOCUMENT_FIELDS},
     surreal_json::take_json_values,
 };
 use anyhow::Result;
-use chrono::Utc;
-use std::collections::{HashMap, VecDeque};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::Arc;
 use sysinfo::{Disks, System};
 
@@ -19,10 +21,107 @@ struct LatencyBucket {
     count: u32,
 }
 
+/// Time span covered by detailed analytics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyticsWindow {
+    Day,
+    Week,
+    Month,
+}
+
+impl AnalyticsWindow {
+    pub fn parse(window: &str) -> Option<Self> {
+        match window {
+            "24h" => Some(Self::Day),
+            "7d" => Some(Self::Week),
+            "30d" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "24h",
+            Self::Week => "7d",
+            Self::Month => "30d",
+        }
+    }
+
+    /// Hourly buckets for a day, daily ones otherwise
+    fn bucket(self) -> Duration {
+        match self {
+            Self::Day => Duration::hours(1),
+            Self::Week | Self::Month => Duration::days(1),
+        }
+    }
+
+    fn bucket_count(self) -> i32 {
+        match self {
+            Self::Day => 24,
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+}
+
+/// Running totals behind a [`QueryStats`]
+#[derive(Debug, Default, Clone, Copy)]
+struct QueryTally {
+    total: i64,
+    zero_results: i64,
+    top_score_sum: f64,
+    scored: i64,
+}
+
+impl QueryTally {
+    fn add(&mut self, result_count: i64, top_score: Option<f64>) {
+        self.total += 1;
+        if result_count == 0 {
+            self.zero_results += 1;
+        }
+        if let Some(score) = top_score.filter(|_| result_count > 0) {
+            self.top_score_sum += score;
+            self.scored += 1;
+        }
+    }
+
+    fn stats(&self, hours: f32) -> QueryStats {
+        let ratio = |n: f64, d: i64| if d > 0 { (n / d as f64) as f32 } else { 0.0 };
+        QueryStats {
+            total: self.total,
+            per_hour: if hours > 0.0 {
+                self.total as f32 / hours
+            } else {
+                0.0
+            },
+            avg_top_score: ratio(self.top_score_sum, self.scored),
+            zero_result_rate: ratio(self.zero_results as f64, self.total),
+        }
+    }
+}
+
+/// How often cached BM25 document lengths are recomputed
+pub const AVG_DOC_LEN_REFRESH: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// Recompute average document lengths every `interval` for the life of the server.
+pub fn spawn_avg_doc_len_refresh(analytics: Arc<AnalyticsService>, interval: std::time::Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = analytics.refresh_avg_doc_lens().await {
+                tracing::warn!("Document length refresh failed: {}", e);
+            }
+        }
+    });
+}
+
 pub struct AnalyticsService {
     db: Arc<Database>,
     system: std::sync::Mutex<System>,
     latency_points: std::sync::Mutex<VecDeque<LatencyBucket>>,
+    /// Mean searchable-text length in terms, per project, for BM25
+    avg_doc_lens: std::sync::RwLock<HashMap<String, f64>>,
 }
 
 impl AnalyticsService {
@@ -31,9 +130,43 @@ impl AnalyticsService {
             db,
             system: std::sync::Mutex::new(System::new_all()),
             latency_points: std::sync::Mutex::new(VecDeque::new()),
+            avg_doc_lens: std::sync::RwLock::new(HashMap::new()),
         }
     }
 
+    /// Cached average document length for `project_id`, once computed.
+    pub fn avg_doc_len(&self, project_id: &str) -> Option<f64> {
+        self.avg_doc_lens.read().unwrap().get(project_id).copied()
+    }
+
+    /// Recompute every project's average document length; returns the
+    /// number of projects cached.
+    pub async fn refresh_avg_doc_lens(&self) -> Result<usize> {
+        let text = DOCUMENT_FIELDS
+            .iter()
+            .map(|field| format!("<string> ({} ?? '')", field))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let query = format!(
+            "SELECT project_id, math::mean(array::len(string::words(string::join(' ', {})))) AS avg_len \
+             FROM objects WHERE project_id != NONE GROUP BY project_id",
+            text
+        );
+        let mut response = self.db.client.query(query).await?.check()?;
+        let lengths: HashMap<String, f64> = take_json_values(&mut response, 0)
+            .into_iter()
+            .filter_map(|row| {
+                Some((
+                    row["project_id"].as_str()?.to_string(),
+                    row["avg_len"].as_f64()?,
+                ))
+            })
+            .collect();
+        let count = lengths.len();
+        *self.avg_doc_lens.write().unwrap() = lengths;
+        Ok(count)
+    }
+
     pub fn record_request_latency(&self, latency_ms: f32) {
         let mut points = self.latency_points.lock().unwrap();
         let timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
@@ -61,6 +194,218 @@ impl AnalyticsService {
         }
     }
 
+    /// Persist one query's outcome; failures are logged and otherwise ignored.
+    pub async fn record_query(
+        &self,
+        project_id: Option<&str>,
+        result_count: usize,
+        top_score: Option<f32>,
+    ) {
+        let query = "CREATE query_stats SET project_id = $project_id, result_count = $result_count, top_score = $top_score";
+        let result = self
+            .db
+            .client
+            .query(query)
+            .bind(("project_id", project_id.map(str::to_string)))
+            .bind(("result_count", result_count as i64))
+            .bind(("top_score", top_score.map(f64::from)))
+            .await
+            .and_then(surrealdb::Response::check);
+        if let Err(e) = result {
+            tracing::warn!("Failed to record query stats: {}", e);
+        }
+    }
+
+    /// Per-project counts and query statistics over `window`, bucketed into a series.
+    pub async fn get_detailed(
+        &self,
+        project_id: Option<&str>,
+        window: AnalyticsWindow,
+    ) -> Result<DetailedAnalytics> {
+        let bucket = window.bucket();
+        let end = Utc::now().duration_trunc(bucket)? + bucket;
+        let start = end - bucket * window.bucket_count();
+        let project_filter = if project_id.is_some() {
+            " AND project_id = $project_id"
+        } else {
+            ""
+        };
+        let query = format!(
+            "SELECT string::lowercase(string::concat('', type)) AS obj_type, count() AS count FROM objects WHERE true{filter} GROUP BY obj_type;
+             RETURN count(SELECT id FROM objects WHERE type = 'FileChunk' AND embedding IS NOT NONE AND embedding IS NOT NULL{filter});
+             RETURN count(SELECT id FROM objects WHERE type = 'FileChunk'{filter});
+             SELECT scope_id, count() AS count FROM cache_block{scope_filter} GROUP BY scope_id;
+             SELECT VALUE string::concat('', created_at) FROM objects WHERE created_at IS NOT NONE AND string::concat('', created_at) >= $start_text{filter};
+             SELECT string::concat('', created_at) AS created_at, result_count, top_score FROM query_stats WHERE created_at >= <datetime>$start{filter};",
+            filter = project_filter,
+            scope_filter = if project_id.is_some() {
+                " WHERE string::contains(scope_id, $project_id)"
+            } else {
+                ""
+            },
+        );
+        let mut result = self
+            .db
+            .client
+            .query(query)
+            .bind(("project_id", project_id.map(str::to_string)))
+            .bind(("start", start.to_rfc3339()))
+            // Stored timestamps are UTC RFC 3339 strings or datetimes, which sort as text
+            .bind(("start_text", start.format("%Y-%m-%dT%H:%M:%S").to_string()))
+            .await?
+            .check()?;
+
+        let count_map = |rows: Vec<serde_json::Value>, key: &str| {
+            rows.iter()
+                .filter_map(|row| {
+                    Some((
+                        row.get(key)?.as_str()?.to_string(),
+                        row.get("count")?.as_i64()?,
+                    ))
+                })
+                .collect::<HashMap<String, i64>>()
+        };
+        let count =
+            |values: Vec<serde_json::Value>| values.first().and_then(|v| v.as_i64()).unwrap_or(0);
+        let objects_by_type = count_map(take_json_values(&mut result, 0), "obj_type");
+        let embedded = count(take_json_values(&mut result, 1));
+        let chunks = count(take_json_values(&mut result, 2));
+        let cache_blocks_by_scope = count_map(take_json_values(&mut result, 3), "scope_id");
+        let created: Vec<serde_json::Value> = take_json_values(&mut result, 4);
+        let queries: Vec<serde_json::Value> = take_json_values(&mut result, 5);
+
+        let bucket_of = |timestamp: &str| {
+            let at = DateTime::parse_from_rfc3339(timestamp)
+                .ok()?
+                .with_timezone(&Utc);
+            let index = (at - start).num_seconds().div_euclid(bucket.num_seconds());
+            (0..window.bucket_count() as i64)
+                .contains(&index)
+                .then_some(index as usize)
+        };
+        let mut series: Vec<(i64, QueryTally)> =
+            vec![Default::default(); window.bucket_count() as usize];
+        for index in created.iter().filter_map(|v| bucket_of(v.as_str()?)) {
+            series[index].0 += 1;
+        }
+        let mut overall = QueryTally::default();
+        for row in &queries {
+            let Some(index) = row
+                .get("created_at")
+                .and_then(|v| v.as_str())
+                .and_then(bucket_of)
+            else {
+                continue;
+            };
+            let result_count = row
+                .get("result_count")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0);
+            let top_score = row.get("top_score").and_then(|v| v.as_f64());
+            series[index].1.add(result_count, top_score);
+            overall.add(result_count, top_score);
+        }
+
+        let bucket_hours = bucket.num_hours() as f32;
+        Ok(DetailedAnalytics {
+            project_id: project_id.map(str::to_string),
+            window: window.as_str().to_string(),
+            bucket_seconds: bucket.num_seconds(),
+            objects_by_type,
+            chunks: ChunkEmbeddingStats {
+                embedded,
+                unembedded: chunks - embedded,
+            },
+            cache_blocks_by_scope,
+            queries: overall.stats(bucket_hours * window.bucket_count() as f32),
+            series: series
+                .into_iter()
+                .enumerate()
+                .map(|(i, (objects_created, tally))| AnalyticsBucket {
+                    timestamp: (start + bucket * i as i32).to_rfc3339(),
+                    objects_created,
+                    queries: tally.stats(bucket_hours),
+                })
+                .collect(),
+            projects: self.project_health(project_id).await?,
+        })
+    }
+
+    /// File, symbol and chunk counts per indexed project, most recently
+    /// indexed first.
+    async fn project_health(&self, project_id: Option<&str>) -> Result<Vec<ProjectHealth>> {
+        let query = format!(
+            "SELECT project_id, name FROM objects WHERE type IN ['Symbol', 'symbol'] AND kind = 'project'{filter};
+             SELECT project_id, count() AS count FROM objects WHERE type IN ['Symbol', 'symbol'] AND kind = 'file'{filter} GROUP BY project_id;
+             SELECT project_id, count() AS count FROM objects WHERE type IN ['Symbol', 'symbol'] AND kind NOT IN ['file', 'project', 'directory']{filter} GROUP BY project_id;
+             SELECT project_id, count() AS count FROM objects WHERE type = 'FileChunk'{filter} GROUP BY project_id;
+             SELECT project_id, count() AS count FROM objects WHERE type = 'FileChunk' AND embedding IS NOT NONE AND embedding IS NOT NULL{filter} GROUP BY project_id;
+             SELECT project_id, time::max(<datetime> updated_at) AS last_indexed FROM objects WHERE (type = 'FileLog' OR type = 'FileChunk' OR kind = 'file') AND updated_at IS NOT NONE{filter} GROUP BY project_id;",
+            filter = if project_id.is_some() {
+                " AND project_id = $project_id"
+            } else {
+                " AND project_id IS NOT NONE"
+            },
+        );
+        let mut result = self
+            .db
+            .client
+            .query(query)
+            .bind(("project_id", project_id.map(str::to_string)))
+            .await?
+            .check()?;
+
+        let mut projects: BTreeMap<String, ProjectHealth> = BTreeMap::new();
+        let mut rows = |index: usize| {
+            take_json_values(&mut result, index)
+                .into_iter()
+                .filter_map(|row| Some((row.get("project_id")?.as_str()?.to_string(), row)))
+                .collect::<Vec<_>>()
+        };
+        let (nodes, files, symbols, chunks, embedded, indexed) =
+            (rows(0), rows(1), rows(2), rows(3), rows(4), rows(5));
+        fn entry(projects: &mut BTreeMap<String, ProjectHealth>, id: String) -> &mut ProjectHealth {
+            projects.entry(id.clone()).or_insert_with(|| ProjectHealth {
+                project_id: id,
+                name: None,
+                files: 0,
+                symbols: 0,
+                chunks: ChunkEmbeddingStats::default(),
+                last_indexed: None,
+            })
+        }
+        let count =
+            |row: &serde_json::Value| row.get("count").and_then(|v| v.as_i64()).unwrap_or(0);
+        for (id, row) in nodes {
+            entry(&mut projects, id).name =
+                row.get("name").and_then(|v| v.as_str()).map(str::to_string);
+        }
+        for (id, row) in files {
+            entry(&mut projects, id).files = count(&row);
+        }
+        for (id, row) in symbols {
+            entry(&mut projects, id).symbols = count(&row);
+        }
+        for (id, row) in chunks {
+            entry(&mut projects, id).chunks.unembedded = count(&row);
+        }
+        for (id, row) in embedded {
+            let project = entry(&mut projects, id);
+            project.chunks.embedded = count(&row);
+            project.chunks.unembedded -= project.chunks.embedded;
+        }
+        for (id, row) in indexed {
+            entry(&mut projects, id).last_indexed = row
+                .get("last_indexed")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+        }
+
+        let mut projects: Vec<ProjectHealth> = projects.into_values().collect();
+        projects.sort_by(|a, b| b.last_indexed.cmp(&a.last_indexed));
+        Ok(projects)
+    }
+
     pub async fn get_analytics(&self) -> Result<AnalyticsData> {
         // Collect all analytics data in parallel
         let (
@@ -74,6 +419,7 @@ impl AnalyticsService {
             request_latency,
             error_distribution,
             system_events,
+            active_agents,
         ) = tokio::try_join!(
             self.get_total_objects(),
             self.get_total_relationships(),
@@ -85,6 +431,7 @@ impl AnalyticsService {
             self.get_request_latency(),
             self.get_error_distribution(),
             self.get_system_events(),
+            self.get_active_agents(),
         )?;
 
         Ok(AnalyticsData {
@@ -98,6 +445,9 @@ impl AnalyticsService {
             request_latency,
             error_distribution,
             system_events,
+            active_agents,
+            detailed: None,
+            embedding_status: None,
         })
     }
 
@@ -120,6 +470,7 @@ impl AnalyticsService {
             "modifies",
             "implements",
             "produced",
+            "superseded_by",
         ];
 
         let mut total = 0_i64;
@@ -197,6 +548,30 @@ impl AnalyticsService {
         Ok(map)
     }
 
+    async fn get_active_agents(&self) -> Result<ActiveAgents> {
+        let query = "SELECT agent_name, run_id, project_id, string::concat('', last_heartbeat) AS last_heartbeat FROM agent_connections WHERE status = 'connected' AND expires_at > time::now() ORDER BY last_heartbeat DESC";
+        let mut result = self.db.client.query(query).await?;
+        let rows: Vec<serde_json::Value> = take_json_values(&mut result, 0);
+
+        let agents: Vec<ActiveAgent> = rows
+            .iter()
+            .filter_map(|row| {
+                let text = |key: &str| row.get(key).and_then(|v| v.as_str()).map(String::from);
+                Some(ActiveAgent {
+                    agent_name: text("agent_name")?,
+                    run_id: text("run_id"),
+                    project_id: text("project_id"),
+                    last_heartbeat: text("last_heartbeat").unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        Ok(ActiveAgents {
+            count: agents.len(),
+            agents,
+        })
+    }
+
     async fn get_recent_activity(&self) -> Result<Vec<ActivityItem>> {
         let query = "SELECT string::concat('', id) AS id, string::concat('', type) AS type, string::concat('', created_at) AS created_at, string::concat('', updated_at) AS updated_at FROM objects ORDER BY created_at DESC LIMIT 10";
         let mut result = self.db.client.query(query).await?;
@@ -297,11 +672,14 @@ impl AnalyticsService {
             .unwrap_or("")
             .to_string();
 
+        let embedding_cache_hits = embedding_cache::total_hits(&self.db).await;
+
         Ok(IndexingStats {
             files_indexed,
             symbols_extracted,
             last_index_time,
             indexing_speed: String::new(),
+            embedding_cache_hits,
         })
     }
 
@@ -397,3 +775,156 @@ impl AnalyticsService {
         Ok(events)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    #[tokio::test]
+    async fn test_detailed_analytics_counts_project_objects_and_queries() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "CREATE objects SET type = 'FileChunk', project_id = 'amp', embedding = [0.1, 0.2], created_at = time::now();
+                 CREATE objects SET type = 'FileChunk', project_id = 'amp', created_at = time::now();
+                 CREATE objects SET type = 'decision', project_id = 'amp', created_at = time::now();
+                 CREATE objects SET type = 'decision', project_id = 'other', created_at = time::now();
+                 CREATE objects SET type = 'note', project_id = 'amp', created_at = '2020-01-01T00:00:00+00:00';
+                 CREATE cache_block SET scope_id = 'project:amp', sequence = 1;
+                 CREATE cache_block SET scope_id = 'project:amp', sequence = 2;
+                 CREATE cache_block SET scope_id = 'project:other', sequence = 1;",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let analytics = &state.analytics_service;
+        analytics.record_query(Some("amp"), 3, Some(0.9)).await;
+        analytics.record_query(Some("amp"), 5, Some(0.5)).await;
+        analytics.record_query(Some("amp"), 0, None).await;
+        analytics.record_query(Some("other"), 1, Some(0.1)).await;
+
+        // Query history is read back from the database, not process memory
+        let restarted = AnalyticsService::new(state.db.clone());
+        let detailed = restarted
+            .get_detailed(Some("amp"), AnalyticsWindow::Day)
+            .await
+            .unwrap();
+
+        assert_eq!(detailed.objects_by_type["filechunk"], 2);
+        assert_eq!(detailed.objects_by_type["decision"], 1);
+        assert_eq!(
+            (detailed.chunks.embedded, detailed.chunks.unembedded),
+            (1, 1)
+        );
+        assert_eq!(
+            detailed.cache_blocks_by_scope,
+            HashMap::from([("project:amp".to_string(), 2)])
+        );
+        assert_eq!(detailed.queries.total, 3);
+        assert!((detailed.queries.avg_top_score - 0.7).abs() < 1e-5);
+        assert!((detailed.queries.zero_result_rate - 1.0 / 3.0).abs() < 1e-5);
+        assert!((detailed.queries.per_hour - 3.0 / 24.0).abs() < 1e-5);
+
+        assert_eq!(detailed.series.len(), 24);
+        assert_eq!(detailed.bucket_seconds, 3600);
+        let current = detailed.series.last().unwrap();
+        assert_eq!(current.objects_created, 3);
+        // Older objects count toward totals but fall outside the series
+        assert_eq!(detailed.objects_by_type["note"], 1);
+        assert_eq!(
+            detailed
+                .series
+                .iter()
+                .map(|b| b.objects_created)
+                .sum::<i64>(),
+            3
+        );
+        assert_eq!(current.queries.total, 3);
+        assert!(detailed.series[..23].iter().all(|b| b.queries.total == 0));
+    }
+
+    #[tokio::test]
+    async fn test_detailed_analytics_reports_project_health() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "CREATE objects SET type = 'Symbol', kind = 'project', name = 'AMP', project_id = 'amp', updated_at = '2026-01-01T00:00:00Z';
+                 CREATE objects SET type = 'Symbol', kind = 'file', project_id = 'amp', updated_at = '2026-01-02T00:00:00Z';
+                 CREATE objects SET type = 'symbol', kind = 'function', project_id = 'amp';
+                 CREATE objects SET type = 'symbol', kind = 'struct', project_id = 'amp';
+                 CREATE objects SET type = 'FileChunk', project_id = 'amp', embedding = [0.1], updated_at = d'2026-01-03T00:00:00Z';
+                 CREATE objects SET type = 'FileChunk', project_id = 'amp', updated_at = '2026-01-02T12:00:00Z';
+                 CREATE objects SET type = 'Symbol', kind = 'project', name = 'Old', project_id = 'old';
+                 CREATE objects SET type = 'Symbol', kind = 'file', project_id = 'old', updated_at = '2025-06-01T00:00:00Z';",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let analytics = &state.analytics_service;
+
+        let detailed = analytics
+            .get_detailed(None, AnalyticsWindow::Day)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = detailed
+            .projects
+            .iter()
+            .map(|p| p.project_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["amp", "old"]);
+        let amp = &detailed.projects[0];
+        assert_eq!(amp.name.as_deref(), Some("AMP"));
+        assert_eq!((amp.files, amp.symbols), (1, 2));
+        assert_eq!((amp.chunks.embedded, amp.chunks.unembedded), (1, 1));
+        assert!(amp
+            .last_indexed
+            .as_deref()
+            .unwrap()
+            .starts_with("2026-01-03"));
+
+        let scoped = analytics
+            .get_detailed(Some("old"), AnalyticsWindow::Day)
+            .await
+            .unwrap();
+        assert_eq!(scoped.projects.len(), 1);
+        assert_eq!(scoped.projects[0].files, 1);
+        assert_eq!(scoped.projects[0].symbols, 0);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_avg_doc_lens_caches_per_project_means() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "CREATE objects SET type = 'symbol', project_id = 'amp', name = 'evict', content = 'drop old cache blocks';
+                 CREATE objects SET type = 'note', project_id = 'amp', title = 'Cache notes';
+                 CREATE objects SET type = 'note', project_id = 'other', content = 'one two three four five six';",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let analytics = &state.analytics_service;
+        assert_eq!(analytics.avg_doc_len("amp"), None);
+
+        assert_eq!(analytics.refresh_avg_doc_lens().await.unwrap(), 2);
+        assert!((analytics.avg_doc_len("amp").unwrap() - 3.5).abs() < 1e-9);
+        assert!((analytics.avg_doc_len("other").unwrap() - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_analytics_window_parse() {
+        assert_eq!(AnalyticsWindow::parse("7d"), Some(AnalyticsWindow::Week));
+        assert_eq!(AnalyticsWindow::Month.bucket_count(), 30);
+        assert_eq!(AnalyticsWindow::parse("1y"), None);
+    }
+}
@@ -8,6 +8,56 @@ use thiserror::Error;
 use crate::database::Database;
 use crate::services::embedding::EmbeddingService;
 
+/// Token counting for cache content; `kind` is the cache item kind
+/// (`fact`, `decision`, `snippet`, `warning`, ...).
+pub trait TokenEstimator: Send + Sync {
+    fn estimate(&self, content: &str, kind: &str) -> usize;
+}
+
+/// chars/4 for ASCII prose, chars/3 for code, one token per CJK character
+/// and two characters per token for other non-ASCII text.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeuristicTokenEstimator;
+
+impl TokenEstimator for HeuristicTokenEstimator {
+    fn estimate(&self, content: &str, kind: &str) -> usize {
+        let (mut ascii, mut symbols, mut cjk, mut other) = (0, 0, 0, 0);
+        for c in content.chars() {
+            if c.is_ascii() {
+                ascii += 1;
+                if c.is_ascii_punctuation() {
+                    symbols += 1;
+                }
+            } else if is_cjk(c) {
+                cjk += 1;
+            } else {
+                other += 1;
+            }
+        }
+        // Operators and brackets mostly tokenize on their own
+        let code = kind == "snippet" || symbols * 8 > ascii;
+        let chars_per_token = if code { 3 } else { 4 };
+        ascii / chars_per_token + cjk + other / 2
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF // Hiragana, Katakana
+            | 0x3400..=0x4DBF // CJK Extension A
+            | 0x4E00..=0x9FFF // CJK Unified Ideographs
+            | 0xAC00..=0xD7AF // Hangul syllables
+            | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+            | 0xFF00..=0xFFEF // Fullwidth forms
+    )
+}
+
+/// Estimate tokens for `content` with the default heuristic
+pub fn token_estimate(content: &str, kind: &str) -> usize {
+    HeuristicTokenEstimator.estimate(content, kind)
+}
+
 #[derive(Debug, Error)]
 pub enum CacheError {
     #[error("Database error: {0}")]
@@ -81,9 +131,9 @@ impl CacheService {
         }
     }
 
-    /// Estimate token count using chars/4 heuristic
+    /// Estimate token count of prose with [`token_estimate`]
     pub fn estimate_tokens(text: &str) -> usize {
-        text.len() / 4
+        token_estimate(text, "text")
     }
 
     /// Get or build memory pack for scope
@@ -605,6 +655,24 @@ mod tests {
         assert_eq!(CacheService::estimate_tokens(""), 0);
     }
 
+    #[test]
+    fn test_token_estimate_by_content() {
+        let prose = "The cache keeps a rolling window of recent blocks for each scope.";
+        assert_eq!(token_estimate(prose, "fact"), prose.len() / 4);
+
+        // Dense code is counted at three characters per token, even outside snippets
+        let code = "fn f(a:&[u8])->u8{a[0]^a[1]}";
+        assert_eq!(token_estimate(code, "snippet"), code.len() / 3);
+        assert_eq!(token_estimate(code, "fact"), code.len() / 3);
+        assert!(token_estimate(code, "fact") > code.len() / 4);
+
+        // Every CJK character is about a token, not a third of one
+        let cjk = "缓存保留最近的块";
+        assert_eq!(token_estimate(cjk, "fact"), 8);
+        assert!(token_estimate(cjk, "fact") > cjk.len() / 4);
+        assert_eq!(token_estimate("kühl café", "fact"), 7 / 4 + 2 / 2);
+    }
+
     #[test]
     fn test_cache_item_kind_serialization() {
         let kind = CacheItemKind::Fact;
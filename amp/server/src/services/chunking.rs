@@ -1,13 +1,31 @@
 #![allow(dead_code)]
+use super::codebase_parser::{CodebaseParser, ParsedSymbol};
+use super::word_windows::{estimate_token_count, line_at, word_windows, CHUNK_SIZE, OVERLAP_SIZE};
+use serde::Serialize;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tree_sitter::Node;
 
-#[derive(Debug, Clone)]
+/// Semantic chunks above this many estimated tokens are split again at
+/// nested definition or block boundaries.
+const MAX_SEMANTIC_CHUNK_TOKENS: usize = 2000;
+
+/// Runs of adjacent symbols smaller than this are packed into one chunk, so
+/// one-line constants do not each become a chunk.
+const MIN_SYMBOL_CHUNK_TOKENS: usize = 60;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ChunkData {
     pub content: String,
     pub start_line: u32,
     pub end_line: u32,
     pub token_count: u32,
     pub hash: String,
+    /// Names of the definitions the chunk covers or sits inside, outermost first
+    pub symbols: Vec<String>,
+    /// First parsed symbol the chunk holds, for labels like "function parse_file"
+    pub symbol_name: Option<String>,
+    pub symbol_kind: Option<String>,
 }
 
 pub struct ChunkingService {
@@ -18,8 +36,8 @@ pub struct ChunkingService {
 impl ChunkingService {
     pub fn new() -> Self {
         Self {
-            chunk_size: 500,
-            overlap_size: 100, // 100-token overlap for better retrieval
+            chunk_size: CHUNK_SIZE,
+            overlap_size: OVERLAP_SIZE,
         }
     }
 
@@ -37,50 +55,370 @@ impl ChunkingService {
             return vec![];
         }
 
-        let tokens: Vec<&str> = content.split_whitespace().collect();
-        let total_tokens = self.estimate_token_count(content);
-
-        if total_tokens <= self.chunk_size {
+        let Some(windows) = word_windows(content, self.chunk_size, self.overlap_size) else {
             return vec![ChunkData {
                 content: content.to_string(),
                 start_line: 1,
                 end_line: lines.len() as u32,
-                token_count: total_tokens as u32,
+                token_count: self.estimate_token_count(content) as u32,
                 hash: self.compute_hash(content),
+                symbols: Vec::new(),
+                symbol_name: None,
+                symbol_kind: None,
             }];
+        };
+
+        windows
+            .into_iter()
+            .map(|window| {
+                // Slice the source so the chunk keeps its layout and its line numbers are exact
+                let chunk_content = &content[window.start..window.end];
+                ChunkData {
+                    content: chunk_content.to_string(),
+                    start_line: line_at(content, window.start),
+                    end_line: line_at(content, window.end),
+                    token_count: window.words as u32,
+                    hash: self.compute_hash(chunk_content),
+                    symbols: Vec::new(),
+                    symbol_name: None,
+                    symbol_kind: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Chunk along the syntax tree: consecutive top-level definitions are
+    /// packed up to `chunk_size` tokens without ever cutting through one, and
+    /// a definition over `MAX_SEMANTIC_CHUNK_TOKENS` is split again at its
+    /// nested boundaries. Falls back to `chunk_file` when the language has no
+    /// grammar or the file does not parse cleanly.
+    pub fn chunk_file_semantic(
+        &self,
+        parser: &CodebaseParser,
+        content: &str,
+        language: &str,
+    ) -> Vec<ChunkData> {
+        let Some(tree) = parser.parse_tree(content, language) else {
+            return self.chunk_file(content, language);
+        };
+        let root = tree.root_node();
+        if root.has_error() {
+            return self.chunk_file(content, language);
         }
 
         let mut chunks = Vec::new();
-        let mut start_idx = 0;
+        self.split_span(content, language, root, 0, content.len(), &[], &mut chunks);
+        chunks
+    }
+
+    /// Chunk `content[start..end]` at the boundaries between `parent`'s
+    /// children. `context` names the definitions the span sits inside.
+    #[allow(clippy::too_many_arguments)]
+    fn split_span(
+        &self,
+        content: &str,
+        language: &str,
+        parent: Node,
+        start: usize,
+        end: usize,
+        context: &[String],
+        out: &mut Vec<ChunkData>,
+    ) {
+        let units = sibling_units(parent, content);
+        if units.is_empty() {
+            self.push_word_chunks(content, language, start, end, context, out);
+            return;
+        }
 
-        while start_idx < tokens.len() {
-            let end_idx = (start_idx + self.chunk_size).min(tokens.len());
-            let chunk_tokens = &tokens[start_idx..end_idx];
-            let chunk_content = chunk_tokens.join(" ");
+        // (start, end, symbol names) of the chunk being packed
+        let mut pending: Option<(usize, usize, Vec<String>)> = None;
+        for (i, &(unit_start, node)) in units.iter().enumerate() {
+            let unit_start = if i == 0 { start } else { unit_start };
+            let unit_end = units.get(i + 1).map_or(end, |&(next, _)| next);
+            let name = definition_name(node, content);
 
-            let (start_line, end_line) =
-                self.estimate_line_range(content, start_idx, end_idx, &tokens);
+            if self.estimate_token_count(&content[unit_start..unit_end]) > MAX_SEMANTIC_CHUNK_TOKENS
+            {
+                if let Some((from, to, names)) = pending.take() {
+                    self.push_span(content, from, to, context, names, out);
+                }
+                let mut nested = context.to_vec();
+                nested.extend(name);
+                let body = node.child_by_field_name("body").unwrap_or(node);
+                if body.named_child_count() > 0 {
+                    self.split_span(content, language, body, unit_start, unit_end, &nested, out);
+                } else {
+                    self.push_word_chunks(content, language, unit_start, unit_end, &nested, out);
+                }
+                continue;
+            }
 
-            chunks.push(ChunkData {
-                content: chunk_content.clone(),
-                start_line,
-                end_line,
-                token_count: chunk_tokens.len() as u32,
-                hash: self.compute_hash(&chunk_content),
-            });
+            match &mut pending {
+                Some((from, to, names))
+                    if self.estimate_token_count(&content[*from..unit_end]) <= self.chunk_size =>
+                {
+                    *to = unit_end;
+                    names.extend(name);
+                }
+                _ => {
+                    if let Some((from, to, names)) = pending.take() {
+                        self.push_span(content, from, to, context, names, out);
+                    }
+                    pending = Some((unit_start, unit_end, name.into_iter().collect()));
+                }
+            }
+        }
+        if let Some((from, to, names)) = pending {
+            self.push_span(content, from, to, context, names, out);
+        }
+    }
 
-            start_idx = if end_idx < tokens.len() {
-                end_idx - self.overlap_size
-            } else {
-                break;
-            };
+    /// Chunk along parsed symbols: one chunk per top-level definition, with
+    /// tiny neighbours packed together and a definition over
+    /// `MAX_SEMANTIC_CHUNK_TOKENS` split at the symbols nested in it, or into
+    /// word windows when it has none. Text between definitions stays with the
+    /// one that follows it. Falls back to `chunk_file` without usable symbols.
+    pub fn chunk_file_by_symbols(
+        &self,
+        parser: &CodebaseParser,
+        content: &str,
+        language: &str,
+        symbols: &[ParsedSymbol],
+    ) -> Vec<ChunkData> {
+        let spans = match parser.parse_tree(content, language) {
+            Some(tree) if !symbols.is_empty() => symbol_spans(tree.root_node(), content, symbols),
+            _ => Vec::new(),
+        };
+        if spans.is_empty() {
+            return self.chunk_file(content, language);
         }
 
+        let mut chunks = Vec::new();
+        self.split_symbols(
+            content,
+            language,
+            0,
+            content.len(),
+            &spans,
+            &[],
+            &mut chunks,
+        );
         chunks
     }
 
+    /// Chunk `content[start..end]` at the boundaries of the outermost of
+    /// `spans` (sorted by start) that fall inside it.
+    #[allow(clippy::too_many_arguments)]
+    fn split_symbols(
+        &self,
+        content: &str,
+        language: &str,
+        start: usize,
+        end: usize,
+        spans: &[SymbolSpan],
+        context: &[String],
+        out: &mut Vec<ChunkData>,
+    ) {
+        let mut top: Vec<&SymbolSpan> = Vec::new();
+        for span in spans {
+            if span.start < start || span.end > end {
+                continue;
+            }
+            if top.last().is_none_or(|prev| span.start >= prev.end) {
+                top.push(span);
+            }
+        }
+        if top.is_empty() {
+            self.push_word_chunks(content, language, start, end, context, out);
+            return;
+        }
+
+        // A unit starts on the line after the previous definition ends
+        let boundaries: Vec<usize> = top
+            .iter()
+            .enumerate()
+            .map(|(i, span)| {
+                if i == 0 {
+                    return start;
+                }
+                let prev_end = top[i - 1].end;
+                let after_prev = content[prev_end..]
+                    .find('\n')
+                    .map_or(end, |offset| prev_end + offset + 1);
+                after_prev.min(span.start)
+            })
+            .collect();
+
+        let mut pending: Option<(usize, usize, Vec<&SymbolSpan>)> = None;
+        for (i, &span) in top.iter().enumerate() {
+            let unit_start = boundaries[i];
+            let unit_end = boundaries.get(i + 1).copied().unwrap_or(end);
+            let unit_tokens = self.estimate_token_count(&content[unit_start..unit_end]);
+
+            if unit_tokens > MAX_SEMANTIC_CHUNK_TOKENS {
+                if let Some((from, to, units)) = pending.take() {
+                    self.push_symbol_span(content, from, to, context, &units, out);
+                }
+                let mut nested = context.to_vec();
+                nested.push(span.name.clone());
+                let inner: Vec<SymbolSpan> = spans
+                    .iter()
+                    .filter(|s| {
+                        s.start >= span.start
+                            && s.end <= span.end
+                            && (s.start, s.end) != (span.start, span.end)
+                    })
+                    .cloned()
+                    .collect();
+                let before = out.len();
+                self.split_symbols(
+                    content, language, unit_start, unit_end, &inner, &nested, out,
+                );
+                for chunk in &mut out[before..] {
+                    if chunk.symbol_name.is_none() {
+                        chunk.symbol_name = Some(span.name.clone());
+                        chunk.symbol_kind = Some(span.kind.clone());
+                    }
+                }
+                continue;
+            }
+
+            match &mut pending {
+                Some((from, to, units))
+                    if unit_tokens < MIN_SYMBOL_CHUNK_TOKENS
+                        && self.estimate_token_count(&content[*from..*to])
+                            < MIN_SYMBOL_CHUNK_TOKENS
+                        && self.estimate_token_count(&content[*from..unit_end])
+                            <= self.chunk_size =>
+                {
+                    *to = unit_end;
+                    units.push(span);
+                }
+                _ => {
+                    if let Some((from, to, units)) = pending.take() {
+                        self.push_symbol_span(content, from, to, context, &units, out);
+                    }
+                    pending = Some((unit_start, unit_end, vec![span]));
+                }
+            }
+        }
+        if let Some((from, to, units)) = pending {
+            self.push_symbol_span(content, from, to, context, &units, out);
+        }
+    }
+
+    fn push_symbol_span(
+        &self,
+        content: &str,
+        start: usize,
+        end: usize,
+        context: &[String],
+        units: &[&SymbolSpan],
+        out: &mut Vec<ChunkData>,
+    ) {
+        let names = units.iter().map(|s| s.name.clone()).collect();
+        let before = out.len();
+        self.push_span(content, start, end, context, names, out);
+        if let (Some(chunk), Some(first)) = (out.get_mut(before), units.first()) {
+            chunk.symbol_name = Some(first.name.clone());
+            chunk.symbol_kind = Some(first.kind.clone());
+        }
+    }
+
+    fn push_span(
+        &self,
+        content: &str,
+        start: usize,
+        end: usize,
+        context: &[String],
+        names: Vec<String>,
+        out: &mut Vec<ChunkData>,
+    ) {
+        let text = content[start..end]
+            .trim_start_matches(['\n', '\r'])
+            .trim_end();
+        if text.trim().is_empty() {
+            return;
+        }
+        let start = end - content[start..end].trim_start_matches(['\n', '\r']).len();
+
+        let mut symbols = context.to_vec();
+        for name in names {
+            if !symbols.contains(&name) {
+                symbols.push(name);
+            }
+        }
+        out.push(ChunkData {
+            content: text.to_string(),
+            start_line: line_at(content, start),
+            end_line: line_at(content, start + text.len()),
+            token_count: self.estimate_token_count(text) as u32,
+            hash: self.compute_hash(text),
+            symbols,
+            symbol_name: None,
+            symbol_kind: None,
+        });
+    }
+
+    /// Word-window fallback for a span with no syntax boundaries to cut at.
+    fn push_word_chunks(
+        &self,
+        content: &str,
+        language: &str,
+        start: usize,
+        end: usize,
+        context: &[String],
+        out: &mut Vec<ChunkData>,
+    ) {
+        let line_offset = line_at(content, start) - 1;
+        for mut chunk in self.chunk_file(&content[start..end], language) {
+            chunk.start_line += line_offset;
+            chunk.end_line += line_offset;
+            chunk.symbols = context.to_vec();
+            out.push(chunk);
+        }
+    }
+
+    /// Split `new` into chunks that need embedding and hashes already stored in `old`.
+    /// Matching is by content hash with multiplicity, so repeated content is counted once per copy.
+    pub fn diff_chunks(old: &[ChunkData], new: &[ChunkData]) -> (Vec<ChunkData>, Vec<String>) {
+        let mut remaining: HashMap<&str, usize> = HashMap::new();
+        for chunk in old {
+            *remaining.entry(chunk.hash.as_str()).or_insert(0) += 1;
+        }
+
+        let mut changed = Vec::new();
+        let mut unchanged = Vec::new();
+        for chunk in new {
+            match remaining.get_mut(chunk.hash.as_str()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    unchanged.push(chunk.hash.clone());
+                }
+                _ => changed.push(chunk.clone()),
+            }
+        }
+
+        (changed, unchanged)
+    }
+
+    /// Stable chunk id derived from the owning file, position, and content.
+    /// Re-chunking unchanged content yields the same ids across re-index.
+    pub fn chunk_id(file_id: &str, chunk_index: usize, content_hash: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(file_id.as_bytes());
+        hasher.update(b":");
+        hasher.update(chunk_index.to_string().as_bytes());
+        hasher.update(b":");
+        hasher.update(content_hash.as_bytes());
+        let digest = hasher.finalize();
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+        uuid::Uuid::from_bytes(bytes).to_string()
+    }
+
     pub fn estimate_token_count(&self, text: &str) -> usize {
-        text.split_whitespace().count() * 13 / 10
+        estimate_token_count(text)
     }
 
     fn compute_hash(&self, content: &str) -> String {
@@ -88,41 +426,125 @@ impl ChunkingService {
         hasher.update(content.as_bytes());
         format!("{:x}", hasher.finalize())
     }
+}
 
-    fn estimate_line_range(
-        &self,
-        content: &str,
-        start_token: usize,
-        end_token: usize,
-        tokens: &[&str],
-    ) -> (u32, u32) {
-        let start_pos = tokens[..start_token]
-            .iter()
-            .map(|t| t.len() + 1)
-            .sum::<usize>();
-        let end_pos = tokens[..end_token]
-            .iter()
-            .map(|t| t.len() + 1)
-            .sum::<usize>();
+/// Children of `parent` that must stay whole, as (start byte, node). Comments,
+/// attributes and decorators start the unit of the node they precede, and a
+/// unit starts at the beginning of its line when only indentation precedes it.
+fn sibling_units<'tree>(parent: Node<'tree>, content: &str) -> Vec<(usize, Node<'tree>)> {
+    let mut units = Vec::new();
+    let mut preamble_start = None;
+    let mut cursor = parent.walk();
+    for child in parent.named_children(&mut cursor) {
+        let kind = child.kind();
+        if kind.contains("comment") || kind.contains("attribute") || kind == "decorator" {
+            preamble_start.get_or_insert(child.start_byte());
+            continue;
+        }
+        let start = preamble_start.take().unwrap_or(child.start_byte());
+        let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+        let start = if content[line_start..start].trim().is_empty() {
+            line_start
+        } else {
+            start
+        };
+        units.push((start, child));
+    }
+    units
+}
 
-        let start_pos = clamp_char_boundary(content, start_pos.min(content.len()));
-        let end_pos = clamp_char_boundary(content, end_pos.min(content.len()));
+/// Byte range of a parsed symbol's whole definition.
+#[derive(Debug, Clone)]
+struct SymbolSpan {
+    name: String,
+    kind: String,
+    start: usize,
+    end: usize,
+}
 
-        let start_line = content[..start_pos]
-            .lines()
-            .count()
-            .max(1) as u32;
-        let end_line = content[..end_pos].lines().count().max(1) as u32;
+/// Definition ranges for `symbols`, sorted by start with enclosing spans first.
+/// Parsed symbols only record their name's range, so the definition is the
+/// name's parent, widened to a wrapping decorator or export.
+fn symbol_spans(root: Node, content: &str, symbols: &[ParsedSymbol]) -> Vec<SymbolSpan> {
+    let mut spans: Vec<SymbolSpan> = symbols
+        .iter()
+        .filter(|s| s.start_byte < s.end_byte && s.end_byte <= content.len())
+        .filter_map(|symbol| {
+            let name = root.descendant_for_byte_range(symbol.start_byte, symbol.end_byte)?;
+            let mut definition = name.parent().filter(|p| p.kind() != root.kind())?;
+            while let Some(parent) = definition.parent() {
+                if !matches!(parent.kind(), "decorated_definition" | "export_statement") {
+                    break;
+                }
+                definition = parent;
+            }
+            Some(SymbolSpan {
+                name: symbol.name.clone(),
+                kind: symbol.symbol_type.clone(),
+                start: definition.start_byte(),
+                end: definition.end_byte(),
+            })
+        })
+        .collect();
+    spans.sort_by_key(|s| (s.start, std::cmp::Reverse(s.end)));
+    spans
+}
 
-        (start_line, end_line)
+/// Named definitions in `content` as (name, first line, last line), in
+/// document order. Decorators and `export` wrappers count as part of the
+/// definition. Empty when the language has no grammar.
+pub fn definition_lines(
+    parser: &CodebaseParser,
+    content: &str,
+    language: &str,
+) -> Vec<(String, u32, u32)> {
+    let Some(tree) = parser.parse_tree(content, language) else {
+        return Vec::new();
+    };
+    let mut definitions = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let kind = node.kind();
+        let is_definition = kind.ends_with("_item")
+            || kind.ends_with("_definition")
+            || kind.ends_with("_declaration")
+            || kind == "type_spec";
+        if is_definition && kind != "decorated_definition" {
+            if let Some(name) = definition_name(node, content) {
+                let mut outer = node;
+                while let Some(parent) = outer.parent() {
+                    if !matches!(parent.kind(), "decorated_definition" | "export_statement") {
+                        break;
+                    }
+                    outer = parent;
+                }
+                definitions.push((
+                    name,
+                    line_at(content, outer.start_byte()),
+                    line_at(content, outer.end_byte()),
+                ));
+            }
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
     }
+    definitions
 }
 
-fn clamp_char_boundary(content: &str, mut idx: usize) -> usize {
-    while idx > 0 && !content.is_char_boundary(idx) {
-        idx -= 1;
-    }
-    idx
+/// Name of the definition at `node`, if it is one.
+fn definition_name(node: Node, content: &str) -> Option<String> {
+    let name = match node.kind() {
+        "impl_item" => node.child_by_field_name("type"),
+        "decorated_definition" => {
+            return definition_name(node.child_by_field_name("definition")?, content)
+        }
+        "export_statement" => {
+            return definition_name(node.child_by_field_name("declaration")?, content)
+        }
+        _ => node.child_by_field_name("name"),
+    }?;
+    name.utf8_text(content.as_bytes()).ok().map(str::to_string)
 }
 
 impl Default for ChunkingService {
@@ -130,3 +552,397 @@ impl Default for ChunkingService {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_ids(service: &ChunkingService, file_id: &str, content: &str) -> Vec<String> {
+        service
+            .chunk_file(content, "rust")
+            .iter()
+            .enumerate()
+            .map(|(idx, chunk)| ChunkingService::chunk_id(file_id, idx, &chunk.hash))
+            .collect()
+    }
+
+    #[test]
+    fn test_rechunking_identical_content_yields_identical_ids() {
+        let service = ChunkingService::with_settings(50, 10);
+        let content = (0..400)
+            .map(|i| format!("let value_{} = {};", i, i))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let first = chunk_ids(&service, "file-abc", &content);
+        let second = chunk_ids(&service, "file-abc", &content);
+        assert!(first.len() > 1);
+        assert_eq!(first, second);
+
+        let other_file = chunk_ids(&service, "file-def", &content);
+        assert_ne!(first, other_file);
+    }
+
+    #[test]
+    fn test_chunk_id_changes_with_content() {
+        let a = ChunkingService::chunk_id("file-abc", 0, "hash-a");
+        let b = ChunkingService::chunk_id("file-abc", 0, "hash-b");
+        let c = ChunkingService::chunk_id("file-abc", 1, "hash-a");
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert!(uuid::Uuid::parse_str(&a).is_ok());
+    }
+
+    #[test]
+    fn test_word_window_chunks_report_exact_lines() {
+        let service = ChunkingService::with_settings(50, 10);
+        // Ten words a line, with indentation and blank lines the old estimate ignored
+        let content: String = (0..20)
+            .map(|line| {
+                let words: Vec<String> = (0..10).map(|w| format!("w{}_{}", line, w)).collect();
+                format!("        {}\n\n", words.join("  "))
+            })
+            .collect();
+
+        let chunks = service.chunk_file(&content, "text");
+        assert_eq!(chunks.len(), 5);
+        for chunk in &chunks {
+            let first = chunk.content.split_whitespace().next().unwrap();
+            let last = chunk.content.split_whitespace().last().unwrap();
+            let line_of =
+                |word: &str| content.lines().position(|l| l.contains(word)).unwrap() as u32 + 1;
+            assert_eq!(
+                (chunk.start_line, chunk.end_line),
+                (line_of(first), line_of(last))
+            );
+            assert_eq!(
+                chunk.token_count,
+                chunk.content.split_whitespace().count() as u32
+            );
+        }
+        // Chunks are slices of the source, layout included
+        assert!(chunks[0].content.starts_with("w0_0  w0_1"));
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (9, 17));
+    }
+
+    #[test]
+    fn test_default_word_windows_match_the_cli_fallback() {
+        // The CLI's fallback chunker pins the same windows for the same input
+        // in `commands::index::tests`; both must change together
+        let content = (0..1200)
+            .map(|i| format!("w{}", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let bounds: Vec<(String, String)> = ChunkingService::new()
+            .chunk_file(&content, "text")
+            .iter()
+            .map(|chunk| {
+                let mut words = chunk.content.split_whitespace();
+                let first = words.next().unwrap().to_string();
+                (first, words.last().unwrap().to_string())
+            })
+            .collect();
+        let expected = [("w0", "w499"), ("w400", "w899"), ("w800", "w1199")];
+        assert_eq!(
+            bounds,
+            expected.map(|(a, b)| (a.to_string(), b.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_word_window_chunk_hashes_are_pinned() {
+        // Stored hashes decide what gets re-embedded, so changing how windows
+        // are sliced must show up here rather than as a silent full re-embed
+        let service = ChunkingService::with_settings(20, 5);
+        let content: String = (0..6)
+            .map(|line| format!("    fn step_{}(a: u8) {{ a + {} }}\n\n", line, line))
+            .collect();
+
+        let hashes: Vec<String> = service
+            .chunk_file(&content, "rust")
+            .into_iter()
+            .map(|chunk| chunk.hash)
+            .collect();
+        assert_eq!(
+            hashes,
+            vec![
+                "c64262892a4425055fcd6a9253c1a1f89d6ab56e9e09328c1ec71bde5aa007fb",
+                "45fb05cde5120f6a61c60756586dc9933ba47348e1c9ab32663516f25ed9f5a6",
+                "6eb2e60284e7552eab25816fb3c3be73565acf9220db3fdb48e87a022182afb5",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_chunks_returns_only_changed() {
+        let service = ChunkingService::with_settings(50, 10);
+        let words: Vec<String> = (0..150).map(|i| format!("w{}", i)).collect();
+        let before = service.chunk_file(&words.join(" "), "text");
+
+        let mut edited = words.clone();
+        edited[140] = "changed".to_string();
+        let after = service.chunk_file(&edited.join(" "), "text");
+        assert_eq!(before.len(), after.len());
+
+        let (changed, unchanged) = ChunkingService::diff_chunks(&before, &after);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].hash, after.last().unwrap().hash);
+        assert_eq!(unchanged.len(), after.len() - 1);
+    }
+
+    #[test]
+    fn test_diff_chunks_counts_repeated_content() {
+        let chunk = |content: &str| ChunkData {
+            content: content.to_string(),
+            start_line: 1,
+            end_line: 1,
+            token_count: 1,
+            hash: content.to_string(),
+            symbols: Vec::new(),
+            symbol_name: None,
+            symbol_kind: None,
+        };
+        let old = vec![chunk("a"), chunk("b")];
+        let new = vec![chunk("a"), chunk("a"), chunk("b")];
+
+        let (changed, unchanged) = ChunkingService::diff_chunks(&old, &new);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].hash, "a");
+        assert_eq!(unchanged, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    /// Six small functions, each with its own vocabulary.
+    fn rust_source() -> String {
+        let topics = [
+            ("parse_header", "header bytes magic version checksum"),
+            ("render_table", "table rows columns width border"),
+            ("send_invoice", "invoice customer amount currency receipt"),
+            ("rotate_logs", "logs archive compress retention disk"),
+            ("score_match", "match tokens weight boost penalty"),
+            ("load_config", "config path environment defaults override"),
+        ];
+        topics
+            .iter()
+            .map(|(name, words)| {
+                let body: Vec<String> = words
+                    .split(' ')
+                    .enumerate()
+                    .map(|(i, word)| format!("    let {}_{} = \"{} {}\";", word, i, word, name))
+                    .collect();
+                format!(
+                    "/// Handles {}.\npub fn {}() {{\n{}\n}}\n",
+                    words,
+                    name,
+                    body.join("\n")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_semantic_chunks_keep_definitions_whole() {
+        let parser = CodebaseParser::new().unwrap();
+        let service = ChunkingService::with_settings(60, 10);
+        let source = rust_source();
+
+        let chunks = service.chunk_file_semantic(&parser, &source, "rust");
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(!chunk.symbols.is_empty());
+            assert_eq!(
+                chunk.content.matches('{').count(),
+                chunk.content.matches('}').count()
+            );
+            let lines: Vec<&str> = source.lines().collect();
+            assert_eq!(
+                lines[chunk.start_line as usize - 1..chunk.end_line as usize].join("\n"),
+                chunk.content
+            );
+        }
+        let symbols: Vec<&str> = chunks
+            .iter()
+            .flat_map(|c| c.symbols.iter().map(String::as_str))
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![
+                "parse_header",
+                "render_table",
+                "send_invoice",
+                "rotate_logs",
+                "score_match",
+                "load_config"
+            ]
+        );
+        assert!(chunks[0].content.starts_with("/// Handles header"));
+    }
+
+    #[test]
+    fn test_semantic_chunking_splits_oversized_definitions_and_falls_back() {
+        let parser = CodebaseParser::new().unwrap();
+        let service = ChunkingService::new();
+        let methods: Vec<String> = (0..40)
+            .map(|i| {
+                let body = (0..40)
+                    .map(|j| format!("        let v{} = {};", j, j))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("    fn method_{}(&self) {{\n{}\n    }}", i, body)
+            })
+            .collect();
+        let source = format!("struct Big;\n\nimpl Big {{\n{}\n}}\n", methods.join("\n\n"));
+
+        let chunks = service.chunk_file_semantic(&parser, &source, "rust");
+        assert!(chunks.len() > 1);
+        assert!(chunks
+            .iter()
+            .all(|c| (c.token_count as usize) <= MAX_SEMANTIC_CHUNK_TOKENS));
+        let nested: Vec<&ChunkData> = chunks
+            .iter()
+            .filter(|c| c.content.contains("fn method_"))
+            .collect();
+        assert!(nested.len() > 1);
+        assert!(nested
+            .iter()
+            .all(|c| c.symbols[0] == "Big" && c.symbols[1].starts_with("method_")));
+        assert!(nested[0].content.contains("impl Big {"));
+
+        // No grammar for plain text, and a broken Rust file: both fall back to word windows
+        let text = "alpha beta gamma";
+        let prose = service.chunk_file_semantic(&parser, text, "text");
+        assert_eq!(prose.len(), 1);
+        assert!(prose[0].symbols.is_empty());
+        let broken = service.chunk_file_semantic(&parser, "fn broken( {", "rust");
+        assert_eq!(broken[0].content, "fn broken( {");
+    }
+
+    /// Retrieval benchmark: ask for each function by its vocabulary, take the
+    /// best chunk by term overlap, and count a hit when that chunk holds the
+    /// whole function. Word windows cut functions apart; AST chunks do not.
+    #[test]
+    fn test_semantic_chunks_retrieve_whole_functions_better_than_word_split() {
+        let parser = CodebaseParser::new().unwrap();
+        let service = ChunkingService::with_settings(40, 8);
+        let source = rust_source();
+        let normalize = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let functions: Vec<String> = source.split("\n\n").map(normalize).collect();
+        let hits = |chunks: &[ChunkData]| {
+            functions
+                .iter()
+                .filter(|function| {
+                    let terms: Vec<&str> = function.split(' ').collect();
+                    let best = chunks
+                        .iter()
+                        .map(|c| normalize(&c.content))
+                        .max_by_key(|c| terms.iter().filter(|t| c.contains(*t)).count())
+                        .unwrap();
+                    best.contains(function.as_str())
+                })
+                .count()
+        };
+
+        let semantic = hits(&service.chunk_file_semantic(&parser, &source, "rust"));
+        let word_split = hits(&service.chunk_file(&source, "rust"));
+        println!(
+            "whole-function retrieval: semantic {}/{}, word split {}/{}",
+            semantic,
+            functions.len(),
+            word_split,
+            functions.len()
+        );
+        assert_eq!(semantic, functions.len());
+        assert!(semantic > word_split);
+    }
+
+    fn parsed_symbols(parser: &CodebaseParser, source: &str) -> Vec<ParsedSymbol> {
+        let (file_log, _, _) = parser
+            .parse_content_incremental(
+                std::path::Path::new("src/lib.rs"),
+                "rust",
+                source.to_string(),
+                None,
+            )
+            .unwrap();
+        file_log.symbols
+    }
+
+    #[test]
+    fn test_symbol_chunks_follow_top_level_symbols() {
+        let parser = CodebaseParser::new().unwrap();
+        let service = ChunkingService::new();
+        let body = |word: &str| {
+            (0..20)
+                .map(|i| format!("    let {}_{} = {};", word, i, i))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let source = format!(
+            "use std::fmt;\n\nconst A: u8 = 1;\nconst B: u8 = 2;\n\n/// Parses a file.\nfn parse_file() {{\n{}\n}}\n\nfn render() {{\n{}\n}}\n",
+            body("parsed"),
+            body("rendered")
+        );
+        let symbols = parsed_symbols(&parser, &source);
+
+        let chunks = service.chunk_file_by_symbols(&parser, &source, "rust", &symbols);
+        let labels: Vec<(Option<&str>, Option<&str>)> = chunks
+            .iter()
+            .map(|c| (c.symbol_kind.as_deref(), c.symbol_name.as_deref()))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                (Some("constant"), Some("A")),
+                (Some("function"), Some("parse_file")),
+                (Some("function"), Some("render")),
+            ],
+            "{:#?}",
+            chunks
+        );
+        // Tiny constants share a chunk; doc comments stay with their function
+        assert_eq!(chunks[0].symbols, vec!["A", "B"]);
+        assert!(chunks[0].content.starts_with("use std::fmt;"));
+        assert!(chunks[1]
+            .content
+            .starts_with("/// Parses a file.\nfn parse_file() {"));
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (30, 51));
+
+        assert_eq!(
+            service.chunk_file_by_symbols(&parser, "alpha beta gamma", "text", &[])[0].content,
+            "alpha beta gamma"
+        );
+    }
+
+    #[test]
+    fn test_symbol_chunks_split_oversized_symbols_at_nested_symbols() {
+        let parser = CodebaseParser::new().unwrap();
+        let service = ChunkingService::new();
+        let methods: Vec<String> = (0..40)
+            .map(|i| {
+                let body = (0..40)
+                    .map(|j| format!("        let v{} = {};", j, j))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("    fn method_{}(&self) {{\n{}\n    }}", i, body)
+            })
+            .collect();
+        let source = format!("struct Big;\n\nimpl Big {{\n{}\n}}\n", methods.join("\n\n"));
+        let symbols = parsed_symbols(&parser, &source);
+
+        let chunks = service.chunk_file_by_symbols(&parser, &source, "rust", &symbols);
+        assert!(chunks.len() > 2);
+        assert!(chunks
+            .iter()
+            .all(|c| (c.token_count as usize) <= MAX_SEMANTIC_CHUNK_TOKENS));
+        let methods: Vec<&ChunkData> = chunks
+            .iter()
+            .filter(|c| c.content.contains("fn method_"))
+            .collect();
+        assert!(methods[0].content.contains("impl Big {"));
+        assert!(methods
+            .iter()
+            .all(|c| c.symbol_name.as_deref().unwrap().starts_with("method_")));
+    }
+}
@@ -1,10 +1,13 @@
 #![allow(dead_code)]
+use crate::services::fallback_parser;
+use crate::services::git_blame::SymbolBlame;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::ops::Range;
 use std::path::Path;
-use tree_sitter::{Language, Parser, Query, QueryCursor, Tree};
+use tree_sitter::{InputEdit, Language, Node, Parser, Point, Query, QueryCursor, Tree};
 use walkdir::WalkDir;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +20,10 @@ pub struct ParsedSymbol {
     pub end_byte: usize,
     pub file_path: String,
     pub language: String,
+    /// Access level where the grammar exposes one: public, private, crate,
+    /// module, or protected. `None` for languages without visibility capture.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +37,14 @@ pub struct FileLog {
     pub recent_changes: Vec<String>,
     pub linked_decisions: Vec<String>,
     pub notes: Vec<String>,
+    /// Last commit per symbol, only filled in when blame is requested for a
+    /// file in a Git work tree
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blame: Vec<SymbolBlame>,
+    /// `(caller, callee)` names for calls made inside named functions, for
+    /// languages with a calls query
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,12 +64,234 @@ pub struct CodebaseParser {
     c_language: Language,
     cpp_language: Language,
     ruby_language: Language,
+    kotlin_language: Language,
 }
 
 struct CodeQueries {
     symbols: Query,
     imports: Query,
     exports: Query,
+    /// Callee names captured as `@call.name`; `None` where calls are not extracted
+    calls: Option<Query>,
+}
+
+/// Captured texts with the byte offset of each capture.
+type PositionedTexts = Vec<(usize, String)>;
+
+/// Tree and extraction results from the last parse of a file, handed back to
+/// [`CodebaseParser::parse_content_incremental`] on the next edit.
+pub struct ParsedFile {
+    content: String,
+    tree: Tree,
+    symbols: Vec<ParsedSymbol>,
+    imports: PositionedTexts,
+    exports: PositionedTexts,
+}
+
+impl ParsedFile {
+    fn dependencies(&self) -> FileDependencies {
+        FileDependencies {
+            imports: self.imports.iter().map(|(_, text)| text.clone()).collect(),
+            exports: self.exports.iter().map(|(_, text)| text.clone()).collect(),
+        }
+    }
+}
+
+/// How a parse changed a file's symbols, matched by type and name. Without a
+/// previous parse every symbol counts as added.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SymbolChanges {
+    pub added: usize,
+    pub removed: usize,
+    /// Symbols present before and after whose definition overlaps the edit
+    pub changed: usize,
+}
+
+impl SymbolChanges {
+    fn between(
+        old_counts: &HashMap<(String, String), usize>,
+        symbols: &[ParsedSymbol],
+        changed: usize,
+    ) -> Self {
+        let new_counts = symbol_counts(symbols);
+        let surplus = |from: &HashMap<(String, String), usize>,
+                       other: &HashMap<(String, String), usize>| {
+            from.iter()
+                .map(|(key, count)| count.saturating_sub(other.get(key).copied().unwrap_or(0)))
+                .sum()
+        };
+        Self {
+            added: surplus(&new_counts, old_counts),
+            removed: surplus(old_counts, &new_counts),
+            changed,
+        }
+    }
+}
+
+fn symbol_key(symbol: &ParsedSymbol) -> (String, String) {
+    (symbol.symbol_type.clone(), symbol.name.clone())
+}
+
+fn symbol_counts(symbols: &[ParsedSymbol]) -> HashMap<(String, String), usize> {
+    let mut counts = HashMap::new();
+    for symbol in symbols {
+        *counts.entry(symbol_key(symbol)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Re-queried span of an incremental parse: `start..end` in the new content,
+/// `start..old_end` in the old one.
+struct Region {
+    start: usize,
+    end: usize,
+    old_end: usize,
+    byte_delta: isize,
+}
+
+impl Region {
+    fn contains(&self, byte: usize) -> bool {
+        (self.start..self.end).contains(&byte)
+    }
+
+    /// Old positioned captures outside the region (shifted past the edit)
+    /// around the fresh ones inside it.
+    fn splice(&self, old: PositionedTexts, fresh: PositionedTexts) -> PositionedTexts {
+        let (before, after): (Vec<_>, Vec<_>) =
+            old.into_iter().partition(|(byte, _)| *byte < self.start);
+        before
+            .into_iter()
+            .chain(fresh.into_iter().filter(|(byte, _)| self.contains(*byte)))
+            .chain(
+                after
+                    .into_iter()
+                    .filter(|(byte, _)| *byte >= self.old_end)
+                    .map(|(byte, text)| (shift(byte, self.byte_delta), text)),
+            )
+            .collect()
+    }
+}
+
+fn shift(value: usize, delta: isize) -> usize {
+    value.saturating_add_signed(delta)
+}
+
+/// Single edit covering every byte that differs between `old` and `new`,
+/// found by trimming their common prefix and suffix.
+fn diff_edit(old: &str, new: &str) -> Option<InputEdit> {
+    if old == new {
+        return None;
+    }
+    let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+    let prefix = old_bytes
+        .iter()
+        .zip(new_bytes)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_bytes[prefix..]
+        .iter()
+        .rev()
+        .zip(new_bytes[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    Some(InputEdit {
+        start_byte: prefix,
+        old_end_byte: old_end,
+        new_end_byte: new_end,
+        start_position: point_at(old_bytes, prefix),
+        old_end_position: point_at(old_bytes, old_end),
+        new_end_position: point_at(new_bytes, new_end),
+    })
+}
+
+fn point_at(text: &[u8], byte: usize) -> Point {
+    let before = &text[..byte];
+    let row = before.iter().filter(|&&b| b == b'\n').count();
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |newline| newline + 1);
+    Point::new(row, byte - line_start)
+}
+
+/// Name of the innermost named function or method containing `node`. Anonymous
+/// functions take the name of the variable they are assigned to, or else defer
+/// to the function around them.
+fn enclosing_function_name<'a>(node: Node, content: &'a str) -> Option<&'a str> {
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        let name = match ancestor.kind() {
+            "function_definition"
+            | "function_declaration"
+            | "generator_function_declaration"
+            | "method_definition"
+            | "function_item" => ancestor.child_by_field_name("name"),
+            "arrow_function" | "function" | "function_expression" => ancestor
+                .parent()
+                .filter(|parent| parent.kind() == "variable_declarator")
+                .and_then(|declarator| declarator.child_by_field_name("name")),
+            _ => None,
+        };
+        if let Some(name) = name.and_then(|name| name.utf8_text(content.as_bytes()).ok()) {
+            return Some(name);
+        }
+        current = ancestor.parent();
+    }
+    None
+}
+
+/// Read the access modifier on a definition node. Only Rust and Java are
+/// supported so far; other languages return `None`.
+fn symbol_visibility(definition: Node, content: &str, language: &str) -> Option<String> {
+    let mut cursor = definition.walk();
+    let visibility = match language {
+        "rust" => {
+            // Impl blocks cannot carry a visibility modifier
+            if definition.kind() == "impl_item" {
+                return None;
+            }
+            let modifier = definition
+                .children(&mut cursor)
+                .find(|child| child.kind() == "visibility_modifier")
+                .and_then(|node| node.utf8_text(content.as_bytes()).ok())
+                .map(|text| text.split_whitespace().collect::<String>());
+            match modifier.as_deref() {
+                None => "private",
+                Some("pub") => "public",
+                Some("pub(crate)") => "crate",
+                Some(_) => "module", // pub(super), pub(self), pub(in path)
+            }
+        }
+        "java" => {
+            let modifiers = definition
+                .children(&mut cursor)
+                .find(|child| child.kind() == "modifiers");
+            let keyword = modifiers.and_then(|node| {
+                let mut cursor = node.walk();
+                let found = node
+                    .children(&mut cursor)
+                    .map(|child| child.kind())
+                    .find(|kind| matches!(*kind, "public" | "private" | "protected"));
+                found
+            });
+            match keyword {
+                Some("public") => "public",
+                Some("private") => "private",
+                Some(_) => "protected",
+                // Interface members are implicitly public
+                None if definition
+                    .parent()
+                    .is_some_and(|parent| parent.kind() == "interface_body") =>
+                {
+                    "public"
+                }
+                None => "module", // package-private
+            }
+        }
+        _ => return None,
+    };
+    Some(visibility.to_string())
 }
 
 impl CodebaseParser {
@@ -69,6 +306,7 @@ impl CodebaseParser {
         let c_language = tree_sitter_c::language();
         let cpp_language = tree_sitter_cpp::language();
         let ruby_language = tree_sitter_ruby::language();
+        let kotlin_language = tree_sitter_kotlin::language();
 
         Ok(Self {
             python_language,
@@ -81,6 +319,7 @@ impl CodebaseParser {
             c_language,
             cpp_language,
             ruby_language,
+            kotlin_language,
         })
     }
 
@@ -113,6 +352,9 @@ impl CodebaseParser {
             (import_from_statement
               module_name: (dotted_name) @import.module)
             
+            (import_from_statement
+              module_name: (relative_import) @import.module)
+            
             (import_from_statement
               name: (dotted_name) @import.name)
             "#,
@@ -129,10 +371,23 @@ impl CodebaseParser {
             "#,
         )?;
 
+        let calls_query = Query::new(
+            self.python_language,
+            r#"
+            (call
+              function: (identifier) @call.name)
+            
+            (call
+              function: (attribute
+                attribute: (identifier) @call.name))
+            "#,
+        )?;
+
         Ok(CodeQueries {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: Some(calls_query),
         })
     }
 
@@ -198,10 +453,23 @@ impl CodebaseParser {
             "#,
         )?;
 
+        let calls_query = Query::new(
+            self.typescript_language,
+            r#"
+            (call_expression
+              function: (identifier) @call.name)
+            
+            (call_expression
+              function: (member_expression
+                property: (property_identifier) @call.name))
+            "#,
+        )?;
+
         Ok(CodeQueries {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: Some(calls_query),
         })
     }
 
@@ -266,10 +534,23 @@ impl CodebaseParser {
             "#,
         )?;
 
+        let calls_query = Query::new(
+            self.javascript_language,
+            r#"
+            (call_expression
+              function: (identifier) @call.name)
+
+            (call_expression
+              function: (member_expression
+                property: (property_identifier) @call.name))
+            "#,
+        )?;
+
         Ok(CodeQueries {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: Some(calls_query),
         })
     }
 
@@ -334,10 +615,27 @@ impl CodebaseParser {
             "#,
         )?;
 
+        let calls_query = Query::new(
+            self.rust_language,
+            r#"
+            (call_expression
+              function: (identifier) @call.name)
+
+            (call_expression
+              function: (scoped_identifier
+                name: (identifier) @call.name))
+
+            (call_expression
+              function: (field_expression
+                field: (field_identifier) @call.name))
+            "#,
+        )?;
+
         Ok(CodeQueries {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: Some(calls_query),
         })
     }
 
@@ -395,6 +693,7 @@ impl CodebaseParser {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: None,
         })
     }
 
@@ -451,6 +750,7 @@ impl CodebaseParser {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: None,
         })
     }
 
@@ -511,6 +811,7 @@ impl CodebaseParser {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: None,
         })
     }
 
@@ -561,6 +862,7 @@ impl CodebaseParser {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: None,
         })
     }
 
@@ -626,6 +928,7 @@ impl CodebaseParser {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: None,
         })
     }
 
@@ -688,6 +991,68 @@ impl CodebaseParser {
             symbols: symbols_query,
             imports: imports_query,
             exports: exports_query,
+            calls: None,
+        })
+    }
+
+    fn create_kotlin_queries(&self) -> Result<CodeQueries> {
+        // Extension functions (`fun String.isBlank()`) keep the receiver in a
+        // user_type node, so the simple_identifier child is always the name
+        let symbols_query = Query::new(
+            self.kotlin_language,
+            r#"
+            (function_declaration
+              (simple_identifier) @function.name) @function.definition
+
+            (class_declaration
+              "class"
+              (type_identifier) @class.name) @class.definition
+
+            (class_declaration
+              "interface"
+              (type_identifier) @interface.name) @interface.definition
+
+            (object_declaration
+              (type_identifier) @object.name) @object.definition
+
+            (companion_object) @companion.definition
+
+            (property_declaration
+              (variable_declaration
+                (simple_identifier) @variable.name)) @variable.definition
+            "#,
+        )?;
+
+        let imports_query = Query::new(
+            self.kotlin_language,
+            r#"
+            (import_header
+              (identifier) @import.name)
+            "#,
+        )?;
+
+        let exports_query = Query::new(
+            self.kotlin_language,
+            r#"
+            (source_file
+              (class_declaration
+                (type_identifier) @export.name))
+
+            (source_file
+              (object_declaration
+                (type_identifier) @export.name))
+
+            (source_file
+              (function_declaration
+                (simple_identifier) @export.name))
+            "#,
+        )?;
+
+        Ok(CodeQueries {
+            symbols: symbols_query,
+            imports: imports_query,
+            exports: exports_query,
+            calls: None,
         })
     }
 
@@ -754,6 +1119,23 @@ impl CodebaseParser {
                                 file_logs.insert(path.to_string_lossy().to_string(), file_log);
                             }
                         }
+                        "kt" | "kts" => {
+                            if let Ok(file_log) = self.parse_file(path, "kotlin") {
+                                file_logs.insert(path.to_string_lossy().to_string(), file_log);
+                            }
+                        }
+                        // No grammar (see Cargo.toml); scanned by fallback_parser
+                        "php" => {
+                            if let Ok(file_log) = self.parse_file(path, "php") {
+                                file_logs.insert(path.to_string_lossy().to_string(), file_log);
+                            }
+                        }
+                        // Scanned like PHP; the language name matches detect_language
+                        "sh" | "bash" | "zsh" => {
+                            if let Ok(file_log) = self.parse_file(path, "shell") {
+                                file_logs.insert(path.to_string_lossy().to_string(), file_log);
+                            }
+                        }
                         _ => continue,
                     }
                 }
@@ -765,96 +1147,286 @@ impl CodebaseParser {
 
     pub fn parse_file(&self, file_path: &Path, language: &str) -> Result<FileLog> {
         let content = std::fs::read_to_string(file_path)?;
-        let content_hash = self.compute_hash(&content);
+        let (file_log, _, _) =
+            self.parse_content_incremental(file_path, language, content, None)?;
+        Ok(file_log)
+    }
 
-        let mut parser = Parser::new();
-        let queries = match language {
-            "python" => {
-                parser.set_language(self.python_language)?;
-                self.create_python_queries()?
-            }
-            "typescript" => {
-                parser.set_language(self.typescript_language)?;
-                self.create_typescript_queries()?
-            }
-            "javascript" => {
-                parser.set_language(self.javascript_language)?;
-                self.create_javascript_queries()?
-            }
-            "rust" => {
-                parser.set_language(self.rust_language)?;
-                self.create_rust_queries()?
-            }
-            "go" => {
-                parser.set_language(self.go_language)?;
-                self.create_go_queries()?
-            }
-            "csharp" => {
-                parser.set_language(self.csharp_language)?;
-                self.create_csharp_queries()?
-            }
-            "java" => {
-                parser.set_language(self.java_language)?;
-                self.create_java_queries()?
-            }
-            "c" => {
-                parser.set_language(self.c_language)?;
-                self.create_c_queries()?
-            }
-            "cpp" => {
-                parser.set_language(self.cpp_language)?;
-                self.create_cpp_queries()?
-            }
-            "ruby" => {
-                parser.set_language(self.ruby_language)?;
-                self.create_ruby_queries()?
+    /// Parse `content`, reusing `previous` (the last parse of the same file)
+    /// when there is one. Only the top-level items touched by the edit are
+    /// queried again; symbols and dependencies elsewhere are carried over with
+    /// shifted positions. Returns the state to pass in on the next edit, which
+    /// is `None` for languages without a grammar.
+    pub fn parse_content_incremental(
+        &self,
+        file_path: &Path,
+        language: &str,
+        content: String,
+        previous: Option<ParsedFile>,
+    ) -> Result<(FileLog, Option<ParsedFile>, SymbolChanges)> {
+        let Some((grammar, queries)) = self.grammar_and_queries(language)? else {
+            // Languages whose grammar can't be built are scanned without a tree
+            if let Some((symbols, dependencies)) =
+                fallback_parser::parse(language, &content, file_path)
+            {
+                let changes = SymbolChanges {
+                    added: symbols.len(),
+                    ..SymbolChanges::default()
+                };
+                let file_log = self.build_file_log(
+                    file_path,
+                    language,
+                    &content,
+                    symbols,
+                    dependencies,
+                    Vec::new(),
+                );
+                return Ok((file_log, None, changes));
             }
-            _ => {
-                // For unsupported languages, return a basic file log without parsing
-                let mut hasher = Sha256::new();
-                hasher.update(&content);
-                let hash = format!("{:x}", hasher.finalize());
-
-                return Ok(FileLog {
-                    path: file_path.to_string_lossy().to_string(),
-                    language: language.to_string(),
-                    last_indexed: chrono::Utc::now().to_rfc3339(),
-                    content_hash: hash,
-                    symbols: Vec::new(),
-                    dependencies: FileDependencies {
-                        imports: Vec::new(),
-                        exports: Vec::new(),
+            // For unsupported languages, return a basic file log without parsing
+            let file_log = self.build_file_log(
+                file_path,
+                language,
+                &content,
+                Vec::new(),
+                FileDependencies {
+                    imports: Vec::new(),
+                    exports: Vec::new(),
+                },
+                vec![format!(
+                    "Language '{}' not yet supported for parsing",
+                    language
+                )],
+            );
+            return Ok((file_log, None, SymbolChanges::default()));
+        };
+
+        let mut parser = Parser::new();
+        parser.set_language(grammar)?;
+
+        let (parsed, changes) = match previous {
+            Some(previous) => match diff_edit(&previous.content, &content) {
+                Some(edit) => self.reparse(
+                    &mut parser,
+                    &queries,
+                    file_path,
+                    language,
+                    content,
+                    previous,
+                    &edit,
+                )?,
+                None => (previous, SymbolChanges::default()),
+            },
+            None => {
+                let tree = parser
+                    .parse(&content, None)
+                    .ok_or_else(|| anyhow!("Failed to parse file: {}", file_path.display()))?;
+                let symbols =
+                    self.extract_symbols(&tree, &content, &queries, file_path, language, None)?;
+                let (imports, exports) = self.extract_dependencies(&tree, &content, &queries, None);
+                let changes = SymbolChanges {
+                    added: symbols.len(),
+                    ..SymbolChanges::default()
+                };
+                (
+                    ParsedFile {
+                        content,
+                        tree,
+                        symbols,
+                        imports,
+                        exports,
                     },
-                    recent_changes: Vec::new(),
-                    linked_decisions: Vec::new(),
-                    notes: vec![format!(
-                        "Language '{}' not yet supported for parsing",
-                        language
-                    )],
-                });
+                    changes,
+                )
             }
         };
 
+        let mut file_log = self.build_file_log(
+            file_path,
+            language,
+            &parsed.content,
+            parsed.symbols.clone(),
+            parsed.dependencies(),
+            Vec::new(),
+        );
+        file_log.calls = self.extract_calls(&parsed.tree, &parsed.content, &queries);
+        Ok((file_log, Some(parsed), changes))
+    }
+
+    /// Apply `edit` to the previous tree, reparse, and re-extract only the
+    /// region the edit could have affected.
+    #[allow(clippy::too_many_arguments)]
+    fn reparse(
+        &self,
+        parser: &mut Parser,
+        queries: &CodeQueries,
+        file_path: &Path,
+        language: &str,
+        content: String,
+        previous: ParsedFile,
+        edit: &InputEdit,
+    ) -> Result<(ParsedFile, SymbolChanges)> {
+        let ParsedFile {
+            tree: mut old_tree,
+            symbols: old_symbols,
+            imports: old_imports,
+            exports: old_exports,
+            ..
+        } = previous;
+        old_tree.edit(edit);
         let tree = parser
-            .parse(&content, None)
+            .parse(&content, Some(&old_tree))
             .ok_or_else(|| anyhow!("Failed to parse file: {}", file_path.display()))?;
 
-        let symbols = self.extract_symbols(&tree, &content, &queries, file_path, language)?;
-        let dependencies = self.extract_dependencies(&tree, &content, &queries)?;
+        // Bytes whose syntax may differ, in new-content offsets
+        let (mut changed_start, mut changed_end) = (edit.start_byte, edit.new_end_byte);
+        for range in old_tree.changed_ranges(&tree) {
+            changed_start = changed_start.min(range.start_byte);
+            changed_end = changed_end.max(range.end_byte);
+        }
+
+        // Widen to whole top-level items so no match straddles the boundary
+        let (mut start, mut end) = (changed_start, changed_end);
+        let root = tree.root_node();
+        for item in root.children(&mut root.walk()) {
+            if item.start_byte() <= changed_end && item.end_byte() >= changed_start {
+                start = start.min(item.start_byte());
+                end = end.max(item.end_byte());
+            }
+        }
+
+        let byte_delta = edit.new_end_byte as isize - edit.old_end_byte as isize;
+        let row_delta = edit.new_end_position.row as isize - edit.old_end_position.row as isize;
+        let region = Region {
+            start,
+            end,
+            old_end: shift(end, -byte_delta),
+            byte_delta,
+        };
 
-        Ok(FileLog {
+        let fresh: Vec<ParsedSymbol> = self
+            .extract_symbols(
+                &tree,
+                &content,
+                queries,
+                file_path,
+                language,
+                Some(start..end),
+            )?
+            .into_iter()
+            .filter(|symbol| region.contains(symbol.start_byte))
+            .collect();
+        // Fresh symbols that existed before and whose definition overlaps the edit
+        let old_counts = symbol_counts(&old_symbols);
+        let changed = fresh
+            .iter()
+            .filter(|symbol| old_counts.contains_key(&symbol_key(symbol)))
+            .filter(|symbol| {
+                root.descendant_for_byte_range(symbol.start_byte, symbol.end_byte)
+                    .and_then(|name| name.parent())
+                    .is_some_and(|definition| {
+                        definition.start_byte() <= edit.new_end_byte
+                            && definition.end_byte() >= edit.start_byte
+                    })
+            })
+            .count();
+
+        let mut symbols = Vec::with_capacity(old_symbols.len());
+        let mut fresh = Some(fresh);
+        for mut symbol in old_symbols {
+            if symbol.start_byte < region.start {
+                symbols.push(symbol);
+                continue;
+            }
+            symbols.extend(fresh.take().into_iter().flatten());
+            if symbol.start_byte >= region.old_end {
+                symbol.start_byte = shift(symbol.start_byte, byte_delta);
+                symbol.end_byte = shift(symbol.end_byte, byte_delta);
+                symbol.start_line = shift(symbol.start_line, row_delta);
+                symbol.end_line = shift(symbol.end_line, row_delta);
+                symbols.push(symbol);
+            }
+        }
+        symbols.extend(fresh.into_iter().flatten());
+        let changes = SymbolChanges::between(&old_counts, &symbols, changed);
+
+        let (imports, exports) =
+            self.extract_dependencies(&tree, &content, queries, Some(start..end));
+        let imports = region.splice(old_imports, imports);
+        let exports = region.splice(old_exports, exports);
+
+        Ok((
+            ParsedFile {
+                content,
+                tree,
+                symbols,
+                imports,
+                exports,
+            },
+            changes,
+        ))
+    }
+
+    fn build_file_log(
+        &self,
+        file_path: &Path,
+        language: &str,
+        content: &str,
+        symbols: Vec<ParsedSymbol>,
+        dependencies: FileDependencies,
+        notes: Vec<String>,
+    ) -> FileLog {
+        FileLog {
             path: file_path.to_string_lossy().to_string(),
             language: language.to_string(),
             last_indexed: chrono::Utc::now().to_rfc3339(),
-            content_hash,
+            content_hash: self.compute_hash(content),
             symbols,
             dependencies,
             recent_changes: Vec::new(),
             linked_decisions: Vec::new(),
-            notes: Vec::new(),
-        })
+            notes,
+            blame: Vec::new(),
+            calls: Vec::new(),
+        }
+    }
+
+    fn grammar(&self, language: &str) -> Option<Language> {
+        match language {
+            "python" => Some(self.python_language),
+            "typescript" => Some(self.typescript_language),
+            "javascript" => Some(self.javascript_language),
+            "rust" => Some(self.rust_language),
+            "go" => Some(self.go_language),
+            "csharp" => Some(self.csharp_language),
+            "java" => Some(self.java_language),
+            "c" => Some(self.c_language),
+            "cpp" => Some(self.cpp_language),
+            "ruby" => Some(self.ruby_language),
+            "kotlin" => Some(self.kotlin_language),
+            _ => None,
+        }
     }
 
+    fn grammar_and_queries(&self, language: &str) -> Result<Option<(Language, CodeQueries)>> {
+        let queries = match language {
+            "python" => self.create_python_queries()?,
+            "typescript" => self.create_typescript_queries()?,
+            "javascript" => self.create_javascript_queries()?,
+            "rust" => self.create_rust_queries()?,
+            "go" => self.create_go_queries()?,
+            "csharp" => self.create_csharp_queries()?,
+            "java" => self.create_java_queries()?,
+            "c" => self.create_c_queries()?,
+            "cpp" => self.create_cpp_queries()?,
+            "ruby" => self.create_ruby_queries()?,
+            "kotlin" => self.create_kotlin_queries()?,
+            _ => return Ok(None),
+        };
+        Ok(self.grammar(language).map(|grammar| (grammar, queries)))
+    }
+
+    /// Symbols in `tree`, limited to matches intersecting `range` when given.
     fn extract_symbols(
         &self,
         tree: &Tree,
@@ -862,16 +1434,20 @@ impl CodebaseParser {
         queries: &CodeQueries,
         file_path: &Path,
         language: &str,
+        range: Option<Range<usize>>,
     ) -> Result<Vec<ParsedSymbol>> {
         let mut cursor = QueryCursor::new();
+        if let Some(range) = range {
+            cursor.set_byte_range(range);
+        }
         let mut symbols = Vec::new();
-
         let matches = cursor.matches(&queries.symbols, tree.root_node(), content.as_bytes());
 
         for m in matches {
             let mut symbol_name = String::new();
             let mut symbol_type = String::from("unknown");
             let mut node_for_position = None;
+            let mut definition_node = None;
 
             for capture in m.captures {
                 let node = capture.node;
@@ -886,12 +1462,28 @@ impl CodebaseParser {
                         .to_string();
                     symbol_name = node.utf8_text(content.as_bytes())?.to_string();
                     node_for_position = Some(node);
-                } else if capture_name.ends_with(".definition") && node_for_position.is_none() {
+                } else if capture_name.ends_with(".definition") {
+                    definition_node = Some(node);
                     // Use the definition node for position if we don't have a name node yet
-                    node_for_position = Some(node);
+                    if node_for_position.is_none() {
+                        node_for_position = Some(node);
+                    }
                 }
             }
 
+            // A Kotlin companion object is named `Companion` unless it declares a name
+            if let Some(companion) =
+                definition_node.filter(|node| node.kind() == "companion_object")
+            {
+                let mut cursor = companion.walk();
+                let declared = companion
+                    .children(&mut cursor)
+                    .find(|child| child.kind() == "type_identifier")
+                    .and_then(|name| name.utf8_text(content.as_bytes()).ok());
+                symbol_name = declared.unwrap_or("Companion").to_string();
+                symbol_type = "object".to_string();
+            }
+
             if !symbol_name.is_empty() {
                 if let Some(pos_node) = node_for_position {
                     symbols.push(ParsedSymbol {
@@ -903,6 +1495,8 @@ impl CodebaseParser {
                         end_byte: pos_node.end_byte(),
                         file_path: file_path.to_string_lossy().to_string(),
                         language: language.to_string(),
+                        visibility: definition_node
+                            .and_then(|node| symbol_visibility(node, content, language)),
                     });
                 }
             }
@@ -911,13 +1505,19 @@ impl CodebaseParser {
         Ok(symbols)
     }
 
+    /// Imports and exports with the byte offset of each capture, limited to
+    /// matches intersecting `range` when given.
     fn extract_dependencies(
         &self,
         tree: &Tree,
         content: &str,
         queries: &CodeQueries,
-    ) -> Result<FileDependencies> {
+        range: Option<Range<usize>>,
+    ) -> (PositionedTexts, PositionedTexts) {
         let mut cursor = QueryCursor::new();
+        if let Some(range) = range {
+            cursor.set_byte_range(range);
+        }
         let mut imports = Vec::new();
         let mut exports = Vec::new();
 
@@ -927,7 +1527,7 @@ impl CodebaseParser {
             for capture in m.captures {
                 let node = capture.node;
                 if let Ok(text) = node.utf8_text(content.as_bytes()) {
-                    imports.push(text.trim_matches('"').to_string());
+                    imports.push((node.start_byte(), text.trim_matches('"').to_string()));
                 }
             }
         }
@@ -938,12 +1538,42 @@ impl CodebaseParser {
             for capture in m.captures {
                 let node = capture.node;
                 if let Ok(text) = node.utf8_text(content.as_bytes()) {
-                    exports.push(text.to_string());
+                    exports.push((node.start_byte(), text.to_string()));
                 }
             }
         }
 
-        Ok(FileDependencies { imports, exports })
+        (imports, exports)
+    }
+
+    /// `(caller, callee)` pairs for every call made inside a named function,
+    /// in source order without duplicates. Calls at module level are skipped.
+    fn extract_calls(
+        &self,
+        tree: &Tree,
+        content: &str,
+        queries: &CodeQueries,
+    ) -> Vec<(String, String)> {
+        let Some(calls_query) = &queries.calls else {
+            return Vec::new();
+        };
+        let mut cursor = QueryCursor::new();
+        let mut calls = Vec::new();
+        for m in cursor.matches(calls_query, tree.root_node(), content.as_bytes()) {
+            for capture in m.captures {
+                let Ok(callee) = capture.node.utf8_text(content.as_bytes()) else {
+                    continue;
+                };
+                let Some(caller) = enclosing_function_name(capture.node, content) else {
+                    continue;
+                };
+                let call = (caller.to_string(), callee.to_string());
+                if !calls.contains(&call) {
+                    calls.push(call);
+                }
+            }
+        }
+        calls
     }
 
     fn compute_hash(&self, content: &str) -> String {
@@ -1013,7 +1643,16 @@ impl CodebaseParser {
         language: &str,
     ) -> Vec<super::chunking::ChunkData> {
         let chunking_service = super::chunking::ChunkingService::new();
-        chunking_service.chunk_file(content, language)
+        chunking_service.chunk_file_semantic(self, content, language)
+    }
+
+    /// Syntax tree for `content`, or `None` when the language has no grammar
+    /// or parsing fails.
+    pub fn parse_tree(&self, content: &str, language: &str) -> Option<Tree> {
+        let grammar = self.grammar(language)?;
+        let mut parser = Parser::new();
+        parser.set_language(grammar).ok()?;
+        parser.parse(content, None)
     }
 
     pub fn generate_filelog_summary(
@@ -1049,7 +1688,8 @@ impl CodebaseParser {
                     "function" => crate::models::SymbolKind::Function,
                     "class" => crate::models::SymbolKind::Class,
                     "variable" => crate::models::SymbolKind::Variable,
-                    "module" => crate::models::SymbolKind::Module,
+                    // Kotlin objects are singletons, closest to a module
+                    "module" | "object" => crate::models::SymbolKind::Module,
                     "type" => crate::models::SymbolKind::Type,
                     _ => crate::models::SymbolKind::Function,
                 },
@@ -1058,6 +1698,7 @@ impl CodebaseParser {
                 content_hash: None,
                 signature: None,
                 documentation: None,
+                visibility: ps.visibility.clone(),
             })
             .collect();
 
@@ -1092,6 +1733,7 @@ class MyClass:
 
 import os
 from typing import List
+from ..pkg.utils import helper
 "#,
         )
         .unwrap();
@@ -1101,6 +1743,10 @@ from typing import List
         assert_eq!(file_log.language, "python");
         assert!(file_log.symbols.len() >= 3); // function, class, method
         assert!(file_log.dependencies.imports.len() >= 1);
+        assert!(file_log
+            .dependencies
+            .imports
+            .contains(&"..pkg.utils".to_string()));
     }
 
     #[test]
@@ -1156,6 +1802,7 @@ export function createUser(name: string): User {
                 end_byte: 30,
                 file_path: "test.py".to_string(),
                 language: "python".to_string(),
+                visibility: None,
             }],
             dependencies: FileDependencies {
                 imports: vec!["os".to_string()],
@@ -1164,6 +1811,8 @@ export function createUser(name: string): User {
             recent_changes: vec!["Added hello function".to_string()],
             linked_decisions: vec!["dec_001".to_string()],
             notes: vec!["Main entry point".to_string()],
+            blame: Vec::new(),
+            calls: Vec::new(),
         };
 
         let markdown = parser.generate_file_log_markdown(&file_log);
@@ -1250,6 +1899,56 @@ pub enum Direction {
         assert!(file_log.dependencies.imports.len() >= 1);
     }
 
+    #[test]
+    fn test_parse_file_extracts_calls_between_functions() {
+        let parser = CodebaseParser::new().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let calls = |name: &str, language: &str, source: &str| {
+            let file_path = dir.path().join(name);
+            std::fs::write(&file_path, source).unwrap();
+            parser.parse_file(&file_path, language).unwrap().calls
+        };
+        let pairs = |expected: &[(&str, &str)]| {
+            expected
+                .iter()
+                .map(|(caller, callee)| (caller.to_string(), callee.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            calls(
+                "calls.py",
+                "python",
+                "import os\n\nsetup()\n\ndef load(path):\n    return os.path.join(path, 'x')\n\ndef main():\n    load('.')\n    load('..')\n",
+            ),
+            pairs(&[("load", "join"), ("main", "load")])
+        );
+        assert_eq!(
+            calls(
+                "calls.js",
+                "javascript",
+                "function load() { return fetch('/x'); }\nconst main = () => { load(); };\n",
+            ),
+            pairs(&[("load", "fetch"), ("main", "load")])
+        );
+        assert_eq!(
+            calls(
+                "calls.ts",
+                "typescript",
+                "class Service {\n  run(): void { this.helper(); }\n  helper(): void {}\n}\n",
+            ),
+            pairs(&[("run", "helper")])
+        );
+        assert_eq!(
+            calls(
+                "calls.rs",
+                "rust",
+                "fn helper() -> u32 { 1 }\n\nstruct Point;\n\nimpl Point {\n    fn new() -> Self { helper(); Self }\n    fn build(&self) { Point::new(); self.len(); }\n}\n",
+            ),
+            pairs(&[("new", "helper"), ("build", "new"), ("build", "len")])
+        );
+    }
+
     #[test]
     fn test_parse_go_file() {
         let parser = CodebaseParser::new().unwrap();
@@ -1380,6 +2079,68 @@ enum Operation {
         assert!(file_log.dependencies.imports.len() >= 1);
     }
 
+    #[test]
+    fn test_symbol_visibility_rust_and_java() {
+        let parser = CodebaseParser::new().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let rust_path = dir.path().join("lib.rs");
+        std::fs::write(
+            &rust_path,
+            r#"
+pub fn exported() {}
+fn internal() {}
+pub(crate) struct Shared;
+pub(super) enum Parent { A }
+"#,
+        )
+        .unwrap();
+        let file_log = parser.parse_file(&rust_path, "rust").unwrap();
+        let visibility = |name: &str| {
+            file_log
+                .symbols
+                .iter()
+                .find(|s| s.name == name)
+                .and_then(|s| s.visibility.clone())
+        };
+        assert_eq!(visibility("exported").as_deref(), Some("public"));
+        assert_eq!(visibility("internal").as_deref(), Some("private"));
+        assert_eq!(visibility("Shared").as_deref(), Some("crate"));
+        assert_eq!(visibility("Parent").as_deref(), Some("module"));
+
+        let java_path = dir.path().join("Api.java");
+        std::fs::write(
+            &java_path,
+            r#"
+public class Api {
+    private int count;
+    protected void hook() {}
+    void helper() {}
+    public int run() { return count; }
+}
+
+interface Service {
+    void serve();
+}
+"#,
+        )
+        .unwrap();
+        let file_log = parser.parse_file(&java_path, "java").unwrap();
+        let visibility = |name: &str| {
+            file_log
+                .symbols
+                .iter()
+                .find(|s| s.name == name)
+                .and_then(|s| s.visibility.clone())
+        };
+        assert_eq!(visibility("Api").as_deref(), Some("public"));
+        assert_eq!(visibility("count").as_deref(), Some("private"));
+        assert_eq!(visibility("hook").as_deref(), Some("protected"));
+        assert_eq!(visibility("helper").as_deref(), Some("module"));
+        assert_eq!(visibility("run").as_deref(), Some("public"));
+        assert_eq!(visibility("serve").as_deref(), Some("public"));
+    }
+
     #[test]
     fn test_parse_c_file() {
         let parser = CodebaseParser::new().unwrap();
@@ -1514,4 +2275,426 @@ end
         assert!(file_log.symbols.len() >= 3); // module, class, methods
         assert!(file_log.dependencies.imports.len() >= 1);
     }
+
+    #[test]
+    fn test_parse_kotlin_file() {
+        let parser = CodebaseParser::new().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("User.kt");
+        std::fs::write(
+            &file_path,
+            r#"
+package com.example.users
+
+import kotlinx.coroutines.flow.Flow
+import java.util.UUID
+import com.example.db.*
+
+data class User(val id: UUID, val name: String) {
+    companion object {
+        const val MAX_NAME = 64
+
+        fun create(name: String): User = User(UUID.randomUUID(), name)
+    }
+}
+
+interface UserRepository {
+    fun find(id: UUID): User?
+    fun observe(): Flow<List<User>>
+}
+
+object UserCache {
+    private val users = mutableMapOf<UUID, User>()
+}
+
+fun String.isValidName(): Boolean = isNotBlank() && length <= User.MAX_NAME
+"#,
+        )
+        .unwrap();
+
+        let file_log = parser.parse_file(&file_path, "kotlin").unwrap();
+        let symbol = |name: &str| {
+            file_log
+                .symbols
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| s.symbol_type.as_str())
+        };
+
+        assert_eq!(file_log.language, "kotlin");
+        assert_eq!(symbol("User"), Some("class"));
+        assert_eq!(symbol("UserRepository"), Some("interface"));
+        assert_eq!(symbol("UserCache"), Some("object"));
+        assert_eq!(symbol("Companion"), Some("object"));
+        assert_eq!(symbol("create"), Some("function"));
+        assert_eq!(symbol("find"), Some("function"));
+        assert_eq!(symbol("MAX_NAME"), Some("variable"));
+        assert_eq!(symbol("users"), Some("variable"));
+        // Extension functions are named without their receiver type
+        assert_eq!(symbol("isValidName"), Some("function"));
+        assert_eq!(symbol("String"), None);
+        assert_eq!(
+            file_log.dependencies.imports,
+            vec![
+                "kotlinx.coroutines.flow.Flow",
+                "java.util.UUID",
+                "com.example.db"
+            ]
+        );
+        assert!(file_log
+            .dependencies
+            .exports
+            .contains(&"UserCache".to_string()));
+
+        // Objects are singletons, summarized as modules
+        let (summary, _, _) =
+            parser.generate_filelog_summary("User.kt", &file_log.symbols, "kotlin");
+        assert!(summary.contains("`UserCache` (module)"));
+    }
+
+    #[test]
+    fn test_parse_php_file() {
+        let parser = CodebaseParser::new().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("UserService.php");
+        std::fs::write(
+            &file_path,
+            r#"<?php
+namespace App\Services;
+
+use App\Models\User;
+use App\Contracts\{Cache, Logger as Log};
+use function App\Support\helper;
+require_once __DIR__ . '/bootstrap.php';
+include 'config.php';
+
+interface Repository
+{
+    public function find(int $id): ?User;
+}
+
+trait Logs
+{
+    protected function log(string $message): void {}
+}
+
+// class Commented {}
+class UserService implements Repository
+{
+    use Logs;
+
+    private function cacheKey(int $id): string
+    {
+        return "user:{$id}";
+    }
+
+    public function find(int $id): ?User
+    {
+        $load = function () use ($id) { return User::class; };
+        return $load();
+    }
+}
+
+function format_name(string $name): string
+{
+    return ucfirst($name);
+}
+"#,
+        )
+        .unwrap();
+
+        let file_log = parser.parse_file(&file_path, "php").unwrap();
+        let symbol = |name: &str| {
+            file_log
+                .symbols
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| (s.symbol_type.as_str(), s.visibility.as_deref()))
+        };
+
+        assert_eq!(file_log.language, "php");
+        assert!(file_log.notes.is_empty());
+        assert_eq!(symbol("Repository"), Some(("interface", None)));
+        assert_eq!(symbol("Logs"), Some(("trait", None)));
+        assert_eq!(symbol("UserService"), Some(("class", None)));
+        assert_eq!(symbol("log"), Some(("method", Some("protected"))));
+        assert_eq!(symbol("cacheKey"), Some(("method", Some("private"))));
+        assert_eq!(symbol("format_name"), Some(("function", None)));
+        assert_eq!(symbol("Commented"), None);
+        assert_eq!(
+            file_log.symbols.iter().filter(|s| s.name == "find").count(),
+            2
+        );
+        assert_eq!(
+            file_log.dependencies.imports,
+            vec![
+                "App\\Models\\User",
+                "App\\Contracts\\Cache",
+                "App\\Contracts\\Logger",
+                "App\\Support\\helper",
+                "/bootstrap.php",
+                "config.php"
+            ]
+        );
+        assert_eq!(
+            file_log.dependencies.exports,
+            vec!["Repository", "Logs", "UserService", "format_name"]
+        );
+
+        // Positions point at the name, like the tree-sitter extractors
+        let service = file_log
+            .symbols
+            .iter()
+            .find(|s| s.name == "UserService")
+            .unwrap();
+        assert_eq!(service.start_line, 20);
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(
+            &content[service.start_byte..service.end_byte],
+            "UserService"
+        );
+    }
+
+    #[test]
+    fn test_parse_shell_file() {
+        let parser = CodebaseParser::new().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("deploy.sh");
+        std::fs::write(
+            &file_path,
+            r#"#!/usr/bin/env bash
+set -euo pipefail
+
+source "$(dirname "$0")/lib/common.sh"
+. ./env.sh
+
+export DEPLOY_ENV="${1:-staging}"
+RETRIES=3
+
+# build() { echo "commented out"; }
+build() {
+    local target=dist
+    cat <<EOF > "$target/VERSION"
+release() {
+EOF
+    echo "building $target"
+}
+
+function deploy {
+    for i in $(seq 1 "$RETRIES"); do
+        build && break
+    done
+}
+
+deploy
+"#,
+        )
+        .unwrap();
+
+        let file_log = parser.parse_file(&file_path, "shell").unwrap();
+        let names_of = |symbol_type: &str| {
+            file_log
+                .symbols
+                .iter()
+                .filter(|s| s.symbol_type == symbol_type)
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(file_log.language, "shell");
+        assert!(file_log.notes.is_empty());
+        assert_eq!(names_of("function"), vec!["build", "deploy"]);
+        assert_eq!(
+            names_of("variable"),
+            vec!["DEPLOY_ENV", "RETRIES", "target"]
+        );
+        assert_eq!(
+            file_log.dependencies.imports,
+            vec!["$(dirname \"$0\")/lib/common.sh", "./env.sh"]
+        );
+        assert_eq!(
+            file_log.dependencies.exports,
+            vec!["DEPLOY_ENV", "build", "deploy"]
+        );
+        let deploy = file_log
+            .symbols
+            .iter()
+            .find(|s| s.name == "deploy")
+            .unwrap();
+        assert_eq!(deploy.start_line, 18);
+    }
+
+    fn sorted_symbols(file_log: &FileLog) -> Vec<String> {
+        let mut symbols: Vec<String> = file_log
+            .symbols
+            .iter()
+            .map(|s| {
+                format!(
+                    "{} {} {}-{} {}-{} {:?}",
+                    s.symbol_type,
+                    s.name,
+                    s.start_line,
+                    s.end_line,
+                    s.start_byte,
+                    s.end_byte,
+                    s.visibility
+                )
+            })
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    fn sorted(items: &[String]) -> Vec<String> {
+        let mut items = items.to_vec();
+        items.sort();
+        items
+    }
+
+    /// Rust source with `count` functions of eleven lines each, blank line included
+    fn generated_rust(count: usize) -> String {
+        let mut source = String::from("use std::collections::HashMap;\n\n");
+        for i in 0..count {
+            source.push_str(&format!(
+                "pub fn function_{i}(input: u32) -> u32 {{\n    let mut total = input;\n    for step in 0..{i} {{\n        total += step;\n    }}\n    let doubled = total * 2;\n    let halved = doubled / 2;\n    assert_eq!(halved, total);\n    total\n}}\n\n"
+            ));
+        }
+        source
+    }
+
+    #[test]
+    fn test_incremental_parse_matches_full_parse() {
+        let parser = CodebaseParser::new().unwrap();
+        let path = Path::new("src/lib.rs");
+        let before = r#"use std::fmt;
+
+pub struct Point {
+    x: f64,
+}
+
+impl Point {
+    pub fn new(x: f64) -> Self {
+        Self { x }
+    }
+}
+
+fn helper() -> u32 {
+    1
+}
+
+pub fn last() {}
+"#;
+        let (_, previous, _) = parser
+            .parse_content_incremental(path, "rust", before.to_string(), None)
+            .unwrap();
+
+        let after = before
+            .replace(
+                "        Self { x }\n",
+                "        let x = x * 2.0;\n        Self { x }\n",
+            )
+            .replace("fn helper() -> u32 {\n    1\n}\n", "")
+            .replace("use std::fmt;\n", "use std::fmt;\nuse std::io;\n");
+        let (incremental, _, changes) = parser
+            .parse_content_incremental(path, "rust", after.clone(), previous)
+            .unwrap();
+        let (full, _, _) = parser
+            .parse_content_incremental(path, "rust", after, None)
+            .unwrap();
+
+        assert_eq!(sorted_symbols(&incremental), sorted_symbols(&full));
+        assert_eq!(
+            sorted(&incremental.dependencies.imports),
+            sorted(&full.dependencies.imports)
+        );
+        assert_eq!(
+            sorted(&incremental.dependencies.exports),
+            sorted(&full.dependencies.exports)
+        );
+        assert_eq!(incremental.content_hash, full.content_hash);
+        assert_eq!(changes.removed, 1);
+        assert_eq!(changes.added, 0);
+    }
+
+    #[test]
+    fn test_incremental_parse_counts_symbol_changes() {
+        let parser = CodebaseParser::new().unwrap();
+        let path = Path::new("src/lib.rs");
+        let source = generated_rust(5);
+        let (_, previous, changes) = parser
+            .parse_content_incremental(path, "rust", source.clone(), None)
+            .unwrap();
+        assert_eq!(changes.added, 5);
+
+        let (_, previous, changes) = parser
+            .parse_content_incremental(path, "rust", source.clone(), previous)
+            .unwrap();
+        assert_eq!(changes, SymbolChanges::default());
+
+        let edited = source.replace("for step in 0..2 {", "for step in 1..2 {");
+        let (_, previous, changes) = parser
+            .parse_content_incremental(path, "rust", edited.clone(), previous)
+            .unwrap();
+        assert_eq!(
+            changes,
+            SymbolChanges {
+                added: 0,
+                removed: 0,
+                changed: 1
+            }
+        );
+
+        let extended = format!("{}fn extra() {{}}\n", edited);
+        let (file_log, _, changes) = parser
+            .parse_content_incremental(path, "rust", extended, previous)
+            .unwrap();
+        assert_eq!(changes.added, 1);
+        assert_eq!(changes.changed, 0);
+        assert_eq!(file_log.symbols.len(), 6);
+    }
+
+    /// Timing comparison; run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_incremental_vs_full_parse() {
+        let parser = CodebaseParser::new().unwrap();
+        let path = Path::new("src/generated.rs");
+        // A 2000-line file with one function changed
+        let source = generated_rust(182);
+        assert!((2000..2020).contains(&source.lines().count()));
+        let edited = source.replace("for step in 0..100 {", "for step in 0..101 {");
+        assert_ne!(source, edited);
+        let runs = 50;
+
+        let started = std::time::Instant::now();
+        for _ in 0..runs {
+            parser
+                .parse_content_incremental(path, "rust", edited.clone(), None)
+                .unwrap();
+        }
+        let full = started.elapsed() / runs;
+
+        let mut incremental = std::time::Duration::ZERO;
+        for _ in 0..runs {
+            let (_, previous, _) = parser
+                .parse_content_incremental(path, "rust", source.clone(), None)
+                .unwrap();
+            let started = std::time::Instant::now();
+            parser
+                .parse_content_incremental(path, "rust", edited.clone(), previous)
+                .unwrap();
+            incremental += started.elapsed();
+        }
+        let incremental = incremental / runs;
+
+        println!(
+            "{} lines: full {:?}, incremental {:?}",
+            source.lines().count(),
+            full,
+            incremental
+        );
+    }
 }
@@ -1,9 +1,18 @@
+use crate::models::settings::SettingsConfig;
 use async_trait::async_trait;
 use thiserror::Error;
 
+pub mod batch;
+pub mod cohere;
 pub mod none;
 pub mod ollama;
 pub mod openai;
+pub mod reloadable;
+pub mod retry;
+
+pub use batch::EmbeddingBatchConfig;
+pub use reloadable::ReloadableEmbedding;
+pub use retry::EmbeddingRetryConfig;
 
 #[derive(Debug, Error)]
 pub enum EmbeddingError {
@@ -13,6 +22,12 @@ pub enum EmbeddingError {
     #[error("API error: {0}")]
     ApiError(String),
 
+    #[error("Rate limited by embedding provider: {0}")]
+    RateLimited(String),
+
+    #[error("Embedding provider rejected credentials: {0}")]
+    Unauthorized(String),
+
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
 
@@ -20,20 +35,87 @@ pub enum EmbeddingError {
     Disabled,
 }
 
+/// Object types embedded with the code model rather than the prose model.
+pub const CODE_OBJECT_TYPES: &[&str] = &["symbol", "FileChunk", "filechunk", "file_chunk"];
+
+/// Which embedding model, and therefore which vector space, an object belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingIndex {
+    Prose,
+    Code,
+}
+
+impl EmbeddingIndex {
+    pub fn for_object_type(object_type: &str) -> Self {
+        if CODE_OBJECT_TYPES.contains(&object_type) {
+            Self::Code
+        } else {
+            Self::Prose
+        }
+    }
+
+    /// SurrealQL condition limiting `type` to objects in this index.
+    pub fn type_condition(self) -> String {
+        let types = CODE_OBJECT_TYPES
+            .iter()
+            .map(|t| format!("'{}'", t))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self {
+            Self::Code => format!("type IN [{}]", types),
+            Self::Prose => format!("type NOT IN [{}]", types),
+        }
+    }
+}
+
 #[async_trait]
 pub trait EmbeddingService: Send + Sync {
     async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError>;
+    /// Embed search input. Providers with asymmetric query/document modes override this.
+    async fn generate_query_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        self.generate_embedding(text).await
+    }
+    /// Embed several texts, preserving input order. Providers with a batch API override this.
+    async fn generate_embeddings_batch(
+        &self,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for text in texts {
+            embeddings.push(self.generate_embedding(text).await?);
+        }
+        Ok(embeddings)
+    }
     fn dimension(&self) -> usize;
     fn is_enabled(&self) -> bool;
+    /// Model identifier, used to key cached embeddings.
+    fn model(&self) -> String {
+        String::new()
+    }
 }
 
+/// The single length every stored vector has, or `None` when embedding is
+/// off or code and prose models produce different lengths.
+pub fn indexable_dimension(
+    prose: &dyn EmbeddingService,
+    code: &dyn EmbeddingService,
+) -> Option<usize> {
+    let dimension = prose.dimension();
+    (prose.is_enabled() && dimension > 0 && (!code.is_enabled() || code.dimension() == dimension))
+        .then_some(dimension)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn create_embedding_service(
     provider: &str,
     openai_api_key: Option<String>,
     openrouter_api_key: Option<String>,
+    cohere_api_key: Option<String>,
     ollama_url: String,
     dimension: usize,
     model: String,
+    retry: EmbeddingRetryConfig,
+    batching: EmbeddingBatchConfig,
 ) -> Box<dyn EmbeddingService> {
     match provider.to_lowercase().as_str() {
         "openai" => {
@@ -43,7 +125,9 @@ pub fn create_embedding_service(
                     model,
                     "https://api.openai.com/v1".to_string(),
                     dimension,
-                ))
+                )
+                .with_retry(retry)
+                .with_batching(batching))
             } else {
                 tracing::warn!("OpenAI provider selected but no API key provided, using None");
                 Box::new(none::NoneEmbedding)
@@ -56,13 +140,89 @@ pub fn create_embedding_service(
                     model,
                     "https://openrouter.ai/api/v1".to_string(),
                     dimension,
-                ))
+                )
+                .with_retry(retry)
+                .with_batching(batching))
             } else {
                 tracing::warn!("OpenRouter provider selected but no API key provided, using None");
                 Box::new(none::NoneEmbedding)
             }
         }
-        "ollama" => Box::new(ollama::OllamaEmbedding::new(ollama_url, dimension, model)),
+        "cohere" => {
+            if let Some(api_key) = cohere_api_key.filter(|key| !key.trim().is_empty()) {
+                Box::new(cohere::CohereEmbedding::new(
+                    api_key,
+                    model,
+                    "https://api.cohere.com".to_string(),
+                    dimension,
+                )
+                .with_retry(retry)
+                .with_batching(batching))
+            } else {
+                tracing::warn!("Cohere provider selected but no API key provided, using None");
+                Box::new(none::NoneEmbedding)
+            }
+        }
+        "ollama" => Box::new(
+            ollama::OllamaEmbedding::new(ollama_url, dimension, model)
+                .with_retry(retry)
+                .with_batching(batching),
+        ),
         _ => Box::new(none::NoneEmbedding),
     }
 }
+
+/// Build the embedding service selected by persisted settings.
+pub fn create_embedding_service_from_settings(
+    settings: &SettingsConfig,
+) -> Box<dyn EmbeddingService> {
+    let (model, dimension) = settings.embedding_model_and_dimension();
+    create_embedding_service(
+        &settings.embedding_provider,
+        Some(settings.openai_api_key.clone()),
+        Some(settings.openrouter_api_key.clone()),
+        Some(settings.cohere_api_key.clone()),
+        settings.ollama_url.clone(),
+        dimension,
+        model,
+        EmbeddingRetryConfig::from_settings(settings),
+        EmbeddingBatchConfig::from_settings(settings),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct LengthEmbedding;
+
+    #[async_trait]
+    impl EmbeddingService for LengthEmbedding {
+        async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+            Ok(vec![text.len() as f32])
+        }
+
+        fn dimension(&self) -> usize {
+            1
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_batch_matches_per_item_order() {
+        let service = LengthEmbedding;
+        let texts: Vec<String> = ["a", "abc", "ab"].iter().map(|s| s.to_string()).collect();
+
+        let batch = service.generate_embeddings_batch(&texts).await.unwrap();
+        let mut single = Vec::new();
+        for text in &texts {
+            single.push(service.generate_embedding(text).await.unwrap());
+        }
+
+        assert_eq!(batch, single);
+        assert_eq!(batch, vec![vec![1.0], vec![3.0], vec![2.0]]);
+    }
+}
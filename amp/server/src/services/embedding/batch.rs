@@ -0,0 +1,102 @@
+use super::EmbeddingError;
+use crate::models::settings::SettingsConfig;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Most inputs OpenAI-compatible `/embeddings` endpoints accept per request.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// How providers split bulk embedding work into requests.
+#[derive(Debug, Clone)]
+pub struct EmbeddingBatchConfig {
+    /// Inputs per request for providers with a batch endpoint.
+    pub batch_size: usize,
+    /// Requests in flight at once.
+    pub concurrency: usize,
+}
+
+impl Default for EmbeddingBatchConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: MAX_BATCH_SIZE,
+            concurrency: 4,
+        }
+    }
+}
+
+impl EmbeddingBatchConfig {
+    pub fn from_settings(settings: &SettingsConfig) -> Self {
+        Self {
+            batch_size: settings.embedding_batch_size.clamp(1, MAX_BATCH_SIZE),
+            concurrency: settings.embedding_concurrency.max(1),
+        }
+    }
+}
+
+/// Run `embed` on each batch with at most `concurrency` in flight and return
+/// the embeddings in input order. The first failure cancels the rest.
+pub(crate) async fn embed_batches<F, Fut>(
+    batches: Vec<Vec<String>>,
+    concurrency: usize,
+    embed: F,
+) -> Result<Vec<Vec<f32>>, EmbeddingError>
+where
+    F: Fn(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<Vec<f32>>, EmbeddingError>> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut join_set = JoinSet::new();
+    let mut results: Vec<Option<Vec<Vec<f32>>>> = vec![None; batches.len()];
+    for (idx, batch) in batches.into_iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let request = embed(batch);
+        join_set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (idx, request.await)
+        });
+    }
+
+    while let Some(joined) = join_set.join_next().await {
+        let (idx, result) = joined
+            .map_err(|e| EmbeddingError::ApiError(format!("Embedding task failed: {}", e)))?;
+        results[idx] = Some(result?);
+    }
+    Ok(results.into_iter().flatten().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_embed_batches_bounds_concurrency_and_keeps_order() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let batches: Vec<Vec<String>> = (0..6).map(|i| vec![i.to_string()]).collect();
+
+        let embeddings = embed_batches(batches, 2, |batch| {
+            let (in_flight, peak) = (in_flight.clone(), peak.clone());
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                // Later batches finish first
+                let n: u64 = batch[0].parse().unwrap();
+                tokio::time::sleep(Duration::from_millis(30 - 5 * n)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(vec![vec![n as f32]])
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            embeddings,
+            (0..6).map(|n| vec![n as f32]).collect::<Vec<_>>()
+        );
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+}
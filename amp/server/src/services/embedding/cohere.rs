@@ -0,0 +1,273 @@
+use super::batch::embed_batches;
+use super::retry::send_with_retry;
+use super::{EmbeddingBatchConfig, EmbeddingError, EmbeddingRetryConfig, EmbeddingService};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// Most texts Cohere's `/v1/embed` accepts per request.
+const MAX_TEXTS_PER_REQUEST: usize = 96;
+
+#[derive(Clone)]
+pub struct CohereEmbedding {
+    client: Client,
+    api_key: String,
+    model: String,
+    base_url: String,
+    dimension: usize,
+    retry: EmbeddingRetryConfig,
+    batching: EmbeddingBatchConfig,
+}
+
+#[derive(Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    texts: &'a [String],
+    input_type: &'a str,
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+impl CohereEmbedding {
+    pub fn new(api_key: String, model: String, base_url: String, dimension: usize) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            model,
+            base_url,
+            dimension,
+            retry: EmbeddingRetryConfig::default(),
+            batching: EmbeddingBatchConfig::default(),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: EmbeddingRetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_batching(mut self, batching: EmbeddingBatchConfig) -> Self {
+        self.batching = batching;
+        self
+    }
+
+    async fn embed(&self, text: &str, input_type: &str) -> Result<Vec<f32>, EmbeddingError> {
+        self.embed_texts(&[text.to_string()], input_type)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| EmbeddingError::InvalidResponse("No embedding in response".to_string()))
+    }
+
+    /// Embed `texts` in one request; embeddings come back in request order.
+    async fn embed_texts(
+        &self,
+        texts: &[String],
+        input_type: &str,
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let request = EmbedRequest {
+            model: &self.model,
+            texts,
+            input_type,
+        };
+
+        let response = send_with_retry(&self.retry, || {
+            self.client
+                .post(format!("{}/v1/embed", self.base_url.trim_end_matches('/')))
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&request)
+        })
+        .await?;
+
+        let embed_response: EmbedResponse = response.json().await?;
+
+        if embed_response.embeddings.len() != texts.len() {
+            return Err(EmbeddingError::InvalidResponse(format!(
+                "Expected {} embeddings, got {}",
+                texts.len(),
+                embed_response.embeddings.len()
+            )));
+        }
+
+        // Cohere models have a fixed output size, so a mismatch means the
+        // configured dimension does not match the selected model.
+        if let Some(embedding) = embed_response
+            .embeddings
+            .iter()
+            .find(|embedding| embedding.len() != self.dimension)
+        {
+            return Err(EmbeddingError::InvalidResponse(format!(
+                "Expected {} dimensions from {}, got {}",
+                self.dimension,
+                self.model,
+                embedding.len()
+            )));
+        }
+
+        Ok(embed_response.embeddings)
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for CohereEmbedding {
+    async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        self.embed(text, "search_document").await
+    }
+
+    async fn generate_query_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        self.embed(text, "search_query").await
+    }
+
+    async fn generate_embeddings_batch(
+        &self,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let batch_size = self.batching.batch_size.clamp(1, MAX_TEXTS_PER_REQUEST);
+        let batches = texts.chunks(batch_size).map(<[String]>::to_vec).collect();
+        embed_batches(batches, self.batching.concurrency, |batch| {
+            let service = self.clone();
+            async move { service.embed_texts(&batch, "search_document").await }
+        })
+        .await
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    fn model(&self) -> String {
+        self.model.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::HeaderMap, routing::post, Json, Router};
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+
+    type Captured = Arc<Mutex<Vec<(Option<String>, Value)>>>;
+
+    async fn spawn_mock(dimension: usize) -> (String, Captured) {
+        let captured: Captured = Arc::new(Mutex::new(Vec::new()));
+
+        async fn embed(
+            State((captured, dimension)): State<(Captured, usize)>,
+            headers: HeaderMap,
+            Json(body): Json<Value>,
+        ) -> Json<Value> {
+            let auth = headers
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string());
+            // Each embedding starts with its text's length, so order can be checked
+            let embeddings: Vec<Vec<f32>> = body["texts"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|text| {
+                    let mut embedding = vec![0.25f32; dimension];
+                    embedding[0] = text.as_str().unwrap().len() as f32;
+                    embedding
+                })
+                .collect();
+            captured.lock().unwrap().push((auth, body));
+            Json(serde_json::json!({
+                "id": "test",
+                "embeddings": embeddings
+            }))
+        }
+
+        let app = Router::new()
+            .route("/v1/embed", post(embed))
+            .with_state((captured.clone(), dimension));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        (format!("http://{}", addr), captured)
+    }
+
+    #[tokio::test]
+    async fn test_request_shape_uses_input_type_per_call() {
+        let (base_url, captured) = spawn_mock(4).await;
+        let service = CohereEmbedding::new(
+            "co-key".to_string(),
+            "embed-english-v3.0".to_string(),
+            base_url,
+            4,
+        );
+
+        let document = service.generate_embedding("fn main() {}").await.unwrap();
+        let query = service.generate_query_embedding("entry point").await.unwrap();
+        assert_eq!(document.len(), 4);
+        assert_eq!(query.len(), 4);
+
+        let requests = captured.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].0.as_deref(), Some("Bearer co-key"));
+        assert_eq!(requests[0].1["model"], "embed-english-v3.0");
+        assert_eq!(requests[0].1["texts"], serde_json::json!(["fn main() {}"]));
+        assert_eq!(requests[0].1["input_type"], "search_document");
+        assert_eq!(requests[1].1["texts"], serde_json::json!(["entry point"]));
+        assert_eq!(requests[1].1["input_type"], "search_query");
+    }
+
+    #[tokio::test]
+    async fn test_dimension_mismatch_is_rejected() {
+        let (base_url, _captured) = spawn_mock(8).await;
+        let service = CohereEmbedding::new(
+            "co-key".to_string(),
+            "embed-english-v3.0".to_string(),
+            base_url,
+            4,
+        );
+
+        assert_eq!(service.dimension(), 4);
+        let result = service.generate_embedding("hello").await;
+        assert!(matches!(result, Err(EmbeddingError::InvalidResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_batch_sends_texts_together_in_order() {
+        let (base_url, captured) = spawn_mock(4).await;
+        let service = CohereEmbedding::new(
+            "co-key".to_string(),
+            "embed-english-v3.0".to_string(),
+            base_url,
+            4,
+        )
+        .with_batching(EmbeddingBatchConfig {
+            batch_size: 100,
+            concurrency: 4,
+        });
+
+        let texts: Vec<String> = (0..200).map(|i| "x".repeat(i + 1)).collect();
+        let batch = service.generate_embeddings_batch(&texts).await.unwrap();
+        let lengths: Vec<f32> = batch.iter().map(|embedding| embedding[0]).collect();
+        let expected: Vec<f32> = (1..=200).map(|len| len as f32).collect();
+        assert_eq!(lengths, expected);
+
+        // Capped at Cohere's 96 texts per request
+        let requests = captured.lock().unwrap();
+        let mut sizes: Vec<usize> = requests
+            .iter()
+            .map(|(_, body)| body["texts"].as_array().unwrap().len())
+            .collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![8, 96, 96]);
+        assert!(requests
+            .iter()
+            .all(|(_, body)| body["input_type"] == "search_document"));
+    }
+}
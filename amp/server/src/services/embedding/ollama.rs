@@ -1,13 +1,18 @@
-use super::{EmbeddingError, EmbeddingService};
+use super::batch::embed_batches;
+use super::retry::send_with_retry;
+use super::{EmbeddingBatchConfig, EmbeddingError, EmbeddingRetryConfig, EmbeddingService};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+#[derive(Clone)]
 pub struct OllamaEmbedding {
     client: Client,
     url: String,
     dimension: usize,
     model: String,
+    retry: EmbeddingRetryConfig,
+    batching: EmbeddingBatchConfig,
 }
 
 #[derive(Serialize)]
@@ -28,8 +33,20 @@ impl OllamaEmbedding {
             url,
             dimension,
             model,
+            retry: EmbeddingRetryConfig::default(),
+            batching: EmbeddingBatchConfig::default(),
         }
     }
+
+    pub fn with_retry(mut self, retry: EmbeddingRetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_batching(mut self, batching: EmbeddingBatchConfig) -> Self {
+        self.batching = batching;
+        self
+    }
 }
 
 #[async_trait]
@@ -40,22 +57,30 @@ impl EmbeddingService for OllamaEmbedding {
             prompt: text.to_string(),
         };
 
-        let response = self
-            .client
-            .post(format!("{}/api/embeddings", self.url))
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(EmbeddingError::ApiError(error_text));
-        }
+        let response = send_with_retry(&self.retry, || {
+            self.client
+                .post(format!("{}/api/embeddings", self.url))
+                .json(&request)
+        })
+        .await?;
 
         let ollama_response: OllamaResponse = response.json().await?;
         Ok(ollama_response.embedding)
     }
 
+    /// Ollama has no batch endpoint, so each text is its own concurrent request.
+    async fn generate_embeddings_batch(
+        &self,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let batches = texts.iter().map(|text| vec![text.clone()]).collect();
+        embed_batches(batches, self.batching.concurrency, |batch| {
+            let service = self.clone();
+            async move { Ok(vec![service.generate_embedding(&batch[0]).await?]) }
+        })
+        .await
+    }
+
     fn dimension(&self) -> usize {
         self.dimension
     }
@@ -63,4 +88,8 @@ impl EmbeddingService for OllamaEmbedding {
     fn is_enabled(&self) -> bool {
         true
     }
+
+    fn model(&self) -> String {
+        self.model.clone()
+    }
 }
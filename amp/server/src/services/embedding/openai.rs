@@ -1,20 +1,25 @@
-use super::{EmbeddingError, EmbeddingService};
+use super::batch::embed_batches;
+use super::retry::send_with_retry;
+use super::{EmbeddingBatchConfig, EmbeddingError, EmbeddingRetryConfig, EmbeddingService};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+#[derive(Clone)]
 pub struct OpenAIEmbedding {
     client: Client,
     api_key: String,
     model: String,
     base_url: String,
     dimension: usize,
+    retry: EmbeddingRetryConfig,
+    batching: EmbeddingBatchConfig,
 }
 
 #[derive(Serialize)]
-struct EmbeddingRequest {
-    model: String,
-    input: String,
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
 }
 
 #[derive(Deserialize)]
@@ -24,6 +29,8 @@ struct EmbeddingResponse {
 
 #[derive(Deserialize)]
 struct EmbeddingData {
+    #[serde(default)]
+    index: Option<usize>,
     embedding: Vec<f32>,
 }
 
@@ -35,44 +42,86 @@ impl OpenAIEmbedding {
             model,
             base_url,
             dimension,
+            retry: EmbeddingRetryConfig::default(),
+            batching: EmbeddingBatchConfig::default(),
         }
     }
-}
 
-#[async_trait]
-impl EmbeddingService for OpenAIEmbedding {
-    async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+    pub fn with_retry(mut self, retry: EmbeddingRetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_batching(mut self, batching: EmbeddingBatchConfig) -> Self {
+        self.batching = batching;
+        self
+    }
+
+    async fn embed_inputs(&self, input: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
         let request = EmbeddingRequest {
-            model: self.model.clone(),
-            input: text.to_string(),
+            model: &self.model,
+            input,
         };
 
-        let response = self
-            .client
-            .post(format!(
-                "{}/embeddings",
-                self.base_url.trim_end_matches('/')
-            ))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(EmbeddingError::ApiError(error_text));
-        }
+        let response = send_with_retry(&self.retry, || {
+            self.client
+                .post(format!(
+                    "{}/embeddings",
+                    self.base_url.trim_end_matches('/')
+                ))
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&request)
+        })
+        .await?;
+
+        let mut embedding_response: EmbeddingResponse = response.json().await?;
 
-        let embedding_response: EmbeddingResponse = response.json().await?;
+        if embedding_response.data.len() != input.len() {
+            return Err(EmbeddingError::InvalidResponse(format!(
+                "Expected {} embeddings, got {}",
+                input.len(),
+                embedding_response.data.len()
+            )));
+        }
 
+        // Providers are not required to return items in request order
         embedding_response
+            .data
+            .sort_by_key(|d| d.index.unwrap_or(usize::MAX));
+
+        Ok(embedding_response
             .data
             .into_iter()
-            .next()
             .map(|d| d.embedding)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for OpenAIEmbedding {
+    async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        self.embed_inputs(&[text.to_string()])
+            .await?
+            .into_iter()
+            .next()
             .ok_or_else(|| EmbeddingError::InvalidResponse("No embedding in response".to_string()))
     }
 
+    async fn generate_embeddings_batch(
+        &self,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let batches = texts
+            .chunks(self.batching.batch_size.max(1))
+            .map(<[String]>::to_vec)
+            .collect();
+        embed_batches(batches, self.batching.concurrency, |batch| {
+            let service = self.clone();
+            async move { service.embed_inputs(&batch).await }
+        })
+        .await
+    }
+
     fn dimension(&self) -> usize {
         self.dimension
     }
@@ -80,4 +129,215 @@ impl EmbeddingService for OpenAIEmbedding {
     fn is_enabled(&self) -> bool {
         true
     }
+
+    fn model(&self) -> String {
+        self.model.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::post, Json, Router};
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+
+    type Captured = Arc<Mutex<Vec<Value>>>;
+
+    fn fake_embedding(text: &str) -> Vec<f32> {
+        vec![text.len() as f32, text.bytes().map(f32::from).sum()]
+    }
+
+    /// Mock that embeds deterministically and returns items in reverse order
+    async fn spawn_mock() -> (String, Captured) {
+        let captured: Captured = Arc::new(Mutex::new(Vec::new()));
+
+        async fn embeddings(
+            State(captured): State<Captured>,
+            Json(body): Json<Value>,
+        ) -> Json<Value> {
+            let inputs: Vec<String> = body["input"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v.as_str().unwrap().to_string())
+                .collect();
+            captured.lock().unwrap().push(body);
+            let data: Vec<Value> = inputs
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(index, text)| {
+                    serde_json::json!({ "index": index, "embedding": fake_embedding(text) })
+                })
+                .collect();
+            Json(serde_json::json!({ "data": data }))
+        }
+
+        let app = Router::new()
+            .route("/embeddings", post(embeddings))
+            .with_state(captured.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        (format!("http://{}", addr), captured)
+    }
+
+    #[tokio::test]
+    async fn test_batch_preserves_order_and_matches_single() {
+        let (base_url, captured) = spawn_mock().await;
+        let service = OpenAIEmbedding::new(
+            "sk-test".to_string(),
+            "text-embedding-3-small".to_string(),
+            base_url,
+            2,
+        )
+        .with_batching(EmbeddingBatchConfig {
+            batch_size: 32,
+            concurrency: 4,
+        });
+
+        let texts: Vec<String> = (0..40).map(|i| format!("chunk {} {}", i, "x".repeat(i))).collect();
+        let batch = service.generate_embeddings_batch(&texts).await.unwrap();
+        assert_eq!(batch.len(), texts.len());
+        assert_eq!(captured.lock().unwrap().len(), 2);
+
+        for (text, embedding) in texts.iter().zip(&batch) {
+            let single = service.generate_embedding(text).await.unwrap();
+            assert_eq!(&single, embedding);
+            assert_eq!(embedding, &fake_embedding(text));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_splits_into_requests_of_at_most_100_inputs() {
+        let (base_url, captured) = spawn_mock().await;
+        let service = OpenAIEmbedding::new(
+            "sk-test".to_string(),
+            "text-embedding-3-small".to_string(),
+            base_url,
+            2,
+        );
+
+        let texts: Vec<String> = (0..250).map(|i| format!("chunk {}", i)).collect();
+        let batch = service.generate_embeddings_batch(&texts).await.unwrap();
+        let expected: Vec<Vec<f32>> = texts.iter().map(|t| fake_embedding(t)).collect();
+        assert_eq!(batch, expected);
+
+        // Requests run concurrently, so they can arrive in any order
+        let mut sizes: Vec<usize> = captured
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|body| body["input"].as_array().unwrap().len())
+            .collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![50, 100, 100]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_of_empty_input_makes_no_requests() {
+        let (base_url, captured) = spawn_mock().await;
+        let service = OpenAIEmbedding::new(
+            "sk-test".to_string(),
+            "text-embedding-3-small".to_string(),
+            base_url,
+            2,
+        );
+
+        let batch = service.generate_embeddings_batch(&[]).await.unwrap();
+        assert!(batch.is_empty());
+        assert!(captured.lock().unwrap().is_empty());
+    }
+
+    /// Mock that answers with `failures` error statuses before succeeding
+    async fn spawn_flaky_mock(status: u16, failures: usize) -> (String, Arc<Mutex<usize>>) {
+        let calls = Arc::new(Mutex::new(0usize));
+
+        let app = Router::new()
+            .route(
+                "/embeddings",
+                post(
+                    move |State(calls): State<Arc<Mutex<usize>>>, Json(body): Json<Value>| async move {
+                        let call = {
+                            let mut calls = calls.lock().unwrap();
+                            *calls += 1;
+                            *calls
+                        };
+                        if call <= failures {
+                            let status = axum::http::StatusCode::from_u16(status).unwrap();
+                            return (status, Json(serde_json::json!({ "error": "nope" })));
+                        }
+                        let text = body["input"][0].as_str().unwrap().to_string();
+                        (
+                            axum::http::StatusCode::OK,
+                            Json(serde_json::json!({
+                                "data": [{ "index": 0, "embedding": fake_embedding(&text) }]
+                            })),
+                        )
+                    },
+                ),
+            )
+            .with_state(calls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        (format!("http://{}", addr), calls)
+    }
+
+    fn fast_retry() -> EmbeddingRetryConfig {
+        EmbeddingRetryConfig {
+            max_attempts: 3,
+            base_delay: std::time::Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limit_then_succeeds() {
+        let (base_url, calls) = spawn_flaky_mock(429, 2).await;
+        let service = OpenAIEmbedding::new(
+            "sk-test".to_string(),
+            "text-embedding-3-small".to_string(),
+            base_url,
+            2,
+        )
+        .with_retry(fast_retry());
+
+        let embedding = service.generate_embedding("hello").await.unwrap();
+        assert_eq!(embedding, fake_embedding("hello"));
+        assert_eq!(*calls.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_exhaustion_and_auth_errors_are_typed() {
+        let (base_url, calls) = spawn_flaky_mock(429, usize::MAX).await;
+        let service = OpenAIEmbedding::new(
+            "sk-test".to_string(),
+            "text-embedding-3-small".to_string(),
+            base_url,
+            2,
+        )
+        .with_retry(fast_retry());
+        let err = service.generate_embedding("hello").await.unwrap_err();
+        assert!(matches!(err, EmbeddingError::RateLimited(_)), "{:?}", err);
+        assert_eq!(*calls.lock().unwrap(), 3);
+
+        let (base_url, calls) = spawn_flaky_mock(401, usize::MAX).await;
+        let service = OpenAIEmbedding::new(
+            "sk-bad".to_string(),
+            "text-embedding-3-small".to_string(),
+            base_url,
+            2,
+        )
+        .with_retry(fast_retry());
+        let err = service.generate_embedding("hello").await.unwrap_err();
+        assert!(matches!(err, EmbeddingError::Unauthorized(_)), "{:?}", err);
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
 }
@@ -0,0 +1,58 @@
+use super::{EmbeddingError, EmbeddingService};
+use async_trait::async_trait;
+use std::sync::{Arc, RwLock};
+
+/// Embedding service that can be swapped at runtime.
+///
+/// Every call clones the current inner service before awaiting, so requests
+/// already in flight finish on the service they started with.
+pub struct ReloadableEmbedding {
+    current: RwLock<Arc<dyn EmbeddingService>>,
+}
+
+impl ReloadableEmbedding {
+    pub fn new(service: Arc<dyn EmbeddingService>) -> Self {
+        Self {
+            current: RwLock::new(service),
+        }
+    }
+
+    pub fn current(&self) -> Arc<dyn EmbeddingService> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Install `service` for new calls and return the one it replaced.
+    pub fn replace(&self, service: Arc<dyn EmbeddingService>) -> Arc<dyn EmbeddingService> {
+        std::mem::replace(&mut *self.current.write().unwrap(), service)
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for ReloadableEmbedding {
+    async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        self.current().generate_embedding(text).await
+    }
+
+    async fn generate_query_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        self.current().generate_query_embedding(text).await
+    }
+
+    async fn generate_embeddings_batch(
+        &self,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        self.current().generate_embeddings_batch(texts).await
+    }
+
+    fn dimension(&self) -> usize {
+        self.current().dimension()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.current().is_enabled()
+    }
+
+    fn model(&self) -> String {
+        self.current().model()
+    }
+}
@@ -0,0 +1,114 @@
+use super::EmbeddingError;
+use crate::models::settings::SettingsConfig;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Retry policy for embedding provider HTTP calls.
+#[derive(Debug, Clone)]
+pub struct EmbeddingRetryConfig {
+    /// Total attempts including the first request; 1 disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each subsequent retry.
+    pub base_delay: Duration,
+}
+
+impl Default for EmbeddingRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl EmbeddingRetryConfig {
+    pub fn from_settings(settings: &SettingsConfig) -> Self {
+        Self {
+            max_attempts: settings.embedding_retry_attempts.max(1),
+            base_delay: Duration::from_millis(settings.embedding_retry_base_delay_ms),
+        }
+    }
+
+    /// Exponential backoff with jitter in `[delay / 2, delay]`.
+    fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.min(16)));
+        let half = delay / 2;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        half + half.mul_f64(f64::from(nanos % 1000) / 1000.0)
+    }
+}
+
+/// Send a request built by `build`, retrying on 429, 5xx, and transport errors.
+/// Auth failures return immediately as `Unauthorized`; other 4xx as `ApiError`.
+pub(crate) async fn send_with_retry<F>(
+    retry: &EmbeddingRetryConfig,
+    build: F,
+) -> Result<Response, EmbeddingError>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let max_attempts = retry.max_attempts.max(1);
+    let mut attempt = 1;
+
+    loop {
+        let error = match build().send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                match status {
+                    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                        return Err(EmbeddingError::Unauthorized(body));
+                    }
+                    StatusCode::TOO_MANY_REQUESTS => EmbeddingError::RateLimited(body),
+                    status if status.is_server_error() => {
+                        EmbeddingError::ApiError(format!("{}: {}", status, body))
+                    }
+                    _ => return Err(EmbeddingError::ApiError(body)),
+                }
+            }
+            Err(e) if e.is_timeout() || e.is_connect() => EmbeddingError::RequestFailed(e),
+            Err(e) => return Err(EmbeddingError::RequestFailed(e)),
+        };
+
+        if attempt >= max_attempts {
+            return Err(error);
+        }
+
+        let delay = retry.backoff(attempt - 1);
+        tracing::warn!(
+            "Embedding request failed (attempt {}/{}), retrying in {:?}: {}",
+            attempt,
+            max_attempts,
+            delay,
+            error
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_within_jitter_bounds() {
+        let retry = EmbeddingRetryConfig::default();
+        for n in 0..4 {
+            let full = Duration::from_millis(500 * 2u64.pow(n));
+            let delay = retry.backoff(n);
+            assert!(
+                delay >= full / 2 && delay <= full,
+                "{:?} vs {:?}",
+                delay,
+                full
+            );
+        }
+    }
+}
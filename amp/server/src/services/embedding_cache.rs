@@ -0,0 +1,214 @@
+use crate::database::Database;
+use crate::services::embedding::{EmbeddingError, EmbeddingService};
+use crate::surreal_json::take_json_values;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use tokio::time::{timeout, Duration};
+
+/// SHA-256 of the exact text sent to the provider.
+pub fn content_hash(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Fetch cached embeddings for `hashes`; lookup failures are treated as misses.
+pub async fn select_cached(
+    db: &Database,
+    hashes: &[String],
+    model: &str,
+    dimension: usize,
+) -> HashMap<String, Vec<f32>> {
+    let mut cached = HashMap::new();
+    if hashes.is_empty() {
+        return cached;
+    }
+
+    let query = "SELECT content_hash, embedding FROM embedding_cache WHERE content_hash IN $hashes AND model = $model AND dimension = $dimension";
+    let result = timeout(
+        Duration::from_secs(5),
+        db.client
+            .query(query)
+            .bind(("hashes", hashes.to_vec()))
+            .bind(("model", model.to_string()))
+            .bind(("dimension", dimension as i64)),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(mut response)) => {
+            for row in take_json_values(&mut response, 0) {
+                let hash = row.get("content_hash").and_then(Value::as_str);
+                let embedding = row
+                    .get("embedding")
+                    .and_then(|v| serde_json::from_value::<Vec<f32>>(v.clone()).ok());
+                if let (Some(hash), Some(embedding)) = (hash, embedding) {
+                    if embedding.len() == dimension {
+                        cached.insert(hash.to_string(), embedding);
+                    }
+                }
+            }
+        }
+        Ok(Err(e)) => tracing::warn!("Embedding cache lookup failed: {}", e),
+        Err(_) => tracing::warn!("Embedding cache lookup timed out"),
+    }
+
+    cached
+}
+
+/// Insert or refresh cache entries; existing hit counts are preserved.
+pub async fn upsert_cached(
+    db: &Database,
+    entries: &[(String, Vec<f32>)],
+    model: &str,
+    dimension: usize,
+) {
+    if entries.is_empty() {
+        return;
+    }
+
+    let entries: Vec<Value> = entries
+        .iter()
+        .map(|(hash, embedding)| serde_json::json!({ "hash": hash, "embedding": embedding }))
+        .collect();
+    let query = r#"
+        FOR $entry IN $entries {
+            UPSERT type::thing('embedding_cache', [$entry.hash, $model, $dimension]) SET
+                content_hash = $entry.hash,
+                model = $model,
+                dimension = $dimension,
+                embedding = $entry.embedding;
+        }
+    "#;
+    let result = timeout(
+        Duration::from_secs(10),
+        db.client
+            .query(query)
+            .bind(("entries", entries))
+            .bind(("model", model.to_string()))
+            .bind(("dimension", dimension as i64)),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(response)) => {
+            if let Err(e) = response.check() {
+                tracing::warn!("Failed to store embedding cache entries: {}", e);
+            }
+        }
+        Ok(Err(e)) => tracing::warn!("Failed to store embedding cache entries: {}", e),
+        Err(_) => tracing::warn!("Embedding cache write timed out"),
+    }
+}
+
+async fn record_hits(db: &Database, hashes: Vec<String>, model: &str, dimension: usize) {
+    let query = "UPDATE embedding_cache SET hits += 1 WHERE content_hash IN $hashes AND model = $model AND dimension = $dimension";
+    let result = timeout(
+        Duration::from_secs(5),
+        db.client
+            .query(query)
+            .bind(("hashes", hashes))
+            .bind(("model", model.to_string()))
+            .bind(("dimension", dimension as i64)),
+    )
+    .await;
+
+    if !matches!(result, Ok(Ok(_))) {
+        tracing::warn!("Failed to record embedding cache hits");
+    }
+}
+
+/// Total cache hits across all entries, for analytics.
+pub async fn total_hits(db: &Database) -> i64 {
+    let query = "SELECT math::sum(hits) AS total FROM embedding_cache GROUP ALL";
+    match timeout(Duration::from_secs(5), db.client.query(query)).await {
+        Ok(Ok(mut response)) => take_json_values(&mut response, 0)
+            .first()
+            .and_then(|row| row.get("total"))
+            .and_then(Value::as_i64)
+            .unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Embed `texts` in order, calling the provider only for content not already cached.
+pub async fn embed_with_cache(
+    db: &Database,
+    service: &dyn EmbeddingService,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+    let model = &service.model();
+    let dimension = service.dimension();
+    let hashes: Vec<String> = texts.iter().map(|text| content_hash(text)).collect();
+
+    let unique: Vec<String> = hashes
+        .iter()
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let mut cached = select_cached(db, &unique, model, dimension).await;
+    let hit_hashes: Vec<String> = cached.keys().cloned().collect();
+
+    // Embed each missing text once, even if it appears several times
+    let mut miss_hashes = Vec::new();
+    let mut miss_texts = Vec::new();
+    let mut seen = HashSet::new();
+    for (hash, text) in hashes.iter().zip(texts) {
+        if !cached.contains_key(hash) && seen.insert(hash.clone()) {
+            miss_hashes.push(hash.clone());
+            miss_texts.push(text.clone());
+        }
+    }
+
+    if !miss_texts.is_empty() {
+        let generated = service.generate_embeddings_batch(&miss_texts).await?;
+        if generated.len() != miss_texts.len() {
+            return Err(EmbeddingError::InvalidResponse(format!(
+                "Expected {} embeddings, got {}",
+                miss_texts.len(),
+                generated.len()
+            )));
+        }
+        let entries: Vec<(String, Vec<f32>)> = miss_hashes.into_iter().zip(generated).collect();
+        upsert_cached(db, &entries, model, dimension).await;
+        cached.extend(entries);
+    }
+
+    if !hit_hashes.is_empty() {
+        record_hits(db, hit_hashes, model, dimension).await;
+    }
+
+    Ok(hashes
+        .iter()
+        .map(|hash| cached.get(hash).cloned().unwrap_or_default())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::CountingEmbedding;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_embed_with_cache_only_calls_provider_on_miss() {
+        let db = Database::new("memory").await.unwrap();
+        db.initialize_schema().await.unwrap();
+        let service = CountingEmbedding::default();
+        let calls = service.texts_embedded.clone();
+
+        let texts = vec!["alpha".to_string(), "beta".to_string(), "alpha".to_string()];
+        let first = embed_with_cache(&db, &service, &texts).await.unwrap();
+        assert_eq!(first, vec![vec![5.0, 1.0], vec![4.0, 1.0], vec![5.0, 1.0]]);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(total_hits(&db).await, 0);
+
+        let texts = vec!["beta".to_string(), "gamma".to_string(), "alpha".to_string()];
+        let second = embed_with_cache(&db, &service, &texts).await.unwrap();
+        assert_eq!(second, vec![vec![4.0, 1.0], vec![5.0, 1.0], vec![5.0, 1.0]]);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(total_hits(&db).await, 2);
+    }
+}
@@ -0,0 +1,693 @@
+//! Declaration and import extraction for languages whose tree-sitter grammar
+//! cannot be built alongside the other dependencies (see the commented-out
+//! grammars in Cargo.toml). A small scanner stands in for the grammar, and
+//! symbols are positioned like the tree-sitter extractors report them: by the
+//! 0-based row and byte range of the symbol's name.
+
+use crate::services::codebase_parser::{FileDependencies, ParsedSymbol};
+use std::path::Path;
+
+/// Symbols and dependencies of `content`, or `None` when `language` has no
+/// fallback scanner.
+pub fn parse(
+    language: &str,
+    content: &str,
+    file_path: &Path,
+) -> Option<(Vec<ParsedSymbol>, FileDependencies)> {
+    let extracted = match language {
+        "php" => php::extract(content),
+        "shell" => shell::extract(content),
+        _ => return None,
+    };
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let symbols = extracted
+        .symbols
+        .into_iter()
+        .map(|declared| {
+            let row = line_starts.partition_point(|&start| start <= declared.start) - 1;
+            ParsedSymbol {
+                start_line: row,
+                end_line: row,
+                start_byte: declared.start,
+                end_byte: declared.start + declared.name.len(),
+                name: declared.name,
+                symbol_type: declared.symbol_type.to_string(),
+                file_path: file_path.to_string_lossy().to_string(),
+                language: language.to_string(),
+                visibility: declared.visibility,
+            }
+        })
+        .collect();
+    Some((
+        symbols,
+        FileDependencies {
+            imports: extracted.imports,
+            exports: extracted.exports,
+        },
+    ))
+}
+
+#[derive(Debug, Default)]
+struct Extracted {
+    symbols: Vec<Declared>,
+    imports: Vec<String>,
+    exports: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Declared {
+    name: String,
+    symbol_type: &'static str,
+    /// Byte offset of the name
+    start: usize,
+    visibility: Option<String>,
+}
+
+mod php {
+    use super::{Declared, Extracted};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Kind<'a> {
+        /// Identifier, keyword or qualified name (`App\Models\User`)
+        Word(&'a str),
+        Variable,
+        /// Contents of a quoted string
+        Str(String),
+        /// `::`, `->` or `?->`, after which keywords are member names
+        Access,
+        Punct(u8),
+    }
+
+    #[derive(Debug)]
+    struct Token<'a> {
+        kind: Kind<'a>,
+        start: usize,
+    }
+
+    /// What a `{` opened
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Body {
+        Class,
+        Function,
+        Other,
+    }
+
+    fn is_word_byte(b: u8) -> bool {
+        b.is_ascii_alphanumeric() || b == b'_' || b == b'\\' || b >= 0x80
+    }
+
+    /// Tokens of the code between `<?php`/`<?=` and `?>`, without comments.
+    fn tokenize(content: &str) -> Vec<Token<'_>> {
+        let bytes = content.as_bytes();
+        let mut tokens = Vec::new();
+        let mut in_code = false;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if !in_code {
+                let Some(offset) = content[i..].find("<?") else {
+                    break;
+                };
+                i += offset + 2;
+                if content[i..].starts_with("php") {
+                    i += 3;
+                } else if content[i..].starts_with('=') {
+                    i += 1;
+                }
+                in_code = true;
+                continue;
+            }
+
+            let rest = &content[i..];
+            match bytes[i] {
+                b'?' if rest.starts_with("?>") => {
+                    // A closing tag also ends the statement
+                    tokens.push(Token {
+                        kind: Kind::Punct(b';'),
+                        start: i,
+                    });
+                    in_code = false;
+                    i += 2;
+                }
+                b'?' if rest.starts_with("?->") => {
+                    tokens.push(Token {
+                        kind: Kind::Access,
+                        start: i,
+                    });
+                    i += 3;
+                }
+                b':' if rest.starts_with("::") => {
+                    tokens.push(Token {
+                        kind: Kind::Access,
+                        start: i,
+                    });
+                    i += 2;
+                }
+                b'-' if rest.starts_with("->") => {
+                    tokens.push(Token {
+                        kind: Kind::Access,
+                        start: i,
+                    });
+                    i += 2;
+                }
+                // Attributes (`#[Route]`) are plain brackets, not comments
+                b'#' if rest.starts_with("#[") => i += 1,
+                b'#' => i = line_comment_end(content, i),
+                b'/' if rest.starts_with("//") => i = line_comment_end(content, i),
+                b'/' if rest.starts_with("/*") => {
+                    i = rest[2..]
+                        .find("*/")
+                        .map_or(bytes.len(), |end| i + 2 + end + 2);
+                }
+                b'<' if rest.starts_with("<<<") => i = heredoc_end(content, i + 3),
+                quote @ (b'\'' | b'"') => {
+                    let mut value = Vec::new();
+                    let mut j = i + 1;
+                    while j < bytes.len() && bytes[j] != quote {
+                        if bytes[j] == b'\\' && j + 1 < bytes.len() {
+                            j += 1;
+                        }
+                        value.push(bytes[j]);
+                        j += 1;
+                    }
+                    tokens.push(Token {
+                        kind: Kind::Str(String::from_utf8_lossy(&value).into_owned()),
+                        start: i,
+                    });
+                    i = j + 1;
+                }
+                b'$' => {
+                    let start = i;
+                    i += 1;
+                    while i < bytes.len() && is_word_byte(bytes[i]) {
+                        i += 1;
+                    }
+                    tokens.push(Token {
+                        kind: Kind::Variable,
+                        start,
+                    });
+                }
+                b if is_word_byte(b) => {
+                    let start = i;
+                    while i < bytes.len() && is_word_byte(bytes[i]) {
+                        i += 1;
+                    }
+                    tokens.push(Token {
+                        kind: Kind::Word(&content[start..i]),
+                        start,
+                    });
+                }
+                b if b.is_ascii_whitespace() => i += 1,
+                b => {
+                    tokens.push(Token {
+                        kind: Kind::Punct(b),
+                        start: i,
+                    });
+                    i += 1;
+                }
+            }
+        }
+        tokens
+    }
+
+    /// End of a `//` or `#` comment: the newline, or a closing tag on the same line.
+    fn line_comment_end(content: &str, start: usize) -> usize {
+        let line_end = content[start..]
+            .find('\n')
+            .map_or(content.len(), |end| start + end);
+        content[start..line_end]
+            .find("?>")
+            .map_or(line_end, |tag| start + tag)
+    }
+
+    /// End of a heredoc or nowdoc whose `<<<` ends at `start`: just past the
+    /// closing identifier, which may be indented.
+    fn heredoc_end(content: &str, start: usize) -> usize {
+        let Some(line_end) = content[start..].find('\n').map(|end| start + end) else {
+            return content.len();
+        };
+        let label = content[start..line_end]
+            .trim()
+            .trim_matches(|c| c == '\'' || c == '"');
+        if label.is_empty() {
+            return line_end;
+        }
+        let mut offset = line_end + 1;
+        for line in content[line_end + 1..].split_inclusive('\n') {
+            let body = line.trim_start();
+            if let Some(after) = body.strip_prefix(label) {
+                if !after.bytes().next().is_some_and(is_word_byte) {
+                    return offset + (line.len() - body.len()) + label.len();
+                }
+            }
+            offset += line.len();
+        }
+        content.len()
+    }
+
+    pub(super) fn extract(content: &str) -> Extracted {
+        let tokens = tokenize(content);
+        let mut extracted = Extracted::default();
+        let mut bodies: Vec<Body> = Vec::new();
+        // What the next `{` opens, when a declaration is waiting for its body
+        let mut pending: Option<Body> = None;
+        let mut visibility: Option<String> = None;
+
+        let word = |index: usize| match tokens.get(index).map(|token| &token.kind) {
+            Some(Kind::Word(word)) => Some(*word),
+            _ => None,
+        };
+
+        let mut i = 0;
+        while i < tokens.len() {
+            let after_access = i > 0 && tokens[i - 1].kind == Kind::Access;
+            let in_class = bodies.last() == Some(&Body::Class);
+            let top_level = !bodies
+                .iter()
+                .any(|body| matches!(body, Body::Class | Body::Function));
+
+            match &tokens[i].kind {
+                Kind::Punct(b'{') => {
+                    bodies.push(pending.take().unwrap_or(Body::Other));
+                    visibility = None;
+                }
+                Kind::Punct(b'}') => {
+                    bodies.pop();
+                    visibility = None;
+                }
+                Kind::Punct(b';') => {
+                    // An abstract or interface method has no body
+                    pending = None;
+                    visibility = None;
+                }
+                Kind::Word(keyword) if !after_access => {
+                    let keyword = keyword.to_ascii_lowercase();
+                    match keyword.as_str() {
+                        "public" | "protected" | "private" => {
+                            visibility = Some(keyword);
+                        }
+                        "class" | "interface" | "trait" | "enum" => {
+                            // Anonymous classes (`new class { ... }`) have a body but no name
+                            pending = Some(Body::Class);
+                            if let Some(name) = word(i + 1) {
+                                let symbol_type = match keyword.as_str() {
+                                    "class" => "class",
+                                    "interface" => "interface",
+                                    "trait" => "trait",
+                                    _ => "enum",
+                                };
+                                declare(&mut extracted, name, symbol_type, &tokens[i + 1], None);
+                                if top_level {
+                                    extracted.exports.push(name.to_string());
+                                }
+                                i += 1;
+                            }
+                        }
+                        "function" => {
+                            pending = Some(Body::Function);
+                            // Skip the `&` of functions returning by reference
+                            let name_index = match tokens.get(i + 1).map(|token| &token.kind) {
+                                Some(Kind::Punct(b'&')) => i + 2,
+                                _ => i + 1,
+                            };
+                            // Closures go straight to their parameter list
+                            if let Some(name) = word(name_index) {
+                                if in_class {
+                                    declare(
+                                        &mut extracted,
+                                        name,
+                                        "method",
+                                        &tokens[name_index],
+                                        visibility.clone(),
+                                    );
+                                } else {
+                                    declare(
+                                        &mut extracted,
+                                        name,
+                                        "function",
+                                        &tokens[name_index],
+                                        None,
+                                    );
+                                    if top_level {
+                                        extracted.exports.push(name.to_string());
+                                    }
+                                }
+                                i = name_index;
+                            }
+                        }
+                        // Namespace imports; inside a class `use` imports traits, and
+                        // after `)` it lists a closure's captured variables
+                        "use"
+                            if !in_class
+                                && !matches!(
+                                    i.checked_sub(1).map(|prev| &tokens[prev].kind),
+                                    Some(Kind::Punct(b')'))
+                                ) =>
+                        {
+                            i = use_declaration(&tokens, i + 1, &mut extracted.imports);
+                            continue;
+                        }
+                        "require" | "require_once" | "include" | "include_once" => {
+                            let path = tokens[i + 1..]
+                                .iter()
+                                .take_while(|token| token.kind != Kind::Punct(b';'))
+                                .find_map(|token| match &token.kind {
+                                    Kind::Str(path) => Some(path.clone()),
+                                    _ => None,
+                                });
+                            extracted.imports.extend(path);
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        extracted
+    }
+
+    fn declare(
+        extracted: &mut Extracted,
+        name: &str,
+        symbol_type: &'static str,
+        token: &Token<'_>,
+        visibility: Option<String>,
+    ) {
+        extracted.symbols.push(Declared {
+            name: name.to_string(),
+            symbol_type,
+            start: token.start,
+            visibility,
+        });
+    }
+
+    /// Names imported by the `use` declaration starting at `start`, including
+    /// group imports (`use App\{Foo, Bar as Baz};`). Returns the index after
+    /// the closing `;`.
+    fn use_declaration(tokens: &[Token<'_>], start: usize, imports: &mut Vec<String>) -> usize {
+        let mut prefix = String::new();
+        let mut current = String::new();
+        let mut alias = false;
+        let mut push = |current: &mut String, prefix: &str| {
+            if !current.is_empty() {
+                imports.push(
+                    format!("{}{}", prefix, current)
+                        .trim_start_matches('\\')
+                        .to_string(),
+                );
+            }
+            current.clear();
+        };
+
+        let mut i = start;
+        while i < tokens.len() {
+            match &tokens[i].kind {
+                Kind::Punct(b';') => {
+                    push(&mut current, &prefix);
+                    return i + 1;
+                }
+                Kind::Punct(b'{') => {
+                    prefix = std::mem::take(&mut current);
+                }
+                Kind::Punct(b',') | Kind::Punct(b'}') => {
+                    push(&mut current, &prefix);
+                    alias = false;
+                }
+                Kind::Word(word) if word.eq_ignore_ascii_case("as") => alias = true,
+                Kind::Word(word)
+                    if current.is_empty()
+                        && (word.eq_ignore_ascii_case("function")
+                            || word.eq_ignore_ascii_case("const")) => {}
+                Kind::Word(word) if !alias => current.push_str(word),
+                _ => {}
+            }
+            i += 1;
+        }
+        push(&mut current, &prefix);
+        i
+    }
+}
+
+mod shell {
+    use super::{Declared, Extracted};
+
+    #[derive(Debug, PartialEq)]
+    enum Token<'a> {
+        /// A word as written (`raw`) and with quoting removed (`value`)
+        Word {
+            raw: &'a str,
+            value: String,
+            start: usize,
+        },
+        /// Newline, `;`, `&`, `|`, `(` or `)`
+        Separator(u8),
+    }
+
+    /// Words and separators, without comments and heredoc bodies.
+    fn tokenize(content: &str) -> Vec<Token<'_>> {
+        let bytes = content.as_bytes();
+        let mut tokens = Vec::new();
+        let mut heredocs: Vec<String> = Vec::new();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let rest = &content[i..];
+            match bytes[i] {
+                b'\n' => {
+                    tokens.push(Token::Separator(b'\n'));
+                    i += 1;
+                    for delimiter in heredocs.drain(..) {
+                        i = heredoc_end(content, i, &delimiter);
+                    }
+                }
+                b'\\' if rest.starts_with("\\\n") => i += 2,
+                b'#' => i = rest.find('\n').map_or(bytes.len(), |end| i + end),
+                b';' | b'&' | b'|' | b'(' | b')' => {
+                    tokens.push(Token::Separator(bytes[i]));
+                    i += 1;
+                }
+                // A here-string's word is an ordinary argument
+                b'<' if rest.starts_with("<<<") => i += 3,
+                b'<' if rest.starts_with("<<") => {
+                    i += if rest.starts_with("<<-") { 3 } else { 2 };
+                    while i < bytes.len() && (bytes[i] == b' ' || bytes[i] == b'\t') {
+                        i += 1;
+                    }
+                    let end = word_end(bytes, i);
+                    heredocs.push(unquote(&content[i..end]));
+                    i = end;
+                }
+                b'<' | b'>' => i += 1,
+                b if b.is_ascii_whitespace() => i += 1,
+                _ => {
+                    let end = word_end(bytes, i);
+                    tokens.push(Token::Word {
+                        raw: &content[i..end],
+                        value: unquote(&content[i..end]),
+                        start: i,
+                    });
+                    i = end;
+                }
+            }
+        }
+        tokens
+    }
+
+    /// End of the word starting at `start`, treating quotes, escapes and
+    /// `$(...)`/`${...}` expansions as part of it.
+    fn word_end(bytes: &[u8], start: usize) -> usize {
+        let closing = |from: usize, quote: u8| {
+            bytes[from..]
+                .iter()
+                .position(|&b| b == quote)
+                .map_or(bytes.len(), |end| from + end + 1)
+        };
+        let mut i = start;
+        while i < bytes.len() {
+            match bytes[i] {
+                b if b.is_ascii_whitespace() => break,
+                b';' | b'&' | b'|' | b'(' | b')' | b'<' | b'>' => break,
+                b'\\' => i += 2,
+                b'\'' => i = closing(i + 1, b'\''),
+                b'`' => i = closing(i + 1, b'`'),
+                b'"' => {
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != b'"' {
+                        i += if bytes[i] == b'\\' { 2 } else { 1 };
+                    }
+                    i += 1;
+                }
+                b'$' if matches!(bytes.get(i + 1), Some(b'(') | Some(b'{')) => {
+                    let (open, close) = if bytes[i + 1] == b'(' {
+                        (b'(', b')')
+                    } else {
+                        (b'{', b'}')
+                    };
+                    let mut depth = 0;
+                    i += 1;
+                    while i < bytes.len() {
+                        if bytes[i] == open {
+                            depth += 1;
+                        } else if bytes[i] == close {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+        i.min(bytes.len())
+    }
+
+    /// `word` with quotes and backslash escapes removed; expansions are kept as written.
+    fn unquote(word: &str) -> String {
+        let mut value = String::with_capacity(word.len());
+        let mut chars = word.chars().peekable();
+        let mut double_quoted = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => double_quoted = !double_quoted,
+                '\'' if !double_quoted => value.extend(chars.by_ref().take_while(|&c| c != '\'')),
+                '\\' => value.extend(chars.next()),
+                '$' if matches!(chars.peek(), Some('(' | '{')) => {
+                    let open = chars.next().unwrap_or_default();
+                    let close = if open == '(' { ')' } else { '}' };
+                    value.push(c);
+                    value.push(open);
+                    let mut depth = 1;
+                    for c in chars.by_ref() {
+                        value.push(c);
+                        if c == open {
+                            depth += 1;
+                        } else if c == close {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                    }
+                }
+                c => value.push(c),
+            }
+        }
+        value
+    }
+
+    /// Start of the line after the heredoc body that begins at `start`.
+    fn heredoc_end(content: &str, start: usize, delimiter: &str) -> usize {
+        let mut offset = start;
+        for line in content[start..].split_inclusive('\n') {
+            offset += line.len();
+            if line.trim_start_matches('\t').trim_end() == delimiter {
+                return offset;
+            }
+        }
+        content.len()
+    }
+
+    /// Variable name of an assignment word (`NAME=value`, `NAME+=value`, `NAME[i]=value`).
+    fn assigned_name(word: &str) -> Option<&str> {
+        let end = word.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))?;
+        let name = &word[..end];
+        let rest = &word[end..];
+        let starts_like_name = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+        let assigns = rest.starts_with('=')
+            || rest.starts_with("+=")
+            || (rest.starts_with('[') && rest.contains("]="));
+        (starts_like_name && assigns).then_some(name)
+    }
+
+    pub(super) fn extract(content: &str) -> Extracted {
+        let tokens = tokenize(content);
+        let mut extracted = Extracted::default();
+        // Whether the next word begins a command
+        let mut at_start = true;
+        // Inside `export`/`local`/... arguments; true for `export`
+        let mut declaring: Option<bool> = None;
+
+        let mut i = 0;
+        while i < tokens.len() {
+            let Token::Word { raw, start, .. } = &tokens[i] else {
+                at_start = true;
+                declaring = None;
+                i += 1;
+                continue;
+            };
+            let raw = *raw;
+
+            if let Some(exported) = declaring {
+                let name = assigned_name(raw);
+                if let Some(name) = name {
+                    declare(&mut extracted, name, "variable", *start);
+                }
+                if exported && !raw.starts_with('-') {
+                    extracted.exports.push(name.unwrap_or(raw).to_string());
+                }
+            } else if at_start {
+                match raw {
+                    // Reserved words that leave the next word in command position
+                    "then" | "do" | "else" | "elif" | "if" | "while" | "until" | "!" | "{"
+                    | "}" | "time" => {}
+                    "function" => {
+                        if let Some(Token::Word {
+                            raw: name, start, ..
+                        }) = tokens.get(i + 1)
+                        {
+                            declare(&mut extracted, name, "function", *start);
+                            extracted.exports.push(name.to_string());
+                            i += 1;
+                        }
+                    }
+                    "export" | "local" | "readonly" | "declare" | "typeset" => {
+                        declaring = Some(raw == "export");
+                        at_start = false;
+                    }
+                    "source" | "." => {
+                        if let Some(Token::Word { value, .. }) = tokens.get(i + 1) {
+                            extracted.imports.push(value.clone());
+                            i += 1;
+                        }
+                        at_start = false;
+                    }
+                    _ => {
+                        if let Some(name) = assigned_name(raw) {
+                            // Assignments can prefix a command, which keeps command position
+                            declare(&mut extracted, name, "variable", *start);
+                        } else if tokens.get(i + 1) == Some(&Token::Separator(b'('))
+                            && tokens.get(i + 2) == Some(&Token::Separator(b')'))
+                        {
+                            declare(&mut extracted, raw, "function", *start);
+                            extracted.exports.push(raw.to_string());
+                            i += 2;
+                        } else {
+                            at_start = false;
+                        }
+                    }
+                }
+            }
+            i += 1;
+        }
+        extracted
+    }
+
+    fn declare(extracted: &mut Extracted, name: &str, symbol_type: &'static str, start: usize) {
+        extracted.symbols.push(Declared {
+            name: name.to_string(),
+            symbol_type,
+            start,
+            visibility: None,
+        });
+    }
+}
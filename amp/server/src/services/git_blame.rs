@@ -0,0 +1,169 @@
+use crate::services::codebase_parser::ParsedSymbol;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Last commit to touch a symbol's line range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolBlame {
+    pub symbol: String,
+    /// Zero-based, like `ParsedSymbol::start_line`
+    pub start_line: usize,
+    pub commit_hash: String,
+    pub author: String,
+    /// RFC 3339 author time
+    pub timestamp: String,
+}
+
+/// Blame each symbol's line range in `file_path`. Empty when the file is not
+/// inside a Git work tree or `git` cannot be run.
+pub fn blame_symbols(file_path: &Path, symbols: &[ParsedSymbol]) -> Vec<SymbolBlame> {
+    let (Some(dir), Some(file_name)) = (file_path.parent(), file_path.file_name()) else {
+        return Vec::new();
+    };
+    if !dir
+        .ancestors()
+        .any(|ancestor| ancestor.join(".git").exists())
+    {
+        return Vec::new();
+    }
+
+    let mut blames = Vec::new();
+    for symbol in symbols {
+        let range = format!("{},{}", symbol.start_line + 1, symbol.end_line + 1);
+        let output = match Command::new("git")
+            .current_dir(dir)
+            .args(["blame", "--porcelain", "-L", &range, "--"])
+            .arg(file_name)
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                tracing::debug!("Skipping blame, git unavailable: {}", e);
+                return Vec::new();
+            }
+        };
+        if !output.status.success() {
+            // Untracked file, or a range past the committed content
+            tracing::debug!(
+                "git blame failed for {}: {}",
+                file_path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            continue;
+        }
+        if let Some((commit_hash, author, time)) =
+            latest_commit(&String::from_utf8_lossy(&output.stdout))
+        {
+            blames.push(SymbolBlame {
+                symbol: symbol.name.clone(),
+                start_line: symbol.start_line,
+                commit_hash,
+                author,
+                timestamp: chrono::DateTime::from_timestamp(time, 0)
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_default(),
+            });
+        }
+    }
+    blames
+}
+
+/// Newest commit (by author time) in `git blame --porcelain` output, as
+/// (hash, author, unix time). Commit headers only follow a hash's first line.
+fn latest_commit(porcelain: &str) -> Option<(String, String, i64)> {
+    let mut commits: HashMap<&str, (String, i64)> = HashMap::new();
+    let mut current: Option<&str> = None;
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            continue;
+        }
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        if key.len() == 40 && key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            current = Some(key);
+            commits.entry(key).or_default();
+            continue;
+        }
+        let Some(entry) = current.and_then(|hash| commits.get_mut(hash)) else {
+            continue;
+        };
+        match key {
+            "author" => entry.0 = value.to_string(),
+            "author-time" => entry.1 = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    commits
+        .into_iter()
+        .max_by_key(|(_, (_, time))| *time)
+        .map(|(hash, (author, time))| (hash.to_string(), author, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::codebase_parser::CodebaseParser;
+
+    #[test]
+    fn test_latest_commit_picks_newest_author_time() {
+        let old = "a".repeat(40);
+        let new = "b".repeat(40);
+        let porcelain = format!(
+            "{old} 1 1 1\nauthor Ada\nauthor-time 100\nfilename a.rs\n\tfn a() {{\n\
+             {new} 2 2 1\nauthor Grace\nauthor-time 200\nfilename a.rs\n\t    1\n\
+             {old} 3 3\nfilename a.rs\n\t}}\n"
+        );
+
+        assert_eq!(
+            latest_commit(&porcelain),
+            Some((new, "Grace".to_string(), 200))
+        );
+    }
+
+    #[test]
+    fn test_blame_symbols_reports_last_commit_per_symbol() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .current_dir(dir.path())
+                .args(["-c", "user.name=Ada", "-c", "user.email=ada@example.com"])
+                .args(args)
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        if !git(&["init", "-q"]) {
+            // No git in PATH
+            return;
+        }
+        let file_path = dir.path().join("lib.rs");
+        std::fs::write(&file_path, "fn first() {}\n\nfn second() {}\n").unwrap();
+        assert!(git(&["add", "lib.rs"]));
+        assert!(git(&["commit", "-qm", "first"]));
+        std::fs::write(&file_path, "fn first() {}\n\nfn second() { 2 }\n").unwrap();
+        assert!(git(&["commit", "-qam", "second"]));
+
+        let file_log = CodebaseParser::new()
+            .unwrap()
+            .parse_file(&file_path, "rust")
+            .unwrap();
+        let blames = blame_symbols(&file_path, &file_log.symbols);
+
+        assert_eq!(blames.len(), 2);
+        assert!(blames.iter().all(|b| b.author == "Ada"));
+        let hash = |symbol: &str| {
+            blames
+                .iter()
+                .find(|b| b.symbol == symbol)
+                .unwrap()
+                .commit_hash
+                .clone()
+        };
+        assert_ne!(hash("first"), hash("second"));
+
+        let outside = tempfile::tempdir().unwrap();
+        let untracked = outside.path().join("lib.rs");
+        std::fs::write(&untracked, "fn first() {}\n").unwrap();
+        assert!(blame_symbols(&untracked, &file_log.symbols).is_empty());
+    }
+}
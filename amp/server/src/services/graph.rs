@@ -9,6 +9,18 @@ use thiserror::Error;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
+/// Relationship tables traversed when a query does not name its own.
+pub const DEFAULT_RELATIONS: [&str; 8] = [
+    "depends_on",
+    "defined_in",
+    "calls",
+    "justified_by",
+    "modifies",
+    "implements",
+    "produced",
+    "superseded_by",
+];
+
 #[derive(Debug, Error)]
 pub enum GraphTraversalError {
     #[error("Database error: {0}")]
@@ -22,6 +34,9 @@ pub enum GraphTraversalError {
 
     #[error("Target node not reachable")]
     TargetNotReachable,
+
+    #[error("Traversal stopped after visiting {0} nodes")]
+    NodeBudgetExceeded(usize),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,13 +56,183 @@ pub struct PathNode {
     pub parent: Option<Uuid>,
 }
 
+/// One hop of a path returned by `shortest_path`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathEdge {
+    pub from: String,
+    pub to: String,
+    pub relation: String,
+    /// `Inbound` when the stored edge points from `to` back to `from`
+    pub direction: GraphDirection,
+}
+
+/// Default cap on nodes a single path search may visit.
+pub const DEFAULT_NODE_BUDGET: usize = 5000;
+
+/// Relationship tables whose edges pass importance to their target.
+pub const IMPORTANCE_RELATIONS: [&str; 3] = ["calls", "depends_on", "defined_in"];
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-6;
+
+/// Where `store_relationship` put a relationship.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkStorage {
+    /// Appended to the source object's `links` array
+    Inline,
+    /// A record in the relation's edge table
+    EdgeTable,
+}
+
 pub struct GraphTraversalService {
     db: Arc<Database>,
+    node_budget: usize,
+    inline_link_threshold: usize,
 }
 
 impl GraphTraversalService {
     pub fn new(db: Arc<Database>) -> Self {
-        Self { db }
+        Self {
+            db,
+            node_budget: DEFAULT_NODE_BUDGET,
+            inline_link_threshold: 0,
+        }
+    }
+
+    pub fn with_node_budget(mut self, node_budget: usize) -> Self {
+        self.node_budget = node_budget.max(1);
+        self
+    }
+
+    /// Sources with fewer than `threshold` relationships keep them inline in
+    /// `links`; 0 writes every relationship to its edge table.
+    pub fn with_inline_link_threshold(mut self, threshold: usize) -> Self {
+        self.inline_link_threshold = threshold;
+        self
+    }
+
+    /// Record `source -relation-> target`. Below the inline threshold the
+    /// link is appended to the source's `links`, saving an edge-table write;
+    /// the relationship that reaches the threshold promotes the source's
+    /// inline links to edge tables along with itself.
+    pub async fn store_relationship(
+        &self,
+        source: &str,
+        relation: &str,
+        target: &str,
+        project_id: &str,
+    ) -> Result<LinkStorage, GraphTraversalError> {
+        let relation = resolve_relations(&[relation.to_string()])?[0];
+        let relate = |relation: &str, target: &str| {
+            format!(
+                "RELATE (type::thing('objects', $source))->{}->(type::thing('objects', {})) \
+                 SET created_at = time::now(), project_id = $project_id;",
+                relation, target
+            )
+        };
+
+        let mut promoted: HashMap<&str, Vec<String>> = HashMap::new();
+        if self.inline_link_threshold > 0 {
+            let degree_query = format!(
+                "SELECT VALUE {{ inline: (links ?? [])[WHERE type IN $relations], edges: array::len(->({})) }} \
+                 FROM type::thing('objects', $source)",
+                DEFAULT_RELATIONS.join(", ")
+            );
+            let mut response = self
+                .run(
+                    self.db
+                        .client
+                        .query(degree_query)
+                        .bind(("source", source.to_string()))
+                        .bind(("relations", DEFAULT_RELATIONS.to_vec())),
+                    "relationship degree",
+                )
+                .await?;
+            // A missing source has nowhere to hold inline links
+            if let Some(current) = take_json_values(&mut response, 0).first() {
+                let inline = current["inline"].as_array().cloned().unwrap_or_default();
+                let edges = current["edges"].as_u64().unwrap_or(0) as usize;
+                if inline.len() + edges + 1 < self.inline_link_threshold {
+                    self.run(
+                        self.db
+                            .client
+                            .query(
+                                "UPDATE type::thing('objects', $source) \
+                                 SET links = array::append(links ?? [], { type: $relation, target: $target })",
+                            )
+                            .bind(("source", source.to_string()))
+                            .bind(("relation", relation))
+                            .bind(("target", target.to_string())),
+                        "inline relationship",
+                    )
+                    .await?;
+                    return Ok(LinkStorage::Inline);
+                }
+                for link in &inline {
+                    let known = DEFAULT_RELATIONS.iter().find(|name| link["type"] == **name);
+                    if let (Some(name), Some(to)) = (known, link["target"].as_str()) {
+                        promoted.entry(name).or_default().push(to.to_string());
+                    }
+                }
+            }
+        }
+
+        let mut statements = vec!["BEGIN TRANSACTION;".to_string()];
+        for name in promoted.keys() {
+            statements.push(format!(
+                "FOR $promoted IN $inline.{} {{ {} }};",
+                name,
+                relate(name, "$promoted")
+            ));
+        }
+        if !promoted.is_empty() {
+            statements.push(
+                "UPDATE type::thing('objects', $source) SET links = links[WHERE type NOT IN $relations];"
+                    .to_string(),
+            );
+        }
+        statements.push(relate(relation, "$target"));
+        statements.push("COMMIT TRANSACTION;".to_string());
+
+        self.run(
+            self.db
+                .client
+                .query(statements.join(" "))
+                .bind(("source", source.to_string()))
+                .bind(("target", target.to_string()))
+                .bind(("project_id", project_id.to_string()))
+                .bind(("relations", DEFAULT_RELATIONS.to_vec()))
+                .bind((
+                    "inline",
+                    serde_json::to_value(&promoted).unwrap_or_default(),
+                )),
+            "edge relationship",
+        )
+        .await?;
+        Ok(LinkStorage::EdgeTable)
+    }
+
+    /// Await a query under the usual timeout and surface statement errors.
+    async fn run(
+        &self,
+        query: surrealdb::method::Query<'_, surrealdb::engine::any::Any>,
+        context: &str,
+    ) -> Result<surrealdb::Response, GraphTraversalError> {
+        match timeout(Duration::from_secs(5), query).await {
+            Ok(Ok(response)) => response.check().map_err(|e| {
+                tracing::error!("Database error in {}: {}", context, e);
+                GraphTraversalError::DatabaseError(e.to_string())
+            }),
+            Ok(Err(e)) => {
+                tracing::error!("Database error in {}: {}", context, e);
+                Err(GraphTraversalError::DatabaseError(e.to_string()))
+            }
+            Err(_) => {
+                tracing::error!("Timeout in {}", context);
+                Err(GraphTraversalError::Timeout)
+            }
+        }
     }
 
     pub async fn execute_multi_hop(
@@ -55,6 +240,13 @@ impl GraphTraversalService {
         query: &GraphQuery,
     ) -> Result<TraversalResult, GraphTraversalError> {
         let max_depth = query.max_depth.unwrap_or(3);
+        let scoped;
+        let query = if query.tenant_id.is_some() {
+            scoped = self.retain_tenant_start_nodes(query).await?;
+            &scoped
+        } else {
+            query
+        };
 
         match &query.algorithm {
             Some(TraversalAlgorithm::Collect) => {
@@ -70,21 +262,496 @@ impl GraphTraversalService {
         }
     }
 
+    /// Bounded BFS over `allowed_edge_types` (every relationship table when
+    /// empty), following edges in either direction. Returns the hops from
+    /// `from` to `to` (empty when they are the same node), or `None` when `to`
+    /// is not reachable within `max_depth`. Gives up with `NodeBudgetExceeded`
+    /// once more than the configured node budget has been visited.
+    pub async fn shortest_path(
+        &self,
+        from: &str,
+        to: &str,
+        max_depth: usize,
+        allowed_edge_types: &[String],
+        tenant_id: &str,
+    ) -> Result<Option<Vec<PathEdge>>, GraphTraversalError> {
+        let relations = resolve_relations(allowed_edge_types)?;
+        if from == to {
+            // Only a node the tenant can see is a path to itself
+            let found = self
+                .query_values_for_ids(
+                    "SELECT VALUE meta::id(id) FROM $ids.map(|$i| type::thing('objects', $i)) \
+                     WHERE tenant_id = $tenant_id"
+                        .to_string(),
+                    &[from.to_string()],
+                    tenant_id,
+                    "path search",
+                )
+                .await?;
+            return Ok((!found.is_empty()).then(Vec::new));
+        }
+
+        let mut parents: HashMap<String, PathEdge> = HashMap::new();
+        let mut visited: HashSet<String> = HashSet::from([from.to_string()]);
+        let mut frontier = vec![from.to_string()];
+
+        for _ in 0..max_depth {
+            if frontier.is_empty() {
+                break;
+            }
+            let current: HashSet<&String> = frontier.iter().collect();
+            let mut next = Vec::new();
+
+            let edges = self
+                .incident_edges(&frontier, &relations, tenant_id)
+                .await?;
+            for (source, target, relation) in edges {
+                let hops = [
+                    (&source, &target, GraphDirection::Outbound),
+                    (&target, &source, GraphDirection::Inbound),
+                ];
+                for (here, there, direction) in hops {
+                    if !current.contains(here) || !visited.insert(there.clone()) {
+                        continue;
+                    }
+                    parents.insert(
+                        there.clone(),
+                        PathEdge {
+                            from: here.clone(),
+                            to: there.clone(),
+                            relation: relation.clone(),
+                            direction,
+                        },
+                    );
+                    if there == to {
+                        let mut path = Vec::new();
+                        let mut node = to;
+                        while let Some(edge) = parents.get(node) {
+                            path.push(edge.clone());
+                            node = &edge.from;
+                        }
+                        path.reverse();
+                        return Ok(Some(path));
+                    }
+                    if visited.len() > self.node_budget {
+                        return Err(GraphTraversalError::NodeBudgetExceeded(self.node_budget));
+                    }
+                    next.push(there.clone());
+                }
+            }
+            frontier = next;
+        }
+
+        Ok(None)
+    }
+
+    /// Edges touching any of `nodes`, as (source, target, relation) with both
+    /// endpoints inside the tenant.
+    async fn incident_edges(
+        &self,
+        nodes: &[String],
+        relations: &[&str],
+        tenant_id: &str,
+    ) -> Result<Vec<(String, String, String)>, GraphTraversalError> {
+        let query_str = format!(
+            "SELECT meta::id(in) AS source, meta::id(out) AS target, meta::tb(id) AS relation FROM {} \
+             WHERE (in IN {list} OR out IN {list}) AND in.tenant_id = $tenant_id AND out.tenant_id = $tenant_id",
+            relations.join(", "),
+            list = "$ids.map(|$i| type::thing('objects', $i))"
+        );
+
+        let mut edges: Vec<(String, String, String)> = self
+            .query_values_for_ids(query_str, nodes, tenant_id, "path search")
+            .await?
+            .into_iter()
+            .filter_map(|edge| {
+                let field = |key: &str| edge.get(key)?.as_str().map(str::to_string);
+                Some((field("source")?, field("target")?, field("relation")?))
+            })
+            .collect();
+        if self.inline_link_threshold == 0 {
+            return Ok(edges);
+        }
+
+        // Inline links held by the frontier or pointing back at it
+        let inline_query =
+            "SELECT VALUE { source: meta::id(id), links: (links ?? [])[WHERE type IN $relations] } \
+             FROM objects WHERE tenant_id = $tenant_id \
+             AND (id IN $nodes.map(|$i| type::thing('objects', $i)) \
+             OR (links ?? [])[WHERE type IN $relations].target CONTAINSANY $nodes)";
+        let mut response = self
+            .run(
+                self.db
+                    .client
+                    .query(inline_query)
+                    .bind(("tenant_id", tenant_id.to_string()))
+                    .bind((
+                        "relations",
+                        relations.iter().map(|r| r.to_string()).collect::<Vec<_>>(),
+                    ))
+                    .bind(("nodes", nodes.to_vec())),
+                "path search",
+            )
+            .await?;
+        let mut inline = Vec::new();
+        for row in take_json_values(&mut response, 0) {
+            let Some(source) = row["source"].as_str() else {
+                continue;
+            };
+            for link in row["links"].as_array().into_iter().flatten() {
+                if let (Some(target), Some(relation)) =
+                    (link["target"].as_str(), link["type"].as_str())
+                {
+                    inline.push((source.to_string(), target.to_string(), relation.to_string()));
+                }
+            }
+        }
+        if inline.is_empty() {
+            return Ok(edges);
+        }
+
+        let targets: Vec<String> = inline.iter().map(|(_, target, _)| target.clone()).collect();
+        let in_tenant: HashSet<String> = self
+            .query_values_for_ids(
+                "SELECT VALUE meta::id(id) FROM $ids.map(|$i| type::thing('objects', $i)) \
+                 WHERE tenant_id = $tenant_id"
+                    .to_string(),
+                &targets,
+                tenant_id,
+                "path search",
+            )
+            .await?
+            .into_iter()
+            .filter_map(|id| id.as_str().map(str::to_string))
+            .collect();
+        edges.extend(
+            inline
+                .into_iter()
+                .filter(|(_, target, _)| in_tenant.contains(target)),
+        );
+        Ok(edges)
+    }
+
+    /// Objects that every one of `ids` points at through an outbound
+    /// `edge_type` edge, e.g. the module two files both depend on. At most
+    /// the node budget's worth of edges is scanned.
+    pub async fn common_neighbors(
+        &self,
+        ids: &[String],
+        edge_type: &str,
+        tenant_id: &str,
+    ) -> Result<Vec<Value>, GraphTraversalError> {
+        let relations = resolve_relations(&[edge_type.to_string()])?;
+        let sources: HashSet<&String> = ids.iter().collect();
+        if sources.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let source_ids: Vec<String> = sources.iter().map(|id| id.to_string()).collect();
+        let query_str = format!(
+            "SELECT meta::id(in) AS source, meta::id(out) AS target FROM {} \
+             WHERE in IN $ids.map(|$i| type::thing('objects', $i)) \
+             AND in.tenant_id = $tenant_id AND out.tenant_id = $tenant_id LIMIT {}",
+            relations[0], self.node_budget
+        );
+        let edges = self
+            .query_values_for_ids(query_str, &source_ids, tenant_id, "common neighbors")
+            .await?;
+
+        let mut reached_from: HashMap<String, HashSet<String>> = HashMap::new();
+        for edge in edges {
+            let field = |key: &str| edge.get(key)?.as_str().map(str::to_string);
+            if let (Some(source), Some(target)) = (field("source"), field("target")) {
+                reached_from.entry(target).or_default().insert(source);
+            }
+        }
+        let mut shared: Vec<String> = reached_from
+            .into_iter()
+            .filter(|(_, reached)| reached.len() == sources.len())
+            .map(|(target, _)| target)
+            .collect();
+        if shared.is_empty() {
+            return Ok(Vec::new());
+        }
+        shared.sort();
+
+        let query_str = "SELECT *, string::concat(id) AS id_string OMIT id, embedding \
+             FROM $ids.map(|$i| type::thing('objects', $i)) WHERE tenant_id = $tenant_id"
+            .to_string();
+        let mut nodes = self
+            .query_values_for_ids(query_str, &shared, tenant_id, "common neighbors")
+            .await?;
+        normalize_object_ids(&mut nodes);
+        Ok(nodes)
+    }
+
+    /// Import cycles among the project's objects: every strongly-connected
+    /// component of the `depends_on` graph with more than one node. Node ids
+    /// are sorted within each cycle, and cycles are sorted by their first id.
+    pub async fn find_cycles(
+        &self,
+        project_id: &str,
+        tenant_id: &str,
+    ) -> Result<Vec<Vec<String>>, GraphTraversalError> {
+        let query_str = "SELECT meta::id(in) AS source, meta::id(out) AS target FROM depends_on \
+             WHERE in.project_id = $project_id AND out.project_id = $project_id \
+             AND in.tenant_id = $tenant_id AND out.tenant_id = $tenant_id"
+            .to_string();
+        let edges: Vec<(String, String)> = self
+            .query_values_with(
+                query_str,
+                &[("project_id", project_id), ("tenant_id", tenant_id)],
+                "cycle detection",
+            )
+            .await?
+            .into_iter()
+            .filter_map(|edge| {
+                let field = |key: &str| edge.get(key)?.as_str().map(str::to_string);
+                Some((field("source")?, field("target")?))
+            })
+            .collect();
+
+        let mut cycles: Vec<Vec<String>> = strongly_connected_components(&edges)
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect();
+        cycles.sort();
+        Ok(cycles)
+    }
+
+    /// PageRank over the project's `calls`, `depends_on` and `defined_in`
+    /// edges, stored as `importance` on every object of the project. Scores
+    /// sum to 1 across the project; returns them highest first.
+    pub async fn compute_importance(
+        &self,
+        project_id: &str,
+        tenant_id: &str,
+    ) -> Result<Vec<(String, f64)>, GraphTraversalError> {
+        let bindings = [("project_id", project_id), ("tenant_id", tenant_id)];
+        let nodes: Vec<String> = self
+            .query_values_with(
+                "SELECT VALUE meta::id(id) FROM objects WHERE project_id = $project_id AND tenant_id = $tenant_id".to_string(),
+                &bindings,
+                "importance nodes",
+            )
+            .await?
+            .into_iter()
+            .filter_map(|id| id.as_str().map(str::to_string))
+            .collect();
+        if nodes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query_str = format!(
+            "SELECT meta::id(in) AS source, meta::id(out) AS target FROM {} \
+             WHERE in.project_id = $project_id AND out.project_id = $project_id \
+             AND in.tenant_id = $tenant_id AND out.tenant_id = $tenant_id",
+            IMPORTANCE_RELATIONS.join(", ")
+        );
+        let edges: Vec<(String, String)> = self
+            .query_values_with(query_str, &bindings, "importance edges")
+            .await?
+            .into_iter()
+            .filter_map(|edge| {
+                let field = |key: &str| edge.get(key)?.as_str().map(str::to_string);
+                Some((field("source")?, field("target")?))
+            })
+            .collect();
+
+        let mut ranked: Vec<(String, f64)> = pagerank(&nodes, &edges).into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let scores: Vec<Value> = ranked
+            .iter()
+            .map(|(id, score)| serde_json::json!({ "id": id, "score": score }))
+            .collect();
+        let update = timeout(
+            Duration::from_secs(30),
+            self.db
+                .client
+                .query("FOR $row IN $scores { UPDATE type::thing('objects', $row.id) SET importance = $row.score; };")
+                .bind(("scores", scores)),
+        )
+        .await;
+        match update {
+            Ok(Ok(response)) => {
+                response
+                    .check()
+                    .map_err(|e| GraphTraversalError::DatabaseError(e.to_string()))?;
+            }
+            Ok(Err(e)) => return Err(GraphTraversalError::DatabaseError(e.to_string())),
+            Err(_) => return Err(GraphTraversalError::Timeout),
+        }
+        Ok(ranked)
+    }
+
+    async fn query_values_with(
+        &self,
+        query_str: String,
+        bindings: &[(&'static str, &str)],
+        context: &str,
+    ) -> Result<Vec<Value>, GraphTraversalError> {
+        let mut query = self.db.client.query(query_str);
+        for (name, value) in bindings {
+            query = query.bind((*name, value.to_string()));
+        }
+        let query_result = timeout(Duration::from_secs(5), query).await;
+
+        match query_result {
+            Ok(Ok(mut response)) => Ok(take_json_values(&mut response, 0)),
+            Ok(Err(e)) => {
+                tracing::error!("Database error in {}: {}", context, e);
+                Err(GraphTraversalError::DatabaseError(e.to_string()))
+            }
+            Err(_) => {
+                tracing::error!("Timeout in {}", context);
+                Err(GraphTraversalError::Timeout)
+            }
+        }
+    }
+
+    /// Rows of `query_str` with `$ids` bound to `ids`, so caller-supplied ids
+    /// are never spliced into SurrealQL.
+    async fn query_values_for_ids(
+        &self,
+        query_str: String,
+        ids: &[String],
+        tenant_id: &str,
+        context: &str,
+    ) -> Result<Vec<Value>, GraphTraversalError> {
+        let mut response = self
+            .run(
+                self.db
+                    .client
+                    .query(query_str)
+                    .bind(("ids", ids.to_vec()))
+                    .bind(("tenant_id", tenant_id.to_string())),
+                context,
+            )
+            .await?;
+        Ok(take_json_values(&mut response, 0))
+    }
+
+    /// Drop start nodes outside the query tenant so traversal never begins in another tenant.
+    async fn retain_tenant_start_nodes(
+        &self,
+        query: &GraphQuery,
+    ) -> Result<GraphQuery, GraphTraversalError> {
+        let mut scoped = query.clone();
+        if query.start_nodes.is_empty() {
+            return Ok(scoped);
+        }
+
+        let node_ids: Vec<String> = query
+            .start_nodes
+            .iter()
+            .map(|id| format!("objects:`{}`", id))
+            .collect();
+        let query_str = format!(
+            "SELECT VALUE meta::id(id) FROM [{}] WHERE tenant_id = $tenant_id",
+            node_ids.join(", ")
+        );
+        let query_result = timeout(
+            Duration::from_secs(5),
+            self.db
+                .client
+                .query(query_str)
+                .bind(("tenant_id", query.tenant_id.clone())),
+        )
+        .await;
+
+        let allowed: HashSet<String> = match query_result {
+            Ok(Ok(mut response)) => take_json_values(&mut response, 0)
+                .into_iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            Ok(Err(e)) => return Err(GraphTraversalError::DatabaseError(e.to_string())),
+            Err(_) => return Err(GraphTraversalError::Timeout),
+        };
+        scoped
+            .start_nodes
+            .retain(|id| allowed.contains(&id.to_string()));
+        Ok(scoped)
+    }
+
     fn relation_list(&self, query: &GraphQuery) -> Vec<String> {
         match &query.relation_types {
             Some(types) if !types.is_empty() => types.clone(),
-            _ => vec![
-                "depends_on".to_string(),
-                "defined_in".to_string(),
-                "calls".to_string(),
-                "justified_by".to_string(),
-                "modifies".to_string(),
-                "implements".to_string(),
-                "produced".to_string(),
-            ],
+            _ => DEFAULT_RELATIONS.map(str::to_string).to_vec(),
         }
     }
 
+    /// Neighbours of `node` reached through relationships kept inline in
+    /// `links` rather than in edge tables. Empty when inline storage is off.
+    async fn inline_neighbors(
+        &self,
+        node: Uuid,
+        query: &GraphQuery,
+        relations: &[String],
+        projection: &str,
+    ) -> Result<Vec<Value>, GraphTraversalError> {
+        if self.inline_link_threshold == 0 {
+            return Ok(Vec::new());
+        }
+        let direction = query
+            .direction
+            .as_ref()
+            .unwrap_or(&GraphDirection::Outbound);
+        let outbound = "meta::id(id) IN $outbound";
+        let inbound = "$id IN (links ?? [])[WHERE type IN $relations].target";
+        let matches = match direction {
+            GraphDirection::Outbound => outbound.to_string(),
+            GraphDirection::Inbound => inbound.to_string(),
+            GraphDirection::Both => format!("{} OR {}", outbound, inbound),
+        };
+        let tenant = if query.tenant_id.is_some() {
+            "tenant_id = $tenant_id AND "
+        } else {
+            ""
+        };
+        let query_str = format!(
+            "LET $outbound = array::flatten(SELECT VALUE (links ?? [])[WHERE type IN $relations].target \
+             FROM type::thing('objects', $id)); \
+             SELECT VALUE {} FROM objects WHERE {}({})",
+            projection, tenant, matches
+        );
+
+        let mut response = self
+            .run(
+                self.db
+                    .client
+                    .query(query_str)
+                    .bind(("id", node.to_string()))
+                    .bind(("relations", relations.to_vec()))
+                    .bind(("tenant_id", query.tenant_id.clone())),
+                "inline link traversal",
+            )
+            .await?;
+        let mut nodes = take_json_values(&mut response, 1);
+        normalize_object_ids(&mut nodes);
+        Ok(nodes)
+    }
+
+    /// Ids of the nodes returned by `inline_neighbors`.
+    async fn inline_neighbor_ids(
+        &self,
+        node: Uuid,
+        query: &GraphQuery,
+        relations: &[String],
+    ) -> Result<Vec<Uuid>, GraphTraversalError> {
+        Ok(self
+            .inline_neighbors(node, query, relations, "{ id: string::concat(id) }")
+            .await?
+            .iter()
+            .filter_map(|node| node.get("id").and_then(|v| v.as_str()))
+            .filter_map(|id| Uuid::parse_str(id.trim_start_matches("objects:")).ok())
+            .collect())
+    }
+
     fn format_relation_clause(&self, relation: &str) -> String {
         relation.to_string()
     }
@@ -110,6 +777,7 @@ impl GraphTraversalService {
             .as_ref()
             .unwrap_or(&GraphDirection::Outbound);
         let relation_list = self.relation_list(query);
+        let target = query.target_table();
 
         while let Some((current_id, depth)) = queue.pop_front() {
             if depth >= max_depth {
@@ -124,28 +792,34 @@ impl GraphTraversalService {
                 let query_str = match direction {
                     GraphDirection::Outbound => {
                         format!(
-                            "SELECT VALUE {{ connected: ->{}->objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, current_id
+                            "SELECT VALUE {{ connected: ->{}->{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, current_id
                         )
                     }
                     GraphDirection::Inbound => {
                         format!(
-                            "SELECT VALUE {{ connected: <-{}<-objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, current_id
+                            "SELECT VALUE {{ connected: <-{}<-{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, current_id
                         )
                     }
                     GraphDirection::Both => {
                         format!(
-                            "SELECT VALUE {{ outbound: ->{}->objects.{}, inbound: <-{}<-objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, relation_clause, projection, current_id
+                            "SELECT VALUE {{ outbound: ->{}->{}.{}, inbound: <-{}<-{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, relation_clause, target, projection, current_id
                         )
                     }
                 };
 
                 tracing::debug!("Collect traversal query at depth {}: {}", depth, query_str);
 
-                let query_result =
-                    timeout(Duration::from_secs(5), self.db.client.query(query_str)).await;
+                let query_result = timeout(
+                    Duration::from_secs(5),
+                    self.db
+                        .client
+                        .query(query_str)
+                        .bind(("tenant_id", query.tenant_id.clone())),
+                )
+                .await;
 
                 let mut connected: Vec<Value> = match query_result {
                     Ok(Ok(mut response)) => {
@@ -201,6 +875,10 @@ impl GraphTraversalService {
                 normalize_object_ids(&mut connected);
                 connected_nodes.extend(connected);
             }
+            connected_nodes.extend(
+                self.inline_neighbors(current_id, query, &relation_list, projection)
+                    .await?,
+            );
 
             // Process connected nodes
             for node in connected_nodes {
@@ -303,6 +981,7 @@ impl GraphTraversalService {
             .as_ref()
             .unwrap_or(&GraphDirection::Outbound);
         let relation_list = self.relation_list(query);
+        let target = query.target_table();
 
         while let Some((current_id, current_path, depth)) = stack.pop() {
             // Add current path to results
@@ -320,26 +999,32 @@ impl GraphTraversalService {
                 let query_str = match direction {
                     GraphDirection::Outbound => {
                         format!(
-                            "SELECT VALUE {{ connected: ->{}->objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, current_id
+                            "SELECT VALUE {{ connected: ->{}->{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, current_id
                         )
                     }
                     GraphDirection::Inbound => {
                         format!(
-                            "SELECT VALUE {{ connected: <-{}<-objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, current_id
+                            "SELECT VALUE {{ connected: <-{}<-{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, current_id
                         )
                     }
                     GraphDirection::Both => {
                         format!(
-                            "SELECT VALUE {{ outbound: ->{}->objects.{}, inbound: <-{}<-objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, relation_clause, projection, current_id
+                            "SELECT VALUE {{ outbound: ->{}->{}.{}, inbound: <-{}<-{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, relation_clause, target, projection, current_id
                         )
                     }
                 };
 
-                let query_result =
-                    timeout(Duration::from_secs(5), self.db.client.query(query_str)).await;
+                let query_result = timeout(
+                    Duration::from_secs(5),
+                    self.db
+                        .client
+                        .query(query_str)
+                        .bind(("tenant_id", query.tenant_id.clone())),
+                )
+                .await;
 
                 let mut node_ids: Vec<Uuid> = match query_result {
                     Ok(Ok(mut response)) => {
@@ -422,6 +1107,10 @@ impl GraphTraversalService {
 
                 connected_nodes.append(&mut node_ids);
             }
+            connected_nodes.extend(
+                self.inline_neighbor_ids(current_id, query, &relation_list)
+                    .await?,
+            );
 
             // Add connected nodes to stack (avoid cycles by checking if node is already in path)
             for next_id in connected_nodes {
@@ -531,6 +1220,7 @@ impl GraphTraversalService {
             .as_ref()
             .unwrap_or(&GraphDirection::Outbound);
         let relation_list = self.relation_list(query);
+        let target = query.target_table();
 
         while let Some(Reverse(current)) = heap.pop() {
             if current.node_id == target_id {
@@ -551,26 +1241,32 @@ impl GraphTraversalService {
                 let query_str = match direction {
                     GraphDirection::Outbound => {
                         format!(
-                            "SELECT VALUE {{ connected: ->{}->objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, current.node_id
+                            "SELECT VALUE {{ connected: ->{}->{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, current.node_id
                         )
                     }
                     GraphDirection::Inbound => {
                         format!(
-                            "SELECT VALUE {{ connected: <-{}<-objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, current.node_id
+                            "SELECT VALUE {{ connected: <-{}<-{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, current.node_id
                         )
                     }
                     GraphDirection::Both => {
                         format!(
-                            "SELECT VALUE {{ outbound: ->{}->objects.{}, inbound: <-{}<-objects.{} }} FROM objects:`{}`",
-                            relation_clause, projection, relation_clause, projection, current.node_id
+                            "SELECT VALUE {{ outbound: ->{}->{}.{}, inbound: <-{}<-{}.{} }} FROM objects:`{}`",
+                            relation_clause, target, projection, relation_clause, target, projection, current.node_id
                         )
                     }
                 };
 
-                let query_result =
-                    timeout(Duration::from_secs(5), self.db.client.query(query_str)).await;
+                let query_result = timeout(
+                    Duration::from_secs(5),
+                    self.db
+                        .client
+                        .query(query_str)
+                        .bind(("tenant_id", query.tenant_id.clone())),
+                )
+                .await;
 
                 let mut node_ids: Vec<Uuid> = match query_result {
                     Ok(Ok(mut response)) => {
@@ -653,6 +1349,10 @@ impl GraphTraversalService {
 
                 connected_nodes.append(&mut node_ids);
             }
+            connected_nodes.extend(
+                self.inline_neighbor_ids(current.node_id, query, &relation_list)
+                    .await?,
+            );
 
             // Add neighbors to heap
             for next_id in connected_nodes {
@@ -672,3 +1372,397 @@ impl GraphTraversalService {
         Ok(None)
     }
 }
+
+/// Relation tables to search, restricted to `requested` when it is non-empty.
+/// Names are interpolated into SurrealQL, so only known relations are accepted.
+fn resolve_relations(requested: &[String]) -> Result<Vec<&'static str>, GraphTraversalError> {
+    if requested.is_empty() {
+        return Ok(DEFAULT_RELATIONS.to_vec());
+    }
+    requested
+        .iter()
+        .map(|name| {
+            DEFAULT_RELATIONS
+                .iter()
+                .copied()
+                .find(|relation| relation == name)
+                .ok_or_else(|| {
+                    GraphTraversalError::InvalidQuery(format!("Unknown edge type: {}", name))
+                })
+        })
+        .collect()
+}
+
+/// Iterative PageRank where each edge passes rank from source to target.
+/// Nodes without outgoing edges spread their rank evenly, so the scores
+/// always sum to 1. Edges touching unknown nodes are ignored.
+fn pagerank(nodes: &[String], edges: &[(String, String)]) -> HashMap<String, f64> {
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    for (source, target) in edges {
+        if let (Some(&from), Some(&to)) = (index.get(source.as_str()), index.get(target.as_str())) {
+            if from != to {
+                outgoing[from].push(to);
+            }
+        }
+    }
+
+    let n = nodes.len() as f64;
+    let mut rank = vec![1.0 / n; nodes.len()];
+    for _ in 0..PAGERANK_MAX_ITERATIONS {
+        let dangling: f64 = rank
+            .iter()
+            .zip(&outgoing)
+            .filter(|(_, targets)| targets.is_empty())
+            .map(|(score, _)| score)
+            .sum();
+        let base = (1.0 - PAGERANK_DAMPING) / n + PAGERANK_DAMPING * dangling / n;
+        let mut next = vec![base; nodes.len()];
+        for (from, targets) in outgoing.iter().enumerate() {
+            let share = PAGERANK_DAMPING * rank[from] / targets.len().max(1) as f64;
+            for &to in targets {
+                next[to] += share;
+            }
+        }
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+
+    nodes.iter().cloned().zip(rank).collect()
+}
+
+/// Tarjan's algorithm, iterative so deep dependency chains cannot overflow
+/// the stack. Returns every component, including single nodes.
+fn strongly_connected_components(edges: &[(String, String)]) -> Vec<Vec<String>> {
+    let mut index_of: HashMap<&str, usize> = HashMap::new();
+    let mut names: Vec<&str> = Vec::new();
+    for (source, target) in edges {
+        for node in [source.as_str(), target.as_str()] {
+            index_of.entry(node).or_insert_with(|| {
+                names.push(node);
+                names.len() - 1
+            });
+        }
+    }
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+    for (source, target) in edges {
+        adjacency[index_of[source.as_str()]].push(index_of[target.as_str()]);
+    }
+
+    let mut order: Vec<Option<usize>> = vec![None; names.len()];
+    let mut low_link = vec![0; names.len()];
+    let mut on_stack = vec![false; names.len()];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for root in 0..names.len() {
+        if order[root].is_some() {
+            continue;
+        }
+        // (node, position of the next neighbour to visit)
+        let mut work = vec![(root, 0)];
+        while let Some(&mut (node, ref mut position)) = work.last_mut() {
+            if *position == 0 && order[node].is_none() {
+                order[node] = Some(next_index);
+                low_link[node] = next_index;
+                next_index += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+
+            if let Some(&neighbour) = adjacency[node].get(*position) {
+                *position += 1;
+                match order[neighbour] {
+                    None => work.push((neighbour, 0)),
+                    Some(neighbour_order) if on_stack[neighbour] => {
+                        low_link[node] = low_link[node].min(neighbour_order);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if Some(low_link[node]) == order[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(names[member].to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    /// a -calls-> b -depends_on-> c <-defined_in- d, plus an isolated node e
+    async fn synthetic_graph() -> Arc<GraphTraversalService> {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "FOR $id IN ['a', 'b', 'c', 'd', 'e'] { CREATE type::thing('objects', $id) SET type = 'symbol', tenant_id = 'default' };
+                 RELATE objects:a->calls->objects:b;
+                 RELATE objects:b->depends_on->objects:c;
+                 RELATE objects:d->defined_in->objects:c;",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        state.graph_service
+    }
+
+    #[tokio::test]
+    async fn test_shortest_path_follows_edges_in_both_directions() {
+        let graph = synthetic_graph().await;
+
+        let path = graph
+            .shortest_path("a", "d", 5, &[], "default")
+            .await
+            .unwrap()
+            .unwrap();
+        let hops: Vec<(&str, &str, &str)> = path
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.relation.as_str()))
+            .collect();
+        assert_eq!(
+            hops,
+            vec![
+                ("a", "b", "calls"),
+                ("b", "c", "depends_on"),
+                ("c", "d", "defined_in")
+            ]
+        );
+        assert_eq!(path[0].direction, GraphDirection::Outbound);
+        assert_eq!(path[2].direction, GraphDirection::Inbound);
+
+        // Too shallow for the three-hop path
+        assert!(graph
+            .shortest_path("a", "d", 2, &[], "default")
+            .await
+            .unwrap()
+            .is_none());
+        // Other tenants see nothing
+        assert!(graph
+            .shortest_path("a", "b", 5, &[], "acme")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shortest_path_unreachable_and_self() {
+        let graph = synthetic_graph().await;
+
+        assert!(graph
+            .shortest_path("a", "e", 5, &[], "default")
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            graph
+                .shortest_path("c", "c", 5, &[], "default")
+                .await
+                .unwrap(),
+            Some(Vec::new())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shortest_path_respects_edge_types_and_node_budget() {
+        let graph = synthetic_graph().await;
+        let types = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        let path = graph
+            .shortest_path("a", "c", 5, &types(&["calls", "depends_on"]), "default")
+            .await
+            .unwrap();
+        assert_eq!(path.map(|p| p.len()), Some(2));
+        assert!(graph
+            .shortest_path("a", "c", 5, &types(&["calls"]), "default")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            graph
+                .shortest_path("a", "c", 5, &types(&["objects"]), "default")
+                .await,
+            Err(GraphTraversalError::InvalidQuery(_))
+        ));
+
+        let state = memory_state(test_config()).await;
+        let tiny = GraphTraversalService::new(state.db.clone()).with_node_budget(1);
+        state
+            .db
+            .client
+            .query("CREATE objects:x SET tenant_id = 'default'; CREATE objects:y SET tenant_id = 'default'; RELATE objects:x->calls->objects:y;")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        assert!(matches!(
+            tiny.shortest_path("x", "z", 5, &[], "default").await,
+            Err(GraphTraversalError::NodeBudgetExceeded(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_common_neighbors_intersects_outbound_targets() {
+        let graph = synthetic_graph().await;
+        graph
+            .db
+            .client
+            .query(
+                "RELATE objects:e->depends_on->objects:c; RELATE objects:b->depends_on->objects:d;",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let ids = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let shared = graph
+            .common_neighbors(&ids(&["b", "e"]), "depends_on", "default")
+            .await
+            .unwrap();
+        let shared_ids: Vec<&str> = shared.iter().filter_map(|n| n["id"].as_str()).collect();
+        assert_eq!(shared_ids, vec!["c"]);
+
+        assert!(graph
+            .common_neighbors(&ids(&["a", "e"]), "depends_on", "default")
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(graph
+            .common_neighbors(&ids(&["b", "e"]), "depends_on", "acme")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_cycles_reports_only_the_import_cycle() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "FOR $id IN ['a', 'b', 'c', 'x', 'y', 'z'] { CREATE type::thing('objects', $id) SET type = 'file', project_id = 'p1', tenant_id = 'default' };
+                 CREATE objects:other SET type = 'file', project_id = 'p2', tenant_id = 'default';
+                 RELATE objects:a->depends_on->objects:b;
+                 RELATE objects:b->depends_on->objects:c;
+                 RELATE objects:c->depends_on->objects:a;
+                 RELATE objects:x->depends_on->objects:y;
+                 RELATE objects:y->depends_on->objects:z;
+                 RELATE objects:z->calls->objects:x;
+                 RELATE objects:z->depends_on->objects:other;
+                 RELATE objects:other->depends_on->objects:x;",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let graph = &state.graph_service;
+
+        assert_eq!(
+            graph.find_cycles("p1", "default").await.unwrap(),
+            vec![vec!["a".to_string(), "b".to_string(), "c".to_string()]]
+        );
+        assert!(graph.find_cycles("p2", "default").await.unwrap().is_empty());
+        assert!(graph.find_cycles("p1", "acme").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pagerank_favours_the_hub_and_sums_to_one() {
+        let nodes: Vec<String> = ["hub", "a", "b", "c"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let edges: Vec<(String, String)> = ["a", "b", "c"]
+            .iter()
+            .map(|spoke| (spoke.to_string(), "hub".to_string()))
+            .chain(std::iter::once(("a".to_string(), "missing".to_string())))
+            .collect();
+
+        let scores = pagerank(&nodes, &edges);
+
+        assert_eq!(scores.len(), 4);
+        assert!((scores.values().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(scores["hub"] > scores["a"]);
+        assert!((scores["a"] - scores["b"]).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_compute_importance_writes_scores_for_the_project() {
+        let state = memory_state(test_config()).await;
+        state
+            .db
+            .client
+            .query(
+                "FOR $id IN ['hub', 'a', 'b', 'c'] { CREATE type::thing('objects', $id) SET type = 'symbol', project_id = 'p1', tenant_id = 'default' };
+                 CREATE objects:other SET type = 'symbol', project_id = 'p2', tenant_id = 'default';
+                 RELATE objects:a->calls->objects:hub;
+                 RELATE objects:b->depends_on->objects:hub;
+                 RELATE objects:c->defined_in->objects:hub;
+                 RELATE objects:other->calls->objects:a;",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let ranked = state
+            .graph_service
+            .compute_importance("p1", "default")
+            .await
+            .unwrap();
+        assert_eq!(ranked.len(), 4);
+        assert_eq!(ranked[0].0, "hub");
+
+        let mut response = state
+            .db
+            .client
+            .query("SELECT VALUE [meta::id(id), importance] FROM objects")
+            .await
+            .unwrap();
+        let stored = take_json_values(&mut response, 0);
+        let importance = |id: &str| {
+            stored
+                .iter()
+                .find(|row| row[0] == id)
+                .map(|row| row[1].clone())
+                .unwrap()
+        };
+        assert_eq!(importance("hub").as_f64(), Some(ranked[0].1));
+        assert!(importance("other").is_null());
+        assert!(state
+            .graph_service
+            .compute_importance("p1", "acme")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
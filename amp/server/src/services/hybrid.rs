@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -6,10 +6,16 @@ use thiserror::Error;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
-use crate::database::Database;
-use crate::handlers::query::{GraphQuery, QueryFilters, QueryRequest, TraversalAlgorithm};
-use crate::services::embedding::EmbeddingService;
+use crate::database::{knn_condition, Database};
+use crate::handlers::query::{
+    expired_warning_condition, GraphQuery, QueryFilters, QueryLog, QueryRequest, SimilarRequest,
+    TraversalAlgorithm,
+};
+use crate::models::settings::SettingsConfig;
+use crate::services::analytics::AnalyticsService;
+use crate::services::embedding::{EmbeddingIndex, EmbeddingService};
 use crate::services::graph::GraphTraversalService;
+use crate::services::index_llm::IndexLlmService;
 use crate::surreal_json::{normalize_object_ids, take_json_values};
 
 #[derive(Debug, Error)]
@@ -23,6 +29,9 @@ pub enum HybridRetrievalError {
     #[error("Invalid query: {0}")]
     InvalidQuery(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Partial failure: {0}")]
     PartialFailure(String),
 }
@@ -35,6 +44,94 @@ pub struct HybridResult {
     pub vector_score: Option<f32>,
     pub graph_score: Option<f32>,
     pub explanation: String,
+    pub score_breakdown: ScoreBreakdown,
+    /// Retrieval systems that returned this result, e.g. "vector" or "text+graph"
+    pub source: String,
+    /// Relevance to the query text from the rerank pass; `total_score` keeps
+    /// the retrieval score
+    pub rerank_score: Option<f32>,
+}
+
+/// Per-result scoring detail: raw signal scores plus the graph RRF contribution
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScoreBreakdown {
+    pub vector_score: Option<f32>,
+    pub text_score: Option<f32>,
+    pub graph_boost: Option<f32>,
+    pub final_score: f32,
+    pub rerank_score: Option<f32>,
+}
+
+/// How the text, vector and graph result lists are fused into one ranking.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// `alpha * vector + (1 - alpha) * text`, plus the graph score
+    WeightedSum { alpha: f32 },
+    /// `Σ 1 / (k + rank)` over every list a result appears in
+    ReciprocalRankFusion { k: u32 },
+}
+
+impl Default for MergeStrategy {
+    fn default() -> Self {
+        Self::ReciprocalRankFusion { k: DEFAULT_RRF_K }
+    }
+}
+
+impl MergeStrategy {
+    /// Contribution of a result at 0-based `rank` with raw `score` in one list.
+    fn contribution(self, signal: Signal, rank: usize, score: f32) -> f32 {
+        match self {
+            Self::ReciprocalRankFusion { k } => 1.0 / (k as f32 + (rank + 1) as f32),
+            Self::WeightedSum { alpha } => {
+                let alpha = alpha.clamp(0.0, 1.0);
+                let weight = match signal {
+                    Signal::Text => 1.0 - alpha,
+                    Signal::Vector => alpha,
+                    Signal::Graph => 1.0,
+                };
+                weight * score
+            }
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::ReciprocalRankFusion { .. } => "rrf",
+            Self::WeightedSum { .. } => "weighted",
+        }
+    }
+}
+
+/// Which signals rank a query. Unset keeps the substring text score.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RetrievalMode {
+    /// Vector similarity only; text matches are dropped
+    VectorOnly,
+    /// BM25 over the text search candidates; vector matches are dropped
+    #[serde(rename = "bm25_only")]
+    BM25Only,
+    /// BM25 over every text and vector candidate, fused with the vector
+    /// score by `merge_strategy` (a weighted sum with this `alpha` if unset)
+    Hybrid { alpha: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Signal {
+    Text,
+    Vector,
+    Graph,
+}
+
+impl Signal {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Text => "Text",
+            Self::Vector => "Vector",
+            Self::Graph => "Graph",
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -51,9 +148,15 @@ pub struct HybridResponse {
 pub struct HybridRetrievalService {
     db: Arc<Database>,
     embedding_service: Arc<dyn EmbeddingService>,
+    code_embedding_service: Arc<dyn EmbeddingService>,
     graph_service: Arc<GraphTraversalService>,
+    log_query_content: bool,
+    superseded_decision_weight: f32,
+    analytics: Option<Arc<AnalyticsService>>,
 }
 
+/// Standard RRF constant; damps the influence of the very top ranks
+pub const DEFAULT_RRF_K: u32 = 60;
 const DEFAULT_GRAPH_MAX_DEPTH: usize = 1;
 const DEFAULT_GRAPH_CAP: usize = 50;
 const DEFAULT_GRAPH_RELATIONS: [&str; 5] = [
@@ -63,6 +166,12 @@ const DEFAULT_GRAPH_RELATIONS: [&str; 5] = [
     "modifies",
     "defined_in",
 ];
+/// Only the head of the ranking is reranked, to bound latency
+pub const RERANK_TOP_K: usize = 20;
+const RERANK_LLM_TIMEOUT_SECS: u64 = 20;
+const RERANK_DOC_CHARS: usize = 600;
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
 
 impl HybridRetrievalService {
     pub fn new(
@@ -72,11 +181,49 @@ impl HybridRetrievalService {
     ) -> Self {
         Self {
             db,
+            code_embedding_service: embedding_service.clone(),
             embedding_service,
             graph_service,
+            log_query_content: false,
+            superseded_decision_weight: 1.0,
+            analytics: None,
         }
     }
 
+    /// Embed code objects' queries with a separate model.
+    pub fn with_code_embedding(
+        mut self,
+        code_embedding_service: Arc<dyn EmbeddingService>,
+    ) -> Self {
+        self.code_embedding_service = code_embedding_service;
+        self
+    }
+
+    /// Log query text verbatim instead of as a hash and length.
+    pub fn with_query_logging(mut self, log_query_content: bool) -> Self {
+        self.log_query_content = log_query_content;
+        self
+    }
+
+    /// Scale superseded decisions' scores so current rationale ranks first.
+    pub fn with_superseded_weight(mut self, weight: f32) -> Self {
+        self.superseded_decision_weight = weight;
+        self
+    }
+
+    /// Read per-project average document lengths for BM25 from `analytics`.
+    pub fn with_analytics(mut self, analytics: Arc<AnalyticsService>) -> Self {
+        self.analytics = Some(analytics);
+        self
+    }
+
+    /// Code and prose vectors live in different spaces and must be searched separately.
+    fn separate_code_index(&self) -> bool {
+        self.code_embedding_service.is_enabled()
+            && (self.code_embedding_service.model() != self.embedding_service.model()
+                || self.code_embedding_service.dimension() != self.embedding_service.dimension())
+    }
+
     pub async fn execute_hybrid_query(
         &self,
         request: &QueryRequest,
@@ -85,9 +232,9 @@ impl HybridRetrievalService {
         let trace_id = Uuid::new_v4();
 
         tracing::info!(
-            "Executing hybrid query: trace_id={}, text={:?}, has_vector={}, has_graph={}",
+            "Executing hybrid query: trace_id={}, text={}, has_vector={}, has_graph={}",
             trace_id,
-            request.text,
+            QueryLog::new(request.text.as_deref(), self.log_query_content),
             request.vector.is_some(),
             request.graph.is_some()
         );
@@ -126,11 +273,8 @@ impl HybridRetrievalService {
             };
 
             let mut seeded_request = request.clone();
-            let autoseed_query = self.build_autoseed_graph_query(
-                &text_results,
-                &vector_results,
-                request.graph.as_ref(),
-            );
+            let autoseed_query =
+                self.build_autoseed_graph_query(&text_results, &vector_results, request);
             if autoseed_query.is_some() {
                 seeded_request.graph = autoseed_query;
             }
@@ -181,6 +325,30 @@ impl HybridRetrievalService {
             });
         }
 
+        let (text_results, vector_results) = match request.retrieval_mode {
+            Some(mode) => {
+                let project_id = request
+                    .filters
+                    .as_ref()
+                    .and_then(|f| f.project_id.as_deref());
+                let avg_doc_len = project_id
+                    .and_then(|project_id| self.analytics.as_ref()?.avg_doc_len(project_id));
+                apply_retrieval_mode(
+                    mode,
+                    request.text.as_deref().unwrap_or_default(),
+                    text_results,
+                    vector_results,
+                    avg_doc_len,
+                )
+            }
+            None => (text_results, vector_results),
+        };
+        let strategy = match (request.retrieval_mode, request.merge_strategy) {
+            (_, Some(strategy)) => strategy,
+            (Some(RetrievalMode::Hybrid { alpha }), None) => MergeStrategy::WeightedSum { alpha },
+            (_, None) => MergeStrategy::default(),
+        };
+
         // Capture counts before merge
         let text_count = text_results.len();
         let vector_count = vector_results.len();
@@ -194,7 +362,9 @@ impl HybridRetrievalService {
         );
 
         // Merge and deduplicate results
-        let merged_results = self.merge_results(text_results, vector_results, graph_results);
+        let mut merged_results =
+            merge_results(text_results, vector_results, graph_results, strategy);
+        down_rank_superseded(&mut merged_results, self.superseded_decision_weight);
 
         let execution_time_ms = start_time.elapsed().as_millis() as u64;
 
@@ -216,6 +386,169 @@ impl HybridRetrievalService {
         })
     }
 
+    /// Reorder the top `RERANK_TOP_K` results by relevance to `query`, scored
+    /// by the configured index model, or by BM25-style term overlap when no
+    /// model is configured or the call fails. Results past the top K keep
+    /// their order.
+    pub async fn rerank(
+        &self,
+        query: &str,
+        results: &mut [HybridResult],
+        settings: Option<&SettingsConfig>,
+    ) {
+        let head = results.len().min(RERANK_TOP_K);
+        if head == 0 || query.trim().is_empty() {
+            return;
+        }
+        let documents: Vec<String> = results[..head]
+            .iter()
+            .map(|result| rerank_document(&result.object))
+            .collect();
+
+        let llm_scores = match settings.filter(|s| s.index_provider != "none") {
+            Some(settings) => {
+                let service = IndexLlmService::new();
+                match timeout(
+                    Duration::from_secs(RERANK_LLM_TIMEOUT_SECS),
+                    service.score_relevance(settings, query, &documents),
+                )
+                .await
+                {
+                    Ok(Ok(scores)) => Some(scores),
+                    Ok(Err(e)) => {
+                        tracing::warn!("LLM rerank failed, using lexical scores: {}", e);
+                        None
+                    }
+                    Err(_) => {
+                        tracing::warn!("LLM rerank timed out, using lexical scores");
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let scores = llm_scores.unwrap_or_else(|| lexical_scores(query, &documents));
+
+        for (result, score) in results[..head].iter_mut().zip(scores) {
+            result.rerank_score = Some(score);
+            result.score_breakdown.rerank_score = Some(score);
+        }
+        // Stable, so ties keep their retrieval order
+        results[..head].sort_by(|a, b| {
+            b.rerank_score
+                .partial_cmp(&a.rerank_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    /// Rank objects by cosine similarity to the stored embedding of
+    /// `request.object_id`, excluding the source object itself.
+    pub async fn find_similar_by_embedding(
+        &self,
+        request: &SimilarRequest,
+    ) -> Result<Vec<(Value, f32)>, HybridRetrievalError> {
+        let object_id = request
+            .object_id
+            .trim()
+            .trim_start_matches("objects:")
+            .to_string();
+
+        // Objects in another tenant look the same as missing ones
+        let source_query = "SELECT VALUE embedding FROM objects WHERE id = type::thing('objects', $id) AND ($tenant_id = NONE OR tenant_id = $tenant_id)";
+        let source = timeout(
+            Duration::from_secs(5),
+            self.db
+                .client
+                .query(source_query)
+                .bind(("id", object_id.clone()))
+                .bind(("tenant_id", request.tenant_id.clone())),
+        )
+        .await;
+
+        let mut rows = match source {
+            Ok(Ok(mut response)) => take_json_values(&mut response, 0),
+            Ok(Err(e)) => return Err(HybridRetrievalError::DatabaseError(e.to_string())),
+            Err(_) => return Err(HybridRetrievalError::Timeout),
+        };
+        if rows.is_empty() {
+            return Err(HybridRetrievalError::NotFound(format!(
+                "Object {} not found",
+                object_id
+            )));
+        }
+        let vector: Vec<f32> = serde_json::from_value(rows.remove(0))
+            .ok()
+            .filter(|v: &Vec<f32>| !v.is_empty())
+            .ok_or_else(|| {
+                HybridRetrievalError::InvalidQuery(format!("Object {} has no embedding", object_id))
+            })?;
+
+        // Cosine similarity fails on mismatched lengths, so only compare same-dimension vectors
+        let mut conditions = vec![
+            "embedding IS NOT NONE".to_string(),
+            "embedding IS NOT NULL".to_string(),
+            "array::len(embedding) = $dimension".to_string(),
+            "id != type::thing('objects', $id)".to_string(),
+        ];
+        if request
+            .types
+            .as_ref()
+            .is_some_and(|types| !types.is_empty())
+        {
+            conditions.push("type IN $types".to_string());
+        }
+        if request.project_id.is_some() {
+            conditions.push("project_id = $project_id".to_string());
+        }
+        if request.tenant_id.is_some() {
+            conditions.push("tenant_id = $tenant_id".to_string());
+        }
+
+        let limit = request.limit.unwrap_or(10).clamp(1, 100);
+        if self.db.vector_index_dimension("objects").await == Some(vector.len()) {
+            // One extra neighbour: the source object is its own nearest
+            conditions.insert(0, knn_condition("embedding", limit + 1, &vector));
+        }
+        let query_str = format!(
+            "SELECT VALUE {{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, title: title, kind: kind, path: path, language: language, signature: signature, file_path: file_path, start_line: start_line, end_line: end_line, documentation: documentation, status: status, provenance: provenance, links: links, importance: importance, similarity: similarity }} FROM (SELECT *, vector::similarity::cosine(embedding, $vector) AS similarity FROM objects WHERE {} ORDER BY similarity DESC LIMIT {})",
+            conditions.join(" AND "),
+            limit
+        );
+
+        let mut query = self
+            .db
+            .client
+            .query(query_str)
+            .bind(("id", object_id))
+            .bind(("dimension", vector.len() as i64))
+            .bind(("vector", vector));
+        if let Some(types) = &request.types {
+            query = query.bind(("types", types.clone()));
+        }
+        if let Some(project_id) = &request.project_id {
+            query = query.bind(("project_id", project_id.clone()));
+        }
+        if let Some(tenant_id) = &request.tenant_id {
+            query = query.bind(("tenant_id", tenant_id.clone()));
+        }
+
+        let mut results = match timeout(Duration::from_secs(10), query).await {
+            Ok(Ok(mut response)) => take_json_values(&mut response, 0),
+            Ok(Err(e)) => return Err(HybridRetrievalError::DatabaseError(e.to_string())),
+            Err(_) => return Err(HybridRetrievalError::Timeout),
+        };
+        normalize_object_ids(&mut results);
+
+        let min_score = request.min_score.unwrap_or(f32::MIN);
+        Ok(results
+            .into_iter()
+            .filter_map(|obj| {
+                let score = obj.get("similarity").and_then(|v| v.as_f64())? as f32;
+                (score >= min_score).then_some((obj, score))
+            })
+            .collect())
+    }
+
     async fn execute_text_search(
         &self,
         request: &QueryRequest,
@@ -226,11 +559,16 @@ impl HybridRetrievalService {
 
         let query_str = self.build_text_query_string(request);
 
-        tracing::debug!("Executing text search: {}", query_str);
+        if self.log_query_content {
+            tracing::debug!("Executing text search: {}", query_str);
+        }
 
         let query_result = timeout(
             Duration::from_secs(10),
-            self.db.client.query(query_str.clone()),
+            self.db
+                .client
+                .query(query_str.clone())
+                .bind(("tenant_id", filter_tenant(request))),
         )
         .await;
 
@@ -278,37 +616,66 @@ impl HybridRetrievalService {
             self.embedding_service.is_enabled()
         );
 
-        let query_vector = if let Some(vector) = &request.vector {
+        if let Some(vector) = &request.vector {
             tracing::info!("Using provided vector of {} dimensions", vector.len());
-            Some(vector.clone())
-        } else if let Some(text) = &request.text {
-            if self.embedding_service.is_enabled() {
-                tracing::info!("Generating embedding for text: '{}'", text);
-                match self.embedding_service.generate_embedding(text).await {
-                    Ok(vec) => {
-                        tracing::info!("Generated embedding: {} dimensions", vec.len());
-                        Some(vec)
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to generate embedding: {}", e);
-                        None
-                    }
-                }
-            } else {
-                tracing::info!("Embedding service disabled, skipping vector generation");
-                None
-            }
-        } else {
+            return self.run_vector_query(request, vector, None).await;
+        }
+        let Some(text) = &request.text else {
             tracing::info!("No text or vector provided");
-            None
+            return Ok(Vec::new());
         };
 
-        if query_vector.is_none() {
-            return Ok(Vec::new());
+        if !self.separate_code_index() {
+            return match self
+                .embed_query(self.embedding_service.as_ref(), text)
+                .await
+            {
+                Some(vector) => self.run_vector_query(request, &vector, None).await,
+                None => Ok(Vec::new()),
+            };
+        }
+
+        // Each index is searched with a query vector from its own model
+        let mut results = Vec::new();
+        for (service, index) in [
+            (&self.embedding_service, EmbeddingIndex::Prose),
+            (&self.code_embedding_service, EmbeddingIndex::Code),
+        ] {
+            if let Some(vector) = self.embed_query(service.as_ref(), text).await {
+                results.extend(self.run_vector_query(request, &vector, Some(index)).await?);
+            }
         }
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(request.limit.unwrap_or(10));
+        Ok(results)
+    }
+
+    async fn embed_query(&self, service: &dyn EmbeddingService, text: &str) -> Option<Vec<f32>> {
+        if !service.is_enabled() {
+            tracing::info!("Embedding service disabled, skipping vector generation");
+            return None;
+        }
+        tracing::info!("Generating embedding for query text");
+        match service.generate_query_embedding(text).await {
+            Ok(vec) => {
+                tracing::info!("Generated embedding: {} dimensions", vec.len());
+                Some(vec)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to generate embedding: {}", e);
+                None
+            }
+        }
+    }
 
-        let vector = query_vector.unwrap();
-        let query_str = self.build_vector_query_string(request, &vector);
+    async fn run_vector_query(
+        &self,
+        request: &QueryRequest,
+        vector: &[f32],
+        index: Option<EmbeddingIndex>,
+    ) -> Result<Vec<(Value, f32, String)>, HybridRetrievalError> {
+        let knn = self.db.vector_index_dimension("objects").await == Some(vector.len());
+        let query_str = self.build_vector_query_string(request, vector, index, knn);
 
         tracing::info!(
             "Executing vector search with {} dimension vector",
@@ -321,7 +688,10 @@ impl HybridRetrievalService {
 
         let query_result = timeout(
             Duration::from_secs(10),
-            self.db.client.query(query_str.clone()),
+            self.db
+                .client
+                .query(query_str.clone())
+                .bind(("tenant_id", filter_tenant(request))),
         )
         .await;
 
@@ -432,148 +802,8 @@ impl HybridRetrievalService {
         }
     }
 
-    fn merge_results(
-        &self,
-        text_results: Vec<(Value, f32, String)>,
-        vector_results: Vec<(Value, f32, String)>,
-        graph_results: Vec<(Value, f32, String)>,
-    ) -> Vec<HybridResult> {
-        // Reciprocal Rank Fusion (RRF) implementation
-        // Formula: RRF(d) = Σ(1 / (k + rank_i(d))) for all retrieval systems
-        const RRF_K: f32 = 60.0; // Standard RRF constant
-
-        let mut result_map: HashMap<String, HybridResult> = HashMap::new();
-
-        // Sort each result set by score (descending) to get proper ranks
-        let mut sorted_text: Vec<_> = text_results.into_iter().enumerate().collect();
-        sorted_text.sort_by(|a, b| {
-            b.1 .1
-                .partial_cmp(&a.1 .1)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        let mut sorted_vector: Vec<_> = vector_results.into_iter().enumerate().collect();
-        sorted_vector.sort_by(|a, b| {
-            b.1 .1
-                .partial_cmp(&a.1 .1)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        let mut sorted_graph: Vec<_> = graph_results.into_iter().enumerate().collect();
-        sorted_graph.sort_by(|a, b| {
-            b.1 .1
-                .partial_cmp(&a.1 .1)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        // Process text results with RRF scoring
-        for (rank, (obj, original_score, explanation)) in sorted_text.into_iter() {
-            if let Some(id) = obj.get("id").and_then(|v| v.as_str()) {
-                let rrf_score = 1.0 / (RRF_K + (rank + 1) as f32);
-                result_map.insert(
-                    id.to_string(),
-                    HybridResult {
-                        object: obj,
-                        total_score: rrf_score,
-                        text_score: Some(original_score),
-                        vector_score: None,
-                        graph_score: None,
-                        explanation: format!(
-                            "Text(rank:{}, rrf:{:.4}): {}",
-                            rank + 1,
-                            rrf_score,
-                            explanation
-                        ),
-                    },
-                );
-            }
-        }
-
-        // Process vector results with RRF scoring
-        for (rank, (obj, original_score, explanation)) in sorted_vector.into_iter() {
-            if let Some(id) = obj.get("id").and_then(|v| v.as_str()) {
-                let rrf_score = 1.0 / (RRF_K + (rank + 1) as f32);
-
-                if let Some(existing) = result_map.get_mut(id) {
-                    existing.total_score += rrf_score;
-                    existing.vector_score = Some(original_score);
-                    existing.explanation = format!(
-                        "{} + Vector(rank:{}, rrf:{:.4}): {}",
-                        existing.explanation,
-                        rank + 1,
-                        rrf_score,
-                        explanation
-                    );
-                } else {
-                    result_map.insert(
-                        id.to_string(),
-                        HybridResult {
-                            object: obj,
-                            total_score: rrf_score,
-                            text_score: None,
-                            vector_score: Some(original_score),
-                            graph_score: None,
-                            explanation: format!(
-                                "Vector(rank:{}, rrf:{:.4}): {}",
-                                rank + 1,
-                                rrf_score,
-                                explanation
-                            ),
-                        },
-                    );
-                }
-            }
-        }
-
-        // Process graph results with RRF scoring
-        for (rank, (obj, original_score, explanation)) in sorted_graph.into_iter() {
-            if let Some(id) = obj.get("id").and_then(|v| v.as_str()) {
-                let rrf_score = 1.0 / (RRF_K + (rank + 1) as f32);
-
-                if let Some(existing) = result_map.get_mut(id) {
-                    existing.total_score += rrf_score;
-                    existing.graph_score = Some(original_score);
-                    existing.explanation = format!(
-                        "{} + Graph(rank:{}, rrf:{:.4}): {}",
-                        existing.explanation,
-                        rank + 1,
-                        rrf_score,
-                        explanation
-                    );
-                } else {
-                    result_map.insert(
-                        id.to_string(),
-                        HybridResult {
-                            object: obj,
-                            total_score: rrf_score,
-                            text_score: None,
-                            vector_score: None,
-                            graph_score: Some(original_score),
-                            explanation: format!(
-                                "Graph(rank:{}, rrf:{:.4}): {}",
-                                rank + 1,
-                                rrf_score,
-                                explanation
-                            ),
-                        },
-                    );
-                }
-            }
-        }
-
-        // Sort by RRF total score (descending) and return
-        let mut results: Vec<HybridResult> = result_map.into_values().collect();
-        results.sort_by(|a, b| {
-            b.total_score
-                .partial_cmp(&a.total_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        results
-    }
-
     fn build_text_query_string(&self, request: &QueryRequest) -> String {
-        let mut query = "SELECT VALUE { id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, kind: kind, path: path, language: language, signature: signature, documentation: documentation, provenance: provenance, links: links, embedding: embedding } FROM objects".to_string();
+        let mut query = "SELECT VALUE { id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, kind: kind, path: path, language: language, signature: signature, file_path: file_path, start_line: start_line, end_line: end_line, documentation: documentation, title: title, severity: severity, expires_at: expires_at, status: status, provenance: provenance, links: links, importance: importance, embedding: embedding } FROM objects".to_string();
         let mut conditions = Vec::new();
 
         if let Some(text) = &request.text {
@@ -585,6 +815,7 @@ impl HybridRetrievalService {
         }
 
         self.add_filter_conditions(&mut conditions, &request.filters);
+        conditions.extend(expired_warning_condition(request.filters.as_ref()));
 
         if !conditions.is_empty() {
             query.push_str(" WHERE ");
@@ -597,31 +828,52 @@ impl HybridRetrievalService {
         query
     }
 
-    fn build_vector_query_string(&self, request: &QueryRequest, vector: &[f32]) -> String {
+    fn build_vector_query_string(
+        &self,
+        request: &QueryRequest,
+        vector: &[f32],
+        index: Option<EmbeddingIndex>,
+        knn: bool,
+    ) -> String {
         let vector_str = vector
             .iter()
             .map(|f| f.to_string())
             .collect::<Vec<_>>()
             .join(", ");
+        let limit = request.limit.unwrap_or(10);
 
-        let mut inner_query = "SELECT id, type, tenant_id, project_id, name, kind, path, language, signature, documentation, provenance, links, embedding FROM objects WHERE embedding IS NOT NONE AND embedding IS NOT NULL".to_string();
+        // With an HNSW index only the nearest candidates are read, not every row
+        let candidates = if knn {
+            format!("{} AND ", knn_condition("embedding", limit, vector))
+        } else {
+            String::new()
+        };
+        let mut inner_query = format!("SELECT id, type, tenant_id, project_id, name, kind, path, language, signature, file_path, start_line, end_line, documentation, title, severity, expires_at, provenance, links, importance, embedding FROM objects WHERE {}embedding IS NOT NONE AND embedding IS NOT NULL", candidates);
 
         let mut conditions = Vec::new();
         self.add_filter_conditions(&mut conditions, &request.filters);
+        if let Some(index) = index {
+            conditions.push(index.type_condition());
+            conditions.push(format!("array::len(embedding) = {}", vector.len()));
+        }
 
         if !conditions.is_empty() {
             inner_query.push_str(" AND ");
             inner_query.push_str(&conditions.join(" AND "));
         }
 
-        let limit = request.limit.unwrap_or(10);
+        // Applied after the nearest-neighbour scan: next to the KNN operator
+        // this condition makes SurrealDB return no rows at all
+        let expiry = expired_warning_condition(request.filters.as_ref())
+            .map(|condition| format!(" WHERE {}", condition))
+            .unwrap_or_default();
         let inner_ranked_query = format!(
-            "SELECT id, type, tenant_id, project_id, name, kind, path, language, signature, documentation, provenance, links, embedding, vector::similarity::cosine(embedding, [{}]) AS similarity FROM ({}) ORDER BY similarity DESC LIMIT {}",
-            vector_str, inner_query, limit
+            "SELECT id, type, tenant_id, project_id, name, kind, path, language, signature, file_path, start_line, end_line, documentation, title, severity, expires_at, provenance, links, importance, embedding, vector::similarity::cosine(embedding, [{}]) AS similarity FROM ({}){} ORDER BY similarity DESC LIMIT {}",
+            vector_str, inner_query, expiry, limit
         );
 
         format!(
-            "SELECT VALUE {{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, kind: kind, path: path, language: language, signature: signature, documentation: documentation, provenance: provenance, links: links, embedding: embedding, similarity: similarity }} FROM ({})",
+            "SELECT VALUE {{ id: string::concat(id), type: type, tenant_id: tenant_id, project_id: project_id, name: name, kind: kind, path: path, language: language, signature: signature, file_path: file_path, start_line: start_line, end_line: end_line, documentation: documentation, title: title, severity: severity, expires_at: expires_at, status: status, provenance: provenance, links: links, importance: importance, embedding: embedding, similarity: similarity }} FROM ({})",
             inner_ranked_query
         )
     }
@@ -660,6 +912,7 @@ impl HybridRetrievalService {
                 "modifies" => 0.8,
                 "justified_by" => 0.75,
                 "produced" => 0.7,
+                "superseded_by" => 0.65,
                 _ => default_weight,
             }
         };
@@ -680,8 +933,9 @@ impl HybridRetrievalService {
         &self,
         text_results: &[(Value, f32, String)],
         vector_results: &[(Value, f32, String)],
-        overrides: Option<&GraphQuery>,
+        request: &QueryRequest,
     ) -> Option<GraphQuery> {
+        let overrides = request.graph.as_ref();
         let mut ids: Vec<Uuid> = Vec::new();
         let mut seen = HashSet::new();
 
@@ -724,6 +978,7 @@ impl HybridRetrievalService {
                 .or(Some(crate::handlers::query::GraphDirection::Both)),
             algorithm: Some(TraversalAlgorithm::Collect),
             target_node: None,
+            tenant_id: filter_tenant(request),
         })
     }
 
@@ -751,9 +1006,13 @@ impl HybridRetrievalService {
                 conditions.push(format!("project_id = '{}'", project_id.replace("'", "\\'")));
             }
 
-            if let Some(tenant_id) = &filters.tenant_id {
-                conditions.push(format!("tenant_id = '{}'", tenant_id.replace("'", "\\'")));
+            // Bound as $tenant_id by the text and vector searches
+            if filters.tenant_id.is_some() {
+                conditions.push("tenant_id = $tenant_id".to_string());
             }
+
+            conditions.extend(filters.created_conditions());
+            conditions.extend(filters.code_conditions());
         }
     }
 
@@ -785,3 +1044,555 @@ impl HybridRetrievalService {
         0.6 // Default for other matches
     }
 }
+
+/// Fields that make up an object's searchable text
+pub const DOCUMENT_FIELDS: [&str; 10] = [
+    "name",
+    "title",
+    "path",
+    "file_path",
+    "signature",
+    "summary",
+    "description",
+    "documentation",
+    "rationale",
+    "content",
+];
+
+/// Tenant filter of `request`, bound as `$tenant_id` wherever the queries reference it
+fn filter_tenant(request: &QueryRequest) -> Option<String> {
+    request.filters.as_ref().and_then(|f| f.tenant_id.clone())
+}
+
+fn document_text(object: &Value) -> String {
+    DOCUMENT_FIELDS
+        .iter()
+        .filter_map(|field| object.get(*field).and_then(|v| v.as_str()))
+        .filter(|value| !value.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Text a reranker sees for one result: its name-like fields and a bounded
+/// slice of its content.
+fn rerank_document(object: &Value) -> String {
+    document_text(object)
+        .chars()
+        .take(RERANK_DOC_CHARS)
+        .collect()
+}
+
+fn rerank_terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.len() > 1)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// BM25 over the candidate set, normalized so the best document scores 1.0.
+fn lexical_scores(query: &str, documents: &[String]) -> Vec<f32> {
+    const K1: f32 = 1.2;
+    const B: f32 = 0.75;
+
+    let query_terms: HashSet<String> = rerank_terms(query).into_iter().collect();
+    let docs: Vec<Vec<String>> = documents.iter().map(|doc| rerank_terms(doc)).collect();
+    let doc_count = docs.len() as f32;
+    let avg_len = (docs.iter().map(Vec::len).sum::<usize>() as f32 / doc_count).max(1.0);
+
+    let raw: Vec<f32> = docs
+        .iter()
+        .map(|terms| {
+            query_terms
+                .iter()
+                .map(|term| {
+                    let tf = terms.iter().filter(|t| *t == term).count() as f32;
+                    if tf == 0.0 {
+                        return 0.0;
+                    }
+                    let containing = docs.iter().filter(|d| d.contains(term)).count() as f32;
+                    let idf = ((doc_count - containing + 0.5) / (containing + 0.5) + 1.0).ln();
+                    let norm = K1 * (1.0 - B + B * terms.len() as f32 / avg_len);
+                    idf * tf * (K1 + 1.0) / (tf + norm)
+                })
+                .sum()
+        })
+        .collect();
+
+    let max = raw.iter().cloned().fold(0.0_f32, f32::max);
+    if max <= 0.0 {
+        return vec![0.0; raw.len()];
+    }
+    raw.into_iter().map(|score| score / max).collect()
+}
+
+/// BM25 relevance of `doc_text` to `query_terms`, with term frequency
+/// saturating at `k1` and length normalization weighted by `b`.
+pub fn compute_bm25_score(
+    query_terms: &[&str],
+    doc_text: &str,
+    avg_doc_len: f64,
+    k1: f32,
+    b: f32,
+) -> f32 {
+    let terms = rerank_terms(doc_text);
+    let length_norm = 1.0 - b + b * terms.len() as f32 / avg_doc_len.max(1.0) as f32;
+    let unique: HashSet<String> = query_terms.iter().map(|t| t.to_lowercase()).collect();
+    unique
+        .iter()
+        .map(|term| {
+            let tf = terms.iter().filter(|t| *t == term).count() as f32;
+            tf * (k1 + 1.0) / (tf + k1 * length_norm)
+        })
+        .sum()
+}
+
+/// An object with its score in one result list and why it matched
+type ScoredObject = (Value, f32, String);
+
+/// Rescore the candidates for `mode`: BM25 replaces the text score, scaled
+/// so the best candidate scores 1.0 like a cosine match. Without a cached
+/// `avg_doc_len` the candidates' own average is used.
+fn apply_retrieval_mode(
+    mode: RetrievalMode,
+    query: &str,
+    text_results: Vec<ScoredObject>,
+    vector_results: Vec<ScoredObject>,
+    avg_doc_len: Option<f64>,
+) -> (Vec<ScoredObject>, Vec<ScoredObject>) {
+    let (candidates, vector_results) = match mode {
+        RetrievalMode::VectorOnly => return (Vec::new(), vector_results),
+        RetrievalMode::BM25Only => (text_results, Vec::new()),
+        RetrievalMode::Hybrid { .. } => {
+            let mut seen = HashSet::new();
+            let candidates = text_results
+                .into_iter()
+                .chain(vector_results.iter().cloned())
+                .filter(|(obj, _, _)| seen.insert(obj["id"].to_string()))
+                .collect();
+            (candidates, vector_results)
+        }
+    };
+
+    let documents: Vec<String> = candidates
+        .iter()
+        .map(|(obj, _, _)| document_text(obj))
+        .collect();
+    let avg_doc_len = avg_doc_len.unwrap_or_else(|| {
+        let total: usize = documents.iter().map(|d| rerank_terms(d).len()).sum();
+        total as f64 / documents.len().max(1) as f64
+    });
+    let query_terms = rerank_terms(query);
+    let query_terms: Vec<&str> = query_terms.iter().map(String::as_str).collect();
+    let scores: Vec<f32> = documents
+        .iter()
+        .map(|doc| compute_bm25_score(&query_terms, doc, avg_doc_len, BM25_K1, BM25_B))
+        .collect();
+    let max = scores.iter().cloned().fold(0.0_f32, f32::max);
+
+    let text_results = candidates
+        .into_iter()
+        .zip(scores)
+        .filter(|(_, score)| *score > 0.0)
+        .map(|((obj, _, _), score)| (obj, score / max, format!("BM25 {:.4}", score)))
+        .collect();
+    (text_results, vector_results)
+}
+
+/// Fuse per-system result lists into one deduplicated ranking.
+fn merge_results(
+    text_results: Vec<(Value, f32, String)>,
+    vector_results: Vec<(Value, f32, String)>,
+    graph_results: Vec<(Value, f32, String)>,
+    strategy: MergeStrategy,
+) -> Vec<HybridResult> {
+    let mut result_map: HashMap<String, HybridResult> = HashMap::new();
+
+    for (signal, mut results) in [
+        (Signal::Text, text_results),
+        (Signal::Vector, vector_results),
+        (Signal::Graph, graph_results),
+    ] {
+        // Rank within each list by its own score
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (rank, (obj, original_score, explanation)) in results.into_iter().enumerate() {
+            let Some(id) = obj.get("id").and_then(|v| v.as_str()).map(str::to_string) else {
+                continue;
+            };
+            let contribution = strategy.contribution(signal, rank, original_score);
+            let detail = format!(
+                "{}(rank:{}, {}:{:.4}): {}",
+                signal.name(),
+                rank + 1,
+                strategy.label(),
+                contribution,
+                explanation
+            );
+
+            let entry = result_map.entry(id).or_insert_with(|| HybridResult {
+                object: obj,
+                total_score: 0.0,
+                text_score: None,
+                vector_score: None,
+                graph_score: None,
+                explanation: String::new(),
+                score_breakdown: ScoreBreakdown::default(),
+                source: String::new(),
+                rerank_score: None,
+            });
+            entry.total_score += contribution;
+            entry.explanation = if entry.explanation.is_empty() {
+                detail
+            } else {
+                format!("{} + {}", entry.explanation, detail)
+            };
+            match signal {
+                Signal::Text => entry.text_score = Some(original_score),
+                Signal::Vector => entry.vector_score = Some(original_score),
+                Signal::Graph => {
+                    entry.graph_score = Some(original_score);
+                    entry.score_breakdown.graph_boost = Some(contribution);
+                }
+            }
+        }
+    }
+
+    for result in result_map.values_mut() {
+        result.score_breakdown.vector_score = result.vector_score;
+        result.score_breakdown.text_score = result.text_score;
+        result.score_breakdown.final_score = result.total_score;
+        result.source = retrieval_source(result);
+    }
+
+    let mut results: Vec<HybridResult> = result_map.into_values().collect();
+    results.sort_by(by_score_then_importance);
+    results
+}
+
+/// Scale the scores of superseded decisions by `weight` and re-sort.
+fn down_rank_superseded(results: &mut [HybridResult], weight: f32) {
+    if weight >= 1.0 {
+        return;
+    }
+    let mut changed = false;
+    for result in results.iter_mut() {
+        let object = &result.object;
+        if object["type"] == "decision" && object["status"] == "superseded" {
+            result.total_score *= weight;
+            result.score_breakdown.final_score = result.total_score;
+            result.explanation = format!("{} x superseded({})", result.explanation, weight);
+            changed = true;
+        }
+    }
+    if changed {
+        results.sort_by(by_score_then_importance);
+    }
+}
+
+/// Highest score first; equal scores fall back to the stored PageRank
+/// `importance`, so central symbols win ties.
+fn by_score_then_importance(a: &HybridResult, b: &HybridResult) -> std::cmp::Ordering {
+    let importance = |result: &HybridResult| result.object["importance"].as_f64().unwrap_or(0.0);
+    b.total_score
+        .partial_cmp(&a.total_score)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then_with(|| importance(b).total_cmp(&importance(a)))
+}
+
+fn retrieval_source(result: &HybridResult) -> String {
+    let mut sources = Vec::new();
+    if result.text_score.is_some() {
+        sources.push("text");
+    }
+    if result.vector_score.is_some() {
+        sources.push("vector");
+    }
+    if result.graph_score.is_some() {
+        sources.push("graph");
+    }
+    sources.join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_state, test_config};
+
+    fn candidate(id: &str, name: &str, content: &str, score: f32) -> HybridResult {
+        HybridResult {
+            object: serde_json::json!({ "id": id, "name": name, "content": content }),
+            total_score: score,
+            text_score: None,
+            vector_score: Some(score),
+            graph_score: None,
+            explanation: String::new(),
+            score_breakdown: ScoreBreakdown::default(),
+            source: "vector".to_string(),
+            rerank_score: None,
+        }
+    }
+
+    fn ranked(entries: &[(&str, f32)]) -> Vec<(Value, f32, String)> {
+        entries
+            .iter()
+            .map(|(id, score)| (serde_json::json!({ "id": id }), *score, String::new()))
+            .collect()
+    }
+
+    #[test]
+    fn test_rrf_merges_partially_overlapping_lists() {
+        // Input order is deliberately not score order; ranks come from scores
+        let text = ranked(&[("c", 0.7), ("a", 0.9), ("b", 0.8)]);
+        let vector = ranked(&[("b", 0.95), ("d", 0.9), ("a", 0.5)]);
+
+        let merged = merge_results(
+            text,
+            vector,
+            Vec::new(),
+            MergeStrategy::ReciprocalRankFusion { k: 60 },
+        );
+
+        // b: 1/62 + 1/61, a: 1/61 + 1/63, d: 1/62, c: 1/63
+        assert_eq!(ids(&merged), vec!["b", "a", "d", "c"]);
+        let b = &merged[0];
+        assert!((b.total_score - (1.0 / 62.0 + 1.0 / 61.0)).abs() < 1e-6);
+        assert_eq!(b.source, "text+vector");
+        assert_eq!(merged[2].source, "vector");
+    }
+
+    #[test]
+    fn test_weighted_sum_uses_raw_scores() {
+        let text = ranked(&[("a", 0.9), ("b", 0.8), ("c", 0.7)]);
+        let vector = ranked(&[("b", 0.6), ("d", 0.9), ("a", 0.5)]);
+
+        let merged = merge_results(
+            text,
+            vector,
+            Vec::new(),
+            MergeStrategy::WeightedSum { alpha: 0.8 },
+        );
+
+        // d: 0.72, b: 0.16 + 0.48, a: 0.18 + 0.40, c: 0.14
+        assert_eq!(ids(&merged), vec!["d", "b", "a", "c"]);
+        assert!((merged[1].total_score - 0.64).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_equal_scores_break_ties_by_importance() {
+        let text = vec![
+            (
+                serde_json::json!({ "id": "leaf", "importance": 0.1 }),
+                0.5,
+                String::new(),
+            ),
+            (
+                serde_json::json!({ "id": "hub", "importance": 0.6 }),
+                0.5,
+                String::new(),
+            ),
+            (serde_json::json!({ "id": "unranked" }), 0.5, String::new()),
+        ];
+
+        let merged = merge_results(
+            text,
+            Vec::new(),
+            Vec::new(),
+            MergeStrategy::WeightedSum { alpha: 0.5 },
+        );
+
+        assert_eq!(ids(&merged), vec!["hub", "leaf", "unranked"]);
+    }
+
+    #[test]
+    fn test_bm25_ranks_exact_match_above_semantic_neighbor() {
+        let corpus = [
+            (
+                "exact",
+                "evict_cache",
+                "Cache eviction keeps the newest blocks",
+            ),
+            (
+                "semantic",
+                "purge_stale",
+                "Drop stale memory entries once storage fills",
+            ),
+            (
+                "other",
+                "parse_config",
+                "Read settings from the environment",
+            ),
+        ];
+        let candidate = |(id, name, content): (&str, &str, &str), score: f32| {
+            let object = serde_json::json!({ "id": id, "name": name, "content": content });
+            (object, score, String::new())
+        };
+        let doc = |i: usize| format!("{} | {}", corpus[i].1, corpus[i].2);
+        let query = ["cache", "eviction"];
+
+        let exact = compute_bm25_score(&query, &doc(0), 6.0, BM25_K1, BM25_B);
+        let semantic = compute_bm25_score(&query, &doc(1), 6.0, BM25_K1, BM25_B);
+        assert!(exact > semantic, "{} <= {}", exact, semantic);
+        assert_eq!(semantic, 0.0);
+
+        // The vector index prefers the semantic neighbour
+        let text = vec![candidate(corpus[0], 0.6), candidate(corpus[2], 0.6)];
+        let vector = vec![candidate(corpus[1], 0.9), candidate(corpus[0], 0.6)];
+
+        let (bm25, dropped) = apply_retrieval_mode(
+            RetrievalMode::BM25Only,
+            "cache eviction",
+            text.clone(),
+            vector.clone(),
+            None,
+        );
+        assert!(dropped.is_empty());
+        assert_eq!(bm25.len(), 1);
+        assert_eq!((bm25[0].0["id"].as_str(), bm25[0].1), (Some("exact"), 1.0));
+
+        let alpha = 0.5;
+        let (text, vector) = apply_retrieval_mode(
+            RetrievalMode::Hybrid { alpha },
+            "cache eviction",
+            text,
+            vector,
+            Some(6.0),
+        );
+        let merged = merge_results(
+            text,
+            vector,
+            Vec::new(),
+            MergeStrategy::WeightedSum { alpha },
+        );
+        assert_eq!(merged[0].object["id"], "exact");
+        assert!((merged[0].total_score - 0.8).abs() < 1e-6);
+        assert!((merged[1].total_score - 0.45).abs() < 1e-6);
+
+        let (text, _) = apply_retrieval_mode(
+            RetrievalMode::VectorOnly,
+            "cache eviction",
+            bm25,
+            Vec::new(),
+            None,
+        );
+        assert!(text.is_empty());
+    }
+
+    #[test]
+    fn test_retrieval_mode_deserializes_tagged_variants() {
+        let bm25: RetrievalMode =
+            serde_json::from_value(serde_json::json!({ "type": "bm25_only" })).unwrap();
+        assert_eq!(bm25, RetrievalMode::BM25Only);
+        let hybrid: RetrievalMode =
+            serde_json::from_value(serde_json::json!({ "type": "hybrid", "alpha": 0.7 })).unwrap();
+        assert_eq!(hybrid, RetrievalMode::Hybrid { alpha: 0.7 });
+    }
+
+    #[test]
+    fn test_superseded_decisions_are_down_ranked() {
+        let mut old = candidate("old", "Sessions in cookies", "", 0.9);
+        old.object["type"] = "decision".into();
+        old.object["status"] = "superseded".into();
+        let mut new = candidate("new", "Sessions in Redis", "", 0.6);
+        new.object["type"] = "decision".into();
+        new.object["status"] = "accepted".into();
+        let mut results = vec![old, new];
+
+        down_rank_superseded(&mut results, 0.5);
+        assert_eq!(results[0].object["id"], "new");
+        assert!((results[1].total_score - 0.45).abs() < 1e-6);
+        assert!(results[1].explanation.ends_with("x superseded(0.5)"));
+    }
+
+    #[test]
+    fn test_merge_strategy_deserializes_tagged_variants() {
+        let rrf: MergeStrategy = serde_json::from_value(
+            serde_json::json!({ "type": "reciprocal_rank_fusion", "k": 10 }),
+        )
+        .unwrap();
+        assert_eq!(rrf, MergeStrategy::ReciprocalRankFusion { k: 10 });
+        let weighted: MergeStrategy =
+            serde_json::from_value(serde_json::json!({ "type": "weighted_sum", "alpha": 0.3 }))
+                .unwrap();
+        assert_eq!(weighted, MergeStrategy::WeightedSum { alpha: 0.3 });
+        assert_eq!(
+            MergeStrategy::default(),
+            MergeStrategy::ReciprocalRankFusion { k: DEFAULT_RRF_K }
+        );
+    }
+
+    /// Vector search ranked the token refresh handler last.
+    fn planted_results() -> Vec<HybridResult> {
+        vec![
+            candidate("a", "render_sidebar", "Draws the navigation sidebar", 0.031),
+            candidate("b", "parse_config", "Reads settings from TOML files", 0.030),
+            candidate(
+                "c",
+                "open_socket",
+                "Connects to the websocket server",
+                0.029,
+            ),
+            candidate(
+                "d",
+                "refresh_auth_token",
+                "Refreshes the expired auth token before retrying the request",
+                0.010,
+            ),
+        ]
+    }
+
+    fn ids(results: &[HybridResult]) -> Vec<&str> {
+        results
+            .iter()
+            .map(|r| r.object["id"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_lexical_rerank_lifts_planted_relevant_result() {
+        let state = memory_state(test_config()).await;
+        let mut results = planted_results();
+
+        state
+            .hybrid_service
+            .rerank("refresh expired auth token", &mut results, None)
+            .await;
+
+        assert_eq!(ids(&results)[0], "d");
+        assert_eq!(results[0].rerank_score, Some(1.0));
+        // Retrieval scores are kept alongside the rerank score
+        assert_eq!(results[0].total_score, 0.010);
+        assert_eq!(results[0].score_breakdown.rerank_score, Some(1.0));
+        // Unrelated results tie at zero and keep their retrieval order
+        assert_eq!(&ids(&results)[1..], &["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_llm_rerank_uses_model_scores() {
+        use axum::{routing::post, Json, Router};
+
+        let app = Router::new().route(
+            "/api/chat",
+            post(|| async {
+                Json(serde_json::json!({ "message": { "content": "Scores: [1, 3, 9, 0]" } }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let settings = SettingsConfig {
+            index_provider: "ollama".to_string(),
+            ollama_url: url,
+            ..Default::default()
+        };
+        let state = memory_state(test_config()).await;
+        let mut results = planted_results();
+        state
+            .hybrid_service
+            .rerank("websocket connection", &mut results, Some(&settings))
+            .await;
+
+        assert_eq!(ids(&results), vec!["c", "b", "a", "d"]);
+        assert_eq!(results[0].rerank_score, Some(0.9));
+    }
+}
@@ -40,34 +40,102 @@ impl IndexLlmService {
         settings: &SettingsConfig,
         input: AiFileLogInput,
     ) -> Result<AiFileLogOutput> {
+        let prompt = build_filelog_prompt(&input);
+        let content = self
+            .complete(
+                settings,
+                "You are a precise codebase analyst. Return ONLY valid JSON.",
+                &prompt,
+            )
+            .await?;
+        parse_filelog_json(&content)
+    }
+
+    /// Summarize a closed cache block's items in 2-4 plain sentences.
+    pub async fn summarize_cache_block(
+        &self,
+        settings: &SettingsConfig,
+        items: &[String],
+    ) -> Result<String> {
+        if items.is_empty() {
+            anyhow::bail!("Cache block has no items to summarize");
+        }
+
+        let prompt = build_block_summary_prompt(items);
+        let content = self
+            .complete(
+                settings,
+                "You summarize an AI agent's working memory. Reply with plain prose only.",
+                &prompt,
+            )
+            .await?;
+
+        let summary = content.trim();
+        if summary.is_empty() {
+            anyhow::bail!("Index model returned an empty summary");
+        }
+        Ok(summary.to_string())
+    }
+
+    /// Score how well each document answers `query`, 0.0-1.0, in input order.
+    pub async fn score_relevance(
+        &self,
+        settings: &SettingsConfig,
+        query: &str,
+        documents: &[String],
+    ) -> Result<Vec<f32>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let prompt = build_relevance_prompt(query, documents);
+        let content = self
+            .complete(
+                settings,
+                "You rank search results for a coding assistant. Return ONLY a JSON array of numbers.",
+                &prompt,
+            )
+            .await?;
+        parse_relevance_scores(&content, documents.len())
+    }
+
+    async fn complete(
+        &self,
+        settings: &SettingsConfig,
+        system: &str,
+        prompt: &str,
+    ) -> Result<String> {
         match settings.index_provider.as_str() {
             "openai" => {
-                self.generate_openai(
+                self.complete_openai(
                     settings,
-                    input,
+                    system,
+                    prompt,
                     "https://api.openai.com/v1/chat/completions",
                 )
                 .await
             }
             "openrouter" => {
-                self.generate_openai(
+                self.complete_openai(
                     settings,
-                    input,
+                    system,
+                    prompt,
                     "https://openrouter.ai/api/v1/chat/completions",
                 )
                 .await
             }
-            "ollama" => self.generate_ollama(settings, input).await,
+            "ollama" => self.complete_ollama(settings, system, prompt).await,
             _ => anyhow::bail!("Index model provider is disabled"),
         }
     }
 
-    async fn generate_openai(
+    async fn complete_openai(
         &self,
         settings: &SettingsConfig,
-        input: AiFileLogInput,
+        system: &str,
+        prompt: &str,
         base_url: &str,
-    ) -> Result<AiFileLogOutput> {
+    ) -> Result<String> {
         let model = match settings.index_provider.as_str() {
             "openrouter" => settings.index_openrouter_model.clone(),
             _ => settings.index_openai_model.clone(),
@@ -81,14 +149,13 @@ impl IndexLlmService {
             anyhow::bail!("API key is missing for index model provider");
         }
 
-        let prompt = build_filelog_prompt(&input);
         let body = serde_json::json!({
             "model": model,
             "temperature": 0.2,
             "messages": [
                 {
                     "role": "system",
-                    "content": "You are a precise codebase analyst. Return ONLY valid JSON."
+                    "content": system
                 },
                 {
                     "role": "user",
@@ -116,27 +183,25 @@ impl IndexLlmService {
         }
 
         let payload: OpenAIChatResponse = response.json().await?;
-        let content = payload
+        payload
             .choices
-            .get(0)
-            .and_then(|c| c.message.content.as_ref())
-            .context("Missing model response content")?;
-
-        parse_filelog_json(content)
+            .first()
+            .and_then(|c| c.message.content.clone())
+            .context("Missing model response content")
     }
 
-    async fn generate_ollama(
+    async fn complete_ollama(
         &self,
         settings: &SettingsConfig,
-        input: AiFileLogInput,
-    ) -> Result<AiFileLogOutput> {
-        let prompt = build_filelog_prompt(&input);
+        system: &str,
+        prompt: &str,
+    ) -> Result<String> {
         let body = serde_json::json!({
             "model": settings.index_ollama_model,
             "messages": [
                 {
                     "role": "system",
-                    "content": "You are a precise codebase analyst. Return ONLY valid JSON."
+                    "content": system
                 },
                 {
                     "role": "user",
@@ -154,8 +219,67 @@ impl IndexLlmService {
         }
 
         let payload: OllamaChatResponse = response.json().await?;
-        parse_filelog_json(&payload.message.content)
+        Ok(payload.message.content)
+    }
+}
+
+fn build_block_summary_prompt(items: &[String]) -> String {
+    format!(
+        "Summarize the following working-memory items from a coding session in 2-4 sentences.\n\
+Focus on what was decided, learned, or changed, and name the files or components involved.\n\
+Do not use bullet points, headings, or preamble.\n\
+\n\
+Items:\n\
+- {}\n",
+        items.join("\n- ")
+    )
+}
+
+fn build_relevance_prompt(query: &str, documents: &[String]) -> String {
+    let listed: Vec<String> = documents
+        .iter()
+        .enumerate()
+        .map(|(idx, doc)| format!("[{}] {}", idx, doc))
+        .collect();
+    format!(
+        "Query: {}\n\
+\n\
+Rate how relevant each document is to the query from 0 (unrelated) to 10 (exact answer).\n\
+Reply with a JSON array of {} numbers, one per document, in document order.\n\
+\n\
+Documents:\n\
+{}\n",
+        query,
+        documents.len(),
+        listed.join("\n")
+    )
+}
+
+/// Parse a `[7, 2, ...]` reply into 0.0-1.0 scores; the count must match.
+fn parse_relevance_scores(raw: &str, expected: usize) -> Result<Vec<f32>> {
+    let trimmed = raw.trim();
+    let start = trimmed
+        .find('[')
+        .context("Missing score array in model response")?;
+    let end = trimmed
+        .rfind(']')
+        .context("Missing score array in model response")?;
+    if end <= start {
+        anyhow::bail!("Malformed score array in model response");
+    }
+    let scores: Vec<f32> =
+        serde_json::from_str(&trimmed[start..=end]).context("Failed to parse relevance scores")?;
+    if scores.len() != expected {
+        anyhow::bail!(
+            "Expected {} relevance scores, model returned {}",
+            expected,
+            scores.len()
+        );
     }
+    Ok(scores
+        .into_iter()
+        .map(|score| (score / 10.0).clamp(0.0, 1.0))
+        .collect())
 }
 
 fn build_filelog_prompt(input: &AiFileLogInput) -> String {
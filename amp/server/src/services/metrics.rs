@@ -0,0 +1,154 @@
+use crate::services::embedding::{EmbeddingError, EmbeddingService};
+use async_trait::async_trait;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::{Arc, Mutex};
+
+/// Prometheus collectors for the `/metrics` endpoint (opt-in via `AMP_METRICS_ENABLED`).
+pub struct MetricsState {
+    registry: Registry,
+    requests_total: IntCounterVec,
+    request_latency: Histogram,
+    objects_total: IntGaugeVec,
+    cache_blocks_total: IntGaugeVec,
+    embedding_errors_total: IntCounter,
+}
+
+impl MetricsState {
+    pub fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new();
+
+        let requests_total = IntCounterVec::new(
+            Opts::new("amp_requests_total", "HTTP requests handled by the AMP server"),
+            &["endpoint", "method", "status"],
+        )?;
+        let request_latency = Histogram::with_opts(HistogramOpts::new(
+            "amp_request_latency_seconds",
+            "HTTP request latency in seconds",
+        ))?;
+        let objects_total = IntGaugeVec::new(
+            Opts::new("amp_objects_total", "Stored objects by type"),
+            &["type"],
+        )?;
+        let cache_blocks_total = IntGaugeVec::new(
+            Opts::new("amp_cache_blocks_total", "Cache blocks by status"),
+            &["status"],
+        )?;
+        let embedding_errors_total = IntCounter::new(
+            "amp_embedding_errors_total",
+            "Failed embedding generation calls",
+        )?;
+
+        registry.register(Box::new(requests_total.clone()))?;
+        registry.register(Box::new(request_latency.clone()))?;
+        registry.register(Box::new(objects_total.clone()))?;
+        registry.register(Box::new(cache_blocks_total.clone()))?;
+        registry.register(Box::new(embedding_errors_total.clone()))?;
+
+        Ok(Self {
+            registry,
+            requests_total,
+            request_latency,
+            objects_total,
+            cache_blocks_total,
+            embedding_errors_total,
+        })
+    }
+
+    pub fn record_request(&self, endpoint: &str, method: &str, status: u16, latency_secs: f64) {
+        self.requests_total
+            .with_label_values(&[endpoint, method, &status.to_string()])
+            .inc();
+        self.request_latency.observe(latency_secs);
+    }
+
+    pub fn record_embedding_error(&self) {
+        self.embedding_errors_total.inc();
+    }
+
+    /// Replace the sampled object counts; types missing from `counts` drop out.
+    pub fn set_object_counts(&self, counts: &[(String, i64)]) {
+        self.objects_total.reset();
+        for (obj_type, count) in counts {
+            self.objects_total.with_label_values(&[obj_type]).set(*count);
+        }
+    }
+
+    pub fn set_cache_block_counts(&self, counts: &[(String, i64)]) {
+        self.cache_blocks_total.reset();
+        for (status, count) in counts {
+            self.cache_blocks_total.with_label_values(&[status]).set(*count);
+        }
+    }
+
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+/// Counts embedding failures from any provider without touching call sites.
+pub struct MeteredEmbedding {
+    inner: Box<dyn EmbeddingService>,
+    metrics: Arc<Mutex<MetricsState>>,
+}
+
+impl MeteredEmbedding {
+    pub fn new(inner: Box<dyn EmbeddingService>, metrics: Arc<Mutex<MetricsState>>) -> Self {
+        Self { inner, metrics }
+    }
+
+    /// Wrap `inner` only when metrics are enabled.
+    pub fn wrap_if_enabled(
+        inner: Box<dyn EmbeddingService>,
+        metrics: Option<&Arc<Mutex<MetricsState>>>,
+    ) -> Arc<dyn EmbeddingService> {
+        match metrics {
+            Some(metrics) => Arc::new(Self::new(inner, metrics.clone())),
+            None => Arc::from(inner),
+        }
+    }
+
+    fn observe<T>(&self, result: Result<T, EmbeddingError>) -> Result<T, EmbeddingError> {
+        if result.is_err() {
+            self.metrics.lock().unwrap().record_embedding_error();
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for MeteredEmbedding {
+    async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        let result = self.inner.generate_embedding(text).await;
+        self.observe(result)
+    }
+
+    async fn generate_query_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        let result = self.inner.generate_query_embedding(text).await;
+        self.observe(result)
+    }
+
+    async fn generate_embeddings_batch(
+        &self,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let result = self.inner.generate_embeddings_batch(texts).await;
+        self.observe(result)
+    }
+
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.inner.is_enabled()
+    }
+
+    fn model(&self) -> String {
+        self.inner.model()
+    }
+}
@@ -3,9 +3,16 @@ pub mod cache;
 pub mod chunking;
 pub mod codebase_parser;
 pub mod embedding;
+pub mod embedding_cache;
+pub mod fallback_parser;
 pub mod filelog_generator;
+pub mod git_blame;
 pub mod graph;
 pub mod hybrid;
 pub mod index_llm;
+pub mod metrics;
+pub mod parse_cache;
+pub mod path_resolver;
 pub mod settings;
 pub mod storage;
+pub mod word_windows;
@@ -0,0 +1,92 @@
+use crate::services::codebase_parser::ParsedFile;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Files kept before the least recently synced one is evicted.
+const CAPACITY: usize = 512;
+
+/// Last parse of recently synced files, keyed by file id, so the next sync of
+/// the same file can reparse incrementally.
+///
+/// Memory only. Persisting the tree next to the FileLog is not possible:
+/// tree-sitter has no API to serialize or deserialize a `Tree`, so one could
+/// only be rebuilt by a full parse anyway. A miss after a restart or an
+/// eviction therefore costs one full parse, after which edits are incremental
+/// again.
+#[derive(Default)]
+pub struct ParseCache {
+    inner: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    /// Parse and the tick it was last stored at, for least-recently-used eviction
+    files: HashMap<String, (u64, ParsedFile)>,
+    tick: u64,
+}
+
+impl ParseCache {
+    /// Remove and return the cached parse of `file_id`.
+    pub fn take(&self, file_id: &str) -> Option<ParsedFile> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.files.remove(file_id).map(|(_, parsed)| parsed)
+    }
+
+    /// Store the parse of `file_id`, evicting the least recently stored file
+    /// when full. Syncs `take` and re-insert their file, so this is also the
+    /// least recently synced one.
+    pub fn insert(&self, file_id: String, parsed: ParsedFile) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.files.len() >= CAPACITY && !inner.files.contains_key(&file_id) {
+            let oldest = inner
+                .files
+                .iter()
+                .min_by_key(|(_, (tick, _))| *tick)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                inner.files.remove(&oldest);
+            }
+        }
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.files.insert(file_id, (tick, parsed));
+    }
+
+    pub fn remove(&self, file_id: &str) {
+        self.inner.lock().unwrap().files.remove(file_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::codebase_parser::CodebaseParser;
+    use std::path::Path;
+
+    fn parsed(parser: &CodebaseParser, source: &str) -> ParsedFile {
+        let (_, parsed, _) = parser
+            .parse_content_incremental(Path::new("src/lib.rs"), "rust", source.to_string(), None)
+            .unwrap();
+        parsed.unwrap()
+    }
+
+    #[test]
+    fn test_full_cache_evicts_the_least_recently_synced_file() {
+        let parser = CodebaseParser::new().unwrap();
+        let cache = ParseCache::default();
+        for i in 0..CAPACITY {
+            cache.insert(format!("file-{}", i), parsed(&parser, "fn a() {}\n"));
+        }
+
+        // Syncing file-0 again makes file-1 the oldest
+        let first = cache.take("file-0").unwrap();
+        cache.insert("file-0".to_string(), first);
+        cache.insert("file-new".to_string(), parsed(&parser, "fn b() {}\n"));
+
+        assert_eq!(cache.inner.lock().unwrap().files.len(), CAPACITY);
+        assert!(cache.take("file-1").is_none());
+        assert!(cache.take("file-0").is_some());
+        assert!(cache.take("file-2").is_some());
+        assert!(cache.take("file-new").is_some());
+    }
+}
@@ -0,0 +1,393 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Whether two paths differing only in letter case name the same file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseSensitivity {
+    Sensitive,
+    Insensitive,
+}
+
+/// Outcome of matching an input's basename against stored paths.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BasenameMatch {
+    None,
+    Unique(String),
+    Ambiguous(Vec<String>),
+}
+
+/// Owns how file paths from agents, the CLI and the Docker mount are turned
+/// into stored keys and back into files on disk.
+///
+/// Stored keys use `\` separators with `\\?\` and `.\` prefixes removed.
+/// Windows drive and UNC paths are case-insensitive and folded to lowercase;
+/// WSL `/mnt/<drive>/` paths are rewritten to their drive form first. Other
+/// paths keep their case unless the server's own filesystem ignores case.
+#[derive(Debug, Clone)]
+pub struct PathResolver {
+    windows_mount_root: String,
+    workspace_mount: String,
+    native_case: CaseSensitivity,
+}
+
+impl PathResolver {
+    pub fn new(
+        windows_mount_root: impl Into<String>,
+        workspace_mount: impl Into<String>,
+        native_case: CaseSensitivity,
+    ) -> Self {
+        Self {
+            windows_mount_root: windows_mount_root.into(),
+            workspace_mount: workspace_mount.into(),
+            native_case,
+        }
+    }
+
+    /// Mount roots from `AMP_WINDOWS_MOUNT_ROOT` / `AMP_WORKSPACE_MOUNT`, case policy from the host OS.
+    pub fn from_env() -> Self {
+        let native_case = if cfg!(any(windows, target_os = "macos")) {
+            CaseSensitivity::Insensitive
+        } else {
+            CaseSensitivity::Sensitive
+        };
+        Self::new(
+            env::var("AMP_WINDOWS_MOUNT_ROOT").unwrap_or_else(|_| "C:\\Users".to_string()),
+            env::var("AMP_WORKSPACE_MOUNT").unwrap_or_else(|_| "/workspace".to_string()),
+            native_case,
+        )
+    }
+
+    /// Case policy for `path`: Windows filesystems ignore case wherever they are reached from.
+    pub fn case_sensitivity(&self, path: &str) -> CaseSensitivity {
+        let path = strip_verbatim(path);
+        if is_windows_path(path) || wsl_drive_path(path).is_some() {
+            CaseSensitivity::Insensitive
+        } else {
+            self.native_case
+        }
+    }
+
+    /// Stored key for `path`, also used for stable file ids.
+    pub fn canonicalize(&self, path: &str) -> String {
+        let stripped = strip_verbatim(path.trim());
+        let (path, case) = match wsl_drive_path(stripped) {
+            Some(drive_path) => (drive_path, CaseSensitivity::Insensitive),
+            None => (stripped.to_string(), self.case_sensitivity(stripped)),
+        };
+        let mut key = path.replace('/', "\\");
+        if let Some(stripped) = key.strip_prefix(".\\") {
+            key = stripped.to_string();
+        }
+        match case {
+            CaseSensitivity::Insensitive => key.to_lowercase(),
+            CaseSensitivity::Sensitive => key,
+        }
+    }
+
+    /// `path` with prefixes stripped and separators unified but its case kept,
+    /// for matching stored paths that were written without folding.
+    pub fn display_path(&self, path: &str) -> String {
+        let key = strip_verbatim(path.trim()).replace('/', "\\");
+        match key.strip_prefix(".\\") {
+            Some(stripped) => stripped.to_string(),
+            None => key,
+        }
+    }
+
+    /// Case-folded key for lookups that must also hit paths stored before
+    /// keys kept their case.
+    pub fn lookup_key(&self, path: &str) -> String {
+        self.display_path(path).to_lowercase()
+    }
+
+    /// Whether `a` and `b` name the same file under the case policy.
+    pub fn same_path(&self, a: &str, b: &str) -> bool {
+        self.canonicalize(a) == self.canonicalize(b)
+    }
+
+    /// Whether `path` is `root` or lies inside it.
+    pub fn is_within(&self, path: &str, root: &str) -> bool {
+        let path = self.canonicalize(path);
+        let root = self.canonicalize(root);
+        let root = root.trim_end_matches('\\');
+        path == root
+            || path
+                .strip_prefix(root)
+                .is_some_and(|rest| rest.starts_with('\\'))
+    }
+
+    /// Host path under the Windows mount root, rewritten to the container mount.
+    pub fn to_container(&self, path: &str) -> Option<PathBuf> {
+        let normalized = strip_verbatim(path).replace('/', "\\");
+        let host_root = self.windows_mount_root.replace('/', "\\");
+        let host_root = host_root.trim_end_matches('\\');
+        let lowered = normalized.to_lowercase();
+        let rest = lowered.strip_prefix(&host_root.to_lowercase())?;
+        if !rest.is_empty() && !rest.starts_with('\\') {
+            return None;
+        }
+
+        let rel = normalized[host_root.len()..].trim_start_matches('\\');
+        let container_root = self.workspace_mount.trim_end_matches('/');
+        Some(PathBuf::from(if rel.is_empty() {
+            container_root.to_string()
+        } else {
+            format!("{}/{}", container_root, rel.replace('\\', "/"))
+        }))
+    }
+
+    /// Container mount path rewritten to the Windows host path it mirrors.
+    pub fn to_host(&self, path: &str) -> Option<String> {
+        let normalized = path.replace('\\', "/");
+        let container_root = self.workspace_mount.trim_end_matches('/');
+        let rest = normalized.strip_prefix(container_root)?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+
+        let rel = rest.trim_start_matches('/');
+        let host_root = self.windows_mount_root.trim_end_matches('\\');
+        Some(if rel.is_empty() {
+            host_root.to_string()
+        } else {
+            format!("{}\\{}", host_root, rel.replace('/', "\\"))
+        })
+    }
+
+    /// Where `path` may live on this machine, most specific first: the
+    /// container mount, the WSL mount, then the path itself with native separators.
+    pub fn disk_candidates(&self, path: &str) -> Vec<PathBuf> {
+        let stripped = strip_verbatim(path.trim());
+        let mut candidates = Vec::new();
+        if let Some(mapped) = self.to_container(stripped) {
+            candidates.push(mapped);
+        }
+        if !cfg!(windows) {
+            if let Some(mounted) = windows_to_wsl(stripped) {
+                candidates.push(mounted);
+            }
+        }
+        let native = if cfg!(windows) {
+            stripped.to_string()
+        } else {
+            stripped.replace('\\', "/")
+        };
+        candidates.push(PathBuf::from(native));
+        candidates.dedup();
+        candidates
+    }
+
+    /// Last path component, as given.
+    pub fn basename<'a>(&self, path: &'a str) -> &'a str {
+        path.rsplit(['\\', '/']).next().unwrap_or(path)
+    }
+
+    /// Whether `input` names only a file, with no directory part.
+    pub fn is_basename_only(&self, input: &str) -> bool {
+        !input.contains('/') && !input.contains('\\')
+    }
+
+    /// Stored paths whose basename is `input`'s basename, deduplicated;
+    /// more than one distinct file is ambiguous.
+    pub fn match_basename<'a>(
+        &self,
+        input: &str,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> BasenameMatch {
+        let wanted = self.basename(input);
+        let mut matches: Vec<String> = Vec::new();
+        for candidate in candidates {
+            let name = self.basename(candidate);
+            let same_name = match self.case_sensitivity(candidate) {
+                CaseSensitivity::Sensitive => name == wanted,
+                CaseSensitivity::Insensitive => name.eq_ignore_ascii_case(wanted),
+            };
+            if same_name && !matches.iter().any(|seen| self.same_path(seen, candidate)) {
+                matches.push(candidate.to_string());
+            }
+        }
+        match matches.len() {
+            0 => BasenameMatch::None,
+            1 => BasenameMatch::Unique(matches.remove(0)),
+            _ => {
+                matches.sort();
+                BasenameMatch::Ambiguous(matches)
+            }
+        }
+    }
+}
+
+fn strip_verbatim(path: &str) -> &str {
+    path.strip_prefix(r"\\?\")
+        .or_else(|| path.strip_prefix("//?/"))
+        .unwrap_or(path)
+}
+
+/// `C:\...`, `C:/...` or a UNC share.
+fn is_windows_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    let drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    drive || path.starts_with(r"\\")
+}
+
+/// `/mnt/c/Users/me` as `c:\Users\me`.
+fn wsl_drive_path(path: &str) -> Option<String> {
+    let rest = path.strip_prefix("/mnt/")?;
+    let mut parts = rest.splitn(2, '/');
+    let drive = parts.next()?;
+    if drive.len() != 1 || !drive.as_bytes()[0].is_ascii_alphabetic() {
+        return None;
+    }
+    let tail = parts.next().unwrap_or("");
+    Some(format!(
+        "{}:\\{}",
+        drive.to_ascii_lowercase(),
+        tail.replace('/', "\\")
+    ))
+}
+
+/// `C:\Users\me` as `/mnt/c/Users/me`.
+fn windows_to_wsl(path: &str) -> Option<PathBuf> {
+    if path.starts_with(r"\\") || !is_windows_path(path) {
+        return None;
+    }
+    let drive = path[..1].to_ascii_lowercase();
+    let tail = path[2..].trim_start_matches(['\\', '/']).replace('\\', "/");
+    Some(PathBuf::from(format!("/mnt/{}/{}", drive, tail)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linux() -> PathResolver {
+        PathResolver::new("C:\\Users", "/workspace", CaseSensitivity::Sensitive)
+    }
+
+    #[test]
+    fn test_windows_drive_paths_fold_case() {
+        let resolver = linux();
+        assert_eq!(
+            resolver.canonicalize("C:\\Users\\Me\\Proj\\Main.rs"),
+            "c:\\users\\me\\proj\\main.rs"
+        );
+        assert_eq!(
+            resolver.canonicalize("C:/Users/Me/Proj/Main.rs"),
+            "c:\\users\\me\\proj\\main.rs"
+        );
+        assert!(resolver.same_path("c:\\users\\ME\\proj\\main.rs", "C:/Users/me/Proj/MAIN.rs"));
+        assert_eq!(
+            resolver.case_sensitivity("D:\\code"),
+            CaseSensitivity::Insensitive
+        );
+    }
+
+    #[test]
+    fn test_verbatim_prefix_is_stripped() {
+        let resolver = linux();
+        assert_eq!(
+            resolver.canonicalize(r"\\?\C:\Users\Me\a.rs"),
+            "c:\\users\\me\\a.rs"
+        );
+        assert_eq!(
+            resolver.display_path(r"\\?\C:\Users\Me\a.rs"),
+            "C:\\Users\\Me\\a.rs"
+        );
+        assert_eq!(
+            resolver.to_container(r"\\?\C:\Users\Me\a.rs"),
+            Some(PathBuf::from("/workspace/Me/a.rs"))
+        );
+        // UNC shares are not verbatim prefixes
+        assert_eq!(
+            resolver.canonicalize(r"\\Server\Share\A.rs"),
+            "\\\\server\\share\\a.rs"
+        );
+    }
+
+    #[test]
+    fn test_wsl_mount_paths_match_their_drive_form() {
+        let resolver = linux();
+        assert_eq!(
+            resolver.canonicalize("/mnt/c/Users/Me/Proj/Main.rs"),
+            "c:\\users\\me\\proj\\main.rs"
+        );
+        assert!(resolver.same_path("/mnt/c/Users/Me/a.rs", "C:\\Users\\me\\A.rs"));
+        assert_eq!(
+            resolver.canonicalize("/mnt/data/Main.rs"),
+            "\\mnt\\data\\Main.rs"
+        );
+        if !cfg!(windows) {
+            assert!(resolver
+                .disk_candidates("D:\\Repo\\a.rs")
+                .contains(&PathBuf::from("/mnt/d/Repo/a.rs")));
+        }
+    }
+
+    #[test]
+    fn test_linux_paths_keep_their_case() {
+        let resolver = linux();
+        assert_eq!(
+            resolver.canonicalize("/home/dev/Proj/README.md"),
+            "\\home\\dev\\Proj\\README.md"
+        );
+        assert!(!resolver.same_path("/home/dev/Readme.md", "/home/dev/README.md"));
+        assert_eq!(resolver.canonicalize("./src/Lib.rs"), "src\\Lib.rs");
+        assert!(resolver.is_within("/home/dev/Proj/src/a.rs", "/home/dev/Proj"));
+        assert!(!resolver.is_within("/home/dev/Project/a.rs", "/home/dev/Proj"));
+        assert!(!resolver.is_within("/home/dev/proj/a.rs", "/home/dev/Proj"));
+
+        let mac = PathResolver::new("C:\\Users", "/workspace", CaseSensitivity::Insensitive);
+        assert!(mac.same_path("/Users/dev/Readme.md", "/users/dev/README.md"));
+    }
+
+    #[test]
+    fn test_container_mount_round_trips() {
+        let resolver = linux();
+        assert_eq!(
+            resolver.to_container("C:\\Users\\Me\\proj\\a.rs"),
+            Some(PathBuf::from("/workspace/Me/proj/a.rs"))
+        );
+        assert_eq!(
+            resolver.to_host("/workspace/Me/proj/a.rs").as_deref(),
+            Some("C:\\Users\\Me\\proj\\a.rs")
+        );
+        assert_eq!(resolver.to_container("C:\\UsersOther\\a.rs"), None);
+        assert_eq!(resolver.to_host("/workspaces/a.rs"), None);
+        assert_eq!(
+            resolver.disk_candidates("C:\\Users\\Me\\a.rs")[0],
+            PathBuf::from("/workspace/Me/a.rs")
+        );
+    }
+
+    #[test]
+    fn test_basename_matching_reports_ambiguity() {
+        let resolver = linux();
+        let stored = [
+            "\\home\\dev\\proj\\src\\utils.py",
+            "\\home\\dev\\proj\\lib\\utils.py",
+            "\\home\\dev\\proj\\lib\\Utils.py",
+            "c:\\users\\me\\proj\\main.rs",
+        ];
+        assert!(resolver.is_basename_only("utils.py"));
+        assert!(!resolver.is_basename_only("src/utils.py"));
+        assert_eq!(
+            resolver.match_basename("utils.py", stored),
+            BasenameMatch::Ambiguous(vec![
+                "\\home\\dev\\proj\\lib\\utils.py".to_string(),
+                "\\home\\dev\\proj\\src\\utils.py".to_string(),
+            ])
+        );
+        assert_eq!(
+            resolver.match_basename("Utils.py", stored),
+            BasenameMatch::Unique("\\home\\dev\\proj\\lib\\Utils.py".to_string())
+        );
+        assert_eq!(
+            resolver.match_basename("MAIN.rs", stored),
+            BasenameMatch::Unique("c:\\users\\me\\proj\\main.rs".to_string())
+        );
+        assert_eq!(
+            resolver.match_basename("other.rs", stored),
+            BasenameMatch::None
+        );
+    }
+}
@@ -1,5 +1,7 @@
-use crate::models::settings::SettingsConfig;
+use crate::models::settings::{default_cache_low_value_patterns, SettingsConfig};
+use crate::surreal_json::take_json_values;
 use anyhow::Result;
+use serde_json::Value;
 use std::env;
 use surrealdb::engine::any::Any;
 use surrealdb::Surreal;
@@ -25,6 +27,106 @@ impl SettingsService {
         }
     }
 
+    /// Load settings for a tenant: the global settings with its overrides merged over them
+    pub async fn load_settings_for_tenant(&self, tenant_id: &str) -> Result<SettingsConfig> {
+        let tenant = self.load_tenant_overrides(tenant_id).await?;
+        self.merged_over_global(tenant.iter()).await
+    }
+
+    /// Load settings for a tenant's project: the global settings with the
+    /// tenant's overrides and then the project's overrides merged over them
+    pub async fn load_settings_for_project(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+    ) -> Result<SettingsConfig> {
+        let tenant = self.load_tenant_overrides(tenant_id).await?;
+        let project = self.load_project_overrides(tenant_id, project_id).await?;
+        self.merged_over_global(tenant.iter().chain(project.iter()))
+            .await
+    }
+
+    /// The global settings with each patch in `overrides` applied in order
+    async fn merged_over_global<'a>(
+        &self,
+        overrides: impl Iterator<Item = &'a Value>,
+    ) -> Result<SettingsConfig> {
+        let global = self.load_settings().await?;
+        let mut overrides = overrides.peekable();
+        if overrides.peek().is_none() {
+            return Ok(global);
+        }
+        let mut merged = serde_json::to_value(&global)?;
+        for patch in overrides {
+            merge_settings_patch(&mut merged, patch);
+        }
+        Ok(serde_json::from_value(merged)?)
+    }
+
+    /// The partial settings a tenant overrides for all of its projects, if any
+    pub async fn load_tenant_overrides(&self, tenant_id: &str) -> Result<Option<Value>> {
+        let mut response = self
+            .db
+            .query("SELECT VALUE overrides FROM type::thing('tenant_settings', $tenant_id)")
+            .bind(("tenant_id", tenant_id.to_string()))
+            .await?;
+        Ok(take_json_values(&mut response, 0)
+            .into_iter()
+            .find(|overrides| overrides.is_object()))
+    }
+
+    /// The partial settings a tenant's project overrides, if it has any
+    pub async fn load_project_overrides(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+    ) -> Result<Option<Value>> {
+        let mut response = self
+            .db
+            .query("SELECT VALUE overrides FROM type::thing('project_settings', [$tenant_id, $id])")
+            .bind(("tenant_id", tenant_id.to_string()))
+            .bind(("id", project_id.to_string()))
+            .await?;
+        Ok(take_json_values(&mut response, 0)
+            .into_iter()
+            .find(|overrides| overrides.is_object()))
+    }
+
+    /// Replace a tenant's overrides. Like project overrides, only the fields
+    /// present are stored.
+    pub async fn save_tenant_overrides(&self, tenant_id: &str, overrides: Value) -> Result<Value> {
+        validate_project_overrides(&overrides)?;
+
+        self.db
+            .query("UPSERT type::thing('tenant_settings', $tenant_id) CONTENT { tenant_id: $tenant_id, overrides: $overrides, updated_at: time::now() }")
+            .bind(("tenant_id", tenant_id.to_string()))
+            .bind(("overrides", overrides.clone()))
+            .await
+            .and_then(surrealdb::Response::check)?;
+        Ok(overrides)
+    }
+
+    /// Replace a tenant's project overrides. Only the fields present in
+    /// `overrides` are stored, so later changes to other global or tenant
+    /// settings still apply.
+    pub async fn save_project_overrides(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        overrides: Value,
+    ) -> Result<Value> {
+        validate_project_overrides(&overrides)?;
+
+        self.db
+            .query("UPSERT type::thing('project_settings', [$tenant_id, $id]) CONTENT { tenant_id: $tenant_id, project_id: $id, overrides: $overrides, updated_at: time::now() }")
+            .bind(("tenant_id", tenant_id.to_string()))
+            .bind(("id", project_id.to_string()))
+            .bind(("overrides", overrides.clone()))
+            .await
+            .and_then(surrealdb::Response::check)?;
+        Ok(overrides)
+    }
+
     /// Load settings from database
     async fn load_from_db(&self) -> Result<SettingsConfig> {
         let result: Option<SettingsConfig> = self.db.select(("settings", "config")).await?;
@@ -68,6 +170,35 @@ impl SettingsService {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(768),
+            cohere_api_key: env::var("COHERE_API_KEY").unwrap_or_default(),
+            cohere_model: env::var("COHERE_EMBEDDING_MODEL")
+                .unwrap_or_else(|_| "embed-english-v3.0".to_string()),
+            cohere_dimension: env::var("COHERE_EMBEDDING_DIMENSION")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1024),
+            code_embedding_provider: env::var("CODE_EMBEDDING_PROVIDER").unwrap_or_default(),
+            code_embedding_model: env::var("CODE_EMBEDDING_MODEL").unwrap_or_default(),
+            code_embedding_dimension: env::var("CODE_EMBEDDING_DIMENSION")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            embedding_retry_attempts: env::var("EMBEDDING_RETRY_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+            embedding_retry_base_delay_ms: env::var("EMBEDDING_RETRY_BASE_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(500),
+            embedding_batch_size: env::var("EMBEDDING_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100),
+            embedding_concurrency: env::var("EMBEDDING_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(4),
             index_provider: env::var("INDEX_PROVIDER").unwrap_or_else(|_| "none".to_string()),
             index_openai_model: env::var("INDEX_OPENAI_MODEL")
                 .unwrap_or_else(|_| "gpt-4o-mini".to_string()),
@@ -86,6 +217,34 @@ impl SettingsService {
                     matches!(normalized.as_str(), "1" | "true" | "yes" | "on")
                 })
                 .unwrap_or(true),
+            cache_llm_summaries: env::var("CACHE_LLM_SUMMARIES")
+                .ok()
+                .map(|value| {
+                    let normalized = value.trim().to_ascii_lowercase();
+                    matches!(normalized.as_str(), "1" | "true" | "yes" | "on")
+                })
+                .unwrap_or(true),
+            cache_filter_low_value: env::var("CACHE_FILTER_LOW_VALUE")
+                .ok()
+                .map(|value| {
+                    let normalized = value.trim().to_ascii_lowercase();
+                    matches!(normalized.as_str(), "1" | "true" | "yes" | "on")
+                })
+                .unwrap_or(false),
+            cache_min_item_chars: env::var("CACHE_MIN_ITEM_CHARS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(8),
+            cache_low_value_patterns: env::var("CACHE_LOW_VALUE_PATTERNS")
+                .ok()
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|pattern| pattern.trim().to_string())
+                        .filter(|pattern| !pattern.is_empty())
+                        .collect()
+                })
+                .unwrap_or_else(default_cache_low_value_patterns),
             max_embedding_dimension: env::var("MAX_EMBEDDING_DIMENSION")
                 .ok()
                 .and_then(|v| v.parse().ok())
@@ -104,3 +263,74 @@ impl SettingsService {
         saved.ok_or_else(|| anyhow::anyhow!("Failed to save settings"))
     }
 }
+
+/// Settings that pick the embedding provider, model or dimension. Every
+/// stored vector shares one index and one query embedding, so these are
+/// global and cannot be overridden per tenant or project.
+const GLOBAL_EMBEDDING_SETTINGS: &[&str] = &[
+    "embeddingProvider",
+    "openaiApiKey",
+    "openaiModel",
+    "openaiDimension",
+    "openrouterApiKey",
+    "openrouterModel",
+    "openrouterDimension",
+    "ollamaUrl",
+    "ollamaModel",
+    "ollamaDimension",
+    "cohereApiKey",
+    "cohereModel",
+    "cohereDimension",
+    "codeEmbeddingProvider",
+    "codeEmbeddingModel",
+    "codeEmbeddingDimension",
+    "embeddingRetryAttempts",
+    "embeddingRetryBaseDelayMs",
+    "embeddingBatchSize",
+    "embeddingConcurrency",
+    "maxEmbeddingDimension",
+];
+
+/// Check that project or tenant overrides are an object of known settings with valid values
+pub fn validate_project_overrides(overrides: &Value) -> Result<()> {
+    let Value::Object(fields) = overrides else {
+        anyhow::bail!("Project settings must be a JSON object");
+    };
+    let mut merged = serde_json::to_value(SettingsConfig::default())?;
+    if let Some(unknown) = fields.keys().find(|key| merged.get(key.as_str()).is_none()) {
+        anyhow::bail!("Unknown setting: {}", unknown);
+    }
+    if let Some(global) = fields
+        .keys()
+        .find(|key| GLOBAL_EMBEDDING_SETTINGS.contains(&key.as_str()))
+    {
+        anyhow::bail!(
+            "{} is a global embedding setting and cannot be overridden",
+            global
+        );
+    }
+    merge_settings_patch(&mut merged, overrides);
+    serde_json::from_value::<SettingsConfig>(merged)?;
+    Ok(())
+}
+
+/// Overlay `patch` onto `base`, recursing into objects. Nulls in the patch are
+/// ignored rather than clearing the base value.
+pub fn merge_settings_patch(base: &mut Value, patch: &Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                if value.is_null() {
+                    continue;
+                }
+                match base.get_mut(key) {
+                    Some(existing) => merge_settings_patch(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch.clone(),
+    }
+}
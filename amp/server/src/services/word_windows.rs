@@ -0,0 +1,74 @@
+//! Word-window chunking, shared with the CLI: it includes this file with
+//! `#[path]` to chunk files the server returned no chunks for, so both sides
+//! slice identical windows. Keep it free of anything but `std`.
+
+/// Words per window.
+pub const CHUNK_SIZE: usize = 500;
+
+/// Words each window repeats from the end of the previous one, for better retrieval.
+pub const OVERLAP_SIZE: usize = 100;
+
+/// One window as a byte range of the source and the number of words in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordWindow {
+    pub start: usize,
+    pub end: usize,
+    pub words: usize,
+}
+
+/// Estimated tokens in `text`, at 1.3 tokens per whitespace-separated word.
+pub fn estimate_token_count(text: &str) -> usize {
+    text.split_whitespace().count() * 13 / 10
+}
+
+/// Windows of `chunk_size` words over `content`, each starting `overlap` words
+/// before the previous one ended. `None` when the whole of `content` fits in
+/// one chunk and should be kept as is.
+pub fn word_windows(content: &str, chunk_size: usize, overlap: usize) -> Option<Vec<WordWindow>> {
+    if estimate_token_count(content) <= chunk_size {
+        return None;
+    }
+
+    let words = word_spans(content);
+    let mut windows = Vec::new();
+    let mut start_idx = 0;
+    while start_idx < words.len() {
+        let end_idx = (start_idx + chunk_size).min(words.len());
+        windows.push(WordWindow {
+            start: words[start_idx].0,
+            end: words[end_idx - 1].1,
+            words: end_idx - start_idx,
+        });
+        start_idx = if end_idx < words.len() {
+            end_idx - overlap
+        } else {
+            break;
+        };
+    }
+    Some(windows)
+}
+
+/// Byte range of each whitespace-separated word in `content`.
+pub fn word_spans(content: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (idx, ch) in content.char_indices() {
+        match (ch.is_whitespace(), start) {
+            (true, Some(word_start)) => {
+                spans.push((word_start, idx));
+                start = None;
+            }
+            (false, None) => start = Some(idx),
+            _ => {}
+        }
+    }
+    if let Some(word_start) = start {
+        spans.push((word_start, content.len()));
+    }
+    spans
+}
+
+/// 1-based line number of byte offset `idx`.
+pub fn line_at(content: &str, idx: usize) -> u32 {
+    content[..idx].matches('\n').count() as u32 + 1
+}
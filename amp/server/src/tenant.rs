@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::convert::Infallible;
+
+pub const TENANT_HEADER: &str = "x-amp-tenant";
+pub const DEFAULT_TENANT: &str = "default";
+
+/// Tenant a request is scoped to, taken from the `X-AMP-Tenant` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tenant(pub String);
+
+impl Tenant {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn from_header(value: Option<&str>) -> Self {
+        match value.map(str::trim) {
+            Some(tenant) if !tenant.is_empty() => Self(tenant.to_string()),
+            _ => Self::default(),
+        }
+    }
+}
+
+impl Default for Tenant {
+    fn default() -> Self {
+        Self(DEFAULT_TENANT.to_string())
+    }
+}
+
+/// Resolve the request tenant once so every handler sees the same value.
+pub async fn extract_tenant(mut request: Request<axum::body::Body>, next: Next) -> Response {
+    let tenant = Tenant::from_header(
+        request
+            .headers()
+            .get(TENANT_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+    request.extensions_mut().insert(tenant);
+    next.run(request).await
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Tenant
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<Tenant>()
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_header_falls_back_to_default() {
+        assert_eq!(Tenant::from_header(Some("acme")).as_str(), "acme");
+        assert_eq!(Tenant::from_header(Some("  acme ")).as_str(), "acme");
+        assert_eq!(Tenant::from_header(Some("   ")).as_str(), DEFAULT_TENANT);
+        assert_eq!(Tenant::from_header(None).as_str(), DEFAULT_TENANT);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    config::Config,
+    database::Database,
+    services::{
+        analytics::AnalyticsService,
+        cache::HeuristicTokenEstimator,
+        embedding::{none::NoneEmbedding, EmbeddingError, EmbeddingService, ReloadableEmbedding},
+        graph::GraphTraversalService,
+        hybrid::HybridRetrievalService,
+        metrics::{MeteredEmbedding, MetricsState},
+        path_resolver::PathResolver,
+        settings::SettingsService,
+    },
+    AppState,
+};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub fn test_config() -> Config {
+    Config {
+        database_url: "memory".to_string(),
+        embedding_service_url: None,
+        max_embedding_dimension: 1536,
+        port: 0,
+        bind_address: "127.0.0.1".to_string(),
+        embedding_provider: "none".to_string(),
+        openai_api_key: None,
+        ollama_url: "http://localhost:11434".to_string(),
+        embedding_dimension: 1536,
+        embedding_model: "none".to_string(),
+        metrics_enabled: false,
+        cache_fanout_concurrency: 8,
+        cache_fanout_background: false,
+        graph_node_budget: 5000,
+        inline_link_threshold: 0,
+        lease_gc_interval_secs: 60,
+        object_gc_interval_secs: 300,
+        log_query_content: false,
+        superseded_decision_weight: 0.5,
+        allow_debug_vectors: false,
+    }
+}
+
+/// Embeds text as `[len, 1.0]` and counts how many texts reached the provider.
+#[derive(Default)]
+pub struct CountingEmbedding {
+    pub texts_embedded: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl EmbeddingService for CountingEmbedding {
+    async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        self.texts_embedded.fetch_add(1, Ordering::SeqCst);
+        Ok(vec![text.len() as f32, 1.0])
+    }
+
+    fn dimension(&self) -> usize {
+        2
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    fn model(&self) -> String {
+        "counting".to_string()
+    }
+}
+
+/// Embeds every text as `[marker, marker]`, so tests can tell which service produced a vector.
+pub struct MarkerEmbedding {
+    pub model: &'static str,
+    pub marker: f32,
+}
+
+#[async_trait]
+impl EmbeddingService for MarkerEmbedding {
+    async fn generate_embedding(&self, _text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        Ok(vec![self.marker, self.marker])
+    }
+
+    fn dimension(&self) -> usize {
+        2
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    fn model(&self) -> String {
+        self.model.to_string()
+    }
+}
+
+/// AppState over an in-memory SurrealDB with the schema applied and embeddings disabled.
+pub async fn memory_state(config: Config) -> AppState {
+    memory_state_with_embedding(config, Box::new(NoneEmbedding)).await
+}
+
+pub async fn memory_state_with_embedding(
+    config: Config,
+    embedding: Box<dyn EmbeddingService>,
+) -> AppState {
+    memory_state_with_embeddings(config, embedding, None).await
+}
+
+/// Like [`memory_state_with_embedding`], with an optional separate code embedding service.
+pub async fn memory_state_with_embeddings(
+    config: Config,
+    embedding: Box<dyn EmbeddingService>,
+    code_embedding: Option<Box<dyn EmbeddingService>>,
+) -> AppState {
+    let db = Arc::new(Database::new("memory").await.unwrap());
+    db.initialize_schema().await.unwrap();
+
+    let metrics = config
+        .metrics_enabled
+        .then(|| Arc::new(Mutex::new(MetricsState::new().unwrap())));
+    let embedding_service = Arc::new(ReloadableEmbedding::new(MeteredEmbedding::wrap_if_enabled(
+        embedding,
+        metrics.as_ref(),
+    )));
+    let code_embedding_service = Arc::new(ReloadableEmbedding::new(match code_embedding {
+        Some(code_embedding) => MeteredEmbedding::wrap_if_enabled(code_embedding, metrics.as_ref()),
+        None => embedding_service.current(),
+    }));
+    let graph_service = Arc::new(
+        GraphTraversalService::new(db.clone())
+            .with_node_budget(config.graph_node_budget)
+            .with_inline_link_threshold(config.inline_link_threshold),
+    );
+    let log_query_content = config.log_query_content;
+    let superseded_decision_weight = config.superseded_decision_weight;
+    let analytics_service = Arc::new(AnalyticsService::new(db.clone()));
+
+    AppState {
+        db: db.clone(),
+        config: Arc::new(config),
+        embedding_service: embedding_service.clone(),
+        code_embedding_service: code_embedding_service.clone(),
+        graph_service: graph_service.clone(),
+        hybrid_service: Arc::new(
+            HybridRetrievalService::new(db.clone(), embedding_service, graph_service)
+                .with_code_embedding(code_embedding_service)
+                .with_query_logging(log_query_content)
+                .with_superseded_weight(superseded_decision_weight)
+                .with_analytics(analytics_service.clone()),
+        ),
+        analytics_service,
+        settings_service: Arc::new(SettingsService::new(db.client.clone())),
+        metrics,
+        parse_cache: Arc::default(),
+        token_estimator: Arc::new(HeuristicTokenEstimator),
+        path_resolver: Arc::new(PathResolver::from_env()),
+    }
+}
@@ -13,7 +13,7 @@ interface SettingsConfig {
   dbPass: string;
   
   // Embedding Provider
-  embeddingProvider: 'openai' | 'openrouter' | 'ollama' | 'none';
+  embeddingProvider: 'openai' | 'openrouter' | 'ollama' | 'cohere' | 'none';
   
   // OpenAI Settings
   openaiApiKey: string;
@@ -30,6 +30,11 @@ interface SettingsConfig {
   ollamaModel: string;
   ollamaDimension: number;
 
+  // Cohere Settings
+  cohereApiKey: string;
+  cohereModel: string;
+  cohereDimension: number;
+
   // Index Model Settings
   indexProvider: 'openai' | 'openrouter' | 'ollama' | 'none';
   indexOpenaiModel: string;
@@ -37,6 +42,10 @@ interface SettingsConfig {
   indexOllamaModel: string;
   indexWorkers: number;
   indexRespectGitignore: boolean;
+  cacheLlmSummaries: boolean;
+  cacheFilterLowValue: boolean;
+  cacheMinItemChars: number;
+  cacheLowValuePatterns: string[];
   
   // Legacy
   maxEmbeddingDimension: number;
@@ -59,12 +68,19 @@ export const Settings: React.FC = () => {
     ollamaUrl: 'http://localhost:11434',
     ollamaModel: 'nomic-embed-text',
     ollamaDimension: 768,
+    cohereApiKey: '',
+    cohereModel: 'embed-english-v3.0',
+    cohereDimension: 1024,
     indexProvider: 'none',
     indexOpenaiModel: 'gpt-4o-mini',
     indexOpenrouterModel: 'openai/gpt-4o-mini',
     indexOllamaModel: 'llama3.1',
     indexWorkers: 4,
     indexRespectGitignore: true,
+    cacheLlmSummaries: true,
+    cacheFilterLowValue: false,
+    cacheMinItemChars: 8,
+    cacheLowValuePatterns: ['ok', 'okay', 'done', 'thanks', 'got it', 'on it', 'will do', 'looking into it'],
     maxEmbeddingDimension: 1536,
   });
 
@@ -74,6 +90,7 @@ export const Settings: React.FC = () => {
   const [success, setSuccess] = useState(false);
   const [showOpenAiKey, setShowOpenAiKey] = useState(false);
   const [showOpenRouterKey, setShowOpenRouterKey] = useState(false);
+  const [showCohereKey, setShowCohereKey] = useState(false);
   const [modelTab, setModelTab] = useState<'index' | 'embeddings'>('index');
   const [showNuclearModal, setShowNuclearModal] = useState(false);
   const [isNuclearDeleting, setIsNuclearDeleting] = useState(false);
@@ -329,6 +346,40 @@ export const Settings: React.FC = () => {
               </p>
             </div>
 
+            <div>
+              <label className="block text-xs font-mono text-stone-400 uppercase mb-2">LLM Cache Summaries</label>
+              <button
+                onClick={() => updateField('cacheLlmSummaries', !config.cacheLlmSummaries)}
+                className={`px-4 py-2 text-sm font-mono uppercase transition-all ${
+                  config.cacheLlmSummaries
+                    ? 'bg-primary text-black border border-red-600 font-bold'
+                    : 'bg-stone-800 text-stone-400 border border-stone-700 hover:bg-stone-700 hover:text-stone-200'
+                }`}
+              >
+                {config.cacheLlmSummaries ? 'Enabled' : 'Disabled'}
+              </button>
+              <p className="text-xs text-stone-500 mt-2 font-mono">
+                Summarize closed cache blocks with the index model. Disable to avoid the extra LLM call.
+              </p>
+            </div>
+
+            <div>
+              <label className="block text-xs font-mono text-stone-400 uppercase mb-2">Filter Low-Value Cache Items</label>
+              <button
+                onClick={() => updateField('cacheFilterLowValue', !config.cacheFilterLowValue)}
+                className={`px-4 py-2 text-sm font-mono uppercase transition-all ${
+                  config.cacheFilterLowValue
+                    ? 'bg-primary text-black border border-red-600 font-bold'
+                    : 'bg-stone-800 text-stone-400 border border-stone-700 hover:bg-stone-700 hover:text-stone-200'
+                }`}
+              >
+                {config.cacheFilterLowValue ? 'Enabled' : 'Disabled'}
+              </button>
+              <p className="text-xs text-stone-500 mt-2 font-mono">
+                Drop cache writes shorter than {config.cacheMinItemChars} characters or matching a trivial phrase ({config.cacheLowValuePatterns.join(', ')}).
+              </p>
+            </div>
+
             {config.indexProvider === 'openai' && (
               <div className="space-y-4 border-t border-stone-800 pt-6">
                 <h4 className="text-sm font-mono text-stone-300 uppercase">OpenAI Configuration</h4>
@@ -436,7 +487,7 @@ export const Settings: React.FC = () => {
             <div>
               <label className="block text-xs font-mono text-stone-400 uppercase mb-2">Provider</label>
               <div className="flex gap-2">
-                {(['none', 'openai', 'openrouter', 'ollama'] as const).map((provider) => (
+                {(['none', 'openai', 'openrouter', 'ollama', 'cohere'] as const).map((provider) => (
                   <button
                     key={provider}
                     onClick={() => updateField('embeddingProvider', provider)}
@@ -576,6 +627,49 @@ export const Settings: React.FC = () => {
               </div>
             )}
 
+            {config.embeddingProvider === 'cohere' && (
+              <div className="space-y-4 border-t border-stone-800 pt-6">
+                <h4 className="text-sm font-mono text-stone-300 uppercase">Cohere Configuration</h4>
+                <div>
+                  <label className="block text-xs font-mono text-stone-400 uppercase mb-2">API Key</label>
+                  <div className="relative">
+                    <input
+                      type={showCohereKey ? 'text' : 'password'}
+                      value={config.cohereApiKey}
+                      onChange={(e) => updateField('cohereApiKey', e.target.value)}
+                      className="w-full bg-stone-900 border border-stone-700 px-3 py-2 text-stone-200 font-mono text-sm focus:border-primary focus:outline-none pr-20"
+                    />
+                    <button
+                      onClick={() => setShowCohereKey(!showCohereKey)}
+                      className="absolute right-2 top-1/2 -translate-y-1/2 text-xs text-stone-500 hover:text-stone-300 font-mono uppercase"
+                    >
+                      {showCohereKey ? 'Hide' : 'Show'}
+                    </button>
+                  </div>
+                </div>
+                <div className="grid grid-cols-1 md:grid-cols-2 gap-4">
+                  <div>
+                    <label className="block text-xs font-mono text-stone-400 uppercase mb-2">Model</label>
+                    <input
+                      type="text"
+                      value={config.cohereModel}
+                      onChange={(e) => updateField('cohereModel', e.target.value)}
+                      className="w-full bg-stone-900 border border-stone-700 px-3 py-2 text-stone-200 font-mono text-sm focus:border-primary focus:outline-none"
+                    />
+                  </div>
+                  <div>
+                    <label className="block text-xs font-mono text-stone-400 uppercase mb-2">Dimension</label>
+                    <input
+                      type="number"
+                      value={config.cohereDimension}
+                      onChange={(e) => updateField('cohereDimension', parseInt(e.target.value))}
+                      className="w-full bg-stone-900 border border-stone-700 px-3 py-2 text-stone-200 font-mono text-sm focus:border-primary focus:outline-none"
+                    />
+                  </div>
+                </div>
+              </div>
+            )}
+
             {config.embeddingProvider === 'none' && (
               <div className="border-t border-stone-800 pt-6">
                 <p className="text-sm text-stone-500 font-mono">
@@ -23,6 +23,7 @@ export interface AnalyticsData {
     symbolsExtracted: number;
     lastIndexTime: string;
     indexingSpeed: string;
+    embeddingCacheHits: number;
   };
   requestLatency: {
     p99: number;
@@ -47,6 +48,27 @@ export interface AnalyticsData {
     status: string;
     alert: boolean;
   }>;
+  detailed?: {
+    projectId: string | null;
+    window: string;
+    bucketSeconds: number;
+    objectsByType: Record<string, number>;
+    chunks: { embedded: number; unembedded: number };
+    cacheBlocksByScope: Record<string, number>;
+    queries: QueryStats;
+    series: Array<{
+      timestamp: string;
+      objectsCreated: number;
+      queries: QueryStats;
+    }>;
+  };
+}
+
+export interface QueryStats {
+  total: number;
+  perHour: number;
+  avgTopScore: number;
+  zeroResultRate: number;
 }
 
 export const useAnalytics = () => {
@@ -76,6 +98,7 @@ export const useAnalytics = () => {
         symbolsExtracted: 0,
         lastIndexTime: '',
         indexingSpeed: '',
+        embeddingCacheHits: 0,
       },
       requestLatency: {
         p99: requestLatency.p99 ?? 0,
@@ -86,6 +109,7 @@ export const useAnalytics = () => {
       },
       errorDistribution: payload.errorDistribution || [],
       systemEvents: payload.systemEvents || [],
+      detailed: payload.detailed,
     };
   };
 